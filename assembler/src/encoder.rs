@@ -5,22 +5,20 @@ use core::program::binary_program::{BinaryInstruction, BinaryProgram, OlaProphet
 use core::vm::opcodes::OlaOpcode;
 use core::vm::operands::{ImmediateValue, OlaOperand};
 use log::debug;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::HashMap;
 use std::str::FromStr;
 
 pub fn encode_asm_from_json_string(json: String) -> Result<BinaryProgram, String> {
-    let bundle: AsmBundle = serde_json::from_str(json.as_str()).unwrap();
-    let relocated = asm_relocate(bundle).unwrap();
-    let program = encode_to_binary(relocated).unwrap();
-    Ok(program)
+    let bundle: AsmBundle = serde_json::from_str(json.as_str())
+        .map_err(|err| format!("serde json from string failed {}", err))?;
+    let relocated = asm_relocate(bundle)?;
+    encode_to_binary(relocated)
 }
 
 pub fn encode_asm_from_json_file(path: String) -> Result<BinaryProgram, String> {
-    let json_str = std::fs::read_to_string(path).unwrap();
-    let bundle: AsmBundle = serde_json::from_str(json_str.as_str()).unwrap();
-    let relocated = asm_relocate(bundle).unwrap();
-    let program = encode_to_binary(relocated).unwrap();
-    Ok(program)
+    let json_str = std::fs::read_to_string(path)
+        .map_err(|err| format!("File read to string failed {}", err))?;
+    encode_asm_from_json_string(json_str)
 }
 
 pub(crate) fn encode_to_binary(bundle: RelocatedAsmBundle) -> Result<BinaryProgram, String> {
@@ -32,7 +30,6 @@ pub(crate) fn encode_to_binary(bundle: RelocatedAsmBundle) -> Result<BinaryProgr
     let mut binary_instructions: Vec<BinaryInstruction> = vec![];
     let mut iter = asm_instructions.iter();
     let mut binary_counter: usize = 0;
-    let mut origin_asm = BTreeMap::new();
 
     while let Some(asm) = iter.next() {
         let ops_result: Result<
@@ -97,6 +94,7 @@ pub(crate) fn encode_to_binary(bundle: RelocatedAsmBundle) -> Result<BinaryProgr
                     ctx: Vec::new(),
                     inputs: asm_prophet.inputs.clone(),
                     outputs: asm_prophet.outputs.clone(),
+                    native: asm_prophet.native.clone(),
                 })
             } else {
                 None
@@ -109,7 +107,6 @@ pub(crate) fn encode_to_binary(bundle: RelocatedAsmBundle) -> Result<BinaryProgr
             dst,
             prophet,
         };
-        origin_asm.insert(binary_counter, asm.asm.clone());
         debug!(
             "binary_counter:{}, asm:{}, code:{}",
             binary_counter, asm.asm, instruction
@@ -117,7 +114,7 @@ pub(crate) fn encode_to_binary(bundle: RelocatedAsmBundle) -> Result<BinaryProgr
         binary_instructions.push(instruction);
         binary_counter += asm.binary_length() as usize;
     }
-    BinaryProgram::from_instructions(binary_instructions, Some(origin_asm), true)
+    BinaryProgram::from_instructions(binary_instructions, Some(bundle.debug_info), true)
 }
 
 fn is_adjusted_operand(asm: &OlaAsmInstruction) -> bool {
@@ -131,87 +128,46 @@ fn is_adjusted_operand(asm: &OlaAsmInstruction) -> bool {
 fn handle_mem_operand(
     asm: &OlaAsmInstruction,
 ) -> Result<(OlaOperand, OlaOperand, OlaOperand), String> {
-    let dst = if asm.opcode == OlaOpcode::MLOAD {
-        asm.dst.clone()
+    let (mem_operand, value_operand) = if asm.opcode == OlaOpcode::MLOAD {
+        (asm.op1.clone(), asm.dst.clone())
     } else {
-        asm.op1.clone()
-    }
-    .unwrap();
-    let dst_reg = match dst {
-        OlaAsmOperand::RegisterOperand { register } => OlaOperand::RegisterOperand { register },
-        _ => {
-            panic!("parse dst reg error")
+        (asm.op0.clone(), asm.op1.clone())
+    };
+    let mem_operand = mem_operand.ok_or(format!("missing memory operand: {}", asm.asm))?;
+    let dst_reg = match value_operand {
+        Some(OlaAsmOperand::RegisterOperand { register }) => {
+            OlaOperand::RegisterOperand { register }
         }
+        _ => return Err(format!("parse dst reg error: {}", asm.asm)),
     };
 
-    let anchor_reg = if asm.opcode == OlaOpcode::MLOAD {
-        match asm.op1.clone().unwrap() {
-            OlaAsmOperand::RegisterWithOffset {
-                register,
-                offset: _,
-            } => OlaOperand::RegisterOperand { register },
-            OlaAsmOperand::RegisterWithFactoredRegOffset {
-                register,
-                offset_register: _,
-                factor: _,
-            } => OlaOperand::RegisterOperand { register },
-            _ => {
-                panic!("parse anchor reg error")
-            }
-        }
-    } else {
-        match asm.op0.clone().unwrap() {
-            OlaAsmOperand::RegisterWithOffset {
-                register,
-                offset: _,
-            } => OlaOperand::RegisterOperand { register },
-            OlaAsmOperand::RegisterWithFactoredRegOffset {
-                register,
-                offset_register: _,
-                factor: _,
-            } => OlaOperand::RegisterOperand { register },
-            _ => {
-                panic!("parse anchor reg error")
-            }
-        }
+    let anchor_reg = match mem_operand.clone() {
+        OlaAsmOperand::RegisterWithOffset {
+            register,
+            offset: _,
+        } => OlaOperand::RegisterOperand { register },
+        OlaAsmOperand::RegisterWithFactoredRegOffset {
+            register,
+            offset_register: _,
+            factor: _,
+        } => OlaOperand::RegisterOperand { register },
+        _ => return Err(format!("parse anchor reg error: {}", asm.asm)),
     };
 
-    let offset = if asm.opcode == OlaOpcode::MLOAD {
-        match asm.op1.clone().unwrap() {
-            OlaAsmOperand::RegisterWithOffset {
-                register: _,
-                offset,
-            } => OlaOperand::ImmediateOperand { value: offset },
-            OlaAsmOperand::RegisterWithFactoredRegOffset {
-                register: _,
-                offset_register,
-                factor,
-            } => OlaOperand::RegisterWithFactor {
-                register: offset_register,
-                factor,
-            },
-            _ => {
-                panic!("parse offset error")
-            }
-        }
-    } else {
-        match asm.op0.clone().unwrap() {
-            OlaAsmOperand::RegisterWithOffset {
-                register: _,
-                offset,
-            } => OlaOperand::ImmediateOperand { value: offset },
-            OlaAsmOperand::RegisterWithFactoredRegOffset {
-                register: _,
-                offset_register,
-                factor,
-            } => OlaOperand::RegisterWithFactor {
-                register: offset_register,
-                factor,
-            },
-            _ => {
-                panic!("parse offset error")
-            }
-        }
+    let offset = match mem_operand {
+        OlaAsmOperand::RegisterWithOffset {
+            register: _,
+            offset,
+        } => OlaOperand::ImmediateOperand { value: offset },
+        OlaAsmOperand::RegisterWithFactoredRegOffset {
+            register: _,
+            offset_register,
+            factor,
+        } => OlaOperand::RegisterWithFactor {
+            register: offset_register,
+            factor,
+        },
+        _ => return Err(format!("parse offset error: {}", asm.asm)),
     };
 
     Ok((anchor_reg, offset, dst_reg))
@@ -234,7 +190,7 @@ fn operand_asm_to_binary(
             OlaAsmOperand::Label { value } => {
                 if let Some(host) = mapper_label_jmp.get(value.as_str()) {
                     Some(OlaOperand::ImmediateOperand {
-                        value: ImmediateValue::from_str(host.to_string().as_str()).unwrap(),
+                        value: ImmediateValue::from_str(host.to_string().as_str())?,
                     })
                 } else {
                     return Err(format!(
@@ -246,7 +202,7 @@ fn operand_asm_to_binary(
             OlaAsmOperand::Identifier { value } => {
                 if let Some(host) = mapper_label_call.get(value.as_str()) {
                     Some(OlaOperand::ImmediateOperand {
-                        value: ImmediateValue::from_str(host.to_string().as_str()).unwrap(),
+                        value: ImmediateValue::from_str(host.to_string().as_str())?,
                     })
                 } else {
                     return Err(format!(
@@ -1,5 +1,8 @@
 use crate::asm::{AsmRow, OlaAsmInstruction};
-use core::program::binary_program::{OlaProphetInput, OlaProphetOutput};
+use core::program::{
+    binary_program::{OlaProphetInput, OlaProphetOutput},
+    debug_info::{OlaDebugInfo, OlaDebugLine},
+};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashMap;
@@ -14,7 +17,8 @@ pub(crate) struct AsmBundle {
 #[derive(Debug, Clone)]
 struct AsmScope {
     label: String,
-    lines: Vec<String>,
+    // (1-based line in the original program, processed line)
+    lines: Vec<(usize, String)>,
 }
 
 impl AsmBundle {
@@ -22,10 +26,12 @@ impl AsmBundle {
         let mut lines = self.program.lines();
         let mut scopes: Vec<AsmScope> = vec![];
         let mut current_scope_label: String = String::new();
-        let mut current_scope_lines: Vec<String> = vec![];
+        let mut current_scope_lines: Vec<(usize, String)> = vec![];
         let mut line_num = 0;
+        let mut source_line = 0;
         loop {
             if let Some(line) = lines.next() {
+                source_line += 1;
                 let processed_line = line_pre_process(line);
                 if processed_line.is_empty() {
                     continue;
@@ -48,10 +54,10 @@ impl AsmBundle {
                         }
                         current_scope_label = label;
                         current_scope_lines.clear();
-                        current_scope_lines.push(processed_line.to_string())
+                        current_scope_lines.push((source_line, processed_line.to_string()))
                     }
                     _ => {
-                        current_scope_lines.push(processed_line.to_string());
+                        current_scope_lines.push((source_line, processed_line.to_string()));
                     }
                 };
             } else {
@@ -95,9 +101,12 @@ pub(crate) struct AsmProphet {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct OlaAsmProphet {
     pub(crate) label: String,
+    #[serde(default)]
     pub(crate) code: String,
     pub(crate) inputs: Vec<OlaProphetInput>,
     pub(crate) outputs: Vec<OlaProphetOutput>,
+    #[serde(default)]
+    pub(crate) native: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -106,6 +115,7 @@ pub(crate) struct RelocatedAsmBundle {
     pub(crate) prophets: HashMap<usize, OlaAsmProphet>,
     pub(crate) mapper_label_call: HashMap<String, usize>,
     pub(crate) mapper_label_jmp: HashMap<String, usize>,
+    pub(crate) debug_info: OlaDebugInfo,
 }
 
 pub(crate) fn asm_relocate(bundle: AsmBundle) -> Result<RelocatedAsmBundle, String> {
@@ -117,8 +127,6 @@ pub(crate) fn asm_relocate(bundle: AsmBundle) -> Result<RelocatedAsmBundle, Stri
         ));
     }
     let scopes = scopes_res.unwrap();
-    let scopes_codes: Vec<String> = scopes.iter().map(|scope| scope.lines.join("\n")).collect();
-    let resorted_program = scopes_codes.join("\n");
 
     let mut instructions: Vec<OlaAsmInstruction> = vec![];
     let mut mapper_label_call: HashMap<String, usize> = HashMap::new();
@@ -128,11 +136,17 @@ pub(crate) fn asm_relocate(bundle: AsmBundle) -> Result<RelocatedAsmBundle, Stri
     let mut counter: usize = 0;
     let mut ori_counter: usize = 0;
     let mut label_stack: Vec<AsmRow> = vec![];
+    let mut debug_info = OlaDebugInfo::default();
 
-    let mut lines = resorted_program.lines();
+    let mut lines = scopes.iter().flat_map(|scope| {
+        scope
+            .lines
+            .iter()
+            .map(move |(source_line, line)| (scope.label.as_str(), *source_line, line.as_str()))
+    });
 
     loop {
-        if let Some(line) = lines.next() {
+        if let Some((function, source_line, line)) = lines.next() {
             let row_res = AsmRow::from_str(line);
             if row_res.is_err() {
                 let err_msg = row_res.err().unwrap();
@@ -141,6 +155,24 @@ pub(crate) fn asm_relocate(bundle: AsmBundle) -> Result<RelocatedAsmBundle, Stri
             let row = row_res.unwrap();
             match row {
                 AsmRow::Instruction(instruction) => {
+                    let labels = label_stack
+                        .iter()
+                        .filter_map(|cached_row| match cached_row {
+                            AsmRow::LabelCall(label) | AsmRow::LabelJmp(label) => {
+                                Some(label.clone())
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    debug_info.insert(
+                        counter as u64,
+                        OlaDebugLine {
+                            line: source_line,
+                            asm: instruction.asm.clone(),
+                            function: function.to_string(),
+                            labels,
+                        },
+                    );
                     label_stack.iter().for_each(|cached_row| match cached_row {
                         AsmRow::LabelCall(label) => {
                             mapper_label_call.insert(label.clone(), counter);
@@ -224,6 +256,7 @@ pub(crate) fn asm_relocate(bundle: AsmBundle) -> Result<RelocatedAsmBundle, Stri
         prophets,
         mapper_label_call,
         mapper_label_jmp,
+        debug_info,
     })
 }
 
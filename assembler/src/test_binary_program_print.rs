@@ -21,7 +21,7 @@ mod tests {
         println!("============== instructions ==============");
         let mut line: u64 = 0;
         for instruction in instructions {
-            println!("{}: {}", line, instruction.get_asm_form_code());
+            println!("{}: {}", line, instruction.get_asm_form_code().unwrap());
             let option_prophet = instruction.clone().prophet;
             let prophet_desc = match option_prophet {
                 Some(prophet) => format!("{}", prophet.code),
@@ -3,7 +3,7 @@ mod tests {
     use core::program::binary_program::BinaryProgram;
     use std::path::PathBuf;
 
-    use crate::encoder::encode_asm_from_json_file;
+    use crate::encoder::{encode_asm_from_json_file, encode_asm_from_json_string};
     use core::program::decoder::decode_binary_program_to_instructions;
 
     #[test]
@@ -56,6 +56,54 @@ mod tests {
         test_decode("fibo_loop.json".to_string());
     }
 
+    #[test]
+    fn test_debug_info() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("test_data/asm/fibo_recursive.json");
+        let program = encode_asm_from_json_file(path.display().to_string()).unwrap();
+        let debug_info = program.debug_info.unwrap();
+
+        let entry = debug_info.line(0).unwrap();
+        assert_eq!(entry.line, 3);
+        assert_eq!(entry.asm, "add r9 r9 4");
+        assert_eq!(entry.function, "main");
+        assert_eq!(
+            entry.labels,
+            vec!["main".to_string(), ".LBL0_0".to_string()]
+        );
+
+        let fib_pc = debug_info.label_pc("fib_recursive").unwrap();
+        assert_eq!(fib_pc, 11);
+        let entry = debug_info.line(fib_pc).unwrap();
+        assert_eq!(entry.line, 11);
+        assert_eq!(entry.function, "fib_recursive");
+        assert_eq!(
+            debug_info.symbolize(fib_pc),
+            "pc 11 in fib_recursive (line 11: add r9 r9 9)"
+        );
+    }
+
+    #[test]
+    fn test_malformed_input_is_an_error() {
+        assert!(encode_asm_from_json_string("not json".to_string()).is_err());
+        let missing_main = r#"{"program":"foo:\nend\n","prophets":[]}"#;
+        assert!(encode_asm_from_json_string(missing_main.to_string()).is_err());
+        let mstore_without_anchor = r#"{"program":"main:\nmstore r1 r2\nend\n","prophets":[]}"#;
+        assert!(encode_asm_from_json_string(mstore_without_anchor.to_string()).is_err());
+        let huge_immediate = format!(
+            r#"{{"program":"main:\nmov r1 {}\nend\n","prophets":[]}}"#,
+            i128::MIN
+        );
+        assert!(encode_asm_from_json_string(huge_immediate).is_err());
+
+        let truncated = BinaryProgram {
+            bytecode: "0x4000000008000000".to_string(),
+            prophets: vec![],
+            debug_info: None,
+        };
+        assert!(decode_binary_program_to_instructions(truncated).is_err());
+    }
+
     fn test_decode(file_name: String) {
         let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         path.push("test_data/asm/");
@@ -1,12 +1,10 @@
 use crate::builtins::bitwise::columns::*;
-use anyhow::Result;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 //use crate::var::{StarkEvaluationTargets, StarkEvaluationVars};
 use crate::stark::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use crate::stark::cross_table_lookup::Column;
-use crate::stark::lookup::*;
-use crate::stark::permutation::*;
+use crate::stark::lookup::Lookup;
 use crate::stark::stark::Stark;
 use crate::stark::vars::{StarkEvaluationTargets, StarkEvaluationVars};
 use plonky2::field::extension::{Extendable, FieldExtension};
@@ -21,22 +19,11 @@ use std::marker::PhantomData;
 
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct BitwiseStark<F, const D: usize> {
-    compress_challenge: Option<F>,
     pub _phantom: PhantomData<F>,
 }
 
 impl<F: RichField, const D: usize> BitwiseStark<F, D> {
     const BASE: usize = 1 << 8;
-
-    pub fn set_compress_challenge(&mut self, challenge: F) -> Result<()> {
-        assert!(self.compress_challenge.is_none(), "already set?");
-        self.compress_challenge = Some(challenge);
-        Ok(())
-    }
-
-    pub fn get_compress_challenge(&self) -> Option<F> {
-        self.compress_challenge
-    }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for BitwiseStark<F, D> {
@@ -74,115 +61,31 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for BitwiseStark<
             reduce_with_powers(&res_limbs, P::Scalar::from_canonical_usize(Self::BASE));
         yield_constr.constraint(computed_sum - res);
 
-        // Constrain compress logic.
-        let beta = FE::from_basefield(self.get_compress_challenge().unwrap());
+        // Constrain compress logic: limbs are range checked to u8 and tags are
+        // opcode bit masks below 2^32, so packing them in base 2^8 is injective.
+        let base = P::Scalar::from_canonical_usize(Self::BASE);
         for i in 0..4 {
-            yield_constr.constraint(
-                lv[TAG]
-                    + lv[OP0_LIMBS.start + i] * beta
-                    + lv[OP1_LIMBS.start + i] * beta * beta
-                    + lv[RES_LIMBS.start + i] * beta * beta * beta
-                    - lv[COMPRESS_LIMBS.start + i],
+            let computed_compress = reduce_with_powers(
+                &[
+                    lv[RES_LIMBS.start + i],
+                    lv[OP1_LIMBS.start + i],
+                    lv[OP0_LIMBS.start + i],
+                    lv[TAG],
+                ],
+                base,
             );
+            yield_constr.constraint(computed_compress - lv[COMPRESS_LIMBS.start + i]);
         }
-
-        eval_lookups(
-            vars,
-            yield_constr,
-            OP0_LIMBS_PERMUTED.start,
-            FIX_RANGE_CHECK_U8_PERMUTED.start,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            OP0_LIMBS_PERMUTED.start + 1,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 1,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            OP0_LIMBS_PERMUTED.start + 2,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 2,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            OP0_LIMBS_PERMUTED.start + 3,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 3,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            OP1_LIMBS_PERMUTED.start,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 4,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            OP1_LIMBS_PERMUTED.start + 1,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 5,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            OP1_LIMBS_PERMUTED.start + 2,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 6,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            OP1_LIMBS_PERMUTED.start + 3,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 7,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            RES_LIMBS_PERMUTED.start,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 8,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            RES_LIMBS_PERMUTED.start + 1,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 9,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            RES_LIMBS_PERMUTED.start + 2,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 10,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            RES_LIMBS_PERMUTED.start + 3,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 11,
-        );
-
-        eval_lookups(
-            vars,
-            yield_constr,
-            COMPRESS_PERMUTED.start,
-            FIX_COMPRESS_PERMUTED.start,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            COMPRESS_PERMUTED.start + 1,
-            FIX_COMPRESS_PERMUTED.start + 1,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            COMPRESS_PERMUTED.start + 2,
-            FIX_COMPRESS_PERMUTED.start + 2,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            COMPRESS_PERMUTED.start + 3,
-            FIX_COMPRESS_PERMUTED.start + 3,
-        );
+        let computed_fix_compress = reduce_with_powers(
+            &[
+                lv[FIX_BITWSIE_RES],
+                lv[FIX_BITWSIE_OP1],
+                lv[FIX_BITWSIE_OP0],
+                lv[FIX_TAG],
+            ],
+            base,
+        );
+        yield_constr.constraint(computed_fix_compress - lv[FIX_COMPRESS]);
     }
 
     fn eval_ext_circuit(
@@ -217,149 +120,50 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for BitwiseStark<
         yield_constr.constraint(builder, res_sum_cs);
 
         // Constrain compress logic.
-        let beta = builder.constant_extension(F::Extension::from_basefield(
-            self.get_compress_challenge().unwrap(),
-        ));
-        let beta_2 = builder.mul_extension(beta, beta);
-        let beta_3 = builder.mul_extension(beta_2, beta);
         for i in 0..4 {
-            let op0_cs = builder.mul_extension(lv[OP0_LIMBS.start + i], beta);
-            let op1_cs = builder.mul_extension(lv[OP1_LIMBS.start + i], beta_2);
-            let res_cs = builder.mul_extension(lv[RES_LIMBS.start + i], beta_3);
-            let cs = builder.add_many_extension([lv[TAG], op0_cs, op1_cs, res_cs]);
-            let cs = builder.sub_extension(cs, lv[COMPRESS_LIMBS.start + i]);
+            let computed_compress = reduce_with_powers_ext_circuit(
+                builder,
+                &[
+                    lv[RES_LIMBS.start + i],
+                    lv[OP1_LIMBS.start + i],
+                    lv[OP0_LIMBS.start + i],
+                    lv[TAG],
+                ],
+                alpha,
+            );
+            let cs = builder.sub_extension(computed_compress, lv[COMPRESS_LIMBS.start + i]);
             yield_constr.constraint(builder, cs);
         }
-
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            OP0_LIMBS_PERMUTED.start,
-            FIX_RANGE_CHECK_U8_PERMUTED.start,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            OP0_LIMBS_PERMUTED.start + 1,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 1,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            OP0_LIMBS_PERMUTED.start + 2,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 2,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            OP0_LIMBS_PERMUTED.start + 3,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 3,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            OP1_LIMBS_PERMUTED.start,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 4,
-        );
-        eval_lookups_circuit(
+        let computed_fix_compress = reduce_with_powers_ext_circuit(
             builder,
-            vars,
-            yield_constr,
-            OP1_LIMBS_PERMUTED.start + 1,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 5,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            OP1_LIMBS_PERMUTED.start + 2,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 6,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            OP1_LIMBS_PERMUTED.start + 3,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 7,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            RES_LIMBS_PERMUTED.start,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 8,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            RES_LIMBS_PERMUTED.start + 1,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 9,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            RES_LIMBS_PERMUTED.start + 2,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 10,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            RES_LIMBS_PERMUTED.start + 3,
-            FIX_RANGE_CHECK_U8_PERMUTED.start + 11,
-        );
-
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            COMPRESS_PERMUTED.start,
-            FIX_COMPRESS_PERMUTED.start,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            COMPRESS_PERMUTED.start + 1,
-            FIX_COMPRESS_PERMUTED.start + 1,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            COMPRESS_PERMUTED.start + 2,
-            FIX_COMPRESS_PERMUTED.start + 2,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            COMPRESS_PERMUTED.start + 3,
-            FIX_COMPRESS_PERMUTED.start + 3,
-        );
+            &[
+                lv[FIX_BITWSIE_RES],
+                lv[FIX_BITWSIE_OP1],
+                lv[FIX_BITWSIE_OP0],
+                lv[FIX_TAG],
+            ],
+            alpha,
+        );
+        let cs = builder.sub_extension(computed_fix_compress, lv[FIX_COMPRESS]);
+        yield_constr.constraint(builder, cs);
     }
 
     fn constraint_degree(&self) -> usize {
         3
     }
 
-    fn permutation_pairs(&self) -> Vec<PermutationPair> {
+    fn lookups(&self) -> Vec<Lookup> {
         vec![
-            PermutationPair::singletons(COMPRESS_LIMBS.start, COMPRESS_PERMUTED.start),
-            PermutationPair::singletons(COMPRESS_LIMBS.start + 1, COMPRESS_PERMUTED.start + 1),
-            PermutationPair::singletons(COMPRESS_LIMBS.start + 2, COMPRESS_PERMUTED.start + 2),
-            PermutationPair::singletons(COMPRESS_LIMBS.start + 3, COMPRESS_PERMUTED.start + 3),
-            PermutationPair::singletons(FIX_COMPRESS, FIX_COMPRESS_PERMUTED.start),
-            PermutationPair::singletons(FIX_COMPRESS, FIX_COMPRESS_PERMUTED.start + 1),
-            PermutationPair::singletons(FIX_COMPRESS, FIX_COMPRESS_PERMUTED.start + 2),
-            PermutationPair::singletons(FIX_COMPRESS, FIX_COMPRESS_PERMUTED.start + 3),
+            Lookup {
+                columns: OP0_LIMBS.chain(OP1_LIMBS).chain(RES_LIMBS).collect(),
+                table_column: FIX_RANGE_CHECK_U8,
+                frequencies_column: FIX_RANGE_CHECK_U8_FREQ,
+            },
+            Lookup {
+                columns: COMPRESS_LIMBS.collect(),
+                table_column: FIX_COMPRESS,
+                frequencies_column: FIX_COMPRESS_FREQ,
+            },
         ]
     }
 }
@@ -373,43 +177,22 @@ pub fn ctl_filter_with_cpu<F: Field>() -> Column<F> {
     Column::single(FILTER)
 }
 
-// Get the column info for Cross_Lookup<Rangecheck_Fixed_table, Bitwise_table>
-/*pub fn ctl_data_with_rangecheck_fixed<F: Field>() -> Vec<Column<F>> {
-    let mut res = Column::singles(OP0_LIMBS).collect_vec();
-    res.extend(Column::singles(OP1_LIMBS).collect_vec());
-    res.extend(Column::singles(RES_LIMBS).collect_vec());
-    res
-}
-pub fn ctl_filter_with_rangecheck_fixed<F: Field>() -> Column<F> {
-    Column::one()
-}
-// Get the column info for Cross_Lookup<Bitwise_Fixed_table, Bitwise_table>
+// Get the column info for Cross_Lookup<Bitwise_table, Bitwise_fixed_table>
 pub fn ctl_data_with_bitwise_fixed<F: Field>() -> Vec<Column<F>> {
-    let mut res =
-        Column::singles([OP0_LIMBS.start, OP1_LIMBS.start, RES_LIMBS.start]).collect_vec();
-    res.extend(
-        Column::singles([
-            OP0_LIMBS.start.add(1),
-            OP1_LIMBS.start.add(1),
-            RES_LIMBS.start.add(1),
-        ])
-        .collect_vec(),
-    );
-    res.extend(
-        Column::singles([
-            OP0_LIMBS.start.add(2),
-            OP1_LIMBS.start.add(2),
-            RES_LIMBS.start.add(2),
-        ])
-        .collect_vec(),
-    );
-    res.extend(Column::singles([OP0_LIMBS.end, OP1_LIMBS.end, RES_LIMBS.end]).collect_vec());
-    res.extend(Column::singles([TAG]));
-    res
+    Column::singles([
+        FIX_TAG,
+        FIX_BITWSIE_OP0,
+        FIX_BITWSIE_OP1,
+        FIX_BITWSIE_RES,
+        FIX_RANGE_CHECK_U8,
+    ])
+    .collect_vec()
 }
+
 pub fn ctl_filter_with_bitwise_fixed<F: Field>() -> Column<F> {
     Column::one()
-}*/
+}
+
 #[cfg(test)]
 mod tests {
     use crate::builtins::bitwise::bitwise_stark::BitwiseStark;
@@ -418,6 +201,7 @@ mod tests {
     use crate::stark::constraint_consumer::ConstraintConsumer;
     use crate::stark::stark::Stark;
     use crate::stark::vars::StarkEvaluationVars;
+    use crate::test_utils::test_stark_circuit_constraints;
     use assembler::encoder::encode_asm_from_json_file;
 
     use core::program::Program;
@@ -437,7 +221,7 @@ mod tests {
         type C = PoseidonGoldilocksConfig;
         type F = <C as GenericConfig<D>>::F;
         type S = BitwiseStark<F, D>;
-        let mut stark = S::default();
+        let stark = S::default();
 
         let program = encode_asm_from_json_file(program_path).unwrap();
         let instructions = program.bytecode.split("\n");
@@ -461,15 +245,13 @@ mod tests {
             &mut BatchCacheManager::default(),
         );
 
-        let (rows, bitwise_beta) =
-            generate_bitwise_trace::<F>(&program.trace.builtin_bitwise_combined);
+        let rows = generate_bitwise_trace::<F>(&program.trace.builtin_bitwise_combined);
         let len = rows[0].len();
         println!(
             "raw trace len:{}, extended len: {}",
             program.trace.builtin_bitwise_combined.len(),
             len
         );
-        stark.set_compress_challenge(bitwise_beta);
 
         let last = F::primitive_root_of_unity(log2_strict(len)).inverse();
         let subgroup =
@@ -535,4 +317,13 @@ mod tests {
         let program_path = path.display().to_string();
         test_bitwise_stark(program_path);
     }
+
+    #[test]
+    fn test_bitwise_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let stark = BitwiseStark::<F, D>::default();
+        test_stark_circuit_constraints::<F, C, _, D>(stark).unwrap();
+    }
 }
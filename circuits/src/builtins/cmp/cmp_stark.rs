@@ -106,7 +106,7 @@ mod tests {
     use crate::stark::constraint_consumer::ConstraintConsumer;
     use crate::stark::stark::Stark;
     use crate::stark::vars::StarkEvaluationVars;
-    use crate::test_utils::test_stark_with_asm_path;
+    use crate::test_utils::{test_stark_circuit_constraints, test_stark_with_asm_path};
     use core::trace::trace::{CmpRow, Trace};
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
@@ -159,4 +159,12 @@ mod tests {
             None,
         );
     }
+
+    #[test]
+    fn test_cmp_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(CmpStark::<F, D>::default()).unwrap();
+    }
 }
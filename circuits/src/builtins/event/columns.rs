@@ -0,0 +1,59 @@
+use std::{collections::BTreeMap, ops::Range};
+
+// Every executed `event` is laid out as a head row, followed by one row per
+// topic and one row per data item:
+//   head:  idx = 0, w = [topic_len, data_len, 0, 0]
+//   topic: idx = i, topic_ptr = mem[op0 + 1 + i], w = topic i
+//   data:  idx = j, w = [mem[op1 + 1 + j], 0, 0, 0]
+// tx_idx, env_idx, clk, op0, op1, topic_len, data_len and event_idx are
+// repeated on every row of the event. Padding rows are at the end.
+pub(crate) const COL_EVENT_TX_IDX: usize = 0;
+pub(crate) const COL_EVENT_ENV_IDX: usize = COL_EVENT_TX_IDX + 1;
+pub(crate) const COL_EVENT_CLK: usize = COL_EVENT_ENV_IDX + 1;
+pub(crate) const COL_EVENT_OP0: usize = COL_EVENT_CLK + 1;
+pub(crate) const COL_EVENT_OP1: usize = COL_EVENT_OP0 + 1;
+pub(crate) const COL_EVENT_TOPIC_LEN: usize = COL_EVENT_OP1 + 1;
+pub(crate) const COL_EVENT_DATA_LEN: usize = COL_EVENT_TOPIC_LEN + 1;
+pub(crate) const COL_EVENT_EVENT_IDX: usize = COL_EVENT_DATA_LEN + 1;
+pub(crate) const COL_EVENT_IS_HEAD: usize = COL_EVENT_EVENT_IDX + 1;
+pub(crate) const COL_EVENT_S_TOPIC: usize = COL_EVENT_IS_HEAD + 1;
+pub(crate) const COL_EVENT_S_DATA: usize = COL_EVENT_S_TOPIC + 1;
+pub(crate) const COL_EVENT_IDX: usize = COL_EVENT_S_DATA + 1;
+pub(crate) const COL_EVENT_TOPIC_PTR: usize = COL_EVENT_IDX + 1;
+pub(crate) const COL_EVENT_W: Range<usize> = COL_EVENT_TOPIC_PTR + 1..COL_EVENT_TOPIC_PTR + 1 + 4;
+pub(crate) const NUM_COL_EVENT: usize = COL_EVENT_W.end;
+
+// Columns which stay the same on all rows of one event.
+pub(crate) const COL_EVENT_CONSTS: [usize; 8] = [
+    COL_EVENT_TX_IDX,
+    COL_EVENT_ENV_IDX,
+    COL_EVENT_CLK,
+    COL_EVENT_OP0,
+    COL_EVENT_OP1,
+    COL_EVENT_TOPIC_LEN,
+    COL_EVENT_DATA_LEN,
+    COL_EVENT_EVENT_IDX,
+];
+
+#[allow(unused)]
+pub(crate) fn get_event_col_name_map() -> BTreeMap<usize, String> {
+    let mut m: BTreeMap<usize, String> = BTreeMap::new();
+    m.insert(COL_EVENT_TX_IDX, "tx_idx".to_string());
+    m.insert(COL_EVENT_ENV_IDX, "env_idx".to_string());
+    m.insert(COL_EVENT_CLK, "clk".to_string());
+    m.insert(COL_EVENT_OP0, "op0".to_string());
+    m.insert(COL_EVENT_OP1, "op1".to_string());
+    m.insert(COL_EVENT_TOPIC_LEN, "topic_len".to_string());
+    m.insert(COL_EVENT_DATA_LEN, "data_len".to_string());
+    m.insert(COL_EVENT_EVENT_IDX, "event_idx".to_string());
+    m.insert(COL_EVENT_IS_HEAD, "is_head".to_string());
+    m.insert(COL_EVENT_S_TOPIC, "s_topic".to_string());
+    m.insert(COL_EVENT_S_DATA, "s_data".to_string());
+    m.insert(COL_EVENT_IDX, "idx".to_string());
+    m.insert(COL_EVENT_TOPIC_PTR, "topic_ptr".to_string());
+    for (index, col) in COL_EVENT_W.into_iter().enumerate() {
+        let name = format!("w_{}", index);
+        m.insert(col, name);
+    }
+    m
+}
@@ -0,0 +1,414 @@
+use core::{types::Field, vm::opcodes::OlaOpcode};
+use std::marker::PhantomData;
+
+use itertools::Itertools;
+use plonky2::{
+    field::{
+        extension::{Extendable, FieldExtension},
+        packed::PackedField,
+    },
+    hash::hash_types::RichField,
+    plonk::circuit_builder::CircuitBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::stark::{
+    constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer},
+    cross_table_lookup::Column,
+    stark::Stark,
+    vars::{StarkEvaluationTargets, StarkEvaluationVars},
+};
+
+use super::columns::*;
+
+pub fn ctl_data_with_cpu<F: Field>() -> Vec<Column<F>> {
+    Column::singles([
+        COL_EVENT_TX_IDX,
+        COL_EVENT_ENV_IDX,
+        COL_EVENT_CLK,
+        COL_EVENT_OP0,
+        COL_EVENT_OP1,
+    ])
+    .collect_vec()
+}
+
+pub fn ctl_filter_with_cpu<F: Field>() -> Column<F> {
+    Column::single(COL_EVENT_IS_HEAD)
+}
+
+fn mem_read<F: Field>(addr: Column<F>, value: Column<F>) -> Vec<Column<F>> {
+    vec![
+        Column::single(COL_EVENT_TX_IDX),
+        Column::single(COL_EVENT_ENV_IDX),
+        Column::single(COL_EVENT_CLK),
+        Column::constant(F::from_canonical_u64(OlaOpcode::EVENT.binary_bit_mask())),
+        addr,
+        value,
+    ]
+}
+
+// Memory reads of the head row: topic_len at op0 and data_len at op1.
+pub fn ctl_data_head_with_mem<F: Field>(is_data: bool) -> Vec<Column<F>> {
+    if is_data {
+        mem_read(
+            Column::single(COL_EVENT_OP1),
+            Column::single(COL_EVENT_DATA_LEN),
+        )
+    } else {
+        mem_read(
+            Column::single(COL_EVENT_OP0),
+            Column::single(COL_EVENT_TOPIC_LEN),
+        )
+    }
+}
+
+pub fn ctl_filter_head_with_mem<F: Field>() -> Column<F> {
+    Column::single(COL_EVENT_IS_HEAD)
+}
+
+// Memory read of the topic pointer at `op0 + 1 + idx`.
+pub fn ctl_data_topic_ptr_with_mem<F: Field>() -> Vec<Column<F>> {
+    mem_read(
+        Column::linear_combination_with_constant(
+            [(COL_EVENT_OP0, F::ONE), (COL_EVENT_IDX, F::ONE)],
+            F::ONE,
+        ),
+        Column::single(COL_EVENT_TOPIC_PTR),
+    )
+}
+
+// Memory read of the `i`th word of a topic at `topic_ptr + i`.
+pub fn ctl_data_topic_with_mem<F: Field>(i: usize) -> Vec<Column<F>> {
+    mem_read(
+        Column::linear_combination_with_constant(
+            [(COL_EVENT_TOPIC_PTR, F::ONE)],
+            F::from_canonical_usize(i),
+        ),
+        Column::single(COL_EVENT_W.start + i),
+    )
+}
+
+pub fn ctl_filter_topic_with_mem<F: Field>() -> Column<F> {
+    Column::single(COL_EVENT_S_TOPIC)
+}
+
+// Memory read of a data item at `op1 + 1 + idx`.
+pub fn ctl_data_data_with_mem<F: Field>() -> Vec<Column<F>> {
+    mem_read(
+        Column::linear_combination_with_constant(
+            [(COL_EVENT_OP1, F::ONE), (COL_EVENT_IDX, F::ONE)],
+            F::ONE,
+        ),
+        Column::single(COL_EVENT_W.start),
+    )
+}
+
+pub fn ctl_filter_data_with_mem<F: Field>() -> Column<F> {
+    Column::single(COL_EVENT_S_DATA)
+}
+
+// (tx_idx, event_idx, kind, idx, w), kind being 0 for head, 1 for topic and 2
+// for data rows. See `PublicValues::event_cells`.
+pub fn ctl_data_public<F: Field>() -> Vec<Column<F>> {
+    let mut res = vec![
+        Column::single(COL_EVENT_TX_IDX),
+        Column::single(COL_EVENT_EVENT_IDX),
+        Column::linear_combination([(COL_EVENT_S_TOPIC, F::ONE), (COL_EVENT_S_DATA, F::TWO)]),
+        Column::single(COL_EVENT_IDX),
+    ];
+    res.extend(Column::singles(COL_EVENT_W));
+    res
+}
+
+pub fn ctl_filter_public<F: Field>() -> Column<F> {
+    Column::sum([COL_EVENT_IS_HEAD, COL_EVENT_S_TOPIC, COL_EVENT_S_DATA])
+}
+
+// Only the shape of every event and the event order inside the segment are
+// constrained here; the values come from memory and the CPU through the
+// lookups above. The order of events inside a transaction and events emitted
+// by reverted call frames are not checked.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct EventStark<F, const D: usize> {
+    pub _phantom: PhantomData<F>,
+}
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for EventStark<F, D> {
+    const COLUMNS: usize = NUM_COL_EVENT;
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: StarkEvaluationVars<FE, P, { Self::COLUMNS }>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+        let h = lv[COL_EVENT_IS_HEAD];
+        let t = lv[COL_EVENT_S_TOPIC];
+        let d = lv[COL_EVENT_S_DATA];
+        let filter = h + t + d;
+        let nv_h = nv[COL_EVENT_IS_HEAD];
+        let nv_t = nv[COL_EVENT_S_TOPIC];
+        let nv_d = nv[COL_EVENT_S_DATA];
+        let nv_filter = nv_h + nv_t + nv_d;
+        // next row starts a new event or is padding.
+        let nv_end = P::ONES - nv_t - nv_d;
+        let idx = lv[COL_EVENT_IDX];
+        let nv_idx = nv[COL_EVENT_IDX];
+        let topic_len = lv[COL_EVENT_TOPIC_LEN];
+        let data_len = lv[COL_EVENT_DATA_LEN];
+
+        // row kinds are binary and exclusive.
+        for s in [h, t, d] {
+            yield_constr.constraint(s * (P::ONES - s));
+        }
+        yield_constr.constraint(filter * (P::ONES - filter));
+
+        // head row carries the lengths.
+        yield_constr.constraint(h * idx);
+        yield_constr.constraint(h * (lv[COL_EVENT_W.start] - topic_len));
+        yield_constr.constraint(h * (lv[COL_EVENT_W.start + 1] - data_len));
+        yield_constr.constraint(h * lv[COL_EVENT_W.start + 2]);
+        yield_constr.constraint(h * lv[COL_EVENT_W.start + 3]);
+        // data row holds one word.
+        for col in COL_EVENT_W.skip(1) {
+            yield_constr.constraint(d * lv[col]);
+        }
+
+        // the segment starts with an event at index 0, and ends with padding.
+        yield_constr.constraint_first_row(t);
+        yield_constr.constraint_first_row(d);
+        yield_constr.constraint_first_row(lv[COL_EVENT_EVENT_IDX]);
+        yield_constr.constraint_last_row(filter);
+        yield_constr.constraint_transition((P::ONES - filter) * nv_filter);
+
+        // event_idx increases by one with every new event.
+        yield_constr.constraint_transition(
+            nv_h * (nv[COL_EVENT_EVENT_IDX] - lv[COL_EVENT_EVENT_IDX] - P::ONES),
+        );
+        // event wide columns do not change inside an event.
+        for col in COL_EVENT_CONSTS {
+            yield_constr.constraint_transition((nv_t + nv_d) * (nv[col] - lv[col]));
+        }
+
+        // head is followed by the first topic, the first data or ends the event.
+        yield_constr.constraint_transition(h * nv_t * nv_idx);
+        yield_constr.constraint_transition(h * nv_d * nv_idx);
+        yield_constr.constraint_transition(h * nv_d * topic_len);
+        yield_constr.constraint_transition(h * nv_end * topic_len);
+        yield_constr.constraint_transition(h * nv_end * data_len);
+        // topics are numbered from 0 to topic_len - 1, then data or the end.
+        yield_constr.constraint_transition(t * nv_t * (nv_idx - idx - P::ONES));
+        yield_constr.constraint_transition(t * nv_d * nv_idx);
+        yield_constr.constraint_transition(t * (P::ONES - nv_t) * (idx + P::ONES - topic_len));
+        yield_constr.constraint_transition(t * nv_end * data_len);
+        // data are numbered from 0 to data_len - 1, then the end.
+        yield_constr.constraint_transition(d * nv_d * (nv_idx - idx - P::ONES));
+        yield_constr.constraint_transition(d * nv_t);
+        yield_constr.constraint_transition(d * nv_end * (idx + P::ONES - data_len));
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+        let one = builder.one_extension();
+        let h = lv[COL_EVENT_IS_HEAD];
+        let t = lv[COL_EVENT_S_TOPIC];
+        let d = lv[COL_EVENT_S_DATA];
+        let filter = builder.add_many_extension([h, t, d]);
+        let nv_h = nv[COL_EVENT_IS_HEAD];
+        let nv_t = nv[COL_EVENT_S_TOPIC];
+        let nv_d = nv[COL_EVENT_S_DATA];
+        let nv_filter = builder.add_many_extension([nv_h, nv_t, nv_d]);
+        let nv_t_or_d = builder.add_extension(nv_t, nv_d);
+        let nv_end = builder.sub_extension(one, nv_t_or_d);
+        let idx = lv[COL_EVENT_IDX];
+        let nv_idx = nv[COL_EVENT_IDX];
+        let topic_len = lv[COL_EVENT_TOPIC_LEN];
+        let data_len = lv[COL_EVENT_DATA_LEN];
+
+        for s in [h, t, d, filter] {
+            let not_s = builder.sub_extension(one, s);
+            let cs = builder.mul_extension(s, not_s);
+            yield_constr.constraint(builder, cs);
+        }
+
+        let cs = builder.mul_extension(h, idx);
+        yield_constr.constraint(builder, cs);
+        let w0_diff = builder.sub_extension(lv[COL_EVENT_W.start], topic_len);
+        let cs = builder.mul_extension(h, w0_diff);
+        yield_constr.constraint(builder, cs);
+        let w1_diff = builder.sub_extension(lv[COL_EVENT_W.start + 1], data_len);
+        let cs = builder.mul_extension(h, w1_diff);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_extension(h, lv[COL_EVENT_W.start + 2]);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_extension(h, lv[COL_EVENT_W.start + 3]);
+        yield_constr.constraint(builder, cs);
+        for col in COL_EVENT_W.skip(1) {
+            let cs = builder.mul_extension(d, lv[col]);
+            yield_constr.constraint(builder, cs);
+        }
+
+        yield_constr.constraint_first_row(builder, t);
+        yield_constr.constraint_first_row(builder, d);
+        yield_constr.constraint_first_row(builder, lv[COL_EVENT_EVENT_IDX]);
+        yield_constr.constraint_last_row(builder, filter);
+        let not_filter = builder.sub_extension(one, filter);
+        let cs = builder.mul_extension(not_filter, nv_filter);
+        yield_constr.constraint_transition(builder, cs);
+
+        let event_idx_diff =
+            builder.sub_extension(nv[COL_EVENT_EVENT_IDX], lv[COL_EVENT_EVENT_IDX]);
+        let event_idx_incr = builder.sub_extension(event_idx_diff, one);
+        let cs = builder.mul_extension(nv_h, event_idx_incr);
+        yield_constr.constraint_transition(builder, cs);
+        for col in COL_EVENT_CONSTS {
+            let diff = builder.sub_extension(nv[col], lv[col]);
+            let cs = builder.mul_extension(nv_t_or_d, diff);
+            yield_constr.constraint_transition(builder, cs);
+        }
+
+        let idx_incr = builder.sub_extension(nv_idx, idx);
+        let idx_incr = builder.sub_extension(idx_incr, one);
+        let idx_next = builder.add_extension(idx, one);
+        let last_topic = builder.sub_extension(idx_next, topic_len);
+        let last_data = builder.sub_extension(idx_next, data_len);
+        let not_nv_t = builder.sub_extension(one, nv_t);
+
+        let cs = builder.mul_many_extension([h, nv_t, nv_idx]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([h, nv_d, nv_idx]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([h, nv_d, topic_len]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([h, nv_end, topic_len]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([h, nv_end, data_len]);
+        yield_constr.constraint_transition(builder, cs);
+
+        let cs = builder.mul_many_extension([t, nv_t, idx_incr]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([t, nv_d, nv_idx]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([t, not_nv_t, last_topic]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([t, nv_end, data_len]);
+        yield_constr.constraint_transition(builder, cs);
+
+        let cs = builder.mul_many_extension([d, nv_d, idx_incr]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_extension(d, nv_t);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([d, nv_end, last_data]);
+        yield_constr.constraint_transition(builder, cs);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        builtins::event::{
+            columns::{get_event_col_name_map, NUM_COL_EVENT},
+            event_stark::EventStark,
+        },
+        generation::event::gen_event_trace,
+        stark::{constraint_consumer::ConstraintConsumer, stark::Stark, vars::StarkEvaluationVars},
+        test_utils::test_stark_circuit_constraints,
+    };
+    use core::{
+        trace::trace::EventRow,
+        types::{Field, GoldilocksField},
+    };
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2_util::log2_strict;
+
+    fn event_row(clk: u64, num_topics: u64, num_data: u64) -> EventRow {
+        let fe = GoldilocksField::from_canonical_u64;
+        EventRow {
+            env_idx: fe(0),
+            clk: fe(clk),
+            op0: fe(100),
+            op1: fe(200),
+            topic_addrs: (0..num_topics).map(|i| fe(300 + 4 * i)).collect(),
+            topics: (0..num_topics)
+                .map(|i| [fe(i), fe(1), fe(2), fe(3)])
+                .collect(),
+            data: (0..num_data).map(|i| fe(10 + i)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_event_stark() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let stark = EventStark::<F, D>::default();
+
+        let batch = vec![
+            vec![event_row(3, 2, 3), event_row(5, 0, 0)],
+            vec![],
+            vec![event_row(2, 1, 0), event_row(4, 0, 2)],
+        ];
+        let rows = gen_event_trace::<F>(&batch);
+        let len = rows[0].len();
+        let last = F::primitive_root_of_unity(log2_strict(len)).inverse();
+        let subgroup =
+            F::cyclic_subgroup_known_order(F::primitive_root_of_unity(log2_strict(len)), len);
+        for i in 0..len {
+            let local_values: [F; NUM_COL_EVENT] = rows
+                .iter()
+                .map(|row| row[i])
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            let next_values: [F; NUM_COL_EVENT] = rows
+                .iter()
+                .map(|row| row[(i + 1) % len])
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            let vars = StarkEvaluationVars {
+                local_values: &local_values,
+                next_values: &next_values,
+            };
+            let mut constraint_consumer = ConstraintConsumer::new(
+                vec![F::rand()],
+                subgroup[i] - last,
+                if i == 0 { F::ONE } else { F::ZERO },
+                if i == len - 1 { F::ONE } else { F::ZERO },
+            );
+            stark.eval_packed_generic(vars, &mut constraint_consumer);
+            for &acc in &constraint_consumer.constraint_accs {
+                if acc != F::ZERO {
+                    let m = get_event_col_name_map();
+                    for (col, name) in m.iter() {
+                        println!(
+                            "{:>12}\t{:>22}\t{:>22}",
+                            name, local_values[*col], next_values[*col]
+                        );
+                    }
+                }
+                assert_eq!(acc, F::ZERO, "constraint error in line {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_event_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(EventStark::<F, D>::default()).unwrap();
+    }
+}
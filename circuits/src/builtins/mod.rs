@@ -1,8 +1,10 @@
 pub mod bitwise;
 pub mod cmp;
+pub mod event;
 pub mod poseidon;
 pub mod rangecheck;
 pub mod rangecheck_small_limbs;
 pub mod sccall;
+pub mod sigcheck;
 pub mod storage;
 pub mod tape;
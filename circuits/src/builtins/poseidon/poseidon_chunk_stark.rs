@@ -8,6 +8,7 @@ use plonky2::{
         packed::PackedField,
     },
     hash::hash_types::RichField,
+    iop::ext_target::ExtensionTarget,
     plonk::circuit_builder::CircuitBuilder,
 };
 use serde::{Deserialize, Serialize};
@@ -274,10 +275,147 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for PoseidonChunk
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut CircuitBuilder<F, D>,
-        _vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+        let one = builder.one_extension();
+        let lv_is_padding = lv[COL_POSEIDON_CHUNK_IS_PADDING_LINE];
+        let lv_is_ext = lv[COL_POSEIDON_CHUNK_IS_EXT_LINE];
+        let nv_is_ext = nv[COL_POSEIDON_CHUNK_IS_EXT_LINE];
+        let lv_not_padding = builder.sub_extension(one, lv_is_padding);
+        let lv_not_ext = builder.sub_extension(one, lv_is_ext);
+        // is_padding_line binary, and change from 0 to 1 only once(or all 1).
+        let cs = builder.mul_extension(lv_is_padding, lv_not_padding);
+        yield_constr.constraint(builder, cs);
+        let padding_diff =
+            builder.sub_extension(nv[COL_POSEIDON_CHUNK_IS_PADDING_LINE], lv_is_padding);
+        let cs = builder.mul_sub_extension(padding_diff, padding_diff, padding_diff);
+        yield_constr.constraint_transition(builder, cs);
+        // is_ext_line is binary
+        let cs = builder.mul_extension(lv_is_ext, lv_not_ext);
+        yield_constr.constraint(builder, cs);
+        // in ext line, tx_idx, env_idx, clk, opcode, op1, dst donnot change.
+        for col in [
+            COL_POSEIDON_CHUNK_TX_IDX,
+            COL_POSEIDON_CHUNK_ENV_IDX,
+            COL_POSEIDON_CHUNK_CLK,
+            COL_POSEIDON_CHUNK_OPCODE,
+            COL_POSEIDON_CHUNK_OP1,
+            COL_POSEIDON_CHUNK_DST,
+        ] {
+            let diff = builder.sub_extension(nv[col], lv[col]);
+            let cs = builder.mul_extension(nv_is_ext, diff);
+            yield_constr.constraint(builder, cs);
+        }
+        // first line is main line or padding line
+        let cs = builder.mul_extension(lv_not_padding, lv_is_ext);
+        yield_constr.constraint_first_row(builder, cs);
+        // is_first_padding[n] is binary; sum of is_first_padding is binary;
+        // is_first_padding[0] is 0
+        for col in COL_POSEIDON_CHUNK_IS_FIRST_PADDING_RANGE {
+            let not_first_padding = builder.sub_extension(one, lv[col]);
+            let cs = builder.mul_extension(lv[col], not_first_padding);
+            yield_constr.constraint(builder, cs);
+        }
+        let sum_is_first_padding = builder
+            .add_many_extension(COL_POSEIDON_CHUNK_IS_FIRST_PADDING_RANGE.map(|col| lv[col]));
+        let not_sum_is_first_padding = builder.sub_extension(one, sum_is_first_padding);
+        let cs = builder.mul_extension(sum_is_first_padding, not_sum_is_first_padding);
+        yield_constr.constraint(builder, cs);
+
+        // define virtual col v_line_acc_addend and v_line_acc_total_addend
+        let mut line_acc_addends = |row: &[ExtensionTarget<D>]| {
+            let mut sum = builder.zero_extension();
+            COL_POSEIDON_CHUNK_IS_FIRST_PADDING_RANGE
+                .map(|col| {
+                    sum = builder.add_extension(sum, row[col]);
+                    builder.sub_extension(one, sum)
+                })
+                .collect::<Vec<_>>()
+        };
+        let v_line_acc_addends = line_acc_addends(lv);
+        let n_v_line_acc_addends = line_acc_addends(nv);
+        let n_v_line_acc_total_addend = builder.add_many_extension(n_v_line_acc_addends);
+        // if next line is ext line, acc_cnt_next = acc_cnt + n_v_line_acc_total_addend
+        let acc_cnt_diff = builder.sub_extension(
+            nv[COL_POSEIDON_CHUNK_ACC_CNT],
+            lv[COL_POSEIDON_CHUNK_ACC_CNT],
+        );
+        let acc_cnt_diff = builder.sub_extension(acc_cnt_diff, n_v_line_acc_total_addend);
+        let cs = builder.mul_extension(nv_is_ext, acc_cnt_diff);
+        yield_constr.constraint(builder, cs);
+        // if sum_is_first_padding is 1, it is last ext line:
+        // 1. next line is main line.
+        // 2. current line is result line.
+        // 3. acc_cnt = op1
+        let cs = builder.mul_extension(sum_is_first_padding, nv_is_ext);
+        yield_constr.constraint(builder, cs);
+        let not_result_line = builder.sub_extension(one, lv[COL_POSEIDON_CHUNK_IS_RESULT_LINE]);
+        let cs = builder.mul_extension(sum_is_first_padding, not_result_line);
+        yield_constr.constraint(builder, cs);
+        let acc_cnt_sub_op1 =
+            builder.sub_extension(lv[COL_POSEIDON_CHUNK_ACC_CNT], lv[COL_POSEIDON_CHUNK_OP1]);
+        let cs = builder.mul_extension(sum_is_first_padding, acc_cnt_sub_op1);
+        yield_constr.constraint(builder, cs);
+        // when acc_cnt != op1, next line must be ext line
+        let nv_not_ext = builder.sub_extension(one, nv_is_ext);
+        let cs = builder.mul_extension(acc_cnt_sub_op1, nv_not_ext);
+        yield_constr.constraint(builder, cs);
+        // main line hash is 0, ext line cap is previous line hash[8~11]
+        for col in COL_POSEIDON_CHUNK_HASH_RANGE {
+            let cs = builder.mul_extension(lv_not_ext, lv[col]);
+            yield_constr.constraint(builder, cs);
+        }
+        for (col_hash, col_cap) in COL_POSEIDON_CHUNK_HASH_RANGE
+            .skip(8)
+            .zip(COL_POSEIDON_CHUNK_CAP_RANGE)
+        {
+            let diff = builder.sub_extension(nv[col_cap], lv[col_hash]);
+            let cs = builder.mul_extension(nv_is_ext, diff);
+            yield_constr.constraint(builder, cs);
+        }
+        // in first ext line, op0 equals main line; in other ext line, op0 increase by 8
+        let op0_diff =
+            builder.sub_extension(nv[COL_POSEIDON_CHUNK_OP0], lv[COL_POSEIDON_CHUNK_OP0]);
+        let cs = builder.mul_many_extension([lv_not_ext, nv_is_ext, op0_diff]);
+        yield_constr.constraint(builder, cs);
+        let eight = builder.constant_extension(F::Extension::from_canonical_u64(8));
+        let op0_diff_sub_8 = builder.sub_extension(op0_diff, eight);
+        let cs = builder.mul_many_extension([lv_is_ext, nv_is_ext, op0_diff_sub_8]);
+        yield_constr.constraint(builder, cs);
+        // filter_looked_cpu: main line is 1.
+        let filter_looked_cpu = lv[COL_POSEIDON_CHUNK_FILTER_LOOKED_CPU];
+        let not_filter_looked_cpu = builder.sub_extension(one, filter_looked_cpu);
+        let cs = builder.mul_many_extension([lv_not_padding, lv_not_ext, not_filter_looked_cpu]);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_many_extension([lv_not_padding, lv_is_ext, filter_looked_cpu]);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_extension(lv_is_padding, filter_looked_cpu);
+        yield_constr.constraint(builder, cs);
+        // filter_looking_mem[]:
+        // 1. in non-ext line, it is 0
+        // 2. in ext line, filter_looking_mem[k] = v_line_acc_addend[k]
+        for (col, line_acc_addend) in
+            COL_POSEIDON_CHUNK_FILTER_LOOKING_MEM_RANGE.zip(v_line_acc_addends)
+        {
+            let filter = lv[col];
+            let cs = builder.mul_extension(lv_not_ext, filter);
+            yield_constr.constraint(builder, cs);
+            let diff = builder.sub_extension(filter, line_acc_addend);
+            let cs = builder.mul_extension(lv_is_ext, diff);
+            yield_constr.constraint(builder, cs);
+        }
+        // filter_looking_poseidon: ext line is 1
+        let filter_looking_poseidon = lv[COL_POSEIDON_CHUNK_FILTER_LOOKING_POSEIDON];
+        let not_filter_looking_poseidon = builder.sub_extension(one, filter_looking_poseidon);
+        let cs =
+            builder.mul_many_extension([lv_not_padding, lv_is_ext, not_filter_looking_poseidon]);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_many_extension([lv_not_padding, lv_not_ext, filter_looking_poseidon]);
+        yield_constr.constraint(builder, cs);
     }
 
     fn constraint_degree(&self) -> usize {
@@ -298,7 +436,7 @@ mod test {
     use crate::stark::stark::Stark;
     use crate::{
         stark::{constraint_consumer::ConstraintConsumer, vars::StarkEvaluationVars},
-        test_utils::test_stark_with_asm_path,
+        test_utils::{test_stark_circuit_constraints, test_stark_with_asm_path},
     };
     use plonky2::{
         field::goldilocks_field::GoldilocksField,
@@ -368,4 +506,13 @@ mod test {
             None,
         );
     }
+
+    #[test]
+    fn test_poseidon_chunk_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(PoseidonChunkStark::<F, D>::default())
+            .unwrap();
+    }
 }
@@ -14,6 +14,7 @@ use plonky2::field::packed::PackedField;
 use plonky2::field::types::Field;
 use plonky2::hash::poseidon::Poseidon;
 use plonky2::hash::{hash_types::RichField, poseidon};
+use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
@@ -139,11 +140,90 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for PoseidonStark
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut CircuitBuilder<F, D>,
-        _vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
-        // todo
+        let lv = vars.local_values;
+        for col in COL_POSEIDON_INPUT_RANGE.skip(9).take(3) {
+            let cap = lv[col];
+            for filter in [
+                FILTER_LOOKED_TREEKEY,
+                FILTER_LOOKED_STORAGE_LEAF,
+                FILTER_LOOKED_STORAGE_BRANCH,
+            ] {
+                let cs = builder.mul_extension(lv[filter], cap);
+                yield_constr.constraint(builder, cs);
+            }
+        }
+        let one = builder.one_extension();
+        let not_leaf_flag = builder.sub_extension(one, lv[COL_POSEIDON_INPUT_RANGE.start + 8]);
+        let cs = builder.mul_extension(lv[FILTER_LOOKED_STORAGE_LEAF], not_leaf_flag);
+        yield_constr.constraint(builder, cs);
+
+        let mut state: [ExtensionTarget<D>; POSEIDON_STATE_WIDTH] =
+            lv[COL_POSEIDON_INPUT_RANGE].try_into().unwrap();
+        let mut round_ctr = 0;
+
+        // First set of full rounds.
+        for r in 0..poseidon::HALF_N_FULL_ROUNDS {
+            <F as Poseidon>::constant_layer_circuit(builder, &mut state, round_ctr);
+            if r != 0 {
+                for i in 0..POSEIDON_STATE_WIDTH {
+                    let sbox_in = lv[Self::full_sbox_0(r, i)];
+                    let cs = builder.sub_extension(state[i], sbox_in);
+                    yield_constr.constraint(builder, cs);
+                    state[i] = sbox_in;
+                }
+            }
+            <F as Poseidon>::sbox_layer_circuit(builder, &mut state);
+            state = <F as Poseidon>::mds_layer_circuit(builder, &state);
+            round_ctr += 1;
+        }
+
+        // Partial rounds.
+        <F as Poseidon>::partial_first_constant_layer_circuit(builder, &mut state);
+        state = <F as Poseidon>::mds_partial_layer_init_circuit(builder, &state);
+        for r in 0..(poseidon::N_PARTIAL_ROUNDS - 1) {
+            let sbox_in = lv[Self::partial_sbox(r)];
+            let cs = builder.sub_extension(state[0], sbox_in);
+            yield_constr.constraint(builder, cs);
+            state[0] = <F as Poseidon>::sbox_monomial_circuit(builder, sbox_in);
+            let c = builder.constant_extension(F::Extension::from_canonical_u64(
+                <F as Poseidon>::FAST_PARTIAL_ROUND_CONSTANTS[r],
+            ));
+            state[0] = builder.add_extension(state[0], c);
+            state = <F as Poseidon>::mds_partial_layer_fast_circuit(builder, &state, r);
+        }
+        let sbox_in = lv[Self::partial_sbox(poseidon::N_PARTIAL_ROUNDS - 1)];
+        let cs = builder.sub_extension(state[0], sbox_in);
+        yield_constr.constraint(builder, cs);
+        state[0] = <F as Poseidon>::sbox_monomial_circuit(builder, sbox_in);
+        state = <F as Poseidon>::mds_partial_layer_fast_circuit(
+            builder,
+            &state,
+            poseidon::N_PARTIAL_ROUNDS - 1,
+        );
+        round_ctr += poseidon::N_PARTIAL_ROUNDS;
+
+        // Second set of full rounds.
+        for r in 0..poseidon::HALF_N_FULL_ROUNDS {
+            <F as Poseidon>::constant_layer_circuit(builder, &mut state, round_ctr);
+            for i in 0..POSEIDON_STATE_WIDTH {
+                let sbox_in = lv[Self::full_sbox_1(r, i)];
+                let cs = builder.sub_extension(state[i], sbox_in);
+                yield_constr.constraint(builder, cs);
+                state[i] = sbox_in;
+            }
+            <F as Poseidon>::sbox_layer_circuit(builder, &mut state);
+            state = <F as Poseidon>::mds_layer_circuit(builder, &state);
+            round_ctr += 1;
+        }
+
+        for i in 0..POSEIDON_STATE_WIDTH {
+            let cs = builder.sub_extension(state[i], lv[COL_POSEIDON_OUTPUT_RANGE.start + i]);
+            yield_constr.constraint(builder, cs);
+        }
     }
 
     fn constraint_degree(&self) -> usize {
@@ -193,7 +273,7 @@ mod test {
         },
         generation::poseidon::generate_poseidon_trace,
         stark::{constraint_consumer::ConstraintConsumer, vars::StarkEvaluationVars},
-        test_utils::test_stark_with_asm_path,
+        test_utils::{test_stark_circuit_constraints, test_stark_with_asm_path},
     };
     use plonky2::{
         field::goldilocks_field::GoldilocksField,
@@ -268,4 +348,12 @@ mod test {
             None,
         );
     }
+
+    #[test]
+    fn test_poseidon_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(PoseidonStark::<F, D>::default()).unwrap();
+    }
 }
@@ -32,13 +32,11 @@ pub(crate) const CMP_FILTER: usize = MEMORY_REGION_FILTER + 1;
 pub(crate) const VAL: usize = CMP_FILTER + 1;
 pub(crate) const LIMB_LO: usize = VAL + 1;
 pub(crate) const LIMB_HI: usize = LIMB_LO + 1;
-pub(crate) const LIMB_LO_PERMUTED: usize = LIMB_HI + 1;
-pub(crate) const LIMB_HI_PERMUTED: usize = LIMB_LO_PERMUTED + 1;
 
-pub(crate) const FIX_RANGE_CHECK_U16: usize = LIMB_HI_PERMUTED + 1;
-pub(crate) const FIX_RANGE_CHECK_U16_PERMUTED_LO: usize = FIX_RANGE_CHECK_U16 + 1;
-pub(crate) const FIX_RANGE_CHECK_U16_PERMUTED_HI: usize = FIX_RANGE_CHECK_U16_PERMUTED_LO + 1;
+pub(crate) const FIX_RANGE_CHECK_U16: usize = LIMB_HI + 1;
+// How many limbs each FIX_RANGE_CHECK_U16 value serves, for the LogUp lookup.
+pub(crate) const FIX_RANGE_CHECK_U16_FREQ: usize = FIX_RANGE_CHECK_U16 + 1;
 
-pub(crate) const COL_NUM_RC: usize = FIX_RANGE_CHECK_U16_PERMUTED_HI + 1; //11
+pub(crate) const COL_NUM_RC: usize = FIX_RANGE_CHECK_U16_FREQ + 1; //9
 
 pub(crate) const RANGE_CHECK_U16_SIZE: usize = 1 << 16; //4
@@ -4,8 +4,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::stark::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 use crate::stark::cross_table_lookup::Column;
-use crate::stark::lookup::*;
-use crate::stark::permutation::*;
+use crate::stark::lookup::Lookup;
 use crate::stark::stark::Stark;
 use crate::stark::vars::{StarkEvaluationTargets, StarkEvaluationVars};
 use plonky2::field::extension::{Extendable, FieldExtension};
@@ -47,19 +46,6 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for RangeCheckSta
         let sum = limb_lo + limb_hi * base;
 
         yield_constr.constraint(val - sum);
-
-        eval_lookups(
-            vars,
-            yield_constr,
-            LIMB_LO_PERMUTED,
-            FIX_RANGE_CHECK_U16_PERMUTED_LO,
-        );
-        eval_lookups(
-            vars,
-            yield_constr,
-            LIMB_HI_PERMUTED,
-            FIX_RANGE_CHECK_U16_PERMUTED_HI,
-        );
     }
 
     fn eval_ext_circuit(
@@ -77,34 +63,18 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for RangeCheckSta
         let sum = builder.mul_add_extension(limb_hi, base, limb_lo);
         let val_sum_diff = builder.sub_extension(val, sum);
         yield_constr.constraint(builder, val_sum_diff);
-
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            LIMB_LO_PERMUTED,
-            FIX_RANGE_CHECK_U16_PERMUTED_LO,
-        );
-        eval_lookups_circuit(
-            builder,
-            vars,
-            yield_constr,
-            LIMB_HI_PERMUTED,
-            FIX_RANGE_CHECK_U16_PERMUTED_HI,
-        );
     }
 
     fn constraint_degree(&self) -> usize {
         3
     }
 
-    fn permutation_pairs(&self) -> Vec<PermutationPair> {
-        vec![
-            PermutationPair::singletons(LIMB_LO, LIMB_LO_PERMUTED),
-            PermutationPair::singletons(LIMB_HI, LIMB_HI_PERMUTED),
-            PermutationPair::singletons(FIX_RANGE_CHECK_U16, FIX_RANGE_CHECK_U16_PERMUTED_LO),
-            PermutationPair::singletons(FIX_RANGE_CHECK_U16, FIX_RANGE_CHECK_U16_PERMUTED_HI),
-        ]
+    fn lookups(&self) -> Vec<Lookup> {
+        vec![Lookup {
+            columns: vec![LIMB_LO, LIMB_HI],
+            table_column: FIX_RANGE_CHECK_U16,
+            frequencies_column: FIX_RANGE_CHECK_U16_FREQ,
+        }]
     }
 }
 
@@ -138,14 +108,16 @@ pub fn ctl_filter_with_cpu<F: Field>() -> Column<F> {
     Column::single(CPU_FILTER)
 }
 
-// Get the column info for Cross_Lookup<Cpu_table, Bitwise_table>
-/*pub fn ctl_data_with_rangecheck_fixed<F: Field>() -> Vec<Column<F>> {
-    let mut res = Column::singles([LIMB_LO, LIMB_HI]).collect_vec();
-    res
+// Get the column info for Cross_Lookup<Rangecheck_table,
+// Rangecheck_fixed_table>
+pub fn ctl_data_with_rangecheck_fixed<F: Field>() -> Vec<Column<F>> {
+    Column::singles([FIX_RANGE_CHECK_U16]).collect_vec()
 }
+
 pub fn ctl_filter_with_rangecheck_fixed<F: Field>() -> Column<F> {
     Column::one()
-}*/
+}
+
 #[cfg(test)]
 mod tests {
     use crate::builtins::rangecheck::rangecheck_stark::RangeCheckStark;
@@ -153,6 +125,7 @@ mod tests {
     use crate::stark::constraint_consumer::ConstraintConsumer;
     use crate::stark::stark::Stark;
     use crate::stark::vars::StarkEvaluationVars;
+    use crate::test_utils::test_stark_circuit_constraints;
     use assembler::encoder::encode_asm_from_json_file;
 
     use core::program::Program;
@@ -263,4 +236,12 @@ mod tests {
         let program_path = "malloc.json";
         test_rc_stark(program_path.to_string());
     }
+
+    #[test]
+    fn test_rc_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(RangeCheckStark::<F, D>::default()).unwrap();
+    }
 }
@@ -84,10 +84,17 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for SCCallStark<F
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut CircuitBuilder<F, D>,
-        _vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
+        let lv = vars.local_values;
+        let clk_diff = builder.sub_extension(
+            lv[COL_SCCALL_CLK_CALLER_RET],
+            lv[COL_SCCALL_CLK_CALLER_CALL],
+        );
+        let cs = builder.sub_extension(clk_diff, lv[COL_SCCALL_CALLER_OP1_IMM]);
+        yield_constr.constraint(builder, cs);
     }
 
     fn constraint_degree(&self) -> usize {
@@ -115,7 +122,7 @@ mod tests {
 
     use crate::{
         stark::{constraint_consumer::ConstraintConsumer, vars::StarkEvaluationVars},
-        test_utils::test_stark_with_asm_path,
+        test_utils::{test_stark_circuit_constraints, test_stark_with_asm_path},
     };
 
     #[test]
@@ -170,4 +177,12 @@ mod tests {
             None,
         );
     }
+
+    #[test]
+    fn test_sccall_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(SCCallStark::<F, D>::default()).unwrap();
+    }
 }
@@ -0,0 +1,39 @@
+use std::{collections::BTreeMap, ops::Range};
+
+// One row per executed `sigcheck`. The 20 inputs are msg_hash, pubkey_x,
+// pubkey_y, r and s, read from memory at `op1..op1 + 20`.
+pub(crate) const COL_SIGCHECK_TX_IDX: usize = 0;
+pub(crate) const COL_SIGCHECK_ENV_IDX: usize = COL_SIGCHECK_TX_IDX + 1;
+pub(crate) const COL_SIGCHECK_CLK: usize = COL_SIGCHECK_ENV_IDX + 1;
+pub(crate) const COL_SIGCHECK_OP1: usize = COL_SIGCHECK_CLK + 1;
+pub(crate) const NUM_SIGCHECK_INPUTS: usize = 20;
+pub(crate) const COL_SIGCHECK_INPUTS: Range<usize> =
+    COL_SIGCHECK_OP1 + 1..COL_SIGCHECK_OP1 + 1 + NUM_SIGCHECK_INPUTS;
+pub(crate) const COL_SIGCHECK_RES: usize = COL_SIGCHECK_INPUTS.end;
+pub(crate) const COL_SIGCHECK_FILTER: usize = COL_SIGCHECK_RES + 1;
+// Always zero. They only keep NUM_COL_SIGCHECK apart from the column count of
+// the other tables: generic_const_exprs can reject functions bounded by two
+// `[(); COLUMNS]:` clauses that evaluate to the same value.
+pub(crate) const COL_SIGCHECK_RESERVED: Range<usize> =
+    COL_SIGCHECK_FILTER + 1..COL_SIGCHECK_FILTER + 3;
+pub(crate) const NUM_COL_SIGCHECK: usize = COL_SIGCHECK_RESERVED.end;
+
+#[allow(unused)]
+pub(crate) fn get_sigcheck_col_name_map() -> BTreeMap<usize, String> {
+    let mut m: BTreeMap<usize, String> = BTreeMap::new();
+    m.insert(COL_SIGCHECK_TX_IDX, "tx_idx".to_string());
+    m.insert(COL_SIGCHECK_ENV_IDX, "env_idx".to_string());
+    m.insert(COL_SIGCHECK_CLK, "clk".to_string());
+    m.insert(COL_SIGCHECK_OP1, "op1".to_string());
+    for (index, col) in COL_SIGCHECK_INPUTS.into_iter().enumerate() {
+        let name = format!("input_{}", index);
+        m.insert(col, name);
+    }
+    m.insert(COL_SIGCHECK_RES, "res".to_string());
+    m.insert(COL_SIGCHECK_FILTER, "filter".to_string());
+    for (index, col) in COL_SIGCHECK_RESERVED.into_iter().enumerate() {
+        let name = format!("reserved_{}", index);
+        m.insert(col, name);
+    }
+    m
+}
@@ -0,0 +1,135 @@
+use core::{types::Field, vm::opcodes::OlaOpcode};
+use std::marker::PhantomData;
+
+use itertools::Itertools;
+use plonky2::{
+    field::{
+        extension::{Extendable, FieldExtension},
+        packed::PackedField,
+    },
+    hash::hash_types::RichField,
+    plonk::circuit_builder::CircuitBuilder,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::stark::{
+    constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer},
+    cross_table_lookup::Column,
+    stark::Stark,
+    vars::{StarkEvaluationTargets, StarkEvaluationVars},
+};
+
+use super::columns::*;
+
+pub fn ctl_data_with_cpu<F: Field>() -> Vec<Column<F>> {
+    Column::singles([
+        COL_SIGCHECK_TX_IDX,
+        COL_SIGCHECK_ENV_IDX,
+        COL_SIGCHECK_CLK,
+        COL_SIGCHECK_OP1,
+        COL_SIGCHECK_RES,
+    ])
+    .collect_vec()
+}
+
+pub fn ctl_filter_with_cpu<F: Field>() -> Column<F> {
+    Column::single(COL_SIGCHECK_FILTER)
+}
+
+// Reads the `i`th input at `op1 + i`, same layout as `memory_stark::ctl_data`.
+pub fn ctl_data_with_mem<F: Field>(i: usize) -> Vec<Column<F>> {
+    vec![
+        Column::single(COL_SIGCHECK_TX_IDX),
+        Column::single(COL_SIGCHECK_ENV_IDX),
+        Column::single(COL_SIGCHECK_CLK),
+        Column::constant(F::from_canonical_u64(OlaOpcode::SIGCHECK.binary_bit_mask())),
+        Column::linear_combination_with_constant(
+            [(COL_SIGCHECK_OP1, F::ONE)],
+            F::from_canonical_usize(i),
+        ),
+        Column::single(COL_SIGCHECK_INPUTS.start + i),
+    ]
+}
+
+pub fn ctl_filter_with_mem<F: Field>() -> Column<F> {
+    Column::single(COL_SIGCHECK_FILTER)
+}
+
+pub fn ctl_data_public<F: Field>() -> Vec<Column<F>> {
+    Column::singles(COL_SIGCHECK_INPUTS.chain([COL_SIGCHECK_RES])).collect_vec()
+}
+
+pub fn ctl_filter_public<F: Field>() -> Column<F> {
+    Column::single(COL_SIGCHECK_FILTER)
+}
+
+// The signatures themselves are not verified in the circuits, this table only
+// binds every checked tuple and its result to the CPU. They are exposed through
+// `PublicValues::sig_checks`, which `verify_proof` checks natively with
+// `verify_sig_checks`. Batch and aggregated proofs only commit to them, so a
+// `sigcheck` result they prove holds only once the preimages are checked with
+// `verify_batch_sig_checks`.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SigCheckStark<F, const D: usize> {
+    pub _phantom: PhantomData<F>,
+}
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for SigCheckStark<F, D> {
+    const COLUMNS: usize = NUM_COL_SIGCHECK;
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: StarkEvaluationVars<FE, P, { Self::COLUMNS }>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let lv = vars.local_values;
+        let filter = lv[COL_SIGCHECK_FILTER];
+        let res = lv[COL_SIGCHECK_RES];
+        yield_constr.constraint(filter * (P::ONES - filter));
+        yield_constr.constraint(res * (P::ONES - res));
+        // padding rows have no result.
+        yield_constr.constraint((P::ONES - filter) * res);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = vars.local_values;
+        let one = builder.one_extension();
+        let filter = lv[COL_SIGCHECK_FILTER];
+        let res = lv[COL_SIGCHECK_RES];
+        let not_filter = builder.sub_extension(one, filter);
+        let cs = builder.mul_extension(filter, not_filter);
+        yield_constr.constraint(builder, cs);
+        let not_res = builder.sub_extension(one, res);
+        let cs = builder.mul_extension(res, not_res);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_extension(not_filter, res);
+        yield_constr.constraint(builder, cs);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        builtins::sigcheck::sigcheck_stark::SigCheckStark,
+        test_utils::test_stark_circuit_constraints,
+    };
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    #[test]
+    fn test_sigcheck_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(SigCheckStark::<F, D>::default()).unwrap();
+    }
+}
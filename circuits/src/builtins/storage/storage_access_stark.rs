@@ -1,3 +1,4 @@
+use anyhow::Result;
 use core::types::Field;
 use std::marker::PhantomData;
 
@@ -8,6 +9,7 @@ use plonky2::{
         packed::PackedField,
     },
     hash::hash_types::RichField,
+    iop::target::Target,
     plonk::circuit_builder::CircuitBuilder,
 };
 use serde::{Deserialize, Serialize};
@@ -105,9 +107,31 @@ pub fn ctl_filter_with_poseidon_bit1<F: Field>() -> Column<F> {
 }
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct StorageAccessStark<F, const D: usize> {
+    state_roots: Option<([u64; 4], [u64; 4])>,
+    #[serde(skip)]
+    state_root_targets: Option<([Target; 4], [Target; 4])>,
     pub _phantom: PhantomData<F>,
 }
 
+impl<F: RichField, const D: usize> StorageAccessStark<F, D> {
+    /// State roots before and after the batch, taken from the public values.
+    pub fn set_state_roots(&mut self, before: [u64; 4], after: [u64; 4]) -> Result<()> {
+        assert!(self.state_roots.is_none(), "already set?");
+        self.state_roots = Some((before, after));
+        Ok(())
+    }
+
+    pub fn get_state_roots(&self) -> Option<([u64; 4], [u64; 4])> {
+        self.state_roots
+    }
+
+    /// Makes `eval_ext_circuit` read the state roots from public value
+    /// targets instead of embedding the native roots as constants.
+    pub fn set_state_root_targets(&mut self, before: [Target; 4], after: [Target; 4]) {
+        self.state_root_targets = Some((before, after));
+    }
+}
+
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for StorageAccessStark<F, D> {
     const COLUMNS: usize = NUM_COL_ST;
     fn eval_packed_generic<FE, P, const D2: usize>(
@@ -230,6 +254,22 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for StorageAccess
                 * lv[COL_ST_HASH_TYPE],
         );
 
+        // pre_root of the first access and root of the last row are the public
+        // state roots
+        let (root_before, root_after) = self.get_state_roots().unwrap();
+        for (col_pre_root_limb, col_root_limb, before, after) in izip!(
+            COL_ST_PRE_ROOT_RANGE,
+            COL_ST_ROOT_RANGE,
+            root_before,
+            root_after
+        ) {
+            yield_constr.constraint_first_row(
+                (P::ONES - lv_is_padding)
+                    * (lv[col_pre_root_limb] - FE::from_canonical_u64(before)),
+            );
+            yield_constr.constraint_last_row(lv[col_root_limb] - FE::from_canonical_u64(after));
+        }
+
         // pre_root and root constraints:
         // in padding line, root not change
         COL_ST_ROOT_RANGE.for_each(|col| {
@@ -324,10 +364,249 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for StorageAccess
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut CircuitBuilder<F, D>,
-        _vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+        let one = builder.one_extension();
+        let lv_is_padding = lv[COL_ST_IS_PADDING];
+        let nv_is_padding = nv[COL_ST_IS_PADDING];
+        let lv_st_access_idx = lv[COL_ST_ACCESS_IDX];
+        let nv_st_access_idx = nv[COL_ST_ACCESS_IDX];
+        let lv_layer = lv[COL_ST_LAYER];
+        let nv_layer = nv[COL_ST_LAYER];
+        let lv_not_padding = builder.sub_extension(one, lv_is_padding);
+        let nv_not_padding = builder.sub_extension(one, nv_is_padding);
+        let access_idx_diff = builder.sub_extension(nv_st_access_idx, lv_st_access_idx);
+        let access_idx_not_change = builder.sub_extension(one, access_idx_diff);
+        // (1 - nv_is_padding) * (nv_st_access_idx - lv_st_access_idx)
+        let nv_access_idx_inc = builder.mul_extension(nv_not_padding, access_idx_diff);
+        // (1 - nv_is_padding) * (1 - (nv_st_access_idx - lv_st_access_idx))
+        let nv_access_idx_same = builder.mul_extension(nv_not_padding, access_idx_not_change);
+        let c_64 = builder.constant_extension(F::Extension::from_canonical_u64(64));
+        let c_128 = builder.constant_extension(F::Extension::from_canonical_u64(128));
+        let c_192 = builder.constant_extension(F::Extension::from_canonical_u64(192));
+        let c_256 = builder.constant_extension(F::Extension::from_canonical_u64(256));
+
+        // is_padding binary and change from 0 to 1 once.
+        let cs = builder.mul_extension(lv_not_padding, lv_is_padding);
+        yield_constr.constraint(builder, cs);
+        let padding_diff = builder.sub_extension(nv_is_padding, lv_is_padding);
+        let cs = builder.mul_sub_extension(padding_diff, padding_diff, padding_diff);
+        yield_constr.constraint_transition(builder, cs);
+        // st_access_idx: from 1, donnot change or increase by 1
+        let cs = builder.mul_sub_extension(lv_not_padding, lv_st_access_idx, lv_not_padding);
+        yield_constr.constraint_first_row(builder, cs);
+        let access_idx_diff_sub_one = builder.sub_extension(access_idx_diff, one);
+        let cs = builder.mul_extension(nv_access_idx_inc, access_idx_diff_sub_one);
+        yield_constr.constraint_transition(builder, cs);
+
+        // layer: from 1 to 256
+        // first line layer is 1
+        let lv_not_layer_one = builder.sub_extension(one, lv_layer);
+        let cs = builder.mul_extension(lv_not_padding, lv_not_layer_one);
+        yield_constr.constraint_first_row(builder, cs);
+        // if st_access_idx not change, layer increase by 1
+        let layer_diff = builder.sub_extension(nv_layer, lv_layer);
+        let layer_diff_sub_one = builder.sub_extension(layer_diff, one);
+        let cs = builder.mul_extension(nv_access_idx_same, layer_diff_sub_one);
+        yield_constr.constraint_transition(builder, cs);
+        // if st_access_idx increase by 1, current layer is 256, next layer is 1
+        let lv_layer_sub_256 = builder.sub_extension(lv_layer, c_256);
+        let cs = builder.mul_extension(nv_access_idx_inc, lv_layer_sub_256);
+        yield_constr.constraint_transition(builder, cs);
+        let nv_layer_sub_one = builder.sub_extension(nv_layer, one);
+        let cs = builder.mul_extension(nv_access_idx_inc, nv_layer_sub_one);
+        yield_constr.constraint_transition(builder, cs);
+        // for lv_layer not 256, nv_layer increase by 1
+        let cs = builder.mul_many_extension([nv_not_padding, lv_layer_sub_256, layer_diff_sub_one]);
+        yield_constr.constraint(builder, cs);
+
+        // is_layer_n constraints
+        // binary
+        for col in [
+            COL_ST_IS_LAYER_1,
+            COL_ST_IS_LAYER_64,
+            COL_ST_IS_LAYER_128,
+            COL_ST_IS_LAYER_192,
+            COL_ST_IS_LAYER_256,
+        ] {
+            let not_layer = builder.sub_extension(one, lv[col]);
+            let cs = builder.mul_extension(lv[col], not_layer);
+            yield_constr.constraint(builder, cs);
+        }
+        // for first line and st_access_idx increased line, is_layer_1 is 1
+        let lv_not_is_layer_1 = builder.sub_extension(one, lv[COL_ST_IS_LAYER_1]);
+        let cs = builder.mul_extension(lv_not_padding, lv_not_is_layer_1);
+        yield_constr.constraint_first_row(builder, cs);
+        let nv_not_is_layer_1 = builder.sub_extension(one, nv[COL_ST_IS_LAYER_1]);
+        let cs = builder.mul_extension(nv_access_idx_inc, nv_not_is_layer_1);
+        yield_constr.constraint_transition(builder, cs);
+        // for not layer n, is_layer_n is 0
+        for (layer, col) in [
+            (one, COL_ST_IS_LAYER_1),
+            (c_64, COL_ST_IS_LAYER_64),
+            (c_128, COL_ST_IS_LAYER_128),
+            (c_192, COL_ST_IS_LAYER_192),
+            (c_256, COL_ST_IS_LAYER_256),
+        ] {
+            let layer_diff = builder.sub_extension(lv[COL_ST_LAYER], layer);
+            let cs = builder.mul_extension(layer_diff, lv[col]);
+            yield_constr.constraint(builder, cs);
+        }
+        // if st_access_idx not change, nv_acc_layer_marker =
+        // lv_acc_layer_marker + sum(markers)
+        let nv_markers = builder.add_many_extension([
+            nv[COL_ST_IS_LAYER_1],
+            nv[COL_ST_IS_LAYER_64],
+            nv[COL_ST_IS_LAYER_128],
+            nv[COL_ST_IS_LAYER_192],
+            nv[COL_ST_IS_LAYER_256],
+        ]);
+        let marker_diff =
+            builder.sub_extension(nv[COL_ST_ACC_LAYER_MARKER], lv[COL_ST_ACC_LAYER_MARKER]);
+        let marker_diff = builder.sub_extension(marker_diff, nv_markers);
+        let cs = builder.mul_extension(nv_access_idx_same, marker_diff);
+        yield_constr.constraint_transition(builder, cs);
+        // if st_access_idx increased, acc_layer_marker must be 5
+        let c_5 = builder.constant_extension(F::Extension::from_canonical_u64(5));
+        let marker_sub_5 = builder.sub_extension(lv[COL_ST_ACC_LAYER_MARKER], c_5);
+        let cs = builder.mul_extension(nv_access_idx_inc, marker_sub_5);
+        yield_constr.constraint_transition(builder, cs);
+
+        // hash_type: layer 256 hash_type = 1, others hash_type = 0
+        // if st_access_idx increased, hash_type = 1
+        let hash_type_sub_one = builder.sub_extension(lv[COL_ST_HASH_TYPE], one);
+        let cs = builder.mul_extension(nv_access_idx_inc, hash_type_sub_one);
+        yield_constr.constraint_transition(builder, cs);
+        // if st_access_idx not change, hash_type = 0
+        let cs = builder.mul_extension(nv_access_idx_same, lv[COL_ST_HASH_TYPE]);
+        yield_constr.constraint_transition(builder, cs);
+
+        // pre_root of the first access and root of the last row are the public
+        // state roots
+        let (root_before, root_after) = match self.state_root_targets {
+            Some((before, after)) => (
+                before.map(|t| builder.convert_to_ext(t)),
+                after.map(|t| builder.convert_to_ext(t)),
+            ),
+            None => {
+                let (before, after) = self.get_state_roots().unwrap();
+                (
+                    before.map(|x| builder.constant_extension(F::Extension::from_canonical_u64(x))),
+                    after.map(|x| builder.constant_extension(F::Extension::from_canonical_u64(x))),
+                )
+            }
+        };
+        for (col_pre_root_limb, col_root_limb, before, after) in izip!(
+            COL_ST_PRE_ROOT_RANGE,
+            COL_ST_ROOT_RANGE,
+            root_before,
+            root_after
+        ) {
+            let pre_root_diff = builder.sub_extension(lv[col_pre_root_limb], before);
+            let cs = builder.mul_extension(lv_not_padding, pre_root_diff);
+            yield_constr.constraint_first_row(builder, cs);
+            let cs = builder.sub_extension(lv[col_root_limb], after);
+            yield_constr.constraint_last_row(builder, cs);
+        }
+
+        // pre_root and root constraints:
+        // in padding line, root not change
+        for col in COL_ST_ROOT_RANGE {
+            let root_diff = builder.sub_extension(nv[col], lv[col]);
+            let cs = builder.mul_extension(nv_is_padding, root_diff);
+            yield_constr.constraint(builder, cs);
+        }
+
+        for (col_pre_root_limb, col_root_limb, col_pre_hash_limb, col_hash_limb) in izip!(
+            COL_ST_PRE_ROOT_RANGE,
+            COL_ST_ROOT_RANGE,
+            COL_ST_PRE_HASH_RANGE,
+            COL_ST_HASH_RANGE
+        ) {
+            // when st_accesss_idx increased, nv_pre_root = lv_root
+            let diff = builder.sub_extension(nv[col_pre_root_limb], lv[col_root_limb]);
+            let cs = builder.mul_extension(nv_access_idx_inc, diff);
+            yield_constr.constraint_transition(builder, cs);
+            // when st_access_idx not change, pre_root and root not change
+            let diff = builder.sub_extension(nv[col_pre_root_limb], lv[col_pre_root_limb]);
+            let cs = builder.mul_extension(nv_access_idx_same, diff);
+            yield_constr.constraint_transition(builder, cs);
+            let diff = builder.sub_extension(nv[col_root_limb], lv[col_root_limb]);
+            let cs = builder.mul_extension(nv_access_idx_same, diff);
+            yield_constr.constraint_transition(builder, cs);
+            // in layer_1 line, root equals related hash
+            let diff = builder.sub_extension(lv[col_pre_root_limb], lv[col_pre_hash_limb]);
+            let cs = builder.mul_extension(lv[COL_ST_IS_LAYER_1], diff);
+            yield_constr.constraint(builder, cs);
+            let diff = builder.sub_extension(lv[col_root_limb], lv[col_hash_limb]);
+            let cs = builder.mul_extension(lv[COL_ST_IS_LAYER_1], diff);
+            yield_constr.constraint(builder, cs);
+        }
+
+        // addr_acc constraints:
+        // layer_bit is binary
+        let not_layer_bit = builder.sub_extension(one, lv[COL_ST_LAYER_BIT]);
+        let cs = builder.mul_extension(lv[COL_ST_LAYER_BIT], not_layer_bit);
+        yield_constr.constraint(builder, cs);
+        // in lines other than 64, 128, 192, 256, nv_addr_acc = acc_acc * 2 +
+        // nv_layer_bit
+        let limb_markers = builder.add_many_extension([
+            lv[COL_ST_IS_LAYER_64],
+            lv[COL_ST_IS_LAYER_128],
+            lv[COL_ST_IS_LAYER_192],
+            lv[COL_ST_IS_LAYER_256],
+        ]);
+        let not_limb_end = builder.sub_extension(one, limb_markers);
+        let addr_acc_doubled = builder.add_extension(lv[COL_ST_ADDR_ACC], lv[COL_ST_ADDR_ACC]);
+        let addr_acc_diff = builder.sub_extension(nv[COL_ST_ADDR_ACC], addr_acc_doubled);
+        let addr_acc_diff = builder.sub_extension(addr_acc_diff, nv[COL_ST_LAYER_BIT]);
+        let cs = builder.mul_extension(not_limb_end, addr_acc_diff);
+        yield_constr.constraint_transition(builder, cs);
+        // in line 64, 128, 192 or 256, addr_addr equals related addr limb
+        for (i, col) in [
+            COL_ST_IS_LAYER_64,
+            COL_ST_IS_LAYER_128,
+            COL_ST_IS_LAYER_192,
+            COL_ST_IS_LAYER_256,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let diff = builder.sub_extension(lv[COL_ST_ADDR_ACC], lv[COL_ST_ADDR_RANGE.start + i]);
+            let cs = builder.mul_extension(lv[col], diff);
+            yield_constr.constraint(builder, cs);
+        }
+
+        // path constraint: when st_access_idx not change, next hash equals path
+        for (col_hash, col_path) in COL_ST_HASH_RANGE.zip(COL_ST_PATH_RANGE) {
+            let diff = builder.sub_extension(lv[col_path], nv[col_hash]);
+            let cs = builder.mul_extension(nv_access_idx_same, diff);
+            yield_constr.constraint_transition(builder, cs);
+        }
+
+        // filter constraints:
+        let bit_0_sum =
+            builder.add_extension(lv[COL_ST_FILTER_IS_HASH_BIT_0], lv[COL_ST_LAYER_BIT]);
+        let bit_0_sum = builder.sub_extension(bit_0_sum, one);
+        let cs = builder.mul_extension(lv_not_padding, bit_0_sum);
+        yield_constr.constraint(builder, cs);
+        let bit_1_diff =
+            builder.sub_extension(lv[COL_ST_FILTER_IS_HASH_BIT_1], lv[COL_ST_LAYER_BIT]);
+        let cs = builder.mul_extension(lv_not_padding, bit_1_diff);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_extension(lv_is_padding, lv[COL_ST_FILTER_IS_HASH_BIT_0]);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_extension(lv_is_padding, lv[COL_ST_FILTER_IS_HASH_BIT_1]);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_extension(lv[COL_ST_FILTER_IS_FOR_PROG], lv[COL_ST_IS_WRITE]);
+        yield_constr.constraint(builder, cs);
+        let not_layer_256 = builder.sub_extension(one, lv[COL_ST_IS_LAYER_256]);
+        let cs = builder.mul_extension(lv[COL_ST_FILTER_IS_FOR_PROG], not_layer_256);
+        yield_constr.constraint(builder, cs);
     }
 
     fn constraint_degree(&self) -> usize {
@@ -344,11 +623,11 @@ mod tests {
         },
         generation::storage::generate_storage_access_trace,
         stark::stark::Stark,
-        test_utils::simple_test_stark,
+        test_utils::{simple_test_stark, test_stark_circuit_constraints},
     };
     use core::{
         trace::trace::Trace,
-        types::{Field, GoldilocksField},
+        types::{merkle_tree::TreeValue, Field, GoldilocksField},
     };
     use std::path::PathBuf;
 
@@ -377,14 +656,21 @@ mod tests {
         type C = PoseidonGoldilocksConfig;
         type F = <C as GenericConfig<D>>::F;
         type S = StorageAccessStark<F, D>;
-        let stark = S::default();
-
         let generate_trace = |trace: Trace| {
-            generate_storage_access_trace(&trace.builtin_storage_hash, &trace.builtin_program_hash)
+            generate_storage_access_trace(
+                &trace.builtin_storage_hash,
+                &trace.builtin_program_hash,
+                trace.start_end_roots.1,
+            )
         };
         let eval_packed_generic =
-            |vars: StarkEvaluationVars<GoldilocksField, GoldilocksField, NUM_COL_ST>,
+            |(before, after): (TreeValue, TreeValue),
+             vars: StarkEvaluationVars<GoldilocksField, GoldilocksField, NUM_COL_ST>,
              constraint_consumer: &mut ConstraintConsumer<GoldilocksField>| {
+                let mut stark = S::default();
+                stark
+                    .set_state_roots(before.map(|x| x.0), after.map(|x| x.0))
+                    .unwrap();
                 stark.eval_packed_generic(vars, constraint_consumer);
             };
         let error_hook =
@@ -408,4 +694,14 @@ mod tests {
             None,
         );
     }
+
+    #[test]
+    fn test_storage_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let mut stark = StorageAccessStark::<F, D>::default();
+        stark.set_state_roots([1, 2, 3, 4], [5, 6, 7, 8]).unwrap();
+        test_stark_circuit_constraints::<F, C, _, D>(stark).unwrap();
+    }
 }
@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::ops::Range;
 
 pub(crate) const COL_TAPE_TX_IDX: usize = 0;
 pub(crate) const COL_TAPE_IS_INIT_SEG: usize = COL_TAPE_TX_IDX + 1;
@@ -6,7 +7,16 @@ pub(crate) const COL_TAPE_OPCODE: usize = COL_TAPE_IS_INIT_SEG + 1;
 pub(crate) const COL_TAPE_ADDR: usize = COL_TAPE_OPCODE + 1;
 pub(crate) const COL_TAPE_VALUE: usize = COL_TAPE_ADDR + 1;
 pub(crate) const COL_FILTER_LOOKED: usize = COL_TAPE_VALUE + 1;
-pub(crate) const NUM_COL_TAPE: usize = COL_FILTER_LOOKED + 1;
+pub(crate) const COL_FILTER_PUBLIC: usize = COL_FILTER_LOOKED + 1;
+pub(crate) const NUM_COL_TAPE: usize = COL_FILTER_PUBLIC + 1;
+
+// Tape header written before a tx is executed, see `TxExeManager::init_tape`.
+// Only the cells that are part of the public values are listed.
+pub(crate) const TAPE_BLOCK_NUMBER: u64 = 0;
+pub(crate) const TAPE_BLOCK_TIMESTAMP: u64 = 1;
+pub(crate) const TAPE_SEQUENCER_ADDRESS: Range<u64> = 2..6;
+pub(crate) const TAPE_CHAIN_ID: u64 = 7;
+pub(crate) const TAPE_TX_HASH: Range<u64> = 21..25;
 
 #[allow(unused)]
 pub(crate) fn get_tape_col_name_map() -> BTreeMap<usize, String> {
@@ -17,5 +27,6 @@ pub(crate) fn get_tape_col_name_map() -> BTreeMap<usize, String> {
     m.insert(COL_TAPE_ADDR, "addr".to_string());
     m.insert(COL_TAPE_VALUE, "value".to_string());
     m.insert(COL_FILTER_LOOKED, "filter".to_string());
+    m.insert(COL_FILTER_PUBLIC, "filter_public".to_string());
     m
 }
@@ -20,8 +20,8 @@ use crate::stark::{
 };
 
 use super::columns::{
-    COL_FILTER_LOOKED, COL_TAPE_ADDR, COL_TAPE_IS_INIT_SEG, COL_TAPE_OPCODE, COL_TAPE_TX_IDX,
-    COL_TAPE_VALUE, NUM_COL_TAPE,
+    COL_FILTER_LOOKED, COL_FILTER_PUBLIC, COL_TAPE_ADDR, COL_TAPE_IS_INIT_SEG, COL_TAPE_OPCODE,
+    COL_TAPE_TX_IDX, COL_TAPE_VALUE, NUM_COL_TAPE,
 };
 
 pub fn ctl_data_tape<F: Field>() -> Vec<Column<F>> {
@@ -38,6 +38,14 @@ pub fn ctl_filter_tape<F: Field>() -> Column<F> {
     Column::single(COL_FILTER_LOOKED)
 }
 
+pub fn ctl_data_tape_public<F: Field>() -> Vec<Column<F>> {
+    Column::singles([COL_TAPE_TX_IDX, COL_TAPE_ADDR, COL_TAPE_VALUE]).collect_vec()
+}
+
+pub fn ctl_filter_tape_public<F: Field>() -> Column<F> {
+    Column::single(COL_FILTER_PUBLIC)
+}
+
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct TapeStark<F, const D: usize> {
     pub _phantom: PhantomData<F>,
@@ -129,14 +137,119 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for TapeStark<F,
                 * (lv[COL_TAPE_OPCODE] - op_tload)
                 * (P::ONES - lv[COL_FILTER_LOOKED]),
         );
+        // public filter selects at most one row per addr: the public values are
+        // looked up against it, so each cell must be counted once
+        yield_constr.constraint(lv[COL_FILTER_PUBLIC] * (P::ONES - lv[COL_FILTER_PUBLIC]));
+        yield_constr.constraint_transition(
+            is_in_same_tx
+                * (P::ONES - (nv[COL_TAPE_ADDR] - lv[COL_TAPE_ADDR]))
+                * nv[COL_FILTER_PUBLIC],
+        );
     }
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut CircuitBuilder<F, D>,
-        _vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+        let one = builder.one_extension();
+        let op_tload = builder.constant_extension(F::Extension::from_canonical_u64(
+            OlaOpcode::TLOAD.binary_bit_mask(),
+        ));
+        let op_tstore = builder.constant_extension(F::Extension::from_canonical_u64(
+            OlaOpcode::TSTORE.binary_bit_mask(),
+        ));
+        let op_sccall = builder.constant_extension(F::Extension::from_canonical_u64(
+            OlaOpcode::SCCALL.binary_bit_mask(),
+        ));
+
+        // opcode can be 0, tstore, tstore
+        let lv_opcode = lv[COL_TAPE_OPCODE];
+        let lv_opcode_sub_tstore = builder.sub_extension(lv_opcode, op_tstore);
+        let lv_opcode_sub_tload = builder.sub_extension(lv_opcode, op_tload);
+        let lv_opcode_sub_sccall = builder.sub_extension(lv_opcode, op_sccall);
+        let not_store_or_call = builder.mul_extension(lv_opcode_sub_tstore, lv_opcode_sub_sccall);
+        let opcode_cs = builder.mul_many_extension([
+            lv_opcode,
+            lv_opcode_sub_tstore,
+            lv_opcode_sub_tload,
+            lv_opcode_sub_sccall,
+        ]);
+        yield_constr.constraint(builder, opcode_cs);
+
+        // tx_idx from 0, not change or increase by one
+        yield_constr.constraint_first_row(builder, lv[COL_TAPE_TX_IDX]);
+        let tx_idx_diff = builder.sub_extension(nv[COL_TAPE_TX_IDX], lv[COL_TAPE_TX_IDX]);
+        let tx_idx_cs = builder.mul_sub_extension(tx_idx_diff, tx_idx_diff, tx_idx_diff);
+        yield_constr.constraint_transition(builder, tx_idx_cs);
+        let is_in_same_tx = builder.sub_extension(one, tx_idx_diff);
+        // is_init_seg start from 0, and can change to 1 once
+        let lv_is_init = lv[COL_TAPE_IS_INIT_SEG];
+        let nv_is_init = nv[COL_TAPE_IS_INIT_SEG];
+        let lv_not_init = builder.sub_extension(one, lv_is_init);
+        let is_init_binary_cs = builder.mul_extension(lv_is_init, lv_not_init);
+        yield_constr.constraint(builder, is_init_binary_cs);
+        let nv_not_init = builder.sub_extension(one, nv_is_init);
+        let tx_change_init_cs = builder.mul_extension(tx_idx_diff, nv_not_init);
+        yield_constr.constraint_transition(builder, tx_change_init_cs);
+        let init_diff = builder.sub_extension(nv_is_init, lv_is_init);
+        let init_diff_sub_one = builder.sub_extension(lv_is_init, nv_is_init);
+        let init_diff_sub_one = builder.sub_extension(init_diff_sub_one, one);
+        let init_change_cs =
+            builder.mul_many_extension([is_in_same_tx, init_diff, init_diff_sub_one]);
+        yield_constr.constraint_transition(builder, init_change_cs);
+        // in init segment opcode can be 0 and tload
+        let init_opcode_cs =
+            builder.mul_many_extension([lv_is_init, lv_opcode, lv_opcode_sub_tload]);
+        yield_constr.constraint(builder, init_opcode_cs);
+        // in non-init segment opcode can be tstore, tstore, sccall
+        let non_init_opcode_cs =
+            builder.mul_many_extension([lv_not_init, lv_opcode_sub_tload, not_store_or_call]);
+        yield_constr.constraint(builder, non_init_opcode_cs);
+        // addr start from 0 and can be same or increase by 1
+        yield_constr.constraint_first_row(builder, lv[COL_TAPE_ADDR]);
+        let tx_change_addr_cs = builder.mul_extension(tx_idx_diff, nv[COL_TAPE_ADDR]);
+        yield_constr.constraint_transition(builder, tx_change_addr_cs);
+        let addr_diff = builder.sub_extension(nv[COL_TAPE_ADDR], lv[COL_TAPE_ADDR]);
+        let addr_diff_sub_one = builder.sub_extension(addr_diff, one);
+        let addr_cs = builder.mul_many_extension([is_in_same_tx, addr_diff, addr_diff_sub_one]);
+        yield_constr.constraint_transition(builder, addr_cs);
+        // same addr have same value, and when addr not change opcode must be tload
+        let addr_not_change = builder.sub_extension(one, addr_diff);
+        let same_tx_same_addr = builder.mul_extension(is_in_same_tx, addr_not_change);
+        let value_diff = builder.sub_extension(nv[COL_TAPE_VALUE], lv[COL_TAPE_VALUE]);
+        let value_cs = builder.mul_extension(same_tx_same_addr, value_diff);
+        yield_constr.constraint_transition(builder, value_cs);
+        let nv_opcode = nv[COL_TAPE_OPCODE];
+        let nv_opcode_sub_tload = builder.sub_extension(nv_opcode, op_tload);
+        let same_addr_opcode_cs = builder.mul_extension(same_tx_same_addr, nv_opcode_sub_tload);
+        yield_constr.constraint_transition(builder, same_addr_opcode_cs);
+        // when addr changed, next opcode must be 0 or tstore (can be applied to the
+        // last padding row)
+        let nv_opcode_sub_tstore = builder.sub_extension(nv_opcode, op_tstore);
+        let nv_opcode_sub_sccall = builder.sub_extension(nv_opcode, op_sccall);
+        let addr_change_opcode_cs = builder.mul_many_extension([
+            is_in_same_tx,
+            addr_diff,
+            nv_opcode,
+            nv_opcode_sub_tstore,
+            nv_opcode_sub_sccall,
+        ]);
+        yield_constr.constraint(builder, addr_change_opcode_cs);
+        // sstore and sccall must be looked
+        let not_looked = builder.sub_extension(one, lv[COL_FILTER_LOOKED]);
+        let looked_cs = builder.mul_many_extension([lv_opcode, lv_opcode_sub_tload, not_looked]);
+        yield_constr.constraint(builder, looked_cs);
+        // public filter selects at most one row per addr
+        let lv_public = lv[COL_FILTER_PUBLIC];
+        let not_public = builder.sub_extension(one, lv_public);
+        let public_binary_cs = builder.mul_extension(lv_public, not_public);
+        yield_constr.constraint(builder, public_binary_cs);
+        let public_cs = builder.mul_extension(same_tx_same_addr, nv[COL_FILTER_PUBLIC]);
+        yield_constr.constraint_transition(builder, public_cs);
     }
 
     fn constraint_degree(&self) -> usize {
@@ -162,7 +275,7 @@ mod tests {
         },
         generation::tape::generate_tape_trace,
         stark::{constraint_consumer::ConstraintConsumer, vars::StarkEvaluationVars},
-        test_utils::test_stark_with_asm_path,
+        test_utils::{test_stark_circuit_constraints, test_stark_with_asm_path},
     };
 
     #[test]
@@ -227,4 +340,12 @@ mod tests {
             None,
         );
     }
+
+    #[test]
+    fn test_tape_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(TapeStark::<F, D>::default()).unwrap();
+    }
 }
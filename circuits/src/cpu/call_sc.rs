@@ -3,9 +3,14 @@ use core::{
     types::Field,
 };
 
-use plonky2::field::{extension::FieldExtension, packed::PackedField};
+use plonky2::field::{
+    extension::{Extendable, FieldExtension},
+    packed::PackedField,
+};
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
 
-use crate::stark::constraint_consumer::ConstraintConsumer;
+use crate::stark::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 
 use super::{
     columns::{
@@ -13,7 +18,7 @@ use super::{
         COL_FILTER_SCCALL_END, COL_IS_EXT_LINE, COL_OP0, COL_OP1, COL_PC, COL_REGS, COL_S_CALL_SC,
         COL_S_END, COL_S_OP0, IS_SCCALL_EXT_LINE,
     },
-    cpu_stark::CpuAdjacentRowWrapper,
+    cpu_stark::{CpuAdjacentRowWrapper, CpuAdjacentRowWrapperTarget},
 };
 
 pub(crate) fn eval_packed_generic<F, FE, P, const D: usize, const D2: usize>(
@@ -132,3 +137,103 @@ pub(crate) fn eval_packed_generic<F, FE, P, const D: usize, const D2: usize>(
             * (P::ONES - wrapper.lv[COL_FILTER_SCCALL_END]),
     );
 }
+
+pub(crate) fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    wrapper: &CpuAdjacentRowWrapperTarget<D>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let lv = wrapper.lv;
+    let nv = wrapper.nv;
+    let one = builder.one_extension();
+    let lv_not_ext_line = builder.sub_extension(one, lv[COL_IS_EXT_LINE]);
+    let not_crossing_inst = builder.sub_extension(one, wrapper.is_crossing_inst);
+    let sccall_main = builder.mul_extension(lv[COL_S_CALL_SC], lv_not_ext_line);
+    let sccall_crossing = builder.mul_extension(lv[COL_S_CALL_SC], wrapper.is_crossing_inst);
+    let end_not_crossing = builder.mul_extension(lv[COL_S_END], not_crossing_inst);
+    let end_ext_not_crossing = builder.mul_extension(end_not_crossing, lv[COL_IS_EXT_LINE]);
+
+    // sccall ext line, op0_reg_sel[0~3] is caller storage addr, op0_reg_sel[4~7] is
+    // caller code addr
+    for i in 0..4 {
+        let diff = builder.sub_extension(
+            nv[COL_S_OP0.start + i],
+            lv[COL_ADDR_STORAGE_RANGE.start + i],
+        );
+        let cs = builder.mul_extension(sccall_main, diff);
+        yield_constr.constraint(builder, cs);
+    }
+    for i in 0..4 {
+        let diff = builder.sub_extension(
+            nv[COL_S_OP0.start + 4 + i],
+            lv[COL_ADDR_CODE_RANGE.start + i],
+        );
+        let cs = builder.mul_extension(sccall_main, diff);
+        yield_constr.constraint(builder, cs);
+    }
+    // op0, op1 all same as main line
+    for col in [COL_OP0, COL_OP1] {
+        let diff = builder.sub_extension(nv[col], lv[col]);
+        let cs = builder.mul_extension(sccall_main, diff);
+        yield_constr.constraint(builder, cs);
+    }
+
+    // in ext line of end, aux0 is env_idx, aux1 is clk
+    let diff = builder.sub_extension(lv[COL_ENV_IDX], nv[COL_AUX0]);
+    let cs = builder.mul_extension(end_not_crossing, diff);
+    yield_constr.constraint_transition(builder, cs);
+    let diff = builder.sub_extension(lv[COL_CLK], nv[COL_AUX1]);
+    let cs = builder.mul_extension(end_not_crossing, diff);
+    yield_constr.constraint_transition(builder, cs);
+    // after call_sc, pc, clk, reg should be zero; ctx should be same as last line
+    let cs = builder.mul_extension(sccall_crossing, nv[COL_CLK]);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_extension(sccall_crossing, nv[COL_PC]);
+    yield_constr.constraint(builder, cs);
+    for i in 0..REGISTER_NUM {
+        let cs = builder.mul_extension(sccall_crossing, nv[COL_REGS.start + i]);
+        yield_constr.constraint(builder, cs);
+    }
+    for ctx_reg_idx in 0..CTX_REGISTER_NUM {
+        for col in [
+            COL_ADDR_STORAGE_RANGE.start + ctx_reg_idx,
+            COL_ADDR_CODE_RANGE.start + ctx_reg_idx,
+        ] {
+            let diff = builder.sub_extension(nv[col], lv[col]);
+            let cs = builder.mul_extension(sccall_crossing, diff);
+            yield_constr.constraint(builder, cs);
+        }
+    }
+    // end ext next line, pc, clk not change
+    for col in [COL_PC, COL_CLK] {
+        let diff = builder.sub_extension(nv[col], lv[col]);
+        let cs = builder.mul_extension(end_ext_not_crossing, diff);
+        yield_constr.constraint(builder, cs);
+    }
+
+    // filter: ext line of sccall is one
+    let filter = lv[IS_SCCALL_EXT_LINE];
+    let not_filter = builder.sub_extension(one, filter);
+    let cs = builder.mul_extension(filter, not_filter);
+    yield_constr.constraint(builder, cs);
+    let not_sccall = builder.sub_extension(one, lv[COL_S_CALL_SC]);
+    let cs = builder.mul_extension(not_sccall, filter);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_many_extension([lv[COL_S_CALL_SC], lv[COL_IS_EXT_LINE], not_filter]);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_extension(sccall_main, filter);
+    yield_constr.constraint(builder, cs);
+
+    // filter of end ext
+    let filter = lv[COL_FILTER_SCCALL_END];
+    let not_filter = builder.sub_extension(one, filter);
+    let cs = builder.mul_extension(filter, not_filter);
+    yield_constr.constraint(builder, cs);
+    let not_end = builder.sub_extension(one, lv[COL_S_END]);
+    let cs = builder.mul_extension(not_end, filter);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_many_extension([lv[COL_S_END], lv_not_ext_line, filter]);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_many_extension([lv[COL_S_END], lv[COL_IS_EXT_LINE], not_filter]);
+    yield_constr.constraint(builder, cs);
+}
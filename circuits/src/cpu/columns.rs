@@ -117,9 +117,11 @@ pub(crate) const COL_S_SSTORE: usize = COL_S_SLOAD + 1;
 pub(crate) const COL_S_TLOAD: usize = COL_S_SSTORE + 1;
 pub(crate) const COL_S_TSTORE: usize = COL_S_TLOAD + 1;
 pub(crate) const COL_S_CALL_SC: usize = COL_S_TSTORE + 1;
-pub(crate) const NUM_OP_SELECTOR: usize = COL_S_CALL_SC - COL_S_SIMPLE_ARITHMATIC_OP + 1;
+pub(crate) const COL_S_SIGCHECK: usize = COL_S_CALL_SC + 1;
+pub(crate) const COL_S_EVENT: usize = COL_S_SIGCHECK + 1;
+pub(crate) const NUM_OP_SELECTOR: usize = COL_S_EVENT - COL_S_SIMPLE_ARITHMATIC_OP + 1;
 
-pub(crate) const COL_IS_ENTRY_SC: usize = COL_S_CALL_SC + 1;
+pub(crate) const COL_IS_ENTRY_SC: usize = COL_S_EVENT + 1;
 pub(crate) const COL_IS_NEXT_LINE_DIFF_INST: usize = COL_IS_ENTRY_SC + 1;
 pub(crate) const COL_IS_NEXT_LINE_SAME_TX: usize = COL_IS_NEXT_LINE_DIFF_INST + 1;
 
@@ -196,6 +198,8 @@ pub(crate) fn get_cpu_col_name_map() -> BTreeMap<usize, String> {
     m.insert(COL_S_TLOAD, "s_tload".to_string());
     m.insert(COL_S_TSTORE, "s_tstore".to_string());
     m.insert(COL_S_CALL_SC, "s_call_sc".to_string());
+    m.insert(COL_S_SIGCHECK, "s_sigcheck".to_string());
+    m.insert(COL_S_EVENT, "s_event".to_string());
     m.insert(COL_IS_ENTRY_SC, "is_entry_sc".to_string());
     m.insert(
         COL_IS_NEXT_LINE_DIFF_INST,
@@ -15,6 +15,7 @@ use {
     plonky2::field::packed::PackedField,
     plonky2::field::types::Field,
     plonky2::hash::hash_types::RichField,
+    plonky2::iop::ext_target::ExtensionTarget,
     plonky2::plonk::circuit_builder::CircuitBuilder,
     std::marker::PhantomData,
 };
@@ -249,6 +250,22 @@ pub fn ctl_filter_cpu_sccall<F: Field>() -> Column<F> {
     Column::single(IS_SCCALL_EXT_LINE)
 }
 
+pub fn ctl_data_cpu_sigcheck<F: Field>() -> Vec<Column<F>> {
+    Column::singles([COL_TX_IDX, COL_ENV_IDX, COL_CLK, COL_OP1, COL_DST]).collect_vec()
+}
+
+pub fn ctl_filter_cpu_sigcheck<F: Field>() -> Column<F> {
+    Column::single(COL_S_SIGCHECK)
+}
+
+pub fn ctl_data_cpu_event<F: Field>() -> Vec<Column<F>> {
+    Column::singles([COL_TX_IDX, COL_ENV_IDX, COL_CLK, COL_OP0, COL_OP1]).collect_vec()
+}
+
+pub fn ctl_filter_cpu_event<F: Field>() -> Column<F> {
+    Column::single(COL_S_EVENT)
+}
+
 pub fn ctl_data_cpu_sccall_end<F: Field>() -> Vec<Column<F>> {
     let mut res = vec![COL_TX_IDX, COL_ENV_IDX];
     for limb_exe_ctx_col in COL_ADDR_STORAGE_RANGE {
@@ -483,6 +500,8 @@ impl<F: RichField, const D: usize> CpuStark<F, D> {
             (lv[COL_S_TLOAD], OlaOpcode::TLOAD.binary_bit_mask()),
             (lv[COL_S_TSTORE], OlaOpcode::TSTORE.binary_bit_mask()),
             (lv[COL_S_CALL_SC], OlaOpcode::SCCALL.binary_bit_mask()),
+            (lv[COL_S_SIGCHECK], OlaOpcode::SIGCHECK.binary_bit_mask()),
+            (lv[COL_S_EVENT], OlaOpcode::EVENT.binary_bit_mask()),
         ];
         yield_constr.constraint(
             lv[COL_S_SIMPLE_ARITHMATIC_OP]
@@ -791,6 +810,444 @@ impl<F: RichField, const D: usize> CpuStark<F, D> {
     }
 }
 
+impl<F: RichField + Extendable<D>, const D: usize> CpuStark<F, D> {
+    fn constraint_wrapper_cols_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let one = builder.one_extension();
+        let not_nv_padding = builder.sub_extension(one, wrapper.nv_is_padding);
+        let tx_idx_diff = builder.sub_extension(wrapper.nv[COL_TX_IDX], wrapper.lv[COL_TX_IDX]);
+        // padding from zero to one, and padding row op is end.
+        let cs = builder.mul_sub_extension(
+            wrapper.lv_is_padding,
+            wrapper.lv_is_padding,
+            wrapper.lv_is_padding,
+        );
+        yield_constr.constraint(builder, cs);
+        let padding_diff = builder.sub_extension(wrapper.nv_is_padding, wrapper.lv_is_padding);
+        let cs = builder.mul_sub_extension(padding_diff, padding_diff, padding_diff);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_sub_extension(
+            wrapper.lv_is_padding,
+            wrapper.lv[COL_S_END],
+            wrapper.lv_is_padding,
+        );
+        yield_constr.constraint(builder, cs);
+        // entry sc env_idx = 0
+        let cs = builder.mul_extension(wrapper.lv_is_entry_sc, wrapper.nv[COL_ENV_IDX]);
+        yield_constr.constraint(builder, cs);
+        // if in same tx, tx_idx should be same
+        let cs = builder.mul_many_extension([not_nv_padding, wrapper.is_in_same_tx, tx_idx_diff]);
+        yield_constr.constraint(builder, cs);
+        // if not same tx, diff of tx_idx should be 1
+        let not_same_tx = builder.sub_extension(one, wrapper.is_in_same_tx);
+        let tx_idx_incr = builder.sub_extension(tx_idx_diff, one);
+        let cs = builder.mul_many_extension([not_nv_padding, not_same_tx, tx_idx_incr]);
+        yield_constr.constraint_transition(builder, cs);
+        // when crossing inst, ext cnt must be ext length.
+        let ext_cnt_diff = builder.sub_extension(wrapper.lv_ext_length, wrapper.lv[COL_EXT_CNT]);
+        let cs = builder.mul_extension(wrapper.is_crossing_inst, ext_cnt_diff);
+        yield_constr.constraint(builder, cs);
+    }
+
+    fn constraint_tx_init_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = wrapper.lv;
+        let nv = wrapper.nv;
+        // first line context init
+        yield_constr.constraint_first_row(builder, lv[COL_TX_IDX]);
+        yield_constr.constraint_first_row(builder, lv[COL_ENV_IDX]);
+        yield_constr.constraint_first_row(builder, lv[COL_CALL_SC_CNT]);
+        yield_constr.constraint_first_row(builder, lv[COL_CLK]);
+        yield_constr.constraint_first_row(builder, lv[COL_PC]);
+        COL_REGS.for_each(|col_reg| {
+            yield_constr.constraint_first_row(builder, lv[col_reg]);
+        });
+        // tx_idx should be the same or increase by one
+        let tx_idx_diff = builder.sub_extension(nv[COL_TX_IDX], lv[COL_TX_IDX]);
+        let cs = builder.mul_extension(wrapper.is_in_same_tx, tx_idx_diff);
+        yield_constr.constraint_transition(builder, cs);
+        // each tx context init
+        let one = builder.one_extension();
+        let not_same_tx = builder.sub_extension(one, wrapper.is_in_same_tx);
+        for col in [COL_ENV_IDX, COL_CALL_SC_CNT, COL_TP, COL_CLK, COL_PC]
+            .into_iter()
+            .chain(COL_REGS)
+        {
+            let cs = builder.mul_extension(not_same_tx, nv[col]);
+            yield_constr.constraint_transition(builder, cs);
+        }
+    }
+
+    fn constraint_env_idx_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = wrapper.lv;
+        let nv = wrapper.nv;
+        let one = builder.one_extension();
+        let not_sccall = builder.sub_extension(one, lv[COL_S_CALL_SC]);
+        let not_crossing_inst = builder.sub_extension(one, wrapper.is_crossing_inst);
+        let sccall_crossing = builder.mul_extension(lv[COL_S_CALL_SC], wrapper.is_crossing_inst);
+        let sccall_not_crossing = builder.mul_extension(lv[COL_S_CALL_SC], not_crossing_inst);
+        let cnt_diff = builder.sub_extension(nv[COL_CALL_SC_CNT], lv[COL_CALL_SC_CNT]);
+        let env_idx_diff = builder.sub_extension(nv[COL_ENV_IDX], lv[COL_ENV_IDX]);
+
+        // call_sc_cnt only increase by 1 on last ext line of call_sc
+        let cnt_incr = builder.sub_extension(cnt_diff, one);
+        let cs = builder.mul_extension(sccall_crossing, cnt_incr);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([wrapper.is_in_same_tx, not_sccall, cnt_diff]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_extension(sccall_not_crossing, cnt_diff);
+        yield_constr.constraint(builder, cs);
+        // env_idx can change on last ext line of call_sc or ext of end, other lines
+        // should be the same
+        // last ext line of call_sc
+        let new_env_idx_diff = builder.sub_extension(nv[COL_ENV_IDX], lv[COL_CALL_SC_CNT]);
+        let cs = builder.mul_extension(sccall_crossing, new_env_idx_diff);
+        yield_constr.constraint(builder, cs);
+        // not call_sc and end, env_idx should be the same
+        let not_sccall_end = builder.sub_extension(not_sccall, lv[COL_S_END]);
+        let cs = builder.mul_extension(not_sccall_end, env_idx_diff);
+        yield_constr.constraint(builder, cs);
+        // call_sc but not last ext line, env_idx should be the same
+        let cs = builder.mul_extension(sccall_not_crossing, env_idx_diff);
+        yield_constr.constraint(builder, cs);
+        // ext of end, env_idx should be the same
+        let cs = builder.mul_many_extension([lv[COL_S_END], lv[COL_IS_EXT_LINE], env_idx_diff]);
+        yield_constr.constraint(builder, cs);
+    }
+
+    fn constraint_opcode_selector_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = wrapper.lv;
+        let ops_to_op = [
+            (lv[COL_S_SIMPLE_ARITHMATIC_OP], 0u64),
+            (lv[COL_S_MOV], OlaOpcode::MOV.binary_bit_mask()),
+            (lv[COL_S_JMP], OlaOpcode::JMP.binary_bit_mask()),
+            (lv[COL_S_CJMP], OlaOpcode::CJMP.binary_bit_mask()),
+            (lv[COL_S_CALL], OlaOpcode::CALL.binary_bit_mask()),
+            (lv[COL_S_RET], OlaOpcode::RET.binary_bit_mask()),
+            (lv[COL_S_MLOAD], OlaOpcode::MLOAD.binary_bit_mask()),
+            (lv[COL_S_MSTORE], OlaOpcode::MSTORE.binary_bit_mask()),
+            (lv[COL_S_END], OlaOpcode::END.binary_bit_mask()),
+            (lv[COL_S_RC], OlaOpcode::RC.binary_bit_mask()),
+            (lv[COL_S_BITWISE], 0u64),
+            (lv[COL_S_NOT], OlaOpcode::NOT.binary_bit_mask()),
+            (lv[COL_S_GTE], OlaOpcode::GTE.binary_bit_mask()),
+            (lv[COL_S_PSDN], OlaOpcode::POSEIDON.binary_bit_mask()),
+            (lv[COL_S_SLOAD], OlaOpcode::SLOAD.binary_bit_mask()),
+            (lv[COL_S_SSTORE], OlaOpcode::SSTORE.binary_bit_mask()),
+            (lv[COL_S_TLOAD], OlaOpcode::TLOAD.binary_bit_mask()),
+            (lv[COL_S_TSTORE], OlaOpcode::TSTORE.binary_bit_mask()),
+            (lv[COL_S_CALL_SC], OlaOpcode::SCCALL.binary_bit_mask()),
+            (lv[COL_S_SIGCHECK], OlaOpcode::SIGCHECK.binary_bit_mask()),
+            (lv[COL_S_EVENT], OlaOpcode::EVENT.binary_bit_mask()),
+        ];
+        let opcode_diff = |builder: &mut CircuitBuilder<F, D>, opcode: OlaOpcode| {
+            builder.add_const_extension(
+                lv[COL_OPCODE],
+                -F::from_canonical_u64(opcode.binary_bit_mask()),
+            )
+        };
+        let mut simple_arithmatic_terms = vec![lv[COL_S_SIMPLE_ARITHMATIC_OP]];
+        for opcode in [
+            OlaOpcode::ADD,
+            OlaOpcode::MUL,
+            OlaOpcode::EQ,
+            OlaOpcode::NEQ,
+            OlaOpcode::ASSERT,
+        ] {
+            simple_arithmatic_terms.push(opcode_diff(builder, opcode));
+        }
+        let cs = builder.mul_many_extension(simple_arithmatic_terms);
+        yield_constr.constraint(builder, cs);
+        let mut bitwise_terms = vec![lv[COL_S_BITWISE]];
+        for opcode in [OlaOpcode::AND, OlaOpcode::OR, OlaOpcode::XOR] {
+            bitwise_terms.push(opcode_diff(builder, opcode));
+        }
+        let cs = builder.mul_many_extension(bitwise_terms);
+        yield_constr.constraint(builder, cs);
+
+        for (s, _) in ops_to_op.iter() {
+            let cs = builder.mul_sub_extension(*s, *s, *s);
+            yield_constr.constraint(builder, cs);
+        }
+
+        // Only one opcode selector enabled.
+        let one = builder.one_extension();
+        let sum_s_op = builder.add_many_extension(ops_to_op.iter().map(|(s, _)| *s));
+        let cs = builder.sub_extension(one, sum_s_op);
+        yield_constr.constraint(builder, cs);
+
+        let zero = builder.zero_extension();
+        let cal_opcode = ops_to_op.iter().fold(zero, |acc, (selector, opcode)| {
+            builder.mul_const_add_extension(F::from_canonical_u64(*opcode), *selector, acc)
+        });
+        let cal_opcode_diff = builder.sub_extension(lv[COL_OPCODE], cal_opcode);
+        let not_bitwise = builder.sub_extension(one, lv[COL_S_BITWISE]);
+        let not_bitwise_arithmatic =
+            builder.sub_extension(not_bitwise, lv[COL_S_SIMPLE_ARITHMATIC_OP]);
+        let cs = builder.mul_extension(cal_opcode_diff, not_bitwise_arithmatic);
+        yield_constr.constraint(builder, cs);
+    }
+
+    fn constraint_instruction_encode_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = wrapper.lv;
+        let one = builder.one_extension();
+        let not_ext_line = builder.sub_extension(one, lv[COL_IS_EXT_LINE]);
+
+        // op_imm should be binary.
+        let cs = builder.mul_sub_extension(lv[COL_OP1_IMM], lv[COL_OP1_IMM], lv[COL_OP1_IMM]);
+        yield_constr.constraint(builder, cs);
+        // Constrain instruction encoding.
+        let mut instruction = builder.mul_const_extension(
+            F::from_canonical_u64(2_u64.pow(Self::OP1_IMM_SHIFT)),
+            lv[COL_OP1_IMM],
+        );
+        // The order of COL_S_OP0, COL_S_OP1, COL_S_DST is r8, r7, .. r0.
+        for (cols, start_shift) in [
+            (COL_S_OP0, Self::OP0_SHIFT_START),
+            (COL_S_OP1, Self::OP1_SHIFT_START),
+            (COL_S_DST, Self::DST_SHIFT_START),
+        ] {
+            let start_shift = 2_u64.pow(start_shift);
+            for (index, col) in cols.rev().enumerate() {
+                let shift = F::from_canonical_u64(start_shift / 2_u64.pow(index as u32));
+                instruction = builder.mul_const_add_extension(shift, lv[col], instruction);
+            }
+        }
+        instruction = builder.add_extension(instruction, lv[COL_OPCODE]);
+        let inst_diff = builder.sub_extension(lv[COL_INST], instruction);
+        let cs = builder.mul_extension(not_ext_line, inst_diff);
+        yield_constr.constraint(builder, cs);
+
+        // When oprand exists, op1 is imm.
+        let imm_diff = builder.sub_extension(lv[COL_OP1], lv[COL_IMM_VAL]);
+        let cs = builder.mul_many_extension([not_ext_line, lv[COL_OP1_IMM], imm_diff]);
+        yield_constr.constraint(builder, cs);
+    }
+
+    fn constraint_operands_mathches_registers_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = wrapper.lv;
+        let one = builder.one_extension();
+        let not_ext_line = builder.sub_extension(one, lv[COL_IS_EXT_LINE]);
+
+        // op0, op1, dst selectors should be binary. (only used as selector in main
+        // line)
+        for col in COL_S_OP0.chain(COL_S_OP1).chain(COL_S_DST) {
+            let s = lv[col];
+            let not_s = builder.sub_extension(one, s);
+            let cs = builder.mul_many_extension([not_ext_line, s, not_s]);
+            yield_constr.constraint(builder, cs);
+        }
+
+        // Only one register used for op0, op1 and dst.
+        let sums = [COL_S_OP0, COL_S_OP1, COL_S_DST]
+            .map(|cols| builder.add_many_extension(cols.map(|col| lv[col])));
+        for sum in sums {
+            let not_sum = builder.sub_extension(one, sum);
+            let cs = builder.mul_many_extension([not_ext_line, sum, not_sum]);
+            yield_constr.constraint(builder, cs);
+        }
+
+        // Op and register permutation.
+        // Register should be next line.
+        for ((cols, regs, col_operand), sum) in [
+            (COL_S_OP0, &wrapper.regs, COL_OP0),
+            (COL_S_OP1, &wrapper.regs, COL_OP1),
+            (COL_S_DST, &wrapper.n_regs, COL_DST),
+        ]
+        .into_iter()
+        .zip(sums)
+        {
+            let zero = builder.zero_extension();
+            let operand = cols.zip(regs.iter()).fold(zero, |acc, (col, r)| {
+                builder.mul_add_extension(lv[col], *r, acc)
+            });
+            let diff = builder.sub_extension(lv[col_operand], operand);
+            let cs = builder.mul_many_extension([not_ext_line, sum, diff]);
+            yield_constr.constraint(builder, cs);
+        }
+    }
+
+    fn constraint_ext_lines_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = wrapper.lv;
+        let nv = wrapper.nv;
+        let one = builder.one_extension();
+        // constraint is_ext_line
+        let not_ext_inst = builder.sub_extension(one, wrapper.lv_is_ext_inst);
+        let cs = builder.mul_extension(not_ext_inst, lv[COL_IS_EXT_LINE]);
+        yield_constr.constraint(builder, cs);
+        let ext_cnt_diff = builder.sub_extension(wrapper.lv_ext_length, lv[COL_EXT_CNT]);
+        let nv_not_ext_line = builder.sub_extension(one, nv[COL_IS_EXT_LINE]);
+        let cs =
+            builder.mul_many_extension([wrapper.lv_is_ext_inst, ext_cnt_diff, nv_not_ext_line]);
+        yield_constr.constraint(builder, cs);
+        // constraint ext_cnt
+        let lv_not_ext_line = builder.sub_extension(one, lv[COL_IS_EXT_LINE]);
+        let cs =
+            builder.mul_many_extension([wrapper.lv_is_ext_inst, lv_not_ext_line, lv[COL_EXT_CNT]]);
+        yield_constr.constraint(builder, cs);
+        let ext_cnt_diff = builder.sub_extension(nv[COL_EXT_CNT], lv[COL_EXT_CNT]);
+        let ext_cnt_incr = builder.sub_extension(ext_cnt_diff, one);
+        let cs =
+            builder.mul_many_extension([wrapper.nv_is_ext_inst, nv[COL_IS_EXT_LINE], ext_cnt_incr]);
+        yield_constr.constraint(builder, cs);
+        // opcode, opcode selector, op1_imm not change
+        for col in [COL_OPCODE]
+            .into_iter()
+            .chain(COL_S_SIMPLE_ARITHMATIC_OP..COL_S_SIMPLE_ARITHMATIC_OP + NUM_OP_SELECTOR)
+            .chain([COL_OP1_IMM])
+        {
+            let diff = builder.sub_extension(nv[col], lv[col]);
+            let cs = builder.mul_extension(nv[COL_IS_EXT_LINE], diff);
+            yield_constr.constraint(builder, cs);
+        }
+    }
+
+    fn constraint_env_unchanged_clk_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = wrapper.lv;
+        let nv = wrapper.nv;
+        let one = builder.one_extension();
+        let clk_diff = builder.sub_extension(nv[COL_CLK], lv[COL_CLK]);
+        // next line is ext line, clk not change (except for end)
+        let nv_not_end = builder.sub_extension(one, nv[COL_S_END]);
+        let cs = builder.mul_many_extension([nv[COL_IS_EXT_LINE], nv_not_end, clk_diff]);
+        yield_constr.constraint(builder, cs);
+        // when not change env, clk increase one when meet main line
+        let not_sccall = builder.sub_extension(one, lv[COL_S_CALL_SC]);
+        let not_sccall_end = builder.sub_extension(not_sccall, lv[COL_S_END]);
+        let nv_not_ext_line = builder.sub_extension(one, nv[COL_IS_EXT_LINE]);
+        let clk_incr = builder.sub_extension(clk_diff, one);
+        let cs = builder.mul_many_extension([
+            wrapper.is_in_same_tx,
+            not_sccall_end,
+            nv_not_ext_line,
+            clk_incr,
+        ]);
+        yield_constr.constraint(builder, cs);
+    }
+
+    fn constraint_env_unchanged_pc_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = wrapper.lv;
+        let nv = wrapper.nv;
+        let one = builder.one_extension();
+        // next line is ext line, pc not change (except for end)
+        let clk_diff = builder.sub_extension(nv[COL_CLK], lv[COL_CLK]);
+        let nv_not_end = builder.sub_extension(one, nv[COL_S_END]);
+        let cs = builder.mul_many_extension([nv[COL_IS_EXT_LINE], nv_not_end, clk_diff]);
+        yield_constr.constraint(builder, cs);
+
+        let s_mem = builder.add_extension(lv[COL_S_MLOAD], lv[COL_S_MSTORE]);
+        let not_mem = builder.sub_extension(one, s_mem);
+        let imm_size = builder.add_extension(one, lv[COL_OP1_IMM]);
+        let mem_size = builder.mul_const_extension(F::TWO, s_mem);
+        let instruction_size = builder.mul_add_extension(not_mem, imm_size, mem_size);
+        let pc_next_inst = builder.add_extension(lv[COL_PC], instruction_size);
+
+        let s_jumps = builder.add_many_extension([
+            lv[COL_S_JMP],
+            lv[COL_S_CJMP],
+            lv[COL_S_CALL],
+            lv[COL_S_RET],
+        ]);
+        let not_jumps = builder.sub_extension(one, s_jumps);
+        let pc_incr = builder.mul_extension(not_jumps, pc_next_inst);
+        let pc_jmp = builder.mul_extension(lv[COL_S_JMP], lv[COL_OP1]);
+        let not_op0 = builder.sub_extension(one, lv[COL_OP0]);
+        let cjmp_op1 = builder.mul_extension(lv[COL_OP0], lv[COL_OP1]);
+        let cjmp_target = builder.mul_add_extension(not_op0, pc_next_inst, cjmp_op1);
+        let pc_cjmp = builder.mul_extension(lv[COL_S_CJMP], cjmp_target);
+        let pc_call = builder.mul_extension(lv[COL_S_CALL], lv[COL_OP1]);
+        let pc_ret = builder.mul_extension(lv[COL_S_RET], lv[COL_DST]);
+        let pc = builder.add_many_extension([pc_incr, pc_jmp, pc_cjmp, pc_call, pc_ret]);
+
+        let nv_not_ext_line = builder.sub_extension(one, nv[COL_IS_EXT_LINE]);
+        let not_end = builder.sub_extension(one, lv[COL_S_END]);
+        let not_end_sccall = builder.sub_extension(not_end, lv[COL_S_CALL_SC]);
+        let pc_diff = builder.sub_extension(nv[COL_PC], pc);
+        let cs = builder.mul_many_extension([nv_not_ext_line, not_end_sccall, pc_diff]);
+        yield_constr.constraint(builder, cs);
+        let cs =
+            builder.mul_many_extension([nv_not_ext_line, lv[COL_S_CJMP], lv[COL_OP0], not_op0]);
+        yield_constr.constraint(builder, cs);
+    }
+
+    fn constraint_reg_consistency_circuit(
+        builder: &mut CircuitBuilder<F, D>,
+        wrapper: &CpuAdjacentRowWrapperTarget<D>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = wrapper.lv;
+        let one = builder.one_extension();
+        let s_dsts: [ExtensionTarget<D>; REGISTER_NUM] = lv[COL_S_DST].try_into().unwrap();
+        let sccall_crossing = builder.mul_extension(lv[COL_S_CALL_SC], wrapper.is_crossing_inst);
+        let not_ext_line = builder.sub_extension(one, lv[COL_IS_EXT_LINE]);
+        let end_main = builder.mul_extension(lv[COL_S_END], not_ext_line);
+        let multi_reg_change = builder.add_many_extension([
+            lv[COL_S_SLOAD],
+            lv[COL_S_PSDN],
+            sccall_crossing,
+            end_main,
+        ]);
+        let not_multi_reg_change = builder.sub_extension(one, multi_reg_change);
+
+        // for normal opcode, only dst reg can change(not include fp)
+        for (dst, l_r, n_r) in izip!(
+            &s_dsts[..REGISTER_NUM - 1],
+            &wrapper.regs[..REGISTER_NUM - 1],
+            &wrapper.n_regs[..REGISTER_NUM - 1]
+        ) {
+            let not_dst = builder.sub_extension(one, *dst);
+            let reg_diff = builder.sub_extension(*n_r, *l_r);
+            let cs = builder.mul_many_extension([not_multi_reg_change, not_dst, reg_diff]);
+            yield_constr.constraint_transition(builder, cs);
+        }
+        // for fp consistency
+        let not_ret = builder.sub_extension(one, lv[COL_S_RET]);
+        let not_ret_sccall = builder.sub_extension(not_ret, sccall_crossing);
+        let fp_unchanged = builder.sub_extension(not_ret_sccall, lv[COL_S_END]);
+        let not_dst = builder.sub_extension(one, s_dsts[REGISTER_NUM - 1]);
+        let fp_diff = builder.sub_extension(
+            wrapper.n_regs[REGISTER_NUM - 1],
+            wrapper.regs[REGISTER_NUM - 1],
+        );
+        let cs = builder.mul_many_extension([fp_unchanged, not_dst, fp_diff]);
+        yield_constr.constraint_transition(builder, cs);
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub(crate) struct CpuAdjacentRowWrapper<'a, F, FE, P, const D: usize, const D2: usize>
 where
@@ -867,6 +1324,85 @@ impl<
     }
 }
 
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct CpuAdjacentRowWrapperTarget<'a, const D: usize> {
+    pub(crate) lv: &'a [ExtensionTarget<D>; NUM_CPU_COLS],
+    pub(crate) nv: &'a [ExtensionTarget<D>; NUM_CPU_COLS],
+    pub(crate) regs: [ExtensionTarget<D>; REGISTER_NUM],
+    pub(crate) n_regs: [ExtensionTarget<D>; REGISTER_NUM],
+    pub(crate) lv_is_padding: ExtensionTarget<D>,
+    pub(crate) nv_is_padding: ExtensionTarget<D>,
+    pub(crate) lv_is_ext_inst: ExtensionTarget<D>,
+    pub(crate) nv_is_ext_inst: ExtensionTarget<D>,
+    pub(crate) lv_ext_length: ExtensionTarget<D>,
+    pub(crate) is_crossing_inst: ExtensionTarget<D>,
+    pub(crate) is_in_same_tx: ExtensionTarget<D>,
+    pub(crate) lv_is_entry_sc: ExtensionTarget<D>,
+}
+
+impl<'a, const D: usize> CpuAdjacentRowWrapperTarget<'a, D> {
+    fn from_targets<F: RichField + Extendable<D>>(
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<'a, D, NUM_CPU_COLS>,
+    ) -> Self {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+        let regs: [ExtensionTarget<D>; REGISTER_NUM] = lv[COL_REGS].try_into().unwrap();
+        let n_regs: [ExtensionTarget<D>; REGISTER_NUM] = nv[COL_REGS].try_into().unwrap();
+
+        let lv_is_padding = lv[COL_IS_PADDING];
+        let nv_is_padding = nv[COL_IS_PADDING];
+        let lv_is_ext_inst = builder.add_many_extension([
+            lv[COL_S_SLOAD],
+            lv[COL_S_SSTORE],
+            lv[COL_S_TLOAD],
+            lv[COL_S_TSTORE],
+            lv[COL_S_CALL_SC],
+            lv[COL_S_END],
+        ]);
+        let nv_is_ext_inst = builder.add_many_extension([
+            nv[COL_S_SLOAD],
+            nv[COL_S_SSTORE],
+            nv[COL_S_TLOAD],
+            nv[COL_S_TSTORE],
+            nv[COL_S_CALL_SC],
+            nv[COL_S_END],
+        ]);
+        let lv_is_entry_sc = lv[COL_IS_ENTRY_SC];
+        let one = builder.one_extension();
+        let not_op0 = builder.sub_extension(one, lv[COL_OP0]);
+        let tload_length = builder.mul_add_extension(lv[COL_OP0], lv[COL_OP1], not_op0);
+        let tload_length = builder.mul_extension(lv[COL_S_TLOAD], tload_length);
+        let tstore_length = builder.mul_extension(lv[COL_S_TSTORE], lv[COL_OP1]);
+        let not_entry_sc = builder.sub_extension(one, lv_is_entry_sc);
+        let end_length = builder.mul_extension(lv[COL_S_END], not_entry_sc);
+        let lv_ext_length = builder.add_many_extension([
+            lv[COL_S_SLOAD],
+            lv[COL_S_SSTORE],
+            tload_length,
+            tstore_length,
+            lv[COL_S_CALL_SC],
+            end_length,
+        ]);
+        let is_crossing_inst = lv[COL_IS_NEXT_LINE_DIFF_INST];
+        let is_in_same_tx = lv[COL_IS_NEXT_LINE_SAME_TX];
+        Self {
+            lv,
+            nv,
+            regs,
+            n_regs,
+            lv_is_padding,
+            nv_is_padding,
+            lv_is_ext_inst,
+            nv_is_ext_inst,
+            lv_ext_length,
+            is_crossing_inst,
+            is_in_same_tx,
+            lv_is_entry_sc,
+        }
+    }
+}
+
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CpuStark<F, D> {
     const COLUMNS: usize = NUM_CPU_COLS;
 
@@ -949,10 +1485,74 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CpuStark<F, D
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut CircuitBuilder<F, D>,
-        _vars: StarkEvaluationTargets<D, NUM_CPU_COLS>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, NUM_CPU_COLS>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+
+        let wrapper = CpuAdjacentRowWrapperTarget::from_targets(builder, vars);
+        let one = builder.one_extension();
+
+        Self::constraint_wrapper_cols_circuit(builder, &wrapper, yield_constr);
+        Self::constraint_tx_init_circuit(builder, &wrapper, yield_constr);
+        // tx_idx not change or increase by 1
+        let not_nv_padding = builder.sub_extension(one, wrapper.nv_is_padding);
+        let not_end = builder.sub_extension(one, lv[COL_S_END]);
+        let tx_idx_diff = builder.sub_extension(nv[COL_TX_IDX], lv[COL_TX_IDX]);
+        let cs = builder.mul_many_extension([not_nv_padding, not_end, tx_idx_diff]);
+        yield_constr.constraint_transition(builder, cs);
+        let tx_idx_incr = builder.sub_extension(tx_idx_diff, one);
+        let cs = builder.mul_many_extension([
+            not_nv_padding,
+            wrapper.lv_is_entry_sc,
+            lv[COL_S_END],
+            tx_idx_incr,
+        ]);
+        yield_constr.constraint_transition(builder, cs);
+        // ctx reg not change on normal opcodes
+        let not_sccall = builder.sub_extension(one, lv[COL_S_CALL_SC]);
+        let ctx_unchanged = builder.mul_many_extension([not_nv_padding, not_end, not_sccall]);
+        for ctx_reg_idx in 0..CTX_REGISTER_NUM {
+            for col in [
+                COL_ADDR_STORAGE_RANGE.start + ctx_reg_idx,
+                COL_ADDR_CODE_RANGE.start + ctx_reg_idx,
+            ] {
+                let diff = builder.sub_extension(nv[col], lv[col]);
+                let cs = builder.mul_extension(ctx_unchanged, diff);
+                yield_constr.constraint_transition(builder, cs);
+            }
+        }
+        // filter imm to prog
+        let not_padding = builder.sub_extension(one, lv[COL_IS_PADDING]);
+        let main_line = builder.sub_extension(not_padding, lv[COL_IS_EXT_LINE]);
+        let not_filter = builder.sub_extension(one, lv[COL_FILTER_LOOKING_PROG_IMM]);
+        let cs = builder.mul_many_extension([main_line, lv[COL_OP1_IMM], not_filter]);
+        yield_constr.constraint(builder, cs);
+        let s_mem = builder.add_extension(lv[COL_S_MLOAD], lv[COL_S_MSTORE]);
+        let cs = builder.mul_many_extension([main_line, s_mem, not_filter]);
+        yield_constr.constraint(builder, cs);
+
+        Self::constraint_ext_lines_circuit(builder, &wrapper, yield_constr);
+        Self::constraint_env_idx_circuit(builder, &wrapper, yield_constr);
+        Self::constraint_opcode_selector_circuit(builder, &wrapper, yield_constr);
+        Self::constraint_instruction_encode_circuit(builder, &wrapper, yield_constr);
+        Self::constraint_operands_mathches_registers_circuit(builder, &wrapper, yield_constr);
+        Self::constraint_env_unchanged_clk_circuit(builder, &wrapper, yield_constr);
+        Self::constraint_env_unchanged_pc_circuit(builder, &wrapper, yield_constr);
+        Self::constraint_reg_consistency_circuit(builder, &wrapper, yield_constr);
+
+        // opcode
+        simple_arithmatic_op::eval_ext_circuit(builder, lv, nv, yield_constr);
+        mov::eval_ext_circuit(builder, lv, nv, yield_constr);
+        call::eval_ext_circuit(builder, lv, nv, yield_constr);
+        ret::eval_ext_circuit(builder, lv, nv, yield_constr);
+        mload::eval_ext_circuit(builder, lv, nv, yield_constr);
+        mstore::eval_ext_circuit(builder, lv, nv, yield_constr);
+        storage::eval_ext_circuit(builder, lv, nv, yield_constr);
+        tape::eval_ext_circuit(builder, &wrapper, yield_constr);
+        call_sc::eval_ext_circuit(builder, &wrapper, yield_constr);
     }
 
     fn constraint_degree(&self) -> usize {
@@ -962,7 +1562,10 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CpuStark<F, D
 
 #[cfg(test)]
 mod tests {
-    use crate::{generation::cpu::generate_cpu_trace, test_utils::test_stark_with_asm_path};
+    use crate::{
+        generation::cpu::generate_cpu_trace,
+        test_utils::{test_stark_circuit_constraints, test_stark_with_asm_path},
+    };
     use core::trace::trace::{Step, Trace};
     use std::path::PathBuf;
     use {
@@ -1055,6 +1658,14 @@ mod tests {
         test_cpu_with_asm_file_name("vote.json".to_string(), Some(init_calldata), Some(db_name));
     }
 
+    #[test]
+    fn test_cpu_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(CpuStark::<F, D>::default()).unwrap();
+    }
+
     #[allow(unused)]
     fn test_cpu_with_asm_file_name(
         file_name: String,
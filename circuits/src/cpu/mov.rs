@@ -15,7 +15,6 @@ pub(crate) fn eval_packed_generic<P: PackedField>(
     yield_constr.constraint(lv[COL_S_MOV] * (lv[COL_DST] - lv[COL_OP1]));
 }
 
-#[allow(dead_code)]
 pub(crate) fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
     lv: &[ExtensionTarget<D>; NUM_CPU_COLS],
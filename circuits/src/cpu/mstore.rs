@@ -32,12 +32,27 @@ pub(crate) fn eval_packed_generic<P: PackedField>(
     );
 }
 
-#[allow(dead_code)]
 pub(crate) fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
-    _builder: &mut CircuitBuilder<F, D>,
-    _lv: &[ExtensionTarget<D>; NUM_CPU_COLS],
+    builder: &mut CircuitBuilder<F, D>,
+    lv: &[ExtensionTarget<D>; NUM_CPU_COLS],
     _nv: &[ExtensionTarget<D>; NUM_CPU_COLS],
-    _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
 ) {
-    // todo
+    let one = builder.one_extension();
+    let not_op1_imm = builder.sub_extension(one, lv[COL_OP1_IMM]);
+    // when op1_imm is 0, aux0 is the imm
+    let aux0_diff = builder.sub_extension(lv[COL_AUX0], lv[COL_IMM_VAL]);
+    let cs = builder.mul_many_extension([lv[COL_S_MSTORE], not_op1_imm, aux0_diff]);
+    yield_constr.constraint(builder, cs);
+    // when op1_imm is 1, addr = anchor + offset => aux1 = op0 + op1
+    let addr = builder.add_extension(lv[COL_OP0], lv[COL_OP1]);
+    let addr_diff = builder.sub_extension(lv[COL_AUX1], addr);
+    let cs = builder.mul_many_extension([lv[COL_S_MSTORE], lv[COL_OP1_IMM], addr_diff]);
+    yield_constr.constraint(builder, cs);
+    // when op1_imm is 0, addr = anchor + offset_factor * offset => aux1 = op0 +
+    // aux0 * op1
+    let addr = builder.mul_add_extension(lv[COL_AUX0], lv[COL_OP1], lv[COL_OP0]);
+    let addr_diff = builder.sub_extension(lv[COL_AUX1], addr);
+    let cs = builder.mul_many_extension([lv[COL_S_MSTORE], not_op1_imm, addr_diff]);
+    yield_constr.constraint(builder, cs);
 }
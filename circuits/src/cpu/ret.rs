@@ -26,7 +26,6 @@ pub(crate) fn eval_packed_generic<P: PackedField>(
     yield_constr.constraint_transition(lv[COL_S_RET] * (nv[COL_REGS.end - 1] - lv[COL_AUX1]));
 }
 
-#[allow(dead_code)]
 pub(crate) fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
     lv: &[ExtensionTarget<D>; NUM_CPU_COLS],
@@ -48,4 +47,9 @@ pub(crate) fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
     let cs = builder.mul_extension(lv[COL_S_RET], no_s_css);
 
     yield_constr.constraint(builder, cs);
+
+    // fp
+    let fp_diff = builder.sub_extension(nv[COL_REGS.end - 1], lv[COL_AUX1]);
+    let cs = builder.mul_extension(lv[COL_S_RET], fp_diff);
+    yield_constr.constraint_transition(builder, cs);
 }
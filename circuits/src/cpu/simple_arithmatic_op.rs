@@ -1,8 +1,13 @@
 use core::vm::opcodes::OlaOpcode;
 
 use super::columns::*;
-use crate::stark::constraint_consumer::ConstraintConsumer;
-use plonky2::field::{extension::FieldExtension, packed::PackedField};
+use crate::stark::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
 
 pub(crate) fn eval_packed_generic<F, FE, P, const D2: usize>(
     lv: &[P; NUM_CPU_COLS],
@@ -51,3 +56,55 @@ pub(crate) fn eval_packed_generic<F, FE, P, const D2: usize>(
 
     yield_constr.constraint(is_assert * (P::ONES - lv[COL_OP1]));
 }
+
+pub(crate) fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    lv: &[ExtensionTarget<D>; NUM_CPU_COLS],
+    _nv: &[ExtensionTarget<D>; NUM_CPU_COLS],
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let one = builder.one_extension();
+    let opcode_sub = |builder: &mut CircuitBuilder<F, D>, op: OlaOpcode| {
+        let op = builder.constant_extension(F::Extension::from_canonical_u64(op.binary_bit_mask()));
+        builder.sub_extension(lv[COL_OPCODE], op)
+    };
+    let sub_add = opcode_sub(builder, OlaOpcode::ADD);
+    let sub_mul = opcode_sub(builder, OlaOpcode::MUL);
+    let sub_eq = opcode_sub(builder, OlaOpcode::EQ);
+    let sub_neq = opcode_sub(builder, OlaOpcode::NEQ);
+    let sub_assert = opcode_sub(builder, OlaOpcode::ASSERT);
+    let s = lv[COL_S_SIMPLE_ARITHMATIC_OP];
+    let is_add = builder.mul_many_extension([s, sub_mul, sub_eq, sub_neq, sub_assert]);
+    let is_mul = builder.mul_many_extension([s, sub_add, sub_eq, sub_neq, sub_assert]);
+    let is_eq = builder.mul_many_extension([s, sub_add, sub_mul, sub_neq, sub_assert]);
+    let is_neq = builder.mul_many_extension([s, sub_add, sub_mul, sub_eq, sub_assert]);
+    let is_assert = builder.mul_many_extension([s, sub_add, sub_mul, sub_eq, sub_neq]);
+
+    let sum = builder.add_extension(lv[COL_OP0], lv[COL_OP1]);
+    let add_diff = builder.sub_extension(lv[COL_DST], sum);
+    let cs = builder.mul_extension(is_add, add_diff);
+    yield_constr.constraint(builder, cs);
+    let product = builder.mul_extension(lv[COL_OP0], lv[COL_OP1]);
+    let mul_diff = builder.sub_extension(lv[COL_DST], product);
+    let cs = builder.mul_extension(is_mul, mul_diff);
+    yield_constr.constraint(builder, cs);
+
+    // eq and neq
+    let op_diff = builder.sub_extension(lv[COL_OP0], lv[COL_OP1]);
+    let diff_aux = builder.mul_extension(op_diff, lv[COL_AUX0]);
+    let res = lv[COL_DST];
+    let not_res = builder.sub_extension(one, res);
+    let not_diff_aux = builder.sub_extension(one, diff_aux);
+    let eq_cs = builder.mul_extension(not_res, not_diff_aux);
+    let eq_cs = builder.mul_add_extension(res, op_diff, eq_cs);
+    let eq_cs = builder.mul_extension(is_eq, eq_cs);
+    let neq_cs = builder.mul_extension(res, not_diff_aux);
+    let neq_cs = builder.mul_add_extension(not_res, op_diff, neq_cs);
+    let neq_cs = builder.mul_extension(is_neq, neq_cs);
+    let cs = builder.add_extension(eq_cs, neq_cs);
+    yield_constr.constraint(builder, cs);
+
+    let not_op1 = builder.sub_extension(one, lv[COL_OP1]);
+    let cs = builder.mul_extension(is_assert, not_op1);
+    yield_constr.constraint(builder, cs);
+}
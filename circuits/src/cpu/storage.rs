@@ -1,6 +1,10 @@
+use plonky2::field::extension::Extendable;
 use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
 
-use crate::stark::constraint_consumer::ConstraintConsumer;
+use crate::stark::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 
 use super::columns::*;
 
@@ -67,3 +71,50 @@ pub(crate) fn eval_packed_generic<P: PackedField>(
         lv_is_storage_op * (P::ONES - lv[COL_IS_EXT_LINE]) * lv[COL_IS_STORAGE_EXT_LINE],
     )
 }
+
+pub(crate) fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    lv: &[ExtensionTarget<D>; NUM_CPU_COLS],
+    nv: &[ExtensionTarget<D>; NUM_CPU_COLS],
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let one = builder.one_extension();
+    let lv_is_storage_op = builder.add_extension(lv[COL_S_SSTORE], lv[COL_S_SLOAD]);
+    // st_access_idx: start from 0, increase 1 when meet sstore or sload
+    let cs = builder.sub_extension(lv[COL_IDX_STORAGE], lv_is_storage_op);
+    yield_constr.constraint_first_row(builder, cs);
+    let idx_diff = builder.sub_extension(nv[COL_IDX_STORAGE], lv[COL_IDX_STORAGE]);
+    let cs = builder.sub_extension(idx_diff, nv[COL_IS_STORAGE_EXT_LINE]);
+    yield_constr.constraint_transition(builder, cs);
+    // op0, op1 same as main line
+    let not_ext_line = builder.sub_extension(one, lv[COL_IS_EXT_LINE]);
+    let is_main_line = builder.mul_extension(lv_is_storage_op, not_ext_line);
+    for col in [COL_OP0, COL_OP1] {
+        let diff = builder.sub_extension(nv[col], lv[col]);
+        let cs = builder.mul_extension(is_main_line, diff);
+        yield_constr.constraint(builder, cs);
+    }
+    // in ext line, op0_sel[0~3] is mem addr which stores storageKey and
+    // op1_sel[0~3] is mem addr which stores value
+    let is_ext_line = builder.mul_extension(lv_is_storage_op, lv[COL_IS_EXT_LINE]);
+    for (sel_start, col) in [(COL_S_OP0.start, COL_OP0), (COL_S_OP1.start, COL_OP1)] {
+        let diff = builder.sub_extension(lv[sel_start], lv[col]);
+        let cs = builder.mul_extension(is_ext_line, diff);
+        yield_constr.constraint(builder, cs);
+        for i in 1..4 {
+            let diff = builder.sub_extension(lv[sel_start + i], lv[sel_start + i - 1]);
+            let diff = builder.sub_extension(diff, one);
+            let cs = builder.mul_extension(is_ext_line, diff);
+            yield_constr.constraint(builder, cs);
+        }
+    }
+    // is_storage_ext constraints:
+    let not_storage_ext_line = builder.sub_extension(one, lv[COL_IS_STORAGE_EXT_LINE]);
+    let cs = builder.mul_extension(is_ext_line, not_storage_ext_line);
+    yield_constr.constraint(builder, cs);
+    let not_storage_op = builder.sub_extension(one, lv_is_storage_op);
+    let cs = builder.mul_extension(not_storage_op, lv[COL_IS_STORAGE_EXT_LINE]);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_extension(is_main_line, lv[COL_IS_STORAGE_EXT_LINE]);
+    yield_constr.constraint(builder, cs);
+}
@@ -1,15 +1,20 @@
 use core::types::Field;
 
-use plonky2::field::{extension::FieldExtension, packed::PackedField};
+use plonky2::field::{
+    extension::{Extendable, FieldExtension},
+    packed::PackedField,
+};
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
 
-use crate::stark::constraint_consumer::ConstraintConsumer;
+use crate::stark::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
 
 use super::{
     columns::{
         COL_AUX0, COL_DST, COL_FILTER_TAPE_LOOKING, COL_IS_EXT_LINE, COL_OP0, COL_OP1,
         COL_S_CALL_SC, COL_S_OP0, COL_S_TLOAD, COL_S_TSTORE, COL_TP,
     },
-    cpu_stark::CpuAdjacentRowWrapper,
+    cpu_stark::{CpuAdjacentRowWrapper, CpuAdjacentRowWrapperTarget},
 };
 
 pub(crate) fn eval_packed_generic<F, FE, P, const D: usize, const D2: usize>(
@@ -146,3 +151,114 @@ pub(crate) fn eval_packed_generic<F, FE, P, const D: usize, const D2: usize>(
             * (P::ONES - wrapper.lv[COL_FILTER_TAPE_LOOKING]),
     );
 }
+
+pub(crate) fn eval_ext_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    wrapper: &CpuAdjacentRowWrapperTarget<D>,
+    yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+) {
+    let lv = wrapper.lv;
+    let nv = wrapper.nv;
+    let one = builder.one_extension();
+    let lv_not_ext_line = builder.sub_extension(one, lv[COL_IS_EXT_LINE]);
+    let nv_not_ext_line = builder.sub_extension(one, nv[COL_IS_EXT_LINE]);
+    let lv_is_tape_op = builder.add_extension(lv[COL_S_TSTORE], lv[COL_S_TLOAD]);
+    let nv_is_tape_op = builder.add_extension(nv[COL_S_TSTORE], nv[COL_S_TLOAD]);
+    let tape_op_ext_lines =
+        builder.mul_many_extension([lv_is_tape_op, lv[COL_IS_EXT_LINE], nv[COL_IS_EXT_LINE]]);
+    let tstore_main = builder.mul_extension(lv[COL_S_TSTORE], lv_not_ext_line);
+    let tload_main = builder.mul_extension(lv[COL_S_TLOAD], lv_not_ext_line);
+    let tstore_last_ext = builder.mul_extension(lv[COL_S_TSTORE], nv_not_ext_line);
+    let tp_diff = builder.sub_extension(nv[COL_TP], lv[COL_TP]);
+    let nv_tp_sub_addr = builder.sub_extension(nv[COL_TP], lv[COL_S_OP0.start]);
+    let nv_tp_sub_addr = builder.sub_extension(nv_tp_sub_addr, one);
+    let addr_diff = builder.sub_extension(nv[COL_S_OP0.start], lv[COL_S_OP0.start]);
+    let addr_diff_sub_one = builder.sub_extension(addr_diff, one);
+
+    // for tload and tstore:
+    // op0 and op1 not change in ext lines
+    let nv_tape_op_ext_line = builder.mul_extension(nv_is_tape_op, nv[COL_IS_EXT_LINE]);
+    for col in [COL_OP0, COL_OP1] {
+        let diff = builder.sub_extension(nv[col], lv[col]);
+        let cs = builder.mul_extension(nv_tape_op_ext_line, diff);
+        yield_constr.constraint(builder, cs);
+    }
+    // aux0 is addr in ext lines, and increase by one
+    let aux0_diff = builder.sub_extension(nv[COL_AUX0], lv[COL_AUX0]);
+    let aux0_diff = builder.sub_extension(aux0_diff, one);
+    let cs = builder.mul_extension(tape_op_ext_lines, aux0_diff);
+    yield_constr.constraint(builder, cs);
+
+    // COL_S_OP0[0] is tape-addr in ext lines, and increase by one
+    // in ext lines
+    let diff = builder.sub_extension(lv[COL_TP], nv[COL_S_OP0.start]);
+    let cs = builder.mul_extension(tstore_main, diff);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_many_extension([
+        lv[COL_S_TSTORE],
+        lv[COL_IS_EXT_LINE],
+        nv[COL_IS_EXT_LINE],
+        addr_diff_sub_one,
+    ]);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_extension(tstore_last_ext, nv_tp_sub_addr);
+    yield_constr.constraint(builder, cs);
+    // main and first ext for tload (flag == 1)
+    let diff = builder.add_extension(nv[COL_S_OP0.start], lv[COL_OP1]);
+    let diff = builder.sub_extension(diff, lv[COL_TP]);
+    let cs = builder.mul_many_extension([tload_main, lv[COL_OP0], diff]);
+    yield_constr.constraint(builder, cs);
+    // main and first ext for tload (flag == 0)
+    let not_flag = builder.sub_extension(one, lv[COL_OP0]);
+    let diff = builder.sub_extension(nv[COL_S_OP0.start], lv[COL_OP1]);
+    let cs = builder.mul_many_extension([tload_main, not_flag, diff]);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_extension(tape_op_ext_lines, addr_diff_sub_one);
+    yield_constr.constraint(builder, cs);
+
+    // for tstore, main op0 equals first ext line's aux0
+    let diff = builder.sub_extension(lv[COL_OP0], nv[COL_AUX0]);
+    let cs = builder.mul_extension(tstore_main, diff);
+    yield_constr.constraint(builder, cs);
+    // for tload, main dst equals first ext line's aux0
+    let diff = builder.sub_extension(lv[COL_DST], nv[COL_AUX0]);
+    let cs = builder.mul_extension(tload_main, diff);
+    yield_constr.constraint(builder, cs);
+
+    // tp only changes when tstore last ext line and sccall next line
+    // not tstore and sccall, tp not change
+    let not_tp_change = builder.sub_extension(one, lv[COL_S_TSTORE]);
+    let not_tp_change = builder.sub_extension(not_tp_change, nv[COL_S_CALL_SC]);
+    let cs = builder.mul_many_extension([wrapper.is_in_same_tx, not_tp_change, tp_diff]);
+    yield_constr.constraint(builder, cs);
+    // for tstore, main only last ext line tp change
+    let cs = builder.mul_many_extension([lv[COL_S_TSTORE], nv[COL_IS_EXT_LINE], tp_diff]);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_extension(tstore_last_ext, nv_tp_sub_addr);
+    yield_constr.constraint(builder, cs);
+    // for sccall, main tp equals ext line's tp; ext line next tp += 12;
+    let lv_not_sccall = builder.sub_extension(one, lv[COL_S_CALL_SC]);
+    let cs = builder.mul_many_extension([lv_not_sccall, nv[COL_S_CALL_SC], tp_diff]);
+    yield_constr.constraint(builder, cs);
+    let cs = builder.mul_many_extension([lv[COL_S_CALL_SC], lv_not_ext_line, tp_diff]);
+    yield_constr.constraint(builder, cs);
+    let tp_diff_sub_12 = builder.add_const_extension(tp_diff, -F::from_canonical_u64(12));
+    let cs = builder.mul_many_extension([lv[COL_S_CALL_SC], lv[COL_IS_EXT_LINE], tp_diff_sub_12]);
+    yield_constr.constraint(builder, cs);
+
+    // filter for tload and tstore: tstore, tload ext lines
+    let filter = lv[COL_FILTER_TAPE_LOOKING];
+    let not_filter = builder.sub_extension(one, filter);
+    let cs = builder.mul_extension(filter, not_filter);
+    yield_constr.constraint(builder, cs);
+    // non tstore, tload, sccall should be 0
+    let not_tape_op = builder.sub_extension(one, lv_is_tape_op);
+    let cs = builder.mul_extension(filter, not_tape_op);
+    yield_constr.constraint(builder, cs);
+    // non ext line should be 0
+    let cs = builder.mul_extension(filter, lv_not_ext_line);
+    yield_constr.constraint(builder, cs);
+    // tstore/tload ext line should be 1
+    let cs = builder.mul_many_extension([lv_is_tape_op, lv[COL_IS_EXT_LINE], not_filter]);
+    yield_constr.constraint(builder, cs);
+}
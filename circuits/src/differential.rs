@@ -0,0 +1,542 @@
+//! Differential testing of the legacy `Process` executor against the binary
+//! `TxExeManager` executor.
+//!
+//! A program is run through both executors with the same tape, the binary
+//! trace is brought to the legacy `Trace` layout with `pre_process`, and the
+//! two are compared row by row: cpu steps, memory accesses, tape, storage
+//! writes and events. The binary trace is then proved and verified, so every
+//! `OlaStark` constraint is checked on it. Programs running interpreted
+//! prophets are only proved: the legacy executor runs native prophets only.
+
+use core::merkle_tree::log::StorageLogKind;
+use core::merkle_tree::tree::AccountTree;
+use core::program::binary_program::BinaryProgram;
+use core::program::Program;
+use core::state::state_storage::StateStorage;
+use core::trace::exe_trace::TxExeTrace;
+use core::trace::trace::{Step, Trace};
+use core::types::merkle_tree::encode_addr;
+use core::vm::hardware::ContractAddress;
+use core::vm::opcodes::OlaOpcode;
+use core::vm::transaction::{init_tx_context_mock, TxCtxInfo};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use executor::batch_exe_manager::BlockExeInfo;
+use executor::config::ExecuteMode;
+use executor::load_tx::init_tape;
+use executor::ola_storage::{DiskStorageWriter, OlaCachedStorage};
+use executor::tx_exe_manager::{OlaTapeInitInfo, TxExeManager};
+use executor::{BatchCacheManager, Process};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::{Field, PrimeField64};
+
+use crate::generation::pre_process::pre_process;
+use crate::test_utils::prove_and_verify_tx_traces;
+
+/// Address both executors run the program at, as storage and code address.
+pub const DIFF_CONTRACT_ADDRESS: ContractAddress = [13, 14, 15, 16];
+
+static NEXT_DB_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// What one executor produced for a program, in the legacy trace layout.
+#[derive(Debug, Clone)]
+pub struct ExecutionOutput {
+    pub trace: Trace,
+    /// Storage writes in execution order, as (contract, storage key, value).
+    pub storage_writes: Vec<(ContractAddress, [u64; 4], [u64; 4])>,
+}
+
+/// The first row the two executors disagree on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub table: &'static str,
+    /// Clock of the mismatching row, `None` for rows written before the
+    /// first step, like the tape initialisation.
+    pub clk: Option<u64>,
+    /// Instruction executed at `clk`.
+    pub instruction: Option<String>,
+    pub legacy: String,
+    pub binary: String,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} diverges", self.table)?;
+        if let Some(clk) = self.clk {
+            write!(f, " at clk {}", clk)?;
+        }
+        if let Some(instruction) = &self.instruction {
+            write!(f, " ({})", instruction)?;
+        }
+        write!(f, ": legacy {}, binary {}", self.legacy, self.binary)
+    }
+}
+
+/// What `diff_program` compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffOutcome {
+    /// Both executors produced the same trace.
+    Same,
+    /// The legacy executor cannot run the program the way the binary one
+    /// did, for the given reason. Only the binary trace was checked.
+    Skipped(String),
+}
+
+/// Runs `program` on both executors, compares their traces and checks the
+/// binary trace against the circuits.
+pub fn diff_program(program: &BinaryProgram, calldata: &[u64]) -> anyhow::Result<DiffOutcome> {
+    let (binary, tx) = run_binary(program, calldata)?;
+    let unsupported = legacy_unsupported(program, &tx);
+    if unsupported.is_none() {
+        let legacy = run_legacy(program, calldata)?;
+        compare_outputs(&legacy, &binary).map_err(|d| anyhow::anyhow!("{}", d))?;
+    }
+    prove_and_verify_tx_traces(vec![tx])?;
+    Ok(unsupported.map_or(DiffOutcome::Same, DiffOutcome::Skipped))
+}
+
+/// Why the legacy `Process` cannot replay `tx`, if it cannot. It only runs
+/// native prophets, `Process::prophet` leaves the outputs of the others
+/// unwritten.
+fn legacy_unsupported(program: &BinaryProgram, tx: &TxExeTrace) -> Option<String> {
+    let hosts: HashSet<u64> = program
+        .prophets
+        .iter()
+        .filter(|p| p.native.is_none())
+        .map(|p| p.host as u64)
+        .collect();
+    tx.cpu
+        .iter()
+        .flat_map(|(_, _, _, pieces)| pieces)
+        .find(|p| !p.is_ext_line && hosts.contains(&p.pc))
+        .map(|p| format!("runs the interpreted prophet at pc {}", p.pc))
+}
+
+pub fn run_legacy(program: &BinaryProgram, calldata: &[u64]) -> anyhow::Result<ExecutionOutput> {
+    let ctx = init_tx_context_mock();
+    let address = fe_4(DIFF_CONTRACT_ADDRESS);
+    let instructions: Vec<String> = program.bytecode.split('\n').map(String::from).collect();
+    let code = program
+        .bytecode_u64s()?
+        .into_iter()
+        .map(GoldilocksField::from_canonical_u64)
+        .collect();
+
+    let mut legacy_program = Program {
+        instructions,
+        trace: Default::default(),
+        debug_info: program.debug_info.clone(),
+        prophets: HashMap::new(),
+        pre_exe_flag: false,
+        print_flag: false,
+    };
+    for item in &program.prophets {
+        legacy_program
+            .prophets
+            .insert(item.host as u64, item.clone());
+    }
+    legacy_program
+        .trace
+        .addr_program_hash
+        .insert(encode_addr(&address), code);
+
+    let mut process = Process::new();
+    process.block_timestamp = ctx.block_timestamp.to_canonical_u64();
+    process.tp = GoldilocksField::ZERO;
+    init_tape(
+        &mut process,
+        calldata.iter().map(|v| fe(*v)).collect(),
+        ctx.caller_address,
+        address,
+        address,
+        &ctx,
+    );
+    process.addr_code = address;
+    process.addr_storage = address;
+    process
+        .execute(
+            &mut legacy_program,
+            &StateStorage::new_test(),
+            &mut BatchCacheManager::default(),
+        )
+        .map_err(|e| anyhow::anyhow!("legacy executor failed: {:?}", e))?;
+
+    let storage_writes = process
+        .storage_queries
+        .iter()
+        .filter(|q| q.kind != StorageLogKind::Read)
+        .map(|q| {
+            (
+                to_u64_4(q.contract_addr),
+                to_u64_4(q.storage_key),
+                to_u64_4(q.value),
+            )
+        })
+        .collect();
+    Ok(ExecutionOutput {
+        trace: legacy_program.trace,
+        storage_writes,
+    })
+}
+
+/// Runs `program` on the binary executor, from a throwaway database it is
+/// deployed to. The raw transaction trace is returned next to the converted
+/// one, to be proved.
+pub fn run_binary(
+    program: &BinaryProgram,
+    calldata: &[u64],
+) -> anyhow::Result<(ExecutionOutput, TxExeTrace)> {
+    let db_path = std::env::temp_dir().join(format!(
+        "ola_diff_{}_{}",
+        std::process::id(),
+        NEXT_DB_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = run_binary_in(db_path.clone(), program, calldata);
+    let _ = std::fs::remove_dir_all(db_path);
+    let tx = result?;
+
+    let storage_writes = tx
+        .storage
+        .iter()
+        .filter(|s| s.is_write)
+        .map(|s| (s.contract_addr, s.storage_key, s.value))
+        .collect();
+    let trace = pre_process(vec![tx.clone()], &mut AccountTree::new_test())
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("no trace for the transaction"))?;
+    Ok((
+        ExecutionOutput {
+            trace,
+            storage_writes,
+        },
+        tx,
+    ))
+}
+
+fn run_binary_in(
+    db_path: PathBuf,
+    program: &BinaryProgram,
+    calldata: &[u64],
+) -> anyhow::Result<TxExeTrace> {
+    let db_path = db_path.to_string_lossy().into_owned();
+    DiskStorageWriter::new(db_path.clone())?
+        .save_program(program.clone(), DIFF_CONTRACT_ADDRESS)?;
+
+    let ctx = init_tx_context_mock();
+    let mut storage = OlaCachedStorage::new(db_path, Some(ctx.block_timestamp.0))?;
+    let (block_info, tx) = tape_init_info(&ctx, calldata);
+    let mut tx_exe_manager = TxExeManager::new(
+        ExecuteMode::Invoke,
+        block_info,
+        tx,
+        &mut storage,
+        DIFF_CONTRACT_ADDRESS,
+        0,
+    );
+    Ok(tx_exe_manager.invoke()?.trace)
+}
+
+/// The binary executor's view of the tape `init_tape` writes for `ctx`.
+fn tape_init_info(ctx: &TxCtxInfo, calldata: &[u64]) -> (BlockExeInfo, OlaTapeInitInfo) {
+    let block_info = BlockExeInfo {
+        block_number: ctx.block_number.to_canonical_u64(),
+        block_timestamp: ctx.block_timestamp.to_canonical_u64(),
+        sequencer_address: to_u64_4(ctx.sequencer_address),
+        chain_id: ctx.chain_id.to_canonical_u64(),
+    };
+    let tx = OlaTapeInitInfo {
+        version: ctx.version.to_canonical_u64(),
+        origin_address: to_u64_4(ctx.caller_address),
+        calldata: calldata.to_vec(),
+        nonce: Some(ctx.nonce.to_canonical_u64()),
+        signature_r: Some(to_u64_4(ctx.signature_r)),
+        signature_s: Some(to_u64_4(ctx.signature_s)),
+        tx_hash: Some(to_u64_4(ctx.tx_hash)),
+        gas_limit: None,
+    };
+    (block_info, tx)
+}
+
+/// Compares the two outputs table by table and returns the first mismatch.
+pub fn compare_outputs(
+    legacy: &ExecutionOutput,
+    binary: &ExecutionOutput,
+) -> Result<(), Divergence> {
+    let (l, b) = (&legacy.trace, &binary.trace);
+    let diverge = |table, clk: Option<u64>, legacy, binary| Divergence {
+        table,
+        clk,
+        instruction: clk.and_then(|c| instruction_at(l, c).or_else(|| instruction_at(b, c))),
+        legacy,
+        binary,
+    };
+
+    let cpu_row = |s: &Step| (s.clk, s.pc, s.instruction, s.immediate_data, s.tp, s.regs);
+    if let Some((i, legacy, binary)) =
+        first_mismatch(l.exec.iter().map(cpu_row), b.exec.iter().map(cpu_row))
+    {
+        let clk = l.exec.get(i).or(b.exec.get(i)).map(|s| s.clk as u64);
+        return Err(diverge("cpu", clk, legacy, binary));
+    }
+
+    let mem_rows = |t: &Trace| {
+        let mut rows: Vec<_> = t
+            .memory
+            .iter()
+            .filter(|m| m.filter_looked_for_main.is_one())
+            .map(|m| (m.env_idx.0, m.clk.0, m.addr.0, m.is_write.0, m.value.0))
+            .collect();
+        rows.sort();
+        rows
+    };
+    let (l_mem, b_mem) = (mem_rows(l), mem_rows(b));
+    if let Some((i, legacy, binary)) = first_mismatch(l_mem.iter(), b_mem.iter()) {
+        let clk = l_mem.get(i).or(b_mem.get(i)).map(|m| m.1);
+        return Err(diverge("memory", clk, legacy, binary));
+    }
+
+    let tape_rows = |t: &Trace| {
+        t.tape
+            .iter()
+            .map(|r| (r.is_init, r.addr.0, r.value.0, r.opcode.0))
+            .collect::<Vec<_>>()
+    };
+    if let Some((i, legacy, binary)) =
+        first_mismatch(tape_rows(l).into_iter(), tape_rows(b).into_iter())
+    {
+        // tape rows carry no clk, the ones written during execution follow
+        // the steps looking into the tape.
+        let is_init = l.tape.get(i).or(b.tape.get(i)).map_or(false, |r| r.is_init);
+        let clk = if is_init {
+            None
+        } else {
+            let n = l.tape.iter().take(i).filter(|r| !r.is_init).count();
+            nth_clk(l, n, |s| s.filter_tape_looking.is_one())
+        };
+        return Err(diverge("tape", clk, legacy, binary));
+    }
+
+    if let Some((i, legacy, binary)) =
+        first_mismatch(legacy.storage_writes.iter(), binary.storage_writes.iter())
+    {
+        let sstore = fe(OlaOpcode::SSTORE.binary_bit_mask());
+        let clk = nth_clk(l, i, |s| s.opcode == sstore && s.is_ext_line.is_zero());
+        return Err(diverge("storage", clk, legacy, binary));
+    }
+
+    let event_rows = |t: &Trace| {
+        t.event
+            .iter()
+            .map(|e| (e.clk.0, e.topics.clone(), e.data.clone()))
+            .collect::<Vec<_>>()
+    };
+    let (l_events, b_events) = (event_rows(l), event_rows(b));
+    if let Some((i, legacy, binary)) = first_mismatch(l_events.iter(), b_events.iter()) {
+        let clk = l_events.get(i).or(b_events.get(i)).map(|e| e.0);
+        return Err(diverge("event", clk, legacy, binary));
+    }
+    Ok(())
+}
+
+/// Index and debug form of the first pair of rows that differ, a missing row
+/// counts as a difference.
+fn first_mismatch<T: PartialEq + fmt::Debug>(
+    mut legacy: impl Iterator<Item = T>,
+    mut binary: impl Iterator<Item = T>,
+) -> Option<(usize, String, String)> {
+    let show = |row: Option<T>| row.map_or("no row".to_string(), |r| format!("{:?}", r));
+    let mut i = 0;
+    loop {
+        match (legacy.next(), binary.next()) {
+            (None, None) => return None,
+            (Some(l), Some(b)) if l == b => i += 1,
+            (l, b) => return Some((i, show(l), show(b))),
+        }
+    }
+}
+
+/// Clock of the `n`-th step matching `filter`.
+fn nth_clk(trace: &Trace, n: usize, filter: impl Fn(&Step) -> bool) -> Option<u64> {
+    trace
+        .exec
+        .iter()
+        .filter(|s| filter(s))
+        .nth(n)
+        .map(|s| s.clk as u64)
+}
+
+/// The instruction executed at `clk`, as asm when the trace has the decoded
+/// text, else as its encoding.
+fn instruction_at(trace: &Trace, clk: u64) -> Option<String> {
+    let step = trace
+        .exec
+        .iter()
+        .find(|s| s.clk as u64 == clk && s.is_ext_line.is_zero())?;
+    Some(match trace.instructions.get(&step.pc) {
+        Some((asm, ..)) => format!("pc {}: {}", step.pc, asm),
+        None => format!("pc {}: {:#x}", step.pc, step.instruction.0),
+    })
+}
+
+fn fe(v: u64) -> GoldilocksField {
+    GoldilocksField::from_canonical_u64(v)
+}
+
+fn fe_4(v: [u64; 4]) -> [GoldilocksField; 4] {
+    v.map(fe)
+}
+
+fn to_u64_4(v: [GoldilocksField; 4]) -> [u64; 4] {
+    v.map(|f| f.to_canonical_u64())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assembler::encoder::encode_asm_from_json_file;
+    use core::program::random_program::random_executable_program;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn asm_dir() -> PathBuf {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("../assembler/test_data/asm/");
+        path
+    }
+
+    /// Programs of the asm test data the differential test cannot run, with
+    /// the reason.
+    const UNRUNNABLE: [(&str, &str); 1] = [(
+        "hand_write_prophet.json",
+        "declares its prophet inputs as bare names, which the assembler no longer reads",
+    )];
+
+    /// Programs whose prophets are interpreted, the legacy executor cannot
+    /// replay them.
+    const INTERPRETED_PROPHETS: [&str; 4] = [
+        "malloc.json",
+        "printf.json",
+        "prophet_sqrt.json",
+        "sqrt_prophet_asm.json",
+    ];
+
+    /// Tape the program is run with: the arguments, their length and the
+    /// function selector for contracts, nothing for programs without an entry
+    /// point reading the calldata.
+    fn calldata_of(file_name: &str) -> Vec<u64> {
+        match file_name {
+            "books.json" => vec![1, 3, 111, 108, 97, 5, 2541629191],
+            "books_asm.json" => vec![1, 3, 111, 108, 97, 5, 120553111],
+            "ecdsa_asm.json" => vec![0, 370402988],
+            "erc20_asm.json" => vec![0, 404098525],
+            "fib_asm.json" => vec![10, 1, 2, 4185064725],
+            "fibo_loop.json" => vec![10, 1, 2, 1015130275],
+            "global.json" => vec![0, 4171824493],
+            "hash_asm.json" => vec![0, 2051797338],
+            "poseidon_hash.json" => vec![0, 1239976900],
+            "printf.json" => vec![5, 111, 108, 97, 118, 109, 11, 12, 8, 3238128773],
+            "ptr_call.json" => vec![0, 2657046596],
+            "sc_input.json" => vec![10, 20, 2, 253268590],
+            "sqrt_prophet_asm.json" => vec![144, 10, 2, 3509365327],
+            "storage_u256_asm.json" => vec![0, 4047015750],
+            "storage_u32.json" => vec![0, 2364819430],
+            "u256_basic_asm.json" => vec![0, 2590488802],
+            "vote.json" => vec![3, 1, 2, 3, 4, 2817135588],
+            "vote_simple_asm.json" => vec![7, 1, 2, 3, 4, 5, 6, 7, 8, 3826510503],
+            "bitwise.json"
+            | "call.json"
+            | "comparison.json"
+            | "context_fetch.json"
+            | "fibo_recursive.json"
+            | "malloc.json"
+            | "mem_gep.json"
+            | "mem_gep_vector.json"
+            | "memory.json"
+            | "poseidon.json"
+            | "prophet_sqrt.json"
+            | "range_check.json"
+            | "storage.json"
+            | "storage_multi_keys.json"
+            | "string_assert.json"
+            | "tape.json" => {
+                vec![]
+            }
+            _ => panic!("no calldata for {}, add it to calldata_of", file_name),
+        }
+    }
+
+    #[test]
+    fn test_diff_asm_test_data() {
+        let mut failures = Vec::new();
+        let mut skipped = Vec::new();
+        let mut entries: Vec<_> = std::fs::read_dir(asm_dir())
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .filter(|p| p.extension().map_or(false, |e| e == "json"))
+            .collect();
+        entries.sort();
+        for path in entries {
+            let file_name = path.file_name().unwrap().to_string_lossy().into_owned();
+            let encoded = encode_asm_from_json_file(path.display().to_string());
+            if let Some((_, reason)) = UNRUNNABLE.iter().find(|(name, _)| *name == file_name) {
+                assert!(encoded.is_err(), "{} encodes now, run it", file_name);
+                skipped.push(format!("{}: {}", file_name, reason));
+                continue;
+            }
+            match diff_program(&encoded.unwrap(), &calldata_of(&file_name)) {
+                Ok(DiffOutcome::Same) => {
+                    assert!(
+                        !INTERPRETED_PROPHETS.contains(&file_name.as_str()),
+                        "{} ran no interpreted prophet",
+                        file_name
+                    );
+                }
+                Ok(DiffOutcome::Skipped(reason)) => {
+                    skipped.push(format!("{}: {}", file_name, reason));
+                }
+                Err(e) => failures.push(format!("{}: {}", file_name, e)),
+            }
+        }
+        eprintln!(
+            "not compared with the legacy executor:\n{}",
+            skipped.join("\n")
+        );
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_diff_random_programs() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut failures = Vec::new();
+        for i in 0..4 {
+            let program = random_executable_program(&mut rng, 64);
+            match diff_program(&program, &[]) {
+                Ok(DiffOutcome::Same) => {}
+                Ok(DiffOutcome::Skipped(reason)) => {
+                    failures.push(format!("program {}: {}", i, reason))
+                }
+                Err(e) => failures.push(format!("program {}: {}\n{}", i, e, program.bytecode)),
+            }
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn test_divergence_reports_first_clk() {
+        let mut path = asm_dir();
+        path.push("fibo_recursive.json");
+        let program = encode_asm_from_json_file(path.display().to_string()).unwrap();
+        let legacy = run_legacy(&program, &calldata_of("fibo_recursive.json")).unwrap();
+        assert_eq!(compare_outputs(&legacy, &legacy), Ok(()));
+
+        let mut binary = legacy.clone();
+        binary.trace.exec[5].regs[0] += GoldilocksField::ONE;
+        let divergence = compare_outputs(&legacy, &binary).unwrap_err();
+        assert_eq!(divergence.table, "cpu");
+        assert_eq!(divergence.clk, Some(legacy.trace.exec[5].clk as u64));
+        assert!(divergence.instruction.is_some());
+    }
+}
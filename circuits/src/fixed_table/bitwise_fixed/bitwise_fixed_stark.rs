@@ -1,60 +1,238 @@
-// use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
-// //use crate::cross_table_lookup::Column;
-// use crate::fixed_table::bitwise_fixed::columns::*;
-// use crate::stark::Stark;
-// use crate::vars::{StarkEvaluationTargets, StarkEvaluationVars};
-// //use itertools::Itertools;
-// use plonky2::field::extension::{Extendable, FieldExtension};
-// use plonky2::field::packed::PackedField;
-// //use plonky2::field::types::Field;
-// use plonky2::hash::hash_types::RichField;
-// //use plonky2::iop::ext_target::ExtensionTarget;
-// use plonky2::plonk::circuit_builder::CircuitBuilder;
-// //use plonky2::plonk::plonk_common::*;
-// use std::marker::PhantomData;
-// //use std::ops::*;
-
-// #[derive(Copy, Clone, Default)]
-// pub struct BitwiseFixedStark<F, const D: usize> {
-//     pub _phantom: PhantomData<F>,
-// }
-
-// impl<F: RichField, const D: usize> BitwiseFixedStark<F, D> {
-//     const BASE: usize = 1 << 8;
-// }
-
-// impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for BitwiseFixedStark<F, D> {
-//     const COLUMNS: usize = COL_NUM;
-
-//     fn eval_packed_generic<FE, P, const D2: usize>(
-//         &self,
-//         _vars: StarkEvaluationVars<FE, P, { COL_NUM }>,
-//         _yield_constr: &mut ConstraintConsumer<P>,
-//     ) where
-//         FE: FieldExtension<D2, BaseField = F>,
-//         P: PackedField<Scalar = FE>,
-//     {
-//     }
-
-//     fn eval_ext_circuit(
-//         &self,
-//         _builder: &mut CircuitBuilder<F, D>,
-//         _vars: StarkEvaluationTargets<D, { COL_NUM }>,
-//         _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
-//     ) {
-//     }
-
-//     fn constraint_degree(&self) -> usize {
-//         1
-//     }
-// }
-
-// // Get the column info for Cross_Lookup<Cpu_table, Bitwise_table>
-// /*pub fn ctl_data_with_bitwise<F: Field>() -> Vec<Column<F>> {
-//     let mut res = Column::singles([TAG, OP0, OP1, RES]).collect_vec();
-//     res
-// }
-
-// pub fn ctl_filter_with_bitwise<F: Field>() -> Column<F> {
-//     Column::one()
-// }*/
+use crate::fixed_table::bitwise_fixed::columns::*;
+use core::program::instruction::Opcode;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::stark::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use crate::stark::cross_table_lookup::Column;
+use crate::stark::stark::Stark;
+use crate::stark::vars::{StarkEvaluationTargets, StarkEvaluationVars};
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::plonk_common::*;
+use std::marker::PhantomData;
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BitwiseFixedStark<F, const D: usize> {
+    pub _phantom: PhantomData<F>,
+}
+
+impl<F: RichField, const D: usize> BitwiseFixedStark<F, D> {
+    const U8_MAX: usize = (1 << 8) - 1;
+
+    fn tags() -> [F; 3] {
+        [Opcode::AND, Opcode::OR, Opcode::XOR].map(|op| F::from_canonical_u64(1 << op as u8))
+    }
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for BitwiseFixedStark<F, D> {
+    const COLUMNS: usize = COL_NUM_BITWISE_FIXED;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: StarkEvaluationVars<FE, P, { COL_NUM_BITWISE_FIXED }>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+
+        // selectors and bits are binary, at most one operation per row.
+        let sels = [lv[SEL_AND], lv[SEL_OR], lv[SEL_XOR]];
+        for sel in sels {
+            yield_constr.constraint(sel * (P::ONES - sel));
+        }
+        let sel_sum = sels[0] + sels[1] + sels[2];
+        yield_constr.constraint(sel_sum * (P::ONES - sel_sum));
+        for i in OP0_BITS.chain(OP1_BITS) {
+            yield_constr.constraint(lv[i] * (P::ONES - lv[i]));
+        }
+
+        // op0 = Sum(op0_bits_i * 2^i), op1 = Sum(op1_bits_i * 2^i)
+        let two = P::Scalar::from_canonical_usize(2);
+        let op0_bits: Vec<_> = lv[OP0_BITS].to_vec();
+        yield_constr.constraint(reduce_with_powers(&op0_bits, two) - lv[OP0]);
+        let op1_bits: Vec<_> = lv[OP1_BITS].to_vec();
+        yield_constr.constraint(reduce_with_powers(&op1_bits, two) - lv[OP1]);
+
+        let tags = Self::tags();
+        let tag = sels
+            .iter()
+            .zip(tags)
+            .map(|(&sel, tag)| sel * FE::from_basefield(tag))
+            .sum::<P>();
+        yield_constr.constraint(lv[TAG] - tag);
+
+        // per bit: and = ab, or = a + b - ab, xor = a + b - 2ab
+        let or_xor = sels[1] + sels[2];
+        let ab_coeff = sels[0] - sels[1] - sels[2] * two;
+        let res_bits: Vec<_> = op0_bits
+            .iter()
+            .zip(op1_bits.iter())
+            .map(|(&a, &b)| or_xor * (a + b) + ab_coeff * a * b)
+            .collect();
+        yield_constr.constraint(reduce_with_powers(&res_bits, two) - lv[RES]);
+
+        // u8 column walks from 0 to 2^8 - 1 in steps of 0 or 1.
+        let u8_diff = nv[RANGE_CHECK_U8] - lv[RANGE_CHECK_U8];
+        yield_constr.constraint_first_row(lv[RANGE_CHECK_U8]);
+        yield_constr.constraint_transition(u8_diff * (u8_diff - P::ONES));
+        yield_constr.constraint_last_row(
+            lv[RANGE_CHECK_U8] - P::Scalar::from_canonical_usize(Self::U8_MAX),
+        );
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { COL_NUM_BITWISE_FIXED }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+        let one = builder.one_extension();
+
+        let sels = [lv[SEL_AND], lv[SEL_OR], lv[SEL_XOR]];
+        for sel in sels {
+            let not_sel = builder.sub_extension(one, sel);
+            let cs = builder.mul_extension(sel, not_sel);
+            yield_constr.constraint(builder, cs);
+        }
+        let sel_sum = builder.add_many_extension(sels);
+        let not_sel_sum = builder.sub_extension(one, sel_sum);
+        let cs = builder.mul_extension(sel_sum, not_sel_sum);
+        yield_constr.constraint(builder, cs);
+        for i in OP0_BITS.chain(OP1_BITS) {
+            let not_bit = builder.sub_extension(one, lv[i]);
+            let cs = builder.mul_extension(lv[i], not_bit);
+            yield_constr.constraint(builder, cs);
+        }
+
+        let two = builder.constant(F::TWO);
+        let op0_bits: Vec<_> = lv[OP0_BITS].to_vec();
+        let computed_sum = reduce_with_powers_ext_circuit(builder, &op0_bits, two);
+        let cs = builder.sub_extension(computed_sum, lv[OP0]);
+        yield_constr.constraint(builder, cs);
+        let op1_bits: Vec<_> = lv[OP1_BITS].to_vec();
+        let computed_sum = reduce_with_powers_ext_circuit(builder, &op1_bits, two);
+        let cs = builder.sub_extension(computed_sum, lv[OP1]);
+        yield_constr.constraint(builder, cs);
+
+        let tags = Self::tags();
+        let mut tag = builder.zero_extension();
+        for (sel, t) in sels.into_iter().zip(tags) {
+            tag = builder.mul_const_add_extension(t, sel, tag);
+        }
+        let cs = builder.sub_extension(lv[TAG], tag);
+        yield_constr.constraint(builder, cs);
+
+        let or_xor = builder.add_extension(sels[1], sels[2]);
+        let and_minus_or = builder.sub_extension(sels[0], sels[1]);
+        let ab_coeff = builder.mul_const_add_extension(-F::TWO, sels[2], and_minus_or);
+        let res_bits: Vec<_> = op0_bits
+            .iter()
+            .zip(op1_bits.iter())
+            .map(|(&a, &b)| {
+                let a_plus_b = builder.add_extension(a, b);
+                let ab = builder.mul_extension(a, b);
+                let ab_term = builder.mul_extension(ab_coeff, ab);
+                builder.mul_add_extension(or_xor, a_plus_b, ab_term)
+            })
+            .collect();
+        let computed_res = reduce_with_powers_ext_circuit(builder, &res_bits, two);
+        let cs = builder.sub_extension(computed_res, lv[RES]);
+        yield_constr.constraint(builder, cs);
+
+        let u8_diff = builder.sub_extension(nv[RANGE_CHECK_U8], lv[RANGE_CHECK_U8]);
+        yield_constr.constraint_first_row(builder, lv[RANGE_CHECK_U8]);
+        let u8_diff_minus_one = builder.sub_extension(u8_diff, one);
+        let cs = builder.mul_extension(u8_diff, u8_diff_minus_one);
+        yield_constr.constraint_transition(builder, cs);
+        let u8_max = builder.constant_extension(F::Extension::from_canonical_usize(Self::U8_MAX));
+        let cs = builder.sub_extension(lv[RANGE_CHECK_U8], u8_max);
+        yield_constr.constraint_last_row(builder, cs);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        3
+    }
+}
+
+// Get the column info for Cross_Lookup<Bitwise_table, Bitwise_fixed_table>
+pub fn ctl_data_with_bitwise<F: Field>() -> Vec<Column<F>> {
+    Column::singles([TAG, OP0, OP1, RES, RANGE_CHECK_U8]).collect_vec()
+}
+
+pub fn ctl_filter_with_bitwise<F: Field>() -> Column<F> {
+    Column::one()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixed_table::bitwise_fixed::bitwise_fixed_stark::BitwiseFixedStark;
+    use crate::generation::fixed_table::generate_bitwise_fixed_trace;
+    use crate::stark::constraint_consumer::ConstraintConsumer;
+    use crate::stark::stark::Stark;
+    use crate::stark::vars::StarkEvaluationVars;
+    use crate::test_utils::test_stark_circuit_constraints;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2_util::log2_strict;
+
+    #[test]
+    fn test_bitwise_fixed_trace() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let stark = BitwiseFixedStark::<F, D>::default();
+
+        let rows = generate_bitwise_fixed_trace::<F>(1 << 18);
+        let len = rows[0].len();
+        let last = F::primitive_root_of_unity(log2_strict(len)).inverse();
+        let subgroup =
+            F::cyclic_subgroup_known_order(F::primitive_root_of_unity(log2_strict(len)), len);
+
+        for i in 0..len {
+            let local_values = rows
+                .iter()
+                .map(|row| row[i])
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            let next_values = rows
+                .iter()
+                .map(|row| row[(i + 1) % len])
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            let vars = StarkEvaluationVars {
+                local_values: &local_values,
+                next_values: &next_values,
+            };
+            let mut constraint_consumer = ConstraintConsumer::new(
+                vec![F::rand()],
+                subgroup[i] - last,
+                if i == 0 { F::ONE } else { F::ZERO },
+                if i == len - 1 { F::ONE } else { F::ZERO },
+            );
+            stark.eval_packed_generic(vars, &mut constraint_consumer);
+            for &acc in &constraint_consumer.constraint_accs {
+                assert_eq!(acc, GoldilocksField::ZERO, "constraint error in line {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bitwise_fixed_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(BitwiseFixedStark::<F, D>::default()).unwrap();
+    }
+}
@@ -1,56 +1,39 @@
-// // 2022-12-26: written by xb
+use std::ops::Range;
 
-// /* Rangecheck_Fixed_Table construction as follows:
-// +-----+-----------+-----------+-----------
-// | TAG |    OP0    |    OP0    |    OP0
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+-----------
-// |  1  | 000000000 | 000000000 | 000000000
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+-----------
-// |  1  | 000000000 | 000000001 | 000000000
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+---------
-// | ... | ......... | ......... | .........
-// +-----+-----------+-----------+---------
-// +-----+-----------+-----------+-----------
-// |  1  | 11111111  | 11111111  | 11111111  // 2^15 + 2^7 - 1
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+-----------
-// |  2  | 000000000 | 000000000 | 000000000 // 2^ 15 + 2^7
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+-----------
-// |  2  | 000000000 | 000000001 | 000000001
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+---------
-// | ... | ......... | ......... | .........
-// +-----+-----------+-----------+---------
-// +-----+-----------+-----------+-----------
-// |  2  | 11111111  | 11111111  | 11111111  // 2 * (2^15 + 2^7) - 1
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+-----------
-// |  3  | 000000000 | 000000000 | 000000000 // 2 * (2^15 + 2^7)
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+-----------
-// |  3  | 000000000 | 000000001 | 000000001
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+---------
-// | ... | ......... | ......... | .........
-// +-----+-----------+-----------+---------
-// +-----+-----------+-----------+-----------
-// |  3  | 11111111  | 11111110  | 00000001
-// +-----+-----------+-----------+-----------
-// +-----+-----------+-----------+-----------
-// |  3  | 11111111  | 11111111  | 00000000  // 3 * (2 ^ 15 + 2^7) - 1
-// +-----+-----------+-----------+-----------
-// */
-// // 1 : AND Operation
-// // 2 : OR  Operation
-// // 3 : XOR Operation
-// pub(crate) const TAG: usize = 0;
+/* Bitwise_Fixed_Table construction as follows:
++-----+-----+-----+-----+----------+----------+---------+--------+---------+----+
+| TAG | OP0 | OP1 | RES | OP0_BITS | OP1_BITS | SEL_AND | SEL_OR | SEL_XOR | U8 |
++-----+-----+-----+-----+----------+----------+---------+--------+---------+----+
+| AND |  0  |  0  |  0  | 00000000 | 00000000 |    1    |   0    |    0    |  0 |
++-----+-----+-----+-----+----------+----------+---------+--------+---------+----+
+| ... | ... | ... | ... | ........ | ........ |   ...   |  ...   |   ...   | .. |
++-----+-----+-----+-----+----------+----------+---------+--------+---------+----+
+| XOR | 255 | 255 |  0  | 11111111 | 11111111 |    0    |   0    |    1    | 255|
++-----+-----+-----+-----+----------+----------+---------+--------+---------+----+
+|  0  |  0  |  0  |  0  | 00000000 | 00000000 |    0    |   0    |    0    | 255|
++-----+-----+-----+-----+----------+----------+---------+--------+---------+----+
 
-// pub(crate) const OP0: usize = TAG + 1;
-// pub(crate) const OP1: usize = OP0 + 1;
-// pub(crate) const RES: usize = OP1 + 1;
+Constraints as follows:
+1. selectors and bits are binary, at most one selector is set
+2. op0 = sum(op0_bits_i * 2^i), op1 = sum(op1_bits_i * 2^i)
+3. tag and res follow the selected operation, computed bit by bit; rows with
+   no selector set are the (0, op0, op1, 0) padding entries
+4. U8 starts at 0, increases by 0 or 1 per row and ends at 2^8 - 1
+5. Cross_Lookup
+    Cross_Lookup{<FIX_TAG, FIX_OP0, FIX_OP1, FIX_RES, FIX_U8>; BITWISE_TABLE}
+*/
+pub(crate) const TAG: usize = 0;
+pub(crate) const OP0: usize = TAG + 1;
+pub(crate) const OP1: usize = OP0 + 1;
+pub(crate) const RES: usize = OP1 + 1;
 
-// pub(crate) const COL_NUM: usize = RES + 1; //4
+pub(crate) const OP0_BITS: Range<usize> = RES + 1..RES + 9;
+pub(crate) const OP1_BITS: Range<usize> = OP0_BITS.end..OP0_BITS.end + 8;
+
+pub(crate) const SEL_AND: usize = OP1_BITS.end;
+pub(crate) const SEL_OR: usize = SEL_AND + 1;
+pub(crate) const SEL_XOR: usize = SEL_OR + 1;
+
+pub(crate) const RANGE_CHECK_U8: usize = SEL_XOR + 1;
+
+pub(crate) const COL_NUM_BITWISE_FIXED: usize = RANGE_CHECK_U8 + 1; //24
@@ -1,39 +1,23 @@
-// // 2022-12-26: written by xb
+/* Rangecheck_Fixed_Table construction as follows:
++---------+
+|   VAL   |
++---------+
+|    0    |
++---------+
+|    1    |
++---------+
+|   ...   |
++---------+
+| 2^16 - 1|
++---------+
+| 2^16 - 1|
++---------+
 
-// /* Rangecheck_Fixed_Table construction as follows:
-// +-----+---------
-// | TAG | Value
-// +-----+---------
-// +-----+---------
-// |  1  | 0
-// +-----+---------
-// +-----+---------
-// |  1  | 1
-// +-----+---------
-// +-----+---------
-// | ... | ...
-// +-----+---------
-// +-----+---------
-// |  1  | 2^8 - 1
-// +-----+---------
-// +-----+---------
-// |  0  | 2^8
-// +-----+---------
-// +-----+---------
-// |  0  | 2^8 + 1
-// +-----+---------
-// +-----+---------
-// | ... | ...
-// +-----+---------
-// +-----+---------
-// |  0  | 2^16 - 1
-// +-----+---------
+Constraints as follows:
+1. VAL starts at 0, increases by 0 or 1 per row and ends at 2^16 - 1
+2. Cross_Lookup
+    Cross_Lookup{<FIX_RANGE_CHECK_U16>; RC_TABLE}
+*/
+pub(crate) const VAL: usize = 0;
 
-// 1 : [0, 2^8)
-// 1, 0 : [0, 2^16)
-// */
-// //pub(crate) const TAG: usize = 0;
-
-// pub(crate) const VAL: usize = 0;
-
-// pub(crate) const COL_NUM: usize = VAL + 1; //4
+pub(crate) const COL_NUM_RC_FIXED: usize = VAL + 1; //1
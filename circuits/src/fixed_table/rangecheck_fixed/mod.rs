@@ -1,2 +1,2 @@
-// pub mod columns;
-// pub mod rangecheck_fixed_stark;
+pub mod columns;
+pub mod rangecheck_fixed_stark;
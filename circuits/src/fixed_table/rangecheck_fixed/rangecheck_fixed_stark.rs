@@ -1,67 +1,146 @@
-// use crate::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
-// use crate::fixed_table::rangecheck_fixed::columns::*;
-// use crate::stark::Stark;
-// use crate::vars::{StarkEvaluationTargets, StarkEvaluationVars};
-// use plonky2::field::extension::{Extendable, FieldExtension};
-// use plonky2::field::packed::PackedField;
-// use plonky2::hash::hash_types::RichField;
-// use plonky2::plonk::circuit_builder::CircuitBuilder;
-// use std::marker::PhantomData;
-
-// #[derive(Copy, Clone, Default)]
-// pub struct RangecheckFixedStark<F, const D: usize> {
-//     pub _phantom: PhantomData<F>,
-// }
-
-// impl<F: RichField, const D: usize> RangecheckFixedStark<F, D> {
-//     const BASE: usize = 1 << 8;
-// }
-
-// impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for RangecheckFixedStark<F, D> {
-//     const COLUMNS: usize = COL_NUM;
-
-//     fn eval_packed_generic<FE, P, const D2: usize>(
-//         &self,
-//         _vars: StarkEvaluationVars<FE, P, { COL_NUM }>,
-//         _yield_constr: &mut ConstraintConsumer<P>,
-//     ) where
-//         FE: FieldExtension<D2, BaseField = F>,
-//         P: PackedField<Scalar = FE>,
-//     {
-//     }
-
-//     fn eval_ext_circuit(
-//         &self,
-//         _builder: &mut CircuitBuilder<F, D>,
-//         _vars: StarkEvaluationTargets<D, { COL_NUM }>,
-//         _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
-//     ) {
-//     }
-
-//     fn constraint_degree(&self) -> usize {
-//         1
-//     }
-// }
-
-// Get the column info for Cross_Lookup<Rangecheck_fixed_table, Bitwise_table>
-/*pub fn ctl_data_with_bitwise<F: Field>() -> Vec<Column<F>> {
-    let mut res = Column::singles([VAL]).collect_vec();
-    res.extend(Column::singles([TAG]).collect_vec());
-
-    res
+use crate::fixed_table::rangecheck_fixed::columns::*;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::stark::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use crate::stark::cross_table_lookup::Column;
+use crate::stark::stark::Stark;
+use crate::stark::vars::{StarkEvaluationTargets, StarkEvaluationVars};
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use std::marker::PhantomData;
+
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RangeCheckFixedStark<F, const D: usize> {
+    pub _phantom: PhantomData<F>,
 }
 
-pub fn ctl_filter_with_bitwise<F: Field>() -> Column<F> {
-    Column::one()
+impl<F: RichField, const D: usize> RangeCheckFixedStark<F, D> {
+    const MAX: usize = (1 << 16) - 1;
 }
 
-// Get the column info for Cross_Lookup<Rangecheck_fixed_table, rangecheck_table>
-pub fn ctl_data_with_rangecheck<F: Field>() -> Vec<Column<F>> {
-    let mut res = Column::singles([VAL]).collect_vec();
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for RangeCheckFixedStark<F, D> {
+    const COLUMNS: usize = COL_NUM_RC_FIXED;
+
+    // VAL walks from 0 to 2^16 - 1 in steps of 0 or 1, so every row holds a
+    // u16 whatever the trace length.
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: StarkEvaluationVars<FE, P, { COL_NUM_RC_FIXED }>,
+        yield_constr: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>,
+    {
+        let val = vars.local_values[VAL];
+        let nv_val = vars.next_values[VAL];
+
+        yield_constr.constraint_first_row(val);
+        let diff = nv_val - val;
+        yield_constr.constraint_transition(diff * (diff - P::ONES));
+        yield_constr.constraint_last_row(val - P::Scalar::from_canonical_usize(Self::MAX));
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { COL_NUM_RC_FIXED }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let val = vars.local_values[VAL];
+        let nv_val = vars.next_values[VAL];
+        let one = builder.one_extension();
+
+        yield_constr.constraint_first_row(builder, val);
+        let diff = builder.sub_extension(nv_val, val);
+        let diff_minus_one = builder.sub_extension(diff, one);
+        let cs = builder.mul_extension(diff, diff_minus_one);
+        yield_constr.constraint_transition(builder, cs);
+        let max = builder.constant_extension(F::Extension::from_canonical_usize(Self::MAX));
+        let cs = builder.sub_extension(val, max);
+        yield_constr.constraint_last_row(builder, cs);
+    }
+
+    fn constraint_degree(&self) -> usize {
+        2
+    }
+}
 
-    res
+// Get the column info for Cross_Lookup<Rangecheck_table,
+// Rangecheck_fixed_table>
+pub fn ctl_data_with_rangecheck<F: Field>() -> Vec<Column<F>> {
+    Column::singles([VAL]).collect_vec()
 }
 
 pub fn ctl_filter_with_rangecheck<F: Field>() -> Column<F> {
     Column::one()
-}*/
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixed_table::rangecheck_fixed::rangecheck_fixed_stark::RangeCheckFixedStark;
+    use crate::generation::fixed_table::generate_rc_fixed_trace;
+    use crate::stark::constraint_consumer::ConstraintConsumer;
+    use crate::stark::stark::Stark;
+    use crate::stark::vars::StarkEvaluationVars;
+    use crate::test_utils::test_stark_circuit_constraints;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2_util::log2_strict;
+
+    #[test]
+    fn test_rc_fixed_trace() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let stark = RangeCheckFixedStark::<F, D>::default();
+
+        let rows = generate_rc_fixed_trace::<F>(1 << 17);
+        let len = rows[0].len();
+        let last = F::primitive_root_of_unity(log2_strict(len)).inverse();
+        let subgroup =
+            F::cyclic_subgroup_known_order(F::primitive_root_of_unity(log2_strict(len)), len);
+
+        for i in 0..len {
+            let local_values = rows
+                .iter()
+                .map(|row| row[i])
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            let next_values = rows
+                .iter()
+                .map(|row| row[(i + 1) % len])
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            let vars = StarkEvaluationVars {
+                local_values: &local_values,
+                next_values: &next_values,
+            };
+            let mut constraint_consumer = ConstraintConsumer::new(
+                vec![F::rand()],
+                subgroup[i] - last,
+                if i == 0 { F::ONE } else { F::ZERO },
+                if i == len - 1 { F::ONE } else { F::ZERO },
+            );
+            stark.eval_packed_generic(vars, &mut constraint_consumer);
+            for &acc in &constraint_consumer.constraint_accs {
+                assert_eq!(acc, GoldilocksField::ZERO, "constraint error in line {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rc_fixed_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(RangeCheckFixedStark::<F, D>::default())
+            .unwrap();
+    }
+}
@@ -1,9 +1,6 @@
-use core::program::instruction::Opcode;
 use core::trace::trace::{BitwiseCombinedRow, CmpRow, RangeCheckRow};
 use plonky2::field::types::PrimeField64;
 use plonky2::hash::hash_types::RichField;
-use plonky2::iop::challenger::Challenger;
-use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 
 use crate::builtins::bitwise::columns as bitwise;
 use crate::builtins::cmp::columns as cmp;
@@ -12,7 +9,9 @@ use crate::builtins::cmp::columns::{
     COL_CMP_OP1,
 };
 use crate::builtins::rangecheck::columns as rangecheck;
-use crate::stark::lookup::permuted_cols;
+use crate::fixed_table::bitwise_fixed::columns as bitwise_fixed;
+use crate::generation::fixed_table::{generate_bitwise_fixed_trace, generate_rc_fixed_trace};
+use crate::stark::lookup::lookup_frequencies;
 
 // add by xb 2023-1-5
 // case 1:
@@ -34,7 +33,7 @@ use crate::stark::lookup::permuted_cols;
 //      looked_table: <0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15>
 pub fn generate_bitwise_trace<F: RichField>(
     cells: &[BitwiseCombinedRow],
-) -> ([Vec<F>; bitwise::COL_NUM_BITWISE], F) {
+) -> [Vec<F>; bitwise::COL_NUM_BITWISE] {
     // Ensure the max rows number.
     let trace_len = cells.len();
     let max_trace_len = trace_len
@@ -62,147 +61,82 @@ pub fn generate_bitwise_trace<F: RichField>(
         trace[bitwise::OP0_LIMBS.start][i] = F::from_canonical_u64(c.op0_0.to_canonical_u64());
         trace[bitwise::OP0_LIMBS.start + 1][i] = F::from_canonical_u64(c.op0_1.to_canonical_u64());
         trace[bitwise::OP0_LIMBS.start + 2][i] = F::from_canonical_u64(c.op0_2.to_canonical_u64());
-        trace[bitwise::OP0_LIMBS.end][i] = F::from_canonical_u64(c.op0_3.to_canonical_u64());
+        trace[bitwise::OP0_LIMBS.start + 3][i] = F::from_canonical_u64(c.op0_3.to_canonical_u64());
 
         trace[bitwise::OP1_LIMBS.start][i] = F::from_canonical_u64(c.op1_0.to_canonical_u64());
         trace[bitwise::OP1_LIMBS.start + 1][i] = F::from_canonical_u64(c.op1_1.to_canonical_u64());
         trace[bitwise::OP1_LIMBS.start + 2][i] = F::from_canonical_u64(c.op1_2.to_canonical_u64());
-        trace[bitwise::OP1_LIMBS.end][i] = F::from_canonical_u64(c.op1_3.to_canonical_u64());
+        trace[bitwise::OP1_LIMBS.start + 3][i] = F::from_canonical_u64(c.op1_3.to_canonical_u64());
 
         trace[bitwise::RES_LIMBS.start][i] = F::from_canonical_u64(c.res_0.to_canonical_u64());
         trace[bitwise::RES_LIMBS.start + 1][i] = F::from_canonical_u64(c.res_1.to_canonical_u64());
         trace[bitwise::RES_LIMBS.start + 2][i] = F::from_canonical_u64(c.res_2.to_canonical_u64());
-        trace[bitwise::RES_LIMBS.end][i] = F::from_canonical_u64(c.res_3.to_canonical_u64());
+        trace[bitwise::RES_LIMBS.start + 3][i] = F::from_canonical_u64(c.res_3.to_canonical_u64());
     }
 
-    // add fix bitwise info
-    // for 2^8 case, the row is 2^15 + 2^7
-    // fixed at 2023-1-16, for 2^8 case, row number is 2^16
-    let mut index = 0;
-    for op0 in 0..bitwise::RANGE_CHECK_U8_SIZE {
-        // add fix rangecheck info
-        trace[bitwise::FIX_RANGE_CHECK_U8][op0] = F::from_canonical_usize(op0);
-
-        //for op1 in op0..bitwise::RANGE_CHECK_U8_SIZE {
-        for op1 in 0..bitwise::RANGE_CHECK_U8_SIZE {
-            // exe the AND OPE ration
-            let res_and = op0 & op1;
-            trace[bitwise::FIX_BITWSIE_OP0][index] = F::from_canonical_usize(op0);
-            trace[bitwise::FIX_BITWSIE_OP1][index] = F::from_canonical_usize(op1);
-            trace[bitwise::FIX_BITWSIE_RES][index] = F::from_canonical_usize(res_and);
-            trace[bitwise::FIX_TAG][index] = F::from_canonical_u64(1_u64 << Opcode::AND as u8);
-
-            let res_or = op0 | op1;
-            trace[bitwise::FIX_BITWSIE_OP0][bitwise::BITWISE_U8_SIZE_PER + index] =
-                F::from_canonical_usize(op0);
-            trace[bitwise::FIX_BITWSIE_OP1][bitwise::BITWISE_U8_SIZE_PER + index] =
-                F::from_canonical_usize(op1);
-            trace[bitwise::FIX_BITWSIE_RES][bitwise::BITWISE_U8_SIZE_PER + index] =
-                F::from_canonical_usize(res_or);
-            trace[bitwise::FIX_TAG][bitwise::BITWISE_U8_SIZE_PER + index] =
-                F::from_canonical_u64(1_u64 << Opcode::OR as u8);
-
-            let res_xor = op0 ^ op1;
-            trace[bitwise::FIX_BITWSIE_OP0][bitwise::BITWISE_U8_SIZE_PER * 2 + index] =
-                F::from_canonical_usize(op0);
-            trace[bitwise::FIX_BITWSIE_OP1][bitwise::BITWISE_U8_SIZE_PER * 2 + index] =
-                F::from_canonical_usize(op1);
-            trace[bitwise::FIX_BITWSIE_RES][bitwise::BITWISE_U8_SIZE_PER * 2 + index] =
-                F::from_canonical_usize(res_xor);
-            trace[bitwise::FIX_TAG][bitwise::BITWISE_U8_SIZE_PER * 2 + index] =
-                F::from_canonical_u64(1_u64 << Opcode::XOR as u8);
-
-            index += 1;
-        }
-    }
-
-    // TODO: We should choose proper columns for oracle.
-    let mut challenger =
-        Challenger::<F, <PoseidonGoldilocksConfig as GenericConfig<2>>::Hasher>::new();
-    for i in 0..bitwise::OP0_LIMBS.len() {
-        challenger.observe_elements(&trace[bitwise::OP0_LIMBS.start + i]);
-    }
-    for i in 0..bitwise::OP1_LIMBS.len() {
-        challenger.observe_elements(&trace[bitwise::OP1_LIMBS.start + i]);
-    }
-    for i in 0..bitwise::RES_LIMBS.len() {
-        challenger.observe_elements(&trace[bitwise::RES_LIMBS.start + i]);
-    }
-    let beta = challenger.get_challenge();
+    // add fix bitwise info, the same rows as the bitwise fixed table.
+    let [fix_tag, fix_op0, fix_op1, fix_res, fix_range_check_u8] = {
+        let mut fixed = generate_bitwise_fixed_trace::<F>(ext_trace_len);
+        [
+            bitwise_fixed::TAG,
+            bitwise_fixed::OP0,
+            bitwise_fixed::OP1,
+            bitwise_fixed::RES,
+            bitwise_fixed::RANGE_CHECK_U8,
+        ]
+        .map(|col| std::mem::take(&mut fixed[col]))
+    };
+    trace[bitwise::FIX_TAG] = fix_tag;
+    trace[bitwise::FIX_BITWSIE_OP0] = fix_op0;
+    trace[bitwise::FIX_BITWSIE_OP1] = fix_op1;
+    trace[bitwise::FIX_BITWSIE_RES] = fix_res;
+    trace[bitwise::FIX_RANGE_CHECK_U8] = fix_range_check_u8;
 
     for i in 0..trace[0].len() {
-        trace[bitwise::COMPRESS_LIMBS.start][i] = trace[bitwise::TAG][i]
-            + trace[bitwise::OP0_LIMBS.start][i] * beta
-            + trace[bitwise::OP1_LIMBS.start][i] * beta * beta
-            + trace[bitwise::RES_LIMBS.start][i] * beta * beta * beta;
-
-        trace[bitwise::COMPRESS_LIMBS.start + 1][i] = trace[bitwise::TAG][i]
-            + trace[bitwise::OP0_LIMBS.start + 1][i] * beta
-            + trace[bitwise::OP1_LIMBS.start + 1][i] * beta * beta
-            + trace[bitwise::RES_LIMBS.start + 1][i] * beta * beta * beta;
-
-        trace[bitwise::COMPRESS_LIMBS.start + 2][i] = trace[bitwise::TAG][i]
-            + trace[bitwise::OP0_LIMBS.start + 2][i] * beta
-            + trace[bitwise::OP1_LIMBS.start + 2][i] * beta * beta
-            + trace[bitwise::RES_LIMBS.start + 2][i] * beta * beta * beta;
-
-        trace[bitwise::COMPRESS_LIMBS.start + 3][i] = trace[bitwise::TAG][i]
-            + trace[bitwise::OP0_LIMBS.start + 3][i] * beta
-            + trace[bitwise::OP1_LIMBS.start + 3][i] * beta * beta
-            + trace[bitwise::RES_LIMBS.start + 3][i] * beta * beta * beta;
-
-        trace[bitwise::FIX_COMPRESS][i] = trace[bitwise::FIX_TAG][i]
-            + trace[bitwise::FIX_BITWSIE_OP0][i] * beta
-            + trace[bitwise::FIX_BITWSIE_OP1][i] * beta * beta
-            + trace[bitwise::FIX_BITWSIE_RES][i] * beta * beta * beta;
-    }
-
-    // add the permutation information
-    for i in 0..4 {
-        // permuted for rangecheck
-        let (permuted_inputs, permuted_table) = permuted_cols(
-            &trace[bitwise::OP0_LIMBS.start + i],
-            &trace[bitwise::FIX_RANGE_CHECK_U8],
-        );
-
-        trace[bitwise::OP0_LIMBS_PERMUTED.start + i] = permuted_inputs;
-        trace[bitwise::FIX_RANGE_CHECK_U8_PERMUTED.start + i] = permuted_table;
-
-        let (permuted_inputs, permuted_table) = permuted_cols(
-            &trace[bitwise::OP1_LIMBS.start + i],
-            &trace[bitwise::FIX_RANGE_CHECK_U8],
-        );
-
-        trace[bitwise::OP1_LIMBS_PERMUTED.start + i] = permuted_inputs;
-        trace[bitwise::FIX_RANGE_CHECK_U8_PERMUTED.start + 4 + i] = permuted_table;
-
-        let (permuted_inputs, permuted_table) = permuted_cols(
-            &trace[bitwise::RES_LIMBS.start + i],
-            &trace[bitwise::FIX_RANGE_CHECK_U8],
-        );
-
-        trace[bitwise::RES_LIMBS_PERMUTED.start + i] = permuted_inputs;
-        trace[bitwise::FIX_RANGE_CHECK_U8_PERMUTED.start + 8 + i] = permuted_table;
-
-        // permutation for bitwise
-        let (permuted_inputs, permuted_table) = permuted_cols(
-            &trace[bitwise::COMPRESS_LIMBS.start + i],
-            &trace[bitwise::FIX_COMPRESS],
+        for j in 0..4 {
+            trace[bitwise::COMPRESS_LIMBS.start + j][i] = bitwise_compress(
+                trace[bitwise::TAG][i],
+                trace[bitwise::OP0_LIMBS.start + j][i],
+                trace[bitwise::OP1_LIMBS.start + j][i],
+                trace[bitwise::RES_LIMBS.start + j][i],
+            );
+        }
+        trace[bitwise::FIX_COMPRESS][i] = bitwise_compress(
+            trace[bitwise::FIX_TAG][i],
+            trace[bitwise::FIX_BITWSIE_OP0][i],
+            trace[bitwise::FIX_BITWSIE_OP1][i],
+            trace[bitwise::FIX_BITWSIE_RES][i],
         );
-
-        trace[bitwise::COMPRESS_PERMUTED.start + i] = permuted_inputs;
-        trace[bitwise::FIX_COMPRESS_PERMUTED.start + i] = permuted_table;
     }
 
-    let trace_row_vecs = trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
+    // add the lookup frequencies of the limbs and compressed limbs
+    let limbs = bitwise::OP0_LIMBS
+        .chain(bitwise::OP1_LIMBS)
+        .chain(bitwise::RES_LIMBS)
+        .map(|col| &trace[col][..])
+        .collect::<Vec<_>>();
+    let range_check_u8_freq = lookup_frequencies(&limbs, &trace[bitwise::FIX_RANGE_CHECK_U8]);
+    let compress_limbs = bitwise::COMPRESS_LIMBS
+        .map(|col| &trace[col][..])
+        .collect::<Vec<_>>();
+    let compress_freq = lookup_frequencies(&compress_limbs, &trace[bitwise::FIX_COMPRESS]);
+    trace[bitwise::FIX_RANGE_CHECK_U8_FREQ] = range_check_u8_freq;
+    trace[bitwise::FIX_COMPRESS_FREQ] = compress_freq;
+
+    trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
         panic!(
             "Expected a Vec of length {} but it was {}",
             bitwise::COL_NUM_BITWISE,
             v.len()
         )
-    });
+    })
+}
 
-    (trace_row_vecs, beta)
+/// Packs a bitwise lookup row in base 2^8, `tag` being the most significant
+/// digit. Injective as limbs are u8 and tags are opcode bit masks below 2^32.
+fn bitwise_compress<F: RichField>(tag: F, op0: F, op1: F, res: F) -> F {
+    let base = F::from_canonical_u64(1 << 8);
+    ((tag * base + op0) * base + op1) * base + res
 }
 
 pub fn generate_cmp_trace<F: RichField>(cells: &[CmpRow]) -> [Vec<F>; cmp::COL_NUM_CMP] {
@@ -274,38 +208,19 @@ pub fn generate_rc_trace<F: RichField>(
         trace[rangecheck::LIMB_LO][i] = F::from_canonical_u64(c.limb_lo.to_canonical_u64());
         trace[rangecheck::LIMB_HI][i] = F::from_canonical_u64(c.limb_hi.to_canonical_u64());
     }
-    // add fix rangecheck info
-    trace[rangecheck::FIX_RANGE_CHECK_U16] = (0..rangecheck::RANGE_CHECK_U16_SIZE)
-        .map(|i| F::from_canonical_usize(i))
-        .collect();
-    if trace[rangecheck::FIX_RANGE_CHECK_U16].len() < ext_trace_len {
-        let append_start = trace[rangecheck::FIX_RANGE_CHECK_U16].len();
-        let append_end_exclusive = ext_trace_len;
-        let append_value = trace[rangecheck::FIX_RANGE_CHECK_U16]
-            .last()
-            .unwrap()
-            .clone();
-        (append_start..append_end_exclusive).for_each(|_| {
-            trace[rangecheck::FIX_RANGE_CHECK_U16].push(append_value.clone());
-        });
-    }
-
-    let (permuted_inputs, permuted_table) = permuted_cols(
-        &trace[rangecheck::LIMB_LO],
+    // add fix rangecheck info, the same rows as the rangecheck fixed table.
+    let [fix_range_check_u16] = generate_rc_fixed_trace::<F>(ext_trace_len);
+    trace[rangecheck::FIX_RANGE_CHECK_U16] = fix_range_check_u16;
+
+    // add the lookup frequencies of the limbs
+    trace[rangecheck::FIX_RANGE_CHECK_U16_FREQ] = lookup_frequencies(
+        &[
+            &trace[rangecheck::LIMB_LO][..],
+            &trace[rangecheck::LIMB_HI][..],
+        ],
         &trace[rangecheck::FIX_RANGE_CHECK_U16],
     );
 
-    trace[rangecheck::LIMB_LO_PERMUTED] = permuted_inputs;
-    trace[rangecheck::FIX_RANGE_CHECK_U16_PERMUTED_LO] = permuted_table;
-
-    let (permuted_inputs, permuted_table) = permuted_cols(
-        &trace[rangecheck::LIMB_HI],
-        &trace[rangecheck::FIX_RANGE_CHECK_U16],
-    );
-
-    trace[rangecheck::LIMB_HI_PERMUTED] = permuted_inputs;
-    trace[rangecheck::FIX_RANGE_CHECK_U16_PERMUTED_HI] = permuted_table;
-
     trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
         panic!(
             "Expected a Vec of length {} but it was {}",
@@ -317,7 +232,7 @@ pub fn generate_rc_trace<F: RichField>(
 
 pub fn gen_bitwise_trace<F: RichField>(
     batch_cells: &Vec<Vec<BitwiseCombinedRow>>,
-) -> ([Vec<F>; bitwise::COL_NUM_BITWISE], F) {
+) -> [Vec<F>; bitwise::COL_NUM_BITWISE] {
     // Ensure the max rows number.
     let trace_len: usize = batch_cells.iter().map(|inner_vec| inner_vec.len()).sum();
     let max_trace_len = trace_len
@@ -348,153 +263,84 @@ pub fn gen_bitwise_trace<F: RichField>(
                 F::from_canonical_u64(c.op0_1.to_canonical_u64());
             trace[bitwise::OP0_LIMBS.start + 2][i] =
                 F::from_canonical_u64(c.op0_2.to_canonical_u64());
-            trace[bitwise::OP0_LIMBS.end][i] = F::from_canonical_u64(c.op0_3.to_canonical_u64());
+            trace[bitwise::OP0_LIMBS.start + 3][i] =
+                F::from_canonical_u64(c.op0_3.to_canonical_u64());
 
             trace[bitwise::OP1_LIMBS.start][i] = F::from_canonical_u64(c.op1_0.to_canonical_u64());
             trace[bitwise::OP1_LIMBS.start + 1][i] =
                 F::from_canonical_u64(c.op1_1.to_canonical_u64());
             trace[bitwise::OP1_LIMBS.start + 2][i] =
                 F::from_canonical_u64(c.op1_2.to_canonical_u64());
-            trace[bitwise::OP1_LIMBS.end][i] = F::from_canonical_u64(c.op1_3.to_canonical_u64());
+            trace[bitwise::OP1_LIMBS.start + 3][i] =
+                F::from_canonical_u64(c.op1_3.to_canonical_u64());
 
             trace[bitwise::RES_LIMBS.start][i] = F::from_canonical_u64(c.res_0.to_canonical_u64());
             trace[bitwise::RES_LIMBS.start + 1][i] =
                 F::from_canonical_u64(c.res_1.to_canonical_u64());
             trace[bitwise::RES_LIMBS.start + 2][i] =
                 F::from_canonical_u64(c.res_2.to_canonical_u64());
-            trace[bitwise::RES_LIMBS.end][i] = F::from_canonical_u64(c.res_3.to_canonical_u64());
+            trace[bitwise::RES_LIMBS.start + 3][i] =
+                F::from_canonical_u64(c.res_3.to_canonical_u64());
             i += 1;
         }
     }
 
-    // add fix bitwise info
-    // for 2^8 case, the row is 2^15 + 2^7
-    // fixed at 2023-1-16, for 2^8 case, row number is 2^16
-    let mut index = 0;
-    for op0 in 0..bitwise::RANGE_CHECK_U8_SIZE {
-        // add fix rangecheck info
-        trace[bitwise::FIX_RANGE_CHECK_U8][op0] = F::from_canonical_usize(op0);
-
-        //for op1 in op0..bitwise::RANGE_CHECK_U8_SIZE {
-        for op1 in 0..bitwise::RANGE_CHECK_U8_SIZE {
-            // exe the AND OPE ration
-            let res_and = op0 & op1;
-            trace[bitwise::FIX_BITWSIE_OP0][index] = F::from_canonical_usize(op0);
-            trace[bitwise::FIX_BITWSIE_OP1][index] = F::from_canonical_usize(op1);
-            trace[bitwise::FIX_BITWSIE_RES][index] = F::from_canonical_usize(res_and);
-            trace[bitwise::FIX_TAG][index] = F::from_canonical_u64(1_u64 << Opcode::AND as u8);
-
-            let res_or = op0 | op1;
-            trace[bitwise::FIX_BITWSIE_OP0][bitwise::BITWISE_U8_SIZE_PER + index] =
-                F::from_canonical_usize(op0);
-            trace[bitwise::FIX_BITWSIE_OP1][bitwise::BITWISE_U8_SIZE_PER + index] =
-                F::from_canonical_usize(op1);
-            trace[bitwise::FIX_BITWSIE_RES][bitwise::BITWISE_U8_SIZE_PER + index] =
-                F::from_canonical_usize(res_or);
-            trace[bitwise::FIX_TAG][bitwise::BITWISE_U8_SIZE_PER + index] =
-                F::from_canonical_u64(1_u64 << Opcode::OR as u8);
-
-            let res_xor = op0 ^ op1;
-            trace[bitwise::FIX_BITWSIE_OP0][bitwise::BITWISE_U8_SIZE_PER * 2 + index] =
-                F::from_canonical_usize(op0);
-            trace[bitwise::FIX_BITWSIE_OP1][bitwise::BITWISE_U8_SIZE_PER * 2 + index] =
-                F::from_canonical_usize(op1);
-            trace[bitwise::FIX_BITWSIE_RES][bitwise::BITWISE_U8_SIZE_PER * 2 + index] =
-                F::from_canonical_usize(res_xor);
-            trace[bitwise::FIX_TAG][bitwise::BITWISE_U8_SIZE_PER * 2 + index] =
-                F::from_canonical_u64(1_u64 << Opcode::XOR as u8);
-
-            index += 1;
-        }
-    }
-
-    // TODO: We should choose proper columns for oracle.
-    let mut challenger =
-        Challenger::<F, <PoseidonGoldilocksConfig as GenericConfig<2>>::Hasher>::new();
-    for i in 0..bitwise::OP0_LIMBS.len() {
-        challenger.observe_elements(&trace[bitwise::OP0_LIMBS.start + i]);
-    }
-    for i in 0..bitwise::OP1_LIMBS.len() {
-        challenger.observe_elements(&trace[bitwise::OP1_LIMBS.start + i]);
-    }
-    for i in 0..bitwise::RES_LIMBS.len() {
-        challenger.observe_elements(&trace[bitwise::RES_LIMBS.start + i]);
-    }
-    let beta = challenger.get_challenge();
+    // add fix bitwise info, the same rows as the bitwise fixed table.
+    let [fix_tag, fix_op0, fix_op1, fix_res, fix_range_check_u8] = {
+        let mut fixed = generate_bitwise_fixed_trace::<F>(ext_trace_len);
+        [
+            bitwise_fixed::TAG,
+            bitwise_fixed::OP0,
+            bitwise_fixed::OP1,
+            bitwise_fixed::RES,
+            bitwise_fixed::RANGE_CHECK_U8,
+        ]
+        .map(|col| std::mem::take(&mut fixed[col]))
+    };
+    trace[bitwise::FIX_TAG] = fix_tag;
+    trace[bitwise::FIX_BITWSIE_OP0] = fix_op0;
+    trace[bitwise::FIX_BITWSIE_OP1] = fix_op1;
+    trace[bitwise::FIX_BITWSIE_RES] = fix_res;
+    trace[bitwise::FIX_RANGE_CHECK_U8] = fix_range_check_u8;
 
     for i in 0..trace[0].len() {
-        trace[bitwise::COMPRESS_LIMBS.start][i] = trace[bitwise::TAG][i]
-            + trace[bitwise::OP0_LIMBS.start][i] * beta
-            + trace[bitwise::OP1_LIMBS.start][i] * beta * beta
-            + trace[bitwise::RES_LIMBS.start][i] * beta * beta * beta;
-
-        trace[bitwise::COMPRESS_LIMBS.start + 1][i] = trace[bitwise::TAG][i]
-            + trace[bitwise::OP0_LIMBS.start + 1][i] * beta
-            + trace[bitwise::OP1_LIMBS.start + 1][i] * beta * beta
-            + trace[bitwise::RES_LIMBS.start + 1][i] * beta * beta * beta;
-
-        trace[bitwise::COMPRESS_LIMBS.start + 2][i] = trace[bitwise::TAG][i]
-            + trace[bitwise::OP0_LIMBS.start + 2][i] * beta
-            + trace[bitwise::OP1_LIMBS.start + 2][i] * beta * beta
-            + trace[bitwise::RES_LIMBS.start + 2][i] * beta * beta * beta;
-
-        trace[bitwise::COMPRESS_LIMBS.start + 3][i] = trace[bitwise::TAG][i]
-            + trace[bitwise::OP0_LIMBS.start + 3][i] * beta
-            + trace[bitwise::OP1_LIMBS.start + 3][i] * beta * beta
-            + trace[bitwise::RES_LIMBS.start + 3][i] * beta * beta * beta;
-
-        trace[bitwise::FIX_COMPRESS][i] = trace[bitwise::FIX_TAG][i]
-            + trace[bitwise::FIX_BITWSIE_OP0][i] * beta
-            + trace[bitwise::FIX_BITWSIE_OP1][i] * beta * beta
-            + trace[bitwise::FIX_BITWSIE_RES][i] * beta * beta * beta;
-    }
-
-    // add the permutation information
-    for i in 0..4 {
-        // permuted for rangecheck
-        let (permuted_inputs, permuted_table) = permuted_cols(
-            &trace[bitwise::OP0_LIMBS.start + i],
-            &trace[bitwise::FIX_RANGE_CHECK_U8],
-        );
-
-        trace[bitwise::OP0_LIMBS_PERMUTED.start + i] = permuted_inputs;
-        trace[bitwise::FIX_RANGE_CHECK_U8_PERMUTED.start + i] = permuted_table;
-
-        let (permuted_inputs, permuted_table) = permuted_cols(
-            &trace[bitwise::OP1_LIMBS.start + i],
-            &trace[bitwise::FIX_RANGE_CHECK_U8],
-        );
-
-        trace[bitwise::OP1_LIMBS_PERMUTED.start + i] = permuted_inputs;
-        trace[bitwise::FIX_RANGE_CHECK_U8_PERMUTED.start + 4 + i] = permuted_table;
-
-        let (permuted_inputs, permuted_table) = permuted_cols(
-            &trace[bitwise::RES_LIMBS.start + i],
-            &trace[bitwise::FIX_RANGE_CHECK_U8],
-        );
-
-        trace[bitwise::RES_LIMBS_PERMUTED.start + i] = permuted_inputs;
-        trace[bitwise::FIX_RANGE_CHECK_U8_PERMUTED.start + 8 + i] = permuted_table;
-
-        // permutation for bitwise
-        let (permuted_inputs, permuted_table) = permuted_cols(
-            &trace[bitwise::COMPRESS_LIMBS.start + i],
-            &trace[bitwise::FIX_COMPRESS],
+        for j in 0..4 {
+            trace[bitwise::COMPRESS_LIMBS.start + j][i] = bitwise_compress(
+                trace[bitwise::TAG][i],
+                trace[bitwise::OP0_LIMBS.start + j][i],
+                trace[bitwise::OP1_LIMBS.start + j][i],
+                trace[bitwise::RES_LIMBS.start + j][i],
+            );
+        }
+        trace[bitwise::FIX_COMPRESS][i] = bitwise_compress(
+            trace[bitwise::FIX_TAG][i],
+            trace[bitwise::FIX_BITWSIE_OP0][i],
+            trace[bitwise::FIX_BITWSIE_OP1][i],
+            trace[bitwise::FIX_BITWSIE_RES][i],
         );
-
-        trace[bitwise::COMPRESS_PERMUTED.start + i] = permuted_inputs;
-        trace[bitwise::FIX_COMPRESS_PERMUTED.start + i] = permuted_table;
     }
 
-    let trace_row_vecs = trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
+    // add the lookup frequencies of the limbs and compressed limbs
+    let limbs = bitwise::OP0_LIMBS
+        .chain(bitwise::OP1_LIMBS)
+        .chain(bitwise::RES_LIMBS)
+        .map(|col| &trace[col][..])
+        .collect::<Vec<_>>();
+    let range_check_u8_freq = lookup_frequencies(&limbs, &trace[bitwise::FIX_RANGE_CHECK_U8]);
+    let compress_limbs = bitwise::COMPRESS_LIMBS
+        .map(|col| &trace[col][..])
+        .collect::<Vec<_>>();
+    let compress_freq = lookup_frequencies(&compress_limbs, &trace[bitwise::FIX_COMPRESS]);
+    trace[bitwise::FIX_RANGE_CHECK_U8_FREQ] = range_check_u8_freq;
+    trace[bitwise::FIX_COMPRESS_FREQ] = compress_freq;
+
+    trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
         panic!(
             "Expected a Vec of length {} but it was {}",
             bitwise::COL_NUM_BITWISE,
             v.len()
         )
-    });
-
-    (trace_row_vecs, beta)
+    })
 }
 
 pub fn gen_cmp_trace<F: RichField>(batch_cells: &Vec<Vec<CmpRow>>) -> [Vec<F>; cmp::COL_NUM_CMP] {
@@ -574,38 +420,19 @@ pub fn gen_rc_trace<F: RichField>(
             i += 1;
         }
     }
-    // add fix rangecheck info
-    trace[rangecheck::FIX_RANGE_CHECK_U16] = (0..rangecheck::RANGE_CHECK_U16_SIZE)
-        .map(|i| F::from_canonical_usize(i))
-        .collect();
-    if trace[rangecheck::FIX_RANGE_CHECK_U16].len() < ext_trace_len {
-        let append_start = trace[rangecheck::FIX_RANGE_CHECK_U16].len();
-        let append_end_exclusive = ext_trace_len;
-        let append_value = trace[rangecheck::FIX_RANGE_CHECK_U16]
-            .last()
-            .unwrap()
-            .clone();
-        (append_start..append_end_exclusive).for_each(|_| {
-            trace[rangecheck::FIX_RANGE_CHECK_U16].push(append_value.clone());
-        });
-    }
-
-    let (permuted_inputs, permuted_table) = permuted_cols(
-        &trace[rangecheck::LIMB_LO],
+    // add fix rangecheck info, the same rows as the rangecheck fixed table.
+    let [fix_range_check_u16] = generate_rc_fixed_trace::<F>(ext_trace_len);
+    trace[rangecheck::FIX_RANGE_CHECK_U16] = fix_range_check_u16;
+
+    // add the lookup frequencies of the limbs
+    trace[rangecheck::FIX_RANGE_CHECK_U16_FREQ] = lookup_frequencies(
+        &[
+            &trace[rangecheck::LIMB_LO][..],
+            &trace[rangecheck::LIMB_HI][..],
+        ],
         &trace[rangecheck::FIX_RANGE_CHECK_U16],
     );
 
-    trace[rangecheck::LIMB_LO_PERMUTED] = permuted_inputs;
-    trace[rangecheck::FIX_RANGE_CHECK_U16_PERMUTED_LO] = permuted_table;
-
-    let (permuted_inputs, permuted_table) = permuted_cols(
-        &trace[rangecheck::LIMB_HI],
-        &trace[rangecheck::FIX_RANGE_CHECK_U16],
-    );
-
-    trace[rangecheck::LIMB_HI_PERMUTED] = permuted_inputs;
-    trace[rangecheck::FIX_RANGE_CHECK_U16_PERMUTED_HI] = permuted_table;
-
     trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
         panic!(
             "Expected a Vec of length {} but it was {}",
@@ -58,6 +58,8 @@ pub fn generate_cpu_trace<F: RichField>(steps: &Vec<Step>) -> [Vec<F>; cpu::NUM_
     opcode_to_selector.insert(OlaOpcode::TLOAD.binary_bit_mask(), cpu::COL_S_TLOAD);
     opcode_to_selector.insert(OlaOpcode::TSTORE.binary_bit_mask(), cpu::COL_S_TSTORE);
     opcode_to_selector.insert(OlaOpcode::SCCALL.binary_bit_mask(), cpu::COL_S_CALL_SC);
+    opcode_to_selector.insert(OlaOpcode::SIGCHECK.binary_bit_mask(), cpu::COL_S_SIGCHECK);
+    opcode_to_selector.insert(OlaOpcode::EVENT.binary_bit_mask(), cpu::COL_S_EVENT);
 
     for (i, s) in steps.iter().enumerate() {
         // env related columns.
@@ -266,6 +268,8 @@ pub fn gen_cpu_trace<F: RichField>(batch_steps: &Vec<Vec<Step>>) -> [Vec<F>; cpu
     opcode_to_selector.insert(OlaOpcode::TLOAD.binary_bit_mask(), cpu::COL_S_TLOAD);
     opcode_to_selector.insert(OlaOpcode::TSTORE.binary_bit_mask(), cpu::COL_S_TSTORE);
     opcode_to_selector.insert(OlaOpcode::SCCALL.binary_bit_mask(), cpu::COL_S_CALL_SC);
+    opcode_to_selector.insert(OlaOpcode::SIGCHECK.binary_bit_mask(), cpu::COL_S_SIGCHECK);
+    opcode_to_selector.insert(OlaOpcode::EVENT.binary_bit_mask(), cpu::COL_S_EVENT);
 
     let mut i: usize = 0;
     for (tx_idx, tx_steps) in batch_steps.iter().enumerate() {
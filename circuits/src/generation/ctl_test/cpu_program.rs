@@ -29,11 +29,7 @@ fn print_cpu_program_ctl_info() {
         .map(|(addr, hash)| (decode_addr(addr), hash))
         .collect::<Vec<_>>();
     let progs_for_program = progs.clone();
-    let (program_cols, _) = generate_prog_trace::<GoldilocksField>(
-        &trace.exec,
-        progs_for_program,
-        trace.start_end_roots,
-    );
+    let program_cols = generate_prog_trace::<GoldilocksField>(&trace.exec, progs_for_program);
     let program_rows = get_rows_vec_from_trace(program_cols);
 
     let insts_looking_cols: Vec<usize> = COL_ADDR_CODE_RANGE.chain([COL_PC, COL_INST]).collect();
@@ -25,6 +25,8 @@ use executor::{
 };
 use plonky2::hash::hash_types::RichField;
 
+use crate::test_utils::test_account_tree;
+
 #[allow(unused)]
 fn get_looking_looked_info<
     const LOOKING_COL_NUM: usize,
@@ -160,15 +162,7 @@ pub fn get_exec_trace(
     path.push("../assembler/test_data/asm/");
     path.push(file_name);
     let program_path = path.display().to_string();
-    let mut db = match db_name {
-        Some(name) => {
-            let mut db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-            db_path.push("../executor/db_test/");
-            db_path.push(name);
-            AccountTree::new_db_test(db_path.display().to_string())
-        }
-        _ => AccountTree::new_test(),
-    };
+    let (mut db, _db_dir) = test_account_tree(db_name);
 
     let program = encode_asm_from_json_file(program_path).unwrap();
     let hash = ZkHasher::default();
@@ -28,8 +28,7 @@ fn print_prog_chunk_program_ctl_info() {
     let prog_chunk_cols = generate_prog_chunk_trace(progs.clone());
     let prog_chunk_rows = get_rows_vec_from_trace(prog_chunk_cols);
 
-    let (program_cols, _) =
-        generate_prog_trace::<GoldilocksField>(&trace.exec, progs, trace.start_end_roots);
+    let program_cols = generate_prog_trace::<GoldilocksField>(&trace.exec, progs);
     let program_rows = get_rows_vec_from_trace(program_cols);
 
     (0..8).for_each(|i| {
@@ -0,0 +1,384 @@
+use core::{
+    trace::exe_trace::{MemExePiece, TxExeTrace},
+    types::merkle_tree::constant::ROOT_TREE_DEPTH,
+    vm::{
+        hardware::{ContractAddress, MEM_HEAP_REGION, MEM_PROPHET_REGION, MEM_STACK_REGION},
+        opcodes::OlaOpcode,
+    },
+};
+use std::{collections::HashMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::builtins::bitwise::columns as bitwise;
+use crate::builtins::cmp::columns::COL_NUM_CMP;
+use crate::builtins::event::columns::NUM_COL_EVENT;
+use crate::builtins::poseidon::columns::{NUM_POSEIDON_CHUNK_COLS, NUM_POSEIDON_COLS};
+use crate::builtins::rangecheck::columns as rangecheck;
+use crate::builtins::sccall::columns::NUM_COL_SCCALL;
+use crate::builtins::sigcheck::columns::NUM_COL_SIGCHECK;
+use crate::builtins::storage::columns::NUM_COL_ST;
+use crate::builtins::tape::columns::NUM_COL_TAPE;
+use crate::cpu::columns::NUM_CPU_COLS;
+use crate::fixed_table::bitwise_fixed::columns as bitwise_fixed;
+use crate::fixed_table::rangecheck_fixed::columns as rangecheck_fixed;
+use crate::memory::columns::NUM_MEM_COLS;
+use crate::program::columns::{NUM_PROG_CHUNK_COLS, NUM_PROG_COLS};
+use crate::stark::config::StarkConfig;
+use crate::stark::ola_stark::{Table, NUM_TABLES};
+
+/// Prover throughput used to turn trace sizes into time and memory. The
+/// defaults are rough, calibrate them against `prove_with_traces` on the
+/// proving machine.
+///
+/// Every proof pays for the fixed lookup tables: BitwiseFixed has at least
+/// 2^18 rows and RangeCheckFixed at least 2^16 rows, even for a batch with no
+/// bitwise operation or range check. Small batches are dominated by them.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ProvingCostModel {
+    pub nanos_per_lde_cell: f64,
+    pub bytes_per_lde_cell: f64,
+}
+
+impl Default for ProvingCostModel {
+    fn default() -> Self {
+        Self {
+            nanos_per_lde_cell: 25.0,
+            bytes_per_lde_cell: 32.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableEstimate {
+    pub table: Table,
+    pub columns: usize,
+    pub rows: usize,
+    pub padded_rows: usize,
+    pub degree_bits: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceEstimate {
+    pub tables: Vec<TableEstimate>,
+    /// Cells of all trace low degree extensions committed by the prover.
+    pub lde_cells: u64,
+    pub proving_time: Duration,
+    pub proving_memory_bytes: u64,
+}
+
+impl TraceEstimate {
+    pub fn max_degree_bits(&self) -> usize {
+        self.tables.iter().map(|t| t.degree_bits).max().unwrap_or(0)
+    }
+
+    /// Tables whose degree is beyond what the prover accepts.
+    pub fn tables_over(&self, max_degree_bits: usize) -> Vec<Table> {
+        self.tables
+            .iter()
+            .filter(|t| t.degree_bits > max_degree_bits)
+            .map(|t| t.table)
+            .collect()
+    }
+}
+
+/// Estimates the size of every `OlaStark` table for a batch of transactions,
+/// without generating the traces. Row counts follow the trace generation in
+/// this module.
+pub fn estimate_traces(
+    txs: &[TxExeTrace],
+    config: &StarkConfig,
+    cost_model: &ProvingCostModel,
+) -> TraceEstimate {
+    let mut table_rows = TableRows::default();
+    for tx in txs {
+        table_rows.add_tx(tx);
+    }
+    let rows = table_rows.rows();
+
+    let tables: Vec<TableEstimate> = TABLES
+        .iter()
+        .map(|(table, columns, min_rows)| {
+            let filled = rows[*table as usize];
+            let padded_rows = padded_len(filled, *min_rows, *table != Table::Cpu);
+            TableEstimate {
+                table: *table,
+                columns: *columns,
+                rows: filled,
+                padded_rows,
+                degree_bits: padded_rows.trailing_zeros() as usize,
+            }
+        })
+        .collect();
+    let lde_cells: u64 = tables
+        .iter()
+        .map(|t| ((t.columns * t.padded_rows) as u64) << config.fri_config.rate_bits)
+        .sum();
+    TraceEstimate {
+        tables,
+        lde_cells,
+        proving_time: Duration::from_nanos(
+            (lde_cells as f64 * cost_model.nanos_per_lde_cell) as u64,
+        ),
+        proving_memory_bytes: (lde_cells as f64 * cost_model.bytes_per_lde_cell) as u64,
+    }
+}
+
+/// Running row counts of every table, updated one transaction at a time so
+/// that growing a batch does not re-estimate the transactions already in it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TableRows {
+    rows: [usize; NUM_TABLES],
+    programs: HashMap<ContractAddress, usize>,
+    progs_total_len: usize,
+    prog_chunk_rows: usize,
+}
+
+impl TableRows {
+    pub(crate) fn add_tx(&mut self, tx: &TxExeTrace) {
+        add_tx_rows(&mut self.rows, tx);
+        for (addr, bytecode) in &tx.programs {
+            if let Some(len) = self.programs.insert(*addr, bytecode.len()) {
+                self.progs_total_len -= len;
+                self.prog_chunk_rows -= div_ceil(len, 8);
+            }
+            self.progs_total_len += bytecode.len();
+            self.prog_chunk_rows += div_ceil(bytecode.len(), 8);
+        }
+    }
+
+    /// Rows of every table for the transactions added so far.
+    pub(crate) fn rows(&self) -> [usize; NUM_TABLES] {
+        let mut rows = self.rows;
+        // every program is hashed in prog chunk and its hash read from the tree.
+        rows[Table::Program as usize] = rows[Table::Program as usize].max(self.progs_total_len);
+        rows[Table::ProgChunk as usize] = self.prog_chunk_rows;
+        rows[Table::StorageAccess as usize] += self.programs.len() * ROOT_TREE_DEPTH;
+        rows[Table::Poseidon as usize] += self.programs.len() * ROOT_TREE_DEPTH * 2;
+        // the last row of the event table is padding.
+        rows[Table::Event as usize] += 1;
+        // the fixed tables are as long as the tables looking them up.
+        rows[Table::BitwiseFixed as usize] = rows[Table::Bitwise as usize];
+        rows[Table::RangeCheckFixed as usize] = rows[Table::RangeCheck as usize];
+        rows
+    }
+}
+
+// table, trace width and the minimal rows of its fixed columns.
+const TABLES: [(Table, usize, usize); NUM_TABLES] = [
+    (Table::Cpu, NUM_CPU_COLS, 0),
+    (Table::Memory, NUM_MEM_COLS, 0),
+    (
+        Table::Bitwise,
+        bitwise::COL_NUM_BITWISE,
+        if bitwise::BITWISE_U8_SIZE > bitwise::RANGE_CHECK_U8_SIZE {
+            bitwise::BITWISE_U8_SIZE
+        } else {
+            bitwise::RANGE_CHECK_U8_SIZE
+        },
+    ),
+    (Table::Cmp, COL_NUM_CMP, 0),
+    (
+        Table::RangeCheck,
+        rangecheck::COL_NUM_RC,
+        rangecheck::RANGE_CHECK_U16_SIZE,
+    ),
+    (Table::Poseidon, NUM_POSEIDON_COLS, 0),
+    (Table::PoseidonChunk, NUM_POSEIDON_CHUNK_COLS, 0),
+    (Table::StorageAccess, NUM_COL_ST, 0),
+    (Table::Tape, NUM_COL_TAPE, 0),
+    (Table::SCCall, NUM_COL_SCCALL, 0),
+    (Table::Program, NUM_PROG_COLS, 0),
+    (Table::ProgChunk, NUM_PROG_CHUNK_COLS, 0),
+    (Table::SigCheck, NUM_COL_SIGCHECK, 0),
+    (Table::Event, NUM_COL_EVENT, 0),
+    (
+        Table::BitwiseFixed,
+        bitwise_fixed::COL_NUM_BITWISE_FIXED,
+        bitwise::BITWISE_U8_SIZE,
+    ),
+    (
+        Table::RangeCheckFixed,
+        rangecheck_fixed::COL_NUM_RC_FIXED,
+        rangecheck::RANGE_CHECK_U16_SIZE,
+    ),
+];
+
+fn add_tx_rows(rows: &mut [usize; NUM_TABLES], tx: &TxExeTrace) {
+    let mut exec_len = 0;
+    for (_, _, _, pieces) in &tx.cpu {
+        rows[Table::Cpu as usize] += pieces.len();
+        exec_len += pieces
+            .iter()
+            .filter(|p| !p.is_ext_line)
+            .map(|p| {
+                let op1_imm = (p.instruction >> 62) & 1 == 1;
+                if op1_imm
+                    || p.opcode == OlaOpcode::MLOAD.binary_bit_mask()
+                    || p.opcode == OlaOpcode::MSTORE.binary_bit_mask()
+                {
+                    2
+                } else {
+                    1
+                }
+            })
+            .sum::<usize>();
+    }
+    rows[Table::Program as usize] += exec_len;
+
+    rows[Table::Memory as usize] += tx.env_mem.values().map(|m| m.len()).sum::<usize>();
+    rows[Table::RangeCheck as usize] += mem_rc_rows(&tx.env_mem);
+    // range checks of the rc opcode and of every comparison.
+    rows[Table::RangeCheck as usize] += tx.rc.len() + tx.cmp.len();
+    rows[Table::Bitwise as usize] += tx.bitwise.len();
+    rows[Table::Cmp as usize] += tx.cmp.len();
+
+    for p in &tx.poseidon {
+        let permutations = div_ceil(p.inputs.len(), 8);
+        rows[Table::Poseidon as usize] += permutations;
+        rows[Table::PoseidonChunk as usize] += permutations + 1;
+    }
+    // tree key hash plus the pre and post hashes along the tree path.
+    rows[Table::Poseidon as usize] += tx.storage.len() * (1 + ROOT_TREE_DEPTH * 2);
+    rows[Table::StorageAccess as usize] += tx.storage.len() * ROOT_TREE_DEPTH;
+
+    rows[Table::Tape as usize] += tx.tape.len();
+    rows[Table::SCCall as usize] += tx.sccall.len();
+    rows[Table::SigCheck as usize] += tx.sigcheck.len();
+    rows[Table::Event as usize] += tx
+        .event
+        .iter()
+        .map(|e| 1 + e.topics.len() + e.data.len())
+        .sum::<usize>();
+}
+
+// Sort and region range checks of the memory table, see `process_mem`.
+fn mem_rc_rows(env_mem: &HashMap<u64, Vec<MemExePiece>>) -> usize {
+    let mut env_idxs: Vec<&u64> = env_mem.keys().collect();
+    env_idxs.sort_unstable();
+    let mut cnt = 0;
+    let mut pre: Option<&MemExePiece> = None;
+    for env_idx in env_idxs {
+        let mut pieces: Vec<&MemExePiece> = env_mem[env_idx].iter().collect();
+        pieces.sort_unstable_by(|a, b| a.addr.cmp(&b.addr).then(a.clk.cmp(&b.clk)));
+        for m in pieces {
+            let is_region_stack = m.addr < MEM_STACK_REGION.end;
+            let is_region_heap = m.addr >= MEM_HEAP_REGION.start && m.addr < MEM_HEAP_REGION.end;
+            let is_region_prophet = m.addr >= MEM_PROPHET_REGION.start;
+            let has_sort_rc = match pre {
+                None => false,
+                Some(p) => {
+                    !is_region_prophet && (!is_region_heap || p.addr >= MEM_STACK_REGION.end)
+                }
+            };
+            cnt += has_sort_rc as usize + !is_region_stack as usize;
+            pre = Some(m);
+        }
+    }
+    cnt
+}
+
+fn padded_len(rows: usize, min_rows: usize, at_least_two: bool) -> usize {
+    let rows = rows.max(min_rows);
+    if at_least_two && rows < 2 {
+        2
+    } else {
+        rows.next_power_of_two()
+    }
+}
+
+fn div_ceil(n: usize, d: usize) -> usize {
+    (n + d - 1) / d
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        trace::exe_trace::{CmpExePiece, MemExePiece, PoseidonPiece, TxExeTrace},
+        vm::{hardware::ExeContext, opcodes::OlaOpcode},
+    };
+    use std::collections::HashMap;
+
+    use crate::{
+        generation::pre_process::tests::cpu_piece,
+        stark::{config::StarkConfig, ola_stark::Table},
+    };
+
+    use super::{estimate_traces, ProvingCostModel};
+
+    #[test]
+    fn test_estimate_traces() {
+        let mem = |addr: u64, clk: u64| MemExePiece {
+            clk,
+            addr,
+            value: 0,
+            is_write: true,
+            opcode: Some(OlaOpcode::MSTORE),
+        };
+        let tx = TxExeTrace {
+            programs: vec![([0, 0, 0, 1], vec![0; 20])],
+            cpu: vec![(
+                0,
+                0,
+                ExeContext::default(),
+                vec![
+                    cpu_piece(0, 0, OlaOpcode::MSTORE, false),
+                    cpu_piece(1, 1, OlaOpcode::ADD, false),
+                    cpu_piece(2, 2, OlaOpcode::POSEIDON, false),
+                    cpu_piece(2, 2, OlaOpcode::POSEIDON, true),
+                    cpu_piece(3, 3, OlaOpcode::END, false),
+                ],
+            )],
+            env_mem: HashMap::from([(0, vec![mem(10, 0), mem(10, 5), mem(12, 1)])]),
+            rc: vec![],
+            bitwise: vec![],
+            cmp: vec![CmpExePiece {
+                op0: 2,
+                op1: 1,
+                is_gte: true,
+            }],
+            poseidon: vec![PoseidonPiece {
+                env_idx: 0,
+                clk: 2,
+                src_addr: 0,
+                len: 9,
+                dst_addr: 0,
+                inputs: vec![0; 9],
+            }],
+            storage: vec![],
+            tape: vec![],
+            sccall: vec![],
+            sigcheck: vec![],
+            event: vec![],
+        };
+        let estimate = estimate_traces(
+            &[tx],
+            &StarkConfig::standard_fast_config(),
+            &ProvingCostModel::default(),
+        );
+        let table = |table: Table| estimate.tables[table as usize];
+
+        assert_eq!(table(Table::Cpu).rows, 5);
+        assert_eq!(table(Table::Cpu).padded_rows, 8);
+        assert_eq!(table(Table::Memory).rows, 3);
+        assert_eq!(table(Table::Memory).padded_rows, 4);
+        // two sort range checks of the memory rows plus the comparison.
+        assert_eq!(table(Table::RangeCheck).rows, 3);
+        assert_eq!(table(Table::RangeCheck).degree_bits, 16);
+        assert_eq!(table(Table::Cmp).rows, 1);
+        assert_eq!(table(Table::Cmp).padded_rows, 2);
+        assert_eq!(table(Table::PoseidonChunk).rows, 3);
+        // 2 permutations and the program hash read along the tree path.
+        assert_eq!(table(Table::Poseidon).rows, 2 + 256 * 2);
+        assert_eq!(table(Table::StorageAccess).rows, 256);
+        // the program is longer than the executed instructions.
+        assert_eq!(table(Table::Program).rows, 20);
+        assert_eq!(table(Table::ProgChunk).rows, 3);
+        assert_eq!(estimate.max_degree_bits(), 18);
+        assert_eq!(
+            estimate.tables_over(16),
+            vec![Table::Bitwise, Table::BitwiseFixed]
+        );
+        assert!(estimate.proving_time.as_nanos() > 0);
+    }
+}
@@ -0,0 +1,71 @@
+use core::{trace::trace::EventRow, types::PrimeField64};
+
+use plonky2::hash::hash_types::RichField;
+
+use crate::builtins::event::columns::*;
+
+fn num_rows(event: &EventRow) -> usize {
+    1 + event.topics.len() + event.data.len()
+}
+
+pub fn generate_event_trace<F: RichField>(cells: &[EventRow]) -> [Vec<F>; NUM_COL_EVENT] {
+    gen_event_trace(&vec![cells.to_vec()])
+}
+
+pub fn gen_event_trace<F: RichField>(batch_cells: &Vec<Vec<EventRow>>) -> [Vec<F>; NUM_COL_EVENT] {
+    let num_filled_row_len: usize = batch_cells.iter().flatten().map(num_rows).sum();
+    // the last row must be padding.
+    let num_padded_rows = (num_filled_row_len + 1).next_power_of_two().max(2);
+
+    let fe = |v: u64| F::from_canonical_u64(v);
+    let mut trace: Vec<Vec<F>> = vec![vec![F::ZERO; num_padded_rows]; NUM_COL_EVENT];
+    let mut i = 0;
+    let mut event_idx = 0;
+    for (tx_idx, events) in batch_cells.iter().enumerate() {
+        for event in events {
+            let topic_len = event.topics.len();
+            let data_len = event.data.len();
+            for row in i..i + num_rows(event) {
+                trace[COL_EVENT_TX_IDX][row] = F::from_canonical_usize(tx_idx);
+                trace[COL_EVENT_ENV_IDX][row] = fe(event.env_idx.to_canonical_u64());
+                trace[COL_EVENT_CLK][row] = fe(event.clk.to_canonical_u64());
+                trace[COL_EVENT_OP0][row] = fe(event.op0.to_canonical_u64());
+                trace[COL_EVENT_OP1][row] = fe(event.op1.to_canonical_u64());
+                trace[COL_EVENT_TOPIC_LEN][row] = F::from_canonical_usize(topic_len);
+                trace[COL_EVENT_DATA_LEN][row] = F::from_canonical_usize(data_len);
+                trace[COL_EVENT_EVENT_IDX][row] = F::from_canonical_usize(event_idx);
+            }
+
+            trace[COL_EVENT_IS_HEAD][i] = F::ONE;
+            trace[COL_EVENT_W.start][i] = F::from_canonical_usize(topic_len);
+            trace[COL_EVENT_W.start + 1][i] = F::from_canonical_usize(data_len);
+            i += 1;
+
+            for (j, (addr, topic)) in event.topic_addrs.iter().zip(&event.topics).enumerate() {
+                trace[COL_EVENT_S_TOPIC][i] = F::ONE;
+                trace[COL_EVENT_IDX][i] = F::from_canonical_usize(j);
+                trace[COL_EVENT_TOPIC_PTR][i] = fe(addr.to_canonical_u64());
+                for (k, w) in topic.iter().enumerate() {
+                    trace[COL_EVENT_W.start + k][i] = fe(w.to_canonical_u64());
+                }
+                i += 1;
+            }
+
+            for (j, data) in event.data.iter().enumerate() {
+                trace[COL_EVENT_S_DATA][i] = F::ONE;
+                trace[COL_EVENT_IDX][i] = F::from_canonical_usize(j);
+                trace[COL_EVENT_W.start][i] = fe(data.to_canonical_u64());
+                i += 1;
+            }
+            event_idx += 1;
+        }
+    }
+
+    trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
+        panic!(
+            "Expected a Vec of length {} but it was {}",
+            NUM_COL_EVENT,
+            v.len()
+        )
+    })
+}
@@ -0,0 +1,66 @@
+use core::program::instruction::Opcode;
+use plonky2::hash::hash_types::RichField;
+
+use crate::builtins::bitwise::columns::{
+    BITWISE_U8_SIZE, BITWISE_U8_SIZE_PER, RANGE_CHECK_U8_SIZE,
+};
+use crate::builtins::rangecheck::columns::RANGE_CHECK_U16_SIZE;
+use crate::fixed_table::bitwise_fixed::columns as bitwise_fixed;
+use crate::fixed_table::rangecheck_fixed::columns as rangecheck_fixed;
+
+/// Generates the AND, OR and XOR tables over u8 operands, one block of 2^16
+/// rows per operation, followed by all zero entries up to `num_rows`. The u8
+/// column counts up to 2^8 - 1 and repeats it to the end.
+pub fn generate_bitwise_fixed_trace<F: RichField>(
+    num_rows: usize,
+) -> [Vec<F>; bitwise_fixed::COL_NUM_BITWISE_FIXED] {
+    assert!(num_rows >= BITWISE_U8_SIZE && num_rows.is_power_of_two());
+    let mut trace: Vec<Vec<F>> =
+        vec![vec![F::ZERO; num_rows]; bitwise_fixed::COL_NUM_BITWISE_FIXED];
+    let ops: [(Opcode, usize, fn(usize, usize) -> usize); 3] = [
+        (Opcode::AND, bitwise_fixed::SEL_AND, |a, b| a & b),
+        (Opcode::OR, bitwise_fixed::SEL_OR, |a, b| a | b),
+        (Opcode::XOR, bitwise_fixed::SEL_XOR, |a, b| a ^ b),
+    ];
+    for (k, (op, sel, eval)) in ops.into_iter().enumerate() {
+        for op0 in 0..RANGE_CHECK_U8_SIZE {
+            for op1 in 0..RANGE_CHECK_U8_SIZE {
+                let i = k * BITWISE_U8_SIZE_PER + op0 * RANGE_CHECK_U8_SIZE + op1;
+                trace[bitwise_fixed::TAG][i] = F::from_canonical_u64(1_u64 << op as u8);
+                trace[bitwise_fixed::OP0][i] = F::from_canonical_usize(op0);
+                trace[bitwise_fixed::OP1][i] = F::from_canonical_usize(op1);
+                trace[bitwise_fixed::RES][i] = F::from_canonical_usize(eval(op0, op1));
+                for j in 0..8 {
+                    trace[bitwise_fixed::OP0_BITS.start + j][i] =
+                        F::from_canonical_usize((op0 >> j) & 1);
+                    trace[bitwise_fixed::OP1_BITS.start + j][i] =
+                        F::from_canonical_usize((op1 >> j) & 1);
+                }
+                trace[sel][i] = F::ONE;
+            }
+        }
+    }
+    for i in 0..num_rows {
+        trace[bitwise_fixed::RANGE_CHECK_U8][i] =
+            F::from_canonical_usize(i.min(RANGE_CHECK_U8_SIZE - 1));
+    }
+
+    trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
+        panic!(
+            "Expected a Vec of length {} but it was {}",
+            bitwise_fixed::COL_NUM_BITWISE_FIXED,
+            v.len()
+        )
+    })
+}
+
+/// Generates the values 0..2^16, the last one repeated up to `num_rows`.
+pub fn generate_rc_fixed_trace<F: RichField>(
+    num_rows: usize,
+) -> [Vec<F>; rangecheck_fixed::COL_NUM_RC_FIXED] {
+    assert!(num_rows >= RANGE_CHECK_U16_SIZE && num_rows.is_power_of_two());
+    let val = (0..num_rows)
+        .map(|i| F::from_canonical_usize(i.min(RANGE_CHECK_U16_SIZE - 1)))
+        .collect();
+    [val]
+}
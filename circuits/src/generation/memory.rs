@@ -46,6 +46,11 @@ pub fn generate_memory_trace<F: RichField>(
     );
     opcode_to_selector.insert(OlaOpcode::SLOAD.binary_bit_mask(), memory::COL_MEM_S_SLOAD);
     opcode_to_selector.insert(0, memory::COL_MEM_S_PROPHET);
+    opcode_to_selector.insert(
+        OlaOpcode::SIGCHECK.binary_bit_mask(),
+        memory::COL_MEM_S_SIGCHECK,
+    );
+    opcode_to_selector.insert(OlaOpcode::EVENT.binary_bit_mask(), memory::COL_MEM_S_EVENT);
 
     let mut trace: Vec<Vec<F>> = vec![vec![F::ZERO; num_padded_rows]; memory::NUM_MEM_COLS];
     for (i, c) in cells.iter().enumerate() {
@@ -195,6 +200,11 @@ pub fn gen_memory_trace<F: RichField>(
     );
     opcode_to_selector.insert(OlaOpcode::SLOAD.binary_bit_mask(), memory::COL_MEM_S_SLOAD);
     opcode_to_selector.insert(0, memory::COL_MEM_S_PROPHET);
+    opcode_to_selector.insert(
+        OlaOpcode::SIGCHECK.binary_bit_mask(),
+        memory::COL_MEM_S_SIGCHECK,
+    );
+    opcode_to_selector.insert(OlaOpcode::EVENT.binary_bit_mask(), memory::COL_MEM_S_EVENT);
 
     let mut trace: Vec<Vec<F>> = vec![vec![F::ZERO; num_padded_rows]; memory::NUM_MEM_COLS];
     let mut i: usize = 0;
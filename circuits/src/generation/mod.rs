@@ -1,108 +1,77 @@
 //use std::collections::HashMap;
 
+use core::merkle_tree::tree::AccountTree;
 use core::program::Program;
-use core::types::merkle_tree::decode_addr;
+use core::trace::exe_trace::TxExeTrace;
+use core::trace::trace::{EventRow, SigCheckRow, TapeRow, Trace};
+use core::types::merkle_tree::{decode_addr, TreeValue};
+use core::types::{Field, GoldilocksField, PrimeField64};
+use itertools::Itertools;
 use std::collections::HashMap;
 
 use std::sync::mpsc::channel;
 use std::thread;
 
-use eth_trie_utils::partial_trie::HashedPartialTrie;
-use ethereum_types::{Address, H256};
-
-//use eth_trie_utils::partial_trie::PartialTrie;
 use plonky2::field::extension::Extendable;
 use plonky2::field::polynomial::PolynomialValues;
 use plonky2::hash::hash_types::RichField;
-use serde::{Deserialize, Serialize};
 
 use crate::stark::ola_stark::{OlaStark, NUM_TABLES};
-use crate::stark::proof::{BlockMetadata, PublicValues, TrieRoots};
+use crate::stark::proof::{EventLog, PublicValues, SigCheck};
 use crate::stark::util::trace_to_poly_values;
 
-use self::builtin::{generate_bitwise_trace, generate_cmp_trace, generate_rc_trace};
-use self::cpu::generate_cpu_trace;
-use self::memory::generate_memory_trace;
-use self::poseidon::generate_poseidon_trace;
-use self::poseidon_chunk::generate_poseidon_chunk_trace;
-use self::sccall::generate_sccall_trace;
+use self::builtin::{
+    gen_bitwise_trace, gen_cmp_trace, gen_rc_trace, generate_bitwise_trace, generate_cmp_trace,
+    generate_rc_trace,
+};
+use self::cpu::{gen_cpu_trace, generate_cpu_trace};
+use self::event::{gen_event_trace, generate_event_trace};
+use self::fixed_table::{generate_bitwise_fixed_trace, generate_rc_fixed_trace};
+use self::memory::{gen_memory_trace, generate_memory_trace};
+use self::poseidon::{gen_poseidon_trace, generate_poseidon_trace};
+use self::poseidon_chunk::{gen_poseidon_chunk_trace, generate_poseidon_chunk_trace};
+use self::pre_process::pre_process;
+use self::sccall::{gen_sccall_trace, generate_sccall_trace};
+use self::sigcheck::{gen_sigcheck_trace, generate_sigcheck_trace};
 use self::storage::generate_storage_access_trace;
-use self::tape::generate_tape_trace;
+use self::tape::{gen_tape_trace, generate_tape_trace, tape_public_values};
 
 pub mod builtin;
 pub mod cpu;
 mod ctl_test;
+pub mod estimate;
+pub mod event;
+pub mod fixed_table;
 pub mod memory;
 pub mod poseidon;
 pub mod poseidon_chunk;
 pub mod prog;
 pub mod sccall;
+pub mod segment;
+pub mod sigcheck;
 pub mod storage;
 pub mod tape;
 
-mod pre_process;
-
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
-/// Inputs needed for trace generation.
-pub struct GenerationInputs {
-    pub signed_txns: Vec<Vec<u8>>,
-    pub tries: TrieInputs,
-    pub trie_roots_after: TrieRoots,
-    pub contract_code: HashMap<H256, Vec<u8>>,
-    pub block_metadata: BlockMetadata,
-    pub addresses: Vec<Address>,
-}
-
-#[derive(Clone, Debug, Deserialize, Serialize, Default)]
-pub struct TrieInputs {
-    /// A partial version of the state trie prior to these transactions. It
-    /// should include all nodes that will be accessed by these
-    /// transactions.
-    pub state_trie: HashedPartialTrie,
-
-    /// A partial version of the transaction trie prior to these transactions.
-    /// It should include all nodes that will be accessed by these
-    /// transactions.
-    pub transactions_trie: HashedPartialTrie,
-
-    /// A partial version of the receipt trie prior to these transactions. It
-    /// should include all nodes that will be accessed by these
-    /// transactions.
-    pub receipts_trie: HashedPartialTrie,
-
-    /// A partial version of each storage trie prior to these transactions. It
-    /// should include all storage tries, and nodes therein, that will be
-    /// accessed by these transactions.
-    pub storage_tries: Vec<(H256, HashedPartialTrie)>,
-}
+pub mod pre_process;
 
-// #[derive(Debug, Clone)]
-// pub struct TxExeTrace {
-//     pub programs: Vec<(ContractAddress, Vec<u64>)>, // contract address to
-// bytecode     pub cpu: Vec<(u64, u64, ExeContext, Vec<CpuExePiece>)>, /*
-// call_sc_cnt, env_idx, context, trace.
-//                                                      * Sorted by execution
-//                                                        env. */
-//     pub env_mem: HashMap<u64, Vec<MemExePiece>>, // env_id to mem, mem not
-// sorted yet.     pub rc: Vec<RcExePiece>,                     /* rc only
-// triggered by range_check
-//                                                   * opcode. */
-//     pub bitwise: Vec<BitwiseExePiece>,
-//     pub cmp: Vec<CmpExePiece>,
-//     pub poseidon: Vec<PoseidonPiece>, // poseidon only triggered by poseidon
-// opcode.     pub storage: Vec<StorageExePiece>,
-//     pub tape: Vec<TapeExePiece>,
-// }
+#[derive(Clone, Debug, Default)]
+/// Inputs needed for trace generation besides the execution trace. Public
+/// values, events included, are all read from the trace.
+pub struct GenerationInputs {}
 
 pub(crate) struct CpuSnapshot {}
 
 pub(crate) struct BatchTxSnapshot {}
 
+/// Generates the traces of a program run by the `Process` executor. It does
+/// not record `sigcheck` and `event` rows, programs using them are only
+/// provable through `generate_batch_traces`.
 pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
     mut program: Program,
     ola_stark: &mut OlaStark<F, D>,
-    inputs: GenerationInputs,
+    _inputs: GenerationInputs,
 ) -> ([Vec<PolynomialValues<F>>; NUM_TABLES], PublicValues) {
+    let start_end_roots = program.trace.start_end_roots;
     let (cpu_tx, cpu_rx) = channel();
     let exec = std::mem::replace(&mut program.trace.exec, Vec::new());
     let exec_for_cpu = exec.clone();
@@ -119,11 +88,14 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
     });
 
     let (bitwise_tx, bitwise_rx) = channel();
+    let (bitwise_fixed_tx, bitwise_fixed_rx) = channel();
     let builtin_bitwise_combined =
         std::mem::replace(&mut program.trace.builtin_bitwise_combined, Vec::new());
     thread::spawn(move || {
-        let (bitwise_rows, bitwise_beta) = generate_bitwise_trace::<F>(&builtin_bitwise_combined);
-        let _ = bitwise_tx.send((trace_to_poly_values(bitwise_rows), bitwise_beta));
+        let bitwise_rows = generate_bitwise_trace::<F>(&builtin_bitwise_combined);
+        let bitwise_fixed_rows = generate_bitwise_fixed_trace::<F>(bitwise_rows[0].len());
+        let _ = bitwise_tx.send(trace_to_poly_values(bitwise_rows));
+        let _ = bitwise_fixed_tx.send(trace_to_poly_values(bitwise_fixed_rows));
     });
 
     let (cmp_tx, cmp_rx) = channel();
@@ -134,10 +106,13 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
     });
 
     let (rc_tx, rc_rx) = channel();
+    let (rc_fixed_tx, rc_fixed_rx) = channel();
     let builtin_rangecheck = std::mem::replace(&mut program.trace.builtin_rangecheck, Vec::new());
     thread::spawn(move || {
         let rc_rows = generate_rc_trace(&builtin_rangecheck);
+        let rc_fixed_rows = generate_rc_fixed_trace::<F>(rc_rows[0].len());
         let _ = rc_tx.send(trace_to_poly_values(rc_rows));
+        let _ = rc_fixed_tx.send(trace_to_poly_values(rc_fixed_rows));
     });
 
     let (poseidon_tx, poseidon_rx) = channel();
@@ -161,14 +136,23 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
         std::mem::replace(&mut program.trace.builtin_storage_hash, Vec::new());
     let builtin_program_hash =
         std::mem::replace(&mut program.trace.builtin_program_hash, Vec::new());
+    let root_after = start_end_roots.1;
     thread::spawn(move || {
         let storage_access_rows =
-            generate_storage_access_trace(&builtin_storage_hash, &builtin_program_hash);
+            generate_storage_access_trace(&builtin_storage_hash, &builtin_program_hash, root_after);
         let _ = storage_tx.send(trace_to_poly_values(storage_access_rows));
     });
 
     let (tape_tx, tape_rx) = channel();
     let tape = std::mem::replace(&mut program.trace.tape, Vec::new());
+    let builtin_sigcheck = std::mem::replace(&mut program.trace.builtin_sigcheck, Vec::new());
+    let event = std::mem::replace(&mut program.trace.event, Vec::new());
+    let public_values = public_values(
+        start_end_roots,
+        std::slice::from_ref(&tape),
+        std::slice::from_ref(&builtin_sigcheck),
+        std::slice::from_ref(&event),
+    );
     thread::spawn(move || {
         let tape_rows = generate_tape_trace(&tape);
         let _ = tape_tx.send(trace_to_poly_values(tape_rows));
@@ -181,6 +165,18 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
         let _ = sccall_tx.send(trace_to_poly_values(sccall_rows));
     });
 
+    let (sigcheck_tx, sigcheck_rx) = channel();
+    thread::spawn(move || {
+        let sigcheck_rows = generate_sigcheck_trace(&builtin_sigcheck);
+        let _ = sigcheck_tx.send(trace_to_poly_values(sigcheck_rows));
+    });
+
+    let (event_tx, event_rx) = channel();
+    thread::spawn(move || {
+        let event_rows = generate_event_trace(&event);
+        let _ = event_tx.send(trace_to_poly_values(event_rows));
+    });
+
     let (program_tx, program_rx) = channel();
     let progs = program
         .trace
@@ -190,9 +186,8 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
         .collect::<Vec<_>>();
     let progs_for_program = progs.clone();
     thread::spawn(move || {
-        let (program_rows, program_beta) =
-            prog::generate_prog_trace::<F>(&exec, progs_for_program, program.trace.start_end_roots);
-        let _ = program_tx.send((trace_to_poly_values(program_rows), program_beta));
+        let program_rows = prog::generate_prog_trace::<F>(&exec, progs_for_program);
+        let _ = program_tx.send(trace_to_poly_values(program_rows));
     });
 
     let (prog_chunk_tx, prog_chunk_rx) = channel();
@@ -201,21 +196,178 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
         let _ = prog_chunk_tx.send(trace_to_poly_values(prog_chunk_rows));
     });
 
-    let (bitwise_trace, bitwise_beta) = bitwise_rx.recv().unwrap();
     ola_stark
-        .bitwise_stark
-        .set_compress_challenge(bitwise_beta)
+        .storage_access_stark
+        .set_state_roots(
+            public_values.state_root_before,
+            public_values.state_root_after,
+        )
         .unwrap();
-    let (program_trace, program_beta) = program_rx.recv().unwrap();
+
+    let traces = [
+        cpu_rx.recv().unwrap(),
+        memory_rx.recv().unwrap(),
+        bitwise_rx.recv().unwrap(),
+        cmp_rx.recv().unwrap(),
+        rc_rx.recv().unwrap(),
+        poseidon_rx.recv().unwrap(),
+        poseidon_chunk_rx.recv().unwrap(),
+        storage_rx.recv().unwrap(),
+        tape_rx.recv().unwrap(),
+        sccall_rx.recv().unwrap(),
+        program_rx.recv().unwrap(),
+        prog_chunk_rx.recv().unwrap(),
+        sigcheck_rx.recv().unwrap(),
+        event_rx.recv().unwrap(),
+        bitwise_fixed_rx.recv().unwrap(),
+        rc_fixed_rx.recv().unwrap(),
+    ];
+
+    (traces, public_values)
+}
+
+/// Generates the traces of a batch of transactions executed by the
+/// `OlaContractExecutor`. Storage accesses are replayed on `account_tree`,
+/// which must hold the state the batch started from.
+pub fn generate_batch_traces<F: RichField + Extendable<D>, const D: usize>(
+    txs: Vec<TxExeTrace>,
+    account_tree: &mut AccountTree,
+    ola_stark: &mut OlaStark<F, D>,
+    _inputs: GenerationInputs,
+) -> ([Vec<PolynomialValues<F>>; NUM_TABLES], PublicValues) {
+    let mut traces = pre_process(txs, account_tree);
+    let root = account_tree.root_hash();
+    let start_end_roots = traces.first().map_or((root, root), |t| t.start_end_roots);
+
+    let (cpu_tx, cpu_rx) = channel();
+    let exec = take_rows(&mut traces, |t| &mut t.exec);
+    let exec_for_program: Vec<_> = exec.concat();
+    thread::spawn(move || {
+        let cpu_rows = gen_cpu_trace::<F>(&exec);
+        let _ = cpu_tx.send(trace_to_poly_values(cpu_rows));
+    });
+
+    let (memory_tx, memory_rx) = channel();
+    let memory = take_rows(&mut traces, |t| &mut t.memory);
+    thread::spawn(move || {
+        let memory_rows = gen_memory_trace::<F>(&memory);
+        let _ = memory_tx.send(trace_to_poly_values(memory_rows));
+    });
+
+    let (bitwise_tx, bitwise_rx) = channel();
+    let (bitwise_fixed_tx, bitwise_fixed_rx) = channel();
+    let builtin_bitwise_combined = take_rows(&mut traces, |t| &mut t.builtin_bitwise_combined);
+    thread::spawn(move || {
+        let bitwise_rows = gen_bitwise_trace::<F>(&builtin_bitwise_combined);
+        let bitwise_fixed_rows = generate_bitwise_fixed_trace::<F>(bitwise_rows[0].len());
+        let _ = bitwise_tx.send(trace_to_poly_values(bitwise_rows));
+        let _ = bitwise_fixed_tx.send(trace_to_poly_values(bitwise_fixed_rows));
+    });
+
+    let (cmp_tx, cmp_rx) = channel();
+    let builtin_cmp = take_rows(&mut traces, |t| &mut t.builtin_cmp);
+    thread::spawn(move || {
+        let cmp_rows = gen_cmp_trace(&builtin_cmp);
+        let _ = cmp_tx.send(trace_to_poly_values(cmp_rows));
+    });
+
+    let (rc_tx, rc_rx) = channel();
+    let (rc_fixed_tx, rc_fixed_rx) = channel();
+    let builtin_rangecheck = take_rows(&mut traces, |t| &mut t.builtin_rangecheck);
+    thread::spawn(move || {
+        let rc_rows = gen_rc_trace(&builtin_rangecheck);
+        let rc_fixed_rows = generate_rc_fixed_trace::<F>(rc_rows[0].len());
+        let _ = rc_tx.send(trace_to_poly_values(rc_rows));
+        let _ = rc_fixed_tx.send(trace_to_poly_values(rc_fixed_rows));
+    });
+
+    let (poseidon_tx, poseidon_rx) = channel();
+    let builtin_poseidon = take_rows(&mut traces, |t| &mut t.builtin_poseidon);
+    thread::spawn(move || {
+        let poseidon_rows = gen_poseidon_trace(&builtin_poseidon);
+        let _ = poseidon_tx.send(trace_to_poly_values(poseidon_rows));
+    });
+
+    let (poseidon_chunk_tx, poseidon_chunk_rx) = channel();
+    let builtin_poseidon_chunk = take_rows(&mut traces, |t| &mut t.builtin_poseidon_chunk);
+    thread::spawn(move || {
+        let poseidon_chunk_rows = gen_poseidon_chunk_trace(&builtin_poseidon_chunk);
+        let _ = poseidon_chunk_tx.send(trace_to_poly_values(poseidon_chunk_rows));
+    });
+
+    let (storage_tx, storage_rx) = channel();
+    let builtin_storage_hash = take_rows(&mut traces, |t| &mut t.builtin_storage_hash).concat();
+    let builtin_program_hash = take_rows(&mut traces, |t| &mut t.builtin_program_hash).concat();
+    let root_after = start_end_roots.1;
+    thread::spawn(move || {
+        let storage_access_rows =
+            generate_storage_access_trace(&builtin_storage_hash, &builtin_program_hash, root_after);
+        let _ = storage_tx.send(trace_to_poly_values(storage_access_rows));
+    });
+
+    let (tape_tx, tape_rx) = channel();
+    let tape = take_rows(&mut traces, |t| &mut t.tape);
+    let builtin_sigcheck = take_rows(&mut traces, |t| &mut t.builtin_sigcheck);
+    let event = take_rows(&mut traces, |t| &mut t.event);
+    let public_values = public_values(start_end_roots, &tape, &builtin_sigcheck, &event);
+    thread::spawn(move || {
+        let tape_rows = gen_tape_trace(&tape);
+        let _ = tape_tx.send(trace_to_poly_values(tape_rows));
+    });
+
+    let (sccall_tx, sccall_rx) = channel();
+    let sc_call = take_rows(&mut traces, |t| &mut t.sc_call);
+    thread::spawn(move || {
+        let sccall_rows = gen_sccall_trace(&sc_call);
+        let _ = sccall_tx.send(trace_to_poly_values(sccall_rows));
+    });
+
+    let (sigcheck_tx, sigcheck_rx) = channel();
+    thread::spawn(move || {
+        let sigcheck_rows = gen_sigcheck_trace(&builtin_sigcheck);
+        let _ = sigcheck_tx.send(trace_to_poly_values(sigcheck_rows));
+    });
+
+    let (event_tx, event_rx) = channel();
+    thread::spawn(move || {
+        let event_rows = gen_event_trace(&event);
+        let _ = event_tx.send(trace_to_poly_values(event_rows));
+    });
+
+    // a contract called by several transactions is in the program table once.
+    let mut addr_program_hash = HashMap::new();
+    for trace in traces {
+        addr_program_hash.extend(trace.addr_program_hash);
+    }
+    let (program_tx, program_rx) = channel();
+    let progs = addr_program_hash
+        .into_iter()
+        .map(|(addr, hash)| (decode_addr(addr), hash))
+        .collect::<Vec<_>>();
+    let progs_for_program = progs.clone();
+    thread::spawn(move || {
+        let program_rows = prog::generate_prog_trace::<F>(&exec_for_program, progs_for_program);
+        let _ = program_tx.send(trace_to_poly_values(program_rows));
+    });
+
+    let (prog_chunk_tx, prog_chunk_rx) = channel();
+    thread::spawn(move || {
+        let prog_chunk_rows = prog::generate_prog_chunk_trace::<F>(progs);
+        let _ = prog_chunk_tx.send(trace_to_poly_values(prog_chunk_rows));
+    });
+
     ola_stark
-        .program_stark
-        .set_compress_challenge(program_beta)
+        .storage_access_stark
+        .set_state_roots(
+            public_values.state_root_before,
+            public_values.state_root_after,
+        )
         .unwrap();
 
     let traces = [
         cpu_rx.recv().unwrap(),
         memory_rx.recv().unwrap(),
-        bitwise_trace,
+        bitwise_rx.recv().unwrap(),
         cmp_rx.recv().unwrap(),
         rc_rx.recv().unwrap(),
         poseidon_rx.recv().unwrap(),
@@ -223,15 +375,68 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
         storage_rx.recv().unwrap(),
         tape_rx.recv().unwrap(),
         sccall_rx.recv().unwrap(),
-        program_trace,
+        program_rx.recv().unwrap(),
         prog_chunk_rx.recv().unwrap(),
+        sigcheck_rx.recv().unwrap(),
+        event_rx.recv().unwrap(),
+        bitwise_fixed_rx.recv().unwrap(),
+        rc_fixed_rx.recv().unwrap(),
     ];
 
-    // TODO: update trie_roots_before & trie_roots_after
-    let public_values = PublicValues {
-        trie_roots_before: TrieRoots::default(),
-        trie_roots_after: TrieRoots::default(),
-        block_metadata: inputs.block_metadata,
-    };
     (traces, public_values)
 }
+
+fn public_values(
+    start_end_roots: (TreeValue, TreeValue),
+    tape: &[Vec<TapeRow>],
+    sigcheck: &[Vec<SigCheckRow>],
+    event: &[Vec<EventRow>],
+) -> PublicValues {
+    let (block_metadata, tx_hashes) = tape_public_values(tape);
+    let to_u64s = |v: &[GoldilocksField]| v.iter().map(|x| x.to_canonical_u64()).collect_vec();
+    let events = event
+        .iter()
+        .enumerate()
+        .flat_map(|(tx_idx, rows)| {
+            rows.iter().map(move |row| EventLog {
+                tx_idx: tx_idx as u64,
+                topics: row
+                    .topics
+                    .iter()
+                    .map(|topic| to_u64s(topic).try_into().unwrap())
+                    .collect(),
+                data: to_u64s(&row.data),
+            })
+        })
+        .collect();
+    let sig_checks = sigcheck
+        .concat()
+        .iter()
+        .map(|row| {
+            let part = |i: usize| to_u64s(&row.inputs[4 * i..4 * i + 4]).try_into().unwrap();
+            SigCheck {
+                msg_hash: part(0),
+                pubkey_x: part(1),
+                pubkey_y: part(2),
+                r: part(3),
+                s: part(4),
+                verified: row.res == GoldilocksField::ONE,
+            }
+        })
+        .collect();
+    PublicValues {
+        state_root_before: start_end_roots.0.map(|x| x.0),
+        state_root_after: start_end_roots.1.map(|x| x.0),
+        block_metadata,
+        tx_hashes,
+        events,
+        sig_checks,
+    }
+}
+
+fn take_rows<T>(traces: &mut [Trace], rows: impl Fn(&mut Trace) -> &mut Vec<T>) -> Vec<Vec<T>> {
+    traces
+        .iter_mut()
+        .map(|trace| std::mem::take(rows(trace)))
+        .collect()
+}
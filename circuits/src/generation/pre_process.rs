@@ -1,20 +1,27 @@
 use core::{
     crypto::poseidon_trace::{
-        calculate_arbitrary_poseidon_and_generate_intermediate_trace,
+        calculate_arbitrary_poseidon, calculate_arbitrary_poseidon_and_generate_intermediate_trace,
         calculate_poseidon_and_generate_intermediate_trace,
     },
+    merkle_tree::{
+        log::{StorageLog, StorageLogKind, WitnessStorageLog},
+        tree::AccountTree,
+    },
+    program::Program,
     trace::{
         exe_trace::{
-            BitwiseExePiece, CmpExePiece, CpuExePiece, MemExePiece, PoseidonPiece, RcExePiece,
-            SCCallPiece, StorageExePiece, TapeExePiece, TxExeTrace,
+            BitwiseExePiece, CmpExePiece, CpuExePiece, EventPiece, MemExePiece, PoseidonPiece,
+            RcExePiece, SCCallPiece, SigCheckPiece, StorageExePiece, TapeExePiece, TxExeTrace,
         },
         trace::{
-            BitwiseCombinedRow, CmpRow, MemoryTraceCell, PoseidonChunkRow, PoseidonRow,
-            RangeCheckRow, RegisterSelector, SCCallRow, Step, TapeRow, Trace,
+            BitwiseCombinedRow, CmpRow, EventRow, MemoryTraceCell, PoseidonChunkRow, PoseidonRow,
+            RangeCheckRow, RegisterSelector, SCCallRow, SigCheckRow, Step, TapeRow, Trace,
         },
     },
-    types::{merkle_tree::encode_addr, Field, GoldilocksField},
-    utils::split_u16_limbs_from_field,
+    types::{
+        merkle_tree::{encode_addr, tree_key_default},
+        Field, GoldilocksField,
+    },
     vm::{
         hardware::{
             ContractAddress, ExeContext, MEM_HEAP_REGION, MEM_PROPHET_REGION, MEM_STACK_REGION,
@@ -22,58 +29,56 @@ use core::{
         opcodes::OlaOpcode,
     },
 };
+use executor::trace::storage_hash_table_gen;
 use std::{cmp::min, collections::HashMap};
 
-// #[derive(Debug, Clone)]
-// pub struct TxExeTrace {
-//     pub programs: Vec<(ContractAddress, Vec<u64>)>, // contract address to
-// bytecode     pub cpu: Vec<(u64, u64, ExeContext, Vec<CpuExePiece>)>, /*
-// call_sc_cnt, env_idx, context, trace.
-//                                                      * Sorted by execution
-//                                                        env. */
-//     pub env_mem: HashMap<u64, Vec<MemExePiece>>, // env_id to mem, mem not
-// sorted yet.     pub rc: Vec<RcExePiece>,                     /* rc only
-// triggered by range_check
-//                                                   * opcode. */
-//     pub bitwise: Vec<BitwiseExePiece>,
-//     pub cmp: Vec<CmpExePiece>,
-//     pub poseidon: Vec<PoseidonPiece>, // poseidon only triggered by poseidon
-// opcode.     pub storage: Vec<StorageExePiece>,
-//     pub tape: Vec<TapeExePiece>,
-// }
-
-// pub struct Trace {
-//     //(inst_asm_str, imm_flag, step, inst_encode, imm_val)
-//     pub instructions: HashMap<u64, (String, u8, u64, GoldilocksField,
-// GoldilocksField)>,     // pub raw_instructions: HashMap<u64, Instruction>,
-//     pub raw_instructions: HashMap<u64, String>,
-//     pub raw_binary_instructions: Vec<String>,
-//     pub addr_program_hash: HashMap<String, Vec<GoldilocksField>>,
-//     pub start_end_roots: (TreeValue, TreeValue),
-//     // todo need limit the trace size
-//     pub exec: Vec<Step>,
-//     pub memory: Vec<MemoryTraceCell>,
-//     pub builtin_rangecheck: Vec<RangeCheckRow>,
-//     pub builtin_bitwise_combined: Vec<BitwiseCombinedRow>,
-//     pub builtin_cmp: Vec<CmpRow>,
-//     pub builtin_poseidon: Vec<PoseidonRow>,
-//     pub builtin_poseidon_chunk: Vec<PoseidonChunkRow>,
-//     pub builtin_storage: Vec<StorageRow>,
-//     pub builtin_storage_hash: Vec<StorageHashRow>,
-//     pub builtin_program_hash: Vec<StorageHashRow>,
-//     pub tape: Vec<TapeRow>,
-//     pub sc_call: Vec<SCCallRow>,
-//     pub ret: Vec<GoldilocksField>,
-// }
-
-fn pre_process(block_exe_trace: Vec<TxExeTrace>) -> Vec<Trace> {
+/// Converts the execution traces of a batch into the legacy [`Trace`] rows the
+/// table generators consume, one [`Trace`] per transaction.
+///
+/// Storage accesses and the code hash reads of the executed programs are
+/// replayed on `account_tree` for the whole batch, their hash rows are kept in
+/// the last [`Trace`]. Every [`Trace`] carries the roots before and after the
+/// batch.
+pub fn pre_process(block_exe_trace: Vec<TxExeTrace>, account_tree: &mut AccountTree) -> Vec<Trace> {
     let mut traces: Vec<Trace> = Vec::new();
+    let mut storage_logs: Vec<WitnessStorageLog> = Vec::new();
+    let mut programs: Vec<(ContractAddress, Vec<u64>)> = Vec::new();
     let mut next_storage_access_idx = 0;
     for tx_trace in block_exe_trace {
+        for (addr, bytecodes) in &tx_trace.programs {
+            if !programs.iter().any(|(a, _)| a == addr) {
+                programs.push((*addr, bytecodes.clone()));
+            }
+        }
+        storage_logs.extend(tx_trace.storage.iter().map(storage_log));
         let trace = gen_tx_trace(&mut next_storage_access_idx, tx_trace);
         traces.push(trace);
     }
-    todo!()
+
+    let start_root = account_tree.root_hash();
+    let mut end_root = start_root;
+    let storage_log_len = storage_logs.len();
+    storage_logs.extend(
+        programs
+            .iter()
+            .map(|(addr, bytecodes)| program_log(addr, bytecodes)),
+    );
+    if !storage_logs.is_empty() {
+        let mut batch = Program::default();
+        let roots = storage_hash_table_gen(storage_logs, storage_log_len, &mut batch, account_tree);
+        if let Some(root) = roots.last() {
+            end_root = *root;
+        }
+        if let Some(last) = traces.last_mut() {
+            last.builtin_storage_hash = batch.trace.builtin_storage_hash;
+            last.builtin_program_hash = batch.trace.builtin_program_hash;
+            last.builtin_poseidon.extend(batch.trace.builtin_poseidon);
+        }
+    }
+    for trace in traces.iter_mut() {
+        trace.start_end_roots = (start_root, end_root);
+    }
+    traces
 }
 
 fn gen_tx_trace(next_storage_access_idx: &mut u64, mut tx: TxExeTrace) -> Trace {
@@ -87,8 +92,37 @@ fn gen_tx_trace(next_storage_access_idx: &mut u64, mut tx: TxExeTrace) -> Trace
     process_poseidon(&mut trace, tx.poseidon);
     process_tape(&mut trace, tx.tape);
     process_sccall(&mut trace, tx.sccall);
+    process_sigcheck(&mut trace, tx.sigcheck);
+    process_event(&mut trace, tx.event);
     add_treekey_poseidon(&mut trace, tx.storage);
-    todo!()
+    trace
+}
+
+fn storage_log(s: &StorageExePiece) -> WitnessStorageLog {
+    let storage_log = if s.is_write {
+        // is_write of the storage table is the log kind, keep it boolean.
+        StorageLog::new_write(
+            StorageLogKind::RepeatedWrite,
+            fe_4(s.tree_key),
+            fe_4(s.value),
+        )
+    } else {
+        StorageLog::new_read_log(fe_4(s.tree_key), fe_4(s.value))
+    };
+    WitnessStorageLog {
+        storage_log,
+        previous_value: fe_4(s.pre_value.unwrap_or_default()),
+    }
+}
+
+// The code hash is looked up by the prog chunk table, hash the bytecode the
+// same way.
+fn program_log(addr: &ContractAddress, bytecodes: &[u64]) -> WitnessStorageLog {
+    let program: Vec<GoldilocksField> = bytecodes.iter().map(|inst| fe(*inst)).collect();
+    WitnessStorageLog {
+        storage_log: StorageLog::new_read_log(fe_4(*addr), calculate_arbitrary_poseidon(&program)),
+        previous_value: tree_key_default(),
+    }
 }
 
 fn process_program(trace: &mut Trace, addr_to_bytecodes: Vec<(ContractAddress, Vec<u64>)>) {
@@ -106,11 +140,18 @@ fn process_cpu(
 ) {
     for (call_sc_cnt, env_idx, context, pieces) in cpu {
         for p in pieces {
+            // the storage table is indexed from 1, the ext line does the access.
+            if (p.opcode == OlaOpcode::SSTORE.binary_bit_mask()
+                || p.opcode == OlaOpcode::SLOAD.binary_bit_mask())
+                && p.is_ext_line
+            {
+                *next_storage_access_idx += 1;
+            }
             let step = Step {
                 env_idx: fe(env_idx),
                 call_sc_cnt: fe(call_sc_cnt),
                 clk: p.clk as u32,
-                pc: p.clk,
+                pc: p.pc,
                 tp: fe(p.tp),
                 addr_storage: fe_4(context.storage_addr),
                 addr_code: fe_4(context.code_addr),
@@ -139,14 +180,8 @@ fn process_cpu(
                 } else {
                     fe(0)
                 },
-                storage_access_idx: fe(next_storage_access_idx.clone()),
+                storage_access_idx: fe(*next_storage_access_idx),
             };
-            if (p.opcode == OlaOpcode::SSTORE.binary_bit_mask()
-                || p.opcode == OlaOpcode::SLOAD.binary_bit_mask())
-                && !p.is_ext_line
-            {
-                *next_storage_access_idx += 1;
-            }
             trace.exec.push(step);
         }
     }
@@ -301,8 +336,8 @@ fn process_bitwise(trace: &mut Trace, bitwise: Vec<BitwiseExePiece>) {
     }
 }
 
-fn process_cmp(trace: &mut Trace, bitwise: Vec<CmpExePiece>) {
-    for c in bitwise {
+fn process_cmp(trace: &mut Trace, cmp: Vec<CmpExePiece>) {
+    for c in cmp {
         let abs_diff = if c.op0 > c.op1 {
             GoldilocksField::from_canonical_u32(c.op0 - c.op1)
         } else {
@@ -323,6 +358,9 @@ fn process_cmp(trace: &mut Trace, bitwise: Vec<CmpExePiece>) {
             filter_looking_rc: GoldilocksField::ONE,
         };
         trace.builtin_cmp.push(row);
+        let mut rc_row = u32_to_rc_no_filter(abs_diff.0 as u32);
+        rc_row.filter_looked_for_comparison = GoldilocksField::ONE;
+        trace.builtin_rangecheck.push(rc_row);
     }
 }
 
@@ -381,15 +419,21 @@ fn process_poseidon(trace: &mut Trace, poseidon: Vec<PoseidonPiece>) {
     }
 }
 
-fn process_tape(trace: &mut Trace, tape: Vec<TapeExePiece>) {
-    for t in tape {
-        let is_init = t.opcode.is_none();
+// The tape table is sorted by addr, and a tload belongs to the segment of the
+// cell it reads.
+fn process_tape(trace: &mut Trace, mut tape: Vec<TapeExePiece>) {
+    tape.sort_by_key(|t| t.addr);
+    let mut is_init = false;
+    for (i, t) in tape.iter().enumerate() {
+        if i == 0 || tape[i - 1].addr != t.addr {
+            is_init = t.opcode.is_none();
+        }
         let row = TapeRow {
             is_init,
             opcode: fe_option_opcode(t.opcode),
             addr: fe(t.addr),
             value: fe(t.value),
-            filter_looked: fe_bool(!is_init),
+            filter_looked: fe_bool(t.opcode.is_some()),
         };
         trace.tape.push(row);
     }
@@ -413,6 +457,46 @@ fn process_sccall(trace: &mut Trace, sccall: Vec<SCCallPiece>) {
     }
 }
 
+fn process_sigcheck(trace: &mut Trace, sigcheck: Vec<SigCheckPiece>) {
+    for s in sigcheck {
+        let mut inputs = [GoldilocksField::ZERO; 20];
+        for (i, n) in s
+            .msg_hash
+            .iter()
+            .chain(s.pubkey_x.iter())
+            .chain(s.pubkey_y.iter())
+            .chain(s.r.iter())
+            .chain(s.s.iter())
+            .enumerate()
+        {
+            inputs[i] = fe(*n);
+        }
+        let row = SigCheckRow {
+            env_idx: fe(s.env_idx),
+            clk: fe(s.clk),
+            op1: fe(s.params_addr),
+            inputs,
+            res: fe_bool(s.verified),
+        };
+        trace.builtin_sigcheck.push(row);
+    }
+}
+
+fn process_event(trace: &mut Trace, event: Vec<EventPiece>) {
+    for e in event {
+        let row = EventRow {
+            env_idx: fe(e.env_idx),
+            clk: fe(e.clk),
+            op0: fe(e.topics_addr),
+            op1: fe(e.data_addr),
+            topic_addrs: e.topic_addrs.into_iter().map(fe).collect(),
+            topics: e.topics.into_iter().map(fe_4).collect(),
+            data: e.data.into_iter().map(fe).collect(),
+        };
+        trace.event.push(row);
+    }
+}
+
 fn add_treekey_poseidon(trace: &mut Trace, storage: Vec<StorageExePiece>) {
     for s in storage {
         let values: Vec<GoldilocksField> = s
@@ -458,14 +542,6 @@ fn fe_is_zero(n: u64) -> bool {
     GoldilocksField::from_canonical_u64(n) == GoldilocksField::ZERO
 }
 
-fn fe_non_zero(n: u64) -> bool {
-    GoldilocksField::from_canonical_u64(n) != GoldilocksField::ZERO
-}
-
-fn fe_option(n: Option<u64>) -> GoldilocksField {
-    GoldilocksField::from_canonical_u64(n.unwrap_or_default())
-}
-
 fn fe_bool(b: bool) -> GoldilocksField {
     if b {
         fe(1)
@@ -495,3 +571,251 @@ fn fe_10(nums: [u64; 10]) -> [GoldilocksField; 10] {
         fe(nums[9]),
     ]
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use core::{
+        merkle_tree::tree::AccountTree,
+        trace::exe_trace::{CmpExePiece, CpuExePiece, StorageExePiece, TapeExePiece, TxExeTrace},
+        types::{merkle_tree::constant::ROOT_TREE_DEPTH, Field, GoldilocksField},
+        vm::{hardware::ExeContext, opcodes::OlaOpcode},
+    };
+    use std::collections::HashMap;
+
+    use plonky2::{
+        field::types::PrimeField64,
+        plonk::config::{GenericConfig, PoseidonGoldilocksConfig},
+    };
+
+    use crate::{
+        builtins::tape::columns::{
+            COL_FILTER_PUBLIC, COL_TAPE_ADDR, COL_TAPE_IS_INIT_SEG, COL_TAPE_TX_IDX, COL_TAPE_VALUE,
+        },
+        generation::{
+            estimate::{estimate_traces, ProvingCostModel},
+            generate_batch_traces, GenerationInputs,
+        },
+        stark::{
+            config::StarkConfig,
+            ola_stark::{OlaStark, Table},
+            proof::BlockMetadata,
+        },
+    };
+
+    use super::pre_process;
+
+    pub(crate) fn cpu_piece(
+        clk: u64,
+        pc: u64,
+        opcode: OlaOpcode,
+        is_ext_line: bool,
+    ) -> CpuExePiece {
+        CpuExePiece {
+            clk,
+            pc,
+            psp: 0,
+            tp: 0,
+            registers: [0; 10],
+            instruction: 0,
+            imm: None,
+            opcode: opcode.binary_bit_mask(),
+            op0: None,
+            op1: None,
+            dst: None,
+            aux0: None,
+            aux1: None,
+            op0_reg_sel: [0; 10],
+            op1_reg_sel: [0; 10],
+            dst_reg_sel: [0; 10],
+            is_ext_line,
+            ext_cnt: is_ext_line as u64,
+            aux_sccall: None,
+        }
+    }
+
+    pub(crate) fn sstore_tx(value: u64) -> TxExeTrace {
+        TxExeTrace {
+            programs: vec![([0, 0, 0, 1], vec![1; 10])],
+            cpu: vec![(
+                0,
+                0,
+                ExeContext::default(),
+                vec![
+                    cpu_piece(0, 0, OlaOpcode::GTE, false),
+                    cpu_piece(1, 1, OlaOpcode::SSTORE, false),
+                    cpu_piece(1, 1, OlaOpcode::SSTORE, true),
+                    cpu_piece(2, 3, OlaOpcode::END, false),
+                ],
+            )],
+            env_mem: HashMap::new(),
+            rc: vec![],
+            bitwise: vec![],
+            cmp: vec![CmpExePiece {
+                op0: 2,
+                op1: 5,
+                is_gte: false,
+            }],
+            poseidon: vec![],
+            storage: vec![StorageExePiece {
+                is_write: true,
+                contract_addr: [0, 0, 0, 1],
+                storage_key: [0, 0, 0, 7],
+                tree_key: [1, 2, 3, 4],
+                pre_value: None,
+                value: [0, 0, 0, value],
+            }],
+            tape: tape_header(value),
+            sccall: vec![],
+            sigcheck: vec![],
+            event: vec![],
+        }
+    }
+
+    // header cells hold `100 + addr`, the tx hash `tx + addr`, and the tx reads
+    // back its hash after execution.
+    fn tape_header(tx: u64) -> Vec<TapeExePiece> {
+        let mut tape: Vec<_> = (0..25)
+            .map(|addr| TapeExePiece {
+                addr,
+                value: if addr < 21 { 100 + addr } else { tx + addr },
+                opcode: None,
+            })
+            .collect();
+        tape.push(TapeExePiece {
+            addr: 21,
+            value: tx + 21,
+            opcode: Some(OlaOpcode::TLOAD),
+        });
+        tape
+    }
+
+    #[test]
+    fn test_pre_process() {
+        let txs = vec![sstore_tx(8), sstore_tx(9)];
+        let estimate = estimate_traces(
+            &txs,
+            &StarkConfig::standard_fast_config(),
+            &ProvingCostModel::default(),
+        );
+        let mut account_tree = AccountTree::new_test();
+        let start_root = account_tree.root_hash();
+        let traces = pre_process(txs, &mut account_tree);
+        assert_eq!(traces.len(), 2);
+
+        let exec = &traces[0].exec;
+        assert_eq!(exec[3].pc, 3);
+        // only the ext line of a storage op moves the access index.
+        let idx: Vec<u64> = traces
+            .iter()
+            .flat_map(|t| t.exec.iter().map(|s| s.storage_access_idx.0))
+            .collect();
+        assert_eq!(idx, vec![0, 0, 1, 1, 1, 1, 2, 2]);
+
+        let cmp_rc = &traces[0].builtin_rangecheck[0];
+        assert_eq!(cmp_rc.val, GoldilocksField::from_canonical_u64(3));
+        assert_eq!(cmp_rc.filter_looked_for_comparison, GoldilocksField::ONE);
+
+        // the batch shares one set of hash rows, the program is read once.
+        assert!(traces[0].builtin_storage_hash.is_empty());
+        assert_eq!(traces[1].builtin_storage_hash.len(), 2 * ROOT_TREE_DEPTH);
+        assert_eq!(traces[1].builtin_program_hash.len(), ROOT_TREE_DEPTH);
+        assert_eq!(
+            traces[1]
+                .builtin_storage_hash
+                .last()
+                .unwrap()
+                .storage_access_idx,
+            2
+        );
+        let end_root = account_tree.root_hash();
+        assert_ne!(start_root, end_root);
+        for trace in traces.iter() {
+            assert_eq!(trace.start_end_roots, (start_root, end_root));
+        }
+
+        let rows = |rows: fn(&core::trace::trace::Trace) -> usize| -> usize {
+            traces.iter().map(rows).sum()
+        };
+        let table = |table: Table| estimate.tables[table as usize].rows;
+        assert_eq!(rows(|t| t.exec.len()), table(Table::Cpu));
+        assert_eq!(rows(|t| t.builtin_cmp.len()), table(Table::Cmp));
+        assert_eq!(rows(|t| t.memory.len()), table(Table::Memory));
+    }
+
+    #[test]
+    fn test_generate_batch_traces() {
+        const D: usize = 2;
+        type F = <PoseidonGoldilocksConfig as GenericConfig<D>>::F;
+        let mut ola_stark = OlaStark::<F, D>::default();
+        let mut account_tree = AccountTree::new_test();
+        let start_root = account_tree.root_hash();
+        let (traces, public_values) = generate_batch_traces(
+            vec![sstore_tx(8), sstore_tx(9)],
+            &mut account_tree,
+            &mut ola_stark,
+            GenerationInputs::default(),
+        );
+        for trace in traces.iter() {
+            assert!(trace[0].len().is_power_of_two());
+        }
+        assert_eq!(traces[Table::Cpu as usize][0].len(), 8);
+        // 2 storage accesses and 1 program read along the tree path.
+        assert_eq!(
+            traces[Table::StorageAccess as usize][0].len(),
+            (3 * ROOT_TREE_DEPTH).next_power_of_two()
+        );
+        assert_eq!(traces[Table::ProgChunk as usize][0].len(), 2);
+
+        assert_eq!(public_values.state_root_before, start_root.map(|x| x.0));
+        assert_eq!(
+            public_values.state_root_after,
+            account_tree.root_hash().map(|x| x.0)
+        );
+        assert_eq!(
+            public_values.block_metadata,
+            BlockMetadata {
+                block_number: 100,
+                block_timestamp: 101,
+                sequencer_address: [102, 103, 104, 105],
+                chain_id: 107,
+            }
+        );
+        assert_eq!(
+            public_values.tx_hashes,
+            vec![[29, 30, 31, 32], [30, 31, 32, 33]]
+        );
+        assert_eq!(
+            ola_stark.storage_access_stark.get_state_roots(),
+            Some((
+                public_values.state_root_before,
+                public_values.state_root_after
+            ))
+        );
+
+        // the rows looked up by the public values are exactly the public cells.
+        let tape = &traces[Table::Tape as usize];
+        let col = |col: usize, row: usize| tape[col].values[row].to_canonical_u64();
+        let mut public_rows: Vec<[u64; 3]> = (0..tape[0].len())
+            .filter(|&row| col(COL_FILTER_PUBLIC, row) == 1)
+            .map(|row| {
+                [
+                    col(COL_TAPE_TX_IDX, row),
+                    col(COL_TAPE_ADDR, row),
+                    col(COL_TAPE_VALUE, row),
+                ]
+            })
+            .collect();
+        let mut cells = public_values.tape_cells();
+        public_rows.sort();
+        cells.sort();
+        assert_eq!(public_rows, cells);
+        // the tload of the tx hash is sorted next to its init cell.
+        let tload_row = (0..tape[0].len())
+            .find(|&row| col(COL_TAPE_ADDR, row) == 22)
+            .unwrap()
+            - 1;
+        assert_eq!(col(COL_TAPE_ADDR, tload_row), 21);
+        assert_eq!(col(COL_TAPE_IS_INIT_SEG, tload_row), 1);
+        assert_eq!(col(COL_FILTER_PUBLIC, tload_row), 0);
+    }
+}
@@ -4,30 +4,17 @@ use core::{
     types::{Field, GoldilocksField},
     vm::opcodes::OlaOpcode,
 };
-use std::cmp::max;
+use std::{cmp::max, ops::Range};
 
 use itertools::Itertools;
-use plonky2::{
-    hash::hash_types::RichField,
-    iop::challenger::Challenger,
-    plonk::config::{GenericConfig, PoseidonGoldilocksConfig},
-};
+use plonky2::hash::hash_types::RichField;
 
-use crate::{program::columns::*, stark::lookup::permuted_cols};
+use crate::{program::columns::*, stark::lookup::permuted_rows};
 
 pub fn generate_prog_trace<F: RichField>(
     execs: &[Step],
     progs: Vec<([GoldilocksField; 4], Vec<GoldilocksField>)>,
-    start_end_roots: ([GoldilocksField; 4], [GoldilocksField; 4]),
-) -> ([Vec<F>; NUM_PROG_COLS], F) {
-    let mut challenger =
-        Challenger::<F, <PoseidonGoldilocksConfig as GenericConfig<2>>::Hasher>::new();
-    for limb_idx in 0..4 {
-        challenger.observe_element(F::from_canonical_u64(start_end_roots.0[limb_idx].0));
-        challenger.observe_element(F::from_canonical_u64(start_end_roots.1[limb_idx].0))
-    }
-    let beta = challenger.get_challenge();
-
+) -> [Vec<F>; NUM_PROG_COLS] {
     let main_lines: Vec<&Step> = execs.iter().filter(|e| e.is_ext_line.0 == 0).collect();
     let exec_len: usize = main_lines
         .iter()
@@ -67,17 +54,6 @@ pub fn generate_prog_trace<F: RichField>(
         trace[COL_PROG_EXEC_PC][exec_index] = F::from_canonical_u64(e.pc);
         trace[COL_PROG_EXEC_INST][exec_index] = F::from_canonical_u64(e.instruction.0);
         trace[COL_PROG_FILTER_EXEC][exec_index] = F::ONE;
-        trace[COL_PROG_EXEC_COMP_PROG][exec_index] = compress(
-            [
-                trace[COL_PROG_EXEC_CODE_ADDR_RANGE.start][exec_index],
-                trace[COL_PROG_EXEC_CODE_ADDR_RANGE.start + 1][exec_index],
-                trace[COL_PROG_EXEC_CODE_ADDR_RANGE.start + 2][exec_index],
-                trace[COL_PROG_EXEC_CODE_ADDR_RANGE.start + 3][exec_index],
-                trace[COL_PROG_EXEC_PC][exec_index],
-                trace[COL_PROG_EXEC_INST][exec_index],
-            ],
-            beta,
-        );
         exec_index += 1;
 
         // for immediate value
@@ -92,17 +68,6 @@ pub fn generate_prog_trace<F: RichField>(
             trace[COL_PROG_EXEC_PC][exec_index] = F::from_canonical_u64(e.pc + 1);
             trace[COL_PROG_EXEC_INST][exec_index] = F::from_canonical_u64(e.immediate_data.0);
             trace[COL_PROG_FILTER_EXEC][exec_index] = F::ONE;
-            trace[COL_PROG_EXEC_COMP_PROG][exec_index] = compress(
-                [
-                    trace[COL_PROG_EXEC_CODE_ADDR_RANGE.start][exec_index],
-                    trace[COL_PROG_EXEC_CODE_ADDR_RANGE.start + 1][exec_index],
-                    trace[COL_PROG_EXEC_CODE_ADDR_RANGE.start + 2][exec_index],
-                    trace[COL_PROG_EXEC_CODE_ADDR_RANGE.start + 3][exec_index],
-                    trace[COL_PROG_EXEC_PC][exec_index],
-                    trace[COL_PROG_EXEC_INST][exec_index],
-                ],
-                beta,
-            );
             exec_index += 1;
         }
     }
@@ -117,42 +82,31 @@ pub fn generate_prog_trace<F: RichField>(
             trace[COL_PROG_PC][prog_index] = F::from_canonical_u64(pc as u64);
             trace[COL_PROG_INST][prog_index] = F::from_canonical_u64(inst.0);
             trace[COL_PROG_FILTER_PROG_CHUNK][prog_index] = F::ONE;
-            trace[COL_PROG_COMP_PROG][prog_index] = compress(
-                [
-                    trace[COL_PROG_CODE_ADDR_RANGE.start][prog_index],
-                    trace[COL_PROG_CODE_ADDR_RANGE.start + 1][prog_index],
-                    trace[COL_PROG_CODE_ADDR_RANGE.start + 2][prog_index],
-                    trace[COL_PROG_CODE_ADDR_RANGE.start + 3][prog_index],
-                    trace[COL_PROG_PC][prog_index],
-                    trace[COL_PROG_INST][prog_index],
-                ],
-                beta,
-            );
             prog_index += 1;
         }
     }
-    let (permuted_inputs, permuted_table) =
-        permuted_cols(&trace[COL_PROG_EXEC_COMP_PROG], &trace[COL_PROG_COMP_PROG]);
-    trace[COL_PROG_EXEC_COMP_PROG_PERM] = permuted_inputs;
-    trace[COL_PROG_COMP_PROG_PERM] = permuted_table;
+    let rows = |cols: Range<usize>| -> Vec<[F; 6]> {
+        (0..num_padded_rows)
+            .map(|i| std::array::from_fn(|j| trace[cols.start + j][i]))
+            .collect()
+    };
+    let (permuted_inputs, permuted_table, same) =
+        permuted_rows(&rows(COL_PROG_EXEC_ROW), &rows(COL_PROG_ROW));
+    for i in 0..num_padded_rows {
+        for j in 0..6 {
+            trace[COL_PROG_EXEC_ROW_PERM.start + j][i] = permuted_inputs[i][j];
+            trace[COL_PROG_ROW_PERM.start + j][i] = permuted_table[i][j];
+        }
+    }
+    trace[COL_PROG_EXEC_ROW_PERM_SAME] = same;
 
-    let trace_row_vecs = trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
+    trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
         panic!(
             "Expected a Vec of length {} but it was {}",
             NUM_PROG_COLS,
             v.len()
         )
-    });
-    (trace_row_vecs, beta)
-}
-
-fn compress<F: RichField>(values: [F; 6], beta: F) -> F {
-    values[0]
-        + values[1] * beta
-        + values[2] * beta * beta
-        + values[3] * beta * beta * beta
-        + values[4] * beta * beta * beta * beta
-        + values[5] * beta * beta * beta * beta * beta
+    })
 }
 
 pub fn generate_prog_chunk_trace<F: RichField>(
@@ -199,6 +153,10 @@ pub fn generate_prog_chunk_trace<F: RichField>(
     for (i, (addr, start_pc, chunk, is_first_line, is_result_line)) in
         vec_addr_pc_chunk.iter().enumerate()
     {
+        // every program is hashed from an empty state.
+        if *is_first_line {
+            pre_hash = [F::ZERO; 12];
+        }
         for j in 0..4 {
             trace[COL_PROG_CHUNK_CODE_ADDR_RANGE.start + j][i] = F::from_canonical_u64(addr[j].0);
         }
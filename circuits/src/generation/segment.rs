@@ -0,0 +1,180 @@
+use core::{trace::exe_trace::TxExeTrace, tx::BatchResult, vm::types::Event};
+
+use anyhow::{ensure, Result};
+
+use super::estimate::TableRows;
+
+/// Consecutive transactions of a batch proven together by one `AllProof`.
+///
+/// Segments end at transaction boundaries. There the CPU table restarts
+/// registers, clk, pc and env from zero and memory belongs to a single
+/// transaction, so the state root is the only state a segment hands over to
+/// the next one.
+#[derive(Debug, Clone, Default)]
+pub struct Segment {
+    pub txs: Vec<TxExeTrace>,
+    /// Events emitted by `txs`.
+    pub events: Vec<Event>,
+}
+
+/// Splits a batch into segments filling at most `1 << max_degree_bits` rows of
+/// every table, or into a single segment when there is no limit. Fixed lookup
+/// tables do not count towards the limit. A transaction too large for the
+/// limit on its own gets a segment of its own. Fails when the events of the
+/// batch are not counted per transaction.
+pub fn split_batch(batch: BatchResult, max_degree_bits: Option<usize>) -> Result<Vec<Segment>> {
+    let BatchResult {
+        tx_traces,
+        events,
+        tx_events_len,
+        ..
+    } = batch;
+    ensure!(
+        tx_traces.len() == tx_events_len.len(),
+        "events of {} txs are counted for a batch of {} txs",
+        tx_events_len.len(),
+        tx_traces.len()
+    );
+
+    let mut events = events.into_iter();
+    let mut segments = Vec::new();
+    let mut segment = Segment::default();
+    let mut table_rows = TableRows::default();
+    for (tx, events_len) in tx_traces.into_iter().zip(tx_events_len) {
+        table_rows.add_tx(&tx);
+        let fits = max_degree_bits.map_or(true, |max_degree_bits| {
+            table_rows
+                .rows()
+                .iter()
+                .all(|rows| *rows <= 1 << max_degree_bits)
+        });
+        if !fits && !segment.txs.is_empty() {
+            segments.push(std::mem::take(&mut segment));
+            table_rows = TableRows::default();
+            table_rows.add_tx(&tx);
+        }
+        segment.txs.push(tx);
+        segment.events.extend(events.by_ref().take(events_len));
+    }
+    if !segment.txs.is_empty() {
+        segments.push(segment);
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        merkle_tree::tree::AccountTree, trace::exe_trace::TxExeTrace, tx::BatchResult,
+        vm::types::Event,
+    };
+
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+    use plonky2_util::log2_ceil;
+
+    use crate::{
+        generation::{
+            estimate::{estimate_traces, ProvingCostModel},
+            generate_batch_traces,
+            pre_process::tests::sstore_tx,
+            GenerationInputs,
+        },
+        stark::{config::StarkConfig, ola_stark::OlaStark},
+    };
+
+    use super::split_batch;
+
+    fn event(index_in_batch: u64) -> Event {
+        Event {
+            batch_number: 0,
+            index_in_batch,
+            address: [0, 0, 0, 1],
+            topics: vec![],
+            data: vec![index_in_batch],
+        }
+    }
+
+    fn batch() -> BatchResult {
+        BatchResult {
+            tx_traces: vec![sstore_tx(8), sstore_tx(9), sstore_tx(10)],
+            storage_access_logs: vec![],
+            events: (0..3).map(event).collect(),
+            tx_events_len: vec![2, 0, 1],
+            block_tip_queries: vec![],
+        }
+    }
+
+    #[test]
+    fn test_split_batch() {
+        const D: usize = 2;
+        type F = <PoseidonGoldilocksConfig as GenericConfig<D>>::F;
+        let config = StarkConfig::standard_fast_config();
+
+        let segments = split_batch(batch(), None).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].txs.len(), 3);
+        assert_eq!(segments[0].events.len(), 3);
+
+        let mut uncounted = batch();
+        uncounted.tx_events_len.pop();
+        assert!(split_batch(uncounted, None).is_err());
+
+        // room for exactly one tx per segment.
+        let max_rows = |txs: &[TxExeTrace]| {
+            estimate_traces(txs, &config, &ProvingCostModel::default())
+                .tables
+                .iter()
+                .map(|t| t.rows)
+                .max()
+                .unwrap()
+        };
+        let max_degree_bits = log2_ceil(max_rows(&[sstore_tx(8)]));
+        assert!(max_rows(&[sstore_tx(8), sstore_tx(9)]) > 1 << max_degree_bits);
+        let segments = split_batch(batch(), Some(max_degree_bits)).unwrap();
+        assert_eq!(segments.len(), 3);
+        let events: Vec<Vec<u64>> = segments
+            .iter()
+            .map(|s| s.events.iter().map(|e| e.index_in_batch).collect())
+            .collect();
+        assert_eq!(events, vec![vec![0, 1], vec![], vec![2]]);
+
+        // segments proven in order hand their state root to the next one.
+        let mut account_tree = AccountTree::new_test();
+        let start_root = account_tree.root_hash().map(|x| x.0);
+        let public_values: Vec<_> = segments
+            .into_iter()
+            .map(|segment| {
+                generate_batch_traces(
+                    segment.txs,
+                    &mut account_tree,
+                    &mut OlaStark::<F, D>::default(),
+                    GenerationInputs::default(),
+                )
+                .1
+            })
+            .collect();
+        assert_eq!(public_values[0].state_root_before, start_root);
+        for pair in public_values.windows(2) {
+            assert_eq!(pair[0].state_root_after, pair[1].state_root_before);
+            assert_ne!(pair[0].state_root_before, pair[0].state_root_after);
+            assert_eq!(pair[1].tx_hashes.len(), 1);
+        }
+        let mut whole_tree = AccountTree::new_test();
+        let whole = generate_batch_traces(
+            batch().tx_traces,
+            &mut whole_tree,
+            &mut OlaStark::<F, D>::default(),
+            GenerationInputs::default(),
+        )
+        .1;
+        assert_eq!(
+            public_values.last().unwrap().state_root_after,
+            whole.state_root_after
+        );
+        let tx_hashes: Vec<_> = public_values
+            .iter()
+            .flat_map(|p| p.tx_hashes.clone())
+            .collect();
+        assert_eq!(tx_hashes, whole.tx_hashes);
+    }
+}
@@ -0,0 +1,53 @@
+use core::{trace::trace::SigCheckRow, types::PrimeField64};
+
+use plonky2::hash::hash_types::RichField;
+
+use crate::builtins::sigcheck::columns::*;
+
+fn fill_row<F: RichField>(trace: &mut [Vec<F>], i: usize, tx_idx: usize, c: &SigCheckRow) {
+    trace[COL_SIGCHECK_TX_IDX][i] = F::from_canonical_usize(tx_idx);
+    trace[COL_SIGCHECK_ENV_IDX][i] = F::from_canonical_u64(c.env_idx.to_canonical_u64());
+    trace[COL_SIGCHECK_CLK][i] = F::from_canonical_u64(c.clk.to_canonical_u64());
+    trace[COL_SIGCHECK_OP1][i] = F::from_canonical_u64(c.op1.to_canonical_u64());
+    for (j, input) in c.inputs.iter().enumerate() {
+        trace[COL_SIGCHECK_INPUTS.start + j][i] = F::from_canonical_u64(input.to_canonical_u64());
+    }
+    trace[COL_SIGCHECK_RES][i] = F::from_canonical_u64(c.res.to_canonical_u64());
+    trace[COL_SIGCHECK_FILTER][i] = F::ONE;
+}
+
+pub fn generate_sigcheck_trace<F: RichField>(cells: &[SigCheckRow]) -> [Vec<F>; NUM_COL_SIGCHECK] {
+    gen_sigcheck_trace(&vec![cells.to_vec()])
+}
+
+pub fn gen_sigcheck_trace<F: RichField>(
+    batch_cells: &Vec<Vec<SigCheckRow>>,
+) -> [Vec<F>; NUM_COL_SIGCHECK] {
+    let num_filled_row_len: usize = batch_cells.iter().map(|inner_vec| inner_vec.len()).sum();
+    let num_padded_rows = if !num_filled_row_len.is_power_of_two() || num_filled_row_len < 2 {
+        if num_filled_row_len < 2 {
+            2
+        } else {
+            num_filled_row_len.next_power_of_two()
+        }
+    } else {
+        num_filled_row_len
+    };
+
+    let mut trace: Vec<Vec<F>> = vec![vec![F::ZERO; num_padded_rows]; NUM_COL_SIGCHECK];
+    let mut i = 0;
+    for (tx_idx, cells) in batch_cells.iter().enumerate() {
+        for c in cells.iter() {
+            fill_row(&mut trace, i, tx_idx, c);
+            i += 1;
+        }
+    }
+
+    trace.try_into().unwrap_or_else(|v: Vec<Vec<F>>| {
+        panic!(
+            "Expected a Vec of length {} but it was {}",
+            NUM_COL_SIGCHECK,
+            v.len()
+        )
+    })
+}
@@ -1,12 +1,15 @@
-use core::trace::trace::StorageHashRow;
+use core::{trace::trace::StorageHashRow, types::merkle_tree::TreeValue};
 
 use plonky2::hash::hash_types::RichField;
 
 use crate::builtins::storage::columns::*;
 
+/// `root_after` is the state root after the accesses, which padding rows keep
+/// when there is no access at all.
 pub fn generate_storage_access_trace<F: RichField>(
     accesses: &[StorageHashRow],
     prog_hash_read: &[StorageHashRow],
+    root_after: TreeValue,
 ) -> [Vec<F>; NUM_COL_ST] {
     let num_filled_row_len: usize = accesses.len() + prog_hash_read.len();
     let num_padded_rows = if !num_filled_row_len.is_power_of_two() || num_filled_row_len < 2 {
@@ -81,28 +84,13 @@ pub fn generate_storage_access_trace<F: RichField>(
         trace[COL_ST_IS_PADDING][i] = F::ZERO;
     }
 
-    let last_root = [
+    let last_root: [F; 4] = std::array::from_fn(|j| {
         if num_filled_row_len == 0 {
-            F::ZERO
-        } else {
-            trace[COL_ST_ROOT_RANGE.start][num_filled_row_len - 1]
-        },
-        if num_filled_row_len == 0 {
-            F::ZERO
+            F::from_canonical_u64(root_after[j].0)
         } else {
-            trace[COL_ST_ROOT_RANGE.start + 1][num_filled_row_len - 1]
-        },
-        if num_filled_row_len == 0 {
-            F::ZERO
-        } else {
-            trace[COL_ST_ROOT_RANGE.start + 2][num_filled_row_len - 1]
-        },
-        if num_filled_row_len == 0 {
-            F::ZERO
-        } else {
-            trace[COL_ST_ROOT_RANGE.start + 3][num_filled_row_len - 1]
-        },
-    ];
+            trace[COL_ST_ROOT_RANGE.start + j][num_filled_row_len - 1]
+        }
+    });
     if num_padded_rows != num_filled_row_len {
         for i in num_filled_row_len..num_padded_rows {
             trace[COL_ST_ROOT_RANGE.start][i] = last_root[0];
@@ -2,11 +2,81 @@ use core::{trace::trace::TapeRow, types::PrimeField64, vm::opcodes::OlaOpcode};
 
 use plonky2::hash::hash_types::RichField;
 
-use crate::builtins::tape::columns::{
-    COL_FILTER_LOOKED, COL_TAPE_ADDR, COL_TAPE_IS_INIT_SEG, COL_TAPE_OPCODE, COL_TAPE_TX_IDX,
-    COL_TAPE_VALUE, NUM_COL_TAPE,
+use crate::{
+    builtins::tape::columns::{
+        COL_FILTER_LOOKED, COL_FILTER_PUBLIC, COL_TAPE_ADDR, COL_TAPE_IS_INIT_SEG, COL_TAPE_OPCODE,
+        COL_TAPE_TX_IDX, COL_TAPE_VALUE, NUM_COL_TAPE, TAPE_BLOCK_NUMBER, TAPE_BLOCK_TIMESTAMP,
+        TAPE_CHAIN_ID, TAPE_SEQUENCER_ADDRESS, TAPE_TX_HASH,
+    },
+    stark::proof::BlockMetadata,
 };
 
+fn header_value(cells: &[TapeRow], addr: u64) -> Option<u64> {
+    cells
+        .iter()
+        .find(|c| c.addr.to_canonical_u64() == addr)
+        .map(|c| c.value.to_canonical_u64())
+}
+
+fn header_tx_hash(cells: &[TapeRow]) -> Option<[u64; 4]> {
+    let hash = TAPE_TX_HASH
+        .map(|addr| header_value(cells, addr))
+        .collect::<Option<Vec<_>>>()?;
+    hash.try_into().ok()
+}
+
+fn is_public_addr(addr: u64) -> bool {
+    addr == TAPE_BLOCK_NUMBER
+        || addr == TAPE_BLOCK_TIMESTAMP
+        || addr == TAPE_CHAIN_ID
+        || TAPE_SEQUENCER_ADDRESS.contains(&addr)
+        || TAPE_TX_HASH.contains(&addr)
+}
+
+/// Block metadata and tx hashes read from the tape headers of a batch. Txs are
+/// taken in order up to the first one without a complete header (e.g. a bare
+/// program run without tx context).
+pub(crate) fn tape_public_values(batch_cells: &[Vec<TapeRow>]) -> (BlockMetadata, Vec<[u64; 4]>) {
+    let tx_hashes = batch_cells
+        .iter()
+        .map_while(|cells| header_tx_hash(cells))
+        .collect::<Vec<_>>();
+    let block_metadata = match batch_cells.first() {
+        Some(cells) if !tx_hashes.is_empty() => BlockMetadata {
+            block_number: header_value(cells, TAPE_BLOCK_NUMBER).unwrap(),
+            block_timestamp: header_value(cells, TAPE_BLOCK_TIMESTAMP).unwrap(),
+            sequencer_address: TAPE_SEQUENCER_ADDRESS
+                .map(|addr| header_value(cells, addr).unwrap())
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            chain_id: header_value(cells, TAPE_CHAIN_ID).unwrap(),
+        },
+        _ => BlockMetadata::default(),
+    };
+    (block_metadata, tx_hashes)
+}
+
+/// Marks the first row of every public header cell of the txs whose header is
+/// part of the public values. Rows of a tx are sorted by addr.
+fn fill_filter_public<F: RichField>(
+    trace: &mut [Vec<F>],
+    batch_cells: &[Vec<TapeRow>],
+    num_public_txs: usize,
+) {
+    let mut i = 0;
+    for (tx_idx, cells) in batch_cells.iter().enumerate() {
+        for (j, c) in cells.iter().enumerate() {
+            let addr = c.addr.to_canonical_u64();
+            let is_first = j == 0 || cells[j - 1].addr.to_canonical_u64() != addr;
+            if tx_idx < num_public_txs && is_first && is_public_addr(addr) {
+                trace[COL_FILTER_PUBLIC][i] = F::ONE;
+            }
+            i += 1;
+        }
+    }
+}
+
 pub fn generate_tape_trace<F: RichField>(cells: &[TapeRow]) -> [Vec<F>; NUM_COL_TAPE] {
     let num_filled_row_len: usize = cells.len();
     let num_padded_rows = if !num_filled_row_len.is_power_of_two() || num_filled_row_len < 2 {
@@ -28,6 +98,9 @@ pub fn generate_tape_trace<F: RichField>(cells: &[TapeRow]) -> [Vec<F>; NUM_COL_
         trace[COL_TAPE_VALUE][i] = F::from_canonical_u64(c.value.to_canonical_u64());
         trace[COL_FILTER_LOOKED][i] = F::from_canonical_u64(c.filter_looked.to_canonical_u64());
     }
+    let batch_cells = [cells.to_vec()];
+    let (_, tx_hashes) = tape_public_values(&batch_cells);
+    fill_filter_public(&mut trace, &batch_cells, tx_hashes.len());
 
     let last_tx_idx = if num_filled_row_len == 0 {
         F::ZERO
@@ -97,6 +170,9 @@ pub fn gen_tape_trace<F: RichField>(batch_cells: &Vec<Vec<TapeRow>>) -> [Vec<F>;
             i += 1;
         }
     }
+    let (_, tx_hashes) = tape_public_values(batch_cells);
+    fill_filter_public(&mut trace, batch_cells, tx_hashes.len());
+
     let last_tx_idx = if num_filled_row_len == 0 {
         F::ZERO
     } else {
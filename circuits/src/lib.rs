@@ -3,6 +3,7 @@
 
 pub mod builtins;
 pub mod cpu;
+pub mod differential;
 pub mod fixed_table;
 pub mod generation;
 pub mod memory;
@@ -26,7 +26,9 @@ pub(crate) const COL_MEM_S_POSEIDON: usize = COL_MEM_S_SCCALL + 1;
 pub(crate) const COL_MEM_S_SSTORE: usize = COL_MEM_S_POSEIDON + 1;
 pub(crate) const COL_MEM_S_SLOAD: usize = COL_MEM_S_SSTORE + 1;
 pub(crate) const COL_MEM_S_PROPHET: usize = COL_MEM_S_SLOAD + 1;
-pub(crate) const COL_MEM_IS_WRITE: usize = COL_MEM_S_PROPHET + 1;
+pub(crate) const COL_MEM_S_SIGCHECK: usize = COL_MEM_S_PROPHET + 1;
+pub(crate) const COL_MEM_S_EVENT: usize = COL_MEM_S_SIGCHECK + 1;
+pub(crate) const COL_MEM_IS_WRITE: usize = COL_MEM_S_EVENT + 1;
 pub(crate) const COL_MEM_VALUE: usize = COL_MEM_IS_WRITE + 1;
 pub(crate) const COL_MEM_DIFF_ADDR: usize = COL_MEM_VALUE + 1;
 pub(crate) const COL_MEM_DIFF_ADDR_INV: usize = COL_MEM_DIFF_ADDR + 1;
@@ -64,6 +66,8 @@ pub(crate) fn get_memory_col_name_map() -> BTreeMap<usize, String> {
     m.insert(COL_MEM_S_SSTORE, String::from("S_SSTORE"));
     m.insert(COL_MEM_S_SLOAD, String::from("S_SLOAD"));
     m.insert(COL_MEM_S_PROPHET, String::from("S_PROPHET"));
+    m.insert(COL_MEM_S_SIGCHECK, String::from("S_SIGCHECK"));
+    m.insert(COL_MEM_S_EVENT, String::from("S_EVENT"));
     m.insert(COL_MEM_IS_WRITE, String::from("IS_WRITE"));
     m.insert(COL_MEM_VALUE, String::from("VALUE"));
     m.insert(COL_MEM_DIFF_ADDR, String::from("DIFF_ADDR"));
@@ -61,6 +61,14 @@ pub fn ctl_filter<F: Field>() -> Column<F> {
     ])
 }
 
+pub fn ctl_filter_with_sigcheck<F: Field>() -> Column<F> {
+    Column::single(COL_MEM_S_SIGCHECK)
+}
+
+pub fn ctl_filter_with_event<F: Field>() -> Column<F> {
+    Column::single(COL_MEM_S_EVENT)
+}
+
 pub fn ctl_data_with_poseidon_chunk<F: Field>() -> Vec<Column<F>> {
     let cols: Vec<_> = Column::singles([
         COL_MEM_TX_IDX,
@@ -151,6 +159,8 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryStark<F
         let op_sstore = P::Scalar::from_canonical_u64(OlaOpcode::SSTORE.binary_bit_mask());
         let op_sload = P::Scalar::from_canonical_u64(OlaOpcode::SLOAD.binary_bit_mask());
         let op_prophet = P::ZEROS;
+        let op_sigcheck = P::Scalar::from_canonical_u64(OlaOpcode::SIGCHECK.binary_bit_mask());
+        let op_event = P::Scalar::from_canonical_u64(OlaOpcode::EVENT.binary_bit_mask());
 
         // constraint opcode and selector matches, selector is binary and only one is
         // selected.
@@ -165,6 +175,8 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryStark<F
         yield_constr.constraint((lv[COL_MEM_OP] - op_sstore) * lv[COL_MEM_S_SSTORE]);
         yield_constr.constraint((lv[COL_MEM_OP] - op_sload) * lv[COL_MEM_S_SLOAD]);
         yield_constr.constraint((lv[COL_MEM_OP] - op_prophet) * lv[COL_MEM_S_PROPHET]);
+        yield_constr.constraint((lv[COL_MEM_OP] - op_sigcheck) * lv[COL_MEM_S_SIGCHECK]);
+        yield_constr.constraint((lv[COL_MEM_OP] - op_event) * lv[COL_MEM_S_EVENT]);
         yield_constr.constraint((P::ONES - lv[COL_MEM_S_MLOAD]) * lv[COL_MEM_S_MLOAD]);
         yield_constr.constraint((P::ONES - lv[COL_MEM_S_MSTORE]) * lv[COL_MEM_S_MSTORE]);
         yield_constr.constraint((P::ONES - lv[COL_MEM_S_CALL]) * lv[COL_MEM_S_CALL]);
@@ -176,6 +188,8 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryStark<F
         yield_constr.constraint((P::ONES - lv[COL_MEM_S_SSTORE]) * lv[COL_MEM_S_SSTORE]);
         yield_constr.constraint((P::ONES - lv[COL_MEM_S_SLOAD]) * lv[COL_MEM_S_SLOAD]);
         yield_constr.constraint((P::ONES - lv[COL_MEM_S_PROPHET]) * lv[COL_MEM_S_PROPHET]);
+        yield_constr.constraint((P::ONES - lv[COL_MEM_S_SIGCHECK]) * lv[COL_MEM_S_SIGCHECK]);
+        yield_constr.constraint((P::ONES - lv[COL_MEM_S_EVENT]) * lv[COL_MEM_S_EVENT]);
         yield_constr.constraint(
             P::ONES
                 - lv[COL_MEM_S_MLOAD]
@@ -188,7 +202,9 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryStark<F
                 - lv[COL_MEM_S_POSEIDON]
                 - lv[COL_MEM_S_SSTORE]
                 - lv[COL_MEM_S_SLOAD]
-                - lv[COL_MEM_S_PROPHET],
+                - lv[COL_MEM_S_PROPHET]
+                - lv[COL_MEM_S_SIGCHECK]
+                - lv[COL_MEM_S_EVENT],
         );
 
         // constraint is_rw region
@@ -218,7 +234,9 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryStark<F
                     - lv[COL_MEM_S_SCCALL]
                     - lv[COL_MEM_S_POSEIDON]
                     - lv[COL_MEM_S_SSTORE]
-                    - lv[COL_MEM_S_SLOAD]),
+                    - lv[COL_MEM_S_SLOAD]
+                    - lv[COL_MEM_S_SIGCHECK]
+                    - lv[COL_MEM_S_EVENT]),
         );
 
         // region is one of stack, heap and prophet
@@ -334,10 +352,235 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryStark<F
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut CircuitBuilder<F, D>,
-        _vars: StarkEvaluationTargets<D, NUM_MEM_COLS>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, NUM_MEM_COLS>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+        let one = builder.one_extension();
+        let zero = builder.zero_extension();
+        // tx_idx not change or increase by one
+        let tx_idx_diff = builder.sub_extension(nv[COL_MEM_TX_IDX], lv[COL_MEM_TX_IDX]);
+        let same_tx = builder.sub_extension(one, tx_idx_diff);
+        let cs = builder.mul_extension(tx_idx_diff, same_tx);
+        yield_constr.constraint_transition(builder, cs);
+        // in same tx, env_idx not change or increase by one
+        let env_idx_diff = builder.sub_extension(nv[COL_MEM_ENV_IDX], lv[COL_MEM_ENV_IDX]);
+        let same_env = builder.sub_extension(one, env_idx_diff);
+        let cs = builder.mul_many_extension([same_tx, env_idx_diff, same_env]);
+        yield_constr.constraint_transition(builder, cs);
+        let same_tx_env = builder.mul_extension(same_tx, same_env);
+
+        let span =
+            builder.constant_extension(F::Extension::from_canonical_u64(2_u64.pow(32).sub(1)));
+        let addr_heap_ptr =
+            builder.constant_extension(F::Extension::from_canonical_u64(ADDR_HEAP_PTR));
+
+        let is_rw = lv[COL_MEM_IS_RW];
+        let region_prophet = lv[COL_MEM_REGION_PROPHET];
+        let nv_region_prophet = nv[COL_MEM_REGION_PROPHET];
+        let region_heap = lv[COL_MEM_REGION_HEAP];
+        let nv_region_heap = nv[COL_MEM_REGION_HEAP];
+        let region_stack = builder.sub_extension(one, region_heap);
+        let region_stack = builder.sub_extension(region_stack, region_prophet);
+        let nv_region_stack = builder.sub_extension(one, nv_region_heap);
+        let nv_region_stack = builder.sub_extension(nv_region_stack, nv_region_prophet);
+        let is_write = lv[COL_MEM_IS_WRITE];
+        let nv_is_write = nv[COL_MEM_IS_WRITE];
+        let addr = lv[COL_MEM_ADDR];
+        let nv_diff_addr_inv = nv[COL_MEM_DIFF_ADDR_INV];
+        let nv_addr = nv[COL_MEM_ADDR];
+        let diff_addr = lv[COL_MEM_DIFF_ADDR];
+        let nv_diff_addr = nv[COL_MEM_DIFF_ADDR];
+        let rw_addr_unchanged = lv[COL_MEM_RW_ADDR_UNCHANGED];
+        let nv_rw_addr_unchanged = nv[COL_MEM_RW_ADDR_UNCHANGED];
+        let diff_addr_cond = lv[COL_MEM_DIFF_ADDR_COND];
+        let value = lv[COL_MEM_VALUE];
+        let nv_value = nv[COL_MEM_VALUE];
+        let diff_clk = lv[COL_MEM_DIFF_CLK];
+        let rc_value = lv[COL_MEM_RC_VALUE];
+        let filter_looking_rc = lv[COL_MEM_FILTER_LOOKING_RC];
+        let lv_filter_looking_rc_cond = lv[COL_MEM_FILTER_LOOKING_RC_COND];
+
+        // constraint opcode and selector matches, selector is binary and only one is
+        // selected.
+        let selectors = [
+            (OlaOpcode::MLOAD.binary_bit_mask(), COL_MEM_S_MLOAD),
+            (OlaOpcode::MSTORE.binary_bit_mask(), COL_MEM_S_MSTORE),
+            (OlaOpcode::CALL.binary_bit_mask(), COL_MEM_S_CALL),
+            (OlaOpcode::RET.binary_bit_mask(), COL_MEM_S_RET),
+            (OlaOpcode::TLOAD.binary_bit_mask(), COL_MEM_S_TLOAD),
+            (OlaOpcode::TSTORE.binary_bit_mask(), COL_MEM_S_TSTORE),
+            (OlaOpcode::SCCALL.binary_bit_mask(), COL_MEM_S_SCCALL),
+            (OlaOpcode::POSEIDON.binary_bit_mask(), COL_MEM_S_POSEIDON),
+            (OlaOpcode::SSTORE.binary_bit_mask(), COL_MEM_S_SSTORE),
+            (OlaOpcode::SLOAD.binary_bit_mask(), COL_MEM_S_SLOAD),
+            (0, COL_MEM_S_PROPHET),
+            (OlaOpcode::SIGCHECK.binary_bit_mask(), COL_MEM_S_SIGCHECK),
+            (OlaOpcode::EVENT.binary_bit_mask(), COL_MEM_S_EVENT),
+        ];
+        for (op, col) in selectors {
+            let op = builder.constant_extension(F::Extension::from_canonical_u64(op));
+            let op_diff = builder.sub_extension(lv[COL_MEM_OP], op);
+            let cs = builder.mul_extension(op_diff, lv[col]);
+            yield_constr.constraint(builder, cs);
+        }
+        for (_, col) in selectors {
+            let not_selected = builder.sub_extension(one, lv[col]);
+            let cs = builder.mul_extension(not_selected, lv[col]);
+            yield_constr.constraint(builder, cs);
+        }
+        let selector_sum = builder.add_many_extension(selectors.map(|(_, col)| lv[col]));
+        let cs = builder.sub_extension(one, selector_sum);
+        yield_constr.constraint(builder, cs);
+
+        // constraint is_rw region
+        let not_rw = builder.sub_extension(one, is_rw);
+        let cs = builder.mul_extension(is_rw, not_rw);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_extension(lv[COL_MEM_IS_RW], lv[COL_MEM_S_PROPHET]);
+        yield_constr.constraint(builder, cs);
+        let prophet_or_mload = builder.add_extension(lv[COL_MEM_S_PROPHET], lv[COL_MEM_S_MLOAD]);
+        let not_prophet_or_mload = builder.sub_extension(one, prophet_or_mload);
+        let cs = builder.mul_extension(not_rw, not_prophet_or_mload);
+        yield_constr.constraint(builder, cs);
+        // constraint is_write
+        let write_ops = builder.add_many_extension([
+            lv[COL_MEM_S_MSTORE],
+            lv[COL_MEM_S_CALL],
+            lv[COL_MEM_S_TLOAD],
+            lv[COL_MEM_S_POSEIDON],
+            lv[COL_MEM_S_SLOAD],
+            lv[COL_MEM_S_PROPHET],
+        ]);
+        let not_write_ops = builder.sub_extension(one, write_ops);
+        let cs = builder.mul_extension(lv[COL_MEM_IS_WRITE], not_write_ops);
+        yield_constr.constraint(builder, cs);
+        let read_ops = builder.add_many_extension([
+            lv[COL_MEM_S_MLOAD],
+            lv[COL_MEM_S_CALL],
+            lv[COL_MEM_S_RET],
+            lv[COL_MEM_S_TSTORE],
+            lv[COL_MEM_S_SCCALL],
+            lv[COL_MEM_S_POSEIDON],
+            lv[COL_MEM_S_SSTORE],
+            lv[COL_MEM_S_SLOAD],
+            lv[COL_MEM_S_SIGCHECK],
+            lv[COL_MEM_S_EVENT],
+        ]);
+        let not_read_ops = builder.sub_extension(one, read_ops);
+        let not_write = builder.sub_extension(one, lv[COL_MEM_IS_WRITE]);
+        let cs = builder.mul_extension(not_write, not_read_ops);
+        yield_constr.constraint(builder, cs);
+
+        // region is one of stack, heap and prophet
+        let region_sum = builder.add_many_extension([region_stack, region_heap, region_prophet]);
+        let cs = builder.sub_extension(one, region_sum);
+        yield_constr.constraint(builder, cs);
+        for region in [region_stack, region_heap, region_prophet] {
+            let not_region = builder.sub_extension(one, region);
+            let cs = builder.mul_extension(region, not_region);
+            yield_constr.constraint(builder, cs);
+        }
+        // make sure region addr is right
+        let prophet_addr = builder.add_extension(addr, diff_addr_cond);
+        let prophet_addr = builder.sub_extension(zero, prophet_addr);
+        let cs = builder.mul_extension(region_prophet, prophet_addr);
+        yield_constr.constraint(builder, cs);
+        let heap_addr = builder.sub_extension(prophet_addr, span);
+        let cs = builder.mul_extension(region_heap, heap_addr);
+        yield_constr.constraint(builder, cs);
+
+        // addr'-addr-diff_addr'= 0
+        let region_heap_diff = builder.sub_extension(nv_region_heap, region_heap);
+        let region_heap_diff_sub_one = builder.sub_extension(region_heap_diff, one);
+        let same_tx_env_region = builder.mul_extension(same_tx_env, region_heap_diff_sub_one);
+        let addr_diff = builder.sub_extension(nv_addr, addr);
+        let addr_diff_cs = builder.sub_extension(addr_diff, nv_diff_addr);
+        let cs = builder.mul_extension(same_tx_env_region, addr_diff_cs);
+        yield_constr.constraint_transition(builder, cs);
+        // constaint rw_addr_unchanged, for stack and heap
+        let rw_addr_unchanged_cs = builder.mul_extension(nv_diff_addr, nv_diff_addr_inv);
+        let rw_addr_unchanged_cs =
+            builder.add_extension(nv_rw_addr_unchanged, rw_addr_unchanged_cs);
+        let rw_addr_unchanged_cs = builder.sub_extension(one, rw_addr_unchanged_cs);
+        let cs = builder.mul_many_extension([
+            same_tx_env,
+            region_stack,
+            nv_region_stack,
+            rw_addr_unchanged_cs,
+        ]);
+        yield_constr.constraint_transition(builder, cs);
+        let cs = builder.mul_many_extension([
+            same_tx_env,
+            region_heap,
+            nv_region_heap,
+            rw_addr_unchanged_cs,
+        ]);
+        yield_constr.constraint_transition(builder, cs);
+
+        // for write once:
+        // 1. addr doesn't change or increase by 1 in prophet region;
+        // 2. when addr not increase, must be read.
+        let prophet_both = builder.mul_extension(region_prophet, nv_region_prophet);
+        let addr_diff_sub_one = builder.sub_extension(addr_diff, one);
+        let cs = builder.mul_many_extension([prophet_both, addr_diff, addr_diff_sub_one]);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_many_extension([prophet_both, addr_diff_sub_one, nv_is_write]);
+        yield_constr.constraint(builder, cs);
+
+        // read/write constraint:
+        // 1. first operation for each addr must be write(except heap ptr);
+        // 2. next value does not change if it is read(except heap ptr).
+        // 3. if heap ptr first op is read, it must be heap_ptr + 1.
+        let not_write = builder.sub_extension(one, is_write);
+        let addr_sub_heap_ptr = builder.sub_extension(addr, addr_heap_ptr);
+        let cs = builder.mul_many_extension([is_rw, not_write, addr_sub_heap_ptr]);
+        yield_constr.constraint_first_row(builder, cs);
+        let nv_not_write = builder.sub_extension(one, nv_is_write);
+        let nv_addr_sub_heap_ptr = builder.sub_extension(nv_addr, addr_heap_ptr);
+        let cs = builder.mul_many_extension([
+            tx_idx_diff,
+            env_idx_diff,
+            nv[COL_MEM_IS_RW],
+            nv_not_write,
+            nv_addr_sub_heap_ptr,
+        ]);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_many_extension([addr_diff, nv_not_write, nv_addr_sub_heap_ptr]);
+        yield_constr.constraint(builder, cs);
+        let value_diff = builder.sub_extension(nv_value, value);
+        let cs = builder.mul_many_extension([nv_not_write, value_diff, nv_addr_sub_heap_ptr]);
+        yield_constr.constraint(builder, cs);
+
+        // The native evaluation selects on `nv_addr == ADDR_HEAP_PTR`, which
+        // outside the trace domain only holds with negligible probability, so
+        // both heap ptr constraints are zero at the opening point.
+        yield_constr.constraint(builder, zero);
+        yield_constr.constraint(builder, zero);
+
+        // rc_value constraint:
+        let rw_same_region =
+            builder.mul_many_extension([same_tx_env, is_rw, region_heap_diff_sub_one]);
+        let rc_clk = builder.mul_extension(rw_addr_unchanged, diff_clk);
+        let rc_clk = builder.sub_extension(rc_value, rc_clk);
+        let addr_changed = builder.sub_extension(one, rw_addr_unchanged);
+        let rc_addr = builder.mul_extension(addr_changed, diff_addr);
+        let rc_addr = builder.sub_extension(rc_value, rc_addr);
+        let cs = builder.mul_many_extension([rw_same_region, rc_clk, rc_addr]);
+        yield_constr.constraint_transition(builder, cs);
+        let not_filter_looking_rc = builder.sub_extension(one, filter_looking_rc);
+        let cs = builder.mul_many_extension([rw_same_region, rc_value, not_filter_looking_rc]);
+        yield_constr.constraint_transition(builder, cs);
+
+        // heap and prophet read, diff_cond must rc
+        let not_filter_looking_rc_cond = builder.sub_extension(one, lv_filter_looking_rc_cond);
+        let cs = builder.mul_extension(not_filter_looking_rc_cond, region_heap);
+        yield_constr.constraint(builder, cs);
+        let cs =
+            builder.mul_many_extension([not_filter_looking_rc_cond, region_prophet, not_write]);
+        yield_constr.constraint(builder, cs);
     }
 
     fn constraint_degree(&self) -> usize {
@@ -352,7 +595,7 @@ mod tests {
     use crate::stark::constraint_consumer::ConstraintConsumer;
     use crate::stark::stark::Stark;
     use crate::stark::vars::StarkEvaluationVars;
-    use crate::test_utils::test_stark_with_asm_path;
+    use crate::test_utils::{test_stark_circuit_constraints, test_stark_with_asm_path};
     use core::trace::trace::{MemoryTraceCell, Trace};
     use core::types::Field;
 
@@ -458,4 +701,12 @@ mod tests {
             None,
         );
     }
+
+    #[test]
+    fn test_memory_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(MemoryStark::<F, D>::default()).unwrap();
+    }
 }
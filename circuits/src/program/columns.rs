@@ -3,15 +3,21 @@ use std::{collections::BTreeMap, ops::Range};
 pub(crate) const COL_PROG_CODE_ADDR_RANGE: Range<usize> = 0..4;
 pub(crate) const COL_PROG_PC: usize = COL_PROG_CODE_ADDR_RANGE.end;
 pub(crate) const COL_PROG_INST: usize = COL_PROG_PC + 1;
-pub(crate) const COL_PROG_COMP_PROG: usize = COL_PROG_INST + 1;
-pub(crate) const COL_PROG_COMP_PROG_PERM: usize = COL_PROG_COMP_PROG + 1;
+// code addr, pc and inst of a program row, looked up by the exec rows.
+pub(crate) const COL_PROG_ROW: Range<usize> = COL_PROG_CODE_ADDR_RANGE.start..COL_PROG_INST + 1;
+pub(crate) const COL_PROG_ROW_PERM: Range<usize> =
+    COL_PROG_ROW.end..COL_PROG_ROW.end + COL_PROG_ROW.end - COL_PROG_ROW.start;
 pub(crate) const COL_PROG_EXEC_CODE_ADDR_RANGE: Range<usize> =
-    COL_PROG_COMP_PROG_PERM + 1..COL_PROG_COMP_PROG_PERM + 1 + 4;
+    COL_PROG_ROW_PERM.end..COL_PROG_ROW_PERM.end + 4;
 pub(crate) const COL_PROG_EXEC_PC: usize = COL_PROG_EXEC_CODE_ADDR_RANGE.end;
 pub(crate) const COL_PROG_EXEC_INST: usize = COL_PROG_EXEC_PC + 1;
-pub(crate) const COL_PROG_EXEC_COMP_PROG: usize = COL_PROG_EXEC_INST + 1;
-pub(crate) const COL_PROG_EXEC_COMP_PROG_PERM: usize = COL_PROG_EXEC_COMP_PROG + 1;
-pub(crate) const COL_PROG_FILTER_EXEC: usize = COL_PROG_EXEC_COMP_PROG_PERM + 1;
+pub(crate) const COL_PROG_EXEC_ROW: Range<usize> =
+    COL_PROG_EXEC_CODE_ADDR_RANGE.start..COL_PROG_EXEC_INST + 1;
+pub(crate) const COL_PROG_EXEC_ROW_PERM: Range<usize> =
+    COL_PROG_EXEC_ROW.end..COL_PROG_EXEC_ROW.end + COL_PROG_EXEC_ROW.end - COL_PROG_EXEC_ROW.start;
+// the permuted exec row is the one above it.
+pub(crate) const COL_PROG_EXEC_ROW_PERM_SAME: usize = COL_PROG_EXEC_ROW_PERM.end;
+pub(crate) const COL_PROG_FILTER_EXEC: usize = COL_PROG_EXEC_ROW_PERM_SAME + 1;
 pub(crate) const COL_PROG_FILTER_PROG_CHUNK: usize = COL_PROG_FILTER_EXEC + 1;
 pub(crate) const NUM_PROG_COLS: usize = COL_PROG_FILTER_PROG_CHUNK + 1;
 
@@ -24,18 +30,23 @@ pub(crate) fn get_prog_col_name_map() -> BTreeMap<usize, String> {
     }
     m.insert(COL_PROG_PC, String::from("PC"));
     m.insert(COL_PROG_INST, String::from("INST"));
-    m.insert(COL_PROG_COMP_PROG, String::from("COMP_PROG"));
-    m.insert(COL_PROG_COMP_PROG_PERM, String::from("COMP_PROG_PERM"));
+    for (index, col) in COL_PROG_ROW_PERM.into_iter().enumerate() {
+        let name = format!("ROW_PERM_{}", index);
+        m.insert(col, name);
+    }
     for (index, col) in COL_PROG_EXEC_CODE_ADDR_RANGE.into_iter().enumerate() {
         let name = format!("EXEC_ADDR_{}", index);
         m.insert(col, name);
     }
     m.insert(COL_PROG_EXEC_PC, String::from("EXEC_PC"));
     m.insert(COL_PROG_EXEC_INST, String::from("EXEC_INST"));
-    m.insert(COL_PROG_EXEC_COMP_PROG, String::from("EXEC_COMP_PROG"));
+    for (index, col) in COL_PROG_EXEC_ROW_PERM.into_iter().enumerate() {
+        let name = format!("EXEC_ROW_PERM_{}", index);
+        m.insert(col, name);
+    }
     m.insert(
-        COL_PROG_EXEC_COMP_PROG_PERM,
-        String::from("EXEC_COMP_PROG_PERM"),
+        COL_PROG_EXEC_ROW_PERM_SAME,
+        String::from("EXEC_ROW_PERM_SAME"),
     );
     m.insert(COL_PROG_FILTER_EXEC, String::from("FILTER_EXEC"));
     m.insert(
@@ -165,10 +165,100 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for ProgChunkStar
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut CircuitBuilder<F, D>,
-        _vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
+        let lv = vars.local_values;
+        let nv = vars.next_values;
+        let one = builder.one_extension();
+        let lv_is_padding = lv[COL_PROG_CHUNK_IS_PADDING_LINE];
+        let nv_is_padding = nv[COL_PROG_CHUNK_IS_PADDING_LINE];
+        let lv_is_first_line = lv[COL_PROG_CHUNK_IS_FIRST_LINE];
+        let nv_is_first_line = nv[COL_PROG_CHUNK_IS_FIRST_LINE];
+        let lv_is_result_line = lv[COL_PROG_CHUNK_IS_RESULT_LINE];
+        let lv_not_padding = builder.sub_extension(one, lv_is_padding);
+        let nv_not_padding = builder.sub_extension(one, nv_is_padding);
+        let lv_not_result_line = builder.sub_extension(one, lv_is_result_line);
+        let nv_not_first_line = builder.sub_extension(one, nv_is_first_line);
+        // (1 - nv_is_padding) * (1 - lv_is_result_line)
+        let nv_in_same_chunk = builder.mul_extension(nv_not_padding, lv_not_result_line);
+
+        // is padding is binary, can change from 0 to 1
+        let cs = builder.mul_extension(lv_is_padding, lv_not_padding);
+        yield_constr.constraint(builder, cs);
+        let padding_diff = builder.sub_extension(nv_is_padding, lv_is_padding);
+        let cs = builder.mul_sub_extension(padding_diff, padding_diff, padding_diff);
+        yield_constr.constraint_transition(builder, cs);
+
+        // is_first_line0 = 1
+        let lv_not_first_line = builder.sub_extension(one, lv_is_first_line);
+        let cs = builder.mul_extension(lv_not_padding, lv_not_first_line);
+        yield_constr.constraint_first_row(builder, cs);
+        // if local not result line, next must not be first line
+        let cs = builder.mul_extension(nv_in_same_chunk, nv_is_first_line);
+        yield_constr.constraint_transition(builder, cs);
+        // if local is result line, next must be first line
+        let cs = builder.mul_many_extension([nv_not_padding, lv_is_result_line, nv_not_first_line]);
+        yield_constr.constraint_transition(builder, cs);
+        // between first line and result line, code_addr not change
+        for (&nv_addr_limb, &lv_addr_limb) in nv[COL_PROG_CHUNK_CODE_ADDR_RANGE]
+            .iter()
+            .zip(lv[COL_PROG_CHUNK_CODE_ADDR_RANGE].iter())
+        {
+            let addr_diff = builder.sub_extension(nv_addr_limb, lv_addr_limb);
+            let cs = builder.mul_extension(nv_in_same_chunk, addr_diff);
+            yield_constr.constraint_transition(builder, cs);
+        }
+        // chunk_start_pc is 0 at first line, increase by 8 between first line and
+        // result line
+        let cs = builder.mul_extension(lv_is_first_line, lv[COL_PROG_CHUNK_START_PC]);
+        yield_constr.constraint(builder, cs);
+        let eight = builder.constant_extension(F::Extension::from_canonical_u64(8));
+        let pc_diff =
+            builder.sub_extension(nv[COL_PROG_CHUNK_START_PC], lv[COL_PROG_CHUNK_START_PC]);
+        let pc_diff = builder.sub_extension(pc_diff, eight);
+        let cs = builder.mul_extension(nv_in_same_chunk, pc_diff);
+        yield_constr.constraint_transition(builder, cs);
+
+        // first line cap is [0;4], other line is last line hash[8~11]
+        for &cap_limb in lv[COL_PROG_CHUNK_CAP_RANGE].iter() {
+            let cs = builder.mul_extension(lv_is_first_line, cap_limb);
+            yield_constr.constraint(builder, cs);
+        }
+        let nv_is_following_line = builder.mul_extension(nv_not_padding, nv_not_first_line);
+        for (&nv_cap_limb, &lv_hash_limb) in nv[COL_PROG_CHUNK_CAP_RANGE]
+            .iter()
+            .zip(lv[COL_PROG_CHUNK_HASH_RANGE].iter().skip(8))
+        {
+            let cap_diff = builder.sub_extension(nv_cap_limb, lv_hash_limb);
+            let cs = builder.mul_extension(nv_is_following_line, cap_diff);
+            yield_constr.constraint(builder, cs);
+        }
+        // filter_looking_prog is 1 in non-result line; in result line, first is
+        // 1, can change to 0
+        let lv_is_inst_line = builder.mul_extension(lv_not_padding, lv_not_result_line);
+        for &filter in lv[COL_PROG_CHUNK_FILTER_LOOKING_PROG_RANGE].iter() {
+            let not_filter = builder.sub_extension(one, filter);
+            let cs = builder.mul_extension(filter, not_filter);
+            yield_constr.constraint(builder, cs);
+            let cs = builder.mul_extension(lv_is_inst_line, not_filter);
+            yield_constr.constraint(builder, cs);
+        }
+        let not_first_filter =
+            builder.sub_extension(one, lv[COL_PROG_CHUNK_FILTER_LOOKING_PROG_RANGE.start]);
+        let cs = builder.mul_extension(lv_is_result_line, not_first_filter);
+        yield_constr.constraint(builder, cs);
+        for (&after, &pre) in lv[COL_PROG_CHUNK_FILTER_LOOKING_PROG_RANGE]
+            .iter()
+            .take(7)
+            .zip(lv[COL_PROG_CHUNK_FILTER_LOOKING_PROG_RANGE].iter().skip(1))
+        {
+            let filter_diff = builder.sub_extension(after, pre);
+            let not_filter_diff = builder.sub_extension(one, filter_diff);
+            let cs = builder.mul_many_extension([lv_is_result_line, filter_diff, not_filter_diff]);
+            yield_constr.constraint(builder, cs);
+        }
     }
 
     fn constraint_degree(&self) -> usize {
@@ -197,7 +287,7 @@ mod tests {
 
     use crate::{
         stark::{constraint_consumer::ConstraintConsumer, vars::StarkEvaluationVars},
-        test_utils::test_stark_with_asm_path,
+        test_utils::{test_stark_circuit_constraints, test_stark_with_asm_path},
     };
 
     #[test]
@@ -270,4 +360,12 @@ mod tests {
             None,
         );
     }
+
+    #[test]
+    fn test_prog_chunk_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        test_stark_circuit_constraints::<F, C, _, D>(ProgChunkStark::<F, D>::default()).unwrap();
+    }
 }
@@ -16,12 +16,11 @@ use super::columns::*;
 use crate::stark::{
     constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer},
     cross_table_lookup::Column,
-    lookup::eval_lookups,
+    lookup::{eval_tuple_lookups, eval_tuple_lookups_circuit},
     permutation::PermutationPair,
     stark::Stark,
     vars::{StarkEvaluationTargets, StarkEvaluationVars},
 };
-use anyhow::Result;
 
 pub fn ctl_data_by_cpu<F: Field>() -> Vec<Column<F>> {
     Column::singles(COL_PROG_EXEC_CODE_ADDR_RANGE.chain([COL_PROG_EXEC_PC, COL_PROG_EXEC_INST]))
@@ -42,22 +41,9 @@ pub fn ctl_filter_by_program_chunk<F: Field>() -> Column<F> {
 
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ProgramStark<F, const D: usize> {
-    compress_challenge: Option<F>,
     pub _phantom: PhantomData<F>,
 }
 
-impl<F: RichField, const D: usize> ProgramStark<F, D> {
-    pub fn set_compress_challenge(&mut self, challenge: F) -> Result<()> {
-        assert!(self.compress_challenge.is_none(), "already set?");
-        self.compress_challenge = Some(challenge);
-        Ok(())
-    }
-
-    pub fn get_compress_challenge(&self) -> Option<F> {
-        self.compress_challenge
-    }
-}
-
 impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for ProgramStark<F, D> {
     const COLUMNS: usize = NUM_PROG_COLS;
 
@@ -69,39 +55,30 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for ProgramStark<
         FE: FieldExtension<D2, BaseField = F>,
         P: PackedField<Scalar = FE>,
     {
-        let beta = FE::from_basefield(self.get_compress_challenge().unwrap());
-        yield_constr.constraint(
-            vars.local_values[COL_PROG_CODE_ADDR_RANGE.start]
-                + vars.local_values[COL_PROG_CODE_ADDR_RANGE.start + 1] * beta
-                + vars.local_values[COL_PROG_CODE_ADDR_RANGE.start + 2] * beta.square()
-                + vars.local_values[COL_PROG_CODE_ADDR_RANGE.start + 3] * beta.cube()
-                + vars.local_values[COL_PROG_PC] * beta.square() * beta.square()
-                + vars.local_values[COL_PROG_INST] * beta.square() * beta.cube()
-                - vars.local_values[COL_PROG_COMP_PROG],
-        );
-        yield_constr.constraint(
-            vars.local_values[COL_PROG_EXEC_CODE_ADDR_RANGE.start]
-                + vars.local_values[COL_PROG_EXEC_CODE_ADDR_RANGE.start + 1] * beta
-                + vars.local_values[COL_PROG_EXEC_CODE_ADDR_RANGE.start + 2] * beta.square()
-                + vars.local_values[COL_PROG_EXEC_CODE_ADDR_RANGE.start + 3] * beta.cube()
-                + vars.local_values[COL_PROG_EXEC_PC] * beta.square() * beta.square()
-                + vars.local_values[COL_PROG_EXEC_INST] * beta.square() * beta.cube()
-                - vars.local_values[COL_PROG_EXEC_COMP_PROG],
-        );
-        eval_lookups(
+        // every executed instruction is an instruction of the program at its pc.
+        eval_tuple_lookups(
             vars,
             yield_constr,
-            COL_PROG_EXEC_COMP_PROG_PERM,
-            COL_PROG_COMP_PROG_PERM,
+            COL_PROG_EXEC_ROW_PERM_SAME,
+            COL_PROG_EXEC_ROW_PERM,
+            COL_PROG_ROW_PERM,
         );
     }
 
     fn eval_ext_circuit(
         &self,
-        _builder: &mut CircuitBuilder<F, D>,
-        _vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
-        _yield_constr: &mut RecursiveConstraintConsumer<F, D>,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: StarkEvaluationTargets<D, { Self::COLUMNS }>,
+        yield_constr: &mut RecursiveConstraintConsumer<F, D>,
     ) {
+        eval_tuple_lookups_circuit(
+            builder,
+            vars,
+            yield_constr,
+            COL_PROG_EXEC_ROW_PERM_SAME,
+            COL_PROG_EXEC_ROW_PERM,
+            COL_PROG_ROW_PERM,
+        );
     }
 
     fn constraint_degree(&self) -> usize {
@@ -110,8 +87,12 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for ProgramStark<
 
     fn permutation_pairs(&self) -> Vec<PermutationPair> {
         vec![
-            PermutationPair::singletons(COL_PROG_COMP_PROG, COL_PROG_COMP_PROG_PERM),
-            PermutationPair::singletons(COL_PROG_EXEC_COMP_PROG, COL_PROG_EXEC_COMP_PROG_PERM),
+            PermutationPair {
+                column_pairs: COL_PROG_ROW.zip(COL_PROG_ROW_PERM).collect(),
+            },
+            PermutationPair {
+                column_pairs: COL_PROG_EXEC_ROW.zip(COL_PROG_EXEC_ROW_PERM).collect(),
+            },
         ]
     }
 }
@@ -136,6 +117,7 @@ mod tests {
     use std::{collections::HashMap, path::PathBuf};
 
     use crate::stark::{constraint_consumer::ConstraintConsumer, vars::StarkEvaluationVars};
+    use crate::test_utils::test_stark_circuit_constraints;
 
     #[test]
     fn test_program_storage() {
@@ -157,7 +139,7 @@ mod tests {
         type C = PoseidonGoldilocksConfig;
         type F = <C as GenericConfig<D>>::F;
         type S = ProgramStark<F, D>;
-        let mut stark = S::default();
+        let stark = S::default();
 
         let program = encode_asm_from_json_file(program_path).unwrap();
         let instructions = program.bytecode.split("\n");
@@ -204,18 +186,14 @@ mod tests {
             })
             .collect::<Vec<_>>();
 
-        let (rows, beta) = generate_prog_trace::<F>(
-            &program.trace.exec,
-            vec![(process.addr_storage, insts)],
-            ([GoldilocksField::ZERO; 4], [GoldilocksField::ZERO; 4]),
-        );
+        let rows =
+            generate_prog_trace::<F>(&program.trace.exec, vec![(process.addr_storage, insts)]);
         let len = rows[0].len();
         println!(
             "raw trace len:{}, extended len: {}",
             program.trace.builtin_bitwise_combined.len(),
             len
         );
-        stark.set_compress_challenge(beta);
         let last = GoldilocksField::primitive_root_of_unity(log2_strict(len)).inverse();
         let subgroup = GoldilocksField::cyclic_subgroup_known_order(
             GoldilocksField::primitive_root_of_unity(log2_strict(len)),
@@ -264,4 +242,13 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_program_circuit_constraints() {
+        const D: usize = 2;
+        type C = PoseidonGoldilocksConfig;
+        type F = <C as GenericConfig<D>>::F;
+        let stark = ProgramStark::<F, D>::default();
+        test_stark_circuit_constraints::<F, C, _, D>(stark).unwrap();
+    }
 }
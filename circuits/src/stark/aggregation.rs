@@ -0,0 +1,548 @@
+use std::ops::Range;
+
+use anyhow::{anyhow, ensure, Result};
+use plonky2::field::extension::Extendable;
+use plonky2::gates::noop::NoopGate;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, MerkleCapTarget, RichField};
+use plonky2::hash::poseidon::PoseidonHash;
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, Witness};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData, VerifierCircuitTarget};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+
+use super::config::StarkConfig;
+use super::ola_stark::{OlaStark, NUM_TABLES};
+use super::proof::{sig_checks_commitment, AllProof, AllProofTarget, SigCheck};
+use super::recursive_verifier::{
+    add_virtual_all_proof, set_all_proof_target, verify_proof_circuit,
+};
+use super::stark::Stark;
+use super::verifier::verify_sig_checks;
+use crate::builtins::bitwise::bitwise_stark::BitwiseStark;
+use crate::builtins::cmp::cmp_stark::CmpStark;
+use crate::builtins::event::event_stark::EventStark;
+use crate::builtins::poseidon::poseidon_chunk_stark::PoseidonChunkStark;
+use crate::builtins::poseidon::poseidon_stark::PoseidonStark;
+use crate::builtins::rangecheck::rangecheck_stark::RangeCheckStark;
+use crate::builtins::sccall::sccall_stark::SCCallStark;
+use crate::builtins::sigcheck::sigcheck_stark::SigCheckStark;
+use crate::builtins::storage::storage_access_stark::StorageAccessStark;
+use crate::builtins::tape::tape_stark::TapeStark;
+use crate::cpu::cpu_stark::CpuStark;
+use crate::fixed_table::bitwise_fixed::bitwise_fixed_stark::BitwiseFixedStark;
+use crate::fixed_table::rangecheck_fixed::rangecheck_fixed_stark::RangeCheckFixedStark;
+use crate::memory::memory_stark::MemoryStark;
+use crate::program::prog_chunk_stark::ProgChunkStark;
+use crate::program::program_stark::ProgramStark;
+
+/// Public inputs of a batch proof, and of the root proof wrapping it:
+/// `state_root_before`, `state_root_after`,
+/// `block_number`, `block_timestamp`, `sequencer_address`, `chain_id`,
+/// `tx_commitment`, `events_commitment` and `sig_checks_commitment`.
+pub const BATCH_STATE_ROOT_BEFORE: Range<usize> = 0..4;
+pub const BATCH_STATE_ROOT_AFTER: Range<usize> = 4..8;
+pub const BATCH_BLOCK_NUMBER: usize = 8;
+pub const BATCH_BLOCK_TIMESTAMP: usize = 9;
+pub const BATCH_SEQUENCER_ADDRESS: Range<usize> = 10..14;
+pub const BATCH_CHAIN_ID: usize = 14;
+pub const BATCH_TX_COMMITMENT: Range<usize> = 15..19;
+pub const BATCH_EVENTS_COMMITMENT: Range<usize> = 19..23;
+pub const BATCH_SIG_CHECKS_COMMITMENT: Range<usize> = 23..27;
+pub const NUM_BATCH_PUBLIC_INPUTS: usize = 27;
+
+/// Public inputs of an aggregated proof: the state root before the first
+/// batch, the state root after the last one and a Poseidon hash of the public
+/// inputs of every aggregated batch proof.
+pub const AGGREGATION_STATE_ROOT_BEFORE: Range<usize> = 0..4;
+pub const AGGREGATION_STATE_ROOT_AFTER: Range<usize> = 4..8;
+pub const AGGREGATION_BATCHES_COMMITMENT: Range<usize> = 8..12;
+pub const NUM_AGGREGATION_PUBLIC_INPUTS: usize = 12;
+
+/// Degree bits of every `RootCircuit`. Verifying a batch proof takes about
+/// 2^12 gates with the standard recursion config.
+pub const ROOT_DEGREE_BITS: usize = 13;
+
+/// Plonky2 circuit verifying the `AllProof` of one batch. The circuit is
+/// specific to the shape of the proof, i.e. the degree and number of CTL Z
+/// polynomials of each table, the number of transactions of the batch and the
+/// shape of its events and signature checks. A `RootCircuit` wraps its proofs
+/// into proofs of one fixed shape.
+///
+/// Signatures checked by `sigcheck` are not proven in the circuits, see
+/// `SigCheckStark`. A batch proof only commits to them through
+/// `sig_checks_commitment`, which `verify_batch_sig_checks` checks.
+pub struct BatchCircuit<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    pub circuit: CircuitData<F, C, D>,
+    all_proof_target: AllProofTarget<D>,
+}
+
+impl<F, C, const D: usize> BatchCircuit<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F>,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    /// Builds a circuit verifying proofs shaped like `all_proof`.
+    pub fn new(
+        ola_stark: OlaStark<F, D>,
+        all_proof: &AllProof<F, C, D>,
+        inner_config: &StarkConfig,
+        circuit_config: CircuitConfig,
+    ) -> Self
+    where
+        [(); CpuStark::<F, D>::COLUMNS]:,
+        [(); MemoryStark::<F, D>::COLUMNS]:,
+        [(); BitwiseStark::<F, D>::COLUMNS]:,
+        [(); CmpStark::<F, D>::COLUMNS]:,
+        [(); RangeCheckStark::<F, D>::COLUMNS]:,
+        [(); PoseidonStark::<F, D>::COLUMNS]:,
+        [(); PoseidonChunkStark::<F, D>::COLUMNS]:,
+        [(); StorageAccessStark::<F, D>::COLUMNS]:,
+        [(); TapeStark::<F, D>::COLUMNS]:,
+        [(); SCCallStark::<F, D>::COLUMNS]:,
+        [(); ProgramStark::<F, D>::COLUMNS]:,
+        [(); ProgChunkStark::<F, D>::COLUMNS]:,
+        [(); SigCheckStark::<F, D>::COLUMNS]:,
+        [(); EventStark::<F, D>::COLUMNS]:,
+        [(); BitwiseFixedStark::<F, D>::COLUMNS]:,
+        [(); RangeCheckFixedStark::<F, D>::COLUMNS]:,
+    {
+        let degree_bits = all_proof.degree_bits(inner_config);
+        let nums_ctl_zs: [usize; NUM_TABLES] =
+            std::array::from_fn(|i| all_proof.stark_proofs[i].num_ctl_zs());
+
+        let mut builder = CircuitBuilder::<F, D>::new(circuit_config);
+        let all_proof_target = add_virtual_all_proof(
+            &mut builder,
+            &ola_stark,
+            inner_config,
+            &degree_bits,
+            &nums_ctl_zs,
+            &all_proof.public_values,
+        );
+        verify_proof_circuit::<F, C, D>(&mut builder, ola_stark, &all_proof_target, inner_config);
+
+        let public_values = &all_proof_target.public_values;
+        let metadata = &public_values.block_metadata;
+        let tx_commitment =
+            builder.hash_n_to_hash_no_pad::<PoseidonHash>(public_values.tx_hashes.concat());
+        let events_commitment = public_values.events_commitment(&mut builder);
+        let sig_checks_commitment = public_values.sig_checks_commitment(&mut builder);
+        builder.register_public_inputs(&public_values.state_root_before);
+        builder.register_public_inputs(&public_values.state_root_after);
+        builder.register_public_input(metadata.block_number);
+        builder.register_public_input(metadata.block_timestamp);
+        builder.register_public_inputs(&metadata.sequencer_address);
+        builder.register_public_input(metadata.chain_id);
+        builder.register_public_inputs(&tx_commitment.elements);
+        builder.register_public_inputs(&events_commitment);
+        builder.register_public_inputs(&sig_checks_commitment);
+
+        Self {
+            circuit: builder.build::<C>(),
+            all_proof_target,
+        }
+    }
+
+    pub fn prove(&self, all_proof: &AllProof<F, C, D>) -> Result<ProofWithPublicInputs<F, C, D>> {
+        let mut pw = PartialWitness::new();
+        set_all_proof_target(&mut pw, &self.all_proof_target, all_proof);
+        self.circuit.prove(pw)
+    }
+
+    pub fn verify(&self, proof: ProofWithPublicInputs<F, C, D>) -> Result<()> {
+        self.circuit.verify(proof)
+    }
+}
+
+/// Checks that `sig_checks` are the preimage of the signature checks
+/// commitment in the `public_inputs` of a batch, root or aggregated proof of
+/// one batch, and verifies them.
+pub fn verify_batch_sig_checks<F: RichField>(
+    public_inputs: &[F],
+    sig_checks: &[SigCheck],
+) -> Result<()> {
+    ensure!(
+        public_inputs.len() == NUM_BATCH_PUBLIC_INPUTS,
+        "expected {} batch public inputs, got {}",
+        NUM_BATCH_PUBLIC_INPUTS,
+        public_inputs.len()
+    );
+    let commitment = sig_checks_commitment(sig_checks).map(F::from_noncanonical_u64);
+    ensure!(
+        public_inputs[BATCH_SIG_CHECKS_COMMITMENT] == commitment,
+        "Signature checks do not match the batch commitment."
+    );
+    verify_sig_checks(sig_checks)
+}
+
+/// Plonky2 circuit wrapping the proofs of one `BatchCircuit`. It has the
+/// public inputs of the batch proof and is padded to `ROOT_DEGREE_BITS`, so
+/// every root circuit has the same `CommonCircuitData` whatever the shape of
+/// its batch circuit. Only its verifier data differs.
+pub struct RootCircuit<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
+    pub circuit: CircuitData<F, C, D>,
+    batch_proof_target: ProofWithPublicInputsTarget<D>,
+}
+
+impl<F, C, const D: usize> RootCircuit<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F>,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    pub fn new(batch_circuit: &BatchCircuit<F, C, D>, circuit_config: CircuitConfig) -> Self {
+        let batch = &batch_circuit.circuit;
+        let mut builder = CircuitBuilder::<F, D>::new(circuit_config);
+        let batch_proof_target = builder.add_virtual_proof_with_pis(&batch.common);
+        let verifier_target = VerifierCircuitTarget {
+            constants_sigmas_cap: builder
+                .constant_merkle_cap(&batch.verifier_only.constants_sigmas_cap),
+            circuit_digest: builder.constant_hash(batch.verifier_only.circuit_digest),
+        };
+        builder.verify_proof::<C>(batch_proof_target.clone(), &verifier_target, &batch.common);
+        builder.register_public_inputs(&batch_proof_target.public_inputs);
+
+        // The builder adds a few gates of its own and pads to the next power
+        // of two, so padding to half the degree plus one ends at
+        // `2^ROOT_DEGREE_BITS`.
+        let min_gates = (1 << (ROOT_DEGREE_BITS - 1)) + 1;
+        for _ in builder.num_gates()..min_gates {
+            builder.add_gate(NoopGate, vec![]);
+        }
+        let circuit = builder.build::<C>();
+        assert_eq!(
+            circuit.common.degree_bits(),
+            ROOT_DEGREE_BITS,
+            "batch circuit is too large to wrap"
+        );
+
+        Self {
+            circuit,
+            batch_proof_target,
+        }
+    }
+
+    pub fn prove(
+        &self,
+        batch_proof: &ProofWithPublicInputs<F, C, D>,
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        let mut pw = PartialWitness::new();
+        pw.set_proof_with_pis_target(&self.batch_proof_target, batch_proof);
+        self.circuit.prove(pw)
+    }
+
+    pub fn verify(&self, proof: ProofWithPublicInputs<F, C, D>) -> Result<()> {
+        self.circuit.verify(proof)
+    }
+}
+
+/// Plonky2 circuit verifying `num_batches` root proofs whose state roots chain
+/// into each other. The verifier data of each inner proof is part of the
+/// witness and is checked to be the one of a root circuit the circuit was
+/// built for, so the circuit, and its verifier key, do not depend on the shape
+/// of the aggregated batches.
+pub struct AggregationCircuit<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+> {
+    pub circuit: CircuitData<F, C, D>,
+    root_digests: Vec<HashOut<F>>,
+    proof_targets: Vec<ProofWithPublicInputsTarget<D>>,
+    root_index_targets: Vec<Target>,
+}
+
+impl<F, C, const D: usize> AggregationCircuit<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F>,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    pub fn new(
+        root_circuits: &[&RootCircuit<F, C, D>],
+        num_batches: usize,
+        circuit_config: CircuitConfig,
+    ) -> Self {
+        assert!(!root_circuits.is_empty(), "no root circuits");
+        assert!(num_batches > 0, "nothing to aggregate");
+        let common = &root_circuits[0].circuit.common;
+        assert!(
+            root_circuits
+                .iter()
+                .all(|root| &root.circuit.common == common),
+            "root circuits do not share their common data"
+        );
+        let mut builder = CircuitBuilder::<F, D>::new(circuit_config);
+
+        let root_verifier_targets = root_circuits
+            .iter()
+            .map(|root| VerifierCircuitTarget {
+                constants_sigmas_cap: builder
+                    .constant_merkle_cap(&root.circuit.verifier_only.constants_sigmas_cap),
+                circuit_digest: builder.constant_hash(root.circuit.verifier_only.circuit_digest),
+            })
+            .collect::<Vec<_>>();
+        let (proof_targets, root_index_targets): (Vec<_>, Vec<_>) = (0..num_batches)
+            .map(|_| {
+                let proof_target = builder.add_virtual_proof_with_pis(common);
+                let root_index = builder.add_virtual_target();
+                let verifier_target =
+                    select_verifier_data(&mut builder, root_index, &root_verifier_targets);
+                builder.verify_proof::<C>(proof_target.clone(), &verifier_target, common);
+                (proof_target, root_index)
+            })
+            .unzip();
+
+        // Each batch starts from the state the previous one ended with.
+        for (prev, next) in proof_targets.iter().zip(proof_targets.iter().skip(1)) {
+            for (after, before) in BATCH_STATE_ROOT_AFTER.zip(BATCH_STATE_ROOT_BEFORE) {
+                builder.connect(prev.public_inputs[after], next.public_inputs[before]);
+            }
+        }
+
+        let first = proof_targets.first().unwrap();
+        let last = proof_targets.last().unwrap();
+        let batches_commitment = builder.hash_n_to_hash_no_pad::<PoseidonHash>(
+            proof_targets
+                .iter()
+                .flat_map(|p| p.public_inputs.clone())
+                .collect(),
+        );
+        builder.register_public_inputs(&first.public_inputs[BATCH_STATE_ROOT_BEFORE]);
+        builder.register_public_inputs(&last.public_inputs[BATCH_STATE_ROOT_AFTER]);
+        builder.register_public_inputs(&batches_commitment.elements);
+
+        Self {
+            circuit: builder.build::<C>(),
+            root_digests: root_circuits
+                .iter()
+                .map(|root| root.circuit.verifier_only.circuit_digest)
+                .collect(),
+            proof_targets,
+            root_index_targets,
+        }
+    }
+
+    /// Aggregates `root_proofs`, each given with the root circuit it was
+    /// proven with.
+    pub fn prove(
+        &self,
+        root_proofs: &[(&RootCircuit<F, C, D>, ProofWithPublicInputs<F, C, D>)],
+    ) -> Result<ProofWithPublicInputs<F, C, D>> {
+        ensure!(
+            root_proofs.len() == self.proof_targets.len(),
+            "expected {} root proofs, got {}",
+            self.proof_targets.len(),
+            root_proofs.len()
+        );
+        let mut pw = PartialWitness::new();
+        for ((target, index_target), (root, proof)) in self
+            .proof_targets
+            .iter()
+            .zip(&self.root_index_targets)
+            .zip(root_proofs)
+        {
+            let digest = root.circuit.verifier_only.circuit_digest;
+            let index = self
+                .root_digests
+                .iter()
+                .position(|d| *d == digest)
+                .ok_or_else(|| anyhow!("root circuit is not aggregated by this circuit"))?;
+            pw.set_target(*index_target, F::from_canonical_usize(index));
+            pw.set_proof_with_pis_target(target, proof);
+        }
+        self.circuit.prove(pw)
+    }
+
+    pub fn verify(&self, proof: ProofWithPublicInputs<F, C, D>) -> Result<()> {
+        self.circuit.verify(proof)
+    }
+}
+
+/// Selects the verifier data at `index` in `candidates`. `random_access`
+/// constrains `index` to the candidates, padded to a power of two with the
+/// last one.
+fn select_verifier_data<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    index: Target,
+    candidates: &[VerifierCircuitTarget],
+) -> VerifierCircuitTarget {
+    let padded = candidates
+        .iter()
+        .chain(std::iter::repeat(candidates.last().unwrap()))
+        .take(candidates.len().next_power_of_two())
+        .collect::<Vec<_>>();
+    let cap_len = candidates[0].constants_sigmas_cap.0.len();
+    let constants_sigmas_cap = MerkleCapTarget(
+        (0..cap_len)
+            .map(|i| {
+                let hashes = padded
+                    .iter()
+                    .map(|vd| vd.constants_sigmas_cap.0[i])
+                    .collect::<Vec<_>>();
+                select_hash(builder, index, &hashes)
+            })
+            .collect(),
+    );
+    let digests = padded
+        .iter()
+        .map(|vd| vd.circuit_digest)
+        .collect::<Vec<_>>();
+    VerifierCircuitTarget {
+        constants_sigmas_cap,
+        circuit_digest: select_hash(builder, index, &digests),
+    }
+}
+
+fn select_hash<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    index: Target,
+    hashes: &[HashOutTarget],
+) -> HashOutTarget {
+    HashOutTarget {
+        elements: std::array::from_fn(|i| {
+            builder.random_access(index, hashes.iter().map(|h| h.elements[i]).collect())
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use plonky2::field::types::Field64;
+    use plonky2::plonk::circuit_data::CircuitConfig;
+    use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
+
+    use super::*;
+    use crate::stark::config::StarkConfig;
+    use crate::stark::ola_stark::OlaStark;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::prove_by_asm_path;
+
+    const D: usize = 2;
+    type C = PoseidonGoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    fn asm_path(file_name: &str) -> String {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("../assembler/test_data/asm/");
+        path.push(file_name);
+        path.display().to_string()
+    }
+
+    #[test]
+    fn test_batch_and_aggregation_circuits() -> Result<()> {
+        let inner_config = StarkConfig::standard_fast_config();
+        let call_proof = prove_by_asm_path::<C>(asm_path("call.json"), None, None)?;
+        let memory_proof = prove_by_asm_path::<C>(asm_path("memory.json"), None, None)?;
+        verify_proof(OlaStark::default(), call_proof.clone(), &inner_config)?;
+        verify_proof(OlaStark::default(), memory_proof.clone(), &inner_config)?;
+        // the batch circuits differ, the root circuits only by their verifier data.
+        assert_ne!(
+            call_proof.degree_bits(&inner_config),
+            memory_proof.degree_bits(&inner_config)
+        );
+
+        let call_batch = BatchCircuit::<F, C, D>::new(
+            OlaStark::default(),
+            &call_proof,
+            &inner_config,
+            CircuitConfig::standard_recursion_config(),
+        );
+        let call_batch_proof = call_batch.prove(&call_proof)?;
+        let public_values = &call_proof.public_values;
+        let expected = public_values
+            .state_root_before
+            .iter()
+            .chain(&public_values.state_root_after)
+            .chain([
+                &public_values.block_metadata.block_number,
+                &public_values.block_metadata.block_timestamp,
+            ])
+            .chain(&public_values.block_metadata.sequencer_address)
+            .chain([&public_values.block_metadata.chain_id])
+            .chain(&public_values.tx_commitment())
+            .chain(&public_values.events_commitment())
+            .chain(&public_values.sig_checks_commitment())
+            .map(|&v| F::from_noncanonical_u64(v))
+            .collect::<Vec<_>>();
+        assert_eq!(call_batch_proof.public_inputs, expected);
+        call_batch.verify(call_batch_proof.clone())?;
+        // the batch proof only commits to its signature checks.
+        verify_batch_sig_checks(&call_batch_proof.public_inputs, &public_values.sig_checks)?;
+        let forged = vec![SigCheck {
+            verified: true,
+            ..Default::default()
+        }];
+        assert!(verify_batch_sig_checks(&call_batch_proof.public_inputs, &forged).is_err());
+
+        let memory_batch = BatchCircuit::<F, C, D>::new(
+            OlaStark::default(),
+            &memory_proof,
+            &inner_config,
+            CircuitConfig::standard_recursion_config(),
+        );
+        let memory_batch_proof = memory_batch.prove(&memory_proof)?;
+
+        let call_root = RootCircuit::new(&call_batch, CircuitConfig::standard_recursion_config());
+        let memory_root =
+            RootCircuit::new(&memory_batch, CircuitConfig::standard_recursion_config());
+        assert_eq!(call_root.circuit.common, memory_root.circuit.common);
+        let call_root_proof = call_root.prove(&call_batch_proof)?;
+        let memory_root_proof = memory_root.prove(&memory_batch_proof)?;
+        assert_eq!(
+            call_root_proof.public_inputs,
+            call_batch_proof.public_inputs
+        );
+        call_root.verify(call_root_proof.clone())?;
+
+        // neither program touches storage, so the batches chain in any order.
+        assert_eq!(
+            public_values.state_root_before,
+            public_values.state_root_after
+        );
+        assert_eq!(
+            memory_proof.public_values.state_root_before,
+            public_values.state_root_after
+        );
+        let aggregation_circuit = AggregationCircuit::<F, C, D>::new(
+            &[&call_root, &memory_root],
+            2,
+            CircuitConfig::standard_recursion_config(),
+        );
+        let aggregated_proof = aggregation_circuit.prove(&[
+            (&call_root, call_root_proof.clone()),
+            (&memory_root, memory_root_proof.clone()),
+        ])?;
+        assert_eq!(
+            aggregated_proof.public_inputs[AGGREGATION_STATE_ROOT_BEFORE],
+            call_batch_proof.public_inputs[BATCH_STATE_ROOT_BEFORE]
+        );
+        let batches_commitment = PoseidonHash::hash_no_pad(
+            &[
+                call_batch_proof.public_inputs.clone(),
+                memory_batch_proof.public_inputs.clone(),
+            ]
+            .concat(),
+        );
+        assert_eq!(
+            aggregated_proof.public_inputs[AGGREGATION_BATCHES_COMMITMENT],
+            batches_commitment.elements
+        );
+        aggregation_circuit.verify(aggregated_proof)?;
+
+        // the same circuit, and verifier key, aggregates batches of any shape.
+        let aggregated_proof = aggregation_circuit.prove(&[
+            (&memory_root, memory_root_proof),
+            (&call_root, call_root_proof),
+        ])?;
+        aggregation_circuit.verify(aggregated_proof)
+    }
+}
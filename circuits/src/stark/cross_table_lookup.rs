@@ -376,6 +376,13 @@ impl<F: Field> TableWithColumns<F> {
     }
 }
 
+/// Lookup of the filtered rows of `looking_tables` in the filtered rows of
+/// `looked_table`, each row looked up exactly once. Checked with one running
+/// grand product Z polynomial per table and challenge.
+///
+/// Only the lookups inside a table (`Stark::lookups`, see `lookup`) use LogUp
+/// with multiplicities. CTLs keep grand products: they match rows one to one,
+/// and the extra looking products of the public values rely on them.
 #[derive(Clone, Debug)]
 pub struct CrossTableLookup<F: Field> {
     looking_tables: Vec<TableWithColumns<F>>,
@@ -499,6 +506,14 @@ impl<F: Field> CrossTableLookup<F> {
         }
     }
 
+    /// The looked table of a CTL whose looking side is provided by the public
+    /// values rather than by other tables.
+    pub(crate) fn public_looked_table(&self) -> Option<Table> {
+        self.looking_tables
+            .is_empty()
+            .then_some(self.looked_table.table)
+    }
+
     #[allow(unused)]
     pub(crate) fn num_ctl_zs(ctls: &[Self], table: Table, num_challenges: usize) -> usize {
         let mut num_ctls = 0;
@@ -571,12 +586,14 @@ pub fn cross_table_lookup_data<F: RichField, C: GenericConfig<D, F = F>, const D
                 challenge,
             );
 
-            debug_assert_eq!(
-                zs_looking
-                    .clone()
-                    .map(|z| *z.values.last().unwrap())
-                    .product::<F>(),
-                *z_looked.values.last().unwrap()
+            // The public values only enter the check on the verifier side.
+            debug_assert!(
+                looking_tables.is_empty()
+                    || zs_looking
+                        .clone()
+                        .map(|z| *z.values.last().unwrap())
+                        .product::<F>()
+                        == *z_looked.values.last().unwrap()
             );
 
             for (table, z) in looking_tables.iter().zip(zs_looking) {
@@ -880,12 +897,14 @@ pub(crate) fn verify_cross_table_lookups<
     config: &StarkConfig,
 ) -> Result<()> {
     let mut ctl_zs_openings = ctl_zs_lasts.iter().map(|v| v.iter()).collect::<Vec<_>>();
-    for CrossTableLookup {
-        looking_tables,
-        looked_table,
-    } in cross_table_lookups.iter()
+    for (
+        CrossTableLookup {
+            looking_tables,
+            looked_table,
+        },
+        extra_product_vec,
+    ) in cross_table_lookups.iter().zip(&ctl_extra_looking_products)
     {
-        let extra_product_vec = &ctl_extra_looking_products[looked_table.table as usize];
         for c in 0..config.num_challenges {
             let looking_zs_prod = looking_tables
                 .iter()
@@ -917,12 +936,16 @@ pub(crate) fn verify_cross_table_lookups_circuit<
     inner_config: &StarkConfig,
 ) {
     let mut ctl_zs_openings = ctl_zs_lasts.iter().map(|v| v.iter()).collect::<Vec<_>>();
-    for CrossTableLookup {
-        looking_tables,
-        looked_table,
-    } in cross_table_lookups.into_iter()
+    for (
+        CrossTableLookup {
+            looking_tables,
+            looked_table,
+        },
+        extra_product_vec,
+    ) in cross_table_lookups
+        .into_iter()
+        .zip(&ctl_extra_looking_products)
     {
-        let extra_product_vec = &ctl_extra_looking_products[looked_table.table as usize];
         for c in 0..inner_config.num_challenges {
             let mut looking_zs_prod = builder.mul_many(
                 looking_tables
@@ -970,6 +993,10 @@ pub(crate) mod testutils {
             looking_tables,
             looked_table,
         } = ctl;
+        // Looked up by the public values, which are not part of the traces.
+        if looking_tables.is_empty() {
+            return;
+        }
 
         // Maps `m` with `(table, i) in m[row]` iff the `i`-th row of `table` is equal
         // to `row` and the filter is 1. Without default values, the CTL check
@@ -3,16 +3,84 @@ use plonky2::fri::proof::{FriProof, FriProofTarget};
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::challenger::{Challenger, RecursiveChallenger};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
 
 use super::config::StarkConfig;
 use super::ola_stark::{OlaStark, NUM_TABLES};
 use super::permutation::{
-    get_grand_product_challenge_set, get_n_grand_product_challenge_sets,
-    get_n_grand_product_challenge_sets_target,
+    get_grand_product_challenge_set, get_grand_product_challenge_set_target,
+    get_n_grand_product_challenge_sets, get_n_grand_product_challenge_sets_target,
 };
 use super::proof::*;
 
+pub(crate) fn observe_public_values<F: RichField, H: Hasher<F>>(
+    challenger: &mut Challenger<F, H>,
+    public_values: &PublicValues,
+) {
+    let PublicValues {
+        state_root_before,
+        state_root_after,
+        block_metadata,
+        tx_hashes,
+        ..
+    } = public_values;
+    let event_cells = public_values.event_cells();
+    let sig_check_cells = public_values.sig_check_cells();
+    let mut values = vec![];
+    values.extend(state_root_before);
+    values.extend(state_root_after);
+    values.push(block_metadata.block_number);
+    values.push(block_metadata.block_timestamp);
+    values.extend(block_metadata.sequencer_address);
+    values.push(block_metadata.chain_id);
+    values.push(tx_hashes.len() as u64);
+    values.extend(tx_hashes.concat());
+    values.push(event_cells.len() as u64);
+    values.extend(event_cells.concat());
+    values.push(sig_check_cells.len() as u64);
+    values.extend(sig_check_cells.concat());
+    challenger.observe_elements(
+        &values
+            .into_iter()
+            .map(F::from_noncanonical_u64)
+            .collect::<Vec<_>>(),
+    );
+}
+
+pub(crate) fn observe_public_values_target<
+    F: RichField + Extendable<D>,
+    H: AlgebraicHasher<F>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    challenger: &mut RecursiveChallenger<F, H, D>,
+    public_values: &PublicValuesTarget,
+) {
+    let PublicValuesTarget {
+        state_root_before,
+        state_root_after,
+        block_metadata,
+        tx_hashes,
+        ..
+    } = public_values;
+    let event_cells = public_values.event_cells(builder);
+    let sig_check_cells = public_values.sig_check_cells();
+    let mut values = vec![];
+    values.extend(state_root_before);
+    values.extend(state_root_after);
+    values.push(block_metadata.block_number);
+    values.push(block_metadata.block_timestamp);
+    values.extend(block_metadata.sequencer_address);
+    values.push(block_metadata.chain_id);
+    values.push(builder.constant(F::from_canonical_usize(tx_hashes.len())));
+    values.extend(tx_hashes.concat());
+    values.push(builder.constant(F::from_canonical_usize(event_cells.len())));
+    values.extend(event_cells.concat());
+    values.push(builder.constant(F::from_canonical_usize(sig_check_cells.len())));
+    values.extend(sig_check_cells.concat());
+    challenger.observe_elements(&values);
+}
+
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> AllProof<F, C, D> {
     /// Computes all Fiat-Shamir challenges used in the STARK proof.
     pub(crate) fn get_challenges(
@@ -26,7 +94,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> A
             challenger.observe_cap(&proof.trace_cap);
         }
 
-        // TODO: Observe public values.
+        observe_public_values(&mut challenger, &self.public_values);
 
         let ctl_challenges =
             get_grand_product_challenge_set(&mut challenger, config.num_challenges);
@@ -60,7 +128,7 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> A
             challenger.observe_cap(&proof.trace_cap);
         }
 
-        // TODO: Observe public values.
+        observe_public_values(&mut challenger, &self.public_values);
 
         let ctl_challenges =
             get_grand_product_challenge_set(&mut challenger, config.num_challenges);
@@ -149,8 +217,49 @@ where
     }
 }
 
+impl<const D: usize> AllProofTarget<D> {
+    /// Computes all Fiat-Shamir challenges used in the STARK proof, in the same
+    /// order as `AllProof::get_challenges`.
+    pub(crate) fn get_challenges<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        ola_stark: &OlaStark<F, D>,
+        config: &StarkConfig,
+    ) -> AllProofChallengesTarget<D>
+    where
+        C::Hasher: AlgebraicHasher<F>,
+    {
+        let mut challenger = RecursiveChallenger::<F, C::Hasher, D>::new(builder);
+
+        for proof in &self.stark_proofs {
+            challenger.observe_cap(&proof.trace_cap);
+        }
+
+        observe_public_values_target(builder, &mut challenger, &self.public_values);
+
+        let ctl_challenges =
+            get_grand_product_challenge_set_target(builder, &mut challenger, config.num_challenges);
+
+        let num_permutation_zs = ola_stark.nums_permutation_zs(config);
+        let num_permutation_batch_sizes = ola_stark.permutation_batch_sizes();
+
+        AllProofChallengesTarget {
+            stark_challenges: std::array::from_fn(|i| {
+                challenger.compact(builder);
+                self.stark_proofs[i].get_challenges::<F, C>(
+                    builder,
+                    &mut challenger,
+                    num_permutation_zs[i] > 0,
+                    num_permutation_batch_sizes[i],
+                    config,
+                )
+            }),
+            ctl_challenges,
+        }
+    }
+}
+
 impl<const D: usize> StarkProofTarget<D> {
-    #[allow(unused)]
     pub(crate) fn get_challenges<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>>(
         &self,
         builder: &mut CircuitBuilder<F, D>,
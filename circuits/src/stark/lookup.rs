@@ -1,40 +1,50 @@
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ops::Range;
 
 use itertools::Itertools;
-use plonky2::field::extension::Extendable;
+use maybe_rayon::*;
+use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::packed::PackedField;
+use plonky2::field::polynomial::PolynomialValues;
 use plonky2::field::types::{Field, PrimeField64};
 use plonky2::hash::hash_types::RichField;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2_util::ceil_div_usize;
 
+use crate::stark::config::StarkConfig;
 use crate::stark::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use crate::stark::permutation::{
+    GrandProductChallengeSet, PermutationCheckDataTarget, PermutationCheckVars,
+};
+use crate::stark::stark::Stark;
 use crate::stark::vars::{StarkEvaluationTargets, StarkEvaluationVars};
 
-pub(crate) fn eval_lookups<F: Field, P: PackedField<Scalar = F>, const COLS: usize>(
+/// Lookup of rows of several columns into a table of rows of as many columns,
+/// without compressing the rows into one value. `col_same` selects the
+/// permuted input rows which repeat the row above them, the other permuted
+/// input rows equal the permuted table row next to them.
+pub(crate) fn eval_tuple_lookups<F: Field, P: PackedField<Scalar = F>, const COLS: usize>(
     vars: StarkEvaluationVars<F, P, COLS>,
     yield_constr: &mut ConstraintConsumer<P>,
-    col_permuted_input: usize,
-    col_permuted_table: usize,
+    col_same: usize,
+    cols_permuted_input: Range<usize>,
+    cols_permuted_table: Range<usize>,
 ) {
-    let local_perm_input = vars.local_values[col_permuted_input];
-    let next_perm_table = vars.next_values[col_permuted_table];
-    let next_perm_input = vars.next_values[col_permuted_input];
-
-    // A "vertical" diff between the local and next permuted inputs.
-    let diff_input_prev = next_perm_input - local_perm_input;
-    // A "horizontal" diff between the next permuted input and permuted table value.
-    let diff_input_table = next_perm_input - next_perm_table;
-
-    yield_constr.constraint(diff_input_prev * diff_input_table);
-
-    // This is actually constraining the first row, as per the spec, since
-    // `diff_input_table` is a diff of the next row's values. In the context of
-    // `constraint_last_row`, the next row is the first row.
-    yield_constr.constraint_last_row(diff_input_table);
+    let next_same = vars.next_values[col_same];
+    yield_constr.constraint(next_same * (P::ONES - next_same));
+    for (col_input, col_table) in cols_permuted_input.zip(cols_permuted_table) {
+        let diff_input_prev = vars.next_values[col_input] - vars.local_values[col_input];
+        let diff_input_table = vars.next_values[col_input] - vars.next_values[col_table];
+        yield_constr.constraint(next_same * diff_input_prev);
+        yield_constr.constraint((P::ONES - next_same) * diff_input_table);
+    }
+    // The first row is the next row of the last one, it has no row above it to
+    // repeat.
+    yield_constr.constraint_last_row(next_same);
 }
 
-#[allow(unused)]
-pub(crate) fn eval_lookups_circuit<
+pub(crate) fn eval_tuple_lookups_circuit<
     F: RichField + Extendable<D>,
     const D: usize,
     const COLS: usize,
@@ -42,60 +52,325 @@ pub(crate) fn eval_lookups_circuit<
     builder: &mut CircuitBuilder<F, D>,
     vars: StarkEvaluationTargets<D, COLS>,
     yield_constr: &mut RecursiveConstraintConsumer<F, D>,
-    col_permuted_input: usize,
-    col_permuted_table: usize,
+    col_same: usize,
+    cols_permuted_input: Range<usize>,
+    cols_permuted_table: Range<usize>,
 ) {
-    let local_perm_input = vars.local_values[col_permuted_input];
-    let next_perm_table = vars.next_values[col_permuted_table];
-    let next_perm_input = vars.next_values[col_permuted_input];
-
-    // A "vertical" diff between the local and next permuted inputs.
-    let diff_input_prev = builder.sub_extension(next_perm_input, local_perm_input);
-    // A "horizontal" diff between the next permuted input and permuted table value.
-    let diff_input_table = builder.sub_extension(next_perm_input, next_perm_table);
-
-    let diff_product = builder.mul_extension(diff_input_prev, diff_input_table);
-    yield_constr.constraint(builder, diff_product);
-
-    // This is actually constraining the first row, as per the spec, since
-    // `diff_input_table` is a diff of the next row's values. In the context of
-    // `constraint_last_row`, the next row is the first row.
-    yield_constr.constraint_last_row(builder, diff_input_table);
+    let one = builder.one_extension();
+    let next_same = vars.next_values[col_same];
+    let next_not_same = builder.sub_extension(one, next_same);
+    let cs = builder.mul_extension(next_same, next_not_same);
+    yield_constr.constraint(builder, cs);
+    for (col_input, col_table) in cols_permuted_input.zip(cols_permuted_table) {
+        let diff_input_prev =
+            builder.sub_extension(vars.next_values[col_input], vars.local_values[col_input]);
+        let diff_input_table =
+            builder.sub_extension(vars.next_values[col_input], vars.next_values[col_table]);
+        let cs = builder.mul_extension(next_same, diff_input_prev);
+        yield_constr.constraint(builder, cs);
+        let cs = builder.mul_extension(next_not_same, diff_input_table);
+        yield_constr.constraint(builder, cs);
+    }
+    yield_constr.constraint_last_row(builder, next_same);
+}
+
+/// A lookup of the values of `columns` into `table_column`, checked with a
+/// logarithmic derivative (LogUp) argument rather than permuted columns: for
+/// a random `gamma`,
+///     sum_rows sum_columns 1 / (x + gamma) = sum_rows m / (t + gamma)
+/// where `m` is read from `frequencies_column`. One table row serves any
+/// number of looked up values, so a lookup adds a single frequency column to
+/// the trace, whatever the number of looked up columns.
+#[derive(Clone, Debug)]
+pub struct Lookup {
+    /// Columns whose values must all appear in the table column.
+    pub columns: Vec<usize>,
+    /// The column holding the table values.
+    pub table_column: usize,
+    /// How many times the table value of each row is looked up.
+    pub frequencies_column: usize,
+}
+
+impl Lookup {
+    /// The helper columns of one challenge: sums of the inverses of the looked
+    /// up values, `batch_size` columns at a time, followed by the running sum.
+    pub fn num_helper_columns(&self, batch_size: usize) -> usize {
+        ceil_div_usize(self.columns.len(), batch_size) + 1
+    }
+}
+
+/// Counts how many times each `table` value appears in `inputs`, on the first
+/// row holding that value. Inputs missing from the table are not counted, the
+/// lookup argument then fails to verify.
+pub fn lookup_frequencies<F: PrimeField64>(inputs: &[&[F]], table: &[F]) -> Vec<F> {
+    let mut first_rows = HashMap::new();
+    for (i, t) in table.iter().enumerate() {
+        first_rows.entry(t.to_canonical_u64()).or_insert(i);
+    }
+    let mut frequencies = vec![0u64; table.len()];
+    for x in inputs.iter().flat_map(|col| col.iter()) {
+        if let Some(&i) = first_rows.get(&x.to_canonical_u64()) {
+            frequencies[i] += 1;
+        }
+    }
+    frequencies.into_iter().map(F::from_canonical_u64).collect()
+}
+
+/// Compute the helper columns of all lookups of `stark`. They are committed
+/// right after the permutation Z polynomials, ordered by lookup then by
+/// challenge.
+pub(crate) fn compute_lookup_helper_polys<F, S, const D: usize>(
+    stark: &S,
+    trace_poly_values: &[PolynomialValues<F>],
+    permutation_challenge_sets: &[GrandProductChallengeSet<F>],
+) -> Vec<PolynomialValues<F>>
+where
+    F: RichField + Extendable<D>,
+    S: Stark<F, D>,
+{
+    let lookups = stark.lookups();
+    let batch_size = stark.permutation_batch_size();
+    lookups
+        .iter()
+        .cartesian_product(&permutation_challenge_sets[0].challenges)
+        .collect_vec()
+        .into_par_iter()
+        .map(|(lookup, challenge)| {
+            compute_lookup_helper_poly(lookup, challenge.gamma, batch_size, trace_poly_values)
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Compute the helper columns of a single lookup and challenge.
+fn compute_lookup_helper_poly<F: Field>(
+    lookup: &Lookup,
+    gamma: F,
+    batch_size: usize,
+    trace_poly_values: &[PolynomialValues<F>],
+) -> Vec<PolynomialValues<F>> {
+    let degree = trace_poly_values[0].len();
+    let inverses = |col: usize| {
+        let shifted = trace_poly_values[col]
+            .values
+            .iter()
+            .map(|&x| x + gamma)
+            .collect_vec();
+        F::batch_multiplicative_inverse(&shifted)
+    };
+
+    let mut helper_polys = Vec::with_capacity(lookup.num_helper_columns(batch_size));
+    let mut sums = vec![F::ZERO; degree];
+    for batch in lookup.columns.chunks(batch_size) {
+        let mut helper = vec![F::ZERO; degree];
+        for &col in batch {
+            for (h, inv) in helper.iter_mut().zip(inverses(col)) {
+                *h += inv;
+            }
+        }
+        for (sum, &h) in sums.iter_mut().zip(&helper) {
+            *sum += h;
+        }
+        helper_polys.push(PolynomialValues::new(helper));
+    }
+
+    let frequencies = &trace_poly_values[lookup.frequencies_column].values;
+    let mut acc = F::ZERO;
+    let running_sum = sums
+        .into_iter()
+        .zip(frequencies)
+        .zip(inverses(lookup.table_column))
+        .map(|((sum, &m), table_inv)| {
+            acc += sum - m * table_inv;
+            acc
+        })
+        .collect();
+    helper_polys.push(PolynomialValues::new(running_sum));
+    helper_polys
+}
+
+/// Constraints of the lookup helper columns, which follow the permutation Zs
+/// in `permutation_vars`.
+pub(crate) fn eval_lookup_checks<F, FE, P, S, const D: usize, const D2: usize>(
+    stark: &S,
+    config: &StarkConfig,
+    vars: StarkEvaluationVars<FE, P, { S::COLUMNS }>,
+    permutation_vars: &PermutationCheckVars<F, FE, P, D2>,
+    consumer: &mut ConstraintConsumer<P>,
+) where
+    F: RichField + Extendable<D>,
+    FE: FieldExtension<D2, BaseField = F>,
+    P: PackedField<Scalar = FE>,
+    S: Stark<F, D>,
+{
+    let batch_size = stark.permutation_batch_size();
+    let mut start = stark.num_permutation_batches(config);
+    for lookup in stark.lookups() {
+        let num_sums = lookup.num_helper_columns(batch_size) - 1;
+        for challenge in &permutation_vars.permutation_challenge_sets[0].challenges {
+            let gamma = FE::from_basefield(challenge.gamma);
+            let local_sums = &permutation_vars.local_zs[start..start + num_sums];
+            let next_sums = &permutation_vars.next_zs[start..start + num_sums];
+            let local_z = permutation_vars.local_zs[start + num_sums];
+            let next_z = permutation_vars.next_zs[start + num_sums];
+            start += num_sums + 1;
+
+            // h * prod_i (x_i + gamma) = sum_i prod_{j != i} (x_j + gamma)
+            for (&h, batch) in local_sums.iter().zip(lookup.columns.chunks(batch_size)) {
+                let shifted = batch
+                    .iter()
+                    .map(|&col| vars.local_values[col] + gamma)
+                    .collect_vec();
+                let numerator = (0..shifted.len())
+                    .map(|i| {
+                        shifted
+                            .iter()
+                            .enumerate()
+                            .filter(|&(j, _)| j != i)
+                            .map(|(_, &x)| x)
+                            .product::<P>()
+                    })
+                    .sum::<P>();
+                consumer.constraint(h * shifted.into_iter().product::<P>() - numerator);
+            }
+
+            // Z(1) = sum h(1) - m(1) / (t(1) + gamma)
+            let local_sum = local_sums.iter().copied().sum::<P>();
+            let local_table = vars.local_values[lookup.table_column] + gamma;
+            let local_frequency = vars.local_values[lookup.frequencies_column];
+            consumer.constraint_first_row((local_z - local_sum) * local_table + local_frequency);
+
+            // Z(gx) = Z(x) + sum h(gx) - m(gx) / (t(gx) + gamma)
+            let next_sum = next_sums.iter().copied().sum::<P>();
+            let next_table = vars.next_values[lookup.table_column] + gamma;
+            let next_frequency = vars.next_values[lookup.frequencies_column];
+            consumer
+                .constraint_transition((next_z - local_z - next_sum) * next_table + next_frequency);
+
+            // The looked up values and the table values balance out.
+            consumer.constraint_last_row(local_z);
+        }
+    }
+}
+
+pub(crate) fn eval_lookup_checks_circuit<F, S, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: &S,
+    config: &StarkConfig,
+    vars: StarkEvaluationTargets<D, { S::COLUMNS }>,
+    permutation_data: &PermutationCheckDataTarget<D>,
+    consumer: &mut RecursiveConstraintConsumer<F, D>,
+) where
+    F: RichField + Extendable<D>,
+    S: Stark<F, D>,
+    [(); S::COLUMNS]:,
+{
+    let batch_size = stark.permutation_batch_size();
+    let mut start = stark.num_permutation_batches(config);
+    for lookup in stark.lookups() {
+        let num_sums = lookup.num_helper_columns(batch_size) - 1;
+        for challenge in &permutation_data.permutation_challenge_sets[0].challenges {
+            let gamma = builder.convert_to_ext(challenge.gamma);
+            let local_sums = &permutation_data.local_zs[start..start + num_sums];
+            let next_sums = &permutation_data.next_zs[start..start + num_sums];
+            let local_z = permutation_data.local_zs[start + num_sums];
+            let next_z = permutation_data.next_zs[start + num_sums];
+            start += num_sums + 1;
+
+            for (&h, batch) in local_sums.iter().zip(lookup.columns.chunks(batch_size)) {
+                let shifted = batch
+                    .iter()
+                    .map(|&col| builder.add_extension(vars.local_values[col], gamma))
+                    .collect_vec();
+                let terms = (0..shifted.len())
+                    .map(|i| {
+                        let others = shifted
+                            .iter()
+                            .enumerate()
+                            .filter(|&(j, _)| j != i)
+                            .map(|(_, &x)| x)
+                            .collect_vec();
+                        builder.mul_many_extension(others)
+                    })
+                    .collect_vec();
+                let numerator = builder.add_many_extension(terms);
+                let product = builder.mul_many_extension(shifted);
+                let cs = builder.mul_sub_extension(h, product, numerator);
+                consumer.constraint(builder, cs);
+            }
+
+            let local_sum = builder.add_many_extension(local_sums.iter().copied());
+            let local_table = builder.add_extension(vars.local_values[lookup.table_column], gamma);
+            let diff = builder.sub_extension(local_z, local_sum);
+            let cs = builder.mul_add_extension(
+                diff,
+                local_table,
+                vars.local_values[lookup.frequencies_column],
+            );
+            consumer.constraint_first_row(builder, cs);
+
+            let next_sum = builder.add_many_extension(next_sums.iter().copied());
+            let next_table = builder.add_extension(vars.next_values[lookup.table_column], gamma);
+            let diff = builder.sub_extension(next_z, local_z);
+            let diff = builder.sub_extension(diff, next_sum);
+            let cs = builder.mul_add_extension(
+                diff,
+                next_table,
+                vars.next_values[lookup.frequencies_column],
+            );
+            consumer.constraint_transition(builder, cs);
+
+            consumer.constraint_last_row(builder, local_z);
+        }
+    }
+}
+
+/// Given input rows and table rows, generate the permuted input and permuted
+/// table rows used by `eval_tuple_lookups`. Also returns the `same` column:
+/// whether a permuted input row repeats the row above it.
+pub fn permuted_rows<F: PrimeField64, const W: usize>(
+    inputs: &[[F; W]],
+    table: &[[F; W]],
+) -> (Vec<[F; W]>, Vec<[F; W]>, Vec<F>) {
+    let canonical = |row: &[F; W]| row.map(|x| x.to_canonical());
+    let inputs = inputs.iter().map(canonical).collect_vec();
+    let table = table.iter().map(canonical).collect_vec();
+    let (permuted_inputs, permuted_table) = permuted_by_key(inputs, table, [F::ZERO; W], |row| {
+        row.map(|x| x.to_noncanonical_u64())
+    });
+    let same = (0..permuted_inputs.len())
+        .map(|i| F::from_bool(i > 0 && permuted_inputs[i] == permuted_inputs[i - 1]))
+        .collect();
+    (permuted_inputs, permuted_table, same)
 }
 
-/// Given an input column and a table column, generate the permuted input and
-/// permuted table columns used in the Halo2 permutation argument.
-pub fn permuted_cols<F: PrimeField64>(inputs: &[F], table: &[F]) -> (Vec<F>, Vec<F>) {
+fn permuted_by_key<T: Copy, K: Ord>(
+    inputs: Vec<T>,
+    table: Vec<T>,
+    default: T,
+    key: impl Fn(&T) -> K,
+) -> (Vec<T>, Vec<T>) {
     let n = inputs.len();
 
     // The permuted inputs do not have to be ordered, but we found that sorting was
     // faster than hash-based grouping. We also sort the table, as this helps us
     // identify "unused" table elements efficiently.
-
-    // To compare elements, e.g. for sorting, we first need them in canonical form.
-    // It would be wasteful to canonicalize in each comparison, as a single
-    // element may be involved in many comparisons. So we will canonicalize once
-    // upfront, then use `to_noncanonical_u64` when comparing elements.
-
     let sorted_inputs = inputs
-        .iter()
-        .map(|x| x.to_canonical())
-        .sorted_unstable_by_key(|x| x.to_noncanonical_u64())
-        .collect_vec();
-    let sorted_table = table
-        .iter()
-        .map(|x| x.to_canonical())
-        .sorted_unstable_by_key(|x| x.to_noncanonical_u64())
+        .into_iter()
+        .sorted_unstable_by_key(&key)
         .collect_vec();
+    let sorted_table = table.into_iter().sorted_unstable_by_key(&key).collect_vec();
 
     let mut unused_table_inds = Vec::with_capacity(n);
     let mut unused_table_vals = Vec::with_capacity(n);
-    let mut permuted_table = vec![F::ZERO; n];
+    let mut permuted_table = vec![default; n];
     let mut i = 0;
     let mut j = 0;
     while (j < n) && (i < n) {
-        let input_val = sorted_inputs[i].to_noncanonical_u64();
-        let table_val = sorted_table[j].to_noncanonical_u64();
+        let input_val = key(&sorted_inputs[i]);
+        let table_val = key(&sorted_table[j]);
         match input_val.cmp(&table_val) {
             Ordering::Greater => {
                 unused_table_vals.push(sorted_table[j]);
@@ -131,46 +406,78 @@ pub fn permuted_cols<F: PrimeField64>(inputs: &[F], table: &[F]) -> (Vec<F>, Vec
     (sorted_inputs, permuted_table)
 }
 
-// add by xb 2023-1-5
-// case 1:
-// looking_table:
-// <0,1,2,3,4,5>
-// looked_table:
-// <0,1,2,3,4,5,6,7>
-// Extend:
-//      looking_table: <0,1,2,3,4,5,5,5>
-//      looked_table: <0,1,2,3,4,5,6,7>
-
-// case 2:
-// looking_table:
-// <0,1,2,3,4,5>
-// looked_table:
-// <0,1,2,3,4,5,6,7,8,9>
-// Extend:
-//      looking_table: <0,1,2,3,4,5,5,5,5,5,5,5,5,5,5,5>
-//      looked_table: <0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15>
-pub fn extend_to_power_of_2<F: PrimeField64>(inputs: &[F], table: &[F]) -> (Vec<F>, Vec<F>) {
-    let looking_trace_len = inputs.len();
-    let looked_trace_len = table.len();
-
-    let expect_trace_len = looking_trace_len.max(looked_trace_len).next_power_of_two();
-
-    let mut extend_input = Vec::with_capacity(expect_trace_len);
-    let extend_table = Vec::with_capacity(expect_trace_len);
-
-    for i in 0..expect_trace_len {
-        if i < looking_trace_len {
-            extend_input.push(inputs[i])
-        } else {
-            extend_input.push(inputs[looking_trace_len - 1])
-        }
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::polynomial::PolynomialValues;
+    use plonky2::field::types::Field;
 
-        if i < looked_trace_len {
-            extend_input.push(table[i])
-        } else {
-            extend_input.push(table[looked_trace_len - 1])
-        }
+    use super::{compute_lookup_helper_poly, lookup_frequencies, Lookup};
+
+    type F = GoldilocksField;
+
+    fn column(values: &[u64]) -> PolynomialValues<F> {
+        PolynomialValues::new(values.iter().map(|&v| F::from_canonical_u64(v)).collect())
+    }
+
+    fn lookup_trace(inputs: [&[u64]; 3], table: &[u64]) -> (Lookup, Vec<PolynomialValues<F>>) {
+        let mut trace = inputs.map(column).to_vec();
+        trace.push(column(table));
+        let frequencies = lookup_frequencies(
+            &[
+                &trace[0].values[..],
+                &trace[1].values[..],
+                &trace[2].values[..],
+            ],
+            &trace[3].values,
+        );
+        trace.push(PolynomialValues::new(frequencies));
+        let lookup = Lookup {
+            columns: vec![0, 1, 2],
+            table_column: 3,
+            frequencies_column: 4,
+        };
+        (lookup, trace)
+    }
+
+    #[test]
+    fn test_lookup_frequencies() {
+        let table = [0, 1, 2, 3, 3, 3, 3, 3].map(F::from_canonical_u64);
+        let inputs = [1, 1, 3, 0, 3, 3, 1, 2].map(F::from_canonical_u64);
+        let frequencies = lookup_frequencies(&[&inputs[..]], &table);
+        assert_eq!(
+            frequencies,
+            [1, 3, 1, 3, 0, 0, 0, 0].map(F::from_canonical_u64).to_vec()
+        );
     }
 
-    (extend_input, extend_table)
+    #[test]
+    fn test_lookup_running_sum() {
+        let table = [0, 1, 2, 3, 4, 5, 6, 7];
+        let gamma = F::rand();
+
+        let (lookup, trace) = lookup_trace(
+            [
+                &[7, 7, 7, 0, 1, 2, 2, 5],
+                &[0, 0, 0, 0, 0, 0, 0, 0],
+                &[3, 4, 4, 4, 6, 6, 1, 1],
+            ],
+            &table,
+        );
+        let helpers = compute_lookup_helper_poly(&lookup, gamma, 2, &trace);
+        assert_eq!(helpers.len(), lookup.num_helper_columns(2));
+        assert_eq!(helpers.last().unwrap().values.last(), Some(&F::ZERO));
+
+        // 8 is not in the table, the running sum does not balance out.
+        let (lookup, trace) = lookup_trace(
+            [
+                &[7, 7, 7, 0, 1, 2, 2, 5],
+                &[0, 0, 0, 0, 0, 0, 0, 0],
+                &[3, 4, 4, 4, 6, 6, 1, 8],
+            ],
+            &table,
+        );
+        let helpers = compute_lookup_helper_poly(&lookup, gamma, 2, &trace);
+        assert_ne!(helpers.last().unwrap().values.last(), Some(&F::ZERO));
+    }
 }
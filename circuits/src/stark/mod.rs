@@ -1,3 +1,4 @@
+pub mod aggregation;
 pub mod config;
 pub mod constraint_consumer;
 pub mod cross_table_lookup;
@@ -6,6 +7,7 @@ pub mod lookup;
 pub mod ola_stark;
 pub mod permutation;
 pub mod proof;
+pub mod recursive_verifier;
 pub mod prover;
 pub mod serialization;
 pub mod stark;
@@ -6,14 +6,19 @@ use super::cross_table_lookup::{CrossTableLookup, TableWithColumns};
 use super::stark::Stark;
 use crate::builtins::bitwise::bitwise_stark::{self, BitwiseStark};
 use crate::builtins::cmp::cmp_stark::{self, CmpStark};
+use crate::builtins::event::event_stark::{self, EventStark};
 use crate::builtins::poseidon::poseidon_chunk_stark::{self, PoseidonChunkStark};
 use crate::builtins::poseidon::poseidon_stark::{self, PoseidonStark};
 use crate::builtins::rangecheck::rangecheck_stark::{self, RangeCheckStark};
 use crate::builtins::sccall::sccall_stark::{self, SCCallStark};
+use crate::builtins::sigcheck::columns::NUM_SIGCHECK_INPUTS;
+use crate::builtins::sigcheck::sigcheck_stark::{self, SigCheckStark};
 use crate::builtins::storage::storage_access_stark::{self, StorageAccessStark};
 use crate::builtins::tape::tape_stark::{self, TapeStark};
 use crate::cpu::cpu_stark;
 use crate::cpu::cpu_stark::CpuStark;
+use crate::fixed_table::bitwise_fixed::bitwise_fixed_stark::{self, BitwiseFixedStark};
+use crate::fixed_table::rangecheck_fixed::rangecheck_fixed_stark::{self, RangeCheckFixedStark};
 use crate::memory::memory_stark::{
     self, ctl_data as mem_ctl_data, ctl_data_mem_rc_diff_cond, ctl_data_mem_sort_rc,
     ctl_filter as mem_ctl_filter, ctl_filter_mem_rc_diff_cond, ctl_filter_mem_sort_rc, MemoryStark,
@@ -42,6 +47,11 @@ pub struct OlaStark<F: RichField + Extendable<D>, const D: usize> {
     pub sccall_stark: SCCallStark<F, D>,
     pub program_stark: ProgramStark<F, D>,
     pub prog_chunk_stark: ProgChunkStark<F, D>,
+    pub sigcheck_stark: SigCheckStark<F, D>,
+    pub event_stark: EventStark<F, D>,
+    // fixed tables
+    pub bitwise_fixed_stark: BitwiseFixedStark<F, D>,
+    pub rangecheck_fixed_stark: RangeCheckFixedStark<F, D>,
 
     pub cross_table_lookups: Vec<CrossTableLookup<F>>,
 }
@@ -51,7 +61,7 @@ impl Serialize for OlaStark<GoldilocksField, 2> {
     where
         S: serde::Serializer,
     {
-        let mut state = serializer.serialize_struct("OlaStark", 13)?;
+        let mut state = serializer.serialize_struct("OlaStark", 17)?;
         state.serialize_field("cpu_stark", &self.cpu_stark)?;
         state.serialize_field("memory_stark", &self.memory_stark)?;
         state.serialize_field("bitwise_stark", &self.bitwise_stark)?;
@@ -64,6 +74,10 @@ impl Serialize for OlaStark<GoldilocksField, 2> {
         state.serialize_field("sccall_stark", &self.sccall_stark)?;
         state.serialize_field("program_stark", &self.program_stark)?;
         state.serialize_field("prog_chunk_stark", &self.prog_chunk_stark)?;
+        state.serialize_field("sigcheck_stark", &self.sigcheck_stark)?;
+        state.serialize_field("event_stark", &self.event_stark)?;
+        state.serialize_field("bitwise_fixed_stark", &self.bitwise_fixed_stark)?;
+        state.serialize_field("rangecheck_fixed_stark", &self.rangecheck_fixed_stark)?;
         state.serialize_field("cross_table_lookups", &self.cross_table_lookups)?;
         state.end()
     }
@@ -87,6 +101,10 @@ impl<'de> Deserialize<'de> for OlaStark<GoldilocksField, 2> {
             SccallStark,
             ProgramStark,
             ProgChunkStark,
+            SigCheckStark,
+            EventStark,
+            BitwiseFixedStark,
+            RangeCheckFixedStark,
             CrossTableLookups,
         }
 
@@ -121,6 +139,10 @@ impl<'de> Deserialize<'de> for OlaStark<GoldilocksField, 2> {
                             "sccall_stark" => Ok(Field::SccallStark),
                             "program_stark" => Ok(Field::ProgramStark),
                             "prog_chunk_stark" => Ok(Field::ProgChunkStark),
+                            "sigcheck_stark" => Ok(Field::SigCheckStark),
+                            "event_stark" => Ok(Field::EventStark),
+                            "bitwise_fixed_stark" => Ok(Field::BitwiseFixedStark),
+                            "rangecheck_fixed_stark" => Ok(Field::RangeCheckFixedStark),
                             "cross_table_lookups" => Ok(Field::CrossTableLookups),
                             _ => Err(de::Error::unknown_field(value, FIELDS)),
                         }
@@ -158,6 +180,10 @@ impl<'de> Deserialize<'de> for OlaStark<GoldilocksField, 2> {
                 let mut sccall_stark = None;
                 let mut program_stark = None;
                 let mut prog_chunk_stark = None;
+                let mut sigcheck_stark = None;
+                let mut event_stark = None;
+                let mut bitwise_fixed_stark = None;
+                let mut rangecheck_fixed_stark = None;
                 let mut cross_table_lookups = None;
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -233,6 +259,30 @@ impl<'de> Deserialize<'de> for OlaStark<GoldilocksField, 2> {
                             }
                             prog_chunk_stark = Some(map.next_value()?);
                         }
+                        Field::SigCheckStark => {
+                            if sigcheck_stark.is_some() {
+                                return Err(de::Error::duplicate_field("sigcheck_stark"));
+                            }
+                            sigcheck_stark = Some(map.next_value()?);
+                        }
+                        Field::EventStark => {
+                            if event_stark.is_some() {
+                                return Err(de::Error::duplicate_field("event_stark"));
+                            }
+                            event_stark = Some(map.next_value()?);
+                        }
+                        Field::BitwiseFixedStark => {
+                            if bitwise_fixed_stark.is_some() {
+                                return Err(de::Error::duplicate_field("bitwise_fixed_stark"));
+                            }
+                            bitwise_fixed_stark = Some(map.next_value()?);
+                        }
+                        Field::RangeCheckFixedStark => {
+                            if rangecheck_fixed_stark.is_some() {
+                                return Err(de::Error::duplicate_field("rangecheck_fixed_stark"));
+                            }
+                            rangecheck_fixed_stark = Some(map.next_value()?);
+                        }
                         Field::CrossTableLookups => {
                             if cross_table_lookups.is_some() {
                                 return Err(de::Error::duplicate_field("cross_table_lookups"));
@@ -265,6 +315,14 @@ impl<'de> Deserialize<'de> for OlaStark<GoldilocksField, 2> {
                     program_stark.ok_or_else(|| de::Error::missing_field("program_stark"))?;
                 let prog_chunk_stark =
                     prog_chunk_stark.ok_or_else(|| de::Error::missing_field("prog_chunk_stark"))?;
+                let sigcheck_stark =
+                    sigcheck_stark.ok_or_else(|| de::Error::missing_field("sigcheck_stark"))?;
+                let event_stark =
+                    event_stark.ok_or_else(|| de::Error::missing_field("event_stark"))?;
+                let bitwise_fixed_stark = bitwise_fixed_stark
+                    .ok_or_else(|| de::Error::missing_field("bitwise_fixed_stark"))?;
+                let rangecheck_fixed_stark = rangecheck_fixed_stark
+                    .ok_or_else(|| de::Error::missing_field("rangecheck_fixed_stark"))?;
                 let cross_table_lookups = cross_table_lookups
                     .ok_or_else(|| de::Error::missing_field("cross_table_lookups"))?;
                 Ok(OlaStark {
@@ -280,6 +338,10 @@ impl<'de> Deserialize<'de> for OlaStark<GoldilocksField, 2> {
                     sccall_stark,
                     program_stark,
                     prog_chunk_stark,
+                    sigcheck_stark,
+                    event_stark,
+                    bitwise_fixed_stark,
+                    rangecheck_fixed_stark,
                     cross_table_lookups,
                 })
             }
@@ -298,6 +360,10 @@ impl<'de> Deserialize<'de> for OlaStark<GoldilocksField, 2> {
             "sccall_stark",
             "program_stark",
             "prog_chunk_stark",
+            "sigcheck_stark",
+            "event_stark",
+            "bitwise_fixed_stark",
+            "rangecheck_fixed_stark",
             "cross_table_lookups",
         ];
         deserializer.deserialize_struct(
@@ -327,6 +393,10 @@ impl<F: RichField + Extendable<D>, const D: usize> Default for OlaStark<F, D> {
             sccall_stark: SCCallStark::default(),
             program_stark: ProgramStark::default(),
             prog_chunk_stark: ProgChunkStark::default(),
+            sigcheck_stark: SigCheckStark::default(),
+            event_stark: EventStark::default(),
+            bitwise_fixed_stark: BitwiseFixedStark::default(),
+            rangecheck_fixed_stark: RangeCheckFixedStark::default(),
             cross_table_lookups: all_cross_table_lookups(),
         }
     }
@@ -335,18 +405,22 @@ impl<F: RichField + Extendable<D>, const D: usize> Default for OlaStark<F, D> {
 impl<F: RichField + Extendable<D>, const D: usize> OlaStark<F, D> {
     pub(crate) fn nums_permutation_zs(&self, config: &StarkConfig) -> [usize; NUM_TABLES] {
         [
-            self.cpu_stark.num_permutation_batches(config),
-            self.memory_stark.num_permutation_batches(config),
-            self.bitwise_stark.num_permutation_batches(config),
-            self.cmp_stark.num_permutation_batches(config),
-            self.rangecheck_stark.num_permutation_batches(config),
-            self.poseidon_stark.num_permutation_batches(config),
-            self.poseidon_chunk_stark.num_permutation_batches(config),
-            self.storage_access_stark.num_permutation_batches(config),
-            self.tape_stark.num_permutation_batches(config),
-            self.sccall_stark.num_permutation_batches(config),
-            self.program_stark.num_permutation_batches(config),
-            self.prog_chunk_stark.num_permutation_batches(config),
+            self.cpu_stark.num_permutation_zs(config),
+            self.memory_stark.num_permutation_zs(config),
+            self.bitwise_stark.num_permutation_zs(config),
+            self.cmp_stark.num_permutation_zs(config),
+            self.rangecheck_stark.num_permutation_zs(config),
+            self.poseidon_stark.num_permutation_zs(config),
+            self.poseidon_chunk_stark.num_permutation_zs(config),
+            self.storage_access_stark.num_permutation_zs(config),
+            self.tape_stark.num_permutation_zs(config),
+            self.sccall_stark.num_permutation_zs(config),
+            self.program_stark.num_permutation_zs(config),
+            self.prog_chunk_stark.num_permutation_zs(config),
+            self.sigcheck_stark.num_permutation_zs(config),
+            self.event_stark.num_permutation_zs(config),
+            self.bitwise_fixed_stark.num_permutation_zs(config),
+            self.rangecheck_fixed_stark.num_permutation_zs(config),
         ]
     }
 
@@ -364,6 +438,10 @@ impl<F: RichField + Extendable<D>, const D: usize> OlaStark<F, D> {
             self.sccall_stark.permutation_batch_size(),
             self.program_stark.permutation_batch_size(),
             self.prog_chunk_stark.permutation_batch_size(),
+            self.sigcheck_stark.permutation_batch_size(),
+            self.event_stark.permutation_batch_size(),
+            self.bitwise_fixed_stark.permutation_batch_size(),
+            self.rangecheck_fixed_stark.permutation_batch_size(),
         ]
     }
 }
@@ -383,9 +461,14 @@ pub enum Table {
     SCCall = 9,
     Program = 10,
     ProgChunk = 11,
+    SigCheck = 12,
+    Event = 13,
+    // fixed tables
+    BitwiseFixed = 14,
+    RangeCheckFixed = 15,
 }
 
-pub(crate) const NUM_TABLES: usize = 12;
+pub(crate) const NUM_TABLES: usize = 16;
 
 pub(crate) fn all_cross_table_lookups<F: Field>() -> Vec<CrossTableLookup<F>> {
     vec![
@@ -403,11 +486,20 @@ pub(crate) fn all_cross_table_lookups<F: Field>() -> Vec<CrossTableLookup<F>> {
         ctl_cpu_storage_access(),
         ctl_storage_access_poseidon(),
         ctl_cpu_tape(),
+        ctl_public_tape(),
         ctl_cpu_sccall(),
         ctl_cpu_sccall_end(),
         ctl_cpu_program(),
         ctl_prog_chunk_prog(),
         ctl_prog_chunk_storage(),
+        ctl_cpu_sigcheck(),
+        ctl_sigcheck_mem(),
+        ctl_public_sigcheck(),
+        ctl_cpu_event(),
+        ctl_event_mem(),
+        ctl_public_event(),
+        ctl_bitwise_bitwise_fixed_table(),
+        ctl_rangecheck_rangecheck_fixed(),
     ]
 }
 
@@ -741,6 +833,17 @@ fn ctl_cpu_tape<F: Field>() -> CrossTableLookup<F> {
     CrossTableLookup::new(all_lookers, tape_looked)
 }
 
+// The looking side is made of the public values, see
+// `PublicValues::tape_cells`.
+fn ctl_public_tape<F: Field>() -> CrossTableLookup<F> {
+    let tape_looked = TableWithColumns::new(
+        Table::Tape,
+        tape_stark::ctl_data_tape_public(),
+        Some(tape_stark::ctl_filter_tape_public()),
+    );
+    CrossTableLookup::new(vec![], tape_looked)
+}
+
 fn ctl_cpu_sccall<F: Field>() -> CrossTableLookup<F> {
     CrossTableLookup::new(
         vec![TableWithColumns::new(
@@ -827,38 +930,143 @@ fn ctl_prog_chunk_storage<F: Field>() -> CrossTableLookup<F> {
     )
 }
 
-// Cross_Lookup_Table(looking_table, looked_table)
-/*fn ctl_bitwise_bitwise_fixed_table<F: Field>() -> CrossTableLookup<F> {
+fn ctl_cpu_sigcheck<F: Field>() -> CrossTableLookup<F> {
     CrossTableLookup::new(
         vec![TableWithColumns::new(
-            Table::BitwiseFixed,
-            bitwise_fixed_stark::ctl_data_with_bitwise(),
-            Some(bitwise_fixed_stark::ctl_filter_with_bitwise()),
+            Table::Cpu,
+            cpu_stark::ctl_data_cpu_sigcheck(),
+            Some(cpu_stark::ctl_filter_cpu_sigcheck()),
         )],
         TableWithColumns::new(
+            Table::SigCheck,
+            sigcheck_stark::ctl_data_with_cpu(),
+            Some(sigcheck_stark::ctl_filter_with_cpu()),
+        ),
+    )
+}
+
+fn ctl_sigcheck_mem<F: Field>() -> CrossTableLookup<F> {
+    let all_lookers = (0..NUM_SIGCHECK_INPUTS)
+        .map(|i: usize| {
+            TableWithColumns::new(
+                Table::SigCheck,
+                sigcheck_stark::ctl_data_with_mem(i),
+                Some(sigcheck_stark::ctl_filter_with_mem()),
+            )
+        })
+        .collect();
+    let mem_looked = TableWithColumns::new(
+        Table::Memory,
+        mem_ctl_data(),
+        Some(memory_stark::ctl_filter_with_sigcheck()),
+    );
+    CrossTableLookup::new(all_lookers, mem_looked)
+}
+
+fn ctl_public_sigcheck<F: Field>() -> CrossTableLookup<F> {
+    let sigcheck_looked = TableWithColumns::new(
+        Table::SigCheck,
+        sigcheck_stark::ctl_data_public(),
+        Some(sigcheck_stark::ctl_filter_public()),
+    );
+    CrossTableLookup::new(vec![], sigcheck_looked)
+}
+
+fn ctl_cpu_event<F: Field>() -> CrossTableLookup<F> {
+    CrossTableLookup::new(
+        vec![TableWithColumns::new(
+            Table::Cpu,
+            cpu_stark::ctl_data_cpu_event(),
+            Some(cpu_stark::ctl_filter_cpu_event()),
+        )],
+        TableWithColumns::new(
+            Table::Event,
+            event_stark::ctl_data_with_cpu(),
+            Some(event_stark::ctl_filter_with_cpu()),
+        ),
+    )
+}
+
+fn ctl_event_mem<F: Field>() -> CrossTableLookup<F> {
+    let looker_head = [false, true].map(|is_data| {
+        TableWithColumns::new(
+            Table::Event,
+            event_stark::ctl_data_head_with_mem(is_data),
+            Some(event_stark::ctl_filter_head_with_mem()),
+        )
+    });
+    let looker_topic_ptr = TableWithColumns::new(
+        Table::Event,
+        event_stark::ctl_data_topic_ptr_with_mem(),
+        Some(event_stark::ctl_filter_topic_with_mem()),
+    );
+    let looker_topic = (0..4).map(|i: usize| {
+        TableWithColumns::new(
+            Table::Event,
+            event_stark::ctl_data_topic_with_mem(i),
+            Some(event_stark::ctl_filter_topic_with_mem()),
+        )
+    });
+    let looker_data = TableWithColumns::new(
+        Table::Event,
+        event_stark::ctl_data_data_with_mem(),
+        Some(event_stark::ctl_filter_data_with_mem()),
+    );
+    let all_lookers = looker_head
+        .into_iter()
+        .chain(iter::once(looker_topic_ptr))
+        .chain(looker_topic)
+        .chain(iter::once(looker_data))
+        .collect();
+    let mem_looked = TableWithColumns::new(
+        Table::Memory,
+        mem_ctl_data(),
+        Some(memory_stark::ctl_filter_with_event()),
+    );
+    CrossTableLookup::new(all_lookers, mem_looked)
+}
+
+fn ctl_public_event<F: Field>() -> CrossTableLookup<F> {
+    let event_looked = TableWithColumns::new(
+        Table::Event,
+        event_stark::ctl_data_public(),
+        Some(event_stark::ctl_filter_public()),
+    );
+    CrossTableLookup::new(vec![], event_looked)
+}
+
+// The fixed columns of the bitwise and rangecheck tables are looked up in
+// the constrained fixed tables, so the permuted lookups inside the builtins
+// run against canonical entries.
+fn ctl_bitwise_bitwise_fixed_table<F: Field>() -> CrossTableLookup<F> {
+    CrossTableLookup::new(
+        vec![TableWithColumns::new(
             Table::Bitwise,
             bitwise_stark::ctl_data_with_bitwise_fixed(),
             Some(bitwise_stark::ctl_filter_with_bitwise_fixed()),
+        )],
+        TableWithColumns::new(
+            Table::BitwiseFixed,
+            bitwise_fixed_stark::ctl_data_with_bitwise(),
+            Some(bitwise_fixed_stark::ctl_filter_with_bitwise()),
         ),
-        None,
     )
-}*/
+}
 
-/*fn ctl_rangecheck_rangecheck_fixed<F: Field>() -> CrossTableLookup<F> {
+fn ctl_rangecheck_rangecheck_fixed<F: Field>() -> CrossTableLookup<F> {
     CrossTableLookup::new(
         vec![TableWithColumns::new(
-            Table::RangecheckFixed,
-            rangecheck_fixed_stark::ctl_data_with_rangecheck(),
-            Some(rangecheck_fixed_stark::ctl_filter_with_rangecheck()),
-        )],
-        TableWithColumns::new(
             Table::RangeCheck,
             rangecheck_stark::ctl_data_with_rangecheck_fixed(),
             Some(rangecheck_stark::ctl_filter_with_rangecheck_fixed()),
+        )],
+        TableWithColumns::new(
+            Table::RangeCheckFixed,
+            rangecheck_fixed_stark::ctl_data_with_rangecheck(),
+            Some(rangecheck_fixed_stark::ctl_filter_with_rangecheck()),
         ),
-        None,
     )
-}*/
+}
 
 // check the correct program with lookup
 
@@ -915,10 +1123,11 @@ mod tests {
     use crate::stark::ola_stark::OlaStark;
     use crate::stark::proof::PublicValues;
     use crate::stark::prover::prove_with_traces;
-    use crate::stark::serialization::Buffer;
+    use crate::stark::serialization::{Buffer, PROOF_FORMAT_VERSION, PROOF_MAGIC};
     use crate::stark::stark::Stark;
     use crate::stark::util::trace_rows_to_poly_values;
     use crate::stark::verifier::verify_proof;
+    use crate::test_utils::prove_by_asm_path;
     use anyhow::Result;
     use assembler::encoder::encode_asm_from_json_file;
     use core::crypto::hash::Hasher;
@@ -959,33 +1168,57 @@ mod tests {
 
     #[test]
     fn test_serialize() {
-        let mut ola_stark = OlaStark::default();
-        assert_eq!(ola_stark.bitwise_stark.get_compress_challenge(), None);
-        assert_eq!(ola_stark.program_stark.get_compress_challenge(), None);
-        let challenge1 = GoldilocksField::rand();
-        let challenge2 = GoldilocksField::rand();
-        ola_stark.bitwise_stark.set_compress_challenge(challenge1);
-        ola_stark.program_stark.set_compress_challenge(challenge2);
-        assert_eq!(
-            ola_stark.bitwise_stark.get_compress_challenge(),
-            Some(challenge1)
-        );
-        assert_eq!(
-            ola_stark.program_stark.get_compress_challenge(),
-            Some(challenge2)
-        );
+        let mut ola_stark = OlaStark::<GoldilocksField, 2>::default();
+        ola_stark
+            .storage_access_stark
+            .set_state_roots([1, 2, 3, 4], [5, 6, 7, 8])
+            .unwrap();
         let data = serde_json::to_string(&ola_stark).unwrap();
         let stark: OlaStark<GoldilocksField, 2> = serde_json::from_str(&data).unwrap();
         assert_eq!(
-            ola_stark.bitwise_stark.get_compress_challenge(),
-            stark.bitwise_stark.get_compress_challenge()
-        );
-        assert_eq!(
-            ola_stark.program_stark.get_compress_challenge(),
-            stark.program_stark.get_compress_challenge()
+            stark.storage_access_stark.get_state_roots(),
+            Some(([1, 2, 3, 4], [5, 6, 7, 8]))
         );
     }
 
+    #[test]
+    fn test_serialize_all_proof() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("../assembler/test_data/asm/memory.json");
+        let proof = prove_by_asm_path::<C>(path.display().to_string(), None, None).unwrap();
+        let config = StarkConfig::standard_fast_config();
+
+        let mut buffer = Buffer::new(Vec::new());
+        buffer.write_all_proof(&proof, &config).unwrap();
+        let bytes = buffer.bytes();
+        assert_eq!(&bytes[..PROOF_MAGIC.len()], &PROOF_MAGIC);
+
+        let (de_proof, de_config) = Buffer::new(bytes.clone())
+            .read_all_proof::<F, C, D>()
+            .unwrap();
+        assert_eq!(de_config, config);
+        assert_eq!(de_proof.public_values, proof.public_values);
+        assert_eq!(de_proof.degree_bits(&config), proof.degree_bits(&config));
+        let mut reserialized = Buffer::new(Vec::new());
+        reserialized.write_all_proof(&de_proof, &de_config).unwrap();
+        assert_eq!(reserialized.bytes(), bytes);
+        verify_proof(OlaStark::default(), de_proof, &de_config).unwrap();
+
+        let read = |bytes: Vec<u8>| Buffer::new(bytes).read_all_proof::<F, C, D>();
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] ^= 1;
+        assert!(read(bad_magic).is_err());
+        let mut bad_version = bytes.clone();
+        bad_version[PROOF_MAGIC.len()..PROOF_MAGIC.len() + 4]
+            .copy_from_slice(&(PROOF_FORMAT_VERSION + 1).to_le_bytes());
+        assert!(read(bad_version).is_err());
+        let mut corrupted = bytes.clone();
+        let mid = corrupted.len() / 2;
+        corrupted[mid] ^= 1;
+        assert!(read(corrupted).is_err());
+        assert!(read(bytes[..bytes.len() - 1].to_vec()).is_err());
+    }
+
     #[test]
     fn fibo_loop_test() {
         let calldata = [10u64, 1u64, 2, 4185064725u64]
@@ -1125,124 +1358,8 @@ mod tests {
         path.push(file_name);
         let program_path = path.display().to_string();
 
-        let mut db = match db_name {
-            Some(name) => {
-                let mut db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-                db_path.push("../executor/db_test/");
-                db_path.push("../executor/db_test/");
-                db_path.push(name);
-                AccountTree::new_db_test(db_path.display().to_string())
-            }
-            _ => AccountTree::new_test(),
-        };
-
-        let program = encode_asm_from_json_file(program_path).unwrap();
-        let hash = ZkHasher::default();
-        let instructions = program.bytecode.split("\n");
-        let code: Vec<_> = instructions
-            .clone()
-            .map(|e| GoldilocksField::from_canonical_u64(u64::from_str_radix(&e[2..], 16).unwrap()))
-            .collect();
-        let code_hash = hash.hash_bytes(&code);
-        let mut prophets = HashMap::new();
-        for item in program.prophets {
-            prophets.insert(item.host as u64, item);
-        }
-
-        let mut program: Program = Program::default();
-
-        for inst in instructions {
-            program.instructions.push(inst.to_string());
-        }
-
-        let mut process = Process::new();
-        process.addr_storage = Address::default();
-
-        let tp_start = 0;
-
-        let callee: Address = [
-            GoldilocksField::from_canonical_u64(9),
-            GoldilocksField::from_canonical_u64(10),
-            GoldilocksField::from_canonical_u64(11),
-            GoldilocksField::from_canonical_u64(12),
-        ];
-        let caller_addr = [
-            GoldilocksField::from_canonical_u64(17),
-            GoldilocksField::from_canonical_u64(18),
-            GoldilocksField::from_canonical_u64(19),
-            GoldilocksField::from_canonical_u64(20),
-        ];
-        let callee_exe_addr = [
-            GoldilocksField::from_canonical_u64(13),
-            GoldilocksField::from_canonical_u64(14),
-            GoldilocksField::from_canonical_u64(15),
-            GoldilocksField::from_canonical_u64(16),
-        ];
-
-        if let Some(calldata) = call_data {
-            process.tp = GoldilocksField::from_canonical_u64(tp_start as u64);
-
-            init_tape(
-                &mut process,
-                calldata,
-                caller_addr,
-                callee,
-                callee_exe_addr,
-                &init_tx_context_mock(),
-            );
-        }
-
-        process.addr_code = callee_exe_addr;
-        process.addr_storage = callee;
-        program
-            .trace
-            .addr_program_hash
-            .insert(encode_addr(&callee_exe_addr), code);
-
-        db.process_block(vec![WitnessStorageLog {
-            storage_log: StorageLog::new_write_log(callee_exe_addr, code_hash),
-            previous_value: tree_key_default(),
-        }]);
-        let _ = db.save();
-
-        let start = db.root_hash();
-
-        process.program_log.push(WitnessStorageLog {
-            storage_log: StorageLog::new_read_log(callee_exe_addr, code_hash),
-            previous_value: tree_key_default(),
-        });
-
-        program.prophets = prophets;
-        // FIXME: account tree is not used, merkle root cannot update.
-        let res = process.execute(
-            &mut program,
-            &StateStorage::new_test(),
-            &mut BatchCacheManager::default(),
-        );
-        match res {
-            Ok(_) => {}
-            Err(e) => {
-                println!("execute err:{:?}", e);
-                panic!("execute program failed");
-            }
-        }
-        let hash_roots = gen_storage_hash_table(&mut process, &mut program, &mut db);
-        gen_storage_table(&mut process, &mut program, hash_roots.unwrap()).unwrap();
-        program.trace.start_end_roots = (start, db.root_hash());
-
-        let inputs = GenerationInputs::default();
-
-        let mut ola_stark = OlaStark::default();
-        let (traces, public_values) = generate_traces(program, &mut ola_stark, inputs);
-
         let config = StarkConfig::standard_fast_config();
-        let proof = prove_with_traces::<F, C, D>(
-            &ola_stark,
-            &config,
-            traces,
-            public_values,
-            &mut TimingTree::default(),
-        );
+        let proof = prove_by_asm_path::<C>(program_path, call_data, db_name);
 
         if let Ok(proof) = proof {
             let ola_stark = OlaStark::default();
@@ -81,7 +81,6 @@ impl GrandProductChallenge<Target> {
         builder.add_extension(reduced, gamma)
     }
 
-    #[allow(dead_code)]
     pub(crate) fn combine_base_circuit<F: RichField + Extendable<D>, const D: usize>(
         &self,
         builder: &mut CircuitBuilder<F, D>,
@@ -318,8 +317,9 @@ pub(crate) fn eval_permutation_checks<F, FE, P, C, S, const D: usize, const D2:
         permutation_challenge_sets,
     } = permutation_vars;
 
-    // Check that Z(1) = 1;
-    for &z in &local_zs {
+    // Check that Z(1) = 1; the lookup helper columns after the Z batches have
+    // their own checks.
+    for &z in &local_zs[..stark.num_permutation_batches(config)] {
         consumer.constraint_first_row(z - FE::ONE);
     }
 
@@ -384,8 +384,9 @@ pub(crate) fn eval_permutation_checks_circuit<F, S, const D: usize>(
     } = permutation_data;
 
     let one = builder.one_extension();
-    // Check that Z(1) = 1;
-    for &z in &local_zs {
+    // Check that Z(1) = 1; the lookup helper columns after the Z batches have
+    // their own checks.
+    for &z in &local_zs[..stark.num_permutation_batches(config)] {
         let z_1 = builder.sub_extension(z, one);
         consumer.constraint_first_row(builder, z_1);
     }
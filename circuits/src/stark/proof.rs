@@ -1,4 +1,4 @@
-use ethereum_types::{Address, H256, U256};
+use core::crypto::poseidon_trace::calculate_arbitrary_poseidon_u64s;
 use itertools::Itertools;
 use maybe_rayon::*;
 use plonky2::field::extension::{Extendable, FieldExtension};
@@ -10,20 +10,25 @@ use plonky2::fri::structure::{
 use plonky2::hash::hash_types::{MerkleCapTarget, RichField};
 use plonky2::hash::hashing::SPONGE_WIDTH;
 use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::hash::poseidon::PoseidonHash;
 use plonky2::iop::ext_target::ExtensionTarget;
-use plonky2::iop::target::Target;
+use plonky2::iop::target::{BoolTarget, Target};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::config::GenericConfig;
 use serde::{Deserialize, Serialize};
+use std::iter;
 
 use super::config::StarkConfig;
 use super::ola_stark::NUM_TABLES;
 use super::permutation::GrandProductChallengeSet;
+use crate::builtins::tape::columns::{
+    TAPE_BLOCK_NUMBER, TAPE_BLOCK_TIMESTAMP, TAPE_CHAIN_ID, TAPE_SEQUENCER_ADDRESS, TAPE_TX_HASH,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(bound = "")]
 pub struct AllProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> {
     pub stark_proofs: [StarkProof<F, C, D>; NUM_TABLES],
-    pub compress_challenges: [F; NUM_TABLES],
     pub public_values: PublicValues,
 }
 
@@ -47,60 +52,322 @@ pub(crate) struct AllChallengerState<F: RichField + Extendable<D>, const D: usiz
     pub ctl_challenges: GrandProductChallengeSet<F>,
 }
 
+pub(crate) struct AllProofChallengesTarget<const D: usize> {
+    pub stark_challenges: [StarkProofChallengesTarget<D>; NUM_TABLES],
+    pub ctl_challenges: GrandProductChallengeSet<Target>,
+}
+
 pub struct AllProofTarget<const D: usize> {
     pub stark_proofs: [StarkProofTarget<D>; NUM_TABLES],
     pub public_values: PublicValuesTarget,
 }
 
-/// Memory values which are public.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// Values which are public: the state roots around the batch, the block the
+/// batch belongs to, its transactions, the events they emitted and the
+/// signatures they checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PublicValues {
-    pub trie_roots_before: TrieRoots,
-    pub trie_roots_after: TrieRoots,
+    pub state_root_before: [u64; 4],
+    pub state_root_after: [u64; 4],
     pub block_metadata: BlockMetadata,
+    /// Hash of every transaction of the batch, in execution order.
+    pub tx_hashes: Vec<[u64; 4]>,
+    /// Events emitted by the batch, sorted by transaction.
+    pub events: Vec<EventLog>,
+    /// Signatures checked by `sigcheck`, in no particular order.
+    pub sig_checks: Vec<SigCheck>,
+}
+
+/// An event emitted by the transaction at `tx_idx` of the batch. The address
+/// of the emitting contract is not part of it, the circuits do not know it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventLog {
+    pub tx_idx: u64,
+    pub topics: Vec<[u64; 4]>,
+    pub data: Vec<u64>,
+}
+
+/// Inputs and result of a `sigcheck`. The circuits only prove the CPU got
+/// `verified` for these inputs, the signature itself is checked natively by
+/// the verifier.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigCheck {
+    pub msg_hash: [u64; 4],
+    pub pubkey_x: [u64; 4],
+    pub pubkey_y: [u64; 4],
+    pub r: [u64; 4],
+    pub s: [u64; 4],
+    pub verified: bool,
+}
+
+impl SigCheck {
+    /// msg_hash, pubkey_x, pubkey_y, r and s, as laid out in memory.
+    pub fn inputs(&self) -> [u64; 20] {
+        let mut inputs = [0; 20];
+        for (i, part) in [self.msg_hash, self.pubkey_x, self.pubkey_y, self.r, self.s]
+            .iter()
+            .enumerate()
+        {
+            inputs[4 * i..4 * i + 4].copy_from_slice(part);
+        }
+        inputs
+    }
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-pub struct TrieRoots {
-    pub state_root: H256,
-    pub transactions_root: H256,
-    pub receipts_root: H256,
+/// Poseidon hash of `sig_checks`, each one flattened as
+/// `msg_hash, pubkey_x, pubkey_y, r, s, verified`.
+pub fn sig_checks_commitment(sig_checks: &[SigCheck]) -> [u64; 4] {
+    let mut inputs = vec![];
+    for sig_check in sig_checks {
+        inputs.extend(sig_check.inputs());
+        inputs.push(sig_check.verified as u64);
+    }
+    calculate_arbitrary_poseidon_u64s(&inputs)
 }
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
 pub struct BlockMetadata {
-    pub block_beneficiary: Address,
-    pub block_timestamp: U256,
-    pub block_number: U256,
-    pub block_difficulty: U256,
-    pub block_gaslimit: U256,
-    pub block_chain_id: U256,
-    pub block_base_fee: U256,
+    pub block_number: u64,
+    pub block_timestamp: u64,
+    pub sequencer_address: [u64; 4],
+    pub chain_id: u64,
 }
 
-/// Memory values which are public.
-/// Note: All the larger integers are encoded with 32-bit limbs in little-endian
-/// order.
+impl PublicValues {
+    /// Poseidon hash of the concatenated transaction hashes.
+    pub fn tx_commitment(&self) -> [u64; 4] {
+        calculate_arbitrary_poseidon_u64s(&self.tx_hashes.concat())
+    }
+
+    /// `(tx_idx, addr, value)` tape cells fixed by the public values: the
+    /// block metadata and the hash of each transaction are written to the
+    /// tape header of every transaction before execution.
+    pub(crate) fn tape_cells(&self) -> Vec<[u64; 3]> {
+        let metadata = &self.block_metadata;
+        let mut header = vec![
+            (TAPE_BLOCK_NUMBER, metadata.block_number),
+            (TAPE_BLOCK_TIMESTAMP, metadata.block_timestamp),
+            (TAPE_CHAIN_ID, metadata.chain_id),
+        ];
+        header.extend(TAPE_SEQUENCER_ADDRESS.zip(metadata.sequencer_address));
+        self.tx_hashes
+            .iter()
+            .enumerate()
+            .flat_map(|(tx_idx, tx_hash)| {
+                header
+                    .iter()
+                    .copied()
+                    .chain(TAPE_TX_HASH.zip(*tx_hash))
+                    .map(move |(addr, value)| [tx_idx as u64, addr, value])
+            })
+            .collect()
+    }
+
+    /// Poseidon hash of the events, each one flattened as
+    /// `tx_idx, #topics, topics, #data, data`.
+    pub fn events_commitment(&self) -> [u64; 4] {
+        let mut inputs = vec![];
+        for event in &self.events {
+            inputs.push(event.tx_idx);
+            inputs.push(event.topics.len() as u64);
+            inputs.extend(event.topics.concat());
+            inputs.push(event.data.len() as u64);
+            inputs.extend(&event.data);
+        }
+        calculate_arbitrary_poseidon_u64s(&inputs)
+    }
+
+    /// Poseidon hash of the signature checks, see `sig_checks_commitment`.
+    pub fn sig_checks_commitment(&self) -> [u64; 4] {
+        sig_checks_commitment(&self.sig_checks)
+    }
+
+    /// `(tx_idx, event_idx, kind, idx, w)` rows of the event table: a head
+    /// row `w = [#topics, #data, 0, 0]` of kind 0, then one row of kind 1 per
+    /// topic and one row of kind 2 per data item.
+    pub(crate) fn event_cells(&self) -> Vec<[u64; 8]> {
+        let mut cells = vec![];
+        for (event_idx, event) in self.events.iter().enumerate() {
+            let head = [event.topics.len() as u64, event.data.len() as u64, 0, 0];
+            let rows = iter::once((0, 0, head))
+                .chain(
+                    event
+                        .topics
+                        .iter()
+                        .enumerate()
+                        .map(|(i, topic)| (1, i, *topic)),
+                )
+                .chain(
+                    event
+                        .data
+                        .iter()
+                        .enumerate()
+                        .map(|(i, data)| (2, i, [*data, 0, 0, 0])),
+                );
+            for (kind, idx, w) in rows {
+                cells.push([
+                    event.tx_idx,
+                    event_idx as u64,
+                    kind,
+                    idx as u64,
+                    w[0],
+                    w[1],
+                    w[2],
+                    w[3],
+                ]);
+            }
+        }
+        cells
+    }
+
+    /// `(inputs, verified)` rows of the sigcheck table.
+    pub(crate) fn sig_check_cells(&self) -> Vec<[u64; 21]> {
+        self.sig_checks
+            .iter()
+            .map(|sig_check| {
+                let mut cell = [0; 21];
+                cell[..20].copy_from_slice(&sig_check.inputs());
+                cell[20] = sig_check.verified as u64;
+                cell
+            })
+            .collect()
+    }
+}
+
+/// Values which are public.
 pub struct PublicValuesTarget {
-    pub trie_roots_before: TrieRootsTarget,
-    pub trie_roots_after: TrieRootsTarget,
+    pub state_root_before: [Target; 4],
+    pub state_root_after: [Target; 4],
     pub block_metadata: BlockMetadataTarget,
+    pub tx_hashes: Vec<[Target; 4]>,
+    pub events: Vec<EventLogTarget>,
+    pub sig_checks: Vec<SigCheckTarget>,
+}
+
+/// The transaction index and the number of topics and data items are part of
+/// the circuit shape.
+pub struct EventLogTarget {
+    pub tx_idx: u64,
+    pub topics: Vec<[Target; 4]>,
+    pub data: Vec<Target>,
 }
 
-pub struct TrieRootsTarget {
-    pub state_root: [Target; 8],
-    pub transactions_root: [Target; 8],
-    pub receipts_root: [Target; 8],
+pub struct SigCheckTarget {
+    pub inputs: [Target; 20],
+    pub verified: BoolTarget,
+}
+
+impl PublicValuesTarget {
+    /// In-circuit counterpart of `PublicValues::tape_cells`.
+    pub(crate) fn tape_cells<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Vec<[Target; 3]> {
+        let metadata = &self.block_metadata;
+        let mut header = vec![
+            (TAPE_BLOCK_NUMBER, metadata.block_number),
+            (TAPE_BLOCK_TIMESTAMP, metadata.block_timestamp),
+            (TAPE_CHAIN_ID, metadata.chain_id),
+        ];
+        header.extend(TAPE_SEQUENCER_ADDRESS.zip(metadata.sequencer_address));
+        let mut cells = vec![];
+        for (tx_idx, tx_hash) in self.tx_hashes.iter().enumerate() {
+            let tx_idx = builder.constant(F::from_canonical_usize(tx_idx));
+            for (addr, value) in header.iter().copied().chain(TAPE_TX_HASH.zip(*tx_hash)) {
+                let addr = builder.constant(F::from_canonical_u64(addr));
+                cells.push([tx_idx, addr, value]);
+            }
+        }
+        cells
+    }
+
+    /// In-circuit counterpart of `PublicValues::event_cells`.
+    pub(crate) fn event_cells<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> Vec<[Target; 8]> {
+        let zero = builder.zero();
+        let mut cells = vec![];
+        for (event_idx, event) in self.events.iter().enumerate() {
+            let tx_idx = builder.constant(F::from_canonical_u64(event.tx_idx));
+            let event_idx = builder.constant(F::from_canonical_usize(event_idx));
+            let num_topics = builder.constant(F::from_canonical_usize(event.topics.len()));
+            let num_data = builder.constant(F::from_canonical_usize(event.data.len()));
+            let mut rows = vec![(0, 0, [num_topics, num_data, zero, zero])];
+            rows.extend(
+                event
+                    .topics
+                    .iter()
+                    .enumerate()
+                    .map(|(i, topic)| (1, i, *topic)),
+            );
+            rows.extend(
+                event
+                    .data
+                    .iter()
+                    .enumerate()
+                    .map(|(i, data)| (2, i, [*data, zero, zero, zero])),
+            );
+            for (kind, idx, w) in rows {
+                let kind = builder.constant(F::from_canonical_u64(kind));
+                let idx = builder.constant(F::from_canonical_usize(idx));
+                cells.push([tx_idx, event_idx, kind, idx, w[0], w[1], w[2], w[3]]);
+            }
+        }
+        cells
+    }
+
+    /// In-circuit counterpart of `PublicValues::sig_check_cells`.
+    pub(crate) fn sig_check_cells(&self) -> Vec<[Target; 21]> {
+        self.sig_checks
+            .iter()
+            .map(|sig_check| {
+                let mut cell = [sig_check.verified.target; 21];
+                cell[..20].copy_from_slice(&sig_check.inputs);
+                cell
+            })
+            .collect()
+    }
+
+    /// In-circuit counterpart of `PublicValues::events_commitment`.
+    pub(crate) fn events_commitment<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> [Target; 4] {
+        let mut inputs = vec![];
+        for event in &self.events {
+            inputs.push(builder.constant(F::from_canonical_u64(event.tx_idx)));
+            inputs.push(builder.constant(F::from_canonical_usize(event.topics.len())));
+            inputs.extend(event.topics.concat());
+            inputs.push(builder.constant(F::from_canonical_usize(event.data.len())));
+            inputs.extend(&event.data);
+        }
+        builder
+            .hash_n_to_hash_no_pad::<PoseidonHash>(inputs)
+            .elements
+    }
+
+    /// In-circuit counterpart of `PublicValues::sig_checks_commitment`.
+    pub(crate) fn sig_checks_commitment<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+    ) -> [Target; 4] {
+        let inputs = self
+            .sig_check_cells()
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        builder
+            .hash_n_to_hash_no_pad::<PoseidonHash>(inputs)
+            .elements
+    }
 }
 
 pub struct BlockMetadataTarget {
-    pub block_beneficiary: [Target; 5],
-    pub block_timestamp: Target,
     pub block_number: Target,
-    pub block_difficulty: Target,
-    pub block_gaslimit: Target,
-    pub block_chain_id: Target,
-    pub block_base_fee: Target,
+    pub block_timestamp: Target,
+    pub sequencer_address: [Target; 4],
+    pub chain_id: Target,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -167,7 +434,6 @@ pub(crate) struct StarkProofChallenges<F: RichField + Extendable<D>, const D: us
     pub fri_challenges: FriChallenges<F, D>,
 }
 
-#[allow(unused)]
 pub(crate) struct StarkProofChallengesTarget<const D: usize> {
     pub permutation_challenge_sets: Option<Vec<GrandProductChallengeSet<Target>>>,
     pub stark_alphas: Vec<Target>,
@@ -1,4 +1,6 @@
+use core::merkle_tree::tree::AccountTree;
 use core::program::Program;
+use core::tx::BatchResult;
 use std::any::type_name;
 use std::collections::BTreeMap;
 
@@ -23,17 +25,24 @@ use plonky2_util::{log2_ceil, log2_strict};
 use super::ola_stark::{OlaStark, Table, NUM_TABLES};
 use crate::builtins::bitwise::bitwise_stark::BitwiseStark;
 use crate::builtins::cmp::cmp_stark::CmpStark;
+use crate::builtins::event::event_stark::EventStark;
 use crate::builtins::poseidon::poseidon_chunk_stark::PoseidonChunkStark;
 use crate::builtins::poseidon::poseidon_stark::PoseidonStark;
+use crate::builtins::rangecheck::rangecheck_stark::RangeCheckStark;
 use crate::builtins::sccall::sccall_stark::SCCallStark;
+use crate::builtins::sigcheck::sigcheck_stark::SigCheckStark;
 use crate::builtins::storage::storage_access_stark::StorageAccessStark;
+use crate::builtins::tape::tape_stark::TapeStark;
+use crate::fixed_table::bitwise_fixed::bitwise_fixed_stark::BitwiseFixedStark;
+use crate::fixed_table::rangecheck_fixed::rangecheck_fixed_stark::RangeCheckFixedStark;
 use crate::program::prog_chunk_stark::ProgChunkStark;
 use crate::program::program_stark::ProgramStark;
-// use crate::builtins::tape::tape_stark::TapeStark;
 //use crate::columns::NUM_CPU_COLS;
 use super::config::StarkConfig;
 use super::constraint_consumer::ConstraintConsumer;
 use super::cross_table_lookup::{cross_table_lookup_data, CtlCheckVars, CtlData};
+use super::get_challenges::observe_public_values;
+use super::lookup::compute_lookup_helper_polys;
 use super::permutation::PermutationCheckVars;
 use super::permutation::{
     compute_permutation_z_polys, get_n_grand_product_challenge_sets, GrandProductChallengeSet,
@@ -43,7 +52,8 @@ use super::stark::Stark;
 use super::vanishing_poly::eval_vanishing_poly;
 use super::vars::StarkEvaluationVars;
 use crate::cpu::cpu_stark::CpuStark;
-use crate::generation::{generate_traces, GenerationInputs};
+use crate::generation::segment::split_batch;
+use crate::generation::{generate_batch_traces, generate_traces, GenerationInputs};
 use crate::memory::memory_stark::MemoryStark;
 
 /// Generate traces, then create all STARK proofs.
@@ -62,19 +72,70 @@ where
     [(); MemoryStark::<F, D>::COLUMNS]:,
     [(); BitwiseStark::<F, D>::COLUMNS]:,
     [(); CmpStark::<F, D>::COLUMNS]:,
-    // [(); RangeCheckStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckStark::<F, D>::COLUMNS]:,
     [(); PoseidonStark::<F, D>::COLUMNS]:,
     [(); PoseidonChunkStark::<F, D>::COLUMNS]:,
     [(); StorageAccessStark::<F, D>::COLUMNS]:,
-    // [(); TapeStark::<F, D>::COLUMNS]:,
+    [(); TapeStark::<F, D>::COLUMNS]:,
     [(); SCCallStark::<F, D>::COLUMNS]:,
     [(); ProgramStark::<F, D>::COLUMNS]:,
     [(); ProgChunkStark::<F, D>::COLUMNS]:,
+    [(); SigCheckStark::<F, D>::COLUMNS]:,
+    [(); EventStark::<F, D>::COLUMNS]:,
+    [(); BitwiseFixedStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckFixedStark::<F, D>::COLUMNS]:,
 {
     let (traces, public_values) = generate_traces(program, ola_stark, inputs);
     prove_with_traces(ola_stark, config, traces, public_values, timing)
 }
 
+/// Proves a batch executed by `BlockExeManager`, one proof per segment of
+/// `split_batch` in execution order. `account_tree` must hold the state the
+/// batch started from; it holds the state after the batch on success.
+pub fn prove_batch<F, C, const D: usize>(
+    batch: BatchResult,
+    account_tree: &mut AccountTree,
+    config: &StarkConfig,
+    max_degree_bits: Option<usize>,
+    timing: &mut TimingTree,
+) -> Result<Vec<AllProof<F, C, D>>>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    [(); C::Hasher::HASH_SIZE]:,
+    [(); CpuStark::<F, D>::COLUMNS]:,
+    [(); MemoryStark::<F, D>::COLUMNS]:,
+    [(); BitwiseStark::<F, D>::COLUMNS]:,
+    [(); CmpStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckStark::<F, D>::COLUMNS]:,
+    [(); PoseidonStark::<F, D>::COLUMNS]:,
+    [(); PoseidonChunkStark::<F, D>::COLUMNS]:,
+    [(); StorageAccessStark::<F, D>::COLUMNS]:,
+    [(); TapeStark::<F, D>::COLUMNS]:,
+    [(); SCCallStark::<F, D>::COLUMNS]:,
+    [(); ProgramStark::<F, D>::COLUMNS]:,
+    [(); ProgChunkStark::<F, D>::COLUMNS]:,
+    [(); SigCheckStark::<F, D>::COLUMNS]:,
+    [(); EventStark::<F, D>::COLUMNS]:,
+    [(); BitwiseFixedStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckFixedStark::<F, D>::COLUMNS]:,
+{
+    split_batch(batch, max_degree_bits)?
+        .into_iter()
+        .map(|segment| {
+            // compress challenges and state roots are set per segment.
+            let mut ola_stark = OlaStark::default();
+            let (traces, public_values) = generate_batch_traces(
+                segment.txs,
+                account_tree,
+                &mut ola_stark,
+                GenerationInputs::default(),
+            );
+            prove_with_traces(&ola_stark, config, traces, public_values, timing)
+        })
+        .collect()
+}
+
 /// Compute all STARK proofs.
 pub fn prove_with_traces<F, C, const D: usize>(
     ola_stark: &OlaStark<F, D>,
@@ -91,14 +152,18 @@ where
     [(); MemoryStark::<F, D>::COLUMNS]:,
     [(); BitwiseStark::<F, D>::COLUMNS]:,
     [(); CmpStark::<F, D>::COLUMNS]:,
-    // [(); RangeCheckStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckStark::<F, D>::COLUMNS]:,
     [(); PoseidonStark::<F, D>::COLUMNS]:,
     [(); PoseidonChunkStark::<F, D>::COLUMNS]:,
     [(); StorageAccessStark::<F, D>::COLUMNS]:,
-    // [(); TapeStark::<F, D>::COLUMNS]:,
+    [(); TapeStark::<F, D>::COLUMNS]:,
     [(); SCCallStark::<F, D>::COLUMNS]:,
     [(); ProgramStark::<F, D>::COLUMNS]:,
     [(); ProgChunkStark::<F, D>::COLUMNS]:,
+    [(); SigCheckStark::<F, D>::COLUMNS]:,
+    [(); EventStark::<F, D>::COLUMNS]:,
+    [(); BitwiseFixedStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckFixedStark::<F, D>::COLUMNS]:,
 {
     let rate_bits = config.fri_config.rate_bits;
     let cap_height = config.fri_config.cap_height;
@@ -140,6 +205,7 @@ where
     for cap in &trace_caps {
         challenger.observe_cap(cap);
     }
+    observe_public_values(&mut challenger, &public_values);
 
     #[cfg(feature = "benchmark")]
     let start = Instant::now();
@@ -285,6 +351,46 @@ where
         timing,
         &mut twiddle_map,
     )?;
+    let sigcheck_proof = prove_single_table(
+        &ola_stark.sigcheck_stark,
+        config,
+        &trace_poly_values[Table::SigCheck as usize],
+        &trace_commitments[Table::SigCheck as usize],
+        &ctl_data_per_table[Table::SigCheck as usize],
+        &mut challenger,
+        timing,
+        &mut twiddle_map,
+    )?;
+    let event_proof = prove_single_table(
+        &ola_stark.event_stark,
+        config,
+        &trace_poly_values[Table::Event as usize],
+        &trace_commitments[Table::Event as usize],
+        &ctl_data_per_table[Table::Event as usize],
+        &mut challenger,
+        timing,
+        &mut twiddle_map,
+    )?;
+    let bitwise_fixed_proof = prove_single_table(
+        &ola_stark.bitwise_fixed_stark,
+        config,
+        &trace_poly_values[Table::BitwiseFixed as usize],
+        &trace_commitments[Table::BitwiseFixed as usize],
+        &ctl_data_per_table[Table::BitwiseFixed as usize],
+        &mut challenger,
+        timing,
+        &mut twiddle_map,
+    )?;
+    let rangecheck_fixed_proof = prove_single_table(
+        &ola_stark.rangecheck_fixed_stark,
+        config,
+        &trace_poly_values[Table::RangeCheckFixed as usize],
+        &trace_commitments[Table::RangeCheckFixed as usize],
+        &ctl_data_per_table[Table::RangeCheckFixed as usize],
+        &mut challenger,
+        timing,
+        &mut twiddle_map,
+    )?;
 
     #[cfg(feature = "benchmark")]
     info!("prove_other_table total time: {:?}", start.elapsed());
@@ -302,26 +408,14 @@ where
         sccall_proof,
         program_proof,
         prog_chunk_proof,
-    ];
-
-    let compress_challenges = [
-        F::ZERO,
-        F::ZERO,
-        ola_stark.bitwise_stark.get_compress_challenge().unwrap(),
-        F::ZERO,
-        F::ZERO,
-        F::ZERO,
-        F::ZERO,
-        F::ZERO,
-        F::ZERO,
-        F::ZERO,
-        ola_stark.program_stark.get_compress_challenge().unwrap(),
-        F::ZERO,
+        sigcheck_proof,
+        event_proof,
+        bitwise_fixed_proof,
+        rangecheck_fixed_proof,
     ];
 
     Ok(AllProof {
         stark_proofs,
-        compress_challenges,
         public_values,
     })
 }
@@ -357,7 +451,7 @@ where
     challenger.compact();
 
     // Permutation arguments.
-    let permutation_challenges = stark.uses_permutation_args().then(|| {
+    let permutation_challenges = stark.uses_permutation_zs().then(|| {
         get_n_grand_product_challenge_sets(
             challenger,
             config.num_challenges,
@@ -369,11 +463,17 @@ where
     let start = Instant::now();
 
     let permutation_zs = permutation_challenges.as_ref().map(|challenges| {
-        timed!(
+        let mut permutation_zs = timed!(
             timing,
             "compute permutation Z(x) polys",
             compute_permutation_z_polys::<F, C, S, D>(stark, config, trace_poly_values, challenges)
-        )
+        );
+        permutation_zs.extend(timed!(
+            timing,
+            "compute lookup helper polys",
+            compute_lookup_helper_polys::<F, S, D>(stark, trace_poly_values, challenges)
+        ));
+        permutation_zs
     });
 
     #[cfg(feature = "benchmark")]
@@ -520,7 +620,7 @@ where
         &permutation_ctl_zs_commitment,
         &quotient_commitment,
         degree_bits,
-        stark.num_permutation_batches(config),
+        stark.num_permutation_zs(config),
     );
 
     #[cfg(feature = "benchmark")]
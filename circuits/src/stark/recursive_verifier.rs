@@ -0,0 +1,754 @@
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Field;
+use plonky2::fri::witness_util::set_fri_proof_target;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::Witness;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::util::reducing::ReducingFactorTarget;
+use plonky2::with_context;
+
+use super::config::StarkConfig;
+use super::constraint_consumer::RecursiveConstraintConsumer;
+use super::cross_table_lookup::{verify_cross_table_lookups_circuit, CtlCheckVarsTarget};
+use super::ola_stark::{OlaStark, Table, NUM_TABLES};
+use super::permutation::{GrandProductChallenge, PermutationCheckDataTarget};
+use super::proof::{
+    AllProof, AllProofChallengesTarget, AllProofTarget, BlockMetadata, BlockMetadataTarget,
+    EventLogTarget, PublicValues, PublicValuesTarget, SigCheckTarget, StarkOpeningSetTarget,
+    StarkProof, StarkProofChallengesTarget, StarkProofTarget,
+};
+use super::stark::Stark;
+use super::vanishing_poly::eval_vanishing_poly_circuit;
+use super::vars::StarkEvaluationTargets;
+use crate::builtins::bitwise::bitwise_stark::BitwiseStark;
+use crate::builtins::cmp::cmp_stark::CmpStark;
+use crate::builtins::event::event_stark::EventStark;
+use crate::builtins::poseidon::poseidon_chunk_stark::PoseidonChunkStark;
+use crate::builtins::poseidon::poseidon_stark::PoseidonStark;
+use crate::builtins::rangecheck::rangecheck_stark::RangeCheckStark;
+use crate::builtins::sccall::sccall_stark::SCCallStark;
+use crate::builtins::sigcheck::sigcheck_stark::SigCheckStark;
+use crate::builtins::storage::storage_access_stark::StorageAccessStark;
+use crate::builtins::tape::tape_stark::TapeStark;
+use crate::cpu::cpu_stark::CpuStark;
+use crate::fixed_table::bitwise_fixed::bitwise_fixed_stark::BitwiseFixedStark;
+use crate::fixed_table::rangecheck_fixed::rangecheck_fixed_stark::RangeCheckFixedStark;
+use crate::memory::memory_stark::MemoryStark;
+use crate::program::prog_chunk_stark::ProgChunkStark;
+use crate::program::program_stark::ProgramStark;
+
+/// In-circuit counterpart of `verify_proof`: verifies every STARK of
+/// `all_proof_target` and the cross-table lookups between them.
+pub fn verify_proof_circuit<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    ola_stark: OlaStark<F, D>,
+    all_proof_target: &AllProofTarget<D>,
+    inner_config: &StarkConfig,
+) where
+    C::Hasher: AlgebraicHasher<F>,
+    [(); CpuStark::<F, D>::COLUMNS]:,
+    [(); MemoryStark::<F, D>::COLUMNS]:,
+    [(); BitwiseStark::<F, D>::COLUMNS]:,
+    [(); CmpStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckStark::<F, D>::COLUMNS]:,
+    [(); PoseidonStark::<F, D>::COLUMNS]:,
+    [(); PoseidonChunkStark::<F, D>::COLUMNS]:,
+    [(); StorageAccessStark::<F, D>::COLUMNS]:,
+    [(); TapeStark::<F, D>::COLUMNS]:,
+    [(); SCCallStark::<F, D>::COLUMNS]:,
+    [(); ProgramStark::<F, D>::COLUMNS]:,
+    [(); ProgChunkStark::<F, D>::COLUMNS]:,
+    [(); SigCheckStark::<F, D>::COLUMNS]:,
+    [(); EventStark::<F, D>::COLUMNS]:,
+    [(); BitwiseFixedStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckFixedStark::<F, D>::COLUMNS]:,
+{
+    let AllProofChallengesTarget {
+        stark_challenges,
+        ctl_challenges,
+    } = all_proof_target.get_challenges::<F, C>(builder, &ola_stark, inner_config);
+
+    let nums_permutation_zs = ola_stark.nums_permutation_zs(inner_config);
+
+    let OlaStark {
+        cpu_stark,
+        memory_stark,
+        bitwise_stark,
+        cmp_stark,
+        rangecheck_stark,
+        poseidon_stark,
+        poseidon_chunk_stark,
+        mut storage_access_stark,
+        tape_stark,
+        sccall_stark,
+        program_stark,
+        prog_chunk_stark,
+        sigcheck_stark,
+        event_stark,
+        bitwise_fixed_stark,
+        rangecheck_fixed_stark,
+        cross_table_lookups,
+    } = ola_stark;
+
+    let public_values = &all_proof_target.public_values;
+    storage_access_stark.set_state_root_targets(
+        public_values.state_root_before,
+        public_values.state_root_after,
+    );
+
+    let ctl_vars_per_table: [Vec<CtlCheckVarsTarget<F, D>>; NUM_TABLES] =
+        std::array::from_fn(|i| {
+            CtlCheckVarsTarget::from_proof(
+                TABLES[i],
+                &all_proof_target.stark_proofs[i],
+                &cross_table_lookups,
+                &ctl_challenges,
+                nums_permutation_zs[i],
+            )
+        });
+
+    macro_rules! verify_table {
+        ($stark:expr, $table:expr) => {
+            with_context!(
+                builder,
+                &format!("verify {:?} proof", $table),
+                verify_stark_proof_with_challenges_circuit::<F, C, _, D>(
+                    builder,
+                    $stark,
+                    &all_proof_target.stark_proofs[$table as usize],
+                    &stark_challenges[$table as usize],
+                    &ctl_vars_per_table[$table as usize],
+                    inner_config,
+                )
+            )
+        };
+    }
+    verify_table!(cpu_stark, Table::Cpu);
+    verify_table!(memory_stark, Table::Memory);
+    verify_table!(bitwise_stark, Table::Bitwise);
+    verify_table!(cmp_stark, Table::Cmp);
+    verify_table!(rangecheck_stark, Table::RangeCheck);
+    verify_table!(poseidon_stark, Table::Poseidon);
+    verify_table!(poseidon_chunk_stark, Table::PoseidonChunk);
+    verify_table!(storage_access_stark, Table::StorageAccess);
+    verify_table!(tape_stark, Table::Tape);
+    verify_table!(sccall_stark, Table::SCCall);
+    verify_table!(program_stark, Table::Program);
+    verify_table!(prog_chunk_stark, Table::ProgChunk);
+    verify_table!(sigcheck_stark, Table::SigCheck);
+    verify_table!(event_stark, Table::Event);
+    verify_table!(bitwise_fixed_stark, Table::BitwiseFixed);
+    verify_table!(rangecheck_fixed_stark, Table::RangeCheckFixed);
+
+    let extra_looking_products = cross_table_lookups
+        .iter()
+        .map(|ctl| {
+            ctl_challenges
+                .challenges
+                .iter()
+                .map(|&challenge| match ctl.public_looked_table() {
+                    Some(Table::Tape) => {
+                        get_tape_extra_looking_products_circuit(builder, public_values, challenge)
+                    }
+                    Some(Table::SigCheck) => get_sig_check_extra_looking_products_circuit(
+                        builder,
+                        public_values,
+                        challenge,
+                    ),
+                    Some(Table::Event) => {
+                        get_event_extra_looking_products_circuit(builder, public_values, challenge)
+                    }
+                    Some(table) => panic!("no public values looked up in {:?}", table),
+                    None => builder.one(),
+                })
+                .collect()
+        })
+        .collect();
+
+    with_context!(
+        builder,
+        "verify cross-table lookups",
+        verify_cross_table_lookups_circuit::<F, C, D>(
+            builder,
+            cross_table_lookups,
+            std::array::from_fn(|i| all_proof_target.stark_proofs[i]
+                .openings
+                .ctl_zs_last
+                .clone()),
+            extra_looking_products,
+            inner_config,
+        )
+    );
+}
+
+const TABLES: [Table; NUM_TABLES] = [
+    Table::Cpu,
+    Table::Memory,
+    Table::Bitwise,
+    Table::Cmp,
+    Table::RangeCheck,
+    Table::Poseidon,
+    Table::PoseidonChunk,
+    Table::StorageAccess,
+    Table::Tape,
+    Table::SCCall,
+    Table::Program,
+    Table::ProgChunk,
+    Table::SigCheck,
+    Table::Event,
+    Table::BitwiseFixed,
+    Table::RangeCheckFixed,
+];
+
+/// In-circuit counterpart of `get_tape_extra_looking_products`.
+fn get_tape_extra_looking_products_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    public_values: &PublicValuesTarget,
+    challenge: GrandProductChallenge<Target>,
+) -> Target {
+    let combined = public_values
+        .tape_cells(builder)
+        .iter()
+        .map(|cell| challenge.combine_base_circuit(builder, cell))
+        .collect::<Vec<_>>();
+    builder.mul_many(combined)
+}
+
+/// In-circuit counterpart of `get_sig_check_extra_looking_products`. The
+/// signatures are not checked in the circuit, see `SigCheckStark`.
+fn get_sig_check_extra_looking_products_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    public_values: &PublicValuesTarget,
+    challenge: GrandProductChallenge<Target>,
+) -> Target {
+    let combined = public_values
+        .sig_check_cells()
+        .iter()
+        .map(|cell| challenge.combine_base_circuit(builder, cell))
+        .collect::<Vec<_>>();
+    builder.mul_many(combined)
+}
+
+/// In-circuit counterpart of `get_event_extra_looking_products`.
+fn get_event_extra_looking_products_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    public_values: &PublicValuesTarget,
+    challenge: GrandProductChallenge<Target>,
+) -> Target {
+    let combined = public_values
+        .event_cells(builder)
+        .iter()
+        .map(|cell| challenge.combine_base_circuit(builder, cell))
+        .collect::<Vec<_>>();
+    builder.mul_many(combined)
+}
+
+fn verify_stark_proof_with_challenges_circuit<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    const D: usize,
+>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: S,
+    proof: &StarkProofTarget<D>,
+    challenges: &StarkProofChallengesTarget<D>,
+    ctl_vars: &[CtlCheckVarsTarget<F, D>],
+    inner_config: &StarkConfig,
+) where
+    C::Hasher: AlgebraicHasher<F>,
+    [(); S::COLUMNS]:,
+{
+    let zero = builder.zero();
+    let one = builder.one_extension();
+
+    let StarkOpeningSetTarget {
+        local_values,
+        next_values,
+        permutation_ctl_zs,
+        permutation_ctl_zs_next,
+        ctl_zs_last,
+        quotient_polys,
+    } = &proof.openings;
+    let vars = StarkEvaluationTargets {
+        local_values: &local_values.to_vec().try_into().unwrap(),
+        next_values: &next_values.to_vec().try_into().unwrap(),
+    };
+
+    let degree_bits = proof.recover_degree_bits(inner_config);
+    let zeta_pow_deg = builder.exp_power_of_2_extension(challenges.stark_zeta, degree_bits);
+    let z_h_zeta = builder.sub_extension(zeta_pow_deg, one);
+    let (l_0, l_last) =
+        eval_l_0_and_l_last_circuit(builder, degree_bits, challenges.stark_zeta, z_h_zeta);
+    let last =
+        builder.constant_extension(F::Extension::primitive_root_of_unity(degree_bits).inverse());
+    let z_last = builder.sub_extension(challenges.stark_zeta, last);
+
+    let mut consumer = RecursiveConstraintConsumer::<F, D>::new(
+        builder.zero_extension(),
+        challenges.stark_alphas.clone(),
+        z_last,
+        l_0,
+        l_last,
+    );
+
+    let num_permutation_zs = stark.num_permutation_zs(inner_config);
+    let permutation_data = stark
+        .uses_permutation_zs()
+        .then(|| PermutationCheckDataTarget {
+            local_zs: permutation_ctl_zs[..num_permutation_zs].to_vec(),
+            next_zs: permutation_ctl_zs_next[..num_permutation_zs].to_vec(),
+            permutation_challenge_sets: challenges.permutation_challenge_sets.clone().unwrap(),
+        });
+
+    with_context!(
+        builder,
+        "evaluate vanishing polynomial",
+        eval_vanishing_poly_circuit::<F, C, S, D>(
+            builder,
+            &stark,
+            inner_config,
+            vars,
+            permutation_data,
+            ctl_vars,
+            &mut consumer,
+        )
+    );
+    let vanishing_polys_zeta = consumer.accumulators();
+
+    // Check each polynomial identity, of the form `vanishing(x) = Z_H(x)
+    // quotient(x)`, at zeta.
+    let mut scale = ReducingFactorTarget::new(zeta_pow_deg);
+    for (i, chunk) in quotient_polys
+        .chunks(stark.quotient_degree_factor())
+        .enumerate()
+    {
+        let recombined_quotient = scale.reduce(chunk, builder);
+        let computed_vanishing_poly = builder.mul_extension(z_h_zeta, recombined_quotient);
+        builder.connect_extension(vanishing_polys_zeta[i], computed_vanishing_poly);
+    }
+
+    let merkle_caps = vec![
+        proof.trace_cap.clone(),
+        proof.permutation_ctl_zs_cap.clone(),
+        proof.quotient_polys_cap.clone(),
+    ];
+
+    let fri_instance = stark.fri_instance_target(
+        builder,
+        challenges.stark_zeta,
+        F::primitive_root_of_unity(degree_bits),
+        degree_bits,
+        ctl_zs_last.len(),
+        inner_config,
+    );
+    with_context!(
+        builder,
+        "verify FRI proof",
+        builder.verify_fri_proof::<C>(
+            &fri_instance,
+            &proof.openings.to_fri_openings(zero),
+            &challenges.fri_challenges,
+            &merkle_caps,
+            &proof.opening_proof,
+            &inner_config.fri_params(degree_bits),
+        )
+    );
+}
+
+/// In-circuit counterpart of `eval_l_0_and_l_last`, reusing `z_x = x^n - 1`
+/// which the caller already needs for the quotient check.
+fn eval_l_0_and_l_last_circuit<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    log_n: usize,
+    x: ExtensionTarget<D>,
+    z_x: ExtensionTarget<D>,
+) -> (ExtensionTarget<D>, ExtensionTarget<D>) {
+    let n = builder.constant_extension(F::Extension::from_canonical_usize(1 << log_n));
+    let g = builder.constant_extension(F::Extension::primitive_root_of_unity(log_n));
+    let one = builder.one_extension();
+    let l_0_deno = builder.mul_sub_extension(n, x, n);
+    let l_last_deno = builder.mul_sub_extension(g, x, one);
+    let l_last_deno = builder.mul_extension(n, l_last_deno);
+
+    (
+        builder.div_extension(z_x, l_0_deno),
+        builder.div_extension(z_x, l_last_deno),
+    )
+}
+
+/// Adds the targets of an `AllProof` whose tables have the given
+/// `degree_bits` and number of CTL Z polynomials, for a batch of the shape of
+/// `public_values`.
+pub fn add_virtual_all_proof<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    ola_stark: &OlaStark<F, D>,
+    config: &StarkConfig,
+    degree_bits: &[usize; NUM_TABLES],
+    nums_ctl_zs: &[usize; NUM_TABLES],
+    public_values: &PublicValues,
+) -> AllProofTarget<D> {
+    let stark_proofs = [
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.cpu_stark,
+            config,
+            degree_bits[Table::Cpu as usize],
+            nums_ctl_zs[Table::Cpu as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.memory_stark,
+            config,
+            degree_bits[Table::Memory as usize],
+            nums_ctl_zs[Table::Memory as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.bitwise_stark,
+            config,
+            degree_bits[Table::Bitwise as usize],
+            nums_ctl_zs[Table::Bitwise as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.cmp_stark,
+            config,
+            degree_bits[Table::Cmp as usize],
+            nums_ctl_zs[Table::Cmp as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.rangecheck_stark,
+            config,
+            degree_bits[Table::RangeCheck as usize],
+            nums_ctl_zs[Table::RangeCheck as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.poseidon_stark,
+            config,
+            degree_bits[Table::Poseidon as usize],
+            nums_ctl_zs[Table::Poseidon as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.poseidon_chunk_stark,
+            config,
+            degree_bits[Table::PoseidonChunk as usize],
+            nums_ctl_zs[Table::PoseidonChunk as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.storage_access_stark,
+            config,
+            degree_bits[Table::StorageAccess as usize],
+            nums_ctl_zs[Table::StorageAccess as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.tape_stark,
+            config,
+            degree_bits[Table::Tape as usize],
+            nums_ctl_zs[Table::Tape as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.sccall_stark,
+            config,
+            degree_bits[Table::SCCall as usize],
+            nums_ctl_zs[Table::SCCall as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.program_stark,
+            config,
+            degree_bits[Table::Program as usize],
+            nums_ctl_zs[Table::Program as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.prog_chunk_stark,
+            config,
+            degree_bits[Table::ProgChunk as usize],
+            nums_ctl_zs[Table::ProgChunk as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.sigcheck_stark,
+            config,
+            degree_bits[Table::SigCheck as usize],
+            nums_ctl_zs[Table::SigCheck as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.event_stark,
+            config,
+            degree_bits[Table::Event as usize],
+            nums_ctl_zs[Table::Event as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.bitwise_fixed_stark,
+            config,
+            degree_bits[Table::BitwiseFixed as usize],
+            nums_ctl_zs[Table::BitwiseFixed as usize],
+        ),
+        add_virtual_stark_proof(
+            builder,
+            &ola_stark.rangecheck_fixed_stark,
+            config,
+            degree_bits[Table::RangeCheckFixed as usize],
+            nums_ctl_zs[Table::RangeCheckFixed as usize],
+        ),
+    ];
+
+    AllProofTarget {
+        stark_proofs,
+        public_values: add_virtual_public_values(builder, public_values),
+    }
+}
+
+/// Adds public value targets of the same shape as `public_values`: its number
+/// of transactions, and the transaction, number of topics and number of data
+/// items of every event.
+pub fn add_virtual_public_values<F: RichField + Extendable<D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    public_values: &PublicValues,
+) -> PublicValuesTarget {
+    PublicValuesTarget {
+        state_root_before: builder.add_virtual_target_arr(),
+        state_root_after: builder.add_virtual_target_arr(),
+        block_metadata: BlockMetadataTarget {
+            block_number: builder.add_virtual_target(),
+            block_timestamp: builder.add_virtual_target(),
+            sequencer_address: builder.add_virtual_target_arr(),
+            chain_id: builder.add_virtual_target(),
+        },
+        tx_hashes: (0..public_values.tx_hashes.len())
+            .map(|_| builder.add_virtual_target_arr())
+            .collect(),
+        events: public_values
+            .events
+            .iter()
+            .map(|event| EventLogTarget {
+                tx_idx: event.tx_idx,
+                topics: (0..event.topics.len())
+                    .map(|_| builder.add_virtual_target_arr())
+                    .collect(),
+                data: builder.add_virtual_targets(event.data.len()),
+            })
+            .collect(),
+        sig_checks: (0..public_values.sig_checks.len())
+            .map(|_| SigCheckTarget {
+                inputs: builder.add_virtual_target_arr(),
+                verified: builder.add_virtual_bool_target_safe(),
+            })
+            .collect(),
+    }
+}
+
+pub fn add_virtual_stark_proof<F: RichField + Extendable<D>, S: Stark<F, D>, const D: usize>(
+    builder: &mut CircuitBuilder<F, D>,
+    stark: &S,
+    config: &StarkConfig,
+    degree_bits: usize,
+    num_ctl_zs: usize,
+) -> StarkProofTarget<D> {
+    let fri_params = config.fri_params(degree_bits);
+    let cap_height = fri_params.config.cap_height;
+    let num_zs = stark.num_permutation_zs(config) + num_ctl_zs;
+
+    let num_leaves_per_oracle = vec![
+        S::COLUMNS,
+        num_zs,
+        stark.quotient_degree_factor() * config.num_challenges,
+    ];
+
+    StarkProofTarget {
+        trace_cap: builder.add_virtual_cap(cap_height),
+        permutation_ctl_zs_cap: builder.add_virtual_cap(cap_height),
+        quotient_polys_cap: builder.add_virtual_cap(cap_height),
+        openings: StarkOpeningSetTarget {
+            local_values: builder.add_virtual_extension_targets(S::COLUMNS),
+            next_values: builder.add_virtual_extension_targets(S::COLUMNS),
+            permutation_ctl_zs: builder.add_virtual_extension_targets(num_zs),
+            permutation_ctl_zs_next: builder.add_virtual_extension_targets(num_zs),
+            ctl_zs_last: builder.add_virtual_targets(num_ctl_zs),
+            quotient_polys: builder.add_virtual_extension_targets(
+                stark.quotient_degree_factor() * config.num_challenges,
+            ),
+        },
+        opening_proof: builder.add_virtual_fri_proof(&num_leaves_per_oracle, &fri_params),
+    }
+}
+
+pub fn set_all_proof_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
+    witness: &mut W,
+    all_proof_target: &AllProofTarget<D>,
+    all_proof: &AllProof<F, C, D>,
+) where
+    F: RichField + Extendable<D>,
+    C::Hasher: AlgebraicHasher<F>,
+    W: Witness<F>,
+{
+    for (pt, p) in all_proof_target
+        .stark_proofs
+        .iter()
+        .zip(&all_proof.stark_proofs)
+    {
+        set_stark_proof_target(witness, pt, p);
+    }
+    set_public_value_targets(
+        witness,
+        &all_proof_target.public_values,
+        &all_proof.public_values,
+    );
+}
+
+pub fn set_stark_proof_target<F, C: GenericConfig<D, F = F>, W, const D: usize>(
+    witness: &mut W,
+    proof_target: &StarkProofTarget<D>,
+    proof: &StarkProof<F, C, D>,
+) where
+    F: RichField + Extendable<D>,
+    C::Hasher: AlgebraicHasher<F>,
+    W: Witness<F>,
+{
+    witness.set_cap_target(&proof_target.trace_cap, &proof.trace_cap);
+    witness.set_cap_target(
+        &proof_target.permutation_ctl_zs_cap,
+        &proof.permutation_ctl_zs_cap,
+    );
+    witness.set_cap_target(&proof_target.quotient_polys_cap, &proof.quotient_polys_cap);
+
+    let openings_target = &proof_target.openings;
+    let openings = &proof.openings;
+    witness.set_extension_targets(&openings_target.local_values, &openings.local_values);
+    witness.set_extension_targets(&openings_target.next_values, &openings.next_values);
+    witness.set_extension_targets(
+        &openings_target.permutation_ctl_zs,
+        &openings.permutation_ctl_zs,
+    );
+    witness.set_extension_targets(
+        &openings_target.permutation_ctl_zs_next,
+        &openings.permutation_ctl_zs_next,
+    );
+    for (&t, &v) in openings_target
+        .ctl_zs_last
+        .iter()
+        .zip(&openings.ctl_zs_last)
+    {
+        witness.set_target(t, v);
+    }
+    witness.set_extension_targets(&openings_target.quotient_polys, &openings.quotient_polys);
+
+    set_fri_proof_target(witness, &proof_target.opening_proof, &proof.opening_proof);
+}
+
+pub fn set_public_value_targets<F, W, const D: usize>(
+    witness: &mut W,
+    public_values_target: &PublicValuesTarget,
+    public_values: &PublicValues,
+) where
+    F: RichField + Extendable<D>,
+    W: Witness<F>,
+{
+    let to_fields = |values: [u64; 4]| values.map(F::from_noncanonical_u64);
+    witness.set_target_arr(
+        public_values_target.state_root_before,
+        to_fields(public_values.state_root_before),
+    );
+    witness.set_target_arr(
+        public_values_target.state_root_after,
+        to_fields(public_values.state_root_after),
+    );
+    set_block_metadata_target(
+        witness,
+        &public_values_target.block_metadata,
+        &public_values.block_metadata,
+    );
+    assert_eq!(
+        public_values_target.tx_hashes.len(),
+        public_values.tx_hashes.len(),
+        "public values target built for a different number of transactions"
+    );
+    for (&t, &v) in public_values_target
+        .tx_hashes
+        .iter()
+        .zip(&public_values.tx_hashes)
+    {
+        witness.set_target_arr(t, to_fields(v));
+    }
+    assert_eq!(
+        public_values_target.events.len(),
+        public_values.events.len(),
+        "public values target built for a different number of events"
+    );
+    for (t, v) in public_values_target
+        .events
+        .iter()
+        .zip(&public_values.events)
+    {
+        assert!(
+            t.tx_idx == v.tx_idx
+                && t.topics.len() == v.topics.len()
+                && t.data.len() == v.data.len(),
+            "public values target built for a different event shape"
+        );
+        for (&topic_t, &topic) in t.topics.iter().zip(&v.topics) {
+            witness.set_target_arr(topic_t, to_fields(topic));
+        }
+        for (&data_t, &data) in t.data.iter().zip(&v.data) {
+            witness.set_target(data_t, F::from_noncanonical_u64(data));
+        }
+    }
+    assert_eq!(
+        public_values_target.sig_checks.len(),
+        public_values.sig_checks.len(),
+        "public values target built for a different number of signature checks"
+    );
+    for (t, v) in public_values_target
+        .sig_checks
+        .iter()
+        .zip(&public_values.sig_checks)
+    {
+        witness.set_target_arr(t.inputs, v.inputs().map(F::from_noncanonical_u64));
+        witness.set_bool_target(t.verified, v.verified);
+    }
+}
+
+fn set_block_metadata_target<F, W>(
+    witness: &mut W,
+    block_metadata_target: &BlockMetadataTarget,
+    block_metadata: &BlockMetadata,
+) where
+    F: RichField,
+    W: Witness<F>,
+{
+    witness.set_target(
+        block_metadata_target.block_number,
+        F::from_noncanonical_u64(block_metadata.block_number),
+    );
+    witness.set_target(
+        block_metadata_target.block_timestamp,
+        F::from_noncanonical_u64(block_metadata.block_timestamp),
+    );
+    witness.set_target_arr(
+        block_metadata_target.sequencer_address,
+        block_metadata
+            .sequencer_address
+            .map(F::from_noncanonical_u64),
+    );
+    witness.set_target(
+        block_metadata_target.chain_id,
+        F::from_noncanonical_u64(block_metadata.chain_id),
+    );
+}
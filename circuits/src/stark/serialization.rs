@@ -1,17 +1,35 @@
 use std::io::Cursor;
-use std::io::{Read, Result, Write};
+use std::io::{Error, ErrorKind, Read, Result, Write};
 
 use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::polynomial::PolynomialCoeffs;
 use plonky2::field::types::{Field64, PrimeField64};
 
 use plonky2::fri::proof::{FriInitialTreeProof, FriProof, FriQueryRound, FriQueryStep};
+use plonky2::fri::reduction_strategies::FriReductionStrategy;
+use plonky2::fri::FriConfig;
 use plonky2::hash::hash_types::RichField;
 use plonky2::hash::merkle_proofs::MerkleProof;
 use plonky2::hash::merkle_tree::MerkleCap;
 use plonky2::plonk::config::{GenericConfig, GenericHashOut, Hasher};
 
-use super::proof::{AllProof, PublicValues, StarkOpeningSet, StarkProof};
+use sha2::{Digest, Sha256};
+
+use super::config::StarkConfig;
+use super::ola_stark::NUM_TABLES;
+use super::proof::{
+    AllProof, BlockMetadata, EventLog, PublicValues, SigCheck, StarkOpeningSet, StarkProof,
+};
+
+/// First bytes of every serialized `AllProof`.
+pub const PROOF_MAGIC: [u8; 8] = *b"OLAPROOF";
+/// Bumped whenever the layout of a serialized `AllProof` changes.
+pub const PROOF_FORMAT_VERSION: u32 = 4;
+const CHECKSUM_LEN: usize = 32;
+
+fn invalid_data(msg: String) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
 
 #[derive(Debug)]
 pub struct Buffer(Cursor<Vec<u8>>);
@@ -47,6 +65,37 @@ impl Buffer {
         Ok(u32::from_le_bytes(buf))
     }
 
+    fn write_u64(&mut self, x: u64) -> Result<()> {
+        self.0.write_all(&x.to_le_bytes())
+    }
+    fn read_u64(&mut self) -> Result<u64> {
+        let mut buf = [0; std::mem::size_of::<u64>()];
+        self.0.read_exact(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn write_usize(&mut self, x: usize) -> Result<()> {
+        self.write_u64(x as u64)
+    }
+    fn read_usize(&mut self) -> Result<usize> {
+        let x = self.read_u64()?;
+        usize::try_from(x).map_err(|_| invalid_data(format!("{} does not fit in usize", x)))
+    }
+
+    fn write_u64_array(&mut self, v: &[u64; 4]) -> Result<()> {
+        for &x in v {
+            self.write_u64(x)?;
+        }
+        Ok(())
+    }
+    fn read_u64_array(&mut self) -> Result<[u64; 4]> {
+        let mut arr = [0; 4];
+        for x in arr.iter_mut() {
+            *x = self.read_u64()?;
+        }
+        Ok(arr)
+    }
+
     fn write_field<F: PrimeField64>(&mut self, x: F) -> Result<()> {
         self.0.write_all(&x.to_canonical_u64().to_le_bytes())
     }
@@ -374,6 +423,174 @@ impl Buffer {
         })
     }
 
+    pub fn write_public_values(&mut self, public_values: &PublicValues) -> Result<()> {
+        self.write_u64_array(&public_values.state_root_before)?;
+        self.write_u64_array(&public_values.state_root_after)?;
+        let block_metadata = &public_values.block_metadata;
+        self.write_u64(block_metadata.block_number)?;
+        self.write_u64(block_metadata.block_timestamp)?;
+        self.write_u64_array(&block_metadata.sequencer_address)?;
+        self.write_u64(block_metadata.chain_id)?;
+        self.write_u32(public_values.tx_hashes.len() as u32)?;
+        for tx_hash in &public_values.tx_hashes {
+            self.write_u64_array(tx_hash)?;
+        }
+        self.write_u32(public_values.events.len() as u32)?;
+        for event in &public_values.events {
+            self.write_u64(event.tx_idx)?;
+            self.write_u32(event.topics.len() as u32)?;
+            for topic in &event.topics {
+                self.write_u64_array(topic)?;
+            }
+            self.write_u32(event.data.len() as u32)?;
+            for &data in &event.data {
+                self.write_u64(data)?;
+            }
+        }
+        self.write_u32(public_values.sig_checks.len() as u32)?;
+        for sig_check in &public_values.sig_checks {
+            self.write_u64_array(&sig_check.msg_hash)?;
+            self.write_u64_array(&sig_check.pubkey_x)?;
+            self.write_u64_array(&sig_check.pubkey_y)?;
+            self.write_u64_array(&sig_check.r)?;
+            self.write_u64_array(&sig_check.s)?;
+            self.write_u8(sig_check.verified as u8)?;
+        }
+        Ok(())
+    }
+    pub fn read_public_values(&mut self) -> Result<PublicValues> {
+        let state_root_before = self.read_u64_array()?;
+        let state_root_after = self.read_u64_array()?;
+        let block_metadata = BlockMetadata {
+            block_number: self.read_u64()?,
+            block_timestamp: self.read_u64()?,
+            sequencer_address: self.read_u64_array()?,
+            chain_id: self.read_u64()?,
+        };
+        let num_txs = self.read_u32()? as usize;
+        let tx_hashes = (0..num_txs)
+            .map(|_| self.read_u64_array())
+            .collect::<Result<Vec<_>>>()?;
+        let num_events = self.read_u32()? as usize;
+        let events = (0..num_events)
+            .map(|_| {
+                let tx_idx = self.read_u64()?;
+                let num_topics = self.read_u32()? as usize;
+                let topics = (0..num_topics)
+                    .map(|_| self.read_u64_array())
+                    .collect::<Result<Vec<_>>>()?;
+                let num_data = self.read_u32()? as usize;
+                let data = (0..num_data)
+                    .map(|_| self.read_u64())
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(EventLog {
+                    tx_idx,
+                    topics,
+                    data,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let num_sig_checks = self.read_u32()? as usize;
+        let sig_checks = (0..num_sig_checks)
+            .map(|_| {
+                Ok(SigCheck {
+                    msg_hash: self.read_u64_array()?,
+                    pubkey_x: self.read_u64_array()?,
+                    pubkey_y: self.read_u64_array()?,
+                    r: self.read_u64_array()?,
+                    s: self.read_u64_array()?,
+                    verified: match self.read_u8()? {
+                        0 => false,
+                        1 => true,
+                        v => return Err(invalid_data(format!("invalid bool {}", v))),
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PublicValues {
+            state_root_before,
+            state_root_after,
+            block_metadata,
+            tx_hashes,
+            events,
+            sig_checks,
+        })
+    }
+
+    pub fn write_stark_config(&mut self, config: &StarkConfig) -> Result<()> {
+        self.write_usize(config.security_bits)?;
+        self.write_usize(config.num_challenges)?;
+        let fri_config = &config.fri_config;
+        self.write_usize(fri_config.rate_bits)?;
+        self.write_usize(fri_config.cap_height)?;
+        self.write_u32(fri_config.proof_of_work_bits)?;
+        self.write_usize(fri_config.num_query_rounds)?;
+        match &fri_config.reduction_strategy {
+            FriReductionStrategy::Fixed(arities) => {
+                self.write_u8(0)?;
+                self.write_u32(arities.len() as u32)?;
+                for &arity_bits in arities {
+                    self.write_usize(arity_bits)?;
+                }
+            }
+            &FriReductionStrategy::ConstantArityBits(arity_bits, final_poly_bits) => {
+                self.write_u8(1)?;
+                self.write_usize(arity_bits)?;
+                self.write_usize(final_poly_bits)?;
+            }
+            &FriReductionStrategy::MinSize(max_arity_bits) => {
+                self.write_u8(2)?;
+                self.write_u8(max_arity_bits.is_some() as u8)?;
+                self.write_usize(max_arity_bits.unwrap_or_default())?;
+            }
+        }
+        Ok(())
+    }
+    pub fn read_stark_config(&mut self) -> Result<StarkConfig> {
+        let security_bits = self.read_usize()?;
+        let num_challenges = self.read_usize()?;
+        let rate_bits = self.read_usize()?;
+        let cap_height = self.read_usize()?;
+        let proof_of_work_bits = self.read_u32()?;
+        let num_query_rounds = self.read_usize()?;
+        let reduction_strategy = match self.read_u8()? {
+            0 => {
+                let len = self.read_u32()?;
+                FriReductionStrategy::Fixed(
+                    (0..len as usize)
+                        .map(|_| self.read_usize())
+                        .collect::<Result<Vec<_>>>()?,
+                )
+            }
+            1 => FriReductionStrategy::ConstantArityBits(self.read_usize()?, self.read_usize()?),
+            2 => {
+                let is_some = self.read_u8()? != 0;
+                let max_arity_bits = self.read_usize()?;
+                FriReductionStrategy::MinSize(is_some.then_some(max_arity_bits))
+            }
+            tag => {
+                return Err(invalid_data(format!(
+                    "unknown FRI reduction strategy {}",
+                    tag
+                )))
+            }
+        };
+        Ok(StarkConfig {
+            security_bits,
+            num_challenges,
+            fri_config: FriConfig {
+                rate_bits,
+                cap_height,
+                proof_of_work_bits,
+                reduction_strategy,
+                num_query_rounds,
+            },
+        })
+    }
+
+    /// Writes a self-describing proof: magic, format version, the config the
+    /// proof was generated with, the degree bits of every table, the proofs,
+    /// the public values and finally a SHA-256 checksum of all of the above.
     pub fn write_all_proof<
         F: RichField + Extendable<D>,
         C: GenericConfig<D, F = F>,
@@ -381,33 +598,98 @@ impl Buffer {
     >(
         &mut self,
         proof: &AllProof<F, C, D>,
+        config: &StarkConfig,
     ) -> Result<()> {
-        self.write_u32(proof.stark_proofs.len() as u32)?;
+        let start = self.0.position() as usize;
+        self.0.write_all(&PROOF_MAGIC)?;
+        self.write_u32(PROOF_FORMAT_VERSION)?;
+        self.write_stark_config(config)?;
+        self.write_u32(NUM_TABLES as u32)?;
+        for degree_bits in proof.degree_bits(config) {
+            self.write_u8(degree_bits as u8)?;
+        }
         for p in &proof.stark_proofs {
             self.write_proof(p)?;
         }
-
-        self.write_field_vec(&proof.compress_challenges)?;
-        // PublicValues
-        Ok(())
+        self.write_public_values(&proof.public_values)?;
+        let checksum = self.checksum_from(start);
+        self.0.write_all(&checksum)
     }
+    /// Reads a proof written by `write_all_proof`, returning it with the config
+    /// it was generated with. Callers must check that config is one they accept
+    /// before verifying the proof.
     pub fn read_all_proof<
         F: RichField + Extendable<D>,
         C: GenericConfig<D, F = F>,
         const D: usize,
     >(
         &mut self,
-    ) -> Result<AllProof<F, C, D>> {
+    ) -> Result<(AllProof<F, C, D>, StarkConfig)> {
+        let start = self.0.position() as usize;
+        let mut magic = [0; PROOF_MAGIC.len()];
+        self.0.read_exact(&mut magic)?;
+        if magic != PROOF_MAGIC {
+            return Err(invalid_data("not an Ola proof".to_string()));
+        }
+        let version = self.read_u32()?;
+        if version != PROOF_FORMAT_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported proof format version {}, expected {}",
+                version, PROOF_FORMAT_VERSION
+            )));
+        }
+        let config = self.read_stark_config()?;
+        let num_tables = self.read_u32()? as usize;
+        if num_tables != NUM_TABLES {
+            return Err(invalid_data(format!(
+                "proof has {} tables, expected {}",
+                num_tables, NUM_TABLES
+            )));
+        }
+        let mut degree_bits = [0; NUM_TABLES];
+        for bits in degree_bits.iter_mut() {
+            *bits = self.read_u8()? as usize;
+        }
         let mut stark_proofs = vec![];
-        let len = self.read_u32()? as usize;
-        for _ in 0..len {
+        for _ in 0..NUM_TABLES {
             stark_proofs.push(self.read_proof()?);
         }
-        let compress_challenges = self.read_field_vec()?;
-        Ok(AllProof {
+        let public_values = self.read_public_values()?;
+
+        let checksum = self.checksum_from(start);
+        let mut expected = [0; CHECKSUM_LEN];
+        self.0.read_exact(&mut expected)?;
+        if checksum != expected {
+            return Err(invalid_data("proof checksum mismatch".to_string()));
+        }
+
+        for (i, p) in stark_proofs.iter().enumerate() {
+            // Same as `StarkProof::recover_degree_bits`, without panicking on
+            // malformed proofs.
+            let lde_bits = p
+                .opening_proof
+                .query_round_proofs
+                .first()
+                .and_then(|r| r.initial_trees_proof.evals_proofs.first())
+                .map(|(_, merkle_proof)| {
+                    config.fri_config.cap_height + merkle_proof.siblings.len()
+                });
+            if lde_bits != Some(degree_bits[i] + config.fri_config.rate_bits) {
+                return Err(invalid_data(format!(
+                    "proof of table {} does not match its degree bits {}",
+                    i, degree_bits[i]
+                )));
+            }
+        }
+        let proof = AllProof {
             stark_proofs: stark_proofs.try_into().unwrap(),
-            compress_challenges: compress_challenges.try_into().unwrap(),
-            public_values: PublicValues::default(),
-        })
+            public_values,
+        };
+        Ok((proof, config))
+    }
+
+    fn checksum_from(&self, start: usize) -> [u8; CHECKSUM_LEN] {
+        let end = self.0.position() as usize;
+        Sha256::digest(&self.0.get_ref()[start..end]).into()
     }
 }
@@ -12,6 +12,7 @@ use plonky2_util::ceil_div_usize;
 
 use super::config::StarkConfig;
 use super::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use super::lookup::Lookup;
 use super::permutation::PermutationPair;
 use super::vars::StarkEvaluationTargets;
 use super::vars::StarkEvaluationVars;
@@ -98,8 +99,8 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         };
         let trace_info = FriPolynomialInfo::from_range(TRACE_ORACLE_INDEX, 0..Self::COLUMNS);
 
-        let num_permutation_batches = self.num_permutation_batches(config);
-        let num_perutation_ctl_polys = num_permutation_batches + num_ctl_zs;
+        let num_permutation_zs = self.num_permutation_zs(config);
+        let num_perutation_ctl_polys = num_permutation_zs + num_ctl_zs;
         let permutation_ctl_oracle = FriOracleInfo {
             num_polys: num_perutation_ctl_polys,
             blinding: false,
@@ -111,7 +112,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
 
         let ctl_zs_info = FriPolynomialInfo::from_range(
             PERMUTATION_CTL_ORACLE_INDEX,
-            num_permutation_batches..num_permutation_batches + num_ctl_zs,
+            num_permutation_zs..num_permutation_zs + num_ctl_zs,
         );
 
         let num_quotient_polys = self.num_quotient_polys(config);
@@ -161,8 +162,8 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
         };
         let trace_info = FriPolynomialInfo::from_range(TRACE_ORACLE_INDEX, 0..Self::COLUMNS);
 
-        let num_permutation_batches = self.num_permutation_batches(inner_config);
-        let num_perutation_ctl_polys = num_permutation_batches + num_ctl_zs;
+        let num_permutation_zs = self.num_permutation_zs(inner_config);
+        let num_perutation_ctl_polys = num_permutation_zs + num_ctl_zs;
         let permutation_ctl_oracle = FriOracleInfo {
             num_polys: num_perutation_ctl_polys,
             blinding: false,
@@ -174,7 +175,7 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
 
         let ctl_zs_info = FriPolynomialInfo::from_range(
             PERMUTATION_CTL_ORACLE_INDEX,
-            num_permutation_batches..num_permutation_batches + num_ctl_zs,
+            num_permutation_zs..num_permutation_zs + num_ctl_zs,
         );
 
         let num_quotient_polys = self.num_quotient_polys(inner_config);
@@ -242,4 +243,35 @@ pub trait Stark<F: RichField + Extendable<D>, const D: usize>: Sync {
             self.permutation_batch_size(),
         )
     }
+
+    /// Lookups checked with a logarithmic derivative argument. Empty by
+    /// default.
+    fn lookups(&self) -> Vec<Lookup> {
+        vec![]
+    }
+
+    fn uses_lookups(&self) -> bool {
+        !self.lookups().is_empty()
+    }
+
+    /// The number of lookup helper columns, committed after the permutation
+    /// Z polynomials. The helper sums are batched like permutation instances.
+    fn num_lookup_helper_columns(&self, config: &StarkConfig) -> usize {
+        self.lookups()
+            .iter()
+            .map(|lookup| lookup.num_helper_columns(self.permutation_batch_size()))
+            .sum::<usize>()
+            * config.num_challenges
+    }
+
+    /// Permutation arguments and lookups both draw the permutation challenges.
+    fn uses_permutation_zs(&self) -> bool {
+        self.uses_permutation_args() || self.uses_lookups()
+    }
+
+    /// The number of polynomials committed before the CTL Zs: the permutation
+    /// Z batches followed by the lookup helper columns.
+    fn num_permutation_zs(&self, config: &StarkConfig) -> usize {
+        self.num_permutation_batches(config) + self.num_lookup_helper_columns(config)
+    }
 }
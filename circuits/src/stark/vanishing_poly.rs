@@ -10,6 +10,7 @@ use super::cross_table_lookup::{
     eval_cross_table_lookup_checks, eval_cross_table_lookup_checks_circuit, CtlCheckVars,
     CtlCheckVarsTarget,
 };
+use super::lookup::{eval_lookup_checks, eval_lookup_checks_circuit};
 use super::permutation::{
     eval_permutation_checks, eval_permutation_checks_circuit, PermutationCheckDataTarget,
     PermutationCheckVars,
@@ -33,6 +34,7 @@ pub(crate) fn eval_vanishing_poly<F, FE, P, C, S, const D: usize, const D2: usiz
 {
     stark.eval_packed_generic(vars, consumer);
     if let Some(permutation_vars) = permutation_vars {
+        eval_lookup_checks::<F, FE, P, S, D, D2>(stark, config, vars, &permutation_vars, consumer);
         eval_permutation_checks::<F, FE, P, C, S, D, D2>(
             stark,
             config,
@@ -61,6 +63,14 @@ pub(crate) fn eval_vanishing_poly_circuit<F, C, S, const D: usize>(
 {
     stark.eval_ext_circuit(builder, vars, consumer);
     if let Some(permutation_data) = permutation_data {
+        eval_lookup_checks_circuit::<F, S, D>(
+            builder,
+            stark,
+            config,
+            vars,
+            &permutation_data,
+            consumer,
+        );
         eval_permutation_checks_circuit::<F, S, D>(
             builder,
             stark,
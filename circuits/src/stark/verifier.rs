@@ -1,6 +1,7 @@
 use std::any::type_name;
 
 use anyhow::{ensure, Result};
+use executor::ecdsa::msg_ecdsa_verify;
 use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::types::Field;
 use plonky2::fri::verifier::verify_fri_proof;
@@ -14,20 +15,25 @@ use super::cross_table_lookup::{verify_cross_table_lookups, CtlCheckVars};
 use super::ola_stark::{OlaStark, Table, NUM_TABLES};
 use super::permutation::{GrandProductChallenge, PermutationCheckVars};
 use super::proof::{
-    AllProof, AllProofChallenges, PublicValues, StarkOpeningSet, StarkProof, StarkProofChallenges,
+    AllProof, AllProofChallenges, PublicValues, SigCheck, StarkOpeningSet, StarkProof,
+    StarkProofChallenges,
 };
 use super::stark::Stark;
 use super::vanishing_poly::eval_vanishing_poly;
 use super::vars::StarkEvaluationVars;
 use crate::builtins::bitwise::bitwise_stark::BitwiseStark;
 use crate::builtins::cmp::cmp_stark::CmpStark;
+use crate::builtins::event::event_stark::EventStark;
 use crate::builtins::poseidon::poseidon_chunk_stark::PoseidonChunkStark;
 use crate::builtins::poseidon::poseidon_stark::PoseidonStark;
 use crate::builtins::rangecheck::rangecheck_stark::RangeCheckStark;
 use crate::builtins::sccall::sccall_stark::SCCallStark;
+use crate::builtins::sigcheck::sigcheck_stark::SigCheckStark;
 use crate::builtins::storage::storage_access_stark::StorageAccessStark;
-// use crate::builtins::tape::tape_stark::TapeStark;
+use crate::builtins::tape::tape_stark::TapeStark;
 use crate::cpu::cpu_stark::CpuStark;
+use crate::fixed_table::bitwise_fixed::bitwise_fixed_stark::BitwiseFixedStark;
+use crate::fixed_table::rangecheck_fixed::rangecheck_fixed_stark::RangeCheckFixedStark;
 use crate::memory::memory_stark::MemoryStark;
 use crate::program::prog_chunk_stark::ProgChunkStark;
 use crate::program::program_stark::ProgramStark;
@@ -47,10 +53,14 @@ where
     [(); PoseidonStark::<F, D>::COLUMNS]:,
     [(); PoseidonChunkStark::<F, D>::COLUMNS]:,
     [(); StorageAccessStark::<F, D>::COLUMNS]:,
-    // [(); TapeStark::<F, D>::COLUMNS]:,
+    [(); TapeStark::<F, D>::COLUMNS]:,
     [(); SCCallStark::<F, D>::COLUMNS]:,
     [(); ProgramStark::<F, D>::COLUMNS]:,
     [(); ProgChunkStark::<F, D>::COLUMNS]:,
+    [(); SigCheckStark::<F, D>::COLUMNS]:,
+    [(); EventStark::<F, D>::COLUMNS]:,
+    [(); BitwiseFixedStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckFixedStark::<F, D>::COLUMNS]:,
 {
     let AllProofChallenges {
         stark_challenges,
@@ -62,29 +72,48 @@ where
     let OlaStark {
         cpu_stark,
         memory_stark,
-        mut bitwise_stark,
+        bitwise_stark,
         cmp_stark,
         rangecheck_stark,
         poseidon_stark,
         poseidon_chunk_stark,
-        storage_access_stark,
+        mut storage_access_stark,
         tape_stark,
         sccall_stark,
-        mut program_stark,
+        program_stark,
         prog_chunk_stark,
+        sigcheck_stark,
+        event_stark,
+        bitwise_fixed_stark,
+        rangecheck_fixed_stark,
         cross_table_lookups,
     } = ola_stark;
 
-    if bitwise_stark.get_compress_challenge().is_none() {
-        bitwise_stark
-            .set_compress_challenge(all_proof.compress_challenges[Table::Bitwise as usize])
-            .unwrap();
-    }
-    if program_stark.get_compress_challenge().is_none() {
-        program_stark
-            .set_compress_challenge(all_proof.compress_challenges[Table::Program as usize])
+    let public_values = &all_proof.public_values;
+    if storage_access_stark.get_state_roots().is_none() {
+        storage_access_stark
+            .set_state_roots(
+                public_values.state_root_before,
+                public_values.state_root_after,
+            )
             .unwrap();
     }
+    ensure!(
+        storage_access_stark.get_state_roots()
+            == Some((
+                public_values.state_root_before,
+                public_values.state_root_after
+            )),
+        "State roots do not match the public values."
+    );
+    verify_sig_checks(&public_values.sig_checks)?;
+    ensure!(
+        public_values
+            .events
+            .windows(2)
+            .all(|pair| pair[0].tx_idx <= pair[1].tx_idx),
+        "Events are not sorted by transaction."
+    );
 
     let ctl_vars_per_table = CtlCheckVars::from_proofs(
         &all_proof.stark_proofs,
@@ -187,15 +216,58 @@ where
         config,
     )?;
 
-    // TODO:
-    // let public_values = all_proof.public_values;
-    let extra_looking_products = vec![vec![F::ONE; config.num_challenges]; NUM_TABLES];
-    // extra_looking_products.push(Vec::new());
-    // for c in 0..config.num_challenges {
-    //     extra_looking_products[Table::StorageAccess as usize].push(
-    //         get_storagehash_extra_looking_products(&public_values,
-    // ctl_challenges.challenges[c]),     );
-    // }
+    verify_stark_proof_with_challenges(
+        sigcheck_stark,
+        &all_proof.stark_proofs[Table::SigCheck as usize],
+        &stark_challenges[Table::SigCheck as usize],
+        &ctl_vars_per_table[Table::SigCheck as usize],
+        config,
+    )?;
+
+    verify_stark_proof_with_challenges(
+        event_stark,
+        &all_proof.stark_proofs[Table::Event as usize],
+        &stark_challenges[Table::Event as usize],
+        &ctl_vars_per_table[Table::Event as usize],
+        config,
+    )?;
+
+    verify_stark_proof_with_challenges(
+        bitwise_fixed_stark,
+        &all_proof.stark_proofs[Table::BitwiseFixed as usize],
+        &stark_challenges[Table::BitwiseFixed as usize],
+        &ctl_vars_per_table[Table::BitwiseFixed as usize],
+        config,
+    )?;
+
+    verify_stark_proof_with_challenges(
+        rangecheck_fixed_stark,
+        &all_proof.stark_proofs[Table::RangeCheckFixed as usize],
+        &stark_challenges[Table::RangeCheckFixed as usize],
+        &ctl_vars_per_table[Table::RangeCheckFixed as usize],
+        config,
+    )?;
+
+    let extra_looking_products = cross_table_lookups
+        .iter()
+        .map(|ctl| {
+            ctl_challenges
+                .challenges
+                .iter()
+                .map(|&challenge| match ctl.public_looked_table() {
+                    Some(Table::Tape) => get_tape_extra_looking_products(public_values, challenge),
+                    Some(Table::SigCheck) => {
+                        get_sig_check_extra_looking_products(public_values, challenge)
+                    }
+                    Some(Table::Event) => {
+                        get_event_extra_looking_products(public_values, challenge)
+                    }
+                    Some(table) => panic!("no public values looked up in {:?}", table),
+                    None => F::ONE,
+                })
+                .collect()
+        })
+        .collect();
 
     verify_cross_table_lookups::<F, C, D>(
         cross_table_lookups,
@@ -205,16 +277,118 @@ where
     )
 }
 
-#[allow(dead_code)]
-pub(crate) fn get_storagehash_extra_looking_products<F, const D: usize>(
-    _public_values: &PublicValues,
-    _challenge: GrandProductChallenge<F>,
-) -> F
-where
+/// Verifies the segment proofs of a batch, in order, and that they form one
+/// batch: every segment belongs to the same block and starts from the state
+/// root the previous one ended at.
+pub fn verify_batch_proof<
     F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    segment_proofs: Vec<AllProof<F, C, D>>,
+    config: &StarkConfig,
+) -> Result<()>
+where
+    [(); C::Hasher::HASH_SIZE]:,
+    [(); CpuStark::<F, D>::COLUMNS]:,
+    [(); MemoryStark::<F, D>::COLUMNS]:,
+    [(); BitwiseStark::<F, D>::COLUMNS]:,
+    [(); CmpStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckStark::<F, D>::COLUMNS]:,
+    [(); PoseidonStark::<F, D>::COLUMNS]:,
+    [(); PoseidonChunkStark::<F, D>::COLUMNS]:,
+    [(); StorageAccessStark::<F, D>::COLUMNS]:,
+    [(); TapeStark::<F, D>::COLUMNS]:,
+    [(); SCCallStark::<F, D>::COLUMNS]:,
+    [(); ProgramStark::<F, D>::COLUMNS]:,
+    [(); ProgChunkStark::<F, D>::COLUMNS]:,
+    [(); SigCheckStark::<F, D>::COLUMNS]:,
+    [(); EventStark::<F, D>::COLUMNS]:,
+    [(); BitwiseFixedStark::<F, D>::COLUMNS]:,
+    [(); RangeCheckFixedStark::<F, D>::COLUMNS]:,
 {
-    let prod = F::ONE;
-    prod
+    ensure!(
+        !segment_proofs.is_empty(),
+        "A batch has at least one segment."
+    );
+    for (i, pair) in segment_proofs.windows(2).enumerate() {
+        let (prev, next) = (&pair[0].public_values, &pair[1].public_values);
+        ensure!(
+            prev.state_root_after == next.state_root_before,
+            "Segment {} does not start from the state root segment {} ended at.",
+            i + 1,
+            i
+        );
+        ensure!(
+            prev.block_metadata == next.block_metadata,
+            "Segments {} and {} belong to different blocks.",
+            i,
+            i + 1
+        );
+    }
+    for all_proof in segment_proofs {
+        verify_proof(OlaStark::default(), all_proof, config)?;
+    }
+    Ok(())
+}
+
+/// Product of the combined public tape cells, which are looked up in the tape
+/// table in place of a looking table.
+pub(crate) fn get_tape_extra_looking_products<F: Field>(
+    public_values: &PublicValues,
+    challenge: GrandProductChallenge<F>,
+) -> F {
+    public_values
+        .tape_cells()
+        .iter()
+        .map(|cell| challenge.combine(&cell.map(F::from_canonical_u64)))
+        .product()
+}
+
+/// Product of the combined public signature checks, looked up in the sigcheck
+/// table.
+pub(crate) fn get_sig_check_extra_looking_products<F: Field>(
+    public_values: &PublicValues,
+    challenge: GrandProductChallenge<F>,
+) -> F {
+    public_values
+        .sig_check_cells()
+        .iter()
+        .map(|cell| challenge.combine(&cell.map(F::from_canonical_u64)))
+        .product()
+}
+
+/// Product of the combined public event rows, looked up in the event table.
+pub(crate) fn get_event_extra_looking_products<F: Field>(
+    public_values: &PublicValues,
+    challenge: GrandProductChallenge<F>,
+) -> F {
+    public_values
+        .event_cells()
+        .iter()
+        .map(|cell| challenge.combine(&cell.map(F::from_canonical_u64)))
+        .product()
+}
+
+/// The circuits only bind the signature checks to the CPU, the signatures are
+/// checked here. A proof whose signature checks were not verified this way
+/// proves nothing about the signatures.
+pub fn verify_sig_checks(sig_checks: &[SigCheck]) -> Result<()> {
+    for (i, sig_check) in sig_checks.iter().enumerate() {
+        let verified = msg_ecdsa_verify(
+            sig_check.msg_hash,
+            sig_check.pubkey_x,
+            sig_check.pubkey_y,
+            sig_check.r,
+            sig_check.s,
+        )?;
+        ensure!(
+            verified == sig_check.verified,
+            "Signature check {} does not match its result.",
+            i
+        );
+    }
+    Ok(())
 }
 
 pub(crate) fn verify_stark_proof_with_challenges<
@@ -261,8 +435,8 @@ where
         l_0,
         l_last,
     );
-    let num_permutation_zs = stark.num_permutation_batches(config);
-    let permutation_data = stark.uses_permutation_args().then(|| PermutationCheckVars {
+    let num_permutation_zs = stark.num_permutation_zs(config);
+    let permutation_data = stark.uses_permutation_zs().then(|| PermutationCheckVars {
         local_zs: permutation_ctl_zs[..num_permutation_zs].to_vec(),
         next_zs: permutation_ctl_zs_next[..num_permutation_zs].to_vec(),
         permutation_challenge_sets: challenges.permutation_challenge_sets.clone().unwrap(),
@@ -358,7 +532,7 @@ where
     let degree_bits = proof.recover_degree_bits(config);
     let fri_params = config.fri_params(degree_bits);
     let cap_height = fri_params.config.cap_height;
-    let num_zs = num_ctl_zs + stark.num_permutation_batches(config);
+    let num_zs = num_ctl_zs + stark.num_permutation_zs(config);
 
     ensure!(trace_cap.height() == cap_height);
     ensure!(permutation_ctl_zs_cap.height() == cap_height);
@@ -2,22 +2,52 @@ use core::crypto::hash::Hasher;
 use core::crypto::ZkHasher;
 use core::merkle_tree::log::{StorageLog, StorageLogKind, WitnessStorageLog};
 use core::state::state_storage::StateStorage;
-use core::types::merkle_tree::{encode_addr, tree_key_default};
+use core::trace::exe_trace::TxExeTrace;
+use core::types::merkle_tree::{encode_addr, tree_key_default, TreeValue};
 use core::{program::Program, trace::trace::Trace, types::account::Address};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use tempfile::TempDir;
 
 use assembler::encoder::encode_asm_from_json_file;
 use executor::trace::{gen_storage_hash_table, gen_storage_table};
 use executor::BatchCacheManager;
 use executor::{load_tx::init_tape, Process};
+use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::{goldilocks_field::GoldilocksField, types::Field};
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::witness::{PartialWitness, Witness};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::CircuitConfig;
+use plonky2::plonk::config::{GenericConfig, Hasher as _, PoseidonGoldilocksConfig};
+use plonky2::util::timing::TimingTree;
 use plonky2_util::log2_strict;
 
+use crate::generation::{generate_batch_traces, generate_traces, GenerationInputs};
+use crate::stark::config::StarkConfig;
+use crate::stark::constraint_consumer::RecursiveConstraintConsumer;
+use crate::stark::ola_stark::OlaStark;
+use crate::stark::proof::AllProof;
+use crate::stark::prover::prove_with_traces;
+use crate::stark::stark::Stark;
+use crate::stark::vars::StarkEvaluationTargets;
+use crate::stark::verifier::verify_proof;
 use crate::stark::{constraint_consumer::ConstraintConsumer, vars::StarkEvaluationVars};
 use core::merkle_tree::tree::AccountTree;
 use core::vm::transaction::init_tx_context_mock;
 
+/// The account tree a test runs against. A named tree lives in a temporary
+/// directory, which is returned so that it outlives the tree.
+pub fn test_account_tree(db_name: Option<String>) -> (AccountTree, Option<TempDir>) {
+    match db_name {
+        Some(name) => {
+            let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+            let tree = AccountTree::new_db_test(dir.path().join(name).display().to_string());
+            (tree, Some(dir))
+        }
+        None => (AccountTree::new_test(), None),
+    }
+}
+
 pub fn test_stark_with_asm_path<Row, const COL_NUM: usize, E, H>(
     path: String,
     get_trace_rows: fn(Trace) -> Vec<Row>,
@@ -33,15 +63,7 @@ pub fn test_stark_with_asm_path<Row, const COL_NUM: usize, E, H>(
     ) -> (),
     H: Fn(usize, StarkEvaluationVars<GoldilocksField, GoldilocksField, COL_NUM>) -> (),
 {
-    let mut db = match db_name {
-        Some(name) => {
-            let mut db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-            db_path.push("../executor/db_test/");
-            db_path.push(name);
-            AccountTree::new_db_test(db_path.display().to_string())
-        }
-        _ => AccountTree::new_test(),
-    };
+    let (mut db, _db_dir) = test_account_tree(db_name);
 
     let program = encode_asm_from_json_file(path).unwrap();
     let hash = ZkHasher::default();
@@ -210,20 +232,13 @@ pub fn simple_test_stark<const COL_NUM: usize, E, H>(
     db_name: Option<String>,
 ) where
     E: Fn(
+        (TreeValue, TreeValue),
         StarkEvaluationVars<GoldilocksField, GoldilocksField, COL_NUM>,
         &mut ConstraintConsumer<GoldilocksField>,
     ) -> (),
     H: Fn(usize, StarkEvaluationVars<GoldilocksField, GoldilocksField, COL_NUM>) -> (),
 {
-    let mut db = match db_name {
-        Some(name) => {
-            let mut db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-            db_path.push("../executor/db_test/");
-            db_path.push(name);
-            AccountTree::new_db_test(db_path.display().to_string())
-        }
-        _ => AccountTree::new_test(),
-    };
+    let (mut db, _db_dir) = test_account_tree(db_name);
 
     let program = encode_asm_from_json_file(path).unwrap();
     let hash = ZkHasher::default();
@@ -329,6 +344,7 @@ pub fn simple_test_stark<const COL_NUM: usize, E, H>(
     gen_storage_table(&mut process, &mut program, hash_roots).unwrap();
     program.trace.start_end_roots = (start, db.root_hash());
 
+    let start_end_roots = program.trace.start_end_roots;
     let rows = generate_trace(program.trace);
     let len = rows[0].len();
 
@@ -370,7 +386,7 @@ pub fn simple_test_stark<const COL_NUM: usize, E, H>(
                 GoldilocksField::ZERO
             },
         );
-        eval_packed_generic(vars, &mut constraint_consumer);
+        eval_packed_generic(start_end_roots, vars, &mut constraint_consumer);
 
         for &acc in &constraint_consumer.constraint_accs {
             if !acc.eq(&GoldilocksField::ZERO) {
@@ -383,3 +399,217 @@ pub fn simple_test_stark<const COL_NUM: usize, E, H>(
         }
     }
 }
+
+/// Checks that `eval_ext_circuit` of `stark` computes the same constraint
+/// combination as `eval_packed_generic` on random local and next rows.
+pub fn test_stark_circuit_constraints<F, C, S, const D: usize>(stark: S) -> anyhow::Result<()>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    S: Stark<F, D>,
+    [(); S::COLUMNS]:,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    let local_values: [F::Extension; S::COLUMNS] =
+        F::Extension::rand_vec(S::COLUMNS).try_into().unwrap();
+    let next_values: [F::Extension; S::COLUMNS] =
+        F::Extension::rand_vec(S::COLUMNS).try_into().unwrap();
+    let alphas = F::rand_vec(1);
+    let z_last = F::Extension::rand();
+    let lagrange_first = F::Extension::rand();
+    let lagrange_last = F::Extension::rand();
+
+    let mut consumer = ConstraintConsumer::<F::Extension>::new(
+        alphas
+            .iter()
+            .copied()
+            .map(F::Extension::from_basefield)
+            .collect(),
+        z_last,
+        lagrange_first,
+        lagrange_last,
+    );
+    stark.eval_ext(
+        StarkEvaluationVars {
+            local_values: &local_values,
+            next_values: &next_values,
+        },
+        &mut consumer,
+    );
+    let native_eval = consumer.accumulators()[0];
+
+    let mut builder = CircuitBuilder::<F, D>::new(CircuitConfig::standard_recursion_config());
+    let mut pw = PartialWitness::<F>::new();
+    let local_targets = builder.add_virtual_extension_targets(S::COLUMNS);
+    let next_targets = builder.add_virtual_extension_targets(S::COLUMNS);
+    for (&t, &v) in local_targets.iter().zip(&local_values) {
+        pw.set_extension_target(t, v);
+    }
+    for (&t, &v) in next_targets.iter().zip(&next_values) {
+        pw.set_extension_target(t, v);
+    }
+    let alphas_target = builder.add_virtual_targets(1);
+    pw.set_target(alphas_target[0], alphas[0]);
+    let z_last_target = builder.add_virtual_extension_target();
+    pw.set_extension_target(z_last_target, z_last);
+    let lagrange_first_target = builder.add_virtual_extension_target();
+    pw.set_extension_target(lagrange_first_target, lagrange_first);
+    let lagrange_last_target = builder.add_virtual_extension_target();
+    pw.set_extension_target(lagrange_last_target, lagrange_last);
+
+    let mut consumer = RecursiveConstraintConsumer::<F, D>::new(
+        builder.zero_extension(),
+        alphas_target,
+        z_last_target,
+        lagrange_first_target,
+        lagrange_last_target,
+    );
+    stark.eval_ext_circuit(
+        &mut builder,
+        StarkEvaluationTargets {
+            local_values: &local_targets.try_into().unwrap(),
+            next_values: &next_targets.try_into().unwrap(),
+        },
+        &mut consumer,
+    );
+    let circuit_eval = consumer.accumulators()[0];
+    let native_eval_target = builder.constant_extension(native_eval);
+    builder.connect_extension(circuit_eval, native_eval_target);
+
+    let data = builder.build::<C>();
+    let proof = data.prove(pw)?;
+    data.verify(proof)
+}
+
+/// Executes the asm json program at `path` and proves the resulting traces
+/// with the standard fast STARK config.
+pub fn prove_by_asm_path<C>(
+    path: String,
+    call_data: Option<Vec<GoldilocksField>>,
+    db_name: Option<String>,
+) -> anyhow::Result<AllProof<GoldilocksField, C, 2>>
+where
+    C: GenericConfig<2, F = GoldilocksField>,
+    [(); C::Hasher::HASH_SIZE]:,
+{
+    let (mut db, _db_dir) = test_account_tree(db_name);
+
+    let program = encode_asm_from_json_file(path).unwrap();
+    let hash = ZkHasher::default();
+    let instructions = program.bytecode.split('\n');
+    let code: Vec<_> = instructions
+        .clone()
+        .map(|e| GoldilocksField::from_canonical_u64(u64::from_str_radix(&e[2..], 16).unwrap()))
+        .collect();
+    let code_hash = hash.hash_bytes(&code);
+    let mut prophets = HashMap::new();
+    for item in program.prophets {
+        prophets.insert(item.host as u64, item);
+    }
+
+    let mut program: Program = Program::default();
+    for inst in instructions {
+        program.instructions.push(inst.to_string());
+    }
+
+    let mut process = Process::new();
+    process.addr_storage = Address::default();
+
+    let callee: Address = [
+        GoldilocksField::from_canonical_u64(9),
+        GoldilocksField::from_canonical_u64(10),
+        GoldilocksField::from_canonical_u64(11),
+        GoldilocksField::from_canonical_u64(12),
+    ];
+    let caller_addr = [
+        GoldilocksField::from_canonical_u64(17),
+        GoldilocksField::from_canonical_u64(18),
+        GoldilocksField::from_canonical_u64(19),
+        GoldilocksField::from_canonical_u64(20),
+    ];
+    let callee_exe_addr = [
+        GoldilocksField::from_canonical_u64(13),
+        GoldilocksField::from_canonical_u64(14),
+        GoldilocksField::from_canonical_u64(15),
+        GoldilocksField::from_canonical_u64(16),
+    ];
+
+    if let Some(calldata) = call_data {
+        process.tp = GoldilocksField::ZERO;
+        init_tape(
+            &mut process,
+            calldata,
+            caller_addr,
+            callee,
+            callee_exe_addr,
+            &init_tx_context_mock(),
+        );
+    }
+
+    process.addr_code = callee_exe_addr;
+    process.addr_storage = callee;
+    program
+        .trace
+        .addr_program_hash
+        .insert(encode_addr(&callee_exe_addr), code);
+
+    db.process_block(vec![WitnessStorageLog {
+        storage_log: StorageLog::new_write_log(callee_exe_addr, code_hash),
+        previous_value: tree_key_default(),
+    }]);
+    let _ = db.save();
+
+    let start = db.root_hash();
+
+    process.program_log.push(WitnessStorageLog {
+        storage_log: StorageLog::new_read_log(callee_exe_addr, code_hash),
+        previous_value: tree_key_default(),
+    });
+
+    program.prophets = prophets;
+    let res = process.execute(
+        &mut program,
+        &StateStorage::new_test(),
+        &mut BatchCacheManager::default(),
+    );
+    if let Err(e) = res {
+        println!("execute err:{:?}", e);
+        panic!("execute program failed");
+    }
+    let hash_roots = gen_storage_hash_table(&mut process, &mut program, &mut db);
+    gen_storage_table(&mut process, &mut program, hash_roots.unwrap()).unwrap();
+    program.trace.start_end_roots = (start, db.root_hash());
+
+    let mut ola_stark = OlaStark::default();
+    let (traces, public_values) =
+        generate_traces(program, &mut ola_stark, GenerationInputs::default());
+
+    prove_with_traces::<GoldilocksField, C, 2>(
+        &ola_stark,
+        &StarkConfig::standard_fast_config(),
+        traces,
+        public_values,
+        &mut TimingTree::default(),
+    )
+}
+
+/// Proves the transaction traces with the standard fast STARK config and
+/// verifies the proof, so every `OlaStark` constraint is checked on them.
+pub fn prove_and_verify_tx_traces(txs: Vec<TxExeTrace>) -> anyhow::Result<()> {
+    let config = StarkConfig::standard_fast_config();
+    let mut ola_stark = OlaStark::default();
+    let (traces, public_values) = generate_batch_traces(
+        txs,
+        &mut AccountTree::new_test(),
+        &mut ola_stark,
+        GenerationInputs::default(),
+    );
+    let proof = prove_with_traces::<GoldilocksField, PoseidonGoldilocksConfig, 2>(
+        &ola_stark,
+        &config,
+        traces,
+        public_values,
+        &mut TimingTree::default(),
+    )?;
+    verify_proof(ola_stark, proof, &config)
+}
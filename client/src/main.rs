@@ -1,26 +1,24 @@
 extern crate clap;
 
 use assembler::encoder::encode_asm_from_json_file;
-use circuits::generation::GenerationInputs;
+use circuits::generation::estimate::{estimate_traces, ProvingCostModel};
+use circuits::generation::{generate_batch_traces, GenerationInputs};
 use circuits::stark::config::StarkConfig;
 use circuits::stark::ola_stark::OlaStark;
-use circuits::stark::prover::prove;
+use circuits::stark::prover::prove_with_traces;
 use circuits::stark::serialization::Buffer;
 use circuits::stark::verifier::verify_proof;
 use clap::{arg, Command};
+use core::merkle_tree::tree::AccountTree;
 use core::program::binary_program::BinaryProgram;
-use core::program::Program;
-use core::state::state_storage::StateStorage;
-use core::trace::trace::Trace;
-use core::vm::transaction::init_tx_context_mock;
-use core::vm::vm_state::Address;
-use executor::load_tx::init_tape;
-use executor::{BatchCacheManager, Process};
-use plonky2::field::goldilocks_field::GoldilocksField;
-use plonky2::field::types::Field;
+use core::trace::exe_trace::TxExeTrace;
+use core::vm::hardware::ContractAddress;
+use executor::batch_exe_manager::BlockExeInfo;
+use executor::config::ExecuteMode;
+use executor::ola_storage::{DiskStorageWriter, OlaCachedStorage};
+use executor::tx_exe_manager::{OlaTapeInitInfo, TxExeManager};
 use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
 use plonky2::util::timing::TimingTree;
-use std::collections::HashMap;
 use std::fs::{self, metadata, File};
 use std::io::{BufRead, BufReader, Read, Write};
 use std::time::Instant;
@@ -32,6 +30,15 @@ type C = PoseidonGoldilocksConfig;
 #[allow(dead_code)]
 type F = <C as GenericConfig<D>>::F;
 
+/// Reads one transaction trace, or a list of them, written by `run`.
+fn read_tx_traces(path: &str) -> Vec<TxExeTrace> {
+    let content = fs::read_to_string(path).unwrap();
+    match serde_json::from_str(&content) {
+        Ok(txs) => txs,
+        Err(_) => vec![serde_json::from_str(&content).unwrap()],
+    }
+}
+
 fn main() {
     let matches = Command::new("olavm")
         .about("Olavm cli")
@@ -54,6 +61,8 @@ fn main() {
                     arg!(-i --input <INPUT> "Must set a binary file for OlaVM executing"),
                     arg!(-a --args <INPUT> "Must set a input args file for OlaVM executing"),
                     arg!(-o --output <OUTPUT> "Must set a output file for OlaVM executing"),
+                    arg!(--db <DB> "Path of the storage database the program is deployed to")
+                        .required(false),
                 ])
                 .arg_required_else_help(true),
         )
@@ -66,6 +75,16 @@ fn main() {
                 ])
                 .arg_required_else_help(true),
         )
+        .subcommand(
+            Command::new("estimate")
+                .about("estimate trace sizes and proving cost of executed transactions")
+                .args(&[
+                    arg!(-i --input <Trace> "Must set a file of one or a list of transaction traces"),
+                    arg!(--"max-degree-bits" <BITS> "Fail when a table is longer than 2^BITS rows")
+                        .required(false),
+                ])
+                .arg_required_else_help(true),
+        )
         .subcommand(
             Command::new("verify")
                 .about("verifiy generated proof")
@@ -95,70 +114,52 @@ fn main() {
             let arg_path = sub_matches.get_one::<String>("args").expect("required");
             let file = File::open(&arg_path).unwrap();
             let reader = BufReader::new(file);
-            let calldata: Vec<_> = reader
+            let calldata: Vec<u64> = reader
                 .lines()
                 .into_iter()
-                .map(|e| GoldilocksField::from_canonical_u64(e.unwrap().parse::<u64>().unwrap()))
+                .map(|e| e.unwrap().parse::<u64>().unwrap())
                 .collect();
 
-            let instructions = program.bytecode.split("\n");
-            let mut prophets = HashMap::new();
-            for item in program.prophets {
-                prophets.insert(item.host as u64, item);
-            }
-
-            let mut program: Program = Program::default();
-            program.prophets = prophets;
-
-            for inst in instructions {
-                program.instructions.push(inst.to_string());
-            }
-
-            let now = Instant::now();
-            let mut process = Process::new();
-
             if calldata.len() < 2 {
                 panic!("args length must larger than 2");
             }
 
-            let tp_start = 0;
-            process.tp = GoldilocksField::from_canonical_u64(tp_start as u64);
-
             //todo: address info need contain in tx!
-            let callee: Address = [
-                GoldilocksField::from_canonical_u64(9),
-                GoldilocksField::from_canonical_u64(10),
-                GoldilocksField::from_canonical_u64(11),
-                GoldilocksField::from_canonical_u64(12),
-            ];
-            let caller_addr = [
-                GoldilocksField::from_canonical_u64(17),
-                GoldilocksField::from_canonical_u64(18),
-                GoldilocksField::from_canonical_u64(19),
-                GoldilocksField::from_canonical_u64(20),
-            ];
-            let callee_exe_addr = [
-                GoldilocksField::from_canonical_u64(13),
-                GoldilocksField::from_canonical_u64(14),
-                GoldilocksField::from_canonical_u64(15),
-                GoldilocksField::from_canonical_u64(16),
-            ];
-            init_tape(
-                &mut process,
-                calldata,
-                caller_addr,
-                callee,
-                callee_exe_addr,
-                &init_tx_context_mock(),
-            );
+            let callee: ContractAddress = [9, 10, 11, 12];
+            let caller_addr: ContractAddress = [17, 18, 19, 20];
+
+            let db_path = match sub_matches.get_one::<String>("db") {
+                Some(path) => path.clone(),
+                None => std::env::temp_dir()
+                    .join("ola_run_db")
+                    .to_string_lossy()
+                    .into_owned(),
+            };
+            DiskStorageWriter::new(db_path.clone())
+                .and_then(|writer| writer.save_program(program, callee))
+                .expect("deploy program fail");
+            let mut storage = OlaCachedStorage::new(db_path, None).unwrap();
 
-            process
-                .execute(
-                    &mut program,
-                    &StateStorage::new_test(),
-                    &mut BatchCacheManager::default(),
-                )
-                .expect("OlaVM execute fail");
+            let now = Instant::now();
+            let block_info = BlockExeInfo {
+                block_number: 0,
+                block_timestamp: 0,
+                sequencer_address: [0; 4],
+                chain_id: 1027,
+            };
+            let tx = OlaTapeInitInfo {
+                version: 0,
+                origin_address: caller_addr,
+                calldata,
+                nonce: None,
+                signature_r: None,
+                signature_s: None,
+                tx_hash: None,
+                gas_limit: None,
+            };
+            let mut tx_exe_manager =
+                TxExeManager::new(ExecuteMode::Invoke, block_info, tx, &mut storage, callee, 0);
+            let result = tx_exe_manager.invoke().expect("OlaVM execute fail");
             println!("exec time:{}", now.elapsed().as_millis());
 
             let now = Instant::now();
@@ -166,7 +167,7 @@ fn main() {
             let path = sub_matches.get_one::<String>("output").expect("required");
             println!("Output trace file path: {}", path);
             let file = File::create(path).unwrap();
-            serde_json::to_writer(file, &program.trace).unwrap();
+            serde_json::to_writer(file, &result.trace).unwrap();
             println!("write time:{}", now.elapsed().as_millis());
 
             println!("Run done!");
@@ -175,28 +176,22 @@ fn main() {
             let path = sub_matches.get_one::<String>("input").expect("required");
             println!("Input trace file path: {}", path);
 
-            let file = File::open(path).unwrap();
-            let reader = BufReader::new(file);
-
-            let trace: Trace = serde_json::from_reader(reader).unwrap();
-            let program: Program = Program {
-                instructions: trace.raw_binary_instructions.clone(),
-                trace,
-                debug_info: None,
-                prophets: HashMap::new(),
-                pre_exe_flag: false,
-                print_flag: false,
-            };
-
-            let inputs = GenerationInputs::default();
+            let txs = read_tx_traces(path);
 
+            let mut account_tree = AccountTree::new_test();
             let mut ola_stark = OlaStark::<F, D>::default();
             let config = StarkConfig::standard_fast_config();
-            let proof = prove::<F, C, D>(
-                program,
+            let (traces, public_values) = generate_batch_traces(
+                txs,
+                &mut account_tree,
                 &mut ola_stark,
-                inputs,
+                GenerationInputs::default(),
+            );
+            let proof = prove_with_traces::<F, C, D>(
+                &ola_stark,
                 &config,
+                traces,
+                public_values,
                 &mut TimingTree::default(),
             )
             .unwrap();
@@ -205,13 +200,48 @@ fn main() {
             println!("Output proof file path: {}", path);
             let mut file = File::create(path).unwrap();
             let mut buffer = Buffer::new(Vec::new());
-            buffer.write_all_proof(&proof).unwrap();
+            buffer.write_all_proof(&proof, &config).unwrap();
             let se_proof = buffer.bytes();
             file.write_all(&se_proof).unwrap();
 
             println!("Proof size: {} bytes", se_proof.len());
             println!("Prove done!");
         }
+        Some(("estimate", sub_matches)) => {
+            let path = sub_matches.get_one::<String>("input").expect("required");
+            println!("Input trace file path: {}", path);
+            let txs = read_tx_traces(path);
+
+            let config = StarkConfig::standard_fast_config();
+            let estimate = estimate_traces(&txs, &config, &ProvingCostModel::default());
+            println!(
+                "{:<16}{:>10}{:>12}{:>14}{:>8}",
+                "table", "columns", "rows", "padded rows", "degree"
+            );
+            for t in &estimate.tables {
+                println!(
+                    "{:<16}{:>10}{:>12}{:>14}{:>8}",
+                    format!("{:?}", t.table),
+                    t.columns,
+                    t.rows,
+                    t.padded_rows,
+                    t.degree_bits
+                );
+            }
+            println!(
+                "Estimated proving time: {:.1}s, memory: {} MB",
+                estimate.proving_time.as_secs_f64(),
+                estimate.proving_memory_bytes >> 20
+            );
+            if let Some(bits) = sub_matches.get_one::<String>("max-degree-bits") {
+                let bits: usize = bits.parse().expect("max-degree-bits must be a number");
+                let over = estimate.tables_over(bits);
+                if !over.is_empty() {
+                    println!("Tables over 2^{} rows: {:?}", bits, over);
+                    std::process::exit(1);
+                }
+            }
+        }
         Some(("verify", sub_matches)) => {
             println!("Loading proof...");
             let path = sub_matches.get_one::<String>("input").expect("required");
@@ -223,15 +253,20 @@ fn main() {
             let _ = file.read(&mut buffer).expect("buffer overflow");
 
             let mut de_buffer = Buffer::new(buffer);
-            let de_proof = de_buffer.read_all_proof::<F, C, D>();
-            if de_proof.is_err() {
-                println!("Deserialize proof failed!");
-                return;
-            }
-            let de_proof = de_proof.unwrap();
+            let (de_proof, proof_config) = match de_buffer.read_all_proof::<F, C, D>() {
+                Ok(res) => res,
+                Err(error) => {
+                    println!("Deserialize proof failed due to: {error}");
+                    return;
+                }
+            };
 
             let ola_stark = OlaStark::<F, D>::default();
             let config = StarkConfig::standard_fast_config();
+            if proof_config != config {
+                println!("Verify failed due to: unexpected stark config {proof_config:?}");
+                return;
+            }
             match verify_proof(ola_stark, de_proof, &config) {
                 Err(error) => println!("Verify failed due to: {error}"),
                 _ => println!("Verify succeed!"),
@@ -1,11 +1,11 @@
 use enum_iterator::all;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
 use std::{
     fmt::{Display, Formatter},
     str::FromStr,
 };
 
+use crate::program::debug_info::OlaDebugInfo;
 use crate::vm::{
     hardware::{OlaRegister, OlaSpecialRegister},
     opcodes::OlaOpcode,
@@ -16,14 +16,15 @@ use crate::vm::{
 pub struct BinaryProgram {
     pub bytecode: String,
     pub prophets: Vec<OlaProphet>,
+    // kept out of the program bytes so it never changes the code hash.
     #[serde(skip)]
-    pub debug_info: Option<BTreeMap<usize, String>>,
+    pub debug_info: Option<OlaDebugInfo>,
 }
 
 impl BinaryProgram {
     pub fn from_instructions(
         instructions: Vec<BinaryInstruction>,
-        debug_info: Option<BTreeMap<usize, String>>,
+        debug_info: Option<OlaDebugInfo>,
         debug_flag: bool,
     ) -> Result<BinaryProgram, String> {
         let mut prophets: Vec<OlaProphet> = vec![];
@@ -68,6 +69,53 @@ impl BinaryProgram {
         }
         anyhow::Ok(u64s)
     }
+
+    /// Bytes the program is stored and hashed as. The names of native
+    /// prophets are appended after the program only when there are any, so a
+    /// program without native prophets keeps the bytes and code hash it had
+    /// before prophets could be native.
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        let stored = StoredProgram {
+            bytecode: self.bytecode.clone(),
+            prophets: self.prophets.iter().map(StoredProphet::from).collect(),
+        };
+        let mut bytes = bincode::serialize(&stored)?;
+        let natives: Vec<(usize, String)> = self
+            .prophets
+            .iter()
+            .filter_map(|prophet| Some((prophet.host, prophet.native.clone()?)))
+            .collect();
+        if !natives.is_empty() {
+            bytes.extend(bincode::serialize(&natives)?);
+        }
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<Self> {
+        let mut reader = bytes;
+        let stored: StoredProgram = bincode::deserialize_from(&mut reader)?;
+        let natives: Vec<(usize, String)> = if reader.is_empty() {
+            vec![]
+        } else {
+            bincode::deserialize_from(&mut reader)?
+        };
+        let prophets = stored
+            .prophets
+            .into_iter()
+            .map(|prophet| {
+                let native = natives
+                    .iter()
+                    .find(|(host, _)| *host == prophet.host)
+                    .map(|(_, name)| name.clone());
+                prophet.into_prophet(native)
+            })
+            .collect();
+        Ok(BinaryProgram {
+            bytecode: stored.bytecode,
+            prophets,
+            debug_info: None,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -127,6 +175,55 @@ impl BinaryInstruction {
         }
     }
 
+    // (dst, op0, op1) that the asm form of the opcode prints.
+    fn required_operands(opcode: OlaOpcode) -> (bool, bool, bool) {
+        match opcode {
+            OlaOpcode::ADD
+            | OlaOpcode::MUL
+            | OlaOpcode::AND
+            | OlaOpcode::OR
+            | OlaOpcode::XOR
+            | OlaOpcode::EQ
+            | OlaOpcode::NEQ
+            | OlaOpcode::GTE
+            | OlaOpcode::TLOAD
+            | OlaOpcode::POSEIDON
+            | OlaOpcode::MLOAD
+            | OlaOpcode::MSTORE => (true, true, true),
+            OlaOpcode::MOV | OlaOpcode::NOT | OlaOpcode::SIGCHECK => (true, false, true),
+            OlaOpcode::CJMP
+            | OlaOpcode::SCCALL
+            | OlaOpcode::SLOAD
+            | OlaOpcode::SSTORE
+            | OlaOpcode::TSTORE
+            | OlaOpcode::EVENT => (false, true, true),
+            OlaOpcode::JMP | OlaOpcode::CALL | OlaOpcode::RC | OlaOpcode::ASSERT => {
+                (false, false, true)
+            }
+            OlaOpcode::RET | OlaOpcode::END => (false, false, false),
+        }
+    }
+
+    fn check_operands(&self) -> Result<(), String> {
+        let (need_dst, need_op0, need_op1) = Self::required_operands(self.opcode);
+        let missing = [
+            ("dst", need_dst && self.dst.is_none()),
+            ("op0", need_op0 && self.op0.is_none()),
+            ("op1", need_op1 && self.op1.is_none()),
+        ];
+        match missing.iter().find(|(_name, missing)| *missing) {
+            Some((name, _)) => Err(format!("{} requires {}: {}", self.opcode, name, self)),
+            None => Ok(()),
+        }
+    }
+
+    fn operand_token(&self, operand: &Option<OlaOperand>, name: &str) -> Result<String, String> {
+        operand
+            .as_ref()
+            .map(|operand| operand.get_asm_token())
+            .ok_or(format!("{} requires {}: {}", self.opcode, name, self))
+    }
+
     pub fn encode(&self) -> Result<Vec<String>, String> {
         let mut instruction_u64: u64 = 0;
         let mut imm: Option<ImmediateValue> = None;
@@ -391,18 +488,20 @@ impl BinaryInstruction {
                 .map(|(reg, _matched)| OlaOperand::RegisterOperand {
                     register: reg.clone(),
                 });
-            Ok(BinaryInstruction {
+            let instruction = BinaryInstruction {
                 opcode,
                 op0,
                 op1,
                 dst,
                 prophet,
-            })
+            };
+            instruction.check_operands()?;
+            Ok(instruction)
         }
     }
 
-    pub fn get_asm_form_code(&self) -> String {
-        match self.opcode {
+    pub fn get_asm_form_code(&self) -> Result<String, String> {
+        let code = match self.opcode {
             OlaOpcode::ADD
             | OlaOpcode::MUL
             | OlaOpcode::AND
@@ -416,9 +515,9 @@ impl BinaryInstruction {
                 format!(
                     "{} {} {} {}",
                     self.opcode.token(),
-                    self.dst.clone().unwrap().get_asm_token(),
-                    self.op0.clone().unwrap().get_asm_token(),
-                    self.op1.clone().unwrap().get_asm_token()
+                    self.operand_token(&self.dst, "dst")?,
+                    self.operand_token(&self.op0, "op0")?,
+                    self.operand_token(&self.op1, "op1")?
                 )
             }
 
@@ -426,15 +525,15 @@ impl BinaryInstruction {
                 format!(
                     "{} {} {}",
                     self.opcode.token(),
-                    self.dst.clone().unwrap().get_asm_token(),
-                    self.op1.clone().unwrap().get_asm_token()
+                    self.operand_token(&self.dst, "dst")?,
+                    self.operand_token(&self.op1, "op1")?
                 )
             }
 
             OlaOpcode::MLOAD => {
-                let anchor = self.op0.clone().unwrap().get_asm_token();
-                let offset = self.op1.clone().unwrap().get_asm_token();
-                let dst_reg = self.dst.clone().unwrap().get_asm_token();
+                let anchor = self.operand_token(&self.op0, "op0")?;
+                let offset = self.operand_token(&self.op1, "op1")?;
+                let dst_reg = self.operand_token(&self.dst, "dst")?;
                 format!(
                     "{} {} [{}+{}]",
                     self.opcode.token(),
@@ -445,9 +544,9 @@ impl BinaryInstruction {
             }
 
             OlaOpcode::MSTORE => {
-                let anchor = self.op0.clone().unwrap().get_asm_token();
-                let offset = self.op1.clone().unwrap().get_asm_token();
-                let value_reg = self.dst.clone().unwrap().get_asm_token();
+                let anchor = self.operand_token(&self.op0, "op0")?;
+                let offset = self.operand_token(&self.op1, "op1")?;
+                let value_reg = self.operand_token(&self.dst, "dst")?;
                 format!(
                     "{} [{}+{}] {}",
                     self.opcode.token(),
@@ -466,8 +565,8 @@ impl BinaryInstruction {
                 format!(
                     "{} {} {}",
                     self.opcode.token(),
-                    self.op0.clone().unwrap().get_asm_token(),
-                    self.op1.clone().unwrap().get_asm_token()
+                    self.operand_token(&self.op0, "op0")?,
+                    self.operand_token(&self.op1, "op1")?
                 )
             }
 
@@ -475,14 +574,15 @@ impl BinaryInstruction {
                 format!(
                     "{} {}",
                     self.opcode.token(),
-                    self.op1.clone().unwrap().get_asm_token()
+                    self.operand_token(&self.op1, "op1")?
                 )
             }
 
             OlaOpcode::RET | OlaOpcode::END => {
                 format!("{}", self.opcode.token())
             }
-        }
+        };
+        Ok(code)
     }
 }
 
@@ -520,6 +620,52 @@ pub struct OlaProphet {
     pub ctx: Vec<(String, u64)>,
     pub inputs: Vec<OlaProphetInput>, //reg 1,2,3 then memory mode, -3, -4, -5...(count from -3)
     pub outputs: Vec<OlaProphetOutput>,
+    // name of a native prophet in `vm::native_prophet` run instead of `code`.
+    // stored apart from the other fields, see `BinaryProgram::to_bytes`.
+    #[serde(default)]
+    pub native: Option<String>,
+}
+
+// layout of a program in `BinaryProgram::to_bytes`, which must not change:
+// deployed programs are stored and hashed as these bytes.
+#[derive(Serialize, Deserialize)]
+struct StoredProgram {
+    bytecode: String,
+    prophets: Vec<StoredProphet>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredProphet {
+    host: usize,
+    code: String,
+    ctx: Vec<(String, u64)>,
+    inputs: Vec<OlaProphetInput>,
+    outputs: Vec<OlaProphetOutput>,
+}
+
+impl From<&OlaProphet> for StoredProphet {
+    fn from(prophet: &OlaProphet) -> Self {
+        StoredProphet {
+            host: prophet.host,
+            code: prophet.code.clone(),
+            ctx: prophet.ctx.clone(),
+            inputs: prophet.inputs.clone(),
+            outputs: prophet.outputs.clone(),
+        }
+    }
+}
+
+impl StoredProphet {
+    fn into_prophet(self, native: Option<String>) -> OlaProphet {
+        OlaProphet {
+            host: self.host,
+            code: self.code,
+            ctx: self.ctx,
+            inputs: self.inputs,
+            outputs: self.outputs,
+            native,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
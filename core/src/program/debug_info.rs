@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Source location of one instruction in the asm it was assembled from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OlaDebugLine {
+    /// 1-based line in the asm program.
+    pub line: usize,
+    pub asm: String,
+    /// Call label of the scope the instruction belongs to.
+    pub function: String,
+    /// Labels attached right before the instruction.
+    pub labels: Vec<String>,
+}
+
+/// Maps instruction offsets (pc) back to asm lines, labels and functions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OlaDebugInfo {
+    pub lines: BTreeMap<u64, OlaDebugLine>,
+}
+
+impl OlaDebugInfo {
+    pub fn insert(&mut self, pc: u64, line: OlaDebugLine) {
+        self.lines.insert(pc, line);
+    }
+
+    pub fn line(&self, pc: u64) -> Option<&OlaDebugLine> {
+        self.lines.get(&pc)
+    }
+
+    pub fn label_pc(&self, label: &str) -> Option<u64> {
+        self.lines
+            .iter()
+            .find(|(_, line)| line.labels.iter().any(|l| l == label))
+            .map(|(pc, _)| *pc)
+    }
+
+    pub fn labels(&self) -> HashMap<String, u64> {
+        self.lines
+            .iter()
+            .flat_map(|(pc, line)| line.labels.iter().map(move |label| (label.clone(), *pc)))
+            .collect()
+    }
+
+    /// Describes a pc as `function (line n: asm)`, or only the pc when it is
+    /// not an instruction offset.
+    pub fn symbolize(&self, pc: u64) -> String {
+        match self.line(pc) {
+            Some(line) => format!(
+                "pc {} in {} (line {}: {})",
+                pc, line.function, line.line, line.asm
+            ),
+            None => format!("pc {}", pc),
+        }
+    }
+}
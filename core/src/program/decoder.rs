@@ -40,6 +40,12 @@ pub fn decode_binary_program_to_instructions(
             break;
         }
     }
+    if !cached_first_instruction.is_empty() {
+        return Err(format!(
+            "Immediate value missing for the last instruction {}",
+            cached_first_instruction[0]
+        ));
+    }
 
     let mut instructions: Vec<BinaryInstruction> = vec![];
     let mut host: usize = 0;
@@ -1,13 +1,17 @@
 use crate::program::binary_program::OlaProphet;
+use crate::program::debug_info::OlaDebugInfo;
 use crate::trace::trace::Trace;
 use plonky2::field::goldilocks_field::GoldilocksField;
 use plonky2::field::types::Field64;
 use serde::{Deserialize, Serialize};
-use std::collections::{BTreeMap, HashMap};
+use std::collections::HashMap;
 
 pub mod binary_program;
+pub mod debug_info;
 pub mod decoder;
 pub mod instruction;
+pub mod random_program;
+pub mod verifier;
 
 /// fixme: use 10 registers
 pub const REGISTER_NUM: usize = 10;
@@ -19,7 +23,7 @@ pub const FIELD_ORDER: u64 = GoldilocksField::ORDER;
 pub struct Program {
     pub instructions: Vec<String>,
     pub trace: Trace,
-    pub debug_info: Option<BTreeMap<usize, String>>,
+    pub debug_info: Option<OlaDebugInfo>,
     pub print_flag: bool,
     pub prophets: HashMap<u64, OlaProphet>,
     pub pre_exe_flag: bool,
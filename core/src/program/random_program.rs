@@ -0,0 +1,471 @@
+//! Random programs over the Ola ISA, for fuzzing and differential testing.
+//!
+//! `random_instruction` builds any opcode with operands the encoder accepts,
+//! which is what the decoding paths are fuzzed with.
+//! `random_executable_program` only chains instruction sequences that run to
+//! `end` without error, so the same program can be fed to both executors.
+
+use enum_iterator::all;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::program::binary_program::{BinaryInstruction, BinaryProgram};
+use crate::program::FIELD_ORDER;
+use crate::vm::hardware::{OlaRegister, OlaSpecialRegister};
+use crate::vm::opcodes::OlaOpcode;
+use crate::vm::operands::{ImmediateValue, OlaOperand};
+
+/// Addresses the executable programs store to, low in the stack region.
+const MEM_BASE_RANGE: std::ops::Range<u64> = 1..1 << 20;
+
+/// Registers the executable programs write. `r9` is left alone, it holds the
+/// frame pointer.
+const SCRATCH_REGISTERS: [OlaRegister; 9] = [
+    OlaRegister::R0,
+    OlaRegister::R1,
+    OlaRegister::R2,
+    OlaRegister::R3,
+    OlaRegister::R4,
+    OlaRegister::R5,
+    OlaRegister::R6,
+    OlaRegister::R7,
+    OlaRegister::R8,
+];
+
+/// A random instruction for `opcode`, with operands of the shapes the
+/// assembler emits for it.
+pub fn random_instruction<R: Rng>(rng: &mut R, opcode: OlaOpcode) -> BinaryInstruction {
+    let (op0, op1, dst) = match opcode {
+        OlaOpcode::ADD
+        | OlaOpcode::MUL
+        | OlaOpcode::AND
+        | OlaOpcode::OR
+        | OlaOpcode::XOR
+        | OlaOpcode::EQ
+        | OlaOpcode::NEQ
+        | OlaOpcode::GTE
+        | OlaOpcode::TLOAD
+        | OlaOpcode::POSEIDON => (
+            Some(reg_operand(random_register(rng))),
+            Some(random_reg_or_imm(rng)),
+            Some(reg_operand(random_register(rng))),
+        ),
+        OlaOpcode::MOV => {
+            let op1 = if rng.gen_ratio(1, 8) {
+                OlaOperand::SpecialReg {
+                    special_reg: OlaSpecialRegister::PSP,
+                }
+            } else {
+                random_reg_or_imm(rng)
+            };
+            (None, Some(op1), Some(reg_operand(random_register(rng))))
+        }
+        OlaOpcode::NOT => (
+            None,
+            Some(random_reg_or_imm(rng)),
+            Some(reg_operand(random_register(rng))),
+        ),
+        OlaOpcode::SIGCHECK => (
+            None,
+            Some(reg_operand(random_register(rng))),
+            Some(reg_operand(random_register(rng))),
+        ),
+        OlaOpcode::MLOAD | OlaOpcode::MSTORE => {
+            let offset = if rng.gen() {
+                OlaOperand::ImmediateOperand {
+                    value: random_immediate(rng),
+                }
+            } else {
+                OlaOperand::RegisterWithFactor {
+                    register: random_register(rng),
+                    factor: random_immediate(rng),
+                }
+            };
+            (
+                Some(reg_operand(random_register(rng))),
+                Some(offset),
+                Some(reg_operand(random_register(rng))),
+            )
+        }
+        OlaOpcode::CJMP
+        | OlaOpcode::SCCALL
+        | OlaOpcode::SLOAD
+        | OlaOpcode::SSTORE
+        | OlaOpcode::TSTORE
+        | OlaOpcode::EVENT => (
+            Some(reg_operand(random_register(rng))),
+            Some(random_reg_or_imm(rng)),
+            None,
+        ),
+        OlaOpcode::JMP | OlaOpcode::CALL => (None, Some(random_reg_or_imm(rng)), None),
+        OlaOpcode::RC | OlaOpcode::ASSERT => (None, Some(reg_operand(random_register(rng))), None),
+        OlaOpcode::RET | OlaOpcode::END => (None, None, None),
+    };
+    BinaryInstruction {
+        opcode,
+        op0,
+        op1,
+        dst,
+        prophet: None,
+    }
+}
+
+/// `len` random instructions over all opcodes, followed by `end`. Jump targets
+/// and memory addresses are arbitrary, so the program is only meant to be
+/// encoded and decoded, not run.
+pub fn random_instructions<R: Rng>(rng: &mut R, len: usize) -> Vec<BinaryInstruction> {
+    let opcodes: Vec<OlaOpcode> = all::<OlaOpcode>().collect();
+    let mut instructions: Vec<BinaryInstruction> = (0..len)
+        .map(|_| {
+            let opcode = *opcodes.choose(rng).unwrap();
+            random_instruction(rng, opcode)
+        })
+        .collect();
+    instructions.push(end_instruction());
+    instructions
+}
+
+/// A program of about `len` instructions both executors run to `end` without
+/// error: operands of u32-only opcodes are loaded with u32 values first,
+/// `mload` only reads addresses stored to before, and jumps only go forward
+/// over arithmetic. Calls, storage, tape and hashing opcodes are not emitted.
+pub fn random_executable_program<R: Rng>(rng: &mut R, len: usize) -> BinaryProgram {
+    let mut builder = ExecutableBuilder::default();
+    while builder.instructions.len() < len {
+        match rng.gen_range(0..7) {
+            0 | 1 => builder.push_field_op(rng),
+            2 => builder.push_u32_op(rng),
+            3 => builder.push_assert(rng),
+            4 => builder.push_mstore(rng),
+            5 => builder.push_mload(rng),
+            _ => builder.push_forward_jump(rng),
+        }
+    }
+    builder.push(end_instruction());
+    builder.build()
+}
+
+#[derive(Default)]
+struct ExecutableBuilder {
+    instructions: Vec<BinaryInstruction>,
+    /// Index of the instruction a jump at the same position goes to.
+    jump_targets: Vec<Option<usize>>,
+    stored_addresses: Vec<u64>,
+}
+
+impl ExecutableBuilder {
+    fn push(&mut self, instruction: BinaryInstruction) {
+        self.instructions.push(instruction);
+        self.jump_targets.push(None);
+    }
+
+    fn push_mov_imm(&mut self, dst: OlaRegister, value: u64) {
+        self.push(BinaryInstruction {
+            opcode: OlaOpcode::MOV,
+            op0: None,
+            op1: Some(imm_operand(value)),
+            dst: Some(reg_operand(dst)),
+            prophet: None,
+        });
+    }
+
+    fn push_field_op<R: Rng>(&mut self, rng: &mut R) {
+        let opcode = *[
+            OlaOpcode::ADD,
+            OlaOpcode::MUL,
+            OlaOpcode::EQ,
+            OlaOpcode::NEQ,
+            OlaOpcode::NOT,
+            OlaOpcode::MOV,
+        ]
+        .choose(rng)
+        .unwrap();
+        let mut instruction = random_instruction(rng, opcode);
+        if instruction.op0.is_some() {
+            instruction.op0 = Some(reg_operand(random_scratch_register(rng)));
+        }
+        if let Some(OlaOperand::RegisterOperand { .. }) = instruction.op1 {
+            instruction.op1 = Some(reg_operand(random_scratch_register(rng)));
+        }
+        instruction.dst = Some(reg_operand(random_scratch_register(rng)));
+        self.push(instruction);
+    }
+
+    fn push_u32_op<R: Rng>(&mut self, rng: &mut R) {
+        let [a, b, dst] = distinct_scratch_registers(rng);
+        let opcode = *[
+            OlaOpcode::AND,
+            OlaOpcode::OR,
+            OlaOpcode::XOR,
+            OlaOpcode::GTE,
+            OlaOpcode::RC,
+        ]
+        .choose(rng)
+        .unwrap();
+        self.push_mov_imm(a, random_u32(rng));
+        if opcode == OlaOpcode::RC {
+            self.push(BinaryInstruction {
+                opcode,
+                op0: None,
+                op1: Some(reg_operand(a)),
+                dst: None,
+                prophet: None,
+            });
+            return;
+        }
+        let op1 = if rng.gen() {
+            self.push_mov_imm(b, random_u32(rng));
+            reg_operand(b)
+        } else {
+            imm_operand(random_u32(rng))
+        };
+        self.push(BinaryInstruction {
+            opcode,
+            op0: Some(reg_operand(a)),
+            op1: Some(op1),
+            dst: Some(reg_operand(dst)),
+            prophet: None,
+        });
+    }
+
+    fn push_assert<R: Rng>(&mut self, rng: &mut R) {
+        let register = random_scratch_register(rng);
+        self.push_mov_imm(register, 1);
+        self.push(BinaryInstruction {
+            opcode: OlaOpcode::ASSERT,
+            op0: None,
+            op1: Some(reg_operand(register)),
+            dst: None,
+            prophet: None,
+        });
+    }
+
+    fn push_mstore<R: Rng>(&mut self, rng: &mut R) {
+        let [anchor, index, value] = distinct_scratch_registers(rng);
+        let base = rng.gen_range(MEM_BASE_RANGE);
+        self.push_mov_imm(anchor, base);
+        let offset = if rng.gen() {
+            let offset = rng.gen_range(0..16);
+            self.stored_addresses.push(base + offset);
+            imm_operand(offset)
+        } else {
+            let index_value = rng.gen_range(0..16);
+            let factor = rng.gen_range(1..4);
+            self.push_mov_imm(index, index_value);
+            self.stored_addresses.push(base + index_value * factor);
+            OlaOperand::RegisterWithFactor {
+                register: index,
+                factor: immediate(factor),
+            }
+        };
+        self.push(BinaryInstruction {
+            opcode: OlaOpcode::MSTORE,
+            op0: Some(reg_operand(anchor)),
+            op1: Some(offset),
+            dst: Some(reg_operand(value)),
+            prophet: None,
+        });
+    }
+
+    fn push_mload<R: Rng>(&mut self, rng: &mut R) {
+        let addr = match self.stored_addresses.choose(rng) {
+            Some(addr) => *addr,
+            None => return self.push_mstore(rng),
+        };
+        let [anchor, dst, _] = distinct_scratch_registers(rng);
+        let offset = rng.gen_range(0..=addr.min(16));
+        self.push_mov_imm(anchor, addr - offset);
+        self.push(BinaryInstruction {
+            opcode: OlaOpcode::MLOAD,
+            op0: Some(reg_operand(anchor)),
+            op1: Some(imm_operand(offset)),
+            dst: Some(reg_operand(dst)),
+            prophet: None,
+        });
+    }
+
+    fn push_forward_jump<R: Rng>(&mut self, rng: &mut R) {
+        let skipped = rng.gen_range(1..4);
+        let jump = if rng.gen() {
+            let cond = random_scratch_register(rng);
+            self.push_mov_imm(cond, rng.gen_range(0..2));
+            BinaryInstruction {
+                opcode: OlaOpcode::CJMP,
+                op0: Some(reg_operand(cond)),
+                op1: Some(imm_operand(0)),
+                dst: None,
+                prophet: None,
+            }
+        } else {
+            BinaryInstruction {
+                opcode: OlaOpcode::JMP,
+                op0: None,
+                op1: Some(imm_operand(0)),
+                dst: None,
+                prophet: None,
+            }
+        };
+        let target = self.instructions.len() + 1 + skipped;
+        self.instructions.push(jump);
+        self.jump_targets.push(Some(target));
+        for _ in 0..skipped {
+            self.push_field_op(rng);
+        }
+    }
+
+    fn build(mut self) -> BinaryProgram {
+        let mut pcs = Vec::with_capacity(self.instructions.len());
+        let mut pc = 0;
+        for instruction in &self.instructions {
+            pcs.push(pc);
+            pc += instruction.binary_length() as u64;
+        }
+        for (instruction, target) in self.instructions.iter_mut().zip(&self.jump_targets) {
+            if let Some(target) = target {
+                instruction.op1 = Some(imm_operand(pcs[*target]));
+            }
+        }
+        BinaryProgram::from_instructions(self.instructions, None, false)
+            .expect("generated instructions always encode")
+    }
+}
+
+fn end_instruction() -> BinaryInstruction {
+    BinaryInstruction {
+        opcode: OlaOpcode::END,
+        op0: None,
+        op1: None,
+        dst: None,
+        prophet: None,
+    }
+}
+
+fn random_register<R: Rng>(rng: &mut R) -> OlaRegister {
+    *all::<OlaRegister>()
+        .collect::<Vec<_>>()
+        .choose(rng)
+        .unwrap()
+}
+
+fn random_scratch_register<R: Rng>(rng: &mut R) -> OlaRegister {
+    *SCRATCH_REGISTERS.choose(rng).unwrap()
+}
+
+fn distinct_scratch_registers<R: Rng>(rng: &mut R) -> [OlaRegister; 3] {
+    let picked: Vec<OlaRegister> = SCRATCH_REGISTERS.choose_multiple(rng, 3).cloned().collect();
+    [picked[0], picked[1], picked[2]]
+}
+
+fn random_u32<R: Rng>(rng: &mut R) -> u64 {
+    match rng.gen_range(0..4) {
+        0 => 0,
+        1 => u32::MAX as u64,
+        _ => rng.gen::<u32>() as u64,
+    }
+}
+
+/// Any field element, biased towards the edges of the u32 and field ranges.
+fn random_immediate<R: Rng>(rng: &mut R) -> ImmediateValue {
+    let value = match rng.gen_range(0..6) {
+        0 => 0,
+        1 => 1,
+        2 => u32::MAX as u64,
+        3 => FIELD_ORDER - 1,
+        4 => rng.gen::<u32>() as u64,
+        _ => rng.gen_range(0..FIELD_ORDER),
+    };
+    immediate(value)
+}
+
+fn random_reg_or_imm<R: Rng>(rng: &mut R) -> OlaOperand {
+    if rng.gen() {
+        reg_operand(random_register(rng))
+    } else {
+        OlaOperand::ImmediateOperand {
+            value: random_immediate(rng),
+        }
+    }
+}
+
+fn immediate(value: u64) -> ImmediateValue {
+    ImmediateValue {
+        hex: format!("{:#x}", value),
+    }
+}
+
+fn imm_operand(value: u64) -> OlaOperand {
+    OlaOperand::ImmediateOperand {
+        value: immediate(value),
+    }
+}
+
+fn reg_operand(register: OlaRegister) -> OlaOperand {
+    OlaOperand::RegisterOperand { register }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::decoder::decode_binary_program_to_instructions;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_random_instructions_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..64 {
+            let instructions = random_instructions(&mut rng, 32);
+            let program =
+                BinaryProgram::from_instructions(instructions.clone(), None, false).unwrap();
+            let decoded = decode_binary_program_to_instructions(program).unwrap();
+            assert_eq!(decoded.len(), instructions.len());
+            for (expected, actual) in instructions.iter().zip(decoded.iter()) {
+                assert_eq!(expected.encode(), actual.encode(), "{}", expected);
+                assert_eq!(
+                    expected.get_asm_form_code().unwrap(),
+                    actual.get_asm_form_code().unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_missing_operands() {
+        let instruction = BinaryInstruction {
+            opcode: OlaOpcode::ADD,
+            op0: Some(reg_operand(OlaRegister::R1)),
+            op1: Some(reg_operand(OlaRegister::R2)),
+            dst: None,
+            prophet: None,
+        };
+        assert!(instruction.get_asm_form_code().is_err());
+        let encoded = instruction.encode().unwrap();
+        assert!(BinaryInstruction::decode(encoded, None).is_err());
+    }
+
+    #[test]
+    fn test_random_executable_program_jumps_forward() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..64 {
+            let program = random_executable_program(&mut rng, 40);
+            let instructions = decode_binary_program_to_instructions(program).unwrap();
+            assert_eq!(instructions.last().unwrap().opcode, OlaOpcode::END);
+
+            let mut pc = 0;
+            let mut pcs = vec![];
+            for instruction in &instructions {
+                pcs.push(pc);
+                pc += instruction.binary_length() as u64;
+            }
+            for (instruction, pc) in instructions.iter().zip(pcs.iter()) {
+                if instruction.opcode == OlaOpcode::JMP || instruction.opcode == OlaOpcode::CJMP {
+                    let target = match &instruction.op1 {
+                        Some(OlaOperand::ImmediateOperand { value }) => value.to_u64().unwrap(),
+                        _ => panic!("jump without immediate target"),
+                    };
+                    assert!(target > *pc && pcs.contains(&target));
+                }
+            }
+        }
+    }
+}
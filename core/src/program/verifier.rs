@@ -0,0 +1,461 @@
+//! Static checks on a `BinaryProgram`, run before it is deployed.
+//!
+//! Everything the executor would only find out while running is checked up
+//! front: every word decodes to an instruction that encodes back to the same
+//! word, immediates are canonical field elements, immediate jump and call
+//! targets land on instruction boundaries, prophets are attached to an
+//! instruction and name a known native prophet if they are native, and an
+//! `end` is reachable from pc 0.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use thiserror::Error;
+
+use crate::program::binary_program::{BinaryInstruction, BinaryProgram};
+use crate::program::FIELD_ORDER;
+use crate::vm::native_prophet::get_native_prophet;
+use crate::vm::opcodes::OlaOpcode;
+use crate::vm::operands::OlaOperand;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BytecodeDiagnostic {
+    #[error("program is empty")]
+    EmptyProgram,
+
+    #[error("line {line}: not a 64-bit hex word: {word}")]
+    InvalidWord { line: usize, word: String },
+
+    #[error("pc {pc}: immediate missing at the end of the bytecode")]
+    TruncatedInstruction { pc: u64 },
+
+    #[error("pc {pc}: immediate {value:#x} is not a canonical field element")]
+    NonCanonicalImmediate { pc: u64, value: u64 },
+
+    #[error("pc {pc}: {reason}")]
+    UndecodableInstruction { pc: u64, reason: String },
+
+    #[error("pc {pc}: {word:#018x} has stray bits, it encodes back to {encoded:#018x}")]
+    NonCanonicalEncoding { pc: u64, word: u64, encoded: u64 },
+
+    #[error("pc {pc}: {opcode} target {target} is not an instruction boundary")]
+    InvalidJumpTarget {
+        pc: u64,
+        opcode: String,
+        target: u64,
+    },
+
+    #[error("prophet host {host} is not an instruction boundary")]
+    UnresolvedProphet { host: usize },
+
+    #[error("prophet host {host} has more than one prophet")]
+    DuplicateProphet { host: usize },
+
+    #[error("prophet at {host}: code is not wrapped in %{{ ... %}}")]
+    MalformedProphet { host: usize },
+
+    #[error("prophet at {host}: unknown native prophet {name}")]
+    UnknownNativeProphet { host: usize, name: String },
+
+    #[error("pc {pc}: execution runs past the last instruction")]
+    FallsOffEnd { pc: u64 },
+
+    #[error("no end instruction is reachable from pc 0")]
+    UnreachableEnd,
+}
+
+/// All the problems found in a program, in bytecode order.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    pub diagnostics: Vec<BytecodeDiagnostic>,
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "bytecode verification failed")?;
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n  {}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn verify_program(program: &BinaryProgram) -> Result<(), VerifyError> {
+    let diagnostics = collect_diagnostics(program);
+    if diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(VerifyError { diagnostics })
+    }
+}
+
+fn collect_diagnostics(program: &BinaryProgram) -> Vec<BytecodeDiagnostic> {
+    let mut diagnostics = vec![];
+
+    let mut words: Vec<u64> = vec![];
+    for (index, line) in program.bytecode.lines().enumerate() {
+        match u64::from_str_radix(line.trim_start_matches("0x"), 16) {
+            Ok(word) => words.push(word),
+            Err(_) => diagnostics.push(BytecodeDiagnostic::InvalidWord {
+                line: index + 1,
+                word: line.to_string(),
+            }),
+        }
+    }
+    if !diagnostics.is_empty() {
+        return diagnostics;
+    }
+    if words.is_empty() {
+        return vec![BytecodeDiagnostic::EmptyProgram];
+    }
+
+    let mut instructions: BTreeMap<u64, BinaryInstruction> = BTreeMap::new();
+    let mut pc = 0usize;
+    while pc < words.len() {
+        let word = words[pc];
+        let length = instruction_length(word);
+        if pc + length > words.len() {
+            diagnostics.push(BytecodeDiagnostic::TruncatedInstruction { pc: pc as u64 });
+            break;
+        }
+        let binary_code: Vec<String> = words[pc..pc + length]
+            .iter()
+            .map(|w| format!("0x{:0>16x}", w))
+            .collect();
+        if length == 2 && words[pc + 1] >= FIELD_ORDER {
+            diagnostics.push(BytecodeDiagnostic::NonCanonicalImmediate {
+                pc: pc as u64,
+                value: words[pc + 1],
+            });
+        } else {
+            match BinaryInstruction::decode(binary_code, None) {
+                Ok(instruction) => {
+                    match instruction.get_inst_imm_u64() {
+                        Ok((encoded, _)) if encoded != word => {
+                            diagnostics.push(BytecodeDiagnostic::NonCanonicalEncoding {
+                                pc: pc as u64,
+                                word,
+                                encoded,
+                            })
+                        }
+                        Ok(_) => {}
+                        Err(err) => diagnostics.push(BytecodeDiagnostic::UndecodableInstruction {
+                            pc: pc as u64,
+                            reason: err.to_string(),
+                        }),
+                    }
+                    instructions.insert(pc as u64, instruction);
+                }
+                Err(reason) => diagnostics.push(BytecodeDiagnostic::UndecodableInstruction {
+                    pc: pc as u64,
+                    reason,
+                }),
+            }
+        }
+        pc += length;
+    }
+    let end_pc = pc as u64;
+
+    let mut hosts = HashSet::new();
+    for prophet in &program.prophets {
+        if !instructions.contains_key(&(prophet.host as u64)) {
+            diagnostics.push(BytecodeDiagnostic::UnresolvedProphet { host: prophet.host });
+        }
+        if !hosts.insert(prophet.host) {
+            diagnostics.push(BytecodeDiagnostic::DuplicateProphet { host: prophet.host });
+        }
+        match &prophet.native {
+            Some(name) if get_native_prophet(name).is_none() => {
+                diagnostics.push(BytecodeDiagnostic::UnknownNativeProphet {
+                    host: prophet.host,
+                    name: name.clone(),
+                })
+            }
+            Some(_) => {}
+            None if !(prophet.code.starts_with("%{") && prophet.code.ends_with("%}")) => {
+                diagnostics.push(BytecodeDiagnostic::MalformedProphet { host: prophet.host })
+            }
+            None => {}
+        }
+    }
+
+    for (pc, instruction) in &instructions {
+        if let Some(target) = static_target(instruction) {
+            if !instructions.contains_key(&target) {
+                diagnostics.push(BytecodeDiagnostic::InvalidJumpTarget {
+                    pc: *pc,
+                    opcode: instruction.opcode.token(),
+                    target,
+                });
+            }
+        }
+    }
+
+    // control flow is only meaningful once every instruction is known.
+    if diagnostics.is_empty() {
+        diagnostics.extend(check_reachable_end(&instructions, end_pc));
+    }
+    diagnostics
+}
+
+/// Same rule the decoder groups words by.
+fn instruction_length(word: u64) -> usize {
+    let is_op1_imm = word & (1 << BinaryInstruction::BIT_SHIFT_OP1_IMM) != 0;
+    let is_mem =
+        word & (OlaOpcode::MLOAD.binary_bit_mask() | OlaOpcode::MSTORE.binary_bit_mask()) != 0;
+    if is_op1_imm || is_mem {
+        2
+    } else {
+        1
+    }
+}
+
+fn static_target(instruction: &BinaryInstruction) -> Option<u64> {
+    match instruction.opcode {
+        OlaOpcode::JMP | OlaOpcode::CJMP | OlaOpcode::CALL => match &instruction.op1 {
+            Some(OlaOperand::ImmediateOperand { value }) => value.to_u64().ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Walks the control flow from pc 0. A call is assumed to return, a jump to a
+// register target can go anywhere, so an `end` is then not required.
+fn check_reachable_end(
+    instructions: &BTreeMap<u64, BinaryInstruction>,
+    end_pc: u64,
+) -> Vec<BytecodeDiagnostic> {
+    let mut diagnostics = vec![];
+    let mut visited: HashSet<u64> = HashSet::new();
+    let mut pending: Vec<u64> = vec![0];
+    let mut end_reached = false;
+    let mut dynamic_jump = false;
+
+    while let Some(pc) = pending.pop() {
+        if !visited.insert(pc) {
+            continue;
+        }
+        let instruction = match instructions.get(&pc) {
+            Some(instruction) => instruction,
+            None => continue,
+        };
+        let next = pc + instruction.binary_length() as u64;
+        let target = static_target(instruction);
+        let falls_through = match instruction.opcode {
+            OlaOpcode::END => {
+                end_reached = true;
+                false
+            }
+            OlaOpcode::RET => false,
+            OlaOpcode::JMP => false,
+            _ => true,
+        };
+        match instruction.opcode {
+            OlaOpcode::JMP | OlaOpcode::CJMP | OlaOpcode::CALL => match target {
+                Some(target) => pending.push(target),
+                None => dynamic_jump = true,
+            },
+            _ => {}
+        }
+        if falls_through {
+            if next >= end_pc {
+                diagnostics.push(BytecodeDiagnostic::FallsOffEnd { pc });
+            } else {
+                pending.push(next);
+            }
+        }
+    }
+
+    if !end_reached && !dynamic_jump {
+        diagnostics.push(BytecodeDiagnostic::UnreachableEnd);
+    }
+    diagnostics.sort_by_key(|d| match d {
+        BytecodeDiagnostic::FallsOffEnd { pc } => *pc,
+        _ => u64::MAX,
+    });
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::binary_program::OlaProphet;
+    use crate::program::random_program::random_executable_program;
+    use crate::vm::hardware::OlaRegister;
+    use crate::vm::operands::ImmediateValue;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn instruction(opcode: OlaOpcode, op1: Option<OlaOperand>) -> BinaryInstruction {
+        BinaryInstruction {
+            opcode,
+            op0: None,
+            op1,
+            dst: None,
+            prophet: None,
+        }
+    }
+
+    fn imm(value: u64) -> Option<OlaOperand> {
+        Some(OlaOperand::ImmediateOperand {
+            value: ImmediateValue {
+                hex: format!("{:#x}", value),
+            },
+        })
+    }
+
+    fn program(instructions: Vec<BinaryInstruction>) -> BinaryProgram {
+        BinaryProgram::from_instructions(instructions, None, false).unwrap()
+    }
+
+    fn diagnostics(program: &BinaryProgram) -> Vec<BytecodeDiagnostic> {
+        verify_program(program).unwrap_err().diagnostics
+    }
+
+    #[test]
+    fn test_random_executable_programs_verify() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..32 {
+            verify_program(&random_executable_program(&mut rng, 48)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_malformed_words() {
+        let mut bad = program(vec![instruction(OlaOpcode::END, None)]);
+        bad.bytecode = "0xzz".to_string();
+        assert_eq!(
+            diagnostics(&bad),
+            vec![BytecodeDiagnostic::InvalidWord {
+                line: 1,
+                word: "0xzz".to_string()
+            }]
+        );
+
+        let jmp = program(vec![instruction(OlaOpcode::JMP, imm(0))]);
+        let mut truncated = jmp.clone();
+        truncated.bytecode = jmp.bytecode.lines().next().unwrap().to_string();
+        assert_eq!(
+            diagnostics(&truncated),
+            vec![BytecodeDiagnostic::TruncatedInstruction { pc: 0 }]
+        );
+
+        let mut non_canonical = jmp.clone();
+        non_canonical.bytecode = format!(
+            "{}\n0xffffffffffffffff",
+            jmp.bytecode.lines().next().unwrap()
+        );
+        assert_eq!(
+            diagnostics(&non_canonical),
+            vec![BytecodeDiagnostic::NonCanonicalImmediate {
+                pc: 0,
+                value: u64::MAX
+            }]
+        );
+
+        // two op1 registers, only the first one decodes.
+        let rc_word = OlaOpcode::RC.binary_bit_mask() | OlaRegister::R3.binary_bit_mask_as_op1();
+        let stray = rc_word | OlaRegister::R4.binary_bit_mask_as_op1();
+        let mut stray_bits = jmp;
+        stray_bits.bytecode = format!("0x{:0>16x}", stray);
+        assert_eq!(
+            diagnostics(&stray_bits),
+            vec![BytecodeDiagnostic::NonCanonicalEncoding {
+                pc: 0,
+                word: stray,
+                encoded: rc_word
+            }]
+        );
+    }
+
+    #[test]
+    fn test_jump_targets_and_end() {
+        // jmp 1 lands on the immediate of the jmp itself.
+        let inside = program(vec![
+            instruction(OlaOpcode::JMP, imm(1)),
+            instruction(OlaOpcode::END, None),
+        ]);
+        assert_eq!(
+            diagnostics(&inside),
+            vec![BytecodeDiagnostic::InvalidJumpTarget {
+                pc: 0,
+                opcode: "jmp".to_string(),
+                target: 1
+            }]
+        );
+
+        let looping = program(vec![
+            instruction(OlaOpcode::JMP, imm(0)),
+            instruction(OlaOpcode::END, None),
+        ]);
+        assert_eq!(
+            diagnostics(&looping),
+            vec![BytecodeDiagnostic::UnreachableEnd]
+        );
+
+        let no_end = program(vec![instruction(OlaOpcode::RC, imm(0))]);
+        assert_eq!(
+            diagnostics(&no_end),
+            vec![
+                BytecodeDiagnostic::FallsOffEnd { pc: 0 },
+                BytecodeDiagnostic::UnreachableEnd
+            ]
+        );
+
+        let call = program(vec![
+            instruction(OlaOpcode::CALL, imm(3)),
+            instruction(OlaOpcode::END, None),
+            instruction(OlaOpcode::RET, None),
+        ]);
+        verify_program(&call).unwrap();
+    }
+
+    #[test]
+    fn test_prophets() {
+        let mut with_prophet = program(vec![
+            instruction(OlaOpcode::RET, None),
+            instruction(OlaOpcode::END, None),
+        ]);
+        let prophet = OlaProphet {
+            host: 1,
+            code: "%{ entry() {} %}".to_string(),
+            ctx: vec![],
+            inputs: vec![],
+            outputs: vec![],
+            native: None,
+        };
+        with_prophet.prophets = vec![
+            prophet.clone(),
+            prophet.clone(),
+            OlaProphet {
+                host: 2,
+                code: "entry() {}".to_string(),
+                ..prophet.clone()
+            },
+        ];
+        assert_eq!(
+            diagnostics(&with_prophet),
+            vec![
+                BytecodeDiagnostic::DuplicateProphet { host: 1 },
+                BytecodeDiagnostic::UnresolvedProphet { host: 2 },
+                BytecodeDiagnostic::MalformedProphet { host: 2 },
+            ]
+        );
+
+        with_prophet.prophets = vec![OlaProphet {
+            code: String::new(),
+            native: Some("isqrt".to_string()),
+            ..prophet.clone()
+        }];
+        verify_program(&with_prophet).unwrap();
+        with_prophet.prophets[0].native = Some("cbrt".to_string());
+        assert_eq!(
+            diagnostics(&with_prophet),
+            vec![BytecodeDiagnostic::UnknownNativeProphet {
+                host: 1,
+                name: "cbrt".to_string()
+            }]
+        );
+    }
+}
@@ -115,6 +115,30 @@ pub struct SCCallPiece {
     pub clk_callee_end: u64,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigCheckPiece {
+    pub env_idx: u64,
+    pub clk: u64,
+    pub params_addr: u64,
+    pub msg_hash: [u64; 4],
+    pub pubkey_x: [u64; 4],
+    pub pubkey_y: [u64; 4],
+    pub r: [u64; 4],
+    pub s: [u64; 4],
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPiece {
+    pub env_idx: u64,
+    pub clk: u64,
+    pub topics_addr: u64,
+    pub data_addr: u64,
+    pub topic_addrs: Vec<u64>,
+    pub topics: Vec<[u64; 4]>,
+    pub data: Vec<u64>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ExeTraceStepDiff {
     pub cpu: Vec<CpuExePiece>,
@@ -125,6 +149,8 @@ pub struct ExeTraceStepDiff {
     pub poseidon: Option<PoseidonPiece>,
     pub storage: Option<StorageExePiece>,
     pub tape: Option<Vec<TapeExePiece>>,
+    pub sigcheck: Option<SigCheckPiece>,
+    pub event: Option<EventPiece>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -141,4 +167,6 @@ pub struct TxExeTrace {
     pub storage: Vec<StorageExePiece>,
     pub tape: Vec<TapeExePiece>,
     pub sccall: Vec<SCCallPiece>,
+    pub sigcheck: Vec<SigCheckPiece>,
+    pub event: Vec<EventPiece>,
 }
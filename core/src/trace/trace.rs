@@ -320,6 +320,27 @@ pub struct SCCallRow {
     pub clk_callee_end: GoldilocksField,
 }
 
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct SigCheckRow {
+    pub env_idx: GoldilocksField,
+    pub clk: GoldilocksField,
+    pub op1: GoldilocksField,
+    // msg_hash, pubkey_x, pubkey_y, r, s read from `op1..op1 + 20`.
+    pub inputs: [GoldilocksField; 20],
+    pub res: GoldilocksField,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRow {
+    pub env_idx: GoldilocksField,
+    pub clk: GoldilocksField,
+    pub op0: GoldilocksField,
+    pub op1: GoldilocksField,
+    pub topic_addrs: Vec<GoldilocksField>,
+    pub topics: Vec<[GoldilocksField; 4]>,
+    pub data: Vec<GoldilocksField>,
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Trace {
     //(inst_asm_str, imm_flag, step, inst_encode, imm_val)
@@ -342,6 +363,8 @@ pub struct Trace {
     pub builtin_program_hash: Vec<StorageHashRow>,
     pub tape: Vec<TapeRow>,
     pub sc_call: Vec<SCCallRow>,
+    pub builtin_sigcheck: Vec<SigCheckRow>,
+    pub event: Vec<EventRow>,
     pub ret: Vec<GoldilocksField>,
 }
 
@@ -8,11 +8,14 @@ pub struct TxResult {
     pub trace: TxExeTrace,
     pub storage_access_logs: Vec<StorageAccessLog>,
     pub events: Vec<Event>,
+    pub gas_used: u64,
 }
 
 pub struct BatchResult {
     pub tx_traces: Vec<TxExeTrace>,
     pub storage_access_logs: Vec<StorageAccessLog>,
     pub events: Vec<Event>,
+    /// Number of events emitted by each transaction of `tx_traces`.
+    pub tx_events_len: Vec<usize>,
     pub block_tip_queries: Vec<StorageAccessLog>,
 }
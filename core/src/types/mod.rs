@@ -6,4 +6,5 @@ pub mod storage;
 
 pub use plonky2::field::goldilocks_field::GoldilocksField;
 pub use plonky2::field::types::Field;
+pub use plonky2::field::types::Field64;
 pub use plonky2::field::types::PrimeField64;
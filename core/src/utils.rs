@@ -44,3 +44,16 @@ pub fn serialize_tree_leaf(leaf: [GoldilocksField; TREE_VALUE_LEN]) -> Vec<u8> {
     }
     bytes
 }
+
+/// Floor of the square root of `value`.
+pub fn isqrt(value: u64) -> u64 {
+    let value = value as u128;
+    let mut root = (value as f64).sqrt() as u128;
+    while root * root > value {
+        root -= 1;
+    }
+    while (root + 1) * (root + 1) <= value {
+        root += 1;
+    }
+    root as u64
+}
@@ -16,6 +16,21 @@ pub enum ProcessorError {
     #[error("Interpreter run error: {0}")]
     InterpreterRunError(String),
 
+    #[error("Prophet at pc {0} failed to compile: {1}")]
+    InterpreterCompileError(usize, String),
+
+    #[error("Prophet at pc {0} exceeded the step limit {1}")]
+    ProphetStepLimitExceeded(usize, u64),
+
+    #[error("Prophet at pc {0} exceeded the call depth limit {1}")]
+    ProphetCallDepthExceeded(usize, usize),
+
+    #[error("Prophet at pc {0} exceeded the malloc limit {1}")]
+    ProphetMemoryLimitExceeded(usize, u64),
+
+    #[error("Prophet at pc {0} has {1} outputs, limit: {2}")]
+    ProphetOutputLimitExceeded(usize, usize, usize),
+
     #[error("U32 range check fail, value out range")]
     U32RangeCheckFail,
 
@@ -100,6 +115,9 @@ pub enum ProcessorError {
     #[error("Too many cpu lifecycle: {0}")]
     CpuLifeCycleOverflow(u64),
 
+    #[error("Out of gas, gas limit: {0}, gas required: {1}")]
+    OutOfGas(u64, u64),
+
     #[error("Cannot sstore in call")]
     StorageStoreOnCallError,
 
@@ -438,6 +438,8 @@ impl OlaMemory {
 pub struct OlaTape {
     tp: u64,
     addr_to_value: HashMap<u64, u64>,
+    // tp of every open call frame.
+    checkpoints: Vec<u64>,
 }
 
 impl Default for OlaTape {
@@ -445,6 +447,7 @@ impl Default for OlaTape {
         Self {
             tp: 0,
             addr_to_value: Default::default(),
+            checkpoints: Vec::new(),
         }
     }
 }
@@ -465,6 +468,26 @@ impl OlaTape {
         }
     }
 
+    /// Opens a new call frame. Values written after this point can be dropped
+    /// by `revert_to_checkpoint`.
+    pub fn checkpoint(&mut self) {
+        self.checkpoints.push(self.tp);
+    }
+
+    /// Closes the innermost call frame and keeps its writes.
+    pub fn commit_checkpoint(&mut self) {
+        self.checkpoints.pop();
+    }
+
+    /// Closes the innermost call frame and drops every value it wrote. tp is
+    /// not rewound: the steps of the reverted frame stay in the trace, and the
+    /// tape table needs every address to be written once.
+    pub fn revert_to_checkpoint(&mut self) {
+        if let Some(tp) = self.checkpoints.pop() {
+            self.addr_to_value.retain(|addr, _| *addr < tp);
+        }
+    }
+
     pub fn read_top(&self, addr: u64) -> anyhow::Result<u64> {
         match self.addr_to_value.get(&addr).copied() {
             Some(v) => Ok(v),
@@ -582,6 +605,23 @@ mod tests {
         assert_eq!(res, 1049);
     }
 
+    #[test]
+    fn test_tape_checkpoint() {
+        let mut tape = OlaTape::default();
+        tape.write(1);
+        tape.checkpoint();
+        tape.write(2);
+        tape.checkpoint();
+        tape.write(3);
+        tape.commit_checkpoint();
+        tape.revert_to_checkpoint();
+        assert_eq!(tape.read_top(0).unwrap(), 1);
+        assert!(tape.read_top(1).is_err());
+        assert!(tape.read_top(2).is_err());
+        tape.write(4);
+        assert_eq!(tape.read_top(3).unwrap(), 4);
+    }
+
     #[test]
     fn test_memory() {
         let mut mem = OlaMemory::default();
@@ -2,6 +2,7 @@ pub mod error;
 pub mod hardware;
 pub mod heap;
 pub mod memory;
+pub mod native_prophet;
 pub mod opcodes;
 pub mod operands;
 pub mod transaction;
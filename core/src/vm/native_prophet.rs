@@ -0,0 +1,273 @@
+use crate::{
+    program::binary_program::OlaProphet,
+    types::{Field, GoldilocksField, PrimeField64},
+    util::converts::u32s_be_to_u256,
+    utils,
+};
+use ethereum_types::U256;
+use plonky2::field::types::Field64;
+
+/// Maps the flattened prophet inputs to `output_len` output values.
+pub type NativeProphetFn = fn(&[u64], usize) -> Result<Vec<u64>, String>;
+
+/// Native prophets, selected by `OlaProphet::native`. u256 values are passed as
+/// 8 big-endian u32 limbs, like the rest of the vm.
+const NATIVE_PROPHETS: &[(&str, NativeProphetFn)] = &[
+    ("felt_inverse", felt_inverse),
+    ("u32_div", u32_div),
+    ("u32_mod", u32_mod),
+    ("u64_div", u64_div),
+    ("u64_mod", u64_mod),
+    ("u256_div_mod", u256_div_mod),
+    ("bit_decompose", bit_decompose),
+    ("isqrt", isqrt),
+    ("mod_exp", mod_exp),
+    ("sort", sort),
+];
+
+pub fn get_native_prophet(name: &str) -> Option<NativeProphetFn> {
+    NATIVE_PROPHETS
+        .iter()
+        .find(|(native_name, _)| *native_name == name)
+        .map(|(_, run)| *run)
+}
+
+/// Runs the native prophet named by `prophet` and checks the result against its
+/// declared outputs, the values are written to the prophet memory region by the
+/// executor.
+pub fn run_native_prophet(prophet: &OlaProphet, inputs: &[u64]) -> Result<Vec<u64>, String> {
+    let name = prophet
+        .native
+        .as_deref()
+        .ok_or_else(|| format!("prophet at pc {} is not native", prophet.host))?;
+    let run = get_native_prophet(name).ok_or_else(|| format!("unknown native prophet {}", name))?;
+    let output_len = prophet.outputs.iter().map(|output| output.length).sum();
+    let outputs = run(inputs, output_len)?;
+    if outputs.len() != output_len {
+        return Err(format!(
+            "native prophet {} returns {} values, {} outputs declared",
+            name,
+            outputs.len(),
+            output_len
+        ));
+    }
+    Ok(outputs)
+}
+
+fn expect_inputs(name: &str, inputs: &[u64], len: usize) -> Result<(), String> {
+    if inputs.len() != len {
+        return Err(format!(
+            "{} expects {} inputs, got {}",
+            name,
+            len,
+            inputs.len()
+        ));
+    }
+    Ok(())
+}
+
+fn expect_u32(name: &str, value: u64) -> Result<u64, String> {
+    if value > u32::MAX as u64 {
+        return Err(format!("{}: {} is not a u32", name, value));
+    }
+    Ok(value)
+}
+
+fn expect_divisor(name: &str, value: u64) -> Result<u64, String> {
+    if value == 0 {
+        return Err(format!("{}: division by zero", name));
+    }
+    Ok(value)
+}
+
+fn felt_inverse(inputs: &[u64], _output_len: usize) -> Result<Vec<u64>, String> {
+    expect_inputs("felt_inverse", inputs, 1)?;
+    let inverse = GoldilocksField::from_noncanonical_u64(inputs[0])
+        .try_inverse()
+        .ok_or("felt_inverse: zero has no inverse")?;
+    Ok(vec![inverse.to_canonical_u64()])
+}
+
+fn u32_div(inputs: &[u64], _output_len: usize) -> Result<Vec<u64>, String> {
+    expect_inputs("u32_div", inputs, 2)?;
+    let a = expect_u32("u32_div", inputs[0])?;
+    let b = expect_divisor("u32_div", expect_u32("u32_div", inputs[1])?)?;
+    Ok(vec![a / b])
+}
+
+fn u32_mod(inputs: &[u64], _output_len: usize) -> Result<Vec<u64>, String> {
+    expect_inputs("u32_mod", inputs, 2)?;
+    let a = expect_u32("u32_mod", inputs[0])?;
+    let b = expect_divisor("u32_mod", expect_u32("u32_mod", inputs[1])?)?;
+    Ok(vec![a % b])
+}
+
+fn u64_div(inputs: &[u64], _output_len: usize) -> Result<Vec<u64>, String> {
+    expect_inputs("u64_div", inputs, 2)?;
+    Ok(vec![inputs[0] / expect_divisor("u64_div", inputs[1])?])
+}
+
+fn u64_mod(inputs: &[u64], _output_len: usize) -> Result<Vec<u64>, String> {
+    expect_inputs("u64_mod", inputs, 2)?;
+    Ok(vec![inputs[0] % expect_divisor("u64_mod", inputs[1])?])
+}
+
+fn u256_from_limbs(limbs: &[u64]) -> Result<U256, String> {
+    let limbs: [u64; 8] = limbs
+        .try_into()
+        .map_err(|_| "u256 needs 8 limbs".to_string())?;
+    u32s_be_to_u256(limbs).map_err(|err| format!("u256_div_mod: {}", err))
+}
+
+fn u256_to_limbs(value: U256) -> Vec<u64> {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes
+        .chunks(4)
+        .map(|chunk| u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as u64)
+        .collect()
+}
+
+/// Inputs are the dividend and the divisor, outputs the quotient and the
+/// remainder.
+fn u256_div_mod(inputs: &[u64], _output_len: usize) -> Result<Vec<u64>, String> {
+    expect_inputs("u256_div_mod", inputs, 16)?;
+    let a = u256_from_limbs(&inputs[..8])?;
+    let b = u256_from_limbs(&inputs[8..])?;
+    if b.is_zero() {
+        return Err("u256_div_mod: division by zero".to_string());
+    }
+    let (quotient, remainder) = a.div_mod(b);
+    let mut outputs = u256_to_limbs(quotient);
+    outputs.extend(u256_to_limbs(remainder));
+    Ok(outputs)
+}
+
+/// Little-endian bits of the input, as many as outputs are declared.
+fn bit_decompose(inputs: &[u64], output_len: usize) -> Result<Vec<u64>, String> {
+    expect_inputs("bit_decompose", inputs, 1)?;
+    let value = inputs[0];
+    if output_len < 64 && value >> output_len != 0 {
+        return Err(format!(
+            "bit_decompose: {} does not fit in {} bits",
+            value, output_len
+        ));
+    }
+    Ok((0..output_len)
+        .map(|i| if i < 64 { (value >> i) & 1 } else { 0 })
+        .collect())
+}
+
+fn isqrt(inputs: &[u64], _output_len: usize) -> Result<Vec<u64>, String> {
+    expect_inputs("isqrt", inputs, 1)?;
+    Ok(vec![utils::isqrt(inputs[0])])
+}
+
+/// Inputs are the base, the exponent and the modulus.
+fn mod_exp(inputs: &[u64], _output_len: usize) -> Result<Vec<u64>, String> {
+    expect_inputs("mod_exp", inputs, 3)?;
+    let modulus = expect_divisor("mod_exp", inputs[2])? as u128;
+    let mut base = inputs[0] as u128 % modulus;
+    let mut exponent = inputs[1];
+    let mut result = 1 % modulus;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent >>= 1;
+    }
+    Ok(vec![result as u64])
+}
+
+fn sort(inputs: &[u64], _output_len: usize) -> Result<Vec<u64>, String> {
+    let mut sorted = inputs.to_vec();
+    sorted.sort_unstable();
+    Ok(sorted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::program::binary_program::{OlaProphetInput, OlaProphetOutput};
+
+    fn native(name: &str, inputs: usize, outputs: usize) -> OlaProphet {
+        OlaProphet {
+            host: 0,
+            code: String::new(),
+            ctx: vec![],
+            inputs: vec![OlaProphetInput {
+                name: "cid.in".to_string(),
+                length: inputs,
+                is_ref: false,
+                is_input_output: false,
+            }],
+            outputs: vec![OlaProphetOutput {
+                name: "cid.out".to_string(),
+                length: outputs,
+                is_ref: false,
+                is_input_output: false,
+            }],
+            native: Some(name.to_string()),
+        }
+    }
+
+    fn run(name: &str, inputs: &[u64], outputs: usize) -> Result<Vec<u64>, String> {
+        run_native_prophet(&native(name, inputs.len(), outputs), inputs)
+    }
+
+    #[test]
+    fn test_arithmetic_hints() {
+        let inverse = run("felt_inverse", &[7], 1).unwrap()[0];
+        assert_eq!(
+            GoldilocksField::from_canonical_u64(7) * GoldilocksField::from_canonical_u64(inverse),
+            GoldilocksField::ONE
+        );
+        assert!(run("felt_inverse", &[0], 1).is_err());
+
+        assert_eq!(run("u32_div", &[17, 5], 1).unwrap(), vec![3]);
+        assert_eq!(run("u32_mod", &[17, 5], 1).unwrap(), vec![2]);
+        assert!(run("u32_div", &[1 << 32, 5], 1).is_err());
+        assert!(run("u32_mod", &[17, 0], 1).is_err());
+        assert_eq!(
+            run("u64_div", &[1 << 40, 3], 1).unwrap(),
+            vec![(1 << 40) / 3]
+        );
+        assert_eq!(
+            run("u64_mod", &[1 << 40, 3], 1).unwrap(),
+            vec![(1 << 40) % 3]
+        );
+
+        assert_eq!(run("isqrt", &[0], 1).unwrap(), vec![0]);
+        assert_eq!(run("isqrt", &[99], 1).unwrap(), vec![9]);
+        assert_eq!(run("isqrt", &[u64::MAX], 1).unwrap(), vec![u32::MAX as u64]);
+
+        assert_eq!(run("mod_exp", &[3, 200, 1000], 1).unwrap(), vec![1]);
+        assert_eq!(run("mod_exp", &[5, 0, 1], 1).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn test_u256_div_mod() {
+        // 2^128 + 5 divided by 2^64.
+        let a = [0, 0, 0, 1, 0, 0, 0, 5];
+        let b = [0, 0, 0, 0, 0, 1, 0, 0];
+        let inputs: Vec<u64> = a.iter().chain(b.iter()).cloned().collect();
+        let outputs = run("u256_div_mod", &inputs, 16).unwrap();
+        assert_eq!(outputs[..8], [0, 0, 0, 0, 0, 1, 0, 0]);
+        assert_eq!(outputs[8..], [0, 0, 0, 0, 0, 0, 0, 5]);
+        assert!(run("u256_div_mod", &[0; 16], 16).is_err());
+    }
+
+    #[test]
+    fn test_bits_and_sort() {
+        assert_eq!(run("bit_decompose", &[6], 4).unwrap(), vec![0, 1, 1, 0]);
+        assert!(run("bit_decompose", &[16], 4).is_err());
+        assert_eq!(run("sort", &[3, 1, 2], 3).unwrap(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_declared_outputs() {
+        assert!(run("u32_div", &[17, 5], 2).is_err());
+        assert!(run("no_such_hint", &[1], 1).is_err());
+    }
+}
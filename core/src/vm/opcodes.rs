@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-#[derive(Debug, Copy, Sequence, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Copy, Sequence, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum OlaOpcode {
     ADD,
     MUL,
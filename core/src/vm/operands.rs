@@ -175,7 +175,7 @@ impl FromStr for ImmediateValue {
         let value = i128::from_str_radix(s, 10)
             .map_err(|_| format!("Immediate is not a valid number: {}", s))?;
         let signed_order = ImmediateValue::ORDER as i128;
-        if value >= signed_order || value * -1 >= signed_order {
+        if value.unsigned_abs() >= signed_order as u128 {
             return Err(format!("Immediate overflow: {}", s));
         }
         let actual_value = if value < 0 {
@@ -11,7 +11,7 @@ use std::mem;
 use anyhow::Ok;
 
 use crate::{
-    config::{ExecuteMode, ADDR_U64_ENTRYPOINT},
+    config::{ExecuteMode, GasSchedule, ProphetLimitsConfig, ADDR_U64_ENTRYPOINT},
     ola_storage::OlaCachedStorage,
     tx_exe_manager::{OlaTapeInitInfo, TxExeManager},
 };
@@ -26,10 +26,13 @@ pub struct BlockExeInfo {
 
 pub struct BlockExeManager {
     block_info: BlockExeInfo,
+    gas_schedule: GasSchedule,
+    prophet_limits: ProphetLimitsConfig,
     storage: OlaCachedStorage,
     tx_traces: Vec<TxExeTrace>,
     storage_access_logs: Vec<StorageAccessLog>,
     events: Vec<Event>,
+    tx_events_len: Vec<usize>,
 }
 
 impl BlockExeManager {
@@ -49,13 +52,24 @@ impl BlockExeManager {
         let storage = OlaCachedStorage::new(storage_db_path, Some(block_timestamp))?;
         Ok(Self {
             block_info,
+            gas_schedule: GasSchedule::default(),
+            prophet_limits: ProphetLimitsConfig::default(),
             storage,
             tx_traces: vec![],
             storage_access_logs: vec![],
             events: vec![],
+            tx_events_len: vec![],
         })
     }
 
+    pub fn set_gas_schedule(&mut self, gas_schedule: GasSchedule) {
+        self.gas_schedule = gas_schedule;
+    }
+
+    pub fn set_prophet_limits(&mut self, prophet_limits: ProphetLimitsConfig) {
+        self.prophet_limits = prophet_limits;
+    }
+
     pub fn invoke(&mut self, tx: OlaTapeInitInfo) -> anyhow::Result<TxResult> {
         self.storage.clear_tx_cache();
         let mut tx_exe_manager: TxExeManager = TxExeManager::new(
@@ -66,20 +80,44 @@ impl BlockExeManager {
             ADDR_U64_ENTRYPOINT,
             self.events.len(),
         );
+        tx_exe_manager.set_gas_schedule(self.gas_schedule.clone());
+        tx_exe_manager.set_prophet_limits(self.prophet_limits.limits(ExecuteMode::Invoke));
         let result = tx_exe_manager.invoke()?;
         self.storage.on_tx_success();
         self.on_tx_success(result.clone());
         Ok(result)
     }
 
+    /// Runs `to` read-only: the writes of the call are dropped, the batch state
+    /// is left as it was.
+    pub fn call(&mut self, tx: OlaTapeInitInfo, to: ContractAddress) -> anyhow::Result<Vec<u64>> {
+        self.storage.clear_tx_cache();
+        let mut tx_exe_manager: TxExeManager = TxExeManager::new(
+            ExecuteMode::Call,
+            self.block_info.clone(),
+            tx,
+            &mut self.storage,
+            to,
+            self.events.len(),
+        );
+        tx_exe_manager.set_gas_schedule(self.gas_schedule.clone());
+        tx_exe_manager.set_prophet_limits(self.prophet_limits.limits(ExecuteMode::Call));
+        let result = tx_exe_manager.call();
+        self.storage.clear_tx_cache();
+        result
+    }
+
     fn on_tx_success(&mut self, tx_result: TxResult) {
         self.tx_traces.push(tx_result.trace);
         self.storage_access_logs
             .extend(tx_result.storage_access_logs);
+        self.tx_events_len.push(tx_result.events.len());
         self.events.extend(tx_result.events);
     }
 
     pub fn finish_batch(&mut self) -> anyhow::Result<BatchResult> {
+        // Drop what a failed transaction left in the tx cache.
+        self.storage.clear_tx_cache();
         // todo version
         let tx = OlaTapeInitInfo {
             version: 0,
@@ -89,6 +127,7 @@ impl BlockExeManager {
             signature_r: None,
             signature_s: None,
             tx_hash: None,
+            gas_limit: None,
         };
         let mut tx_exe_manager: TxExeManager = TxExeManager::new(
             ExecuteMode::Invoke,
@@ -98,6 +137,8 @@ impl BlockExeManager {
             ADDR_U64_ENTRYPOINT,
             self.events.len(),
         );
+        tx_exe_manager.set_gas_schedule(self.gas_schedule.clone());
+        tx_exe_manager.set_prophet_limits(self.prophet_limits.limits(ExecuteMode::Invoke));
         let result = tx_exe_manager.invoke()?;
         let block_tip_queries = self.storage.get_tx_storage_access_logs();
         self.storage.on_tx_success();
@@ -106,6 +147,7 @@ impl BlockExeManager {
             tx_traces: mem::replace(&mut self.tx_traces, Vec::new()),
             storage_access_logs: mem::replace(&mut self.storage_access_logs, Vec::new()),
             events: mem::replace(&mut self.events, Vec::new()),
+            tx_events_len: mem::replace(&mut self.tx_events_len, Vec::new()),
             block_tip_queries,
         })
     }
@@ -1,4 +1,7 @@
-use core::vm::hardware::ContractAddress;
+use core::vm::{hardware::ContractAddress, opcodes::OlaOpcode};
+use enum_iterator::all;
+use interpreter::interpreter::ProphetLimits;
+use std::collections::HashMap;
 
 pub const ADDR_U64_ENTRYPOINT: ContractAddress = [0, 0, 0, 32769];
 pub const ADDR_U64_CODE_STORAGE: ContractAddress = [0, 0, 0, 32770];
@@ -10,10 +13,85 @@ pub const ADDR_U64_SYSTEM_CONTEXT: ContractAddress = [0, 0, 0, 32779];
 
 pub const FUNCTION_SELECTOR_SYSTEM_ENTRANCE: u64 = 3234502684;
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// Written to the tape in place of the return data length when a sccall
+/// callee fails. It is the field element -1, which no real return data length
+/// can reach, so the caller can tell a reverted callee from a normal return.
+pub const SCCALL_RETURN_LEN_REVERTED: u64 = 0xFFFFFFFF00000000;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum ExecuteMode {
     Invoke,
     Call,
     PreExecute,
     Debug,
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GasSchedule {
+    costs: HashMap<OlaOpcode, u64>,
+    // Charged per prophet evaluation step, native prophets per output value.
+    prophet_step: u64,
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        let costs = all::<OlaOpcode>()
+            .map(|opcode| {
+                let cost = match opcode {
+                    OlaOpcode::POSEIDON => 20,
+                    OlaOpcode::SLOAD => 50,
+                    OlaOpcode::SSTORE => 100,
+                    OlaOpcode::SCCALL => 100,
+                    OlaOpcode::SIGCHECK => 200,
+                    _ => 1,
+                };
+                (opcode, cost)
+            })
+            .collect();
+        Self {
+            costs,
+            prophet_step: 1,
+        }
+    }
+}
+
+impl GasSchedule {
+    pub fn new(costs: HashMap<OlaOpcode, u64>) -> Self {
+        let mut schedule = Self::default();
+        schedule.costs.extend(costs);
+        schedule
+    }
+
+    pub fn set_cost(&mut self, opcode: OlaOpcode, cost: u64) {
+        self.costs.insert(opcode, cost);
+    }
+
+    pub fn cost(&self, opcode: OlaOpcode) -> u64 {
+        self.costs.get(&opcode).copied().unwrap_or(1)
+    }
+
+    pub fn set_prophet_step_cost(&mut self, cost: u64) {
+        self.prophet_step = cost;
+    }
+
+    pub fn prophet_step_cost(&self) -> u64 {
+        self.prophet_step
+    }
+}
+
+/// Prophet resource limits of each `ExecuteMode`, modes without an entry use
+/// `ProphetLimits::default()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProphetLimitsConfig {
+    limits: HashMap<ExecuteMode, ProphetLimits>,
+}
+
+impl ProphetLimitsConfig {
+    pub fn set_limits(&mut self, mode: ExecuteMode, limits: ProphetLimits) {
+        self.limits.insert(mode, limits);
+    }
+
+    pub fn limits(&self, mode: ExecuteMode) -> ProphetLimits {
+        self.limits.get(&mode).copied().unwrap_or_default()
+    }
+}
@@ -2,11 +2,12 @@ use core::{
     crypto::poseidon_trace::calculate_arbitrary_poseidon_u64s,
     program::{
         binary_program::{BinaryInstruction, BinaryProgram, OlaProphet},
+        debug_info::OlaDebugInfo,
         decoder::decode_binary_program_to_instructions,
     },
     trace::exe_trace::{
-        CpuExePiece, CpuPieceAuxSCCall, ExeTraceStepDiff, MemExePiece, PoseidonPiece, RcExePiece,
-        StorageExePiece, TapeExePiece,
+        CpuExePiece, CpuPieceAuxSCCall, EventPiece, ExeTraceStepDiff, MemExePiece, PoseidonPiece,
+        RcExePiece, SigCheckPiece, StorageExePiece, TapeExePiece,
     },
     types::{Field, GoldilocksField, PrimeField64},
     vm::{
@@ -15,6 +16,8 @@ use core::{
             ContractAddress, ExeContext, OlaMemory, OlaRegister, OlaSpecialRegister, OlaStorage,
             OlaTape, NUM_GENERAL_PURPOSE_REGISTER,
         },
+        heap::HEAP_PTR,
+        native_prophet::run_native_prophet,
         opcodes::OlaOpcode,
         operands::OlaOperand,
         types::Hash,
@@ -26,12 +29,17 @@ use core::{
 use std::{collections::HashMap, vec};
 
 use anyhow::Ok;
-use interpreter::{interpreter::Interpreter, utils::number::NumberRet};
-use regex::Regex;
+use interpreter::{interpreter::ProphetLimits, utils::number::NumberRet};
 
 use crate::{
-    config::ExecuteMode, ecdsa::msg_ecdsa_verify, exe_trace::tx::TxTraceManager,
-    ola_storage::OlaCachedStorage, tx_exe_manager::TxEventManager,
+    config::ExecuteMode,
+    debugger::OlaDebugger,
+    ecdsa::msg_ecdsa_verify,
+    exe_trace::tx::TxTraceManager,
+    ola_storage::OlaCachedStorage,
+    profiler::OlaProfiler,
+    prophet::CompiledProphets,
+    tx_exe_manager::{TxEventManager, TxGasManager},
 };
 
 const MAX_CLK: u64 = 1000_000_000_000;
@@ -53,6 +61,11 @@ pub(crate) struct OlaContractExecutor {
     registers: [u64; NUM_GENERAL_PURPOSE_REGISTER],
     memory: OlaMemory,
     instructions: HashMap<u64, BinaryInstruction>,
+    prophets: CompiledProphets,
+    prophet_limits: ProphetLimits,
+    debug_info: Option<OlaDebugInfo>,
+    // pcs of the call instructions not returned yet.
+    call_stack: Vec<u64>,
     output: Vec<u64>,
     state: OlaContractExecutorState,
 }
@@ -62,7 +75,10 @@ impl OlaContractExecutor {
         mode: ExecuteMode,
         context: ExeContext,
         program: BinaryProgram,
+        prophets: CompiledProphets,
+        prophet_limits: ProphetLimits,
     ) -> anyhow::Result<Self> {
+        let debug_info = program.debug_info.clone();
         let instructions = decode_binary_program_to_instructions(program);
         match instructions {
             Result::Ok(instruction_vec) => {
@@ -89,6 +105,10 @@ impl OlaContractExecutor {
                     registers: [0; NUM_GENERAL_PURPOSE_REGISTER],
                     memory,
                     instructions,
+                    prophets,
+                    prophet_limits,
+                    debug_info,
+                    call_stack: vec![],
                     output: vec![],
                     state: OlaContractExecutorState::Running,
                 })
@@ -113,6 +133,18 @@ impl OlaContractExecutor {
         self.registers
     }
 
+    pub fn get_memory(&self) -> &OlaMemory {
+        &self.memory
+    }
+
+    pub fn get_debug_info(&self) -> Option<&OlaDebugInfo> {
+        self.debug_info.as_ref()
+    }
+
+    pub fn get_call_stack(&self) -> &[u64] {
+        &self.call_stack
+    }
+
     pub fn get_code_addr(&self) -> ContractAddress {
         self.context.code_addr
     }
@@ -121,51 +153,35 @@ impl OlaContractExecutor {
         self.context.storage_addr
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn resume(
         &mut self,
         tape: &mut OlaTape,
         tx_event_manager: &mut TxEventManager,
+        tx_gas_manager: &mut TxGasManager,
         storage: &mut OlaCachedStorage,
         trace_manager: &mut TxTraceManager,
+        mut debugger: Option<&mut OlaDebugger>,
+        mut profiler: Option<&mut OlaProfiler>,
     ) -> anyhow::Result<OlaContractExecutorState> {
         loop {
             if let Some(instruction) = self.instructions.get(&self.pc) {
                 let instruction = instruction.clone();
 
-                // println!(
-                //     "↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓ clk: {}, pc: {}, tp: {}, psp: {}, {}
-                // ↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓↓",
-                //     self.clk,
-                //     self.pc,
-                //     tape.tp(),
-                //     self.memory.psp(),
-                //     instruction.get_asm_form_code()
-                // );
-                // if instruction.prophet.is_some() {
-                //     println!("prophet: {:?}", instruction.prophet);
-                // }
-                // println!("--------------- registers ---------------");
-                // self.registers
-                //     .into_iter()
-                //     .enumerate()
-                //     .for_each(|(index, value)| {
-                //         print!("r{}({}), ", index, value);
-                //     });
-                // println!();
-                // println!("--------------- memory ---------------");
-                // self.memory.dump();
-                // println!("--------------- tape ---------------");
-                // tape.dump();
-                // println!("--------------- storage ---------------");
-                // storage.dump_tx();
-                // println!();
+                if self.mode == ExecuteMode::Debug {
+                    if let Some(debugger) = debugger.as_deref_mut() {
+                        debugger.on_step(self, &instruction, tape, storage)?;
+                    }
+                }
 
                 let step_result = self.run_one_step(
                     instruction.clone(),
                     tape,
                     tx_event_manager,
+                    tx_gas_manager,
                     storage,
                     trace_manager,
+                    profiler.as_deref_mut(),
                 );
                 if step_result.is_ok() {
                     match step_result.unwrap() {
@@ -187,8 +203,8 @@ impl OlaContractExecutor {
                     }
                 } else {
                     let err = step_result.err().unwrap();
-                    self.on_step_err(instruction, tape, storage, err.to_string());
-                    return Err(err.into());
+                    let backtrace = self.on_step_err(instruction, tape, storage, err.to_string());
+                    return Err(err.context(backtrace));
                 }
             } else {
                 return Err(ProcessorError::PcVistInv(self.pc).into());
@@ -196,15 +212,20 @@ impl OlaContractExecutor {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn run_one_step(
         &mut self,
         instruction: BinaryInstruction,
         tape: &mut OlaTape,
         tx_event_manager: &mut TxEventManager,
+        tx_gas_manager: &mut TxGasManager,
         storage: &mut OlaCachedStorage,
         trace_manager: &mut TxTraceManager,
+        profiler: Option<&mut OlaProfiler>,
     ) -> anyhow::Result<OlaContractExecutorState> {
         let opcode = instruction.opcode;
+        let pc = self.pc;
+        tx_gas_manager.consume(opcode)?;
         // cache sccall params
         let sccall_is_delegate_callee = if opcode == OlaOpcode::SCCALL {
             let (op0, op1) = self.get_op0_op1(instruction.clone())?;
@@ -218,8 +239,12 @@ impl OlaContractExecutor {
             None
         };
 
-        let trace_diff = self.process_step(instruction, tape, tx_event_manager, storage)?;
+        let trace_diff =
+            self.process_step(instruction, tape, tx_event_manager, tx_gas_manager, storage)?;
         self.clk += 1;
+        if let Some(profiler) = profiler {
+            profiler.on_step(self, opcode, trace_diff.as_ref());
+        }
         if let Some(step_diff) = trace_diff {
             trace_manager.on_step(step_diff);
         }
@@ -242,6 +267,14 @@ impl OlaContractExecutor {
                 self.state = state.clone();
                 Ok(state)
             }
+            OlaOpcode::CALL => {
+                self.call_stack.push(pc);
+                Ok(OlaContractExecutorState::Running)
+            }
+            OlaOpcode::RET => {
+                self.call_stack.pop();
+                Ok(OlaContractExecutorState::Running)
+            }
             _ => Ok(OlaContractExecutorState::Running),
         }
     }
@@ -297,6 +330,7 @@ impl OlaContractExecutor {
         instruction: BinaryInstruction,
         tape: &mut OlaTape,
         tx_event_manager: &mut TxEventManager,
+        tx_gas_manager: &mut TxGasManager,
         storage: &mut OlaCachedStorage,
     ) -> anyhow::Result<Option<ExeTraceStepDiff>> {
         let tp = tape.tp();
@@ -333,7 +367,7 @@ impl OlaContractExecutor {
         self.apply_state_diff(tape, storage, state_diff)?;
 
         if let Some(prophet) = prophet_attached {
-            let (exe_mem_diffs, trace_mem_diffs) = self.process_prophet(prophet)?;
+            let (exe_mem_diffs, trace_mem_diffs) = self.process_prophet(prophet, tx_gas_manager)?;
             let state_diff = vec![OlaStateDiff::Memory(exe_mem_diffs)];
             self.apply_state_diff(tape, storage, state_diff)?;
             if trace_diff.is_some() {
@@ -356,6 +390,8 @@ impl OlaContractExecutor {
                     poseidon: None,
                     storage: None,
                     tape: None,
+                    sigcheck: None,
+                    event: None,
                 });
                 return Ok(trace_diff);
             }
@@ -447,7 +483,13 @@ impl OlaContractExecutor {
                 OlaOperand::RegisterOperand { register } => {
                     (self.registers[register.index() as usize], register)
                 }
-                _ => todo!(),
+                _ => {
+                    return Err(ProcessorError::InvalidInstruction(format!(
+                        "op1 must be a register for {}",
+                        instruction.opcode
+                    ))
+                    .into())
+                }
             },
             None => {
                 return Err(ProcessorError::InvalidInstruction(format!(
@@ -699,6 +741,8 @@ impl OlaContractExecutor {
                 poseidon: None,
                 tape: None,
                 storage: None,
+                sigcheck: None,
+                event: None,
             })
         } else {
             None
@@ -769,6 +813,8 @@ impl OlaContractExecutor {
                 poseidon: None,
                 tape: None,
                 storage: None,
+                sigcheck: None,
+                event: None,
             })
         } else {
             None
@@ -1000,6 +1046,8 @@ impl OlaContractExecutor {
                     pre_value: Some(value),
                     value,
                 }),
+                sigcheck: None,
+                event: None,
             })
         } else {
             None
@@ -1095,6 +1143,8 @@ impl OlaContractExecutor {
                     pre_value,
                     value,
                 }),
+                sigcheck: None,
+                event: None,
             })
         } else {
             None
@@ -1188,6 +1238,8 @@ impl OlaContractExecutor {
                         .collect(),
                 ),
                 storage: None,
+                sigcheck: None,
+                event: None,
             })
         } else {
             None
@@ -1262,6 +1314,8 @@ impl OlaContractExecutor {
                         .collect(),
                 ),
                 storage: None,
+                sigcheck: None,
+                event: None,
             })
         } else {
             None
@@ -1351,6 +1405,8 @@ impl OlaContractExecutor {
                         .collect(),
                 ),
                 storage: None,
+                sigcheck: None,
+                event: None,
             })
         } else {
             None
@@ -1385,9 +1441,33 @@ impl OlaContractExecutor {
         let verified = msg_ecdsa_verify(msg_hash, pubkey_x, pubkey_y, r, s)?;
         let res = if verified { 1 } else { 0 };
         let state_diff = self.get_state_diff_only_dst_reg(inst_len, dst_reg, res);
-        // todo ecdsa piece
         let trace_diff = if self.is_trace_needed() {
-            Some(self.get_trace_diff_with_cpu(instruction, tp, None, Some(op1), Some(res))?)
+            let mut diff =
+                self.get_trace_diff_with_cpu(instruction, tp, None, Some(op1), Some(res))?;
+            diff.mem = Some(
+                (op1..op1 + 20)
+                    .zip(params.iter())
+                    .map(|(addr, value)| MemExePiece {
+                        clk: self.clk,
+                        addr,
+                        value: *value,
+                        is_write: false,
+                        opcode: Some(opcode),
+                    })
+                    .collect(),
+            );
+            diff.sigcheck = Some(SigCheckPiece {
+                env_idx: 0, // env_idx will be set in TxTraceManager
+                clk: self.clk,
+                params_addr: op1,
+                msg_hash,
+                pubkey_x,
+                pubkey_y,
+                r,
+                s,
+                verified,
+            });
+            Some(diff)
         } else {
             None
         };
@@ -1401,6 +1481,7 @@ impl OlaContractExecutor {
         tx_event_manager: &mut TxEventManager,
     ) -> anyhow::Result<(Vec<OlaStateDiff>, Option<ExeTraceStepDiff>)> {
         let inst_len = instruction.binary_length();
+        let opcode = instruction.opcode;
         if self.mode == ExecuteMode::Call {
             return Err(ProcessorError::EventOnCallError.into());
         }
@@ -1408,25 +1489,57 @@ impl OlaContractExecutor {
         let topic_len = self.memory.read(op0)?;
         let topic_start_addrs = self.memory.batch_read(op0 + 1, topic_len as u64)?;
         let mut topics: Vec<Hash> = vec![];
-        for addr in topic_start_addrs {
-            let topic = self.memory.batch_read(addr, 4)?;
+        for addr in topic_start_addrs.iter() {
+            let topic = self.memory.batch_read(*addr, 4)?;
             topics.push(topic.try_into().expect("Hash slice with incorrect length"));
         }
 
         let data_length = self.memory.read(op1)?;
         let data = self.memory.batch_read(op1 + 1, data_length)?;
 
+        let trace_diff = if self.is_trace_needed() {
+            let mem_read = |addr: u64, value: u64| MemExePiece {
+                clk: self.clk,
+                addr,
+                value,
+                is_write: false,
+                opcode: Some(opcode),
+            };
+            let mut mem = vec![mem_read(op0, topic_len)];
+            for (i, (addr, topic)) in topic_start_addrs.iter().zip(topics.iter()).enumerate() {
+                mem.push(mem_read(op0 + 1 + i as u64, *addr));
+                mem.extend((0..4).map(|j| mem_read(addr + j, topic[j as usize])));
+            }
+            mem.push(mem_read(op1, data_length));
+            mem.extend(
+                (op1 + 1..)
+                    .zip(data.iter())
+                    .map(|(addr, v)| mem_read(addr, *v)),
+            );
+
+            let mut diff =
+                self.get_trace_diff_with_cpu(instruction, tp, Some(op0), Some(op1), None)?;
+            diff.mem = Some(mem);
+            diff.event = Some(EventPiece {
+                env_idx: 0, // env_idx will be set in TxTraceManager
+                clk: self.clk,
+                topics_addr: op0,
+                data_addr: op1,
+                topic_addrs: topic_start_addrs,
+                topics: topics.clone(),
+                data: data.clone(),
+            });
+            Some(diff)
+        } else {
+            None
+        };
+
         tx_event_manager.on_event(topics, data);
         let spec_reg_diff = OlaStateDiff::SpecReg(SpecRegisterDiff {
             pc: Some(self.pc + inst_len as u64),
         });
 
         let state_diff = vec![spec_reg_diff];
-        let trace_diff = if self.is_trace_needed() {
-            Some(self.get_trace_diff_with_cpu(instruction, tp, Some(op0), Some(op1), None)?)
-        } else {
-            None
-        };
         Ok((state_diff, trace_diff))
     }
 
@@ -1635,24 +1748,68 @@ impl OlaContractExecutor {
     fn process_prophet(
         &mut self,
         prophet: OlaProphet,
+        tx_gas_manager: &mut TxGasManager,
     ) -> anyhow::Result<(Vec<MemoryDiff>, Vec<MemExePiece>)> {
-        let re = Regex::new(r"^%\{([\s\S]*)%}$").map_err(|err| {
-            ProcessorError::RegexNewError(format!("Regex::new failed with err: {}", err))
-        })?;
-        let code = re
-            .captures(&prophet.code)
-            .ok_or(ProcessorError::RegexCaptureError(String::from(
-                "Regex capture failed in prophet code",
-            )))?
-            .get(1)
-            .ok_or(ProcessorError::ArrayIndexError(String::from(
-                "Empty data at index 1 in prophet.code",
-            )))?
-            .as_str();
-        let mut interpreter = Interpreter::new(code);
+        self.prophet_limits.check_outputs(&prophet)?;
+        let flatten_inputs = self.prophet_inputs(&prophet)?;
+        let values: Vec<u64> = if prophet.native.is_some() {
+            let values = run_native_prophet(&prophet, &flatten_inputs)
+                .map_err(|err| ProcessorError::InterpreterRunError(err))?;
+            tx_gas_manager.consume_prophet(values.len() as u64)?;
+            values
+        } else {
+            let interpreter = self.prophets.get(&(prophet.host as u64)).cloned().ok_or(
+                ProcessorError::InterpreterRunError(format!(
+                    "prophet at pc {} is not compiled",
+                    prophet.host
+                )),
+            )?;
+            let mut cloned = prophet.clone();
+            cloned.ctx.push((HEAP_PTR.to_string(), self.memory.hp()));
+            let run = interpreter.execute_with_limits(
+                &cloned,
+                flatten_inputs,
+                &self.memory,
+                &self.prophet_limits,
+            )?;
+            // the steps of a failed run are charged too.
+            tx_gas_manager.consume_prophet(run.steps)?;
+            match run.values? {
+                NumberRet::Single(_) => return Err(ProcessorError::ParseIntError.into()),
+                NumberRet::Multiple(mut values) => {
+                    let _ = values.pop();
+                    values
+                        .iter()
+                        .map(|value| value.get_number().map(|number| number as u64))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|err| ProcessorError::InterpreterRunError(err))?
+                }
+            }
+        };
+        let mut exe_diffs: Vec<MemoryDiff> = vec![];
+        let mut trace_diffs: Vec<MemExePiece> = vec![];
+        self.psp_start = self.memory.psp();
+        for value in values {
+            let exe_diff = MemoryDiff {
+                addr: self.memory.psp(),
+                value,
+            };
+            let trace_diff = MemExePiece {
+                clk: 0,
+                addr: self.memory.psp(),
+                value,
+                is_write: true,
+                opcode: None,
+            };
+            exe_diffs.push(exe_diff);
+            trace_diffs.push(trace_diff);
+        }
+        Ok((exe_diffs, trace_diffs))
+    }
 
+    fn prophet_inputs(&self, prophet: &OlaProphet) -> anyhow::Result<Vec<u64>> {
         let mut flatten_inputs: Vec<u64> = vec![];
-        for input in prophet.clone().inputs {
+        for input in prophet.inputs.iter() {
             // reg 1,2,3 then memory mode, -3, -4, -5...(count from -3)
             let enqueued_len = flatten_inputs.len();
             let origin_values: Vec<u64> = if enqueued_len < 3 && enqueued_len + input.length < 3 {
@@ -1694,36 +1851,7 @@ impl OlaContractExecutor {
                 flatten_inputs.extend(origin_values);
             };
         }
-        let mut cloned = prophet.clone();
-        cloned.ctx.push(("_heap_ptr".to_string(), self.memory.hp()));
-        let out = interpreter
-            .run(&cloned, flatten_inputs, &self.memory)
-            .map_err(|err| ProcessorError::InterpreterRunError(err))?;
-        let mut exe_diffs: Vec<MemoryDiff> = vec![];
-        let mut trace_diffs: Vec<MemExePiece> = vec![];
-        match out {
-            NumberRet::Single(_) => return Err(ProcessorError::ParseIntError.into()),
-            NumberRet::Multiple(mut values) => {
-                self.psp_start = self.memory.psp();
-                let _ = values.pop();
-                for value in values {
-                    let exe_diff = MemoryDiff {
-                        addr: self.memory.psp(),
-                        value: value.get_number() as u64,
-                    };
-                    let trace_diff = MemExePiece {
-                        clk: 0,
-                        addr: self.memory.psp(),
-                        value: value.get_number() as u64,
-                        is_write: true,
-                        opcode: None,
-                    };
-                    exe_diffs.push(exe_diff);
-                    trace_diffs.push(trace_diff);
-                }
-            }
-        }
-        Ok((exe_diffs, trace_diffs))
+        Ok(flatten_inputs)
     }
 
     fn is_trace_needed(&self) -> bool {
@@ -1747,6 +1875,8 @@ impl OlaContractExecutor {
             poseidon: None,
             tape: None,
             storage: None,
+            sigcheck: None,
+            event: None,
         })
     }
     fn get_common_cpu_exe_pieces(
@@ -1797,11 +1927,16 @@ impl OlaContractExecutor {
         tape: &mut OlaTape,
         storage: &mut OlaCachedStorage,
         err: String,
-    ) {
+    ) -> String {
+        let backtrace = self.symbolized_backtrace();
         if self.mode == ExecuteMode::Debug {
             println!("========== step error ===========");
-            println!("instruction: {}", instruction.get_asm_form_code());
+            match instruction.get_asm_form_code() {
+                Result::Ok(asm) => println!("instruction: {}", asm),
+                Err(_) => println!("instruction: {}", instruction),
+            }
             println!("err: {}", err);
+            println!("{}", backtrace);
             println!("------------ cpu -----------------");
             println!(
                 "clk: {}, pc: {}, tp: {}, psp: {}",
@@ -1823,5 +1958,25 @@ impl OlaContractExecutor {
             println!("---------- storage -------------");
             storage.dump_tx();
         }
+        backtrace
+    }
+
+    fn symbolized_backtrace(&self) -> String {
+        let frames: Vec<String> = std::iter::once(&self.pc)
+            .chain(self.call_stack.iter().rev())
+            .enumerate()
+            .map(|(depth, pc)| {
+                let location = match &self.debug_info {
+                    Some(debug_info) => debug_info.symbolize(*pc),
+                    None => format!("pc {}", pc),
+                };
+                format!("  #{} {}", depth, location)
+            })
+            .collect();
+        format!(
+            "backtrace of code {:?}:\n{}",
+            self.context.code_addr,
+            frames.join("\n")
+        )
     }
 }
@@ -0,0 +1,404 @@
+use core::{
+    program::{binary_program::BinaryInstruction, debug_info::OlaDebugInfo},
+    vm::{
+        hardware::{ContractAddress, OlaStorageKey, OlaTape},
+        opcodes::OlaOpcode,
+    },
+};
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+
+use anyhow::Ok;
+
+use crate::{
+    contract_executor::OlaContractExecutor, ola_storage::OlaCachedStorage,
+    tx_exe_manager::EnvOutlineSnapshot,
+};
+
+const HELP: &str = "\
+commands:
+  s, step                 execute one instruction
+  n, next                 execute one instruction, stepping over call and sccall
+  c, continue             run until the next breakpoint
+  b, break <pc|label>     set a breakpoint, in the current contract for labels
+  d, delete <pc|label>    remove a breakpoint
+  breakpoints             list breakpoints
+  r, regs                 print registers
+  mem <addr> [len]        print memory
+  tape [from] [len]       print tape, the whole tape by default
+  storage [k0 k1 k2 k3]   print a storage slot of the current contract, or all tx writes
+  bt, backtrace           print the call stack across call and sccall frames
+  w, where                print the current instruction
+  q, quit                 abort the transaction
+  h, help                 print this message";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OlaBreakpoint {
+    pub code_addr: Option<ContractAddress>,
+    pub pc: u64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum DebugStepMode {
+    Step,
+    StepOver { env_depth: usize, call_depth: usize },
+    Continue,
+}
+
+/// Interactive debugger driven by `OlaContractExecutor::resume` before every
+/// step when the tx runs in `ExecuteMode::Debug`. Commands are read from
+/// `input` and everything is printed to `output`.
+pub struct OlaDebugger {
+    input: Box<dyn BufRead>,
+    output: Box<dyn Write>,
+    breakpoints: Vec<OlaBreakpoint>,
+    debug_infos: HashMap<ContractAddress, OlaDebugInfo>,
+    step_mode: DebugStepMode,
+    env_idx: usize,
+    // suspended sccall callers with the pcs of their pending call instructions.
+    callers: Vec<(EnvOutlineSnapshot, Vec<u64>)>,
+    aborted: bool,
+}
+
+impl OlaDebugger {
+    pub fn new(input: Box<dyn BufRead>, output: Box<dyn Write>) -> Self {
+        Self {
+            input,
+            output,
+            breakpoints: Vec::new(),
+            debug_infos: HashMap::new(),
+            step_mode: DebugStepMode::Step,
+            env_idx: 0,
+            callers: Vec::new(),
+            aborted: false,
+        }
+    }
+
+    pub fn stdio() -> Self {
+        Self::new(
+            Box::new(std::io::BufReader::new(std::io::stdin())),
+            Box::new(std::io::stdout()),
+        )
+    }
+
+    /// Registers the debug info of a contract, used to set breakpoints by label
+    /// and to annotate paused instructions. Contracts whose program carries
+    /// debug info are registered on their first step.
+    pub fn add_debug_info(&mut self, code_addr: ContractAddress, debug_info: OlaDebugInfo) {
+        self.debug_infos.insert(code_addr, debug_info);
+    }
+
+    pub fn label_pc(&self, code_addr: ContractAddress, label: &str) -> Option<u64> {
+        self.debug_infos.get(&code_addr)?.label_pc(label)
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: OlaBreakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    /// Runs without pausing at the first instruction.
+    pub fn start_running(&mut self) {
+        self.step_mode = DebugStepMode::Continue;
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+
+    pub(crate) fn on_env_resumed(
+        &mut self,
+        env_idx: usize,
+        callers: Vec<(EnvOutlineSnapshot, Vec<u64>)>,
+    ) {
+        self.env_idx = env_idx;
+        self.callers = callers;
+    }
+
+    pub(crate) fn on_step(
+        &mut self,
+        executor: &OlaContractExecutor,
+        instruction: &BinaryInstruction,
+        tape: &OlaTape,
+        storage: &mut OlaCachedStorage,
+    ) -> anyhow::Result<()> {
+        let code_addr = executor.get_code_addr();
+        if let Some(debug_info) = executor.get_debug_info() {
+            self.debug_infos
+                .entry(code_addr)
+                .or_insert_with(|| debug_info.clone());
+        }
+        if self.should_pause(executor) {
+            self.print_location(executor, instruction)?;
+            self.prompt(executor, instruction, tape, storage)?;
+        }
+        if self.aborted {
+            anyhow::bail!("execution aborted by debugger");
+        }
+        Ok(())
+    }
+
+    fn should_pause(&self, executor: &OlaContractExecutor) -> bool {
+        let pc = executor.get_pc();
+        let code_addr = executor.get_code_addr();
+        let hit = self.breakpoints.iter().any(|breakpoint| {
+            breakpoint.pc == pc
+                && breakpoint
+                    .code_addr
+                    .map(|addr| addr == code_addr)
+                    .unwrap_or(true)
+        });
+        match self.step_mode {
+            DebugStepMode::Step => true,
+            DebugStepMode::StepOver {
+                env_depth,
+                call_depth,
+            } => {
+                let depth = self.callers.len();
+                hit || depth < env_depth
+                    || (depth == env_depth && executor.get_call_stack().len() <= call_depth)
+            }
+            DebugStepMode::Continue => hit,
+        }
+    }
+
+    fn prompt(
+        &mut self,
+        executor: &OlaContractExecutor,
+        instruction: &BinaryInstruction,
+        tape: &OlaTape,
+        storage: &mut OlaCachedStorage,
+    ) -> anyhow::Result<()> {
+        loop {
+            write!(self.output, "(oladb) ")?;
+            self.output.flush()?;
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                // no more commands, run to the end.
+                self.step_mode = DebugStepMode::Continue;
+                writeln!(self.output)?;
+                return Ok(());
+            }
+            let args: Vec<&str> = line.split_whitespace().collect();
+            let Some((cmd, args)) = args.split_first() else {
+                continue;
+            };
+            match *cmd {
+                "s" | "step" => {
+                    self.step_mode = DebugStepMode::Step;
+                    return Ok(());
+                }
+                "n" | "next" => {
+                    self.step_mode = match instruction.opcode {
+                        OlaOpcode::CALL | OlaOpcode::SCCALL => DebugStepMode::StepOver {
+                            env_depth: self.callers.len(),
+                            call_depth: executor.get_call_stack().len(),
+                        },
+                        _ => DebugStepMode::Step,
+                    };
+                    return Ok(());
+                }
+                "c" | "continue" => {
+                    self.step_mode = DebugStepMode::Continue;
+                    return Ok(());
+                }
+                "q" | "quit" => {
+                    self.aborted = true;
+                    return Ok(());
+                }
+                "b" | "break" => match self.parse_breakpoint(executor, args) {
+                    Some(breakpoint) => {
+                        self.add_breakpoint(breakpoint);
+                        writeln!(self.output, "breakpoint set at pc {}", breakpoint.pc)?;
+                    }
+                    None => writeln!(self.output, "usage: break <pc|label>")?,
+                },
+                "d" | "delete" => match self.parse_breakpoint(executor, args) {
+                    Some(breakpoint) => {
+                        self.breakpoints.retain(|b| *b != breakpoint);
+                        writeln!(self.output, "breakpoint removed at pc {}", breakpoint.pc)?;
+                    }
+                    None => writeln!(self.output, "usage: delete <pc|label>")?,
+                },
+                "breakpoints" => {
+                    for breakpoint in self.breakpoints.clone() {
+                        match breakpoint.code_addr {
+                            Some(addr) => {
+                                writeln!(self.output, "pc {} in {:?}", breakpoint.pc, addr)?
+                            }
+                            None => writeln!(self.output, "pc {}", breakpoint.pc)?,
+                        }
+                    }
+                }
+                "r" | "regs" => self.print_regs(executor)?,
+                "mem" => self.print_mem(executor, args)?,
+                "tape" => self.print_tape(tape, args)?,
+                "storage" => self.print_storage(executor, storage, args)?,
+                "bt" | "backtrace" => self.print_backtrace(executor)?,
+                "w" | "where" => self.print_location(executor, instruction)?,
+                "h" | "help" => writeln!(self.output, "{}", HELP)?,
+                _ => writeln!(self.output, "unknown command: {}, try help", cmd)?,
+            }
+        }
+    }
+
+    fn parse_breakpoint(
+        &self,
+        executor: &OlaContractExecutor,
+        args: &[&str],
+    ) -> Option<OlaBreakpoint> {
+        let target = args.first()?;
+        if let Result::Ok(pc) = target.parse::<u64>() {
+            return Some(OlaBreakpoint {
+                code_addr: None,
+                pc,
+            });
+        }
+        let code_addr = executor.get_code_addr();
+        let pc = self.label_pc(code_addr, target)?;
+        Some(OlaBreakpoint {
+            code_addr: Some(code_addr),
+            pc,
+        })
+    }
+
+    fn label_at(&self, code_addr: ContractAddress, pc: u64) -> Option<&String> {
+        self.debug_infos.get(&code_addr)?.line(pc)?.labels.first()
+    }
+
+    fn source_at(&self, code_addr: ContractAddress, pc: u64) -> Option<String> {
+        let line = self.debug_infos.get(&code_addr)?.line(pc)?;
+        Some(format!(" in {} at line {}", line.function, line.line))
+    }
+
+    fn print_location(
+        &mut self,
+        executor: &OlaContractExecutor,
+        instruction: &BinaryInstruction,
+    ) -> anyhow::Result<()> {
+        let pc = executor.get_pc();
+        if let Some(label) = self.label_at(executor.get_code_addr(), pc).cloned() {
+            writeln!(self.output, "{}:", label)?;
+        }
+        let source = self
+            .source_at(executor.get_code_addr(), pc)
+            .unwrap_or_default();
+        writeln!(
+            self.output,
+            "[env {}] clk: {}, pc: {}, {}{}",
+            self.env_idx,
+            executor.get_clk(),
+            pc,
+            instruction
+                .get_asm_form_code()
+                .unwrap_or_else(|_| instruction.to_string()),
+            source
+        )?;
+        Ok(())
+    }
+
+    fn print_regs(&mut self, executor: &OlaContractExecutor) -> anyhow::Result<()> {
+        let regs: Vec<String> = executor
+            .get_regs()
+            .iter()
+            .enumerate()
+            .map(|(index, value)| format!("r{}({})", index, value))
+            .collect();
+        writeln!(self.output, "{}", regs.join(", "))?;
+        writeln!(self.output, "psp({})", executor.get_memory().psp())?;
+        Ok(())
+    }
+
+    fn print_mem(&mut self, executor: &OlaContractExecutor, args: &[&str]) -> anyhow::Result<()> {
+        let nums: Vec<u64> = args.iter().filter_map(|arg| arg.parse().ok()).collect();
+        let Some(from) = nums.first() else {
+            writeln!(self.output, "usage: mem <addr> [len]")?;
+            return Ok(());
+        };
+        let len = nums.get(1).copied().unwrap_or(1);
+        for addr in *from..from.saturating_add(len) {
+            match executor.get_memory().read(addr) {
+                Result::Ok(value) => writeln!(self.output, "{}: {}", addr, value)?,
+                Err(_) => writeln!(self.output, "{}: <uninit>", addr)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn print_tape(&mut self, tape: &OlaTape, args: &[&str]) -> anyhow::Result<()> {
+        let nums: Vec<u64> = args.iter().filter_map(|arg| arg.parse().ok()).collect();
+        let from = nums.first().copied().unwrap_or(0);
+        let to = match nums.get(1) {
+            Some(len) => from.saturating_add(*len).min(tape.tp()),
+            None => tape.tp(),
+        };
+        writeln!(self.output, "tp({})", tape.tp())?;
+        for addr in from..to {
+            match tape.read_top(addr) {
+                Result::Ok(value) => writeln!(self.output, "{}: {}", addr, value)?,
+                // Every address below tp was written once, so a hole there was
+                // dropped by a reverted call frame.
+                Err(_) if addr < tape.tp() => writeln!(self.output, "{}: <reverted>", addr)?,
+                Err(_) => writeln!(self.output, "{}: <uninit>", addr)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn print_storage(
+        &mut self,
+        executor: &OlaContractExecutor,
+        storage: &mut OlaCachedStorage,
+        args: &[&str],
+    ) -> anyhow::Result<()> {
+        if args.is_empty() {
+            for (tree_key, value) in storage.get_cached_tx_modification() {
+                writeln!(self.output, "[{:?}]: [{:?}]", tree_key, value)?;
+            }
+            return Ok(());
+        }
+        let key: Vec<u64> = args.iter().filter_map(|arg| arg.parse().ok()).collect();
+        let Result::Ok(key) = OlaStorageKey::try_from(key) else {
+            writeln!(self.output, "usage: storage [k0 k1 k2 k3]")?;
+            return Ok(());
+        };
+        let value = storage.read(executor.get_storage_addr(), key)?;
+        writeln!(self.output, "[{:?}]: {:?}", key, value)?;
+        Ok(())
+    }
+
+    fn print_backtrace(&mut self, executor: &OlaContractExecutor) -> anyhow::Result<()> {
+        let mut frames: Vec<(usize, ContractAddress, u64)> =
+            vec![(self.env_idx, executor.get_code_addr(), executor.get_pc())];
+        executor
+            .get_call_stack()
+            .iter()
+            .rev()
+            .for_each(|pc| frames.push((self.env_idx, executor.get_code_addr(), *pc)));
+        for (caller, call_stack) in self.callers.iter().rev() {
+            let env_idx = caller.env_idx as usize;
+            let code_addr = caller.context.code_addr;
+            frames.push((env_idx, code_addr, caller.pc));
+            call_stack
+                .iter()
+                .rev()
+                .for_each(|pc| frames.push((env_idx, code_addr, *pc)));
+        }
+        for (depth, (env_idx, code_addr, pc)) in frames.into_iter().enumerate() {
+            let label = self
+                .label_at(code_addr, pc)
+                .map(|label| format!(" <{}>", label))
+                .unwrap_or_default();
+            let source = self.source_at(code_addr, pc).unwrap_or_default();
+            writeln!(
+                self.output,
+                "#{} env {} code {:?} pc {}{}{}",
+                depth, env_idx, code_addr, pc, label, source
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -33,6 +33,8 @@ pub struct TxTraceManager {
     tape: Vec<TapeExePiece>,
     caller_stack: Vec<CallerInfo>,
     sccall: Vec<SCCallPiece>,
+    sigcheck: Vec<SigCheckPiece>,
+    event: Vec<EventPiece>,
 }
 
 impl Default for TxTraceManager {
@@ -54,6 +56,8 @@ impl Default for TxTraceManager {
             tape: Vec::new(),
             caller_stack: Vec::new(),
             sccall: Vec::new(),
+            sigcheck: Vec::new(),
+            event: Vec::new(),
         }
     }
 }
@@ -152,6 +156,14 @@ impl TxTraceManager {
         if let Some(tape) = diff.tape {
             self.tape.extend(tape);
         }
+        if let Some(mut sigcheck) = diff.sigcheck {
+            sigcheck.env_idx = self.current_env_idx as u64;
+            self.sigcheck.push(sigcheck);
+        }
+        if let Some(mut event) = diff.event {
+            event.env_idx = self.current_env_idx as u64;
+            self.event.push(event);
+        }
     }
 
     pub fn on_call(
@@ -187,6 +199,19 @@ impl TxTraceManager {
         }
     }
 
+    /// Drops the caller of a reverted call frame and records the status it
+    /// reads back from the tape. The steps of the reverted frame stay in the
+    /// trace with no sccall row linking them to the caller, so a tx that
+    /// reverted a call frame cannot be proven yet.
+    pub fn on_revert(&mut self, tp: u64, status: u64) {
+        self.caller_stack.pop();
+        self.tape.push(TapeExePiece {
+            addr: tp,
+            value: status,
+            opcode: None,
+        });
+    }
+
     pub fn build_trace(&self, accessed_bytecodes: Vec<(ContractAddress, Vec<u64>)>) -> TxExeTrace {
         TxExeTrace {
             programs: accessed_bytecodes,
@@ -199,6 +224,8 @@ impl TxTraceManager {
             storage: self.storage.clone(),
             tape: self.tape.clone(),
             sccall: self.sccall.clone(),
+            sigcheck: self.sigcheck.clone(),
+            event: self.event.clone(),
         }
     }
 }
@@ -30,6 +30,7 @@ use core::types::merkle_tree::{tree_key_default, TreeKey, TreeValue};
 use core::types::storage::StorageKey;
 use core::util::poseidon_utils::POSEIDON_INPUT_NUM;
 use core::vm::heap::HEAP_PTR;
+use core::vm::native_prophet::run_native_prophet;
 use interpreter::interpreter::Interpreter;
 use interpreter::utils::number::NumberRet::{Multiple, Single};
 use log::debug;
@@ -55,10 +56,13 @@ mod decode;
 pub mod batch_exe_manager;
 pub mod config;
 pub(crate) mod contract_executor;
-mod ecdsa;
+pub mod debugger;
+pub mod ecdsa;
 mod exe_trace;
 pub mod load_tx;
 pub mod ola_storage;
+pub mod profiler;
+pub mod prophet;
 pub mod storage;
 mod tape;
 #[cfg(test)]
@@ -225,6 +229,10 @@ enum MemRangeType {
     MemRegion,
 }
 
+/// The legacy executor, which decodes instructions to text and writes the
+/// `Trace` directly. Front-ends run transactions with `TxExeManager`, this is
+/// only kept for the circuit tests built on its `Trace`; the same `Trace` is
+/// produced from a `TxExeTrace` by `circuits::generation::pre_process`.
 #[derive(Debug, Clone)]
 pub struct Process {
     pub block_timestamp: u64,
@@ -383,7 +391,47 @@ impl Process {
         Ok(value)
     }
 
+    fn native_prophet(&mut self, prophet: &OlaProphet) -> Result<(), ProcessorError> {
+        let mut values = Vec::new();
+        let mut reg_index = PROPHET_INPUT_REG_START_INDEX;
+        let mut fp = PROPHET_INPUT_FP_START_OFFSET;
+        for input in prophet.inputs.iter() {
+            for _ in 0..input.length {
+                let value = self.read_prophet_input(
+                    input,
+                    PROPHET_INPUT_REG_END_INDEX,
+                    &mut reg_index,
+                    &mut fp,
+                )?;
+                values.push(value);
+            }
+        }
+
+        let outputs =
+            run_native_prophet(prophet, &values).map_err(ProcessorError::InterpreterRunError)?;
+        self.psp_start = self.psp;
+        for value in outputs {
+            self.memory.write(
+                self.psp.0,
+                0,
+                GoldilocksField::ZERO,
+                GoldilocksField::from_canonical_u64(MemoryType::WriteOnce as u64),
+                GoldilocksField::from_canonical_u64(MemoryOperation::Write as u64),
+                GoldilocksField::from_canonical_u64(FilterLockForMain::False as u64),
+                GoldilocksField::ONE,
+                GoldilocksField::ZERO,
+                GoldilocksField(value),
+                self.env_idx,
+            );
+            self.psp += GoldilocksField::ONE;
+        }
+        Ok(())
+    }
+
     pub fn prophet(&mut self, prophet: &mut OlaProphet) -> Result<(), ProcessorError> {
+        if prophet.native.is_some() {
+            return self.native_prophet(prophet);
+        }
         println!("refactoring, prophet not work now.");
         // debug!("prophet code:{}", prophet.code);
 
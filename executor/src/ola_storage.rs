@@ -1,6 +1,6 @@
 use core::{
     crypto::poseidon_trace::calculate_arbitrary_poseidon_u64s,
-    program::binary_program::BinaryProgram,
+    program::{binary_program::BinaryProgram, debug_info::OlaDebugInfo, verifier::verify_program},
     util::converts::{bytes_to_u64s, u64s_to_bytes},
     vm::{
         error::ProcessorError,
@@ -8,6 +8,7 @@ use core::{
             ContractAddress, OlaStorage, OlaStorageKey, OlaStorageValue, StorageAccessKind,
             StorageAccessLog,
         },
+        types::Hash,
     },
 };
 use std::{collections::HashMap, num::NonZeroUsize, path::PathBuf};
@@ -17,15 +18,18 @@ use lru::LruCache;
 use plonky2::hash::utils::poseidon_hash_bytes;
 use rocksdb::{BlockBasedOptions, ColumnFamilyDescriptor, Options, WriteBatch, DB};
 
+use crate::prophet::{compile_prophets, CompiledProphets, ProphetCache};
+
 #[derive(Debug, Clone, Copy)]
 pub enum SequencerColumnFamily {
     State,
     FactoryDeps,
+    DebugInfo,
 }
 
 impl SequencerColumnFamily {
     fn all() -> &'static [Self] {
-        &[Self::State, Self::FactoryDeps]
+        &[Self::State, Self::FactoryDeps, Self::DebugInfo]
     }
 }
 
@@ -34,6 +38,7 @@ impl std::fmt::Display for SequencerColumnFamily {
         let value = match self {
             Self::State => "state",
             Self::FactoryDeps => "factory_deps",
+            Self::DebugInfo => "debug_info",
         };
         write!(formatter, "{}", value)
     }
@@ -74,8 +79,14 @@ impl DiskStorageWriter {
         program: BinaryProgram,
         contract_addr: ContractAddress,
     ) -> anyhow::Result<()> {
+        verify_program(&program)?;
+        compile_prophets(&program)?;
         let prog_hash_treekey = Self::get_program_treekey(contract_addr);
-        let program_bytes = bincode::serialize(&program)?;
+        let program_bytes = program.to_bytes()?;
+        let debug_info_bytes = match &program.debug_info {
+            Some(debug_info) => Some(bincode::serialize(debug_info)?),
+            None => None,
+        };
 
         let program_hash = poseidon_hash_bytes(program_bytes.as_ref()).to_vec();
         let hash_u64s = bytes_to_u64s(program_hash.clone());
@@ -91,6 +102,19 @@ impl DiskStorageWriter {
             Some(cf) => {
                 let mut batch = WriteBatch::default();
                 batch.put_cf(cf, &program_hash, &program_bytes);
+                // debug info is keyed by the same code hash, in its own column family.
+                if let Some(bytes) = debug_info_bytes {
+                    match self
+                        .db
+                        .cf_handle(&SequencerColumnFamily::DebugInfo.to_string())
+                    {
+                        Some(debug_cf) => batch.put_cf(debug_cf, &program_hash, &bytes),
+                        None => bail!(ProcessorError::IoError(
+                            "[DiskStorageWriter] Column family debug_info doesn't exist"
+                                .to_string(),
+                        )),
+                    }
+                }
                 let write_res = self.db.write(batch);
                 if write_res.is_err() {
                     return Err(write_res.err().unwrap().into());
@@ -138,10 +162,14 @@ struct DiskStorageReader {
 impl DiskStorageReader {
     pub fn new(storage_db_path: String) -> anyhow::Result<Self> {
         let options = Self::rocksdb_options(true);
+        let storage_db_path_buf: PathBuf = storage_db_path.into();
+        // a secondary instance cannot create column families, so only open the ones the
+        // primary already has, databases written before debug info existed lack it.
+        let existing_cfs = DB::list_cf(&options, &storage_db_path_buf).unwrap_or_default();
         let cfs = SequencerColumnFamily::all()
             .iter()
+            .filter(|cf| existing_cfs.is_empty() || existing_cfs.contains(&cf.to_string()))
             .map(|cf| ColumnFamilyDescriptor::new(cf.to_string(), options.clone()));
-        let storage_db_path_buf: PathBuf = storage_db_path.into();
         let secondary_path = storage_db_path_buf.join("ola-secondary");
         let db = DB::open_cf_descriptors_as_secondary(
             &options,
@@ -196,7 +224,12 @@ impl DiskStorageReader {
         }
     }
 
-    pub fn load_program(&self, contract_addr: ContractAddress) -> anyhow::Result<BinaryProgram> {
+    /// Loads the program deployed at `contract_addr` together with its code
+    /// hash.
+    pub fn load_program(
+        &self,
+        contract_addr: ContractAddress,
+    ) -> anyhow::Result<(Hash, BinaryProgram)> {
         let prog_hash_treekey = Self::get_program_treekey(contract_addr);
         let prog_hash = self.load(prog_hash_treekey)?;
         if let Some(hash) = prog_hash {
@@ -205,12 +238,13 @@ impl DiskStorageReader {
                 .cf_handle(&SequencerColumnFamily::FactoryDeps.to_string());
             if let Some(cf) = c {
                 let key = u64s_to_bytes(&hash);
-                let loaded = self.db.get_cf(cf, key).map_err(|e| {
+                let loaded = self.db.get_cf(cf, &key).map_err(|e| {
                     ProcessorError::ProgLoadError(format!("load program bytes failed: {}", e))
                 })?;
                 if let Some(bytes) = loaded {
-                    let program: BinaryProgram = bincode::deserialize(&bytes)?;
-                    Ok(program)
+                    let mut program = BinaryProgram::from_bytes(&bytes)?;
+                    program.debug_info = self.load_debug_info(&key)?;
+                    Ok((hash, program))
                 } else {
                     Err(
                         ProcessorError::ProgLoadError("program bytes not found.".to_string())
@@ -233,6 +267,21 @@ impl DiskStorageReader {
         }
     }
 
+    fn load_debug_info(&self, program_hash: &[u8]) -> anyhow::Result<Option<OlaDebugInfo>> {
+        let c = self
+            .db
+            .cf_handle(&SequencerColumnFamily::DebugInfo.to_string());
+        if let Some(cf) = c {
+            let loaded = self.db.get_cf(cf, program_hash).map_err(|e| {
+                ProcessorError::ProgLoadError(format!("load debug info failed: {}", e))
+            })?;
+            if let Some(bytes) = loaded {
+                return Ok(Some(bincode::deserialize(&bytes)?));
+            }
+        }
+        Ok(None)
+    }
+
     fn get_program_treekey(contract_addr: ContractAddress) -> OlaStorageKey {
         let slot_to_hash = [[0u64; 4], contract_addr].concat();
         let key = calculate_arbitrary_poseidon_u64s(&slot_to_hash);
@@ -248,22 +297,29 @@ pub struct OlaCachedStorage {
     tx_cached_storage: HashMap<OlaStorageKey, OlaStorageValue>,
     storage_logs: Vec<StorageAccessLog>,
     tx_storage_logs: Vec<StorageAccessLog>,
+    tx_storage_journal: Vec<(OlaStorageKey, Option<OlaStorageValue>)>,
+    checkpoints: Vec<(usize, usize)>, // (journal length, tx storage logs length)
     disk_storage_reader: DiskStorageReader,
-    prog_cache: LruCache<ContractAddress, BinaryProgram>,
+    prog_cache: LruCache<ContractAddress, (Hash, BinaryProgram)>,
+    prophet_cache: ProphetCache,
 }
 
 impl OlaCachedStorage {
     pub fn new(storage_db_path: String, block_timestamp: Option<u64>) -> anyhow::Result<Self> {
         let disk_storage_reader = DiskStorageReader::new(storage_db_path)?;
         let prog_cache = LruCache::new(NonZeroUsize::new(50).unwrap());
+        let prophet_cache = ProphetCache::new(NonZeroUsize::new(500).unwrap());
         Ok(Self {
             block_timestamp: block_timestamp.unwrap_or_default(),
             cached_storage: HashMap::new(),
             tx_cached_storage: HashMap::new(),
             storage_logs: Vec::new(),
             tx_storage_logs: Vec::new(),
+            tx_storage_journal: Vec::new(),
+            checkpoints: Vec::new(),
             disk_storage_reader,
             prog_cache,
+            prophet_cache,
         })
     }
 
@@ -309,7 +365,8 @@ impl OlaCachedStorage {
         } else {
             StorageAccessKind::InitialWrite
         };
-        self.tx_cached_storage.insert(tree_key, value);
+        let tx_pre_value = self.tx_cached_storage.insert(tree_key, value);
+        self.tx_storage_journal.push((tree_key, tx_pre_value));
         self.tx_storage_logs.push(StorageAccessLog {
             block_timestamp: self.block_timestamp,
             kind,
@@ -321,14 +378,64 @@ impl OlaCachedStorage {
         Ok(())
     }
 
+    /// Opens a new call frame. Writes made after this point can be dropped by
+    /// `revert_to_checkpoint` without touching writes of the outer frames.
+    pub fn checkpoint(&mut self) {
+        self.checkpoints
+            .push((self.tx_storage_journal.len(), self.tx_storage_logs.len()));
+    }
+
+    /// Closes the innermost call frame and keeps its writes.
+    pub fn commit_checkpoint(&mut self) {
+        self.checkpoints.pop();
+        if self.checkpoints.is_empty() {
+            self.tx_storage_journal.clear();
+        }
+    }
+
+    /// Closes the innermost call frame and rolls back every write and access
+    /// log it produced.
+    pub fn revert_to_checkpoint(&mut self) {
+        if let Some((journal_len, logs_len)) = self.checkpoints.pop() {
+            while self.tx_storage_journal.len() > journal_len {
+                if let Some((tree_key, tx_pre_value)) = self.tx_storage_journal.pop() {
+                    match tx_pre_value {
+                        Some(value) => self.tx_cached_storage.insert(tree_key, value),
+                        None => self.tx_cached_storage.remove(&tree_key),
+                    };
+                }
+            }
+            self.tx_storage_logs.truncate(logs_len);
+        }
+    }
+
     pub fn get_program(&mut self, contract_addr: ContractAddress) -> anyhow::Result<BinaryProgram> {
+        let (_, program) = self.get_program_with_hash(contract_addr)?;
+        Ok(program)
+    }
+
+    /// Returns the program at `contract_addr` with its prophets compiled, the
+    /// compiled form is shared by every program with the same code hash.
+    pub fn get_program_with_prophets(
+        &mut self,
+        contract_addr: ContractAddress,
+    ) -> anyhow::Result<(BinaryProgram, CompiledProphets)> {
+        let (code_hash, program) = self.get_program_with_hash(contract_addr)?;
+        let prophets = self.prophet_cache.load(code_hash, &program)?;
+        Ok((program, prophets))
+    }
+
+    fn get_program_with_hash(
+        &mut self,
+        contract_addr: ContractAddress,
+    ) -> anyhow::Result<(Hash, BinaryProgram)> {
         let cached = self.prog_cache.get(&contract_addr);
-        if let Some(program) = cached {
-            return Ok(program.clone());
+        if let Some(loaded) = cached {
+            return Ok(loaded.clone());
         }
-        let program = self.disk_storage_reader.load_program(contract_addr)?;
-        self.prog_cache.put(contract_addr, program.clone());
-        Ok(program)
+        let loaded = self.disk_storage_reader.load_program(contract_addr)?;
+        self.prog_cache.put(contract_addr, loaded.clone());
+        Ok(loaded)
     }
 
     pub fn get_tree_key(
@@ -392,10 +499,14 @@ impl OlaStorage for OlaCachedStorage {
     fn on_tx_success(&mut self) {
         self.cached_storage.extend(self.tx_cached_storage.drain());
         self.storage_logs.append(&mut self.tx_storage_logs);
+        self.tx_storage_journal.clear();
+        self.checkpoints.clear();
     }
 
     fn clear_tx_cache(&mut self) {
         self.tx_cached_storage.clear();
-        self.storage_logs.clear();
+        self.tx_storage_logs.clear();
+        self.tx_storage_journal.clear();
+        self.checkpoints.clear();
     }
 }
@@ -0,0 +1,224 @@
+use core::vm::{hardware::ContractAddress, opcodes::OlaOpcode};
+use core::{program::debug_info::OlaDebugInfo, trace::exe_trace::ExeTraceStepDiff};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::Write,
+    path::Path,
+};
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::contract_executor::OlaContractExecutor;
+
+/// Resources consumed by some part of a transaction. Everything but `cycles`
+/// is read from the step trace, so it stays zero in modes without trace.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OlaProfileCounters {
+    pub cycles: u64,
+    pub mem_accesses: u64,
+    pub poseidons: u64,
+    pub storage_accesses: u64,
+    pub range_checks: u64,
+}
+
+impl OlaProfileCounters {
+    fn from_step(diff: Option<&ExeTraceStepDiff>) -> Self {
+        let mut counters = Self {
+            cycles: 1,
+            ..Default::default()
+        };
+        if let Some(diff) = diff {
+            counters.mem_accesses = diff.mem.as_ref().map_or(0, |mem| mem.len() as u64);
+            counters.poseidons = diff.poseidon.is_some() as u64;
+            counters.storage_accesses = diff.storage.is_some() as u64;
+            counters.range_checks = diff.rc.is_some() as u64;
+        }
+        counters
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.cycles += other.cycles;
+        self.mem_accesses += other.mem_accesses;
+        self.poseidons += other.poseidons;
+        self.storage_accesses += other.storage_accesses;
+        self.range_checks += other.range_checks;
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OlaFunctionProfile {
+    /// `contract::function`, the function name comes from the debug info or
+    /// is `pc_<entry>` without it.
+    pub name: String,
+    pub calls: u64,
+    /// Spent in the function body only.
+    pub self_cost: OlaProfileCounters,
+    /// Spent in the function and everything it called, recursion counted
+    /// once.
+    pub inclusive_cost: OlaProfileCounters,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OlaProfileSummary {
+    pub total: OlaProfileCounters,
+    /// Sorted by inclusive cycles, most expensive first.
+    pub functions: Vec<OlaFunctionProfile>,
+}
+
+/// Attributes the cost of every executed instruction to the function it
+/// belongs to, following CALL/RET inside a contract and SCCALL across
+/// contracts.
+#[derive(Debug, Default)]
+pub struct OlaProfiler {
+    // env_idx and frames of the envs not ended yet, the last one is running.
+    envs: Vec<(usize, Vec<String>)>,
+    stacks: HashMap<Vec<String>, OlaProfileCounters>,
+    functions: BTreeMap<String, OlaFunctionProfile>,
+    total: OlaProfileCounters,
+}
+
+impl OlaProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn on_env_resumed(
+        &mut self,
+        env_idx: usize,
+        code_addr: ContractAddress,
+        debug_info: Option<&OlaDebugInfo>,
+    ) {
+        // a resumed caller means its callees have ended or reverted.
+        if let Some(pos) = self.envs.iter().position(|(idx, _)| *idx == env_idx) {
+            self.envs.truncate(pos + 1);
+            return;
+        }
+        let entry = Self::frame_name(code_addr, debug_info, 0);
+        self.function_mut(&entry).calls += 1;
+        self.envs.push((env_idx, vec![entry]));
+    }
+
+    pub(crate) fn on_step(
+        &mut self,
+        executor: &OlaContractExecutor,
+        opcode: OlaOpcode,
+        diff: Option<&ExeTraceStepDiff>,
+    ) {
+        let cost = OlaProfileCounters::from_step(diff);
+        self.total.add(&cost);
+        let stack: Vec<String> = self
+            .envs
+            .iter()
+            .flat_map(|(_, frames)| frames.iter().cloned())
+            .collect();
+        if let Some(current) = stack.last() {
+            self.function_mut(current).self_cost.add(&cost);
+        }
+        let mut seen: Vec<&String> = Vec::new();
+        for frame in stack.iter() {
+            if !seen.contains(&frame) {
+                seen.push(frame);
+                self.function_mut(frame).inclusive_cost.add(&cost);
+            }
+        }
+        self.stacks.entry(stack).or_default().add(&cost);
+
+        match opcode {
+            OlaOpcode::CALL => {
+                // the step has been applied, pc is the callee entry.
+                let callee = Self::frame_name(
+                    executor.get_code_addr(),
+                    executor.get_debug_info(),
+                    executor.get_pc(),
+                );
+                self.function_mut(&callee).calls += 1;
+                if let Some((_, frames)) = self.envs.last_mut() {
+                    frames.push(callee);
+                }
+            }
+            OlaOpcode::RET => {
+                if let Some((_, frames)) = self.envs.last_mut() {
+                    if frames.len() > 1 {
+                        frames.pop();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn total(&self) -> OlaProfileCounters {
+        self.total
+    }
+
+    /// Self cycles per call stack, one `frame;frame;frame cycles` line each,
+    /// the input format of flamegraph.pl and inferno.
+    pub fn folded_stacks(&self) -> String {
+        let mut lines: Vec<String> = self
+            .stacks
+            .iter()
+            .map(|(stack, counters)| format!("{} {}", stack.join(";"), counters.cycles))
+            .collect();
+        lines.sort();
+        lines
+            .iter()
+            .fold(String::new(), |folded, line| folded + line + "\n")
+    }
+
+    pub fn summary(&self) -> OlaProfileSummary {
+        let mut functions: Vec<OlaFunctionProfile> = self.functions.values().cloned().collect();
+        functions.sort_by(|a, b| {
+            b.inclusive_cost
+                .cycles
+                .cmp(&a.inclusive_cost.cycles)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        OlaProfileSummary {
+            total: self.total,
+            functions,
+        }
+    }
+
+    pub fn write_folded_stacks(&self, path: &Path) -> anyhow::Result<()> {
+        let mut file = File::create(path)?;
+        file.write_all(self.folded_stacks().as_bytes())?;
+        Ok(())
+    }
+
+    pub fn write_summary(&self, path: &Path) -> anyhow::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, &self.summary())?;
+        Ok(())
+    }
+
+    fn function_mut(&mut self, name: &str) -> &mut OlaFunctionProfile {
+        self.functions
+            .entry(name.to_string())
+            .or_insert_with(|| OlaFunctionProfile {
+                name: name.to_string(),
+                ..Default::default()
+            })
+    }
+
+    fn frame_name(
+        code_addr: ContractAddress,
+        debug_info: Option<&OlaDebugInfo>,
+        entry_pc: u64,
+    ) -> String {
+        let function = debug_info
+            .and_then(|info| info.line(entry_pc))
+            .map(|line| line.function.clone())
+            .unwrap_or_else(|| format!("pc_{}", entry_pc));
+        format!("{}::{}", Self::contract_name(code_addr), function)
+    }
+
+    fn contract_name(code_addr: ContractAddress) -> String {
+        let hex = format!(
+            "{:016x}{:016x}{:016x}{:016x}",
+            code_addr[0], code_addr[1], code_addr[2], code_addr[3]
+        );
+        let trimmed = hex.trim_start_matches('0');
+        format!("0x{}", if trimmed.is_empty() { "0" } else { trimmed })
+    }
+}
@@ -0,0 +1,85 @@
+use core::{
+    program::binary_program::{BinaryProgram, OlaProphet},
+    vm::{error::ProcessorError, heap::HEAP_PTR, native_prophet::get_native_prophet, types::Hash},
+};
+use std::{collections::HashMap, num::NonZeroUsize, sync::Arc};
+
+use interpreter::interpreter::Interpreter;
+use lru::LruCache;
+
+/// Compiled prophets of one program, keyed by the pc of their host
+/// instruction. Native prophets are not compiled and have no entry.
+pub type CompiledProphets = HashMap<u64, Arc<Interpreter>>;
+
+/// Parses and checks `prophet` with the context the executor provides when it
+/// runs, see `OlaContractExecutor::process_prophet`.
+pub fn compile_prophet(prophet: &OlaProphet) -> anyhow::Result<Interpreter> {
+    let mut declared = prophet.clone();
+    declared.ctx.push((HEAP_PTR.to_string(), 0));
+    Interpreter::compile(&declared)
+        .map_err(|err| ProcessorError::InterpreterCompileError(prophet.host, err).into())
+}
+
+fn check_native_prophet(prophet: &OlaProphet) -> anyhow::Result<()> {
+    match &prophet.native {
+        Some(name) if get_native_prophet(name).is_none() => {
+            Err(ProcessorError::InterpreterCompileError(
+                prophet.host,
+                format!("unknown native prophet {}", name),
+            )
+            .into())
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn compile_prophets(program: &BinaryProgram) -> anyhow::Result<CompiledProphets> {
+    let mut compiled = CompiledProphets::new();
+    for prophet in program.prophets.iter() {
+        if prophet.native.is_some() {
+            check_native_prophet(prophet)?;
+            continue;
+        }
+        compiled.insert(prophet.host as u64, Arc::new(compile_prophet(prophet)?));
+    }
+    Ok(compiled)
+}
+
+/// Compiled prophets shared by every program with the same code hash, so a
+/// prophet is parsed once however often its contract is called.
+pub struct ProphetCache {
+    compiled: LruCache<(Hash, u64), Arc<Interpreter>>,
+}
+
+impl ProphetCache {
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            compiled: LruCache::new(capacity),
+        }
+    }
+
+    pub fn load(
+        &mut self,
+        code_hash: Hash,
+        program: &BinaryProgram,
+    ) -> anyhow::Result<CompiledProphets> {
+        let mut compiled = CompiledProphets::new();
+        for prophet in program.prophets.iter() {
+            if prophet.native.is_some() {
+                check_native_prophet(prophet)?;
+                continue;
+            }
+            let host = prophet.host as u64;
+            let interpreter = match self.compiled.get(&(code_hash, host)).cloned() {
+                Some(interpreter) => interpreter,
+                None => {
+                    let interpreter = Arc::new(compile_prophet(prophet)?);
+                    self.compiled.put((code_hash, host), interpreter.clone());
+                    interpreter
+                }
+            };
+            compiled.insert(host, interpreter);
+        }
+        Ok(compiled)
+    }
+}
@@ -3,30 +3,43 @@ mod tests {
     use crate::{
         batch_exe_manager::BlockExeInfo,
         config::*,
+        debugger::{OlaBreakpoint, OlaDebugger},
         ola_storage::{DiskStorageWriter, OlaCachedStorage},
+        profiler::OlaProfiler,
+        prophet::compile_prophet,
         tx_exe_manager::{OlaTapeInitInfo, TxExeManager},
     };
     use anyhow::Ok;
+    use assembler::encoder::encode_asm_from_json_string;
     use core::{
         program::{
             binary_program::{BinaryInstruction, BinaryProgram},
+            debug_info::{OlaDebugInfo, OlaDebugLine},
             decoder::decode_binary_program_to_instructions,
+            verifier::{BytecodeDiagnostic, VerifyError},
         },
         vm::{
-            hardware::{ContractAddress, OlaStorage},
+            error::ProcessorError,
+            hardware::{ContractAddress, OlaMemory, OlaStorage},
+            heap::HEAP_PTR,
+            opcodes::OlaOpcode,
             types::Event,
         },
     };
-    use interpreter::sema::symbol;
+    use interpreter::{interpreter::ProphetLimits, sema::symbol};
 
     use ola_lang_abi::{Abi, FixedArray4, FixedArray8, Value};
     use std::{
+        cell::RefCell,
         collections::HashMap,
         fs::File,
-        io::BufReader,
+        io::{BufReader, Cursor, Write},
         path::PathBuf,
+        rc::Rc,
+        sync::Arc,
         time::{SystemTime, UNIX_EPOCH},
     };
+    use tempfile::TempDir;
 
     #[test]
     fn test_program() {
@@ -75,6 +88,641 @@ mod tests {
         println!("result: {:?}", result);
     }
 
+    #[test]
+    fn test_gas_limit() {
+        let mut writer = get_writer().unwrap();
+        let address = [991, 992, 993, 995];
+        deploy(&mut writer, "contracts/vote_simple_bin.json", address).unwrap();
+        let init_calldata = vec![7, 1, 2, 3, 4, 5, 6, 7, 8, 3826510503];
+        let block_info = BlockExeInfo {
+            block_number: 0,
+            block_timestamp: 0,
+            sequencer_address: [1001, 1002, 1003, 1004],
+            chain_id: 1027,
+        };
+        let tx = OlaTapeInitInfo {
+            version: 0,
+            origin_address: [2001, 2002, 2003, 2004],
+            calldata: init_calldata.clone(),
+            nonce: Some(0),
+            signature_r: None,
+            signature_s: None,
+            tx_hash: None,
+            gas_limit: None,
+        };
+        let mut storage = get_storage().unwrap();
+        let gas_used =
+            TxExeManager::new(ExecuteMode::Debug, block_info, tx, &mut storage, address, 0)
+                .invoke()
+                .unwrap()
+                .gas_used;
+        assert!(gas_used > 0);
+
+        let tx = OlaTapeInitInfo {
+            version: 0,
+            origin_address: [2001, 2002, 2003, 2004],
+            calldata: init_calldata,
+            nonce: Some(0),
+            signature_r: None,
+            signature_s: None,
+            tx_hash: None,
+            gas_limit: Some(gas_used - 1),
+        };
+        let mut storage = get_storage().unwrap();
+        let err = TxExeManager::new(ExecuteMode::Debug, block_info, tx, &mut storage, address, 0)
+            .invoke()
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProcessorError>(),
+            Some(ProcessorError::OutOfGas(..))
+        ));
+    }
+
+    #[test]
+    fn test_storage_checkpoint() {
+        let mut storage = get_storage().unwrap();
+        let address = [0, 0, 0, 7777];
+        let key = [0, 0, 0, 1];
+        storage.sstore(address, key, [0, 0, 0, 1]).unwrap();
+
+        storage.checkpoint();
+        storage.sstore(address, key, [0, 0, 0, 2]).unwrap();
+        storage.checkpoint();
+        storage.sstore(address, key, [0, 0, 0, 3]).unwrap();
+        storage.revert_to_checkpoint();
+        assert_eq!(storage.read(address, key).unwrap(), Some([0, 0, 0, 2]));
+        storage.commit_checkpoint();
+        assert_eq!(storage.read(address, key).unwrap(), Some([0, 0, 0, 2]));
+
+        storage.checkpoint();
+        storage.sstore(address, [0, 0, 0, 2], [0, 0, 0, 4]).unwrap();
+        storage.revert_to_checkpoint();
+        assert_eq!(storage.read(address, [0, 0, 0, 2]).unwrap(), None);
+        assert_eq!(storage.get_tx_storage_access_logs().len(), 2);
+    }
+
+    #[test]
+    fn test_sccall_revert() {
+        // The callee writes storage, an event and the tape, then fails an assert.
+        let callee_asm = "main:
+.LBL0_0:
+  mov r0 0
+  mov r1 100
+  mstore [r1] r0
+  mstore [r1,+1] r0
+  mstore [r1,+2] r0
+  mov r2 1
+  mstore [r1,+3] r2
+  mov r2 104
+  mstore [r2] r0
+  mstore [r2,+1] r0
+  mstore [r2,+2] r0
+  mov r3 5
+  mstore [r2,+3] r3
+  sstore r1 r2
+  mov r3 1
+  mov r4 200
+  mstore [r4] r3
+  mstore [r4,+1] r1
+  mov r5 210
+  mstore [r5] r3
+  mstore [r5,+1] r3
+  log r4 r5
+  mov r6 123456789
+  mov r7 220
+  mstore [r7] r6
+  tstore r7 1
+  assert r0
+  end";
+        // The caller stores and logs the return length of its sccall.
+        let caller_asm = "main:
+.LBL0_0:
+  mov r0 0
+  mov r1 100
+  mstore [r1] r0
+  mstore [r1,+1] r0
+  mstore [r1,+2] r0
+  mov r2 8891
+  mstore [r1,+3] r2
+  sccall r1 0
+  mov r2 110
+  mov r3 1
+  tload r2 r3 1
+  mload r3 [r2]
+  mov r1 120
+  mstore [r1] r0
+  mstore [r1,+1] r0
+  mstore [r1,+2] r0
+  mov r2 2
+  mstore [r1,+3] r2
+  mov r2 124
+  mstore [r2] r0
+  mstore [r2,+1] r0
+  mstore [r2,+2] r0
+  mstore [r2,+3] r3
+  sstore r1 r2
+  mov r4 1
+  mov r5 200
+  mstore [r5] r4
+  mstore [r5,+1] r1
+  mov r6 210
+  mstore [r6] r4
+  mstore [r6,+1] r3
+  log r5 r6
+  end";
+        let writer = get_writer().unwrap();
+        let caller = [0, 0, 0, 8890];
+        let callee = [0, 0, 0, 8891];
+        let program = |asm: &str| {
+            let bundle = serde_json::json!({ "program": asm, "prophets": [] });
+            encode_asm_from_json_string(bundle.to_string()).unwrap()
+        };
+        let caller_program = program(caller_asm);
+        let end_pc = decode_binary_program_to_instructions(caller_program.clone())
+            .unwrap()
+            .iter()
+            .take_while(|instruction| instruction.opcode != OlaOpcode::END)
+            .map(|instruction| instruction.binary_length() as u64)
+            .sum();
+        writer.save_program(caller_program, caller).unwrap();
+        writer.save_program(program(callee_asm), callee).unwrap();
+
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let mut debugger = OlaDebugger::new(
+            Box::new(Cursor::new(b"tape\nc\n".to_vec())),
+            Box::new(SharedBuf(output.clone())),
+        );
+        debugger.start_running();
+        debugger.add_breakpoint(OlaBreakpoint {
+            code_addr: Some(caller),
+            pc: end_pc,
+        });
+        let block_info = BlockExeInfo {
+            block_number: 0,
+            block_timestamp: 0,
+            sequencer_address: [1001, 1002, 1003, 1004],
+            chain_id: 1027,
+        };
+        let tx = OlaTapeInitInfo {
+            version: 0,
+            origin_address: [2001, 2002, 2003, 2004],
+            calldata: vec![],
+            nonce: Some(0),
+            signature_r: None,
+            signature_s: None,
+            tx_hash: None,
+            gas_limit: None,
+        };
+        let mut storage = get_storage().unwrap();
+        let mut tx_exe_manager =
+            TxExeManager::new(ExecuteMode::Debug, block_info, tx, &mut storage, caller, 0);
+        tx_exe_manager.set_debugger(debugger);
+        let result = tx_exe_manager.invoke().unwrap();
+
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].address, caller);
+        assert_eq!(result.events[0].data, vec![SCCALL_RETURN_LEN_REVERTED]);
+        assert_eq!(
+            storage.read(caller, [0, 0, 0, 2]).unwrap(),
+            Some([0, 0, 0, SCCALL_RETURN_LEN_REVERTED])
+        );
+        assert_eq!(storage.read(callee, [0, 0, 0, 1]).unwrap(), None);
+        let output = String::from_utf8(output.borrow().clone()).unwrap();
+        assert!(output.contains(": <reverted>"));
+        assert!(!output.contains(": 123456789"));
+    }
+
+    #[test]
+    fn test_prophets_compiled_once() {
+        let mut writer = get_writer().unwrap();
+        let first = [0, 0, 0, 8881];
+        let second = [0, 0, 0, 8882];
+        deploy(&mut writer, "contracts/u256_basic_bin.json", first).unwrap();
+        deploy(&mut writer, "contracts/u256_basic_bin.json", second).unwrap();
+
+        let mut storage = get_storage().unwrap();
+        let (program, first_prophets) = storage.get_program_with_prophets(first).unwrap();
+        let (_, second_prophets) = storage.get_program_with_prophets(second).unwrap();
+        assert_eq!(first_prophets.len(), program.prophets.len());
+        for (host, interpreter) in first_prophets.iter() {
+            assert!(Arc::ptr_eq(interpreter, &second_prophets[host]));
+        }
+    }
+
+    #[test]
+    fn test_malformed_prophet_rejected_at_deploy() {
+        let mut path = get_test_dir();
+        path.push("contracts/u256_basic_bin.json");
+        let file = File::open(path).unwrap();
+        let program: BinaryProgram = serde_json::from_reader(BufReader::new(file)).unwrap();
+
+        let writer = get_writer().unwrap();
+        for statement in ["cid.out = ;", "cid.out = 99999999999999999999;"] {
+            let mut program = program.clone();
+            program.prophets[0].code =
+                format!("%{{\n    entry() {{\n        {}\n    }}\n%}}", statement);
+            let err = writer.save_program(program, [0, 0, 0, 8883]).unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<ProcessorError>(),
+                Some(ProcessorError::InterpreterCompileError(80, _))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_native_prophets() {
+        let mut writer = get_writer().unwrap();
+        let interpreted = [0, 0, 0, 8884];
+        let native = [0, 0, 0, 8885];
+        deploy(&mut writer, "contracts/u256_basic_bin.json", interpreted).unwrap();
+
+        let mut path = get_test_dir();
+        path.push("contracts/u256_basic_bin.json");
+        let file = File::open(path).unwrap();
+        let mut program: BinaryProgram = serde_json::from_reader(BufReader::new(file)).unwrap();
+        for prophet in program.prophets.iter_mut() {
+            let name = match prophet.host {
+                804 => "u64_mod",
+                829 => "u64_div",
+                _ => continue,
+            };
+            prophet.code = String::new();
+            prophet.native = Some(name.to_string());
+        }
+        writer.save_program(program, native).unwrap();
+
+        let (_, prophets) = get_storage()
+            .unwrap()
+            .get_program_with_prophets(native)
+            .unwrap();
+        assert!(!prophets.contains_key(&804) && !prophets.contains_key(&829));
+
+        let calldata = vec![0u64, 2590488802];
+        assert_eq!(
+            call(native, calldata.clone(), None).unwrap(),
+            call(interpreted, calldata, None).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_load_program_stored_before_native_prophets() {
+        // u256_basic_bin.json as bincode stored it before prophets could be native.
+        let mut path = get_test_dir();
+        path.push("contracts/u256_basic_bin.bincode");
+        let stored = std::fs::read(path).unwrap();
+        let program = BinaryProgram::from_bytes(&stored).unwrap();
+        assert!(program
+            .prophets
+            .iter()
+            .all(|prophet| prophet.native.is_none()));
+        assert_eq!(program.to_bytes().unwrap(), stored);
+
+        let mut path = get_test_dir();
+        path.push("contracts/u256_basic_bin.json");
+        let file = File::open(path).unwrap();
+        let mut program: BinaryProgram = serde_json::from_reader(BufReader::new(file)).unwrap();
+        assert_eq!(program.to_bytes().unwrap(), stored);
+
+        let host = program.prophets[0].host;
+        program.prophets[0].native = Some("u64_div".to_string());
+        let bytes = program.to_bytes().unwrap();
+        assert_ne!(bytes, stored);
+        let loaded = BinaryProgram::from_bytes(&bytes).unwrap();
+        for prophet in loaded.prophets.iter() {
+            let expected = (prophet.host == host).then_some("u64_div");
+            assert_eq!(prophet.native.as_deref(), expected);
+        }
+    }
+
+    #[test]
+    fn test_prophet_limits() {
+        let mut writer = get_writer().unwrap();
+        let address = [0, 0, 0, 8887];
+        deploy(&mut writer, "contracts/u256_basic_bin.json", address).unwrap();
+        let call_with_limits = |limits: ProphetLimits| {
+            let block_info = BlockExeInfo {
+                block_number: 0,
+                block_timestamp: 0,
+                sequencer_address: [1001, 1002, 1003, 1004],
+                chain_id: 1027,
+            };
+            let tx = OlaTapeInitInfo {
+                version: 0,
+                origin_address: [0, 0, 0, 0],
+                calldata: vec![0u64, 2590488802],
+                nonce: None,
+                signature_r: None,
+                signature_s: None,
+                tx_hash: None,
+                gas_limit: None,
+            };
+            let mut storage = get_storage().unwrap();
+            let mut tx_exe_manager =
+                TxExeManager::new(ExecuteMode::Debug, block_info, tx, &mut storage, address, 0);
+            tx_exe_manager.set_prophet_limits(limits);
+            tx_exe_manager.call()
+        };
+
+        assert!(call_with_limits(ProphetLimits::default()).is_ok());
+        let err = call_with_limits(ProphetLimits {
+            max_steps: 1,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProcessorError>(),
+            Some(ProcessorError::ProphetStepLimitExceeded(_, 1))
+        ));
+        let err = call_with_limits(ProphetLimits {
+            max_outputs: 0,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProcessorError>(),
+            Some(ProcessorError::ProphetOutputLimitExceeded(_, _, 0))
+        ));
+    }
+
+    #[test]
+    fn test_failed_prophet_run_reports_steps() {
+        let mut path = get_test_dir();
+        path.push("contracts/u256_basic_bin.json");
+        let file = File::open(path).unwrap();
+        let program: BinaryProgram = serde_json::from_reader(BufReader::new(file)).unwrap();
+        let mut prophet = program.prophets[0].clone();
+        let interpreter = compile_prophet(&prophet).unwrap();
+        prophet.ctx.push((HEAP_PTR.to_string(), 0));
+
+        let limits = ProphetLimits {
+            max_steps: 1,
+            ..Default::default()
+        };
+        let run = interpreter
+            .execute_with_limits(&prophet, vec![1], &OlaMemory::default(), &limits)
+            .unwrap();
+        assert!(matches!(
+            run.values,
+            Err(ProcessorError::ProphetStepLimitExceeded(80, 1))
+        ));
+        assert_eq!(run.steps, 2);
+    }
+
+    #[test]
+    fn test_unknown_native_prophet_rejected_at_deploy() {
+        let mut path = get_test_dir();
+        path.push("contracts/u256_basic_bin.json");
+        let file = File::open(path).unwrap();
+        let mut program: BinaryProgram = serde_json::from_reader(BufReader::new(file)).unwrap();
+        program.prophets[0].native = Some("cbrt".to_string());
+
+        let writer = get_writer().unwrap();
+        let err = writer.save_program(program, [0, 0, 0, 8886]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<VerifyError>().unwrap().diagnostics,
+            vec![BytecodeDiagnostic::UnknownNativeProphet {
+                host: 80,
+                name: "cbrt".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_debugger() {
+        let mut writer = get_writer().unwrap();
+        let address = [991, 992, 993, 996];
+        deploy(&mut writer, "contracts/vote_simple_bin.json", address).unwrap();
+        let block_info = BlockExeInfo {
+            block_number: 0,
+            block_timestamp: 0,
+            sequencer_address: [1001, 1002, 1003, 1004],
+            chain_id: 1027,
+        };
+        let tx = || OlaTapeInitInfo {
+            version: 0,
+            origin_address: [2001, 2002, 2003, 2004],
+            calldata: vec![7, 1, 2, 3, 4, 5, 6, 7, 8, 3826510503],
+            nonce: Some(0),
+            signature_r: None,
+            signature_s: None,
+            tx_hash: None,
+            gas_limit: None,
+        };
+
+        // pc 6 and pc 19 both call the function at pc 55.
+        let commands = "b 6\nc\nn\nw\nb 55\nc\nbt\nregs\nd 55\nd 6\nbreakpoints\nc\n";
+        let output = Rc::new(RefCell::new(Vec::new()));
+        let mut debugger = OlaDebugger::new(
+            Box::new(Cursor::new(commands.as_bytes().to_vec())),
+            Box::new(SharedBuf(output.clone())),
+        );
+        let mut debug_info = OlaDebugInfo::default();
+        debug_info.insert(
+            55,
+            OlaDebugLine {
+                line: 120,
+                asm: "add r9 r9 4".to_string(),
+                function: "callee".to_string(),
+                labels: vec!["callee".to_string()],
+            },
+        );
+        debugger.add_debug_info(address, debug_info);
+        // Deleting pc 6 must not remove the breakpoint of another contract.
+        debugger.add_breakpoint(OlaBreakpoint {
+            code_addr: Some([1, 2, 3, 4]),
+            pc: 6,
+        });
+        let mut storage = get_storage().unwrap();
+        let mut tx_exe_manager = TxExeManager::new(
+            ExecuteMode::Debug,
+            block_info,
+            tx(),
+            &mut storage,
+            address,
+            0,
+        );
+        tx_exe_manager.set_debugger(debugger);
+        tx_exe_manager.invoke().unwrap();
+        let output = String::from_utf8(output.borrow().clone()).unwrap();
+        assert!(output.contains("breakpoint set at pc 6"));
+        assert!(output.contains("clk: 0, pc: 0,"));
+        assert!(output.contains("pc: 8, mov r5 r0"));
+        assert!(output
+            .contains("#0 env 0 code [991, 992, 993, 996] pc 55 <callee> in callee at line 120"));
+        assert!(output.contains("#1 env 0 code [991, 992, 993, 996] pc 19"));
+        assert!(output.contains("psp("));
+        assert!(output.contains("breakpoint removed at pc 55"));
+        assert!(output.contains("breakpoint removed at pc 6"));
+        assert!(output.contains("pc 6 in [1, 2, 3, 4]"));
+
+        let mut debugger = OlaDebugger::new(
+            Box::new(Cursor::new(b"s\nq\n".to_vec())),
+            Box::new(std::io::sink()),
+        );
+        debugger.start_running();
+        debugger.add_breakpoint(OlaBreakpoint {
+            code_addr: None,
+            pc: 6,
+        });
+        let mut storage = get_storage().unwrap();
+        let mut tx_exe_manager = TxExeManager::new(
+            ExecuteMode::Debug,
+            block_info,
+            tx(),
+            &mut storage,
+            address,
+            0,
+        );
+        tx_exe_manager.set_debugger(debugger);
+        assert!(tx_exe_manager.invoke().is_err());
+    }
+
+    #[test]
+    fn test_debug_info_backtrace() {
+        let writer = get_writer().unwrap();
+        let address = [991, 992, 993, 997];
+        let mut path = get_test_dir();
+        path.push("contracts/vote_simple_bin.json");
+        let mut program: BinaryProgram =
+            serde_json::from_reader(BufReader::new(File::open(path).unwrap())).unwrap();
+        let mut debug_info = OlaDebugInfo::default();
+        debug_info.insert(
+            6,
+            OlaDebugLine {
+                line: 6,
+                asm: "call vote_init".to_string(),
+                function: "main".to_string(),
+                labels: vec![],
+            },
+        );
+        debug_info.insert(
+            55,
+            OlaDebugLine {
+                line: 40,
+                asm: "add r9 r9 4".to_string(),
+                function: "vote_init".to_string(),
+                labels: vec!["vote_init".to_string()],
+            },
+        );
+        program.debug_info = Some(debug_info.clone());
+        writer.save_program(program, address).unwrap();
+
+        let mut storage = get_storage().unwrap();
+        assert_eq!(
+            storage.get_program(address).unwrap().debug_info,
+            Some(debug_info)
+        );
+
+        // runs out of gas at the first instruction of the function called on pc 6.
+        let block_info = BlockExeInfo {
+            block_number: 0,
+            block_timestamp: 0,
+            sequencer_address: [1001, 1002, 1003, 1004],
+            chain_id: 1027,
+        };
+        let tx = OlaTapeInitInfo {
+            version: 0,
+            origin_address: [2001, 2002, 2003, 2004],
+            calldata: vec![7, 1, 2, 3, 4, 5, 6, 7, 8, 3826510503],
+            nonce: Some(0),
+            signature_r: None,
+            signature_s: None,
+            tx_hash: None,
+            gas_limit: Some(4),
+        };
+        let err = TxExeManager::new(
+            ExecuteMode::Invoke,
+            block_info,
+            tx,
+            &mut storage,
+            address,
+            0,
+        )
+        .invoke()
+        .unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("#0 pc 55 in vote_init (line 40: add r9 r9 4)"));
+        assert!(msg.contains("#1 pc 6 in main (line 6: call vote_init)"));
+        assert!(matches!(
+            err.downcast_ref::<ProcessorError>(),
+            Some(ProcessorError::OutOfGas(..))
+        ));
+    }
+
+    #[test]
+    fn test_profiler() {
+        let writer = get_writer().unwrap();
+        let address = [0, 0, 0, 998];
+        let mut path = get_test_dir();
+        path.push("contracts/vote_simple_bin.json");
+        let mut program: BinaryProgram =
+            serde_json::from_reader(BufReader::new(File::open(path).unwrap())).unwrap();
+        let mut debug_info = OlaDebugInfo::default();
+        debug_info.insert(
+            0,
+            OlaDebugLine {
+                line: 1,
+                asm: String::new(),
+                function: "main".to_string(),
+                labels: vec![],
+            },
+        );
+        program.debug_info = Some(debug_info);
+        writer.save_program(program, address).unwrap();
+
+        let block_info = BlockExeInfo {
+            block_number: 0,
+            block_timestamp: 0,
+            sequencer_address: [1001, 1002, 1003, 1004],
+            chain_id: 1027,
+        };
+        let tx = OlaTapeInitInfo {
+            version: 0,
+            origin_address: [2001, 2002, 2003, 2004],
+            calldata: vec![7, 1, 2, 3, 4, 5, 6, 7, 8, 3826510503],
+            nonce: Some(0),
+            signature_r: None,
+            signature_s: None,
+            tx_hash: None,
+            gas_limit: None,
+        };
+        let mut storage = get_storage().unwrap();
+        let mut tx_exe_manager = TxExeManager::new(
+            ExecuteMode::Invoke,
+            block_info,
+            tx,
+            &mut storage,
+            address,
+            0,
+        );
+        tx_exe_manager.set_profiler(OlaProfiler::new());
+        tx_exe_manager.invoke().unwrap();
+        let profiler = tx_exe_manager.take_profiler().unwrap();
+
+        let summary = profiler.summary();
+        let main = &summary.functions[0];
+        assert_eq!(main.name, "0x3e6::main");
+        assert_eq!(main.calls, 1);
+        assert_eq!(main.inclusive_cost, summary.total);
+        // functions without debug info are named by their entry pc.
+        assert!(summary
+            .functions
+            .iter()
+            .any(|f| f.name.starts_with("0x3e6::pc_") && f.calls > 1));
+        assert!(summary.total.mem_accesses > 0);
+        assert!(summary.total.storage_accesses > 0);
+
+        let folded = profiler.folded_stacks();
+        assert!(folded
+            .lines()
+            .any(|line| line.starts_with("0x3e6::main;0x3e6::pc_")));
+        let cycles: u64 = folded
+            .lines()
+            .map(|line| line.rsplit(' ').next().unwrap().parse::<u64>().unwrap())
+            .sum();
+        assert_eq!(cycles, summary.total.cycles);
+    }
+
     #[test]
     fn test_storage_u256() {
         let mut writer = get_writer().unwrap();
@@ -562,6 +1210,17 @@ mod tests {
         }
     }
 
+    struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            std::io::Result::Ok(())
+        }
+    }
+
     fn call(
         address: ContractAddress,
         calldata: Vec<u64>,
@@ -585,6 +1244,7 @@ mod tests {
             signature_r: None,
             signature_s: None,
             tx_hash: None,
+            gas_limit: None,
         };
         let mut tx_exe_manager: TxExeManager =
             TxExeManager::new(ExecuteMode::Debug, block_info, tx, &mut storage, address, 0);
@@ -618,6 +1278,7 @@ mod tests {
             signature_r: None,
             signature_s: None,
             tx_hash: None,
+            gas_limit: None,
         };
         let mut tx_exe_manager: TxExeManager =
             TxExeManager::new(ExecuteMode::Debug, block_info, tx, &mut storage, address, 0);
@@ -678,10 +1339,19 @@ mod tests {
         Ok(writer)
     }
 
+    thread_local! {
+        // Every test thread gets its own database, removed when the thread
+        // exits. The primary is created up front so that a storage can be
+        // opened before anything is deployed.
+        static DB_DIR: TempDir = {
+            let dir = tempfile::tempdir().unwrap();
+            DiskStorageWriter::new(dir.path().display().to_string()).unwrap();
+            dir
+        };
+    }
+
     fn get_db_path() -> String {
-        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-        path.push("db_test");
-        path.into_os_string().into_string().unwrap()
+        DB_DIR.with(|dir| dir.path().display().to_string())
     }
 
     fn get_test_dir() -> PathBuf {
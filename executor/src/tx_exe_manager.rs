@@ -2,20 +2,28 @@ use core::{
     trace::exe_trace::TxExeTrace,
     tx::TxResult,
     vm::{
+        error::ProcessorError,
         hardware::{ContractAddress, ExeContext, OlaTape},
+        opcodes::OlaOpcode,
         types::{Event, Hash},
     },
 };
 use std::collections::HashMap;
 
 use anyhow::Ok;
+use interpreter::interpreter::ProphetLimits;
 
 use crate::{
     batch_exe_manager::BlockExeInfo,
-    config::{ExecuteMode, ADDR_U64_ENTRYPOINT, FUNCTION_SELECTOR_SYSTEM_ENTRANCE},
+    config::{
+        ExecuteMode, GasSchedule, ADDR_U64_ENTRYPOINT, FUNCTION_SELECTOR_SYSTEM_ENTRANCE,
+        SCCALL_RETURN_LEN_REVERTED,
+    },
     contract_executor::{OlaContractExecutor, OlaContractExecutorState},
+    debugger::OlaDebugger,
     exe_trace::tx::TxTraceManager,
     ola_storage::OlaCachedStorage,
+    profiler::OlaProfiler,
 };
 
 #[derive(Debug, Copy, Clone, Default)]
@@ -34,6 +42,7 @@ pub struct OlaTapeInitInfo {
     pub signature_r: Option<[u64; 4]>,
     pub signature_s: Option<[u64; 4]>,
     pub tx_hash: Option<[u64; 4]>,
+    pub gas_limit: Option<u64>,
 }
 
 pub(crate) struct TxEventManager {
@@ -41,6 +50,7 @@ pub(crate) struct TxEventManager {
     prev_events_cnt_in_batch: usize,
     biz_contract_address: ContractAddress,
     events: Vec<Event>,
+    checkpoints: Vec<usize>,
 }
 
 impl TxEventManager {
@@ -54,6 +64,7 @@ impl TxEventManager {
             prev_events_cnt_in_batch,
             biz_contract_address,
             events: Vec::new(),
+            checkpoints: Vec::new(),
         }
     }
 
@@ -71,6 +82,94 @@ impl TxEventManager {
             data,
         });
     }
+
+    pub fn checkpoint(&mut self) {
+        self.checkpoints.push(self.events.len());
+    }
+
+    pub fn commit_checkpoint(&mut self) {
+        self.checkpoints.pop();
+    }
+
+    pub fn revert_to_checkpoint(&mut self) {
+        if let Some(events_len) = self.checkpoints.pop() {
+            self.events.truncate(events_len);
+        }
+    }
+}
+
+/// Whether `err` is raised by the contract that failed, so that only its call
+/// frame is reverted and the caller goes on. Every other error, including
+/// errors that are not a ProcessorError, aborts the tx.
+fn reverts_call_frame(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<ProcessorError>(),
+        Some(
+            ProcessorError::U32RangeCheckFail
+                | ProcessorError::AssertFail(..)
+                | ProcessorError::MemVistInv(_)
+                | ProcessorError::TapeVistInv(_)
+                | ProcessorError::PcVistInv(_)
+                | ProcessorError::TloadFlagInvalid(_)
+                | ProcessorError::TstoreError(_)
+                | ProcessorError::PubKeyInvalid(_)
+                | ProcessorError::SignatureInvalid(_)
+                | ProcessorError::MessageInvalid(_)
+                | ProcessorError::MstoreError(_)
+                | ProcessorError::MloadError(_)
+                | ProcessorError::MemoryAccessError(_)
+                | ProcessorError::TapeAccessError(_)
+                | ProcessorError::InvalidInstruction(_)
+                | ProcessorError::CannotSStoreInCall
+                | ProcessorError::StorageStoreOnCallError
+                | ProcessorError::InvalidTopicLength(_)
+                | ProcessorError::EventOnCallError
+                | ProcessorError::InterpreterRunError(_)
+                | ProcessorError::ProphetStepLimitExceeded(..)
+                | ProcessorError::ProphetCallDepthExceeded(..)
+                | ProcessorError::ProphetMemoryLimitExceeded(..)
+                | ProcessorError::ProphetOutputLimitExceeded(..)
+        )
+    )
+}
+
+pub(crate) struct TxGasManager {
+    schedule: GasSchedule,
+    gas_limit: Option<u64>,
+    gas_used: u64,
+}
+
+impl TxGasManager {
+    fn new(schedule: GasSchedule, gas_limit: Option<u64>) -> Self {
+        Self {
+            schedule,
+            gas_limit,
+            gas_used: 0,
+        }
+    }
+
+    pub fn gas_used(&self) -> u64 {
+        self.gas_used
+    }
+
+    pub fn consume(&mut self, opcode: OlaOpcode) -> anyhow::Result<()> {
+        self.charge(self.schedule.cost(opcode))
+    }
+
+    pub fn consume_prophet(&mut self, steps: u64) -> anyhow::Result<()> {
+        self.charge(steps.saturating_mul(self.schedule.prophet_step_cost()))
+    }
+
+    fn charge(&mut self, cost: u64) -> anyhow::Result<()> {
+        let required = self.gas_used.saturating_add(cost);
+        if let Some(gas_limit) = self.gas_limit {
+            if required > gas_limit {
+                return Err(ProcessorError::OutOfGas(gas_limit, required).into());
+            }
+        }
+        self.gas_used = required;
+        Ok(())
+    }
 }
 
 pub struct TxExeManager<'batch> {
@@ -79,10 +178,14 @@ pub struct TxExeManager<'batch> {
     env_stack: Vec<(usize, OlaContractExecutor)>,
     tape: OlaTape,
     tx_event_manager: TxEventManager,
+    tx_gas_manager: TxGasManager,
+    prophet_limits: ProphetLimits,
     storage: &'batch mut OlaCachedStorage,
     trace_manager: TxTraceManager,
     entry_contract: ContractAddress,
     accessed_bytecodes: HashMap<ContractAddress, Vec<u64>>,
+    debugger: Option<OlaDebugger>,
+    profiler: Option<OlaProfiler>,
 }
 
 impl<'batch> TxExeManager<'batch> {
@@ -117,16 +220,49 @@ impl<'batch> TxExeManager<'batch> {
                 prev_events_cnt_in_batch,
                 biz_contract_address,
             ),
+            tx_gas_manager: TxGasManager::new(GasSchedule::default(), tx.gas_limit),
+            prophet_limits: ProphetLimits::default(),
             storage,
             trace_manager: TxTraceManager::default(),
             entry_contract,
             accessed_bytecodes: HashMap::new(),
+            debugger: None,
+            profiler: None,
         };
         let init_values = manager.init_tape(block_info, tx, entry_contract);
         manager.trace_manager.init_tape(init_values);
         manager
     }
 
+    pub fn set_gas_schedule(&mut self, schedule: GasSchedule) {
+        self.tx_gas_manager.schedule = schedule;
+    }
+
+    pub fn set_prophet_limits(&mut self, limits: ProphetLimits) {
+        self.prophet_limits = limits;
+    }
+
+    /// Attaches an interactive debugger, only consulted in
+    /// `ExecuteMode::Debug`.
+    pub fn set_debugger(&mut self, debugger: OlaDebugger) {
+        self.debugger = Some(debugger);
+    }
+
+    pub fn take_debugger(&mut self) -> Option<OlaDebugger> {
+        self.debugger.take()
+    }
+
+    /// Attaches a profiler. Memory, poseidon, storage and range check counts
+    /// need the step trace, so they are only collected in `ExecuteMode::Invoke`
+    /// and `ExecuteMode::Debug`.
+    pub fn set_profiler(&mut self, profiler: OlaProfiler) {
+        self.profiler = Some(profiler);
+    }
+
+    pub fn take_profiler(&mut self) -> Option<OlaProfiler> {
+        self.profiler.take()
+    }
+
     fn init_tape(
         &mut self,
         block_info: BlockExeInfo,
@@ -153,7 +289,9 @@ impl<'batch> TxExeManager<'batch> {
     }
 
     pub fn invoke(&mut self) -> anyhow::Result<TxResult> {
-        let program = self.storage.get_program(self.entry_contract)?;
+        let (program, prophets) = self
+            .storage
+            .get_program_with_prophets(self.entry_contract)?;
         self.accessed_bytecodes
             .insert(self.entry_contract, program.bytecode_u64s()?);
         let entry_env = OlaContractExecutor::new(
@@ -163,18 +301,44 @@ impl<'batch> TxExeManager<'batch> {
                 code_addr: self.entry_contract,
             },
             program,
+            prophets,
+            self.prophet_limits,
         )?;
         self.enqueue_new_env(entry_env);
 
         loop {
             let env = self.pop_env();
             if let Some((env_idx, mut executor)) = env {
-                let result = executor.resume(
+                self.on_debugger_env_resumed(env_idx);
+                if let Some(profiler) = self.profiler.as_mut() {
+                    profiler.on_env_resumed(
+                        env_idx,
+                        executor.get_code_addr(),
+                        executor.get_debug_info(),
+                    );
+                }
+                let result = match executor.resume(
                     &mut self.tape,
                     &mut self.tx_event_manager,
+                    &mut self.tx_gas_manager,
                     self.storage,
                     &mut self.trace_manager,
-                )?;
+                    self.debugger.as_mut(),
+                    self.profiler.as_mut(),
+                ) {
+                    Result::Ok(result) => result,
+                    Err(err) => {
+                        // a failed callee only reverts its own call frame, the caller goes on.
+                        if self.env_stack.is_empty()
+                            || self.is_debugger_aborted()
+                            || !reverts_call_frame(&err)
+                        {
+                            return Err(err);
+                        }
+                        self.revert_call_frame(env_idx, err);
+                        continue;
+                    }
+                };
                 match result {
                     OlaContractExecutorState::Running => {
                         anyhow::bail!("Invalid Executor result, cannot be Running.")
@@ -194,9 +358,11 @@ impl<'batch> TxExeManager<'batch> {
                             executor.get_clk() - 1,
                             executor.get_regs(),
                         );
-                        let callee_program = self.storage.get_program(callee_addr)?;
+                        let (callee_program, callee_prophets) =
+                            self.storage.get_program_with_prophets(callee_addr)?;
                         let storage_addr = executor.get_storage_addr();
                         self.enqueue_caller(env_idx, executor);
+                        self.enter_call_frame();
 
                         if !self.accessed_bytecodes.contains_key(&callee_addr) {
                             self.accessed_bytecodes
@@ -209,6 +375,8 @@ impl<'batch> TxExeManager<'batch> {
                                 code_addr: callee_addr,
                             },
                             callee_program,
+                            callee_prophets,
+                            self.prophet_limits,
                         )?;
                         self.enqueue_new_env(callee);
                     }
@@ -227,8 +395,10 @@ impl<'batch> TxExeManager<'batch> {
                             executor.get_clk() - 1,
                             executor.get_regs(),
                         );
-                        let callee_program = self.storage.get_program(callee_addr)?;
+                        let (callee_program, callee_prophets) =
+                            self.storage.get_program_with_prophets(callee_addr)?;
                         self.enqueue_caller(env_idx, executor);
+                        self.enter_call_frame();
 
                         if !self.accessed_bytecodes.contains_key(&callee_addr) {
                             self.accessed_bytecodes
@@ -241,6 +411,8 @@ impl<'batch> TxExeManager<'batch> {
                                 code_addr: callee_addr,
                             },
                             callee_program,
+                            callee_prophets,
+                            self.prophet_limits,
                         )?;
                         self.enqueue_new_env(callee);
                     }
@@ -248,9 +420,11 @@ impl<'batch> TxExeManager<'batch> {
                         if self.mode == ExecuteMode::Debug {
                             println!("[END] {:?}", executor.get_storage_addr());
                         }
+                        if !self.env_stack.is_empty() {
+                            self.commit_call_frame();
+                        }
                         self.trace_manager
                             .on_end(env_idx as u64, executor.get_clk() - 1)
-                        // no need to do anything
                     }
                 }
             } else {
@@ -261,12 +435,15 @@ impl<'batch> TxExeManager<'batch> {
             trace: self.get_tx_trace(),
             storage_access_logs: self.storage.get_tx_storage_access_logs(),
             events: self.tx_event_manager.events.clone(),
+            gas_used: self.tx_gas_manager.gas_used(),
         };
         Ok(result)
     }
 
     pub fn call(&mut self) -> anyhow::Result<Vec<u64>> {
-        let program = self.storage.get_program(self.entry_contract)?;
+        let (program, prophets) = self
+            .storage
+            .get_program_with_prophets(self.entry_contract)?;
         let entry_env = OlaContractExecutor::new(
             self.mode,
             ExeContext {
@@ -274,26 +451,54 @@ impl<'batch> TxExeManager<'batch> {
                 code_addr: self.entry_contract,
             },
             program,
+            prophets,
+            self.prophet_limits,
         )?;
         self.enqueue_new_env(entry_env);
         let mut output: Vec<u64> = vec![];
         loop {
             let env = self.pop_env();
             if let Some((env_idx, mut executor)) = env {
-                let result = executor.resume(
+                self.on_debugger_env_resumed(env_idx);
+                if let Some(profiler) = self.profiler.as_mut() {
+                    profiler.on_env_resumed(
+                        env_idx,
+                        executor.get_code_addr(),
+                        executor.get_debug_info(),
+                    );
+                }
+                let result = match executor.resume(
                     &mut self.tape,
                     &mut self.tx_event_manager,
+                    &mut self.tx_gas_manager,
                     self.storage,
                     &mut self.trace_manager,
-                )?;
+                    self.debugger.as_mut(),
+                    self.profiler.as_mut(),
+                ) {
+                    Result::Ok(result) => result,
+                    Err(err) => {
+                        // a failed callee only reverts its own call frame, the caller goes on.
+                        if self.env_stack.is_empty()
+                            || self.is_debugger_aborted()
+                            || !reverts_call_frame(&err)
+                        {
+                            return Err(err);
+                        }
+                        self.revert_call_frame(env_idx, err);
+                        continue;
+                    }
+                };
                 match result {
                     OlaContractExecutorState::Running => {
                         anyhow::bail!("Invalid Executor result, cannot be Running.")
                     }
                     OlaContractExecutorState::DelegateCalling(callee_addr) => {
-                        let callee_program = self.storage.get_program(callee_addr)?;
+                        let (callee_program, callee_prophets) =
+                            self.storage.get_program_with_prophets(callee_addr)?;
                         let storage_addr = executor.get_storage_addr();
                         self.enqueue_caller(env_idx, executor);
+                        self.enter_call_frame();
                         let callee = OlaContractExecutor::new(
                             self.mode,
                             ExeContext {
@@ -301,12 +506,16 @@ impl<'batch> TxExeManager<'batch> {
                                 code_addr: callee_addr,
                             },
                             callee_program,
+                            callee_prophets,
+                            self.prophet_limits,
                         )?;
                         self.enqueue_new_env(callee);
                     }
                     OlaContractExecutorState::Calling(callee_addr) => {
-                        let callee_program = self.storage.get_program(callee_addr)?;
+                        let (callee_program, callee_prophets) =
+                            self.storage.get_program_with_prophets(callee_addr)?;
                         self.enqueue_caller(env_idx, executor);
+                        self.enter_call_frame();
                         let callee = OlaContractExecutor::new(
                             self.mode,
                             ExeContext {
@@ -314,6 +523,8 @@ impl<'batch> TxExeManager<'batch> {
                                 code_addr: callee_addr,
                             },
                             callee_program,
+                            callee_prophets,
+                            self.prophet_limits,
                         )?;
                         self.enqueue_new_env(callee);
                     }
@@ -322,6 +533,7 @@ impl<'batch> TxExeManager<'batch> {
                             output = o;
                             break;
                         }
+                        self.commit_call_frame();
                     }
                 }
             } else {
@@ -331,6 +543,61 @@ impl<'batch> TxExeManager<'batch> {
         Ok(output)
     }
 
+    fn on_debugger_env_resumed(&mut self, env_idx: usize) {
+        if let Some(debugger) = self.debugger.as_mut() {
+            let callers = self
+                .env_stack
+                .iter()
+                .map(|(idx, env)| {
+                    let snapshot = EnvOutlineSnapshot {
+                        env_idx: *idx as u64,
+                        clk: env.get_clk(),
+                        pc: env.get_pc(),
+                        context: ExeContext {
+                            storage_addr: env.get_storage_addr(),
+                            code_addr: env.get_code_addr(),
+                        },
+                    };
+                    (snapshot, env.get_call_stack().to_vec())
+                })
+                .collect();
+            debugger.on_env_resumed(env_idx, callers);
+        }
+    }
+
+    fn is_debugger_aborted(&self) -> bool {
+        self.debugger
+            .as_ref()
+            .map(|debugger| debugger.is_aborted())
+            .unwrap_or(false)
+    }
+
+    fn enter_call_frame(&mut self) {
+        self.storage.checkpoint();
+        self.tx_event_manager.checkpoint();
+        self.tape.checkpoint();
+    }
+
+    fn commit_call_frame(&mut self) {
+        self.storage.commit_checkpoint();
+        self.tx_event_manager.commit_checkpoint();
+        self.tape.commit_checkpoint();
+    }
+
+    fn revert_call_frame(&mut self, callee_env_idx: usize, err: anyhow::Error) {
+        if self.mode == ExecuteMode::Debug {
+            println!("[REVERTED] env_idx: {}, err: {}", callee_env_idx, err);
+        }
+        self.storage.revert_to_checkpoint();
+        self.tx_event_manager.revert_to_checkpoint();
+        self.tape.revert_to_checkpoint();
+        if self.is_trace_needed() {
+            self.trace_manager
+                .on_revert(self.tape.tp(), SCCALL_RETURN_LEN_REVERTED);
+        }
+        self.tape.write(SCCALL_RETURN_LEN_REVERTED);
+    }
+
     fn pop_env(&mut self) -> Option<(usize, OlaContractExecutor)> {
         if let Some((env_idx, env)) = self.env_stack.pop() {
             if self.mode == ExecuteMode::Debug {
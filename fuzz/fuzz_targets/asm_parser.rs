@@ -0,0 +1,10 @@
+#![no_main]
+
+use assembler::encoder::encode_asm_from_json_string;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|program: &str| {
+    let bundle = serde_json::json!({ "program": program, "prophets": [] });
+    let _ = encode_asm_from_json_string(bundle.to_string());
+    let _ = encode_asm_from_json_string(program.to_string());
+});
@@ -0,0 +1,24 @@
+#![no_main]
+
+use core::program::binary_program::BinaryInstruction;
+use libfuzzer_sys::fuzz_target;
+
+// One or two bytecode words, the second being the immediate. Whatever decodes
+// has to encode back to the same words.
+fuzz_target!(|words: (u64, Option<u64>)| {
+    let mut binary_code = vec![format!("0x{:0>16x}", words.0)];
+    if let Some(imm) = words.1 {
+        binary_code.push(format!("0x{:x}", imm));
+    }
+    if let Ok(instruction) = BinaryInstruction::decode(binary_code, None) {
+        instruction
+            .get_asm_form_code()
+            .expect("decoded instruction must have its operands");
+        let _ = instruction.get_inst_imm_u64();
+        if let Ok(encoded) = instruction.encode() {
+            let decoded = BinaryInstruction::decode(encoded.clone(), None)
+                .expect("re-encoded instruction must decode");
+            assert_eq!(decoded.encode(), Ok(encoded));
+        }
+    }
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use core::program::binary_program::BinaryProgram;
+use core::program::decoder::decode_binary_program_to_instructions;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|words: Vec<u64>| {
+    let bytecode = words
+        .iter()
+        .map(|w| format!("0x{:0>16x}", w))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let program = BinaryProgram {
+        bytecode,
+        prophets: vec![],
+        debug_info: None,
+    };
+    if let Ok(instructions) = decode_binary_program_to_instructions(program) {
+        for instruction in instructions {
+            instruction
+                .get_asm_form_code()
+                .expect("decoded instruction must have its operands");
+        }
+    }
+});
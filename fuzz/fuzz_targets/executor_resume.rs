@@ -0,0 +1,94 @@
+#![no_main]
+
+use core::program::binary_program::{BinaryInstruction, BinaryProgram};
+use core::program::random_program::random_instructions;
+use core::vm::opcodes::OlaOpcode;
+use core::vm::operands::{ImmediateValue, OlaOperand};
+use executor::batch_exe_manager::BlockExeInfo;
+use executor::config::ExecuteMode;
+use executor::ola_storage::{DiskStorageWriter, OlaCachedStorage};
+use executor::tx_exe_manager::{OlaTapeInitInfo, TxExeManager};
+use libfuzzer_sys::fuzz_target;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const CONTRACT_ADDRESS: [u64; 4] = [13, 14, 15, 16];
+
+// Bounds every run, random jumps loop easily.
+const GAS_LIMIT: u64 = 100_000;
+
+// Moves immediate jump and call targets onto instruction boundaries, deploy
+// refuses anything else.
+fn fix_jump_targets(instructions: &mut [BinaryInstruction]) {
+    let mut pcs = vec![];
+    let mut pc = 0;
+    for instruction in instructions.iter() {
+        pcs.push(pc);
+        pc += instruction.binary_length() as u64;
+    }
+    for instruction in instructions.iter_mut() {
+        let is_jump = matches!(
+            instruction.opcode,
+            OlaOpcode::JMP | OlaOpcode::CJMP | OlaOpcode::CALL
+        );
+        if let (true, Some(OlaOperand::ImmediateOperand { value })) = (is_jump, &instruction.op1) {
+            let target = pcs[(value.to_u64().unwrap_or(0) % pcs.len() as u64) as usize];
+            instruction.op1 = Some(OlaOperand::ImmediateOperand {
+                value: ImmediateValue {
+                    hex: format!("{:#x}", target),
+                },
+            });
+        }
+    }
+}
+
+// Runs a random program that passes the deploy checks through the contract
+// executor's `resume` loop. Execution errors are expected, panics are not.
+fuzz_target!(|input: (u64, u8, Vec<u64>)| {
+    let (seed, len, calldata) = input;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut instructions = random_instructions(&mut rng, len as usize);
+    fix_jump_targets(&mut instructions);
+    let program = match BinaryProgram::from_instructions(instructions, None, false) {
+        Ok(program) => program,
+        Err(_) => return,
+    };
+
+    let db = tempfile::tempdir().expect("temp dir");
+    let db_path = db.path().to_string_lossy().into_owned();
+    // programs without a reachable `end` are rejected here.
+    if DiskStorageWriter::new(db_path.clone())
+        .expect("storage writer")
+        .save_program(program, CONTRACT_ADDRESS)
+        .is_err()
+    {
+        return;
+    }
+    let mut storage = OlaCachedStorage::new(db_path, None).expect("storage");
+
+    let block_info = BlockExeInfo {
+        block_number: 0,
+        block_timestamp: 0,
+        sequencer_address: [0; 4],
+        chain_id: 1027,
+    };
+    let tx = OlaTapeInitInfo {
+        version: 0,
+        origin_address: [0; 4],
+        calldata,
+        nonce: None,
+        signature_r: None,
+        signature_s: None,
+        tx_hash: None,
+        gas_limit: Some(GAS_LIMIT),
+    };
+    let mut tx_exe_manager = TxExeManager::new(
+        ExecuteMode::Invoke,
+        block_info,
+        tx,
+        &mut storage,
+        CONTRACT_ADDRESS,
+        0,
+    );
+    let _ = tx_exe_manager.invoke();
+});
@@ -1,18 +1,24 @@
 use core::program::binary_program::OlaProphet;
 use core::util::converts::u32s_be_to_u256;
+use core::vm::error::ProcessorError;
 use core::vm::hardware::OlaMemory;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::ops::Deref;
+use std::sync::{Arc, RwLock};
 
+use crate::interpreter::ProphetLimits;
 use crate::lexer::token::Token;
 use crate::lexer::token::Token::{Array, ArrayId, Cid, Id, IndexId};
 use crate::parser::node::{
     ArrayIdentNode, ArrayNumNode, AssignNode, BinOpNode, BlockNode, CallNode, CompoundNode,
-    CondStatNode, ContextIdentNode, EntryBlockNode, EntryNode, FeltNumNode, FunctionNode,
-    IdentDeclarationNode, IdentIndexNode, IdentNode, IntegerNumNode, LoopStatNode, MallocNode,
-    MultiAssignNode, PrintfNode, ReturnNode, SqrtNode, TypeNode, UnaryOpNode,
+    CondStatNode, ContextIdentNode, EntryBlockNode, EntryNode, FeltNumNode, ForLoopNode,
+    FunctionNode, IdentDeclarationNode, IdentIndexNode, IdentNode, IntegerNumNode, InvNode,
+    LoopStatNode, MallocNode, MultiAssignNode, Node, PrintfNode, ReadMemNode, ReturnNode, SqrtNode,
+    TypeNode, UnaryOpNode,
 };
 use crate::parser::traversal::{is_node_type, safe_downcast_ref, Traversal};
+use crate::sema::symbol::BuiltIn;
 use crate::sema::symbol::Symbol::FuncSymbol;
 use crate::utils::number::Number::{Bool, Nil};
 use crate::utils::number::NumberRet::{Multiple, Single};
@@ -24,22 +30,22 @@ macro_rules! ident_lookup {
     ($func:tt, $idents: tt, $ret: ty, $single : tt) => {
         pub fn $func(&mut self, name: &str) -> $ret {
             if let Some(value) = self.call_stack.records[self.stack_depth].$idents.get(name) {
-                ident_lookup_ret!(value, $single)
+                ident_lookup_ret!(name, value, $single)
             } else if let Some(value) = self.call_stack.records[GLOBAL_LEVEL].$idents.get(name) {
-                ident_lookup_ret!(value, $single)
+                ident_lookup_ret!(name, value, $single)
             } else {
-                panic!("ident :{} not exist", name)
+                Err(format!("ident :{} not exist", name))
             }
         }
     };
     ($func:tt, $idents: tt,  $ret: ty, $index: ident, $single : tt) => {
         pub fn $func(&mut self, name: &str, $index: usize) -> NumberResult {
             if let Some(value) = self.call_stack.records[self.stack_depth].$idents.get(name) {
-                ident_lookup_ret!(value, $index, $single)
+                ident_lookup_ret!(name, value, $index, $single)
             } else if let Some(value) = self.call_stack.records[GLOBAL_LEVEL].$idents.get(name) {
-                ident_lookup_ret!(value, $index, $single)
+                ident_lookup_ret!(name, value, $index, $single)
             } else {
-                panic!("index ident :{} not exist", name)
+                Err(format!("index ident :{} not exist", name))
             }
         }
     };
@@ -47,13 +53,17 @@ macro_rules! ident_lookup {
 
 #[macro_export]
 macro_rules! ident_lookup_ret {
-    ($value: tt, $index: tt, $single : tt) => {
-        return Ok($single(
-            $value.clone().unwrap().get($index).unwrap().clone(),
-        ))
+    ($name: tt, $value: tt, $index: tt, $single : tt) => {
+        match $value.as_ref().and_then(|values| values.get($index)) {
+            Some(value) => Ok($single(value.clone())),
+            None => Err(format!("{}[{}] not exist", $name, $index)),
+        }
     };
-    ($value: tt, $single : tt) => {
-        return Ok($single($value.clone().unwrap()))
+    ($name: tt, $value: tt, $single : tt) => {
+        match $value {
+            Some(value) => Ok($single(value.clone())),
+            None => Err(format!("ident :{} not assigned", $name)),
+        }
     };
 }
 
@@ -104,16 +114,34 @@ pub struct Executor<'a> {
     // pub vm_mem: &'a MemoryTree,
     mem: &'a OlaMemory,
     stack_depth: usize,
+    host: usize,
+    limits: ProphetLimits,
+    steps: u64,
+    call_depth: usize,
+    allocated: u64,
+    // The limit the run exceeded, reported instead of the error string.
+    limit_error: Option<ProcessorError>,
 }
 
 impl<'a> Executor<'a> {
-    pub fn new(prophet: &OlaProphet, values: Vec<u64>, mem: &'a OlaMemory) -> Self {
+    pub fn new(
+        prophet: &OlaProphet,
+        values: Vec<u64>,
+        mem: &'a OlaMemory,
+        limits: ProphetLimits,
+    ) -> Self {
         let mut executor = Executor {
             call_stack: CallStack::new(),
             context: Vec::new(),
             outputs: Vec::new(),
             mem,
             stack_depth: GLOBAL_LEVEL,
+            host: prophet.host,
+            limits,
+            steps: 0,
+            call_depth: 0,
+            allocated: 0,
+            limit_error: None,
         };
         executor.call_stack.records.push(RuntimeRecord::new(
             "global".to_string(),
@@ -163,116 +191,109 @@ impl<'a> Executor<'a> {
         executor
     }
 
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn take_limit_error(&mut self) -> Option<ProcessorError> {
+        self.limit_error.take()
+    }
+
+    fn exceed(&mut self, err: ProcessorError) -> Result<(), String> {
+        let msg = err.to_string();
+        self.limit_error = Some(err);
+        Err(msg)
+    }
+
+    /// Counts `n` evaluation steps against the step limit.
+    fn step(&mut self, n: u64) -> Result<(), String> {
+        self.steps = self.steps.saturating_add(n);
+        if self.steps > self.limits.max_steps {
+            return self.exceed(ProcessorError::ProphetStepLimitExceeded(
+                self.host,
+                self.limits.max_steps,
+            ));
+        }
+        Ok(())
+    }
+
+    /// The level of the record declaring `name`, the current one or the global
+    /// one.
+    fn record_level(&self, name: &str, array: bool) -> Result<usize, String> {
+        [self.stack_depth, GLOBAL_LEVEL]
+            .into_iter()
+            .find(|level| {
+                let record = &self.call_stack.records[*level];
+                if array {
+                    record.array_idents.contains_key(name)
+                } else {
+                    record.idents.contains_key(name)
+                }
+            })
+            .ok_or_else(|| format!("assign ident :{} not exist", name))
+    }
+
+    /// Converts `value` to the type of the variable it is assigned to,
+    /// unassigned prophet outputs are felts.
+    fn typed_value(old: Option<&Number>, value: Number) -> Result<Number, String> {
+        match old {
+            Some(old) => value.cast(&old.number_type()?),
+            None => value.cast(&Token::Felt),
+        }
+    }
+
     pub fn assign_value(&mut self, id: &Token, value: NumberRet) -> NumberResult {
         match id {
             Id(name) | Cid(name) => {
                 debug!("assign ident  name:{}, value:{:?}", name, value);
-                let value = value.get_single();
-
-                if self.call_stack.records[self.stack_depth]
-                    .idents
-                    .get(&name.to_string())
-                    .is_some()
-                {
-                    self.call_stack.records[self.stack_depth]
-                        .idents
-                        .insert(name.to_string(), Some(value));
-                } else if self.call_stack.records[GLOBAL_LEVEL]
-                    .idents
-                    .get(&name.to_string())
-                    .is_some()
-                {
-                    self.call_stack.records[GLOBAL_LEVEL]
-                        .idents
-                        .insert(name.to_string(), Some(value));
-                } else {
-                    panic!("assign ident :{} not exist", name);
-                }
+                let level = self.record_level(name, false)?;
+                let idents = &mut self.call_stack.records[level].idents;
+                let value = Self::typed_value(idents[name].as_ref(), value.get_single()?)?;
+                idents.insert(name.to_string(), Some(value));
             }
             ArrayId(name) => {
-                let value = value.get_multiple();
-                debug!("assign array ident  name:{}, value:{:?}", name, value);
-                if self.call_stack.records[self.stack_depth]
-                    .array_idents
-                    .get(&name.to_string())
-                    .is_some()
-                {
-                    assert_eq!(
-                        self.call_stack.records[self.stack_depth].array_idents[&name.to_string()]
-                            .as_ref()
-                            .unwrap()
-                            .len(),
-                        value.len()
-                    );
-                    self.call_stack.records[self.stack_depth]
-                        .array_idents
-                        .insert(name.to_string(), Some(value));
-                } else if self.call_stack.records[GLOBAL_LEVEL]
-                    .array_idents
-                    .get(&name.to_string())
-                    .is_some()
-                {
-                    self.call_stack.records[GLOBAL_LEVEL]
-                        .array_idents
-                        .insert(name.to_string(), Some(value));
-                } else {
-                    panic!("assign ident :{} not exist", name);
-                }
+                let values = value.get_multiple()?;
+                debug!("assign array ident  name:{}, value:{:?}", name, values);
+                let level = self.record_level(name, true)?;
+                let array_idents = &mut self.call_stack.records[level].array_idents;
+                let values = match &array_idents[name] {
+                    Some(old) if old.len() != values.len() => {
+                        return Err(format!(
+                            "assign {} values to array {} of length {}",
+                            values.len(),
+                            name,
+                            old.len()
+                        ))
+                    }
+                    Some(old) => old
+                        .iter()
+                        .zip(values)
+                        .map(|(old, value)| Self::typed_value(Some(old), value))
+                        .collect::<Result<Vec<_>, _>>()?,
+                    None => values
+                        .into_iter()
+                        .map(|value| Self::typed_value(None, value))
+                        .collect::<Result<Vec<_>, _>>()?,
+                };
+                array_idents.insert(name.to_string(), Some(values));
             }
             IndexId(name, index_node) => {
-                if self.call_stack.records[self.stack_depth]
+                let level = self.record_level(name, true)?;
+                let index = self.travel(index_node)?.get_single()?.get_number()?;
+                debug!(
+                    "assign index ident  name:{} index:{} , value:{:?}",
+                    name, index, value
+                );
+                let value = value.get_single()?;
+                let element = self.call_stack.records[level]
                     .array_idents
-                    .get(&name.to_string())
-                    .is_some()
-                {
-                    let index = self.travel(index_node)?.get_single().get_number();
-                    debug!(
-                        "assign index ident  name:{} index:{} , value:{:?}",
-                        name, index, value
-                    );
-                    assert!(
-                        self.call_stack.records[self.stack_depth].array_idents[&name.to_string()]
-                            .as_ref()
-                            .unwrap()
-                            .len()
-                            > index
-                    );
-                    let mut values = self.call_stack.records[self.stack_depth].array_idents
-                        [&name.to_string()]
-                        .clone()
-                        .unwrap();
-                    values[index] = value.get_single();
-
-                    self.call_stack.records[self.stack_depth]
-                        .array_idents
-                        .insert(name.to_string(), Some(values));
-                } else if self.call_stack.records[GLOBAL_LEVEL]
-                    .array_idents
-                    .get(&name.to_string())
-                    .is_some()
-                {
-                    let index = self.travel(index_node)?.get_single().get_number();
-                    assert!(
-                        self.call_stack.records[GLOBAL_LEVEL].array_idents[&name.to_string()]
-                            .as_ref()
-                            .unwrap()
-                            .len()
-                            > index
-                    );
-                    let mut values = self.call_stack.records[GLOBAL_LEVEL].array_idents
-                        [&name.to_string()]
-                        .clone()
-                        .unwrap();
-                    values[index] = value.get_single();
-
-                    self.call_stack.records[GLOBAL_LEVEL]
-                        .array_idents
-                        .insert(name.to_string(), Some(values));
-                } else {
-                    panic!("assign ident :{} not exist", name);
-                }
+                    .get_mut(name)
+                    .and_then(|values| values.as_mut())
+                    .and_then(|values| values.get_mut(index))
+                    .ok_or_else(|| format!("{}[{}] not exist", name, index))?;
+                *element = Self::typed_value(Some(&*element), value)?;
             }
-            _ => panic!("not support assign id type"),
+            _ => return Err(format!("not support assign id type: {}", id)),
         }
         Ok(Single(Nil))
     }
@@ -281,13 +302,70 @@ impl<'a> Executor<'a> {
     ident_lookup!(array_lookup, array_idents, NumberResult, Multiple);
     ident_lookup!(index_lookup, array_idents, NumberResult, index, Single);
 
-    pub fn is_return(&mut self, ret: &NumberRet) -> bool {
+    /// Whether `child` executed a return statement. A call statement also
+    /// yields the values the function returned, but does not return itself.
+    pub fn is_return(&mut self, child: &Arc<RwLock<dyn Node>>, ret: &NumberRet) -> bool {
         if let Multiple(_) = ret {
-            return true;
+            !is_node_type::<CallNode>(child)
         } else {
             false
         }
     }
+
+    /// Casts the values returned by a function to its declared return types.
+    fn return_values(name: &Token, returns: &[BuiltIn], ret: NumberRet) -> NumberResult {
+        let values = match ret {
+            Multiple(values) => values,
+            Single(_) => Vec::new(),
+        };
+        let mut types = Vec::new();
+        for BuiltIn(token) in returns {
+            match token {
+                Array(element_type, len) => types.extend(vec![element_type.as_ref(); *len]),
+                _ => types.push(token),
+            }
+        }
+        if values.len() != types.len() {
+            return Err(format!(
+                "function {} returns {} values, {} declared",
+                name,
+                values.len(),
+                types.len()
+            ));
+        }
+        if returns.is_empty() {
+            return Ok(Single(Nil));
+        }
+        let values = values
+            .iter()
+            .zip(types)
+            .map(|(value, token)| value.cast(token))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Multiple(values))
+    }
+
+    fn run_for_loop(&mut self, node: &mut ForLoopNode) -> NumberResult {
+        for init in node.init.iter() {
+            self.travel(init)?;
+        }
+        loop {
+            self.step(1)?;
+            match self.travel(&node.condition)?.get_single()? {
+                Bool(true) => {
+                    for child in node.consequences.iter() {
+                        let ret = self.travel(child)?;
+                        if self.is_return(child, &ret) {
+                            return Ok(ret);
+                        }
+                    }
+                }
+                Bool(false) => break,
+                cond => return Err(format!("condition {} is not a bool", cond.to_string())),
+            }
+            self.travel(&node.step)?;
+        }
+        Ok(Single(Nil))
+    }
 }
 
 impl<'a> Traversal for Executor<'a> {
@@ -329,6 +407,13 @@ impl<'a> Traversal for Executor<'a> {
     }
 
     fn travel_call(&mut self, node: &mut CallNode) -> NumberResult {
+        self.step(1)?;
+        if self.call_depth >= self.limits.max_call_depth {
+            self.exceed(ProcessorError::ProphetCallDepthExceeded(
+                self.host,
+                self.limits.max_call_depth,
+            ))?;
+        }
         let record_level = self.call_stack.records.len();
         let mut ctx = RuntimeRecord::new(
             node.func_name.to_string(),
@@ -336,24 +421,44 @@ impl<'a> Traversal for Executor<'a> {
             record_level + 1,
         );
 
-        let mut ret = Ok(Single(Nil));
-        if let FuncSymbol(_func_name, ref params, block) =
-            node.func_symbol.clone().unwrap().read().unwrap().deref()
-        {
+        let func_symbol = node
+            .func_symbol
+            .clone()
+            .ok_or_else(|| format!("function {} not resolved", node.func_name))?;
+        let func_symbol = func_symbol
+            .read()
+            .map_err(|err| format!("failed to lock read lock {}", err))?;
+        if let FuncSymbol(_func_name, ref params, returns, block) = func_symbol.deref() {
             for (param, input) in params.iter().zip(node.actual_params.iter()) {
-                if let Single(number) = self.travel(input).unwrap() {
-                    ctx.idents.insert(param.0.to_string(), Some(number));
-                } else if let Multiple(numbers) = self.travel(input).unwrap() {
-                    ctx.array_idents.insert(param.0.to_string(), Some(numbers));
+                let BuiltIn(param_type) = &param.1;
+                match (self.travel(input)?, param_type) {
+                    (Single(number), _) => {
+                        ctx.idents
+                            .insert(param.0.to_string(), Some(number.cast(param_type)?));
+                    }
+                    (Multiple(numbers), Array(element_type, _)) => {
+                        let numbers = numbers
+                            .iter()
+                            .map(|number| number.cast(element_type))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        ctx.array_idents.insert(param.0.to_string(), Some(numbers));
+                    }
+                    (Multiple(_), _) => {
+                        return Err(format!("param {} is not an array", param.0));
+                    }
                 }
             }
             self.call_stack.records.push(ctx);
             self.stack_depth += 1;
-            ret = self.travel(&block);
+            self.call_depth += 1;
+            let ret = self.travel(block);
+            self.call_stack.records.pop();
+            self.stack_depth -= 1;
+            self.call_depth -= 1;
+            Self::return_values(&node.func_name, returns, ret?)
+        } else {
+            Err(format!("{} is not a function", node.func_name))
         }
-        self.call_stack.records.pop();
-        self.stack_depth -= 1;
-        ret
     }
 
     fn travel_block(&mut self, node: &mut BlockNode) -> NumberResult {
@@ -387,7 +492,8 @@ impl<'a> Traversal for Executor<'a> {
             type_node: TypeNode { token },
         } = node;
 
-        if let Array(_element_type, len) = token {
+        if let Array(element_type, len) = token {
+            self.step(*len as u64)?;
             if let Id(name) = identifier {
                 if self.call_stack.records[self.stack_depth]
                     .array_idents
@@ -401,9 +507,12 @@ impl<'a> Traversal for Executor<'a> {
                 }
                 self.call_stack.records[self.stack_depth]
                     .array_idents
-                    .insert(name.to_string(), Some(vec![Nil; *len]));
+                    .insert(
+                        name.to_string(),
+                        Some(vec![Number::try_from(element_type.as_ref())?; *len]),
+                    );
             } else {
-                panic!("cannot get id name");
+                return Err(format!("cannot get id name: {}", identifier));
             }
         } else if let Id(name) = identifier {
             if self.call_stack.records[self.stack_depth].idents.get(name) != None {
@@ -414,7 +523,7 @@ impl<'a> Traversal for Executor<'a> {
             }
             self.call_stack.records[self.stack_depth]
                 .idents
-                .insert(name.to_string(), None);
+                .insert(name.to_string(), Some(Number::try_from(&*token)?));
         }
         Ok(Single(Nil))
     }
@@ -453,7 +562,7 @@ impl<'a> Traversal for Executor<'a> {
         {
             let value = self.travel(index)?;
             debug!("ident:{},{:?}", name, value);
-            self.index_lookup(name, value.get_single().get_number())
+            self.index_lookup(name, value.get_single()?.get_number()?)
         } else {
             Err(format!("Invalid identifier found {}", node.identifier))
         }
@@ -466,24 +575,30 @@ impl<'a> Traversal for Executor<'a> {
             operator,
         } = node;
 
-        let lhs = self.travel(left)?.get_single();
-        let rhs = self.travel(right)?.get_single();
+        self.step(1)?;
+        let lhs = self.travel(left)?.get_single()?;
+        let rhs = self.travel(right)?.get_single()?;
 
         let ret = match operator {
-            Token::Plus => lhs + rhs,
-            Token::Multiply => lhs * rhs,
-            Token::Minus => lhs - rhs,
-            Token::IntegerDivision => lhs / rhs,
-            Token::Mod => lhs % rhs,
-            Token::Equal => Bool(lhs == rhs),
-            Token::NotEqual => Bool(lhs != rhs),
-            Token::LessThan => Bool(lhs < rhs),
-            Token::GreaterThan => Bool(lhs > rhs),
-            Token::LessEqual => Bool(lhs <= rhs),
-            Token::GreaterEqual => Bool(lhs >= rhs),
-            Token::And => Bool(lhs.get_number() != 0 && rhs.get_number() != 0),
-            Token::Or => Bool(lhs.get_number() != 0 || rhs.get_number() != 0),
-            _ => panic!("Unknown operator found: {}", operator),
+            Token::Plus => lhs.checked_add(rhs)?,
+            Token::Multiply => lhs.checked_mul(rhs)?,
+            Token::Minus => lhs.checked_sub(rhs)?,
+            Token::IntegerDivision => lhs.checked_div(rhs)?,
+            Token::Mod => lhs.checked_rem(rhs)?,
+            Token::Equal => Bool(lhs.compare(&rhs)? == Ordering::Equal),
+            Token::NotEqual => Bool(lhs.compare(&rhs)? != Ordering::Equal),
+            Token::LessThan => Bool(lhs.compare(&rhs)? == Ordering::Less),
+            Token::GreaterThan => Bool(lhs.compare(&rhs)? == Ordering::Greater),
+            Token::LessEqual => Bool(lhs.compare(&rhs)? != Ordering::Greater),
+            Token::GreaterEqual => Bool(lhs.compare(&rhs)? != Ordering::Less),
+            Token::And => Bool(lhs.is_true()? && rhs.is_true()?),
+            Token::Or => Bool(lhs.is_true()? || rhs.is_true()?),
+            Token::BitAnd => lhs.bit_and(rhs)?,
+            Token::BitOr => lhs.bit_or(rhs)?,
+            Token::BitXor => lhs.bit_xor(rhs)?,
+            Token::ShiftLeft => lhs.checked_shl(rhs)?,
+            Token::ShiftRight => lhs.checked_shr(rhs)?,
+            _ => return Err(format!("Unknown operator found: {}", operator)),
         };
         return Ok(Single(ret));
     }
@@ -493,8 +608,12 @@ impl<'a> Traversal for Executor<'a> {
         match operator {
             Token::Plus => self.travel(expr),
             Token::Minus => {
-                let ret = self.travel(expr)?.get_single();
-                Ok(Single(-ret))
+                let ret = self.travel(expr)?.get_single()?;
+                Ok(Single(ret.checked_neg()?))
+            }
+            Token::Not => {
+                let ret = self.travel(expr)?.get_single()?;
+                Ok(Single(ret.bit_not()?))
             }
             _ => Err(format!("Unexpected Unary Operator found: {}", operator)),
         }
@@ -503,7 +622,7 @@ impl<'a> Traversal for Executor<'a> {
     fn travel_compound(&mut self, node: &mut CompoundNode) -> NumberResult {
         for child in node.children.iter() {
             let ret = self.travel(child)?;
-            if self.is_return(&ret) {
+            if self.is_return(child, &ret) {
                 return Ok(ret);
             }
         }
@@ -511,6 +630,7 @@ impl<'a> Traversal for Executor<'a> {
     }
 
     fn travel_assign(&mut self, node: &mut AssignNode) -> NumberResult {
+        self.step(1)?;
         let value = self.travel(&node.expr)?;
         self.assign_value(&node.identifier, value)?;
 
@@ -548,62 +668,78 @@ impl<'a> Traversal for Executor<'a> {
     }
 
     fn travel_cond(&mut self, node: &mut CondStatNode) -> NumberResult {
-        let res = self.travel(&node.condition)?;
-        if let Single(Bool(flag)) = res {
+        self.step(1)?;
+        let res = self.travel(&node.condition)?.get_single()?;
+        if let Bool(flag) = res {
             if flag == true {
                 for child in node.consequences.iter() {
                     let ret = self.travel(child)?;
-                    if self.is_return(&ret) {
+                    if self.is_return(child, &ret) {
                         return Ok(ret);
                     }
                 }
             } else {
                 for child in node.alternatives.iter() {
                     let ret = self.travel(child)?;
-                    if self.is_return(&ret) {
+                    if self.is_return(child, &ret) {
                         return Ok(ret);
                     }
                 }
             }
+            Ok(Single(Nil))
+        } else {
+            Err(format!("condition {} is not a bool", res.to_string()))
         }
-        Ok(Single(Nil))
     }
 
     fn travel_loop(&mut self, node: &mut LoopStatNode) -> NumberResult {
-        let mut res = self.travel(&node.condition);
-        while let Ok(Single(cond)) = res {
-            if let Bool(flag) = cond {
-                if flag == true {
+        loop {
+            self.step(1)?;
+            match self.travel(&node.condition)?.get_single()? {
+                Bool(true) => {
                     for child in node.consequences.iter() {
                         let ret = self.travel(child)?;
-                        if self.is_return(&ret) {
+                        if self.is_return(child, &ret) {
                             return Ok(ret);
                         }
                     }
-                } else {
-                    break;
                 }
+                Bool(false) => break,
+                cond => return Err(format!("condition {} is not a bool", cond.to_string())),
             }
-            res = self.travel(&node.condition);
         }
         Ok(Single(Nil))
     }
 
+    fn travel_for_loop(&mut self, node: &mut ForLoopNode) -> NumberResult {
+        let ret = self.run_for_loop(node);
+        // Drop the loop variable so that the loop can run again.
+        let record = &mut self.call_stack.records[self.stack_depth];
+        for init in node.init.iter() {
+            let init = init
+                .read()
+                .map_err(|err| format!("failed to lock read lock {}", err))?;
+            if let Some(declaration) = init.as_any().downcast_ref::<IdentDeclarationNode>() {
+                let name = declaration.ident_node.identifier.to_string();
+                record.idents.remove(&name);
+                record.array_idents.remove(&name);
+            }
+        }
+        ret
+    }
+
     fn travel_function(&mut self, _node: &mut FunctionNode) -> NumberResult {
         Ok(Single(Nil))
     }
 
     fn travel_sqrt(&mut self, node: &mut SqrtNode) -> NumberResult {
-        let value_res = self.travel(&node.sqrt_value);
-        if let Ok(Single(value)) = value_res {
-            let res = match value {
-                Number::Felt(number) => Ok(Single(Number::Felt((number as f64).sqrt() as i128))),
-                _ => panic!("wrong sqrt value type"),
-            };
-            res
-        } else {
-            panic!("can not get sqrt value")
-        }
+        let value = self.travel(&node.sqrt_value)?.get_single()?;
+        Ok(Single(value.sqrt()?))
+    }
+
+    fn travel_inv(&mut self, node: &mut InvNode) -> NumberResult {
+        let value = self.travel(&node.inv_value)?.get_single()?;
+        Ok(Single(value.inverse()?))
     }
 
     fn travel_return(&mut self, node: &mut ReturnNode) -> NumberResult {
@@ -626,55 +762,73 @@ impl<'a> Traversal for Executor<'a> {
 
     fn travel_multi_assign(&mut self, node: &mut MultiAssignNode) -> NumberResult {
         let res = self.travel(&node.call)?;
-        let res = res.get_multiple();
+        let res = res.get_multiple()?;
 
         for (index, ident_node) in node.identifier.iter().enumerate() {
             let ident;
             if is_node_type::<IdentNode>(ident_node) {
-                ident = safe_downcast_ref::<IdentNode>(ident_node)
-                    .identifier
-                    .clone();
+                ident = safe_downcast_ref(ident_node, |node: &IdentNode| node.identifier.clone());
             } else if is_node_type::<ContextIdentNode>(ident_node) {
-                ident = safe_downcast_ref::<ContextIdentNode>(ident_node)
-                    .identifier
-                    .clone();
+                ident = safe_downcast_ref(ident_node, |node: &ContextIdentNode| {
+                    node.identifier.clone()
+                });
             } else if is_node_type::<IdentDeclarationNode>(ident_node) {
                 self.travel(&ident_node)?;
-                ident = safe_downcast_ref::<IdentDeclarationNode>(ident_node)
-                    .ident_node
-                    .identifier
-                    .clone();
+                ident = safe_downcast_ref(ident_node, |node: &IdentDeclarationNode| {
+                    node.ident_node.identifier.clone()
+                });
             } else {
-                panic!("not support ident node type");
+                return Err("not support ident node type".to_string());
             }
-            self.assign_value(&ident, Single(res.get(index).unwrap().clone()))?;
+            let value = res
+                .get(index)
+                .ok_or_else(|| format!("call returns no value for {}", ident))?;
+            self.assign_value(&ident, Single(value.clone()))?;
         }
         Ok(Single(Nil))
     }
 
     fn travel_malloc(&mut self, node: &mut MallocNode) -> NumberResult {
-        let value_res = self.travel(&node.num_bytes);
-        let hp_name = self.context.get(HP_ADDR_INDEX).unwrap().clone();
-        let hp = self.lookup(&hp_name);
-        if let Ok(Single(value)) = value_res {
-            let res = match value {
-                Number::Felt(number) => Single(hp.unwrap().get_single() + Number::Felt(number)),
-                Number::I32(number) => Single(hp.unwrap().get_single() + Number::I32(number)),
-                _ => panic!("wrong sqrt value type"),
-            };
-            self.assign_value(&Id(hp_name), res.clone())?;
-            Ok(res)
-        } else {
-            panic!("can not get sqrt value")
+        let value = self.travel(&node.num_bytes)?.get_single()?;
+        self.allocated = self.allocated.saturating_add(value.get_number()? as u64);
+        if self.allocated > self.limits.max_malloc {
+            self.exceed(ProcessorError::ProphetMemoryLimitExceeded(
+                self.host,
+                self.limits.max_malloc,
+            ))?;
         }
+        let hp_name = self
+            .context
+            .get(HP_ADDR_INDEX)
+            .ok_or("malloc without heap pointer")?
+            .clone();
+        let hp = self.lookup(&hp_name)?.get_single()?;
+        let res = Single(hp.checked_add(value)?);
+        self.assign_value(&Id(hp_name), res.clone())?;
+        Ok(res)
+    }
+
+    fn travel_read_mem(&mut self, node: &mut ReadMemNode) -> NumberResult {
+        let ptr = self.travel(&node.ptr)?.get_single()?.get_number()? as u64;
+        let len = self.travel(&node.len)?.get_single()?.get_number()? as u64;
+        self.step(len)?;
+        let values = self
+            .mem
+            .batch_read(ptr, len)
+            .map_err(|err| err.to_string())?;
+        Ok(Multiple(values.into_iter().map(Number::from).collect()))
     }
 
     fn travel_printf(&mut self, node: &mut PrintfNode) -> NumberResult {
-        let flag_ret = self.travel(&node.flag)?.get_single().get_number();
+        let flag_ret = self.travel(&node.flag)?.get_single()?.get_number()?;
         if flag_ret == 4 {
-            let addr = self.travel(&node.val_addr)?.get_single().get_number() as u64;
-            let limbs = self.mem.batch_read(addr, 8).unwrap();
-            let u256_str = u32s_be_to_u256(limbs.clone().try_into().unwrap()).unwrap();
+            let addr = self.travel(&node.val_addr)?.get_single()?.get_number()? as u64;
+            let limbs = self
+                .mem
+                .batch_read(addr, 8)
+                .map_err(|err| err.to_string())?;
+            let u256_str = u32s_be_to_u256(limbs.clone().try_into().unwrap())
+                .map_err(|err| err.to_string())?;
             println!(
                 "U256: 0x{:x}, limbs:=[{},{},{},{},{},{},{},{}]",
                 u256_str,
@@ -690,11 +844,14 @@ impl<'a> Traversal for Executor<'a> {
         } else if flag_ret == 3 {
             println!(
                 "print value={}",
-                self.travel(&node.val_addr)?.get_single().get_number()
+                self.travel(&node.val_addr)?.get_single()?.get_number()?
             );
         } else if flag_ret == 2 {
-            let addr = self.travel(&node.val_addr)?.get_single().get_number() as u64;
-            let limbs = self.mem.batch_read(addr, 4).unwrap();
+            let addr = self.travel(&node.val_addr)?.get_single()?.get_number()? as u64;
+            let limbs = self
+                .mem
+                .batch_read(addr, 4)
+                .map_err(|err| err.to_string())?;
             println!(
                 "print addr:={},{},{},{}",
                 limbs.get(0).unwrap(),
@@ -703,21 +860,28 @@ impl<'a> Traversal for Executor<'a> {
                 limbs.get(3).unwrap(),
             );
         } else if flag_ret == 1 {
-            let addr = self.travel(&node.val_addr)?.get_single().get_number() as u64;
-            let len = self.mem.read(addr).unwrap();
+            let addr = self.travel(&node.val_addr)?.get_single()?.get_number()? as u64;
+            let len = self.mem.read(addr).map_err(|err| err.to_string())?;
             let str: Vec<u8> = self
                 .mem
                 .batch_read(addr + 1, len)
-                .unwrap()
+                .map_err(|err| err.to_string())?
                 .iter()
                 .map(|v| *v as u8)
                 .collect();
-            println!("print str={}", String::from_utf8(str).unwrap());
+            println!(
+                "print str={}",
+                String::from_utf8(str).map_err(|err| err.to_string())?
+            );
         } else if flag_ret == 0 {
-            let addr = self.travel(&node.val_addr)?.get_single().get_number() as u64;
-            let len = self.mem.read(addr).unwrap();
+            let addr = self.travel(&node.val_addr)?.get_single()?.get_number()? as u64;
+            let len = self.mem.read(addr).map_err(|err| err.to_string())?;
             (addr + 1..addr + 1 + len)
-                .zip(self.mem.batch_read(addr + 1, len).unwrap())
+                .zip(
+                    self.mem
+                        .batch_read(addr + 1, len)
+                        .map_err(|err| err.to_string())?,
+                )
                 .for_each(|(addr, val)| {
                     println!("print mem:{}, value:{}", addr, val);
                 });
@@ -4,8 +4,11 @@ use crate::interpreter::executor::Executor;
 use crate::parser::node::Node;
 use crate::parser::Parser;
 use crate::sema::SymTableGen;
-use crate::utils::number::NumberResult;
-use core::{program::binary_program::OlaProphet, vm::hardware::OlaMemory};
+use crate::utils::number::{NumberResult, NumberRet};
+use core::{
+    program::binary_program::OlaProphet,
+    vm::{error::ProcessorError, hardware::OlaMemory},
+};
 use log::debug;
 use std::sync::{Arc, RwLock};
 
@@ -13,24 +16,125 @@ pub struct Interpreter {
     pub root_node: Arc<RwLock<dyn Node>>,
 }
 
+/// Resource limits of one prophet run. Prophets come with the contract, so a
+/// malicious or buggy one must not be able to stall the vm.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ProphetLimits {
+    /// Evaluation steps: operators, assignments, calls, loop iterations and
+    /// the elements of declared arrays and memory reads.
+    pub max_steps: u64,
+    /// Nested function calls.
+    pub max_call_depth: usize,
+    /// Heap words handed out by `malloc`.
+    pub max_malloc: u64,
+    /// Values written back to the prophet memory.
+    pub max_outputs: usize,
+}
+
+impl Default for ProphetLimits {
+    fn default() -> Self {
+        Self {
+            max_steps: 1_000_000,
+            max_call_depth: 64,
+            max_malloc: 1 << 20,
+            max_outputs: 1024,
+        }
+    }
+}
+
+impl ProphetLimits {
+    pub fn check_outputs(&self, prophet: &OlaProphet) -> Result<(), ProcessorError> {
+        let outputs = prophet.outputs.iter().map(|output| output.length).sum();
+        if outputs > self.max_outputs {
+            return Err(ProcessorError::ProphetOutputLimitExceeded(
+                prophet.host,
+                outputs,
+                self.max_outputs,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The values of a prophet run, or the error it failed with, and the steps it
+/// took either way.
+pub struct ProphetRun {
+    pub values: Result<NumberRet, ProcessorError>,
+    pub steps: u64,
+}
+
+/// Strips the `%{ ... %}` wrapper the assembler puts around prophet code.
+pub fn prophet_body(code: &str) -> Result<&str, String> {
+    code.strip_prefix("%{")
+        .and_then(|body| body.strip_suffix("%}"))
+        .ok_or_else(|| "prophet code is not wrapped in %{ ... %}".to_string())
+}
+
 impl Interpreter {
-    pub fn new(text: &str) -> Self {
-        let mut parser = Parser::new(&text);
-        let root_node = parser.parse();
-        Interpreter { root_node }
+    pub fn new(text: &str) -> Result<Self, String> {
+        let mut parser = Parser::new(text)?;
+        let root_node = parser.parse()?;
+        Ok(Interpreter { root_node })
     }
 
-    pub fn run(&mut self, prophet: &OlaProphet, values: Vec<u64>, mem: &OlaMemory) -> NumberResult {
+    /// Parses and checks a prophet once, so that it can be executed any number
+    /// of times. `prophet` must declare every context variable the caller will
+    /// provide at execution time, the values are not used here.
+    ///
+    /// Sema rewrites the tree in place, so a compiled interpreter must be run
+    /// with `execute`, not `run`.
+    pub fn compile(prophet: &OlaProphet) -> Result<Self, String> {
+        let interpreter = Self::new(prophet_body(&prophet.code)?)?;
+        interpreter.check(prophet)?;
+        Ok(interpreter)
+    }
+
+    fn check(&self, prophet: &OlaProphet) -> NumberResult {
         debug!("sema");
         self.root_node
             .write()
             .map_err(|err| format!("failed to lock write lock {}", err))?
-            .traverse(&mut SymTableGen::new(&prophet))?;
+            .traverse(&mut SymTableGen::new(&prophet)?)
+    }
+
+    pub fn run(&mut self, prophet: &OlaProphet, values: Vec<u64>, mem: &OlaMemory) -> NumberResult {
+        self.check(prophet)?;
+        self.execute(prophet, values, mem)
+    }
+
+    pub fn execute(&self, prophet: &OlaProphet, values: Vec<u64>, mem: &OlaMemory) -> NumberResult {
+        self.execute_with_limits(prophet, values, mem, &ProphetLimits::default())
+            .and_then(|run| run.values)
+            .map_err(|err| err.to_string())
+    }
+
+    /// Executes a compiled prophet, failing with a dedicated error as soon as
+    /// it exceeds one of `limits`. A run that fails after taking steps is
+    /// returned with its error in `values`, so that the steps can be charged.
+    pub fn execute_with_limits(
+        &self,
+        prophet: &OlaProphet,
+        values: Vec<u64>,
+        mem: &OlaMemory,
+        limits: &ProphetLimits,
+    ) -> Result<ProphetRun, ProcessorError> {
         debug!("executor");
-        let mut exe = Executor::new(&prophet, values, mem);
-        self.root_node
+        limits.check_outputs(prophet)?;
+        let mut exe = Executor::new(&prophet, values, mem, *limits);
+        let values = self
+            .root_node
             .write()
-            .map_err(|err| format!("failed to lock write lock {}", err))?
-            .traverse(&mut exe)
+            .map_err(|err| {
+                ProcessorError::InterpreterRunError(format!("failed to lock write lock {}", err))
+            })?
+            .traverse(&mut exe);
+        let values = values.map_err(|err| {
+            exe.take_limit_error()
+                .unwrap_or(ProcessorError::InterpreterRunError(err))
+        });
+        Ok(ProphetRun {
+            values,
+            steps: exe.steps(),
+        })
     }
 }
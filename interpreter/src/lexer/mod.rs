@@ -2,11 +2,13 @@ pub mod token;
 use self::token::Token;
 
 use crate::lexer::token::Token::{
-    And, Assign, Begin, Colon, Comma, Dot, Else, End, Entry, Equal, Felt, FeltConst, Function,
-    GreaterEqual, GreaterThan, I32Const, Id, If, IntegerDivision, LBracket, LParen, LessEqual,
-    LessThan, Malloc, Minus, Mod, Multiply, NotEqual, Or, Plus, Printf, RBracket, RParen, Return,
-    ReturnDel, Semi, Sqrt, While, EOF, I32,
+    And, Assign, Begin, BitAnd, BitOr, BitXor, Colon, Comma, Dot, Else, End, Entry, Equal, Felt,
+    FeltConst, For, Function, GreaterEqual, GreaterThan, I32Const, Id, If, IntegerDivision, Inv,
+    LBracket, LParen, LessEqual, LessThan, Malloc, Minus, Mod, Multiply, Not, NotEqual, Or, Plus,
+    Printf, RBracket, RParen, ReadMem, Return, ReturnDel, Semi, ShiftLeft, ShiftRight, Sqrt,
+    Struct, While, EOF, I32, U32, U64,
 };
+use core::types::{Field64, GoldilocksField};
 
 #[derive(Clone)]
 pub struct Lexer {
@@ -17,53 +19,56 @@ pub struct Lexer {
 
 impl Lexer {
     pub fn new(text: &str) -> Self {
-        let chars: Vec<char> = text.chars().collect();
         Lexer {
             text: text.to_string(),
             position: 0,
-            current_char: Some(chars[0]),
+            current_char: text.as_bytes().first().map(|byte| *byte as char),
         }
     }
 
     pub fn match_reserved(&self, token: &str) -> (bool, Token) {
         match token {
             "I32" => (true, I32),
+            "U32" => (true, U32),
+            "U64" => (true, U64),
             "FELT" => (true, Felt),
             "WHILE" => (true, While),
+            "FOR" => (true, For),
+            "STRUCT" => (true, Struct),
             "IF" => (true, If),
             "ELSE" => (true, Else),
             "ENTRY" => (true, Entry),
             "FUNCTION" => (true, Function),
             "RETURN" => (true, Return),
             "SQRT" => (true, Sqrt),
+            "INV" => (true, Inv),
             "MALLOC" => (true, Malloc),
             "PRINTF" => (true, Printf),
+            "READ_MEM" => (true, ReadMem),
             _ => (false, EOF),
         }
     }
     /// Returns an option to the character following
     /// the current token.
     pub fn peek(&self) -> Option<char> {
-        let position = self.position + 1;
-        if position > self.text.len() - 1 {
-            None
-        } else {
-            Some(self.text.as_bytes()[position] as char)
-        }
+        self.text
+            .as_bytes()
+            .get(self.position + 1)
+            .map(|byte| *byte as char)
     }
     /// Advances the lexer position within the input text,
     /// setting the `current_char` to value found at that
     /// location.
     fn advance(&mut self) {
         self.position += 1;
-        if self.position > self.text.len() - 1 {
-            self.current_char = None
-        } else {
-            self.current_char = Some(self.text.as_bytes()[self.position] as char)
-        }
+        self.current_char = self
+            .text
+            .as_bytes()
+            .get(self.position)
+            .map(|byte| *byte as char);
     }
     fn skip_comment(&mut self) {
-        while self.current_char != Some('\n') {
+        while self.current_char.is_some() && self.current_char != Some('\n') {
             self.advance()
         }
         self.advance()
@@ -74,79 +79,89 @@ impl Lexer {
         }
     }
     /// Handles identifiers and reserved keywords
-    fn id(&mut self) -> Option<Token> {
+    fn id(&mut self) -> Result<Token, String> {
         let mut result = String::new();
-        while self.current_char != None && self.current_char.unwrap().is_alphanumeric()
-            || self.current_char.unwrap() == '.'
-            || self.current_char.unwrap() == '_'
-        {
-            result.push(self.current_char.unwrap());
+        while let Some(char) = self.current_char {
+            if !(char.is_alphanumeric() || char == '.' || char == '_') {
+                break;
+            }
+            result.push(char);
             self.advance();
         }
         let uppercase_result = result.to_uppercase();
 
         let (reserved, token) = self.match_reserved(uppercase_result.as_str());
         if reserved {
-            Some(token)
+            Ok(token)
         } else {
-            Some(Id(result))
+            Ok(Id(result))
         }
     }
 
-    fn number(&mut self) -> Option<Token> {
+    fn number(&mut self) -> Result<Token, String> {
         let mut digits = String::new();
         while self.current_char != None && self.current_char.unwrap().is_digit(10) {
             digits.push(self.current_char.unwrap());
             self.advance();
         }
         if digits.parse::<i32>().is_ok() {
-            Some(I32Const(digits))
-        } else if digits.parse::<u64>().is_ok() {
-            Some(FeltConst(digits))
+            Ok(I32Const(digits))
+        } else if matches!(digits.parse::<u64>(), Ok(value) if value < GoldilocksField::ORDER) {
+            Ok(FeltConst(digits))
         } else {
-            panic!("invalid const number");
+            Err(format!("invalid const number: {}", digits))
         }
     }
 
-    pub fn get_next_token(&mut self) -> Option<Token> {
-        while self.current_char != None {
-            let token = match self.current_char.unwrap() {
+    pub fn get_next_token(&mut self) -> Result<Token, String> {
+        while let Some(current_char) = self.current_char {
+            let token = match current_char {
                 char if char.is_whitespace() => {
                     self.skip_whitespace();
                     continue;
                 }
-                '-' if self.peek().unwrap() == '>' => {
+                '-' if self.peek() == Some('>') => {
+                    self.advance();
+                    self.advance();
+                    Ok(ReturnDel)
+                }
+                '=' if self.peek() == Some('=') => {
+                    self.advance();
+                    self.advance();
+                    Ok(Equal)
+                }
+                '!' if self.peek() == Some('=') => {
                     self.advance();
                     self.advance();
-                    Some(ReturnDel)
+                    Ok(NotEqual)
                 }
-                '=' if self.peek().unwrap() == '=' => {
+                '<' if self.peek() == Some('<') => {
                     self.advance();
                     self.advance();
-                    Some(Equal)
+                    Ok(ShiftLeft)
                 }
-                '!' if self.peek().unwrap() == '=' => {
+                '>' if self.peek() == Some('>') => {
                     self.advance();
                     self.advance();
-                    Some(NotEqual)
+                    Ok(ShiftRight)
                 }
-                '<' if self.peek().unwrap() == '=' => {
+                '<' if self.peek() == Some('=') => {
                     self.advance();
                     self.advance();
-                    Some(LessEqual)
+                    Ok(LessEqual)
                 }
-                '>' if self.peek().unwrap() == '=' => {
+                '>' if self.peek() == Some('=') => {
                     self.advance();
                     self.advance();
-                    Some(GreaterEqual)
+                    Ok(GreaterEqual)
                 }
                 '<' => {
                     self.advance();
-                    Some(LessThan)
+                    Ok(LessThan)
                 }
                 '>' => {
                     self.advance();
-                    Some(GreaterThan)
+                    Ok(GreaterThan)
                 }
                 '#' => {
                     self.advance();
@@ -156,87 +171,103 @@ impl Lexer {
                 char if char.is_digit(10) => self.number(),
                 '+' => {
                     self.advance();
-                    Some(Plus)
+                    Ok(Plus)
                 }
                 char if char.is_alphanumeric() => self.id(),
-                '_' if self.peek().unwrap().is_alphanumeric() => {
+                '_' if self.peek().map_or(false, |char| char.is_alphanumeric()) => {
                     self.advance();
                     self.id()
                 }
                 '=' => {
                     self.advance();
-                    Some(Assign)
+                    Ok(Assign)
                 }
                 ':' => {
                     self.advance();
-                    Some(Colon)
+                    Ok(Colon)
                 }
                 ';' => {
                     self.advance();
-                    Some(Semi)
+                    Ok(Semi)
                 }
                 ',' => {
                     self.advance();
-                    Some(Comma)
+                    Ok(Comma)
                 }
                 '.' => {
                     self.advance();
-                    Some(Dot)
+                    Ok(Dot)
                 }
                 '-' => {
                     self.advance();
-                    Some(Minus)
+                    Ok(Minus)
                 }
                 '*' => {
                     self.advance();
-                    Some(Multiply)
+                    Ok(Multiply)
                 }
                 '/' => {
                     self.advance();
-                    Some(IntegerDivision)
+                    Ok(IntegerDivision)
                 }
                 '%' => {
                     self.advance();
-                    Some(Mod)
+                    Ok(Mod)
                 }
                 '(' => {
                     self.advance();
-                    Some(LParen)
+                    Ok(LParen)
                 }
                 ')' => {
                     self.advance();
-                    Some(RParen)
+                    Ok(RParen)
                 }
                 '{' => {
                     self.advance();
-                    Some(Begin)
+                    Ok(Begin)
                 }
                 '}' => {
                     self.advance();
-                    Some(End)
+                    Ok(End)
                 }
                 '[' => {
                     self.advance();
-                    Some(LBracket)
+                    Ok(LBracket)
                 }
                 ']' => {
                     self.advance();
-                    Some(RBracket)
+                    Ok(RBracket)
                 }
-                '&' if self.peek().unwrap() == '&' => {
+                '&' if self.peek() == Some('&') => {
                     self.advance();
                     self.advance();
-                    Some(And)
+                    Ok(And)
                 }
-                '|' if self.peek().unwrap() == '|' => {
+                '|' if self.peek() == Some('|') => {
+                    self.advance();
                     self.advance();
+                    Ok(Or)
+                }
+                '&' => {
+                    self.advance();
+                    Ok(BitAnd)
+                }
+                '|' => {
+                    self.advance();
+                    Ok(BitOr)
+                }
+                '^' => {
+                    self.advance();
+                    Ok(BitXor)
+                }
+                '!' => {
                     self.advance();
-                    Some(Or)
+                    Ok(Not)
                 }
-                unknown => panic!("Unknown token found: {}", unknown),
+                unknown => Err(format!("Unknown token found: {}", unknown)),
             };
             return token;
         }
-        Some(EOF)
+        Ok(EOF)
     }
 }
@@ -6,6 +6,8 @@ use std::sync::{Arc, RwLock};
 pub enum Token {
     Felt,
     I32,
+    U32,
+    U64,
     Array(Box<Token>, usize),
     FeltConst(String),
     I32Const(String),
@@ -31,6 +33,12 @@ pub enum Token {
     Else,
     And,
     Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+    Not,
     GreaterThan,
     LessThan,
     Equal,
@@ -38,10 +46,13 @@ pub enum Token {
     GreaterEqual,
     NotEqual,
     While,
+    For,
     Function,
+    Struct,
     Return,
     Entry,
     Sqrt,
+    Inv,
     ReturnDel,
     AS,
     LBracket,
@@ -49,6 +60,7 @@ pub enum Token {
     EOF,
     Malloc,
     Printf,
+    ReadMem,
 }
 
 impl PartialEq for Token {
@@ -67,6 +79,8 @@ impl<'a> fmt::Display for Token {
         let output = match self {
             Token::Felt => "FELT",
             Token::I32 => "I32",
+            Token::U32 => "U32",
+            Token::U64 => "U64",
             Token::Array(_, _) => &pre_fmt,
             Token::FeltConst(value) => value,
             Token::I32Const(value) => value,
@@ -92,6 +106,12 @@ impl<'a> fmt::Display for Token {
             Token::Else => "else",
             Token::And => "&&",
             Token::Or => "||",
+            Token::BitAnd => "&",
+            Token::BitOr => "|",
+            Token::BitXor => "^",
+            Token::ShiftLeft => "<<",
+            Token::ShiftRight => ">>",
+            Token::Not => "!",
             Token::LessThan => "<",
             Token::GreaterThan => ">",
             Token::Equal => "==",
@@ -99,10 +119,13 @@ impl<'a> fmt::Display for Token {
             Token::GreaterEqual => ">=",
             Token::NotEqual => "!=",
             Token::While => "while",
+            Token::For => "for",
             Token::Function => "function",
+            Token::Struct => "struct",
             Token::Return => "return",
             Token::Entry => "entry",
             Token::Sqrt => "sqrt",
+            Token::Inv => "inv",
             Token::ReturnDel => "->",
             Token::AS => "as",
             Token::LBracket => "[",
@@ -110,6 +133,7 @@ impl<'a> fmt::Display for Token {
             Token::EOF => "EOF",
             Token::Malloc => "malloc",
             Token::Printf => "printf",
+            Token::ReadMem => "read_mem",
         };
         write!(f, "{}", output)
     }
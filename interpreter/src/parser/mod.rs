@@ -1,87 +1,102 @@
 use crate::lexer::token::Token;
 use crate::lexer::token::Token::{
-    And, Array, Assign, Begin, Cid, Comma, Else, End, Entry, Equal, Felt, FeltConst, Function,
-    GreaterEqual, GreaterThan, I32Const, Id, If, IndexId, IntegerDivision, LBracket, LParen,
-    LessEqual, LessThan, Malloc, Minus, Mod, Multiply, NotEqual, Or, Plus, Printf, RBracket,
-    RParen, Return, ReturnDel, Semi, Sqrt, While, EOF, I32,
+    And, Array, Assign, Begin, BitAnd, BitOr, BitXor, Cid, Comma, Else, End, Entry, Equal, Felt,
+    FeltConst, For, Function, GreaterEqual, GreaterThan, I32Const, Id, If, IndexId,
+    IntegerDivision, Inv, LBracket, LParen, LessEqual, LessThan, Malloc, Minus, Mod, Multiply, Not,
+    NotEqual, Or, Plus, Printf, RBracket, RParen, ReadMem, Return, ReturnDel, Semi, ShiftLeft,
+    ShiftRight, Sqrt, Struct, While, EOF, I32, U32, U64,
 };
 use crate::lexer::Lexer;
 use crate::parser::node::{
     ArrayNumNode, AssignNode, BinOpNode, BlockNode, CallNode, CompoundNode, CondStatNode,
-    ContextIdentNode, EntryBlockNode, EntryNode, FeltNumNode, FunctionNode, IdentDeclarationNode,
-    IdentIndexNode, IdentNode, IntegerNumNode, LoopStatNode, MallocNode, MultiAssignNode, Node,
-    PrintfNode, ReturnNode, SqrtNode, TypeNode, UnaryOpNode,
+    ContextIdentNode, EntryBlockNode, EntryNode, FeltNumNode, ForLoopNode, FunctionNode,
+    IdentDeclarationNode, IdentIndexNode, IdentNode, IntegerNumNode, InvNode, LoopStatNode,
+    MallocNode, MultiAssignNode, Node, PrintfNode, ReadMemNode, ReturnNode, SqrtNode, TypeNode,
+    UnaryOpNode,
 };
 use crate::utils::number::Number;
 use log::debug;
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 pub mod node;
 pub mod traversal;
 
+pub type ParseResult<T> = Result<T, String>;
+
 #[macro_export]
 macro_rules! array_type_node {
     ($v: expr, $len: expr) => {
-        $v.consume(&LBracket);
+        $v.consume(&LBracket)?;
         $len = match $v.get_current_token() {
             FeltConst(num) => num,
             I32Const(num) => num,
-            _ => panic!("not support token type for declare"),
+            token => return Err(format!("not support token type for declare: {}", token)),
         };
-        $v.consume(&$v.get_current_token());
-        $v.consume(&RBracket);
+        $v.consume(&$v.get_current_token())?;
+        $v.consume(&RBracket)?;
     };
 }
 
 #[derive(Clone)]
 pub struct Parser {
     lexer: Lexer,
-    current_token: Option<Token>,
+    current_token: Token,
+    /// Field names and types of the structs defined so far, a struct variable
+    /// `p` is declared as one variable `p.field` per field.
+    structs: HashMap<String, Vec<(String, Token)>>,
 }
 
 impl Parser {
-    pub fn new(text: &str) -> Self {
+    pub fn new(text: &str) -> ParseResult<Self> {
         let mut lexer = Lexer::new(&text);
-        let current_token = lexer.get_next_token();
+        let current_token = lexer.get_next_token()?;
 
-        Parser {
+        Ok(Parser {
             lexer,
             current_token,
-        }
+            structs: HashMap::new(),
+        })
     }
     fn get_current_token(&self) -> Token {
-        self.current_token.clone().unwrap()
+        self.current_token.clone()
     }
 
-    fn consume(&mut self, token_type: &Token) {
+    fn consume(&mut self, token_type: &Token) -> ParseResult<()> {
         let current_token = self.get_current_token();
 
         if current_token == *token_type {
-            self.current_token = self.lexer.get_next_token();
+            self.current_token = self.lexer.get_next_token()?;
+            Ok(())
         } else {
-            panic!(
+            Err(format!(
                 "Unexpected token error: expected {}, received {}",
                 token_type, current_token
-            );
+            ))
         }
     }
 
-    fn entry(&mut self) -> Arc<RwLock<dyn Node>> {
-        let declarations = self.global_declarations();
-        self.consume(&Entry);
-        self.consume(&LParen);
-        self.consume(&RParen);
+    fn array_len(len: &str) -> ParseResult<usize> {
+        len.parse()
+            .map_err(|err| format!("invalid array length {}: {}", len, err))
+    }
+
+    fn entry(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let declarations = self.global_declarations()?;
+        self.consume(&Entry)?;
+        self.consume(&LParen)?;
+        self.consume(&RParen)?;
 
-        let entry_block = self.entry_block();
+        let entry_block = self.entry_block()?;
         let node = EntryNode::new(declarations, entry_block);
-        Arc::new(RwLock::new(node))
+        Ok(Arc::new(RwLock::new(node)))
     }
 
     fn ident_declaration_assignment(
         &mut self,
         type_node: &TypeNode,
         function_param_flag: bool,
-    ) -> Vec<Arc<RwLock<dyn Node>>> {
+    ) -> ParseResult<Vec<Arc<RwLock<dyn Node>>>> {
         let mut declarations: Vec<Arc<RwLock<dyn Node>>> = vec![];
         let mut len = Default::default();
         let mut array_flag = false;
@@ -100,264 +115,348 @@ impl Parser {
                     IdentNode::new(Id(id.clone())),
                     TypeNode::new(Array(
                         Box::new(type_node.token.clone()),
-                        len.parse().unwrap(),
+                        Self::array_len(&len)?,
                     )),
                 );
                 declarations.push(Arc::new(RwLock::new(node)));
 
                 let ident = self.get_current_token();
-                self.consume(&ident);
+                self.consume(&ident)?;
             } else {
-                declarations.extend(self.ident_declaration(&type_node));
+                declarations.extend(self.ident_declaration(&type_node)?);
             }
             if !function_param_flag {
                 if self.get_current_token() == Assign {
                     debug!("declarations id assign:{}", id);
-                    let expr = self.assignment_call_statement(Some(Id(id)));
+                    let expr = self.assignment_call_statement(Some(Id(id)))?;
                     declarations.push(expr);
-                    self.consume(&Semi);
+                    self.consume(&Semi)?;
                 } else {
-                    self.consume(&Semi);
+                    self.consume(&Semi)?;
                 }
             } else {
                 if self.get_current_token() == Comma {
-                    self.consume(&Comma);
+                    self.consume(&Comma)?;
                 }
             }
         } else {
-            panic!("declare no ident:{}", current_token);
+            return Err(format!("declare no ident:{}", current_token));
         }
-        declarations
+        Ok(declarations)
     }
 
-    fn global_declarations(&mut self) -> Vec<Arc<RwLock<dyn Node>>> {
+    fn struct_definition(&mut self) -> ParseResult<()> {
+        self.consume(&Struct)?;
+        let name = match self.get_current_token() {
+            Id(name) => name,
+            token => return Err(format!("struct name not found: {}", token)),
+        };
+        if self.structs.contains_key(&name) {
+            return Err(format!("struct {} defined twice", name));
+        }
+        self.consume(&self.get_current_token())?;
+        self.consume(&Begin)?;
+        let mut fields: Vec<(String, Token)> = Vec::new();
+        while self.is_type_spec() {
+            let mut token = self.type_spec()?.token;
+            if self.get_current_token() == LBracket {
+                let len;
+                array_type_node!(self, len);
+                token = Array(Box::new(token), Self::array_len(&len)?);
+            }
+            let field = match self.get_current_token() {
+                Id(field) if !field.contains('.') => field,
+                token => return Err(format!("invalid field of struct {}: {}", name, token)),
+            };
+            if fields.iter().any(|(declared, _)| *declared == field) {
+                return Err(format!("field {} of struct {} declared twice", field, name));
+            }
+            self.consume(&self.get_current_token())?;
+            self.consume(&Semi)?;
+            fields.push((field, token));
+        }
+        self.consume(&End)?;
+        if self.get_current_token() == Semi {
+            self.consume(&Semi)?;
+        }
+        self.structs.insert(name, fields);
+        Ok(())
+    }
+
+    fn is_struct_type(&self) -> bool {
+        matches!(&self.current_token, Id(name) if self.structs.contains_key(name))
+    }
+
+    fn struct_declaration(&mut self) -> ParseResult<Vec<Arc<RwLock<dyn Node>>>> {
+        let name = self.get_current_token().to_string();
+        self.consume(&self.get_current_token())?;
+        let ident = match self.get_current_token() {
+            Id(ident) => ident,
+            token => return Err(format!("declare no ident:{}", token)),
+        };
+        self.consume(&self.get_current_token())?;
+        self.consume(&Semi)?;
+        let mut declarations: Vec<Arc<RwLock<dyn Node>>> = vec![];
+        for (field, token) in self.structs[&name].iter() {
+            let node = IdentDeclarationNode::new(
+                IdentNode::new(Id(format!("{}.{}", ident, field))),
+                TypeNode::new(token.clone()),
+            );
+            declarations.push(Arc::new(RwLock::new(node)));
+        }
+        Ok(declarations)
+    }
+
+    fn global_declarations(&mut self) -> ParseResult<Vec<Arc<RwLock<dyn Node>>>> {
         let mut declarations: Vec<Arc<RwLock<dyn Node>>> = vec![];
         loop {
-            if self.get_current_token() == I32 || self.get_current_token() == Felt {
-                let type_node = self.type_spec();
-                declarations.extend(self.ident_declaration_assignment(&type_node, false));
+            if self.is_type_spec() {
+                let type_node = self.type_spec()?;
+                declarations.extend(self.ident_declaration_assignment(&type_node, false)?);
+            } else if self.get_current_token() == Struct {
+                self.struct_definition()?;
+            } else if self.is_struct_type() {
+                declarations.extend(self.struct_declaration()?);
             } else if self.get_current_token() == Function {
-                self.consume(&self.get_current_token());
+                self.consume(&self.get_current_token())?;
                 let current_token = self.get_current_token();
                 if let Id(id) = current_token {
                     debug!("function name:{}", id);
-                    self.consume(&self.get_current_token());
-                    self.consume(&LParen);
+                    self.consume(&self.get_current_token())?;
+                    self.consume(&LParen)?;
                     let mut params = Vec::new();
-                    while self.get_current_token() == I32 || self.get_current_token() == Felt {
-                        let type_node = self.type_spec();
-                        params.extend(self.ident_declaration_assignment(&type_node, true));
+                    while self.is_type_spec() {
+                        let type_node = self.type_spec()?;
+                        params.extend(self.ident_declaration_assignment(&type_node, true)?);
                     }
-                    self.consume(&RParen);
+                    self.consume(&RParen)?;
                     let mut returns: Vec<Arc<RwLock<(dyn Node)>>> = vec![];
                     if self.get_current_token() == ReturnDel {
-                        self.consume(&ReturnDel);
+                        self.consume(&ReturnDel)?;
 
                         if self.get_current_token() == LParen {
-                            self.consume(&LParen);
-                            while self.get_current_token() == I32
-                                || self.get_current_token() == Felt
-                            {
-                                let type_node = self.type_spec();
+                            self.consume(&LParen)?;
+                            while self.is_type_spec() {
+                                let type_node = self.type_spec()?;
                                 if self.get_current_token() == LBracket {
                                     let len;
                                     array_type_node!(self, len);
                                     let token =
-                                        Array(Box::new(type_node.token), len.parse().unwrap());
+                                        Array(Box::new(type_node.token), Self::array_len(&len)?);
                                     let node = TypeNode::new(token);
                                     returns.push(Arc::new(RwLock::new(node)));
                                 } else {
                                     returns.push(Arc::new(RwLock::new(type_node)));
                                 }
                                 if Comma == self.get_current_token() {
-                                    self.consume(&Comma);
+                                    self.consume(&Comma)?;
                                 }
                             }
-                            self.consume(&RParen);
+                            self.consume(&RParen)?;
                         } else {
-                            let type_node = self.type_spec();
+                            let type_node = self.type_spec()?;
                             if self.get_current_token() == LBracket {
                                 let len;
                                 array_type_node!(self, len);
-                                let token = Array(Box::new(type_node.token), len.parse().unwrap());
+                                let token =
+                                    Array(Box::new(type_node.token), Self::array_len(&len)?);
                                 let node = TypeNode::new(token);
                                 returns.push(Arc::new(RwLock::new(node)));
                             } else {
                                 returns.push(Arc::new(RwLock::new(type_node)));
                             }
                             if Comma == self.get_current_token() {
-                                self.consume(&Comma);
+                                self.consume(&Comma)?;
                             }
                         }
                     }
-                    let block = self.block();
+                    let block = self.block()?;
                     let node = FunctionNode::new(Id(id), params, returns, block);
                     declarations.push(Arc::new(RwLock::new(node)));
                 } else {
-                    panic!("function name not found");
+                    return Err(format!("function name not found: {}", current_token));
                 }
             } else {
                 break;
             }
         }
-        declarations
+        Ok(declarations)
     }
 
-    fn entry_block(&mut self) -> Arc<RwLock<dyn Node>> {
+    fn entry_block(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
         // block : declarations compound_statement
-        self.consume(&Begin);
-        let declarations = self.declarations();
+        self.consume(&Begin)?;
+        let declarations = self.declarations()?;
         debug!("in entry state");
-        let compound_statement = self.compound_statement();
-        self.consume(&End);
+        let compound_statement = self.compound_statement()?;
+        self.consume(&End)?;
         let node = EntryBlockNode::new(declarations, compound_statement);
-        Arc::new(RwLock::new(node))
+        Ok(Arc::new(RwLock::new(node)))
     }
 
-    fn block(&mut self) -> Arc<RwLock<dyn Node>> {
-        self.consume(&Begin);
-        let declarations = self.declarations();
+    fn block(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        self.consume(&Begin)?;
+        let declarations = self.declarations()?;
         debug!("in state");
-        let compound_statement = self.compound_statement();
-        self.consume(&End);
+        let compound_statement = self.compound_statement()?;
+        self.consume(&End)?;
         let node = BlockNode::new(declarations, compound_statement);
-        Arc::new(RwLock::new(node))
+        Ok(Arc::new(RwLock::new(node)))
     }
 
-    fn compound_statement(&mut self) -> Arc<RwLock<dyn Node>> {
+    fn compound_statement(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
         // compound_statement : Begin statement_list End
-        let nodes = self.statement_list();
+        let nodes = self.statement_list()?;
 
-        Arc::new(RwLock::new(CompoundNode::new(nodes)))
+        Ok(Arc::new(RwLock::new(CompoundNode::new(nodes))))
     }
 
-    fn statement_list(&mut self) -> Vec<Arc<RwLock<dyn Node>>> {
+    fn statement_list(&mut self) -> ParseResult<Vec<Arc<RwLock<dyn Node>>>> {
         let mut results = vec![];
 
         loop {
             if let Id(id) = self.get_current_token() {
                 debug!("id:{}", id);
-                self.consume(&self.get_current_token());
+                self.consume(&self.get_current_token())?;
                 let mut token = Id(id.clone());
                 if self.get_current_token() == LBracket {
-                    self.consume(&LBracket);
-                    let index = self.add_expr();
+                    self.consume(&LBracket)?;
+                    let index = self.add_expr()?;
                     token = IndexId(id.to_string(), index);
-                    self.consume(&RBracket);
+                    self.consume(&RBracket)?;
                 }
 
-                results.push(self.assignment_call_statement(Some(token)));
+                results.push(self.assignment_call_statement(Some(token))?);
 
                 if let Id(_) = self.get_current_token() {
-                    panic!(
+                    return Err(format!(
                         "Invalid token in statement list: {}",
                         self.get_current_token()
-                    )
+                    ));
                 }
                 if self.get_current_token() != End {
-                    self.consume(&Semi);
+                    self.consume(&Semi)?;
                 }
             } else if let Cid(_id) = self.get_current_token() {
-                results.push(self.assignment_call_statement(None));
+                results.push(self.assignment_call_statement(None)?);
                 if let Id(_) = self.get_current_token() {
-                    panic!(
+                    return Err(format!(
                         "Invalid token in statement list: {}",
                         self.get_current_token()
-                    )
+                    ));
                 }
                 if self.get_current_token() != End {
-                    self.consume(&Semi);
+                    self.consume(&Semi)?;
                 }
             } else if If == self.get_current_token() {
-                results.push(self.cond_statement());
+                results.push(self.cond_statement()?);
             } else if While == self.get_current_token() {
-                results.push(self.loop_statement());
+                results.push(self.loop_statement()?);
+            } else if For == self.get_current_token() {
+                results.push(self.for_statement()?);
             } else if End == self.get_current_token() {
                 break;
             } else if Return == self.get_current_token() {
-                self.consume(&Return);
+                self.consume(&Return)?;
                 let mut returns = Vec::new();
                 if self.get_current_token() != LParen {
-                    let expr = self.or_expr();
+                    let expr = self.or_expr()?;
                     returns.push(expr);
                 } else {
-                    self.consume(&self.get_current_token());
+                    self.consume(&self.get_current_token())?;
                     while self.get_current_token() != RParen {
-                        let expr = self.or_expr();
+                        let expr = self.or_expr()?;
                         returns.push(expr);
                         if self.get_current_token() == Comma {
-                            self.consume(&self.get_current_token());
+                            self.consume(&self.get_current_token())?;
                         }
                     }
-                    self.consume(&RParen);
+                    self.consume(&RParen)?;
                 }
                 if self.get_current_token() == Semi {
-                    self.consume(&Semi);
+                    self.consume(&Semi)?;
                 }
                 let node = ReturnNode::new(returns);
                 results.push(Arc::new(RwLock::new(node)));
                 if self.get_current_token() != End {
-                    self.consume(&Semi);
+                    self.consume(&Semi)?;
                 }
             } else if LParen == self.get_current_token() {
-                self.consume(&LParen);
+                self.consume(&LParen)?;
                 let mut idents = Vec::new();
                 while self.get_current_token() != RParen {
-                    if self.get_current_token() == I32 || self.get_current_token() == Felt {
-                        let type_node = self.type_spec();
-                        idents.extend(self.ident_declaration_assignment(&type_node, true));
+                    if self.is_type_spec() {
+                        let type_node = self.type_spec()?;
+                        idents.extend(self.ident_declaration_assignment(&type_node, true)?);
                     } else if let Id(_) = self.get_current_token() {
                         idents.push(Arc::new(RwLock::new(IdentNode::new(
                             self.get_current_token(),
                         ))));
-                        self.consume(&self.get_current_token());
+                        self.consume(&self.get_current_token())?;
                         if self.get_current_token() == Comma {
-                            self.consume(&Comma);
+                            self.consume(&Comma)?;
                         }
+                    } else {
+                        return Err(format!(
+                            "Invalid token in multiple assignment: {}",
+                            self.get_current_token()
+                        ));
                     }
                 }
-                self.consume(&RParen);
+                self.consume(&RParen)?;
                 if self.get_current_token() == Assign {
-                    self.consume(&Assign);
-                    let call = self.call_statement(None);
+                    self.consume(&Assign)?;
+                    let call = self.call_statement(None)?;
                     let node = MultiAssignNode::new(idents, Vec::new(), call, Assign);
                     results.push(Arc::new(RwLock::new(node)));
                 }
                 if self.get_current_token() == Semi {
-                    self.consume(&Semi);
+                    self.consume(&Semi)?;
                 }
             } else if Printf == self.get_current_token() {
-                self.consume(&self.get_current_token());
-                self.consume(&LParen);
-                let start = self.or_expr();
-                self.consume(&Comma);
-                let flag = self.or_expr();
-                self.consume(&RParen);
+                self.consume(&self.get_current_token())?;
+                self.consume(&LParen)?;
+                let start = self.or_expr()?;
+                self.consume(&Comma)?;
+                let flag = self.or_expr()?;
+                self.consume(&RParen)?;
                 results.push(Arc::new(RwLock::new(PrintfNode::new(start, flag))));
                 if self.get_current_token() == Semi {
-                    self.consume(&Semi);
+                    self.consume(&Semi)?;
                 }
+            } else {
+                return Err(format!(
+                    "Invalid token in statement list: {}",
+                    self.get_current_token()
+                ));
             }
         }
-        results
+        Ok(results)
     }
 
-    fn type_spec(&mut self) -> TypeNode {
+    fn is_type_spec(&self) -> bool {
+        matches!(self.current_token, I32 | U32 | U64 | Felt)
+    }
+
+    fn type_spec(&mut self) -> ParseResult<TypeNode> {
         let current_token = self.get_current_token();
         match current_token {
-            I32 | Felt => {
-                self.consume(&current_token);
-                TypeNode::new(current_token)
+            I32 | U32 | U64 | Felt => {
+                self.consume(&current_token)?;
+                Ok(TypeNode::new(current_token))
             }
-            token => panic!("Unknown token type found {}", token),
+            token => Err(format!("Unknown token type found {}", token)),
         }
     }
 
-    fn ident_declaration(&mut self, type_node: &TypeNode) -> Vec<Arc<RwLock<dyn Node>>> {
+    fn ident_declaration(
+        &mut self,
+        type_node: &TypeNode,
+    ) -> ParseResult<Vec<Arc<RwLock<dyn Node>>>> {
         // variable_declaration : Id (Comma Id)* Colon type_spec
         let mut ident_nodes: Vec<IdentNode> = Vec::new();
         let identifier = self.get_current_token();
-        self.consume(&identifier);
+        self.consume(&identifier)?;
 
         ident_nodes.push(IdentNode::new(identifier));
 
@@ -366,229 +465,371 @@ impl Parser {
             let declaration = IdentDeclarationNode::new(node, type_node.clone());
             declaration_nodes.push(Arc::new(RwLock::new(declaration)));
         }
-        declaration_nodes
+        Ok(declaration_nodes)
     }
 
-    fn declarations(&mut self) -> Vec<Arc<RwLock<dyn Node>>> {
+    fn declarations(&mut self) -> ParseResult<Vec<Arc<RwLock<dyn Node>>>> {
         let mut declarations: Vec<Arc<RwLock<dyn Node>>> = vec![];
-        while self.get_current_token() == I32 || self.get_current_token() == Felt {
-            let type_node = self.type_spec();
-            declarations.extend(self.ident_declaration_assignment(&type_node, false));
+        loop {
+            if self.is_type_spec() {
+                let type_node = self.type_spec()?;
+                declarations.extend(self.ident_declaration_assignment(&type_node, false)?);
+            } else if self.is_struct_type() {
+                declarations.extend(self.struct_declaration()?);
+            } else {
+                break;
+            }
         }
-        declarations
+        Ok(declarations)
     }
 
-    fn call_statement(&mut self, id: Option<Token>) -> Arc<RwLock<dyn Node>> {
+    fn call_statement(&mut self, id: Option<Token>) -> ParseResult<Arc<RwLock<dyn Node>>> {
         let left;
         if id.is_none() {
             left = self.get_current_token();
-            self.consume(&left);
+            self.consume(&left)?;
         } else {
             left = id.unwrap();
         }
-        self.consume(&LParen);
+        self.consume(&LParen)?;
         let mut params = Vec::new();
         while self.get_current_token() != RParen {
-            let param = self.or_expr();
+            let param = self.or_expr()?;
             params.push(param);
             if self.get_current_token() == Comma {
-                self.consume(&Comma);
+                self.consume(&Comma)?;
             }
         }
-        self.consume(&RParen);
+        self.consume(&RParen)?;
         let node = CallNode::new(left, params);
-        Arc::new(RwLock::new(node))
+        Ok(Arc::new(RwLock::new(node)))
     }
 
-    fn assignment_call_statement(&mut self, id: Option<Token>) -> Arc<RwLock<dyn Node>> {
+    fn assignment_call_statement(
+        &mut self,
+        id: Option<Token>,
+    ) -> ParseResult<Arc<RwLock<dyn Node>>> {
         let left;
         if id.is_none() {
             left = self.get_current_token();
-            self.consume(&left);
+            self.consume(&left)?;
         } else {
             left = id.unwrap();
         }
         let current_token = self.get_current_token();
         if current_token == Assign {
-            self.consume(&Assign);
-            let right = self.or_expr();
+            self.consume(&Assign)?;
+            let right = self.or_expr()?;
             let node = AssignNode::new(left, right, current_token);
-            Arc::new(RwLock::new(node))
+            Ok(Arc::new(RwLock::new(node)))
         } else if current_token == LParen {
             self.call_statement(Some(left))
         } else {
-            panic!("assignment_call_statement mot match:{}", current_token)
+            Err(format!(
+                "assignment_call_statement mot match:{}",
+                current_token
+            ))
         }
     }
 
-    fn cond_statement(&mut self) -> Arc<RwLock<dyn Node>> {
-        self.consume(&If);
-        let condition = self.or_expr();
+    fn cond_statement(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        self.consume(&If)?;
+        let condition = self.or_expr()?;
 
-        self.consume(&Begin);
-        let true_state = self.statement_list();
-        self.consume(&End);
+        self.consume(&Begin)?;
+        let true_state = self.statement_list()?;
+        self.consume(&End)?;
 
         let mut flase_state = Vec::new();
         if Else == self.get_current_token() {
-            self.consume(&Else);
+            self.consume(&Else)?;
             if If == self.get_current_token() {
-                flase_state.push(self.cond_statement());
+                flase_state.push(self.cond_statement()?);
             } else if Begin == self.get_current_token() {
-                self.consume(&Begin);
-                flase_state.extend(self.statement_list());
-                self.consume(&End);
+                self.consume(&Begin)?;
+                flase_state.extend(self.statement_list()?);
+                self.consume(&End)?;
             } else {
-                panic!("not support condition branch");
+                return Err(format!(
+                    "not support condition branch: {}",
+                    self.get_current_token()
+                ));
             }
         }
         let node = CondStatNode::new(condition, true_state, flase_state);
 
-        return Arc::new(RwLock::new(node));
+        Ok(Arc::new(RwLock::new(node)))
     }
 
-    fn loop_statement(&mut self) -> Arc<RwLock<dyn Node>> {
-        self.consume(&While);
-        let condition = self.or_expr();
+    fn loop_statement(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        self.consume(&While)?;
+        let condition = self.or_expr()?;
 
-        self.consume(&Begin);
-        let consequences = self.statement_list();
-        self.consume(&End);
+        self.consume(&Begin)?;
+        let consequences = self.statement_list()?;
+        self.consume(&End)?;
 
         let node = LoopStatNode::new(condition, consequences);
 
-        Arc::new(RwLock::new(node))
+        Ok(Arc::new(RwLock::new(node)))
+    }
+
+    fn for_assignment(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let mut token = self.get_current_token();
+        match &token {
+            Id(id) => {
+                let id = id.clone();
+                self.consume(&token)?;
+                if self.get_current_token() == LBracket {
+                    self.consume(&LBracket)?;
+                    let index = self.add_expr()?;
+                    token = IndexId(id, index);
+                    self.consume(&RBracket)?;
+                }
+            }
+            _ => return Err(format!("Invalid token in for statement: {}", token)),
+        }
+        self.assignment_call_statement(Some(token))
+    }
+
+    fn for_statement(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        // for (init; condition; step) { statement_list }
+        self.consume(&For)?;
+        self.consume(&LParen)?;
+        let init = if self.is_type_spec() {
+            let type_node = self.type_spec()?;
+            self.ident_declaration_assignment(&type_node, false)?
+        } else {
+            let init = self.for_assignment()?;
+            self.consume(&Semi)?;
+            vec![init]
+        };
+        let condition = self.or_expr()?;
+        self.consume(&Semi)?;
+        let step = self.for_assignment()?;
+        self.consume(&RParen)?;
+
+        self.consume(&Begin)?;
+        let consequences = self.statement_list()?;
+        self.consume(&End)?;
+
+        let node = ForLoopNode::new(init, condition, step, consequences);
+
+        Ok(Arc::new(RwLock::new(node)))
     }
 
-    fn identifier(&mut self) -> Arc<RwLock<dyn Node>> {
+    fn identifier(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
         let current_token = self.get_current_token();
         if let Id(_) = current_token {
-            self.consume(&self.get_current_token());
+            self.consume(&self.get_current_token())?;
             if self.get_current_token() == LParen {
-                self.consume(&self.get_current_token());
+                self.consume(&self.get_current_token())?;
                 let mut params = Vec::new();
                 while self.get_current_token() != RParen {
-                    let param = self.or_expr();
+                    let param = self.or_expr()?;
                     params.push(param);
                     if self.get_current_token() == Comma {
-                        self.consume(&Comma);
+                        self.consume(&Comma)?;
                     }
                 }
-                self.consume(&RParen);
+                self.consume(&RParen)?;
                 let node = CallNode::new(current_token, params);
-                Arc::new(RwLock::new(node))
+                Ok(Arc::new(RwLock::new(node)))
             } else if LBracket == self.get_current_token() {
-                self.consume(&LBracket);
-                let index = self.add_expr();
+                self.consume(&LBracket)?;
+                let index = self.add_expr()?;
                 let node = IdentIndexNode::new(current_token, index);
-                self.consume(&RBracket);
-                Arc::new(RwLock::new(node))
+                self.consume(&RBracket)?;
+                Ok(Arc::new(RwLock::new(node)))
             } else {
                 let node = IdentNode::new(current_token);
-                Arc::new(RwLock::new(node))
+                Ok(Arc::new(RwLock::new(node)))
             }
         } else if let Cid(_) = self.get_current_token() {
-            self.consume(&current_token);
+            self.consume(&current_token)?;
             let node = ContextIdentNode::new(current_token);
-            Arc::new(RwLock::new(node))
+            Ok(Arc::new(RwLock::new(node)))
         } else {
-            panic!("Invalid variable: {}", current_token);
+            Err(format!("Invalid variable: {}", current_token))
         }
     }
 
-    fn cast_expr(&mut self) -> Arc<RwLock<dyn Node>> {
+    fn cast_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
         let mut current_token = self.get_current_token();
 
-        match current_token {
-            Plus | Minus => {
-                self.consume(&current_token);
-                let node = UnaryOpNode::new(current_token, self.mul_expr());
+        let node: Arc<RwLock<dyn Node>> = match current_token {
+            Plus | Minus | Not => {
+                self.consume(&current_token)?;
+                let node = UnaryOpNode::new(current_token, self.mul_expr()?);
                 Arc::new(RwLock::new(node))
             }
             FeltConst(value) => {
                 current_token = self.get_current_token();
-                self.consume(&current_token);
-                Arc::new(RwLock::new(FeltNumNode::new(value.parse::<u64>().unwrap())))
+                self.consume(&current_token)?;
+                let value = value
+                    .parse::<u64>()
+                    .map_err(|err| format!("invalid felt const {}: {}", value, err))?;
+                Arc::new(RwLock::new(FeltNumNode::new(value)))
             }
             I32Const(value) => {
                 current_token = self.get_current_token();
-                self.consume(&current_token);
-                Arc::new(RwLock::new(IntegerNumNode::new(
-                    value.parse::<i32>().unwrap(),
-                )))
+                self.consume(&current_token)?;
+                let value = value
+                    .parse::<i32>()
+                    .map_err(|err| format!("invalid i32 const {}: {}", value, err))?;
+                Arc::new(RwLock::new(IntegerNumNode::new(value)))
             }
             Sqrt => {
-                self.consume(&current_token);
-                self.consume(&LParen);
-                let sqrt_value = self.or_expr();
-                self.consume(&RParen);
+                self.consume(&current_token)?;
+                self.consume(&LParen)?;
+                let sqrt_value = self.or_expr()?;
+                self.consume(&RParen)?;
                 Arc::new(RwLock::new(SqrtNode::new(sqrt_value)))
             }
+            Inv => {
+                self.consume(&current_token)?;
+                self.consume(&LParen)?;
+                let inv_value = self.or_expr()?;
+                self.consume(&RParen)?;
+                Arc::new(RwLock::new(InvNode::new(inv_value)))
+            }
             Malloc => {
-                self.consume(&current_token);
-                self.consume(&LParen);
-                let num_bytes = self.or_expr();
-                self.consume(&RParen);
+                self.consume(&current_token)?;
+                self.consume(&LParen)?;
+                let num_bytes = self.or_expr()?;
+                self.consume(&RParen)?;
                 Arc::new(RwLock::new(MallocNode::new(num_bytes)))
             }
+            ReadMem => {
+                self.consume(&current_token)?;
+                self.consume(&LParen)?;
+                let ptr = self.or_expr()?;
+                self.consume(&Comma)?;
+                let len = self.or_expr()?;
+                self.consume(&RParen)?;
+                Arc::new(RwLock::new(ReadMemNode::new(ptr, len)))
+            }
             LParen => {
-                self.consume(&current_token);
-                let node = self.or_expr();
-                self.consume(&RParen);
+                self.consume(&current_token)?;
+                let node = self.or_expr()?;
+                self.consume(&RParen)?;
                 node
             }
-            Id(_) | Cid(_) => self.identifier(),
-            LBracket => self.array_const(),
+            Id(_) | Cid(_) => self.identifier()?,
+            LBracket => self.array_const()?,
             Printf => {
-                self.consume(&current_token);
-                self.consume(&LParen);
-                let start = self.or_expr();
-                self.consume(&Comma);
-                let flag = self.or_expr();
-                self.consume(&RParen);
+                self.consume(&current_token)?;
+                self.consume(&LParen)?;
+                let start = self.or_expr()?;
+                self.consume(&Comma)?;
+                let flag = self.or_expr()?;
+                self.consume(&RParen)?;
                 Arc::new(RwLock::new(PrintfNode::new(start, flag)))
             }
-            _ => panic!(
-                "not support token in cast_expr:{}",
-                self.get_current_token()
-            ),
-        }
+            _ => {
+                return Err(format!(
+                    "not support token in cast_expr:{}",
+                    self.get_current_token()
+                ))
+            }
+        };
+        Ok(node)
     }
 
-    fn mul_expr(&mut self) -> Arc<RwLock<dyn Node>> {
-        let mut node = self.cast_expr();
+    fn mul_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let mut node = self.cast_expr()?;
         let mut current_token = self.get_current_token();
 
         while current_token == Multiply || current_token == IntegerDivision || current_token == Mod
         {
-            self.consume(&current_token);
+            self.consume(&current_token)?;
             node = Arc::new(RwLock::new(BinOpNode::new(
                 node,
-                self.cast_expr(),
+                self.cast_expr()?,
                 current_token,
             )));
             current_token = self.get_current_token();
         }
-        node
+        Ok(node)
     }
 
-    fn add_expr(&mut self) -> Arc<RwLock<dyn Node>> {
-        let mut node = self.mul_expr();
+    fn add_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let mut node = self.mul_expr()?;
         let mut current_token = self.get_current_token();
         while current_token == Plus || current_token == Minus {
-            self.consume(&current_token);
+            self.consume(&current_token)?;
+            node = Arc::new(RwLock::new(BinOpNode::new(
+                node,
+                self.mul_expr()?,
+                current_token,
+            )));
+            current_token = self.get_current_token();
+        }
+        Ok(node)
+    }
+
+    fn shift_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let mut node = self.add_expr()?;
+        let mut current_token = self.get_current_token();
+        while current_token == ShiftLeft || current_token == ShiftRight {
+            self.consume(&current_token)?;
+            node = Arc::new(RwLock::new(BinOpNode::new(
+                node,
+                self.add_expr()?,
+                current_token,
+            )));
+            current_token = self.get_current_token();
+        }
+        Ok(node)
+    }
+
+    fn bit_and_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let mut node = self.shift_expr()?;
+        let mut current_token = self.get_current_token();
+        while current_token == BitAnd {
+            self.consume(&current_token)?;
+            node = Arc::new(RwLock::new(BinOpNode::new(
+                node,
+                self.shift_expr()?,
+                current_token,
+            )));
+            current_token = self.get_current_token();
+        }
+        Ok(node)
+    }
+
+    fn bit_xor_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let mut node = self.bit_and_expr()?;
+        let mut current_token = self.get_current_token();
+        while current_token == BitXor {
+            self.consume(&current_token)?;
+            node = Arc::new(RwLock::new(BinOpNode::new(
+                node,
+                self.bit_and_expr()?,
+                current_token,
+            )));
+            current_token = self.get_current_token();
+        }
+        Ok(node)
+    }
+
+    fn bit_or_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let mut node = self.bit_xor_expr()?;
+        let mut current_token = self.get_current_token();
+        while current_token == BitOr {
+            self.consume(&current_token)?;
             node = Arc::new(RwLock::new(BinOpNode::new(
                 node,
-                self.mul_expr(),
+                self.bit_xor_expr()?,
                 current_token,
             )));
             current_token = self.get_current_token();
         }
-        node
+        Ok(node)
     }
 
-    fn rel_expr(&mut self) -> Arc<RwLock<dyn Node>> {
-        let left = self.add_expr();
+    fn rel_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let left = self.bit_or_expr()?;
         let current_token = self.get_current_token();
         if (current_token == GreaterThan)
             || (current_token == NotEqual)
@@ -597,82 +838,91 @@ impl Parser {
             || (current_token == LessThan)
             || (current_token == LessEqual)
         {
-            self.consume(&current_token);
-            let right = self.add_expr();
+            self.consume(&current_token)?;
+            let right = self.bit_or_expr()?;
             let node = BinOpNode::new(left, right, current_token);
-            Arc::new(RwLock::new(node))
+            Ok(Arc::new(RwLock::new(node)))
         } else {
-            left
+            Ok(left)
         }
     }
 
-    fn and_expr(&mut self) -> Arc<RwLock<dyn Node>> {
-        let mut node = self.rel_expr();
+    fn and_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let mut node = self.rel_expr()?;
         let mut current_token = self.get_current_token();
         while current_token == And {
-            self.consume(&current_token);
+            self.consume(&current_token)?;
             node = Arc::new(RwLock::new(BinOpNode::new(
                 node,
-                self.rel_expr(),
+                self.rel_expr()?,
                 current_token,
             )));
             current_token = self.get_current_token();
         }
-        node
+        Ok(node)
     }
 
-    fn or_expr(&mut self) -> Arc<RwLock<dyn Node>> {
-        let mut node = self.and_expr();
+    fn or_expr(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let mut node = self.and_expr()?;
         let mut current_token = self.get_current_token();
         while current_token == Or {
-            self.consume(&current_token);
+            self.consume(&current_token)?;
             node = Arc::new(RwLock::new(BinOpNode::new(
                 node,
-                self.and_expr(),
+                self.and_expr()?,
                 current_token,
             )));
             current_token = self.get_current_token();
         }
-        node
+        Ok(node)
     }
 
-    fn array_const(&mut self) -> Arc<RwLock<dyn Node>> {
-        self.consume(&LBracket);
+    fn array_const(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        self.consume(&LBracket)?;
         let mut values = Vec::new();
         loop {
             let current_token = self.get_current_token();
             if let I32Const(value) = current_token {
-                values.push(Number::I32(value.parse().unwrap()));
-                self.consume(&self.get_current_token());
+                let value = value
+                    .parse::<i32>()
+                    .map_err(|err| format!("invalid i32 const {}: {}", value, err))?;
+                values.push(Number::I32(value));
+                self.consume(&self.get_current_token())?;
 
                 if Comma == self.get_current_token() {
-                    self.consume(&self.get_current_token());
+                    self.consume(&self.get_current_token())?;
                 }
             } else if let FeltConst(value) = current_token {
-                values.push(Number::Felt(value.parse().unwrap()));
+                let value = value
+                    .parse::<u64>()
+                    .map_err(|err| format!("invalid felt const {}: {}", value, err))?;
+                values.push(Number::from(value));
 
-                self.consume(&self.get_current_token());
+                self.consume(&self.get_current_token())?;
                 if Comma == self.get_current_token() {
-                    self.consume(&self.get_current_token());
+                    self.consume(&self.get_current_token())?;
                 }
             } else if RBracket == self.get_current_token() {
-                self.consume(&RBracket);
+                self.consume(&RBracket)?;
                 break;
             } else {
-                panic!("invalid array const")
+                return Err(format!("invalid array const: {}", current_token));
             }
         }
 
         let node = ArrayNumNode::new(values);
-        Arc::new(RwLock::new(node))
+        Ok(Arc::new(RwLock::new(node)))
     }
 
-    pub fn parse(&mut self) -> Arc<RwLock<dyn Node>> {
-        let node = self.entry();
+    pub fn parse(&mut self) -> ParseResult<Arc<RwLock<dyn Node>>> {
+        let node = self.entry()?;
         let current_token = self.get_current_token();
         if current_token != EOF {
-            panic!("Unexpected token found at end of file: {}", current_token);
+            return Err(format!(
+                "Unexpected token found at end of file: {}",
+                current_token
+            ));
         }
-        node
+        Ok(node)
     }
 }
@@ -9,7 +9,7 @@ use crate::sema::symbol::Symbol;
 use crate::utils::number::{Number, NumberResult};
 use node_derive::Node;
 
-pub trait Node {
+pub trait Node: Send + Sync {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn traverse(&mut self, visitor: &mut dyn Traversal) -> NumberResult;
@@ -67,23 +67,22 @@ impl BinOpNode {
 
 pub fn to_string(node: &Arc<RwLock<dyn Node>>) -> String {
     if is_node_type::<IntegerNumNode>(node) {
-        safe_downcast_ref::<IntegerNumNode>(node).value.to_string()
+        safe_downcast_ref(node, |node: &IntegerNumNode| node.value.to_string())
     } else if is_node_type::<FeltNumNode>(node) {
-        safe_downcast_ref::<FeltNumNode>(node).value.to_string()
+        safe_downcast_ref(node, |node: &FeltNumNode| node.value.to_string())
     } else if is_node_type::<IdentNode>(node) {
-        safe_downcast_ref::<IdentNode>(node).identifier.to_string()
+        safe_downcast_ref(node, |node: &IdentNode| node.identifier.to_string())
     } else if is_node_type::<ContextIdentNode>(node) {
-        safe_downcast_ref::<ContextIdentNode>(node)
-            .identifier
-            .to_string()
+        safe_downcast_ref(node, |node: &ContextIdentNode| node.identifier.to_string())
     } else {
-        let bin_node = safe_downcast_ref::<BinOpNode>(node);
-        format!(
-            "BinOpNode({} {} {}) ",
-            to_string(&bin_node.left),
-            to_string(&bin_node.right),
-            bin_node.operator
-        )
+        safe_downcast_ref(node, |bin_node: &BinOpNode| {
+            format!(
+                "BinOpNode({} {} {}) ",
+                to_string(&bin_node.left),
+                to_string(&bin_node.right),
+                bin_node.operator
+            )
+        })
     }
 }
 
@@ -317,6 +316,30 @@ impl LoopStatNode {
     }
 }
 
+#[derive(Node)]
+pub struct ForLoopNode {
+    pub init: Vec<Arc<RwLock<dyn Node>>>,
+    pub condition: Arc<RwLock<dyn Node>>,
+    pub step: Arc<RwLock<dyn Node>>,
+    pub consequences: Vec<Arc<RwLock<dyn Node>>>,
+}
+
+impl ForLoopNode {
+    pub fn new(
+        init: Vec<Arc<RwLock<dyn Node>>>,
+        condition: Arc<RwLock<dyn Node>>,
+        step: Arc<RwLock<dyn Node>>,
+        consequences: Vec<Arc<RwLock<dyn Node>>>,
+    ) -> Self {
+        ForLoopNode {
+            init,
+            condition,
+            step,
+            consequences,
+        }
+    }
+}
+
 #[derive(Node)]
 pub struct EntryNode {
     pub global_declarations: Vec<Arc<RwLock<dyn Node>>>,
@@ -387,6 +410,17 @@ impl SqrtNode {
     }
 }
 
+#[derive(Node)]
+pub struct InvNode {
+    pub inv_value: Arc<RwLock<dyn Node>>,
+}
+
+impl InvNode {
+    pub fn new(inv_value: Arc<RwLock<dyn Node>>) -> Self {
+        InvNode { inv_value }
+    }
+}
+
 #[derive(Node)]
 pub struct ReturnNode {
     pub returns: Vec<Arc<RwLock<dyn Node>>>,
@@ -409,6 +443,18 @@ impl MallocNode {
     }
 }
 
+#[derive(Node)]
+pub struct ReadMemNode {
+    pub ptr: Arc<RwLock<dyn Node>>,
+    pub len: Arc<RwLock<dyn Node>>,
+}
+
+impl ReadMemNode {
+    pub fn new(ptr: Arc<RwLock<dyn Node>>, len: Arc<RwLock<dyn Node>>) -> Self {
+        ReadMemNode { ptr, len }
+    }
+}
+
 #[derive(Clone, Node)]
 pub struct PrintfNode {
     pub flag: Arc<RwLock<dyn Node>>,
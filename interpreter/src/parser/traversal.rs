@@ -1,8 +1,9 @@
 use crate::parser::node::{
     ArrayIdentNode, ArrayNumNode, AssignNode, BinOpNode, BlockNode, CallNode, CompoundNode,
-    CondStatNode, ContextIdentNode, EntryBlockNode, EntryNode, FeltNumNode, FunctionNode,
-    IdentDeclarationNode, IdentIndexNode, IdentNode, IntegerNumNode, LoopStatNode, MallocNode,
-    MultiAssignNode, Node, PrintfNode, ReturnNode, SqrtNode, TypeNode, UnaryOpNode,
+    CondStatNode, ContextIdentNode, EntryBlockNode, EntryNode, FeltNumNode, ForLoopNode,
+    FunctionNode, IdentDeclarationNode, IdentIndexNode, IdentNode, IntegerNumNode, InvNode,
+    LoopStatNode, MallocNode, MultiAssignNode, Node, PrintfNode, ReadMemNode, ReturnNode, SqrtNode,
+    TypeNode, UnaryOpNode,
 };
 use crate::utils::number::NumberResult;
 use std::sync::{Arc, RwLock};
@@ -11,16 +12,16 @@ pub fn is_node_type<T: Node + 'static>(node: &Arc<RwLock<dyn Node>>) -> bool {
     node.read().unwrap().as_any().is::<T>()
 }
 
-pub fn safe_downcast_ref<T>(node: &Arc<RwLock<dyn Node>>) -> Arc<T>
+pub fn safe_downcast_ref<T, R>(node: &Arc<RwLock<dyn Node>>, f: impl FnOnce(&T) -> R) -> R
 where
     T: Node + 'static,
 {
-    node.read()
+    f(node
+        .read()
         .unwrap()
         .as_any()
-        .downcast_ref::<Arc<T>>()
-        .expect("Failed to downcast to the specific ref node type")
-        .clone()
+        .downcast_ref::<T>()
+        .expect("Failed to downcast to the specific ref node type"))
 }
 
 pub trait Traversal {
@@ -153,6 +154,14 @@ pub trait Traversal {
                     .downcast_mut::<LoopStatNode>()
                     .expect("Failed to downcast to LoopStatNode type"),
             )
+        } else if is_node_type::<ForLoopNode>(node) {
+            self.travel_for_loop(
+                node.write()
+                    .unwrap()
+                    .as_any_mut()
+                    .downcast_mut::<ForLoopNode>()
+                    .expect("Failed to downcast to ForLoopNode type"),
+            )
         } else if is_node_type::<EntryNode>(node) {
             self.travel_entry(
                 node.write()
@@ -193,6 +202,14 @@ pub trait Traversal {
                     .downcast_mut::<SqrtNode>()
                     .expect("Failed to downcast to SqrtNode type"),
             )
+        } else if is_node_type::<InvNode>(node) {
+            self.travel_inv(
+                node.write()
+                    .unwrap()
+                    .as_any_mut()
+                    .downcast_mut::<InvNode>()
+                    .expect("Failed to downcast to InvNode type"),
+            )
         } else if is_node_type::<ReturnNode>(node) {
             self.travel_return(
                 node.write()
@@ -217,6 +234,14 @@ pub trait Traversal {
                     .downcast_mut::<MallocNode>()
                     .expect("Failed to downcast to MallocNode type"),
             )
+        } else if is_node_type::<ReadMemNode>(node) {
+            self.travel_read_mem(
+                node.write()
+                    .unwrap()
+                    .as_any_mut()
+                    .downcast_mut::<ReadMemNode>()
+                    .expect("Failed to downcast to ReadMemNode type"),
+            )
         } else if is_node_type::<PrintfNode>(node) {
             self.travel_printf(
                 node.write()
@@ -243,6 +268,7 @@ pub trait Traversal {
     fn travel_compound(&mut self, node: &mut CompoundNode) -> NumberResult;
     fn travel_cond(&mut self, node: &mut CondStatNode) -> NumberResult;
     fn travel_loop(&mut self, node: &mut LoopStatNode) -> NumberResult;
+    fn travel_for_loop(&mut self, node: &mut ForLoopNode) -> NumberResult;
     fn travel_ident(&mut self, node: &mut IdentNode) -> NumberResult;
     fn travel_ident_index(&mut self, node: &mut IdentIndexNode) -> NumberResult;
     fn travel_context_ident(&mut self, node: &mut ContextIdentNode) -> NumberResult;
@@ -250,9 +276,11 @@ pub trait Traversal {
     fn travel_entry(&mut self, node: &mut EntryNode) -> NumberResult;
     fn travel_call(&mut self, node: &mut CallNode) -> NumberResult;
     fn travel_sqrt(&mut self, node: &mut SqrtNode) -> NumberResult;
+    fn travel_inv(&mut self, node: &mut InvNode) -> NumberResult;
     fn travel_return(&mut self, node: &mut ReturnNode) -> NumberResult;
     fn travel_multi_assign(&mut self, node: &mut MultiAssignNode) -> NumberResult;
     fn travel_malloc(&mut self, node: &mut MallocNode) -> NumberResult;
+    fn travel_read_mem(&mut self, node: &mut ReadMemNode) -> NumberResult;
 
     fn travel_printf(&mut self, node: &mut PrintfNode) -> NumberResult;
 }
@@ -2,14 +2,15 @@ use crate::lexer::token::Token;
 use crate::lexer::token::Token::{Array, ArrayId, Cid, Felt, Id};
 use crate::parser::node::{
     ArrayIdentNode, ArrayNumNode, AssignNode, BinOpNode, BlockNode, CallNode, CompoundNode,
-    CondStatNode, ContextIdentNode, EntryBlockNode, EntryNode, FeltNumNode, FunctionNode,
-    IdentDeclarationNode, IdentIndexNode, IdentNode, IntegerNumNode, LoopStatNode, MallocNode,
-    MultiAssignNode, Node, PrintfNode, ReturnNode, SqrtNode, TypeNode, UnaryOpNode,
+    CondStatNode, ContextIdentNode, EntryBlockNode, EntryNode, FeltNumNode, ForLoopNode,
+    FunctionNode, IdentDeclarationNode, IdentIndexNode, IdentNode, IntegerNumNode, InvNode,
+    LoopStatNode, MallocNode, MultiAssignNode, Node, PrintfNode, ReadMemNode, ReturnNode, SqrtNode,
+    TypeNode, UnaryOpNode,
 };
 use crate::parser::traversal::{is_node_type, safe_downcast_ref, Traversal};
 use crate::sema::symbol::Symbol::{BuiltInSymbol, FuncSymbol, IdentSymbol};
 use crate::sema::symbol::{BuiltIn, SymbolTable};
-use crate::utils::number::Number::Nil;
+use crate::utils::number::Number::{Bool, Nil};
 use crate::utils::number::NumberRet::{Multiple, Single};
 use crate::utils::number::{number_from_token, Number, NumberResult};
 use core::program::binary_program::OlaProphet;
@@ -24,14 +25,14 @@ macro_rules! inf_var_insert {
     ($input: tt, $current_scope: tt) => {
         if $input.length == 1 {
             let variable = IdentSymbol($input.name.to_string(), BuiltIn(Felt), None);
-            $current_scope.insert(variable);
+            $current_scope.insert(variable)?;
         } else {
             let variable = IdentSymbol(
                 $input.name.to_string(),
                 BuiltIn(Array(Box::new(Felt), $input.length)),
                 None,
             );
-            $current_scope.insert(variable);
+            $current_scope.insert(variable)?;
         }
     };
 }
@@ -39,16 +40,20 @@ macro_rules! inf_var_insert {
 #[derive(Clone)]
 pub struct SymTableGen {
     current_scope: Arc<RwLock<SymbolTable>>,
+    /// The function being checked. A function body is locked while it runs, so
+    /// it cannot call itself.
+    current_function: Option<String>,
 }
 
 impl SymTableGen {
-    pub fn new(prophet: &OlaProphet) -> Self {
+    pub fn new(prophet: &OlaProphet) -> Result<Self, String> {
         let gen = SymTableGen {
             current_scope: Arc::new(RwLock::new(SymbolTable::new(
                 "Global Scope".to_string(),
                 1,
                 None,
             ))),
+            current_function: None,
         };
 
         let mut current_scope = gen.current_scope.write().unwrap();
@@ -58,14 +63,14 @@ impl SymTableGen {
 
         for ctx in &prophet.ctx {
             let variable = IdentSymbol(ctx.0.to_string(), BuiltIn(Felt), None);
-            current_scope.insert(variable);
+            current_scope.insert(variable)?;
         }
 
         for output in prophet.outputs.iter() {
             inf_var_insert!(output, current_scope);
         }
         drop(current_scope);
-        gen
+        Ok(gen)
     }
 }
 
@@ -111,21 +116,21 @@ impl Traversal for SymTableGen {
             debug!("insert id name:{}", name);
             let mut current_scope = self.current_scope.write().unwrap();
             if let Array(builtin_token, len) = token {
-                if let BuiltInSymbol(builtin) = current_scope.get(&builtin_token) {
+                if let BuiltInSymbol(builtin) = current_scope.get(&builtin_token)? {
                     let variable = IdentSymbol(name.to_string(), builtin, Some(*len));
-                    current_scope.insert(variable);
+                    current_scope.insert(variable)?;
                 }
-            } else if let BuiltInSymbol(builtin) = current_scope.get(&token) {
+            } else if let BuiltInSymbol(builtin) = current_scope.get(&token)? {
                 let variable = IdentSymbol(name.to_string(), builtin, None);
-                current_scope.insert(variable);
+                current_scope.insert(variable)?;
             } else {
-                panic!("Invalid builtin type {}", token);
+                return Err(format!("Invalid builtin type {}", token));
             }
         }
         Ok(Single(Nil))
     }
     fn travel_type(&mut self, node: &mut TypeNode) -> NumberResult {
-        Ok(Single(Number::from(&node.token)))
+        Ok(Single(Number::try_from(&node.token)?))
     }
 
     fn travel_array_ident(&mut self, _node: &mut ArrayIdentNode) -> NumberResult {
@@ -137,11 +142,14 @@ impl Traversal for SymTableGen {
     }
 
     fn travel_felt(&mut self, _node: &mut FeltNumNode) -> NumberResult {
-        Ok(Single(Number::Felt(0)))
+        Ok(Single(Number::try_from(&Felt)?))
     }
 
     fn travel_array(&mut self, node: &mut ArrayNumNode) -> NumberResult {
-        Ok(Single(Number::from(&node.values[0].number_type())))
+        match node.values.first() {
+            Some(value) => Ok(Single(Number::try_from(&value.number_type()?)?)),
+            None => Err("Empty array const found.".to_string()),
+        }
     }
 
     fn travel_ident_index(&mut self, node: &mut IdentIndexNode) -> NumberResult {
@@ -165,19 +173,38 @@ impl Traversal for SymTableGen {
     }
 
     fn travel_binop(&mut self, node: &mut BinOpNode) -> NumberResult {
-        let left = self.travel(&node.left)?;
-        let right = self.travel(&node.right)?;
-        let left_type = match left {
-            Single(num) => num,
-            Multiple(nums) => nums[0].clone(),
-        };
-
-        let right_type = match right {
-            Single(num) => num,
-            Multiple(nums) => nums[0].clone(),
-        };
-        let binop_type = left_type.binop_number_type(&right_type);
-        Ok(Single(Number::from(&binop_type)))
+        let left_type = self.travel(&node.left)?.get_single()?;
+        let right_type = self.travel(&node.right)?.get_single()?;
+        match node.operator {
+            Token::And | Token::Or => Ok(Single(Bool(false))),
+            Token::Equal | Token::NotEqual if matches!(left_type, Bool(_)) => match right_type {
+                Bool(_) => Ok(Single(Bool(false))),
+                _ => Err(format!(
+                    "type mismatch: bool and {}",
+                    right_type.type_name()
+                )),
+            },
+            Token::Equal
+            | Token::NotEqual
+            | Token::LessThan
+            | Token::GreaterThan
+            | Token::LessEqual
+            | Token::GreaterEqual => {
+                left_type.binop_number_type(&right_type)?;
+                Ok(Single(Bool(false)))
+            }
+            Token::ShiftLeft | Token::ShiftRight => {
+                // An integer literal takes the type of any number, so this
+                // only checks both operands are numbers.
+                right_type.binop_number_type(&Number::I32(0))?;
+                let shift_type = left_type.binop_number_type(&Number::I32(0))?;
+                Ok(Single(Number::try_from(&shift_type)?))
+            }
+            _ => {
+                let binop_type = left_type.binop_number_type(&right_type)?;
+                Ok(Single(Number::try_from(&binop_type)?))
+            }
+        }
     }
     fn travel_unary_op(&mut self, node: &mut UnaryOpNode) -> NumberResult {
         self.travel(&node.expr)
@@ -225,11 +252,11 @@ impl Traversal for SymTableGen {
                         node.identifier = ArrayId(name.to_string());
                     }
                     if size.is_some() {
-                        return Ok(Single(number_from_token(&token, size.unwrap())));
+                        return Ok(Single(number_from_token(&token, size.unwrap())?));
                     }
-                    Ok(Single(Number::from(&token)))
+                    Ok(Single(Number::try_from(&token)?))
                 } else {
-                    panic!("ident not support symbol type")
+                    Err(format!("ident {} not support symbol type", name))
                 }
             }
         } else {
@@ -281,6 +308,27 @@ impl Traversal for SymTableGen {
         Ok(Single(Nil))
     }
 
+    fn travel_for_loop(&mut self, node: &mut ForLoopNode) -> NumberResult {
+        // The loop variable is only visible in the loop.
+        let cur = self.current_scope.clone();
+        let scope_level = cur.read().unwrap().scope_level;
+        let loop_scope = SymbolTable::new(Token::For.to_string(), scope_level + 1, Some(cur));
+        self.current_scope = Arc::new(RwLock::new(loop_scope));
+
+        for expr in node.init.iter() {
+            self.travel(expr)?;
+        }
+        self.travel(&node.condition)?;
+        self.travel(&node.step)?;
+        for expr in node.consequences.iter() {
+            self.travel(expr)?;
+        }
+
+        let enclosing_scope = self.current_scope.read().unwrap().enclosing_scope.clone();
+        self.current_scope = enclosing_scope.unwrap();
+        Ok(Single(Nil))
+    }
+
     fn travel_function(&mut self, node: &mut FunctionNode) -> NumberResult {
         if let Id(func_name) = &node.func_name {
             let mut param_symbols = Vec::new();
@@ -309,7 +357,20 @@ impl Traversal for SymTableGen {
                 let symbol = IdentSymbol(name.clone(), ident_type, token_len);
                 param_scope.insert(name.clone(), symbol);
             }
-            let func_symbol = FuncSymbol(func_name.to_string(), param_symbols, node.block.clone());
+            let mut return_types = Vec::new();
+            for return_node in &node.returns {
+                let return_node = return_node.read().unwrap();
+                match return_node.as_any().downcast_ref::<TypeNode>() {
+                    Some(type_node) => return_types.push(BuiltIn(type_node.token.clone())),
+                    None => return Err(format!("invalid return type of function {}", func_name)),
+                }
+            }
+            let func_symbol = FuncSymbol(
+                func_name.to_string(),
+                param_symbols,
+                return_types,
+                node.block.clone(),
+            );
             self.current_scope
                 .write()
                 .unwrap()
@@ -320,7 +381,9 @@ impl Traversal for SymTableGen {
             let mut cur_scope = SymbolTable::new(func_name.to_string(), scope_level + 1, Some(cur));
             cur_scope.symbols = param_scope;
             self.current_scope = Arc::new(RwLock::new(cur_scope));
+            self.current_function = Some(func_name.to_string());
             self.travel(&node.block)?;
+            self.current_function = None;
             let enclosing_scope = self.current_scope.read().unwrap().enclosing_scope.clone();
             self.current_scope = enclosing_scope.unwrap();
         }
@@ -328,6 +391,9 @@ impl Traversal for SymTableGen {
     }
 
     fn travel_call(&mut self, node: &mut CallNode) -> NumberResult {
+        if self.current_function.as_deref() == Some(&node.func_name.to_string()) {
+            return Err(format!("recursive call of function {}", node.func_name));
+        }
         let symbol = self
             .current_scope
             .read()
@@ -339,51 +405,68 @@ impl Traversal for SymTableGen {
             let res = self.travel(param)?;
             let param_type = match res {
                 Single(num) => num,
-                Multiple(nums) => number_from_token(&nums[0].number_type(), nums.len()),
+                Multiple(nums) => match nums.first() {
+                    Some(num) => number_from_token(&num.number_type()?, nums.len())?,
+                    None => return Err(format!("empty param of function {}", node.func_name)),
+                },
             };
 
             actual_types.push(param_type);
         }
         if let Some(func_symbol) = symbol {
-            if let FuncSymbol(name, params, body) = func_symbol {
+            if let FuncSymbol(name, params, returns, body) = func_symbol {
+                if params.len() != actual_types.len() {
+                    return Err(format!(
+                        "function {} takes {} params, {} given",
+                        name,
+                        params.len(),
+                        actual_types.len()
+                    ));
+                }
                 for (index, item) in params.iter().enumerate() {
-                    if !Number::from(&item.1 .0).eq(&actual_types.get(index).unwrap()) {
-                        panic!("function params type not match")
+                    let param_type = Number::try_from(&item.1 .0)?;
+                    match actual_types.get(index) {
+                        Some(actual_type) if param_type.eq(actual_type) => {}
+                        _ => return Err(format!("function {} params type not match", name)),
                     }
                 }
-                node.func_symbol = Some(Arc::new(RwLock::new(FuncSymbol(name, params, body))));
+                let return_types = returns
+                    .iter()
+                    .map(|ret| Number::try_from(&ret.0))
+                    .collect::<Result<Vec<_>, _>>()?;
+                node.func_symbol = Some(Arc::new(RwLock::new(FuncSymbol(
+                    name, params, returns, body,
+                ))));
+                if return_types.is_empty() {
+                    Ok(Single(Nil))
+                } else {
+                    Ok(Multiple(return_types))
+                }
             } else {
-                panic!("not support symbol for function")
+                Err(format!(
+                    "not support symbol for function {}",
+                    node.func_name
+                ))
             }
         } else {
-            panic!("not found function");
+            Err(format!("not found function {}", node.func_name))
         }
-        Ok(Single(Nil))
     }
 
     fn travel_sqrt(&mut self, node: &mut SqrtNode) -> NumberResult {
         self.travel(&node.sqrt_value)
     }
 
+    fn travel_inv(&mut self, node: &mut InvNode) -> NumberResult {
+        let value = self.travel(&node.inv_value)?.get_single()?;
+        let felt = Number::try_from(&Felt)?;
+        felt.binop_number_type(&value)?;
+        Ok(Single(felt))
+    }
+
     fn travel_return(&mut self, node: &mut ReturnNode) -> NumberResult {
         for ret in &node.returns {
-            if is_node_type::<IdentNode>(ret) {
-                let mut ident = ret.write().unwrap();
-                let ident = ident.as_any_mut().downcast_mut::<IdentNode>().unwrap();
-
-                let name = ident.identifier.clone().to_string();
-                if self.current_scope.read().unwrap().lookup(&name).is_none() {
-                    return Err(format!("assign Undeclared variable {} found.", name));
-                } else {
-                    if let IdentSymbol(name, BuiltIn(_token), size) =
-                        self.current_scope.read().unwrap().lookup(&name).unwrap()
-                    {
-                        if size.is_some() {
-                            ident.identifier = ArrayId(name.to_string());
-                        }
-                    }
-                }
-            }
+            self.travel(ret)?;
         }
         Ok(Single(Nil))
     }
@@ -391,16 +474,13 @@ impl Traversal for SymTableGen {
     fn travel_multi_assign(&mut self, node: &mut MultiAssignNode) -> NumberResult {
         for node in node.identifier.iter() {
             if is_node_type::<IdentNode>(node) {
-                let ident = &safe_downcast_ref::<IdentNode>(node).identifier.clone();
-                let name = ident.to_string();
+                let name = safe_downcast_ref(node, |node: &IdentNode| node.identifier.to_string());
                 if self.current_scope.read().unwrap().lookup(&name).is_none() {
                     return Err(format!("assign Undeclared variable {} found.", name));
                 }
             } else if is_node_type::<ContextIdentNode>(node) {
-                let ident = &safe_downcast_ref::<ContextIdentNode>(node)
-                    .identifier
-                    .clone();
-                let name = ident.to_string();
+                let name =
+                    safe_downcast_ref(node, |node: &ContextIdentNode| node.identifier.to_string());
                 if self.current_scope.read().unwrap().lookup(&name).is_none() {
                     return Err(format!("assign Undeclared variable {} found.", name));
                 }
@@ -416,6 +496,12 @@ impl Traversal for SymTableGen {
         self.travel(&node.num_bytes)
     }
 
+    fn travel_read_mem(&mut self, node: &mut ReadMemNode) -> NumberResult {
+        self.travel(&node.ptr)?;
+        self.travel(&node.len)?;
+        Ok(Single(Number::try_from(&Felt)?))
+    }
+
     fn travel_printf(&mut self, node: &mut PrintfNode) -> NumberResult {
         self.travel(&node.flag)?;
         let ret = self.travel(&node.val_addr);
@@ -9,10 +9,10 @@ use std::sync::{Arc, RwLock};
 pub struct BuiltIn(pub Token);
 
 impl BuiltIn {
-    pub fn new(name: Token) -> Self {
+    pub fn new(name: Token) -> Result<Self, String> {
         match name {
-            Token::I32 | Token::Felt => BuiltIn(name),
-            _ => panic!("Invalid symbol value found {}", name),
+            Token::I32 | Token::U32 | Token::U64 | Token::Felt => Ok(BuiltIn(name)),
+            _ => Err(format!("Invalid symbol value found {}", name)),
         }
     }
 }
@@ -21,7 +21,13 @@ impl BuiltIn {
 pub enum Symbol {
     BuiltInSymbol(BuiltIn),
     IdentSymbol(String, BuiltIn, Option<usize>),
-    FuncSymbol(String, Vec<(String, BuiltIn)>, Arc<RwLock<dyn Node>>),
+    /// Name, parameters, return types and body of a function.
+    FuncSymbol(
+        String,
+        Vec<(String, BuiltIn)>,
+        Vec<BuiltIn>,
+        Arc<RwLock<dyn Node>>,
+    ),
 }
 
 #[derive(Clone)]
@@ -54,19 +60,16 @@ impl SymbolTable {
             .insert(builtin.0.to_string(), BuiltInSymbol(builtin));
     }
     // Returns the builtin type for the given token reference.
-    pub fn get(&self, name: &Token) -> Symbol {
-        let symbol = self.lookup(&name.to_string());
-        if symbol.is_some() {
-            symbol.unwrap()
-        } else {
-            panic!("token {} not found", name)
-        }
+    pub fn get(&self, name: &Token) -> Result<Symbol, String> {
+        self.lookup(&name.to_string())
+            .ok_or(format!("token {} not found", name))
     }
-    pub fn insert(&mut self, symbol: Symbol) {
+    pub fn insert(&mut self, symbol: Symbol) -> Result<(), String> {
         if let IdentSymbol(key, _kind, _) = symbol.clone() {
             self.symbols.insert(key, symbol);
+            Ok(())
         } else {
-            panic!("{}", format!("Error, Invalid Symbol! {}", symbol));
+            Err(format!("Error, Invalid Symbol! {}", symbol))
         }
     }
     pub fn lookup(&self, key: &str) -> Option<Symbol> {
@@ -83,10 +86,10 @@ impl SymbolTable {
         }
     }
     fn initialise_builtins(&mut self) {
-        let u32_type = BuiltIn::new(Token::I32);
-        let felt_type = BuiltIn::new(Token::Felt);
-        self.set(u32_type);
-        self.set(felt_type);
+        self.set(BuiltIn(Token::I32));
+        self.set(BuiltIn(Token::U32));
+        self.set(BuiltIn(Token::U64));
+        self.set(BuiltIn(Token::Felt));
     }
 }
 
@@ -104,13 +107,14 @@ impl fmt::Display for Symbol {
             match self {
                 BuiltInSymbol(symbol) => symbol.to_string(),
                 IdentSymbol(key, symbol, size) => format!("{}: {},size:{:?}", key, symbol, size),
-                FuncSymbol(func_name, params, _) => {
+                FuncSymbol(func_name, params, returns, _) => {
                     let mut output: String = String::new();
                     for param in params {
                         let (name, kind) = param;
                         output += &format!("{}: {}", name, kind);
                     }
-                    format!("{} {{ {} }}", func_name, output)
+                    let returns: Vec<String> = returns.iter().map(|ret| ret.to_string()).collect();
+                    format!("{} {{ {} }} -> ({})", func_name, output, returns.join(", "))
                 }
             }
         )
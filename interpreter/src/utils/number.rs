@@ -1,62 +1,65 @@
 use crate::lexer::token::Token;
-use crate::utils::number::Number::{Bool, Felt, Nil, I32};
+use crate::utils::number::Number::{Bool, Felt, Nil, I32, U32, U64};
 use crate::utils::number::NumberRet::{Multiple, Single};
+use core::types::{Field, Field64, GoldilocksField, PrimeField64};
+use core::utils::isqrt;
 use regex::Regex;
 use std::cmp::Ordering;
-use std::ops;
-use std::ops::Not;
 use std::str::FromStr;
 
+/// Applies a binary operation after bringing both operands to a common type.
+/// Integers use `$int_op`, a checked method returning `None` on overflow,
+/// felts use `$felt_op`.
 #[macro_export]
 macro_rules! number_binop {
-    ($v: expr, $op: tt, $rhs: ident, $op_desc: tt) => {
-        match $v {
-            Nil => $rhs,
-            I32(left) => match $rhs {
-                Nil => $v,
-                I32(right) => I32(left $op right),
-                Felt(right) => Felt(left as i128 $op right),
-                _ => panic!("{} not use bool", $op_desc),
-            },
-            Felt(left) => match $rhs {
-                Nil => $v,
-                I32(right) => Felt(left $op right as i128),
-                Felt(right) => Felt(left $op right),
-                _ => panic!("{} not use bool", $op_desc),
-            },
-            _ => panic!("{} not use bool", $op_desc),
+    ($v: expr, $rhs: ident, $int_op: ident, $felt_op: expr, $op_desc: tt) => {
+        match $v.unify($rhs)? {
+            (I32(left), I32(right)) => left.$int_op(right).map(I32),
+            (U32(left), U32(right)) => left.$int_op(right).map(U32),
+            (U64(left), U64(right)) => left.$int_op(right).map(U64),
+            (Felt(left), Felt(right)) => $felt_op(left, right).map(Felt),
+            (left, right) => {
+                return Err(format!(
+                    "{} not support {} and {}",
+                    $op_desc,
+                    left.type_name(),
+                    right.type_name()
+                ))
+            }
         }
+        .ok_or_else(|| format!("{} overflow", $op_desc))
     };
-    ($v: expr, $op: tt, $rhs: tt,  $op_desc: tt, $cmp: tt) => {
-        match $v {
-            Nil => match $rhs {
-                Nil => 0 $op 0,
-                I32(_) => 0 $op 1 ,
-                Felt(_) => 0 $op 1,
-                _ => panic!("{} not use bool",  $op_desc),
-            },
-            I32(left) => match $rhs {
-                Nil => 0 $op 1,
-                I32(right) => left $op right,
-                Felt(right) => (*left as i128) $op *right,
-                _ => panic!("{} not use bool",  $op_desc),
-            },
-            Felt(left) => match $rhs {
-                Nil => 0 $op 1,
-                I32(right) => *left $op (*right as i128),
-                Felt(right) => *left $op *right,
-                _ => panic!("{} not use bool", $op_desc),
-            },
-            _ => panic!("{} not use bool", $op_desc),
+}
+
+macro_rules! number_bitop {
+    ($v: expr, $rhs: ident, $op: tt, $op_desc: tt) => {
+        match $v.unify($rhs)? {
+            (I32(left), I32(right)) => Ok(I32(left $op right)),
+            (U32(left), U32(right)) => Ok(U32(left $op right)),
+            (U64(left), U64(right)) => Ok(U64(left $op right)),
+            (Felt(left), Felt(right)) => {
+                U64(left.to_canonical_u64() $op right.to_canonical_u64()).cast(&Token::Felt)
+            }
+            (left, right) => Err(format!(
+                "{} not support {} and {}",
+                $op_desc,
+                left.type_name(),
+                right.type_name()
+            )),
         }
     };
 }
 
+/// A prophet value. `Felt` is a Goldilocks field element, the integer types
+/// are checked and report overflow as an error. `I32` is also the type of
+/// integer literals, which take the type of the other operand.
 #[derive(Debug, Clone)]
 pub enum Number {
     Nil,
     I32(i32),
-    Felt(i128),
+    U32(u32),
+    U64(u64),
+    Felt(GoldilocksField),
     Bool(bool),
 }
 
@@ -67,23 +70,23 @@ pub enum NumberRet {
 }
 
 impl NumberRet {
-    pub fn get_single(self) -> Number {
+    pub fn get_single(self) -> Result<Number, String> {
         match self {
-            Single(value) => value,
-            Multiple(values) => {
+            Single(value) => Ok(value),
+            Multiple(mut values) => {
                 if values.len() == 1 {
-                    return values.get(0).unwrap().clone();
+                    Ok(values.remove(0))
                 } else {
-                    panic!("binop not support multi value")
+                    Err("binop not support multi value".to_string())
                 }
             }
         }
     }
 
-    pub fn get_multiple(self) -> Vec<Number> {
+    pub fn get_multiple(self) -> Result<Vec<Number>, String> {
         match self {
-            Single(_) => panic!("is single value"),
-            Multiple(values) => values,
+            Single(_) => Err("is single value".to_string()),
+            Multiple(values) => Ok(values),
         }
     }
 }
@@ -91,17 +94,24 @@ impl NumberRet {
 pub type NumberResult = Result<NumberRet, String>;
 
 fn convert(text: &str) -> Number {
-    let reg = Regex::new(r"^I32\((?P<u32>[-+]?\d+)\)|^Felt\((?P<felt>[-+]?\d+)\)").unwrap();
+    let reg = Regex::new(concat!(
+        r"^I32\((?P<i32>[-+]?\d+)\)|^U32\((?P<u32>\d+)\)",
+        r"|^U64\((?P<u64>\d+)\)|^Felt\((?P<felt>\d+)\)"
+    ))
+    .unwrap();
 
-    let cap = reg.captures(text).unwrap();
-    let int_as_str = cap.name("u32").map_or("", |m| m.as_str());
-    let felt_as_str = cap.name("felt").map_or("", |m| m.as_str());
-    if !int_as_str.is_empty() {
-        let value = int_as_str.parse::<i32>().unwrap();
+    let cap = match reg.captures(text) {
+        Some(cap) => cap,
+        None => return Nil,
+    };
+    if let Some(value) = cap.name("i32").and_then(|m| m.as_str().parse().ok()) {
         I32(value)
-    } else if !felt_as_str.is_empty() {
-        let value = felt_as_str.parse::<i128>().unwrap();
-        Felt(value)
+    } else if let Some(value) = cap.name("u32").and_then(|m| m.as_str().parse().ok()) {
+        U32(value)
+    } else if let Some(value) = cap.name("u64").and_then(|m| m.as_str().parse().ok()) {
+        U64(value)
+    } else if let Some(value) = cap.name("felt").and_then(|m| m.as_str().parse().ok()) {
+        Felt(GoldilocksField::from_noncanonical_u64(value))
     } else {
         Nil
     }
@@ -119,160 +129,338 @@ impl ToString for Number {
         match self {
             Nil => "Nil".into(),
             I32(value) => format!("I32({})", value),
-            Felt(value) => format!("Felt({})", value),
+            U32(value) => format!("U32({})", value),
+            U64(value) => format!("U64({})", value),
+            Felt(value) => format!("Felt({})", value.to_canonical_u64()),
             Bool(value) => format!("Bool({})", value),
         }
     }
 }
 
-impl ops::Neg for Number {
-    type Output = Number;
-
-    fn neg(self) -> Self {
-        match self {
-            Nil => Nil,
-            I32(value) => I32(-value),
-            Felt(value) => Felt(value.not()),
-            Bool(value) => Bool(value.not()),
-        }
+impl PartialEq for Number {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.compare(rhs) == Ok(Ordering::Equal)
     }
 }
 
-impl ops::Add for Number {
-    type Output = Number;
-    fn add(self, rhs: Number) -> Number {
-        number_binop!(self, +, rhs, "add")
+impl From<i32> for Number {
+    fn from(num: i32) -> Self {
+        I32(num)
     }
 }
 
-impl ops::Mul for Number {
-    type Output = Number;
-    fn mul(self, rhs: Number) -> Number {
-        number_binop!(self, *, rhs, "mul")
+impl From<u64> for Number {
+    fn from(num: u64) -> Self {
+        Felt(GoldilocksField::from_noncanonical_u64(num))
     }
 }
 
-impl ops::Sub for Number {
-    type Output = Number;
-    fn sub(self, rhs: Number) -> Number {
-        number_binop!(self, - , rhs, "sub")
+impl TryFrom<&Token> for Number {
+    type Error = String;
+
+    fn try_from(token: &Token) -> Result<Self, Self::Error> {
+        match token {
+            Token::I32 => Ok(I32(0)),
+            Token::U32 => Ok(U32(0)),
+            Token::U64 => Ok(U64(0)),
+            Token::Felt => Ok(Felt(GoldilocksField::ZERO)),
+            Token::Array(token, len) => number_from_token(token, *len),
+            _ => Err(format!("not support token to Number:{}", token)),
+        }
     }
 }
 
-impl ops::Div for Number {
-    type Output = Number;
-    fn div(self, rhs: Number) -> Number {
-        number_binop!(self, /, rhs, "divide")
+pub fn number_from_token(token: &Token, len: usize) -> Result<Number, String> {
+    match token {
+        Token::Felt => Ok(Number::Felt(GoldilocksField::from_canonical_u64(
+            len as u64,
+        ))),
+        Token::I32 => Ok(Number::I32(len as i32)),
+        Token::U32 => Ok(Number::U32(len as u32)),
+        Token::U64 => Ok(Number::U64(len as u64)),
+        _ => Err(format!("not support array of {}", token)),
     }
 }
 
-impl ops::Rem for Number {
-    type Output = Number;
-    fn rem(self, rhs: Number) -> Number {
-        number_binop!(self, %, rhs, "rem")
+impl Number {
+    pub fn number_type(&self) -> Result<Token, String> {
+        match self {
+            Felt(_) => Ok(Token::Felt),
+            I32(_) => Ok(Token::I32),
+            U32(_) => Ok(Token::U32),
+            U64(_) => Ok(Token::U64),
+            Bool(_) => Ok(Token::I32),
+            Nil => Err("nil has no type".to_string()),
+        }
     }
-}
 
-impl PartialEq for Number {
-    fn eq(&self, rhs: &Self) -> bool {
-        number_binop!(self, ==, rhs, "eq",cmp)
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Nil => "nil",
+            I32(_) => "i32",
+            U32(_) => "u32",
+            U64(_) => "u64",
+            Felt(_) => "felt",
+            Bool(_) => "bool",
+        }
     }
 
-    fn ne(&self, rhs: &Self) -> bool {
-        number_binop!(self, !=, rhs, "ne", cmp)
+    /// The common type of a binary operation, integer literals take the type of
+    /// the other operand and any other mix is an error.
+    pub fn binop_number_type(&self, rhs: &Number) -> Result<Token, String> {
+        match (self, rhs) {
+            (I32(_), I32(_)) => Ok(Token::I32),
+            (I32(_), U32(_)) | (U32(_), I32(_)) | (U32(_), U32(_)) => Ok(Token::U32),
+            (I32(_), U64(_)) | (U64(_), I32(_)) | (U64(_), U64(_)) => Ok(Token::U64),
+            (I32(_), Felt(_)) | (Felt(_), I32(_)) | (Felt(_), Felt(_)) => Ok(Token::Felt),
+            _ => Err(format!(
+                "type mismatch: {} and {}",
+                self.type_name(),
+                rhs.type_name()
+            )),
+        }
     }
-}
 
-impl PartialOrd for Number {
-    fn partial_cmp(&self, _other: &Self) -> Option<Ordering> {
-        todo!()
+    fn unify(self, rhs: Number) -> Result<(Number, Number), String> {
+        let token = self.binop_number_type(&rhs)?;
+        Ok((self.cast(&token)?, rhs.cast(&token)?))
     }
 
-    fn lt(&self, rhs: &Self) -> bool {
-        number_binop!(self, <, rhs, "lt", cmp)
+    fn to_felt(&self) -> Result<GoldilocksField, String> {
+        match self {
+            I32(value) => Ok(GoldilocksField::from_noncanonical_i64(*value as i64)),
+            U32(value) => Ok(GoldilocksField::from_canonical_u64(*value as u64)),
+            U64(value) if *value < GoldilocksField::ORDER => {
+                Ok(GoldilocksField::from_canonical_u64(*value))
+            }
+            Felt(value) => Ok(*value),
+            _ => Err(format!("{} can not convert to felt", self.to_string())),
+        }
     }
 
-    fn le(&self, rhs: &Self) -> bool {
-        number_binop!(self, <=, rhs, "le",  cmp)
+    fn to_u64(&self) -> Result<u64, String> {
+        match self {
+            I32(value) if *value >= 0 => Ok(*value as u64),
+            U32(value) => Ok(*value as u64),
+            U64(value) => Ok(*value),
+            Felt(value) => Ok(value.to_canonical_u64()),
+            _ => Err(format!("{} can not convert to u64", self.to_string())),
+        }
     }
 
-    fn gt(&self, rhs: &Self) -> bool {
-        number_binop!(self, >, rhs, "gt", cmp)
+    /// Converts to the type `token`, failing if the value does not fit.
+    pub fn cast(&self, token: &Token) -> Result<Number, String> {
+        let out_of_range = || format!("{} does not fit in {}", self.to_string(), token);
+        match token {
+            Token::Felt => self.to_felt().map(Felt),
+            Token::U64 => self.to_u64().map(U64),
+            Token::U32 => {
+                let value = self.to_u64()?;
+                u32::try_from(value).map(U32).map_err(|_| out_of_range())
+            }
+            Token::I32 => match self {
+                I32(value) => Ok(I32(*value)),
+                _ => {
+                    let value = self.to_u64()?;
+                    i32::try_from(value).map(I32).map_err(|_| out_of_range())
+                }
+            },
+            _ => Err(format!("can not convert {} to {}", self.to_string(), token)),
+        }
     }
 
-    fn ge(&self, rhs: &Self) -> bool {
-        number_binop!(self, >=, rhs, "ge", cmp)
+    fn is_zero(&self) -> bool {
+        match self {
+            I32(value) => *value == 0,
+            U32(value) => *value == 0,
+            U64(value) => *value == 0,
+            Felt(value) => *value == GoldilocksField::ZERO,
+            _ => false,
+        }
     }
-}
 
-impl From<i32> for Number {
-    fn from(num: i32) -> Self {
-        I32(num)
+    pub fn checked_add(self, rhs: Number) -> Result<Number, String> {
+        number_binop!(self, rhs, checked_add, felt_add, "add")
     }
-}
 
-impl From<u64> for Number {
-    fn from(num: u64) -> Self {
-        Felt(num as i128)
+    pub fn checked_sub(self, rhs: Number) -> Result<Number, String> {
+        number_binop!(self, rhs, checked_sub, felt_sub, "sub")
     }
-}
 
-impl From<&Token> for Number {
-    fn from(token: &Token) -> Self {
-        match token {
-            Token::I32 => I32(0),
-            Token::Felt => Felt(0),
-            Token::Array(token, len) => number_from_token(token, *len),
-            _ => panic!("not support token to Number:{}", token),
+    pub fn checked_mul(self, rhs: Number) -> Result<Number, String> {
+        number_binop!(self, rhs, checked_mul, felt_mul, "mul")
+    }
+
+    /// Integer division, on felts it divides the canonical values as the hints
+    /// emitted by the compiler expect. Field division is `a * inv(b)`.
+    pub fn checked_div(self, rhs: Number) -> Result<Number, String> {
+        if rhs.is_zero() {
+            return Err("divide by zero".to_string());
         }
+        number_binop!(self, rhs, checked_div, felt_div, "divide")
     }
-}
 
-pub fn number_from_token(token: &Token, len: usize) -> Number {
-    match token {
-        Token::Felt => Number::Felt(len as i128),
-        Token::I32 => Number::I32(len as i32),
-        _ => panic!("wrong type"),
+    pub fn checked_rem(self, rhs: Number) -> Result<Number, String> {
+        if rhs.is_zero() {
+            return Err("rem by zero".to_string());
+        }
+        number_binop!(self, rhs, checked_rem, felt_rem, "rem")
     }
-}
 
-impl Number {
-    pub fn number_type(&self) -> Token {
+    pub fn checked_neg(self) -> Result<Number, String> {
         match self {
-            Felt(_) => Token::Felt,
-            I32(_) => Token::I32,
-            Bool(_) => Token::I32,
-            Nil => panic!("wrong type"),
+            I32(value) => value
+                .checked_neg()
+                .map(I32)
+                .ok_or_else(|| "neg overflow".to_string()),
+            Felt(value) => Ok(Felt(-value)),
+            Bool(value) => Ok(Bool(!value)),
+            _ => Err(format!("neg not support {}", self.type_name())),
         }
     }
 
-    pub fn binop_number_type(&self, rhs: &Number) -> Token {
+    /// Bitwise and, on felts it works on the canonical values like `/`.
+    pub fn bit_and(self, rhs: Number) -> Result<Number, String> {
+        number_bitop!(self, rhs, &, "bit and")
+    }
+
+    pub fn bit_or(self, rhs: Number) -> Result<Number, String> {
+        number_bitop!(self, rhs, |, "bit or")
+    }
+
+    pub fn bit_xor(self, rhs: Number) -> Result<Number, String> {
+        number_bitop!(self, rhs, ^, "bit xor")
+    }
+
+    /// Bitwise not of an integer, logical not of a bool. A felt has no fixed
+    /// width and is rejected.
+    pub fn bit_not(self) -> Result<Number, String> {
         match self {
-            Felt(_) => match rhs {
-                I32(_) => Token::Felt,
-                Felt(_) => Token::Felt,
-                _ => panic!("felt op {:?} not support", rhs),
-            },
-            I32(_) => match rhs {
-                I32(_) => Token::I32,
-                Felt(_) => Token::Felt,
-                _ => panic!("i32 op {:?} not support", rhs),
-            },
-            Bool(_) => match rhs {
-                Bool(_) => Token::Felt,
-                _ => panic!("bool op {:?} not support", rhs),
-            },
-            Nil => panic!("Nil not support"),
+            I32(value) => Ok(I32(!value)),
+            U32(value) => Ok(U32(!value)),
+            U64(value) => Ok(U64(!value)),
+            Bool(value) => Ok(Bool(!value)),
+            _ => Err(format!("not not support {}", self.type_name())),
+        }
+    }
+
+    fn shift_amount(&self) -> Result<u32, String> {
+        match self {
+            Bool(_) | Nil => Err(format!("shift by {} not support", self.type_name())),
+            _ => u32::try_from(self.to_u64()?)
+                .map_err(|_| format!("shift by {} overflow", self.to_string())),
+        }
+    }
+
+    /// Shifts keep the type of the left operand. Integer types drop the bits
+    /// shifted out, a felt shifts its canonical value and must stay below the
+    /// field order.
+    pub fn checked_shl(self, rhs: Number) -> Result<Number, String> {
+        let shift = rhs.shift_amount()?;
+        match self {
+            I32(value) => value.checked_shl(shift).map(I32),
+            U32(value) => value.checked_shl(shift).map(U32),
+            U64(value) => value.checked_shl(shift).map(U64),
+            Felt(value) => {
+                let value = value.to_canonical_u64();
+                value
+                    .checked_shl(shift)
+                    .filter(|shifted| shifted >> shift == value)
+                    .filter(|shifted| *shifted < GoldilocksField::ORDER)
+                    .map(|shifted| Felt(GoldilocksField::from_canonical_u64(shifted)))
+            }
+            _ => return Err(format!("shl not support {}", self.type_name())),
         }
+        .ok_or_else(|| "shl overflow".to_string())
     }
 
-    pub fn get_number(&self) -> usize {
-        let value = match self {
-            Felt(num) => *num as usize,
-            I32(num) => *num as usize,
-            Bool(num) => *num as usize,
-            Nil => panic!("wrong type"),
-        };
-        value
+    pub fn checked_shr(self, rhs: Number) -> Result<Number, String> {
+        let shift = rhs.shift_amount()?;
+        match self {
+            I32(value) => value.checked_shr(shift).map(I32),
+            U32(value) => value.checked_shr(shift).map(U32),
+            U64(value) => value.checked_shr(shift).map(U64),
+            Felt(value) => value
+                .to_canonical_u64()
+                .checked_shr(shift)
+                .map(|shifted| Felt(GoldilocksField::from_canonical_u64(shifted))),
+            _ => return Err(format!("shr not support {}", self.type_name())),
+        }
+        .ok_or_else(|| "shr overflow".to_string())
     }
+
+    pub fn compare(&self, rhs: &Number) -> Result<Ordering, String> {
+        if let (Bool(left), Bool(right)) = (self, rhs) {
+            return Ok(left.cmp(right));
+        }
+        match self.clone().unify(rhs.clone())? {
+            (I32(left), I32(right)) => Ok(left.cmp(&right)),
+            (left, right) => Ok(left.to_u64()?.cmp(&right.to_u64()?)),
+        }
+    }
+
+    /// Field inverse, an integer literal is taken as a felt.
+    pub fn inverse(self) -> Result<Number, String> {
+        match self {
+            I32(_) | Felt(_) => self
+                .to_felt()?
+                .try_inverse()
+                .map(Felt)
+                .ok_or_else(|| "zero has no inverse".to_string()),
+            _ => Err(format!("inv not support {}", self.type_name())),
+        }
+    }
+
+    /// Integer square root, of the canonical value for felts.
+    pub fn sqrt(self) -> Result<Number, String> {
+        match self {
+            I32(value) if value >= 0 => Ok(I32(isqrt(value as u64) as i32)),
+            U32(value) => Ok(U32(isqrt(value as u64) as u32)),
+            U64(value) => Ok(U64(isqrt(value))),
+            Felt(value) => Ok(Felt(GoldilocksField::from_canonical_u64(isqrt(
+                value.to_canonical_u64(),
+            )))),
+            _ => Err(format!("sqrt not support {}", self.to_string())),
+        }
+    }
+
+    pub fn is_true(&self) -> Result<bool, String> {
+        match self {
+            Bool(value) => Ok(*value),
+            Nil => Err("nil is not a condition".to_string()),
+            _ => Ok(!self.is_zero()),
+        }
+    }
+
+    /// The value as written to vm memory, the canonical value of its felt.
+    pub fn get_number(&self) -> Result<usize, String> {
+        match self {
+            Bool(value) => Ok(*value as usize),
+            _ => Ok(self.to_felt()?.to_canonical_u64() as usize),
+        }
+    }
+}
+
+fn felt_add(left: GoldilocksField, right: GoldilocksField) -> Option<GoldilocksField> {
+    Some(left + right)
+}
+
+fn felt_sub(left: GoldilocksField, right: GoldilocksField) -> Option<GoldilocksField> {
+    Some(left - right)
+}
+
+fn felt_mul(left: GoldilocksField, right: GoldilocksField) -> Option<GoldilocksField> {
+    Some(left * right)
+}
+
+fn felt_div(left: GoldilocksField, right: GoldilocksField) -> Option<GoldilocksField> {
+    left.to_canonical_u64()
+        .checked_div(right.to_canonical_u64())
+        .map(GoldilocksField::from_canonical_u64)
+}
+
+fn felt_rem(left: GoldilocksField, right: GoldilocksField) -> Option<GoldilocksField> {
+    left.to_canonical_u64()
+        .checked_rem(right.to_canonical_u64())
+        .map(GoldilocksField::from_canonical_u64)
 }
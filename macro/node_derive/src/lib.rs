@@ -27,13 +27,16 @@ pub fn node_derive(input: TokenStream) -> TokenStream {
         "CompoundNode" => quote!(travel.travel_compound(self)),
         "CondStatNode" => quote!(travel.travel_cond(self)),
         "LoopStatNode" => quote!(travel.travel_loop(self)),
+        "ForLoopNode" => quote!(travel.travel_for_loop(self)),
         "EntryNode" => quote!(travel.travel_entry(self)),
         "FunctionNode" => quote!(travel.travel_function(self)),
         "CallNode" => quote!(travel.travel_call(self)),
         "SqrtNode" => quote!(travel.travel_sqrt(self)),
+        "InvNode" => quote!(travel.travel_inv(self)),
         "ReturnNode" => quote!(travel.travel_return(self)),
         "MultiAssignNode" => quote!(travel.travel_multi_assign(self)),
         "MallocNode" => quote!(travel.travel_malloc(self)),
+        "ReadMemNode" => quote!(travel.travel_read_mem(self)),
         "PrintfNode" => quote!(travel.travel_printf(self)),
         _ => panic!(""),
     };
@@ -10,7 +10,6 @@ use std::{
 
 use clap::Parser;
 use ethereum_types::H256;
-use executor::BatchCacheManager;
 use ola_lang_abi::{Abi, Param, Value};
 use plonky2::hash::utils::bytes_to_u64s;
 
@@ -121,13 +120,11 @@ impl Call {
             tx_init_info,
         );
         let exec_res = vm.execute_tx(
-            to.map(|n| GoldilocksField::from_canonical_u64(n)),
             to.map(|n| GoldilocksField::from_canonical_u64(n)),
             calldata
                 .iter()
                 .map(|n| GoldilocksField::from_canonical_u64(*n))
                 .collect(),
-            &mut BatchCacheManager::default(),
             false,
         );
 
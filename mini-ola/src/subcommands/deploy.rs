@@ -1,5 +1,5 @@
 use core::{
-    program::binary_program::BinaryProgram,
+    program::{binary_program::BinaryProgram, verifier::verify_program},
     state::utils::get_prog_hash_cf_key_from_contract_addr,
     storage::db::{Database, RocksDB, SequencerColumnFamily},
     types::{storage::u8_arr_to_field_arr, Field, GoldilocksField},
@@ -30,7 +30,8 @@ pub struct Deploy {
 impl Deploy {
     pub fn run(self) -> anyhow::Result<()> {
         let program: BinaryProgram = serde_json::from_reader(File::open(self.contract)?)?;
-        let program_bytes = bincode::serialize(&program)?;
+        verify_program(&program)?;
+        let program_bytes = program.to_bytes()?;
         let program_hash = poseidon_hash_bytes(program_bytes.as_ref()).to_vec();
 
         let target_address: [u8; 32] = if let Some(addr) = self.address {
@@ -1,8 +1,5 @@
 use core::{
-    crypto::poseidon_trace::calculate_arbitrary_poseidon,
-    merkle_tree::log::StorageLogKind,
-    storage::db::SequencerColumnFamily,
-    types::{merkle_tree::tree_key_to_u8_arr, Field, GoldilocksField},
+    types::{Field, GoldilocksField},
     vm::transaction::TxCtxInfo,
 };
 use std::{
@@ -13,10 +10,8 @@ use std::{
 
 use clap::Parser;
 use ethereum_types::H256;
-use executor::BatchCacheManager;
 use ola_lang_abi::{Abi, Param, Value};
 use plonky2::hash::utils::bytes_to_u64s;
-use rocksdb::WriteBatch;
 
 use crate::utils::{
     address_from_hex_be, h256_to_u64_array, ExpandedPathbufParser, OLA_RAW_TX_TYPE,
@@ -127,44 +122,17 @@ impl Invoke {
             tx_init_info,
         );
         let exec_res = vm.execute_tx(
-            to.map(|n| GoldilocksField::from_canonical_u64(n)),
             to.map(|n| GoldilocksField::from_canonical_u64(n)),
             calldata
                 .iter()
                 .map(|n| GoldilocksField::from_canonical_u64(*n))
                 .collect(),
-            &mut BatchCacheManager::default(),
             false,
         );
 
-        match exec_res {
-            Ok(_) => {
-                let mut batch = WriteBatch::default();
-                vm.ola_state.storage_queries.iter().for_each(|q| {
-                    if q.kind != StorageLogKind::Read {
-                        let mut tree_key = Vec::new();
-                        tree_key.extend_from_slice(&q.contract_addr);
-                        tree_key.extend_from_slice(&q.storage_key);
-                        let tree_key = calculate_arbitrary_poseidon(&tree_key);
-                        let key = tree_key_to_u8_arr(&tree_key);
-                        let cf = vm
-                            .ola_state
-                            .state_storage
-                            .db
-                            .cf_sequencer_handle(SequencerColumnFamily::State);
-                        let value = tree_key_to_u8_arr(&q.value);
-                        batch.put_cf(cf, &key, &value);
-                    }
-                });
-                let db_write = vm.ola_state.state_storage.db.write(batch);
-                match db_write {
-                    Ok(_) => println!("Tx execute success."),
-                    Err(e) => eprintln!("Invoke TX Error: {}", e),
-                }
-            }
-            Err(e) => {
-                eprintln!("Invoke TX Error: {}", e)
-            }
+        match exec_res.and_then(|_| vm.commit_storage_changes()) {
+            Ok(_) => println!("Tx execute success."),
+            Err(e) => eprintln!("Invoke TX Error: {}", e),
         }
         Ok(())
     }
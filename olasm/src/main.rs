@@ -1,6 +1,8 @@
 use clap::{CommandFactory, Parser, Subcommand};
 use colored::Colorize;
-use subcommands::{call::Call, deploy::Deploy, deploy_sys::DeploySys, invoke::Invoke};
+use subcommands::{
+    call::Call, debug::Debug, deploy::Deploy, deploy_sys::DeploySys, invoke::Invoke,
+};
 
 mod subcommands;
 mod utils;
@@ -24,6 +26,8 @@ enum Subcommands {
     Invoke(Invoke),
     #[clap(about = "Make a state query.")]
     Call(Call),
+    #[clap(about = "Step through a transaction interactively.")]
+    Debug(Debug),
 }
 
 fn main() {
@@ -45,6 +49,7 @@ fn run_command(cli: Cli) -> anyhow::Result<()> {
             Subcommands::Deploy(cmd) => cmd.run(),
             Subcommands::Invoke(cmd) => cmd.run(),
             Subcommands::Call(cmd) => cmd.run(),
+            Subcommands::Debug(cmd) => cmd.run(),
         },
     }
 }
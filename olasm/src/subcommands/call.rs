@@ -115,6 +115,7 @@ impl Call {
             signature_r: None,
             signature_s: None,
             tx_hash: None,
+            gas_limit: None,
         };
 
         let mut tx_exe_manager: TxExeManager =
@@ -0,0 +1,135 @@
+use std::path::PathBuf;
+
+use assembler::encoder::encode_asm_from_json_file;
+use clap::Parser;
+use executor::{
+    config::ExecuteMode,
+    debugger::{OlaBreakpoint, OlaDebugger},
+    ola_storage::OlaCachedStorage,
+    tx_exe_manager::{OlaTapeInitInfo, TxExeManager},
+};
+
+use crate::utils::ExpandedPathbufParser;
+
+use super::invoke::TxRequest;
+
+#[derive(Debug, Parser)]
+pub struct Debug {
+    #[clap(long, help = "Wether use system contract as entrance.")]
+    sys: bool,
+    #[clap(long, help = "Path of rocksdb database")]
+    db: Option<PathBuf>,
+    #[clap(long, help = "Caller Address")]
+    caller: Option<String>,
+    #[clap(long, help = "Provide transaction nonce manually")]
+    nonce: Option<u64>,
+    #[clap(long, help = "Provide block number manually")]
+    block: Option<u64>,
+    #[clap(long, help = "Provide second timestamp manually")]
+    timestamp: Option<u64>,
+    #[clap(long, help = "Provide transaction gas limit manually")]
+    gas_limit: Option<u64>,
+    #[clap(
+        long,
+        value_parser = ExpandedPathbufParser,
+        help = "Path to the asm json of the called contract, for contracts deployed without debug info"
+    )]
+    asm: Option<PathBuf>,
+    #[clap(
+        long = "break",
+        short = 'b',
+        help = "Breakpoint by pc or label in the called contract, can be repeated"
+    )]
+    breakpoints: Vec<String>,
+    #[clap(
+        long,
+        help = "Run until the first breakpoint instead of pausing at the first step"
+    )]
+    run: bool,
+    #[clap(
+        value_parser = ExpandedPathbufParser,
+        help = "Path to the JSON keystore"
+    )]
+    abi: PathBuf,
+    #[clap(help = "One or more contract calls. See documentation for more details")]
+    calls: Vec<String>,
+}
+
+impl Debug {
+    pub fn run(self) -> anyhow::Result<()> {
+        let TxRequest {
+            caller,
+            block_info,
+            db_path,
+            to,
+            entry_contract,
+            calldata,
+        } = TxRequest::parse(
+            self.sys,
+            self.db,
+            self.caller,
+            self.block,
+            self.timestamp,
+            &self.abi,
+            self.calls,
+        )?;
+        let mut storage = OlaCachedStorage::new(db_path, Some(block_info.block_timestamp))?;
+
+        let mut debugger = OlaDebugger::stdio();
+        let debug_info = match self.asm {
+            Some(asm) => {
+                encode_asm_from_json_file(asm.display().to_string())
+                    .map_err(|e| anyhow::anyhow!("encode asm error: {}", e))?
+                    .debug_info
+            }
+            None => storage.get_program(to)?.debug_info,
+        };
+        if let Some(debug_info) = debug_info {
+            debugger.add_debug_info(to, debug_info);
+        }
+        for breakpoint in self.breakpoints {
+            let pc = match breakpoint.parse::<u64>() {
+                Ok(pc) => pc,
+                Err(_) => debugger
+                    .label_pc(to, breakpoint.as_str())
+                    .ok_or_else(|| anyhow::anyhow!("unknown label: {}", breakpoint))?,
+            };
+            debugger.add_breakpoint(OlaBreakpoint {
+                code_addr: Some(to),
+                pc,
+            });
+        }
+        if self.run {
+            debugger.start_running();
+        }
+
+        let tx = OlaTapeInitInfo {
+            version: 0,
+            origin_address: caller,
+            calldata,
+            nonce: self.nonce,
+            signature_r: None,
+            signature_s: None,
+            tx_hash: None,
+            gas_limit: self.gas_limit,
+        };
+
+        let mut tx_exe_manager: TxExeManager = TxExeManager::new(
+            ExecuteMode::Debug,
+            block_info,
+            tx,
+            &mut storage,
+            entry_contract,
+            0,
+        );
+        tx_exe_manager.set_debugger(debugger);
+        let result = tx_exe_manager.invoke()?;
+        println!(
+            "============ Debug session finished with {} storage changes, {} events, {} gas used, nothing saved ============",
+            storage.get_cached_tx_modification().len(),
+            result.events.len(),
+            result.gas_used
+        );
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use core::{
 };
 use std::{
     fs::File,
-    path::PathBuf,
+    path::{Path, PathBuf},
     time::{SystemTime, UNIX_EPOCH},
 };
 
@@ -14,9 +14,10 @@ use executor::{
     batch_exe_manager::BlockExeInfo,
     config::{ExecuteMode, ADDR_U64_ENTRYPOINT},
     ola_storage::{DiskStorageWriter, OlaCachedStorage},
+    profiler::OlaProfiler,
     tx_exe_manager::{OlaTapeInitInfo, TxExeManager},
 };
-use ola_lang_abi::{Abi, FixedArray4, Param, Value};
+use ola_lang_abi::{Abi, FixedArray4, Value};
 
 use crate::utils::{address_from_hex_be, h256_to_u64_array, ExpandedPathbufParser};
 
@@ -36,6 +37,20 @@ pub struct Invoke {
     block: Option<u64>,
     #[clap(long, help = "Provide second timestamp manually")]
     timestamp: Option<u64>,
+    #[clap(long, help = "Provide transaction gas limit manually")]
+    gas_limit: Option<u64>,
+    #[clap(
+        long,
+        value_parser = ExpandedPathbufParser,
+        help = "Profile the transaction, writing <PROFILE>.folded and <PROFILE>.json"
+    )]
+    profile: Option<PathBuf>,
+    #[clap(
+        long,
+        value_parser = ExpandedPathbufParser,
+        help = "Write the execution trace of the transaction to a JSON file"
+    )]
+    trace: Option<PathBuf>,
     #[clap(
         value_parser = ExpandedPathbufParser,
         help = "Path to the JSON keystore"
@@ -47,99 +62,66 @@ pub struct Invoke {
 
 impl Invoke {
     pub fn run(self) -> anyhow::Result<()> {
-        let caller_address: [u64; 4] = if let Some(addr) = self.caller {
-            let bytes = address_from_hex_be(addr.as_str()).unwrap();
-            let caller_vec = bytes_to_u64s(bytes.to_vec());
-            let mut caller = [0u64; 4];
-            caller.clone_from_slice(&caller_vec[..4]);
-            caller
-        } else {
-            h256_to_u64_array(&H256::random())
-        };
-
-        let block_number = if let Some(n) = self.block { n } else { 0 };
-        let block_timestamp = if let Some(n) = self.timestamp {
-            n
-        } else {
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs()
-        };
-        let db_home = match self.db {
-            Some(path) => path,
-            None => PathBuf::from("./db"),
-        };
-
-        let db_path_string = db_home.as_path().to_str().unwrap().to_string();
-        let writer = DiskStorageWriter::new(db_path_string.clone())?;
-        let mut storage = OlaCachedStorage::new(db_path_string, Some(block_timestamp))?;
-        let block_info = BlockExeInfo {
-            block_number,
-            block_timestamp,
-            sequencer_address: [1001, 1002, 1003, 1004],
-            chain_id: 1027,
-        };
-
-        let mut arg_iter = self.calls.into_iter();
-        let contract_address_hex = arg_iter.next().expect("contract address needed");
-        let contract_address_bytes = address_from_hex_be(contract_address_hex.as_str()).unwrap();
-        let to_vec = bytes_to_u64s(contract_address_bytes.to_vec());
-        let mut to = [0u64; 4];
-        to.clone_from_slice(&to_vec[..4]);
-
-        let abi_file = File::open(self.abi).expect("failed to open ABI file");
-        let function_sig_name = arg_iter.next().expect("function signature needed");
-        let abi: Abi = serde_json::from_reader(abi_file)?;
-        let func = abi
-            .functions
-            .iter()
-            .find(|func| func.name == function_sig_name)
-            .expect("function not found");
-        let func_inputs = &func.inputs;
-        if arg_iter.len() != func_inputs.len() {
-            anyhow::bail!(
-                "invalid args length: {} args expected, you input {}",
-                func_inputs.len(),
-                arg_iter.len()
-            )
-        }
-        let param_to_input: Vec<(&Param, String)> =
-            func_inputs.into_iter().zip(arg_iter.into_iter()).collect();
-        let params: Vec<Value> = param_to_input
-            .iter()
-            .map(|(p, i)| ToValue::parse_input((**p).clone(), i.clone()))
-            .collect();
-        let biz_calldata = abi
-            .encode_input_with_signature(func.signature().as_str(), params.as_slice())
-            .unwrap();
-        let calldata = if self.sys {
-            build_entry_point_calldata(caller_address, to, biz_calldata, None)?
-        } else {
-            biz_calldata
-        };
-        let entry_contract = if self.sys { ADDR_U64_ENTRYPOINT } else { to };
+        let TxRequest {
+            caller,
+            block_info,
+            db_path,
+            to: _,
+            entry_contract,
+            calldata,
+        } = TxRequest::parse(
+            self.sys,
+            self.db,
+            self.caller,
+            self.block,
+            self.timestamp,
+            &self.abi,
+            self.calls,
+        )?;
+        let writer = DiskStorageWriter::new(db_path.clone())?;
+        let mut storage = OlaCachedStorage::new(db_path, Some(block_info.block_timestamp))?;
 
         // todo: signature.
         let tx = OlaTapeInitInfo {
             version: 0,
-            origin_address: caller_address,
+            origin_address: caller,
             calldata,
             nonce: self.nonce,
             signature_r: None,
             signature_s: None,
             tx_hash: None,
+            gas_limit: self.gas_limit,
         };
 
-        let mut tx_exe_manager: TxExeManager = TxExeManager::new(
-            ExecuteMode::Debug,
-            block_info,
-            tx,
-            &mut storage,
-            entry_contract,
-            0,
-        );
+        // the execution trace is only recorded in invoke mode.
+        let mode = if self.trace.is_some() {
+            ExecuteMode::Invoke
+        } else {
+            ExecuteMode::Debug
+        };
+        let mut tx_exe_manager: TxExeManager =
+            TxExeManager::new(mode, block_info, tx, &mut storage, entry_contract, 0);
+        if self.profile.is_some() {
+            tx_exe_manager.set_profiler(OlaProfiler::new());
+        }
         let result = tx_exe_manager.invoke()?;
+        if let (Some(path), Some(profiler)) = (self.profile, tx_exe_manager.take_profiler()) {
+            let folded = path.with_extension("folded");
+            let summary = path.with_extension("json");
+            profiler.write_folded_stacks(folded.as_path())?;
+            profiler.write_summary(summary.as_path())?;
+            println!(
+                "Profile written to {} and {}, {} cycles in total",
+                folded.display(),
+                summary.display(),
+                profiler.total().cycles
+            );
+        }
+        if let Some(path) = self.trace {
+            let file = File::create(path.as_path())?;
+            serde_json::to_writer(file, &result.trace)?;
+            println!("Trace written to {}", path.display());
+        }
         storage.on_tx_success();
         let cached = storage.get_cached_modification();
         for (key, value) in cached.clone() {
@@ -149,8 +131,8 @@ impl Invoke {
         let storage_change_size = cached.len();
         let event_size = result.events.len();
         println!(
-            "============ Invoke success with {} storage changes, {} events ============",
-            storage_change_size, event_size
+            "============ Invoke success with {} storage changes, {} events, {} gas used ============",
+            storage_change_size, event_size, result.gas_used
         );
         if storage_change_size > 0 {
             println!("Storage changes:");
@@ -168,7 +150,109 @@ impl Invoke {
     }
 }
 
-fn build_entry_point_calldata(
+/// The transaction `invoke` and `debug` run: who sends it, in which block,
+/// to which contract and with which calldata.
+pub(crate) struct TxRequest {
+    pub caller: ContractAddress,
+    pub block_info: BlockExeInfo,
+    pub db_path: String,
+    /// The called contract.
+    pub to: ContractAddress,
+    /// The contract the tx enters, the entrypoint for system calls.
+    pub entry_contract: ContractAddress,
+    pub calldata: Vec<u64>,
+}
+
+impl TxRequest {
+    /// `calls` is the contract address, the function name and its arguments.
+    pub fn parse(
+        sys: bool,
+        db: Option<PathBuf>,
+        caller: Option<String>,
+        block: Option<u64>,
+        timestamp: Option<u64>,
+        abi: &Path,
+        calls: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        let caller = match caller {
+            Some(addr) => parse_address(addr.as_str())?,
+            None => h256_to_u64_array(&H256::random()),
+        };
+        let block_timestamp = match timestamp {
+            Some(timestamp) => timestamp,
+            None => SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+        let block_info = BlockExeInfo {
+            block_number: block.unwrap_or(0),
+            block_timestamp,
+            sequencer_address: [1001, 1002, 1003, 1004],
+            chain_id: 1027,
+        };
+        let db_path = db
+            .unwrap_or_else(|| PathBuf::from("./db"))
+            .to_str()
+            .ok_or_else(|| anyhow::anyhow!("db path is not valid unicode"))?
+            .to_string();
+
+        let mut arg_iter = calls.into_iter();
+        let to = parse_address(
+            arg_iter
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("contract address needed"))?
+                .as_str(),
+        )?;
+        let function_name = arg_iter
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("function signature needed"))?;
+        let abi_file = File::open(abi)
+            .map_err(|e| anyhow::anyhow!("failed to open ABI file {}: {}", abi.display(), e))?;
+        let abi: Abi = serde_json::from_reader(abi_file)?;
+        let func = abi
+            .functions
+            .iter()
+            .find(|func| func.name == function_name)
+            .ok_or_else(|| anyhow::anyhow!("function not found: {}", function_name))?;
+        if arg_iter.len() != func.inputs.len() {
+            anyhow::bail!(
+                "invalid args length: {} args expected, you input {}",
+                func.inputs.len(),
+                arg_iter.len()
+            )
+        }
+        let params: Vec<Value> = func
+            .inputs
+            .iter()
+            .zip(arg_iter)
+            .map(|(param, input)| ToValue::parse_input(param.clone(), input))
+            .collect();
+        let biz_calldata = abi
+            .encode_input_with_signature(func.signature().as_str(), params.as_slice())
+            .map_err(|e| anyhow::anyhow!("failed to encode calldata: {:?}", e))?;
+        let (entry_contract, calldata) = if sys {
+            let calldata = build_entry_point_calldata(caller, to, biz_calldata, None)?;
+            (ADDR_U64_ENTRYPOINT, calldata)
+        } else {
+            (to, biz_calldata)
+        };
+        Ok(Self {
+            caller,
+            block_info,
+            db_path,
+            to,
+            entry_contract,
+            calldata,
+        })
+    }
+}
+
+fn parse_address(hex: &str) -> anyhow::Result<ContractAddress> {
+    let bytes = address_from_hex_be(hex)?;
+    let mut address = [0u64; 4];
+    address.clone_from_slice(&bytes_to_u64s(bytes.to_vec())[..4]);
+    Ok(address)
+}
+
+pub(crate) fn build_entry_point_calldata(
     from: ContractAddress,
     to: ContractAddress,
     biz_calldata: Vec<u64>,
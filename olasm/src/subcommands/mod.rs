@@ -1,4 +1,5 @@
 pub mod call;
+pub mod debug;
 pub mod deploy;
 pub mod deploy_sys;
 pub mod invoke;
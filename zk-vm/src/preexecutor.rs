@@ -1,6 +1,5 @@
 use std::path::PathBuf;
 
-use executor::BatchCacheManager;
 use ola_core::{
     state::error::StateError,
     types::{Field, GoldilocksField},
@@ -47,13 +46,7 @@ impl PreExecutor {
 
         let entry_point_addr =
             ENTRY_POINT_ADDRESS.map(|fe| GoldilocksField::from_canonical_u64(fe));
-        let exec_res = vm.execute_tx(
-            entry_point_addr,
-            entry_point_addr,
-            tx_info.get_calldata(),
-            &mut BatchCacheManager::default(),
-            true,
-        );
+        let exec_res = vm.execute_tx(entry_point_addr, tx_info.get_calldata(), true);
 
         match exec_res {
             Ok(_) => Ok(()),
@@ -3,7 +3,6 @@ pub mod tests {
     use crate::config::ENTRY_POINT_ADDRESS;
     use crate::OlaVM;
 
-    use executor::trace::gen_storage_hash_table;
     use ola_core::types::merkle_tree::TreeValue;
     use ola_core::types::Field;
     use ola_core::types::GoldilocksField;
@@ -76,12 +75,12 @@ pub mod tests {
             GoldilocksField::from_canonical_u64(3965482278),
         ];
 
-        let res = node.execute_tx(caller_address, caller_exe_address, calldata, false);
+        let res = node.execute_tx(caller_exe_address, calldata, false);
 
         if res.is_ok() {
             println!("run tx success:{:?}", res);
-            let tx_trace = node.ola_state.gen_tx_trace();
-            let trace_json_format = serde_json::to_string(&tx_trace).unwrap();
+            let tx_trace = &node.tx_result.as_ref().unwrap().trace;
+            let trace_json_format = serde_json::to_string(tx_trace).unwrap();
             let mut file = File::create(format!("sccall.txt")).unwrap();
             file.write_all(trace_json_format.as_ref()).unwrap();
         } else {
@@ -100,7 +99,6 @@ pub mod tests {
         let state_db_path = state_db.as_path();
         let mut vm = OlaVM::new(tree_db_path, state_db_path, mock_tx_info());
 
-        let caller = [1, 2, 3, 4].map(|v| GoldilocksField::from_canonical_u64(v));
         let contract_address =
             ENTRY_POINT_ADDRESS.map(|fe| GoldilocksField::from_canonical_u64(fe));
         let calldata = [
@@ -124,7 +122,7 @@ pub mod tests {
         .into_iter()
         .map(|v| GoldilocksField::from_canonical_u64(v))
         .collect();
-        let res = vm.execute_tx(caller, contract_addr, calldata, false);
+        let res = vm.execute_tx(contract_address, calldata, false);
         match res {
             Ok(_) => println!("OK"),
             Err(e) => eprint!("err: {}", e),
@@ -1,20 +1,13 @@
-use std::{mem, path::PathBuf};
-
-use executor::BatchCacheManager;
+use executor::{batch_exe_manager::BlockExeManager, tx_exe_manager::OlaTapeInitInfo};
 use ola_core::{
-    merkle_tree::log::StorageQuery,
     state::error::StateError,
-    trace::trace::Trace,
+    tx::{BatchResult, TxResult},
     types::{
-        merkle_tree::{u8_arr_to_tree_key, TreeValue},
-        storage::u8_arr_to_field_arr,
-        Field, GoldilocksField,
+        merkle_tree::u8_arr_to_tree_key, storage::u8_arr_to_field_arr, Field, GoldilocksField,
+        PrimeField64,
     },
-    vm::transaction::TxCtxInfo,
 };
 
-use crate::{config::ENTRY_POINT_ADDRESS, OlaVM};
-
 pub struct BlockInfo {
     pub block_number: u32,
     pub block_timestamp: u64,
@@ -69,6 +62,19 @@ impl CallInfo {
     fn get_calldata(&self) -> Vec<GoldilocksField> {
         u8_arr_to_field_arr(&self.calldata)
     }
+
+    fn to_tape_init_info(&self) -> OlaTapeInitInfo {
+        OlaTapeInitInfo {
+            version: self.version as u64,
+            origin_address: to_u64_arr(self.get_caller_address()),
+            calldata: to_u64_vec(self.get_calldata()),
+            nonce: None,
+            signature_r: None,
+            signature_s: None,
+            tx_hash: None,
+            gas_limit: None,
+        }
+    }
 }
 
 impl TxInfo {
@@ -93,174 +99,97 @@ impl TxInfo {
     pub fn get_tx_hash(&self) -> [GoldilocksField; 4] {
         u8_arr_to_tree_key(&self.tx_hash.to_vec())
     }
+
+    fn to_tape_init_info(&self) -> OlaTapeInitInfo {
+        OlaTapeInitInfo {
+            version: self.version as u64,
+            origin_address: to_u64_arr(self.get_caller_address()),
+            calldata: to_u64_vec(self.get_calldata()),
+            nonce: Some(self.nonce as u64),
+            signature_r: Some(to_u64_arr(self.get_signature_r())),
+            signature_s: Some(to_u64_arr(self.get_signature_s())),
+            tx_hash: Some(to_u64_arr(self.get_tx_hash())),
+            gas_limit: None,
+        }
+    }
+}
+
+pub(crate) fn to_u64_arr(fields: [GoldilocksField; 4]) -> [u64; 4] {
+    fields.map(|f| f.to_canonical_u64())
 }
 
-pub struct InvokeResult {
-    pub trace: Trace,
-    pub storage_queries: Vec<StorageQuery>,
+fn to_u64_vec(fields: Vec<GoldilocksField>) -> Vec<u64> {
+    fields.iter().map(|f| f.to_canonical_u64()).collect()
 }
 
-pub struct FinishBatchResult {
-    pub trace: Trace,
-    pub storage_queries: Vec<StorageQuery>,
-    pub block_tip_queries: Vec<StorageQuery>,
+pub(crate) fn exec_error(e: anyhow::Error) -> StateError {
+    StateError::VmExecError(e.to_string())
 }
 
+/// Executes the transactions of one batch. The storage is opened once, when
+/// the manager is created, and its cache lives until `finish_batch`, so the
+/// transactions of a batch read each other's writes without going back to the
+/// database.
 pub struct VmManager {
-    tree_db_path: String,
-    state_db_path: String,
-    block_info: BlockInfo,
-    storage_queries: Vec<StorageQuery>,
-    cache_manager: BatchCacheManager,
+    block_exe_manager: BlockExeManager,
     is_alive: bool,
 }
 
 impl VmManager {
-    pub fn new(block_info: BlockInfo, tree_db_path: String, state_db_path: String) -> Self {
-        Self {
-            tree_db_path,
-            state_db_path,
-            block_info,
-            storage_queries: vec![],
-            cache_manager: BatchCacheManager::default(),
+    /// Opens the storage at `storage_db_path` for the whole batch.
+    ///
+    /// Unlike the former `new(block_info, tree_db_path, state_db_path)`, the
+    /// merkle tree database is not opened by the VM any more, and opening the
+    /// storage can fail.
+    pub fn new(block_info: BlockInfo, storage_db_path: String) -> Result<Self, StateError> {
+        let block_exe_manager = BlockExeManager::new(
+            storage_db_path,
+            block_info.chain_id as u64,
+            block_info.block_number as u64,
+            block_info.block_timestamp,
+            to_u64_arr(block_info.get_sequencer_address()),
+        )
+        .map_err(exec_error)?;
+        Ok(Self {
+            block_exe_manager,
             is_alive: true,
-        }
+        })
     }
 
     pub fn call(&mut self, call_info: CallInfo) -> Result<Vec<u64>, StateError> {
         if !self.is_alive {
             return Err(StateError::CalledAfterBatchFinished);
         }
-        let tx_init_info = TxCtxInfo {
-            block_number: self.block_info.get_block_number(),
-            block_timestamp: self.block_info.get_timestamp(),
-            sequencer_address: self.block_info.get_sequencer_address(),
-            version: call_info.get_version(),
-            chain_id: self.block_info.get_chain_id(),
-            caller_address: call_info.get_caller_address(),
-            nonce: GoldilocksField::ZERO,
-            signature_r: [GoldilocksField::ZERO; 4],
-            signature_s: [GoldilocksField::ZERO; 4],
-            tx_hash: [GoldilocksField::ZERO; 4],
-        };
-        let tree_db_path_buf: PathBuf = self.tree_db_path.clone().into();
-        let state_db_path_buf: PathBuf = self.state_db_path.clone().into();
-        let mut vm = OlaVM::new_call(
-            tree_db_path_buf.as_path(),
-            state_db_path_buf.as_path(),
-            tx_init_info,
-        );
-        let exec_res = vm.execute_tx(
-            call_info.get_to_address(),
-            call_info.get_to_address(),
-            call_info.get_calldata(),
-            &mut self.cache_manager,
-            false,
-        );
-        match exec_res {
-            Ok(_) => {
-                let return_data = vm.ola_state.return_data.iter().map(|f| f.0).collect();
-                Ok(return_data)
-            }
-            Err(e) => Err(e),
-        }
-    }
-
-    pub fn invoke(&mut self, tx_info: TxInfo) -> Result<InvokeResult, StateError> {
+        self.block_exe_manager
+            .call(
+                call_info.to_tape_init_info(),
+                to_u64_arr(call_info.get_to_address()),
+            )
+            .map_err(exec_error)
+    }
+
+    /// Runs a transaction of the batch. The result carries the
+    /// `TxExeTrace` of the new executor and its storage access logs, where
+    /// the former `InvokeResult` carried a legacy `Trace` and storage queries.
+    pub fn invoke(&mut self, tx_info: TxInfo) -> Result<TxResult, StateError> {
         if !self.is_alive {
             return Err(StateError::VMNotAvaliable);
         }
-        let tx_init_info = TxCtxInfo {
-            block_number: self.block_info.get_block_number(),
-            block_timestamp: self.block_info.get_timestamp(),
-            sequencer_address: self.block_info.get_sequencer_address(),
-            version: tx_info.get_version(),
-            chain_id: self.block_info.get_chain_id(),
-            caller_address: tx_info.get_caller_address(),
-            nonce: tx_info.get_nonce(),
-            signature_r: tx_info.get_signature_r(),
-            signature_s: tx_info.get_signature_s(),
-            tx_hash: tx_info.get_tx_hash(),
-        };
-        let tree_db_path_buf: PathBuf = self.tree_db_path.clone().into();
-        let state_db_path_buf: PathBuf = self.state_db_path.clone().into();
-        let mut vm = OlaVM::new(
-            tree_db_path_buf.as_path(),
-            state_db_path_buf.as_path(),
-            tx_init_info,
-        );
-
-        let entry_point_addr =
-            ENTRY_POINT_ADDRESS.map(|fe| GoldilocksField::from_canonical_u64(fe));
-        let exec_res = vm.execute_tx(
-            entry_point_addr,
-            entry_point_addr,
-            tx_info.get_calldata(),
-            &mut self.cache_manager,
-            false,
-        );
-
-        let to_append = vm.ola_state.storage_queries.clone();
-        self.storage_queries.extend(to_append);
-
-        match exec_res {
-            Ok(_) => Ok(InvokeResult {
-                trace: vm.ola_state.gen_tx_trace(),
-                storage_queries: vm.ola_state.storage_queries,
-            }),
-            Err(e) => Err(e),
-        }
+        self.block_exe_manager
+            .invoke(tx_info.to_tape_init_info())
+            .map_err(exec_error)
     }
 
-    pub fn finish_batch(&mut self) -> Result<FinishBatchResult, StateError> {
-        let tx_init_info = TxCtxInfo {
-            block_number: self.block_info.get_block_number(),
-            block_timestamp: self.block_info.get_timestamp(),
-            sequencer_address: self.block_info.get_sequencer_address(),
-            version: GoldilocksField::ZERO,
-            chain_id: self.block_info.get_chain_id(),
-            caller_address: self.block_info.get_sequencer_address(),
-            nonce: GoldilocksField::ZERO,
-            signature_r: TreeValue::default(),
-            signature_s: TreeValue::default(),
-            tx_hash: TreeValue::default(),
-        };
-        let tree_db_path_buf: PathBuf = self.tree_db_path.clone().into();
-        let state_db_path_buf: PathBuf = self.state_db_path.clone().into();
-        let mut vm = OlaVM::new(
-            tree_db_path_buf.as_path(),
-            state_db_path_buf.as_path(),
-            tx_init_info,
-        );
-
-        let entry_point_addr =
-            ENTRY_POINT_ADDRESS.map(|fe| GoldilocksField::from_canonical_u64(fe));
-        let calldata = [self.block_info.block_number as u64, 1, 2190639505]
-            .iter()
-            .map(|l| GoldilocksField::from_canonical_u64(*l))
-            .collect();
-
-        let exec_res = vm.execute_tx(
-            entry_point_addr,
-            entry_point_addr,
-            calldata,
-            &mut self.cache_manager,
-            false,
-        );
-
-        let to_append = vm.ola_state.storage_queries.clone();
-        self.storage_queries.extend(to_append);
-
-        match exec_res {
-            Ok(_) => {
-                self.is_alive = false;
-                Ok(FinishBatchResult {
-                    trace: vm.ola_state.gen_tx_trace(),
-                    storage_queries: mem::take(&mut self.storage_queries),
-                    block_tip_queries: vm.ola_state.storage_queries,
-                })
-            }
-            Err(e) => Err(e),
+    /// Ends the batch. The result carries the traces, storage access logs and
+    /// events of every transaction, block tip included, and the block tip
+    /// queries. The former `FinishBatchResult` carried the legacy `Trace` of
+    /// the block tip only.
+    pub fn finish_batch(&mut self) -> Result<BatchResult, StateError> {
+        if !self.is_alive {
+            return Err(StateError::VMNotAvaliable);
         }
+        let result = self.block_exe_manager.finish_batch().map_err(exec_error)?;
+        self.is_alive = false;
+        Ok(result)
     }
 }